@@ -0,0 +1,117 @@
+use pnet::datalink::MacAddr;
+use pnet::packet::arp::{ArpHardwareTypes, ArpOperations, MutableArpPacket};
+use pnet::packet::ethernet::{EtherTypes, MutableEthernetPacket};
+use pnet::packet::icmpv6::ndp::{
+    MutableNeighborAdvertPacket, NdpOptionTypes, NeighborAdvertFlags, NeighborAdvertPacket,
+};
+use pnet::packet::icmpv6::{self, Icmpv6Packet, Icmpv6Types};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv6::MutableIpv6Packet;
+use pnet::packet::{MutablePacket, Packet};
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+// packet constants; the neighbor advertisement contains the target
+// link-layer address option
+const ARP_SIZE: usize = MutableArpPacket::minimum_packet_size();
+const ARP_PACKET_SIZE: usize = MutableEthernetPacket::minimum_packet_size() + ARP_SIZE;
+const OPTION_SIZE: usize = 8;
+const ADVERT_SIZE: usize = NeighborAdvertPacket::minimum_packet_size() + OPTION_SIZE;
+const IPV6_SIZE: usize = MutableIpv6Packet::minimum_packet_size() + ADVERT_SIZE;
+const NDP_PACKET_SIZE: usize = MutableEthernetPacket::minimum_packet_size() + IPV6_SIZE;
+
+// all-nodes multicast address and its mac address
+const ALL_NODES: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 1);
+const ALL_NODES_MAC: MacAddr = MacAddr(0x33, 0x33, 0, 0, 0, 1);
+
+// create the gratuitous arp request or, if reply is set, reply that
+// announces the binding of ip to mac
+fn create_gratuitous_arp(mac: MacAddr, ip: Ipv4Addr, reply: bool) -> Vec<u8> {
+    // create arp packet; sender and target ip address are the announced one
+    let mut arp_buffer = [0u8; ARP_SIZE];
+    let mut arp_packet = MutableArpPacket::new(&mut arp_buffer).unwrap();
+    arp_packet.set_hardware_type(ArpHardwareTypes::Ethernet);
+    arp_packet.set_protocol_type(EtherTypes::Ipv4);
+    arp_packet.set_hw_addr_len(6);
+    arp_packet.set_proto_addr_len(4);
+    match reply {
+        true => {
+            arp_packet.set_operation(ArpOperations::Reply);
+            arp_packet.set_target_hw_addr(mac);
+        }
+        false => {
+            arp_packet.set_operation(ArpOperations::Request);
+            arp_packet.set_target_hw_addr(MacAddr::zero());
+        }
+    }
+    arp_packet.set_sender_hw_addr(mac);
+    arp_packet.set_sender_proto_addr(ip);
+    arp_packet.set_target_proto_addr(ip);
+
+    // create ethernet packet
+    let mut ethernet_buffer = vec![0u8; ARP_PACKET_SIZE];
+    let mut ethernet_packet = MutableEthernetPacket::new(&mut ethernet_buffer).unwrap();
+    ethernet_packet.set_source(mac);
+    ethernet_packet.set_destination(MacAddr::broadcast());
+    ethernet_packet.set_ethertype(EtherTypes::Arp);
+    ethernet_packet.set_payload(arp_packet.packet_mut());
+
+    ethernet_buffer
+}
+
+// create the unsolicited neighbor advertisement to all nodes that
+// announces the binding of ip to mac and overrides cached bindings; if
+// router is set, the router flag is set
+fn create_unsolicited_advert(mac: MacAddr, ip: Ipv6Addr, router: bool) -> Vec<u8> {
+    // create neighbor advertisement packet with target link-layer address
+    let mut advert_buffer = [0u8; ADVERT_SIZE];
+    let mut advert_packet = MutableNeighborAdvertPacket::new(&mut advert_buffer).unwrap();
+    advert_packet.set_icmpv6_type(Icmpv6Types::NeighborAdvert);
+    let mut flags = NeighborAdvertFlags::Override;
+    if router {
+        flags |= NeighborAdvertFlags::Router;
+    }
+    advert_packet.set_flags(flags);
+    advert_packet.set_target_addr(ip);
+    let option = &mut advert_packet.packet_mut()[ADVERT_SIZE - OPTION_SIZE..];
+    option[0] = NdpOptionTypes::TargetLLAddr.0;
+    option[1] = 1;
+    option[2..].copy_from_slice(&mac.octets());
+    let checksum = icmpv6::checksum(
+        &Icmpv6Packet::new(advert_packet.packet()).unwrap(),
+        &ip,
+        &ALL_NODES,
+    );
+    advert_packet.set_checksum(checksum);
+
+    // create ipv6 packet; neighbor discovery requires a hop limit of 255
+    let mut ipv6_buffer = [0u8; IPV6_SIZE];
+    let mut ipv6_packet = MutableIpv6Packet::new(&mut ipv6_buffer).unwrap();
+    ipv6_packet.set_version(6);
+    ipv6_packet.set_payload_length(ADVERT_SIZE as u16);
+    ipv6_packet.set_next_header(IpNextHeaderProtocols::Icmpv6);
+    ipv6_packet.set_hop_limit(255);
+    ipv6_packet.set_source(ip);
+    ipv6_packet.set_destination(ALL_NODES);
+    ipv6_packet.set_payload(advert_packet.packet_mut());
+
+    // create ethernet packet
+    let mut ethernet_buffer = vec![0u8; NDP_PACKET_SIZE];
+    let mut ethernet_packet = MutableEthernetPacket::new(&mut ethernet_buffer).unwrap();
+    ethernet_packet.set_source(mac);
+    ethernet_packet.set_destination(ALL_NODES_MAC);
+    ethernet_packet.set_ethertype(EtherTypes::Ipv6);
+    ethernet_packet.set_payload(ipv6_packet.packet_mut());
+
+    ethernet_buffer
+}
+
+// create the frame that announces the binding of ip to mac: a gratuitous
+// arp request or reply for ipv4 addresses and an unsolicited neighbor
+// advertisement for ipv6 addresses
+pub fn create_announcement(mac: MacAddr, ip: IpAddr, reply: bool, router: bool) -> Vec<u8> {
+    match ip {
+        IpAddr::V4(ip) => create_gratuitous_arp(mac, ip, reply),
+        IpAddr::V6(ip) => create_unsolicited_advert(mac, ip, router),
+    }
+}
@@ -0,0 +1,104 @@
+use clap::Parser;
+use packet_utils::parse::{parse_mac, parse_seconds};
+use pnet::datalink::MacAddr;
+
+use std::net::IpAddr;
+
+// command line configuration of the arp tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Monitor arp messages, detect arp spoofing and announce bindings")]
+pub struct Config {
+    // network interface to capture arp messages on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Capture arp messages on the network interface NAME; by \
+        default, the first interface that is up, is not a loopback and has an \
+        ip address is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., to see unicast \
+        arp replies between other hosts on a mirrored port"
+    )]
+    pub promiscuous: bool,
+
+    // only print alerts
+    #[arg(short, long, help = "Only print alerts instead of every arp message")]
+    pub quiet: bool,
+
+    // gratuitous arp storm detection
+    #[arg(
+        long,
+        value_name = "COUNT",
+        default_value_t = 10,
+        help = "Alert if a host sends more than COUNT gratuitous arp \
+        messages within the storm window"
+    )]
+    pub storm_threshold: u32,
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 1.0,
+        value_parser = parse_seconds,
+        help = "Length of the window in which gratuitous arp messages of a \
+        host are counted"
+    )]
+    pub storm_window: f64,
+
+    // send announcements instead of monitoring
+    #[arg(
+        short,
+        long,
+        value_name = "IP",
+        help = "Announce the binding of IP to the mac address with gratuitous \
+        arp messages or, for ipv6 addresses, unsolicited neighbor \
+        advertisements with the override flag instead of monitoring arp \
+        messages, e.g., to test failover or populate neighbor caches"
+    )]
+    pub announce: Option<IpAddr>,
+    #[arg(
+        long,
+        value_name = "MAC",
+        value_parser = parse_mac,
+        requires = "announce",
+        help = "Announce the binding to MAC; by default, the mac address of \
+        the interface is used"
+    )]
+    pub mac: Option<MacAddr>,
+    #[arg(
+        short,
+        long,
+        value_name = "COUNT",
+        default_value_t = 3,
+        value_parser = clap::value_parser!(u32).range(1..),
+        requires = "announce",
+        help = "Send COUNT announcements"
+    )]
+    pub count: u32,
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 1.0,
+        value_parser = parse_seconds,
+        requires = "announce",
+        help = "Wait SECONDS between announcements"
+    )]
+    pub interval: f64,
+    #[arg(
+        long,
+        requires = "announce",
+        help = "Send gratuitous arp replies instead of requests"
+    )]
+    pub reply: bool,
+    #[arg(
+        long,
+        requires = "announce",
+        help = "Set the router flag in neighbor advertisements"
+    )]
+    pub router: bool,
+}
@@ -0,0 +1,147 @@
+extern crate pnet;
+
+mod announce;
+mod config;
+mod message;
+mod table;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::interface::find_interface;
+use pnet::datalink::{DataLinkReceiver, DataLinkSender, NetworkInterface};
+
+use std::io;
+use std::net::IpAddr;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use announce::create_announcement;
+use config::Config;
+use message::ArpMessage;
+use table::ArpTable;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// receive arp messages, add them to the table and print them and the alerts
+// they caused until running is unset
+fn monitor(
+    rx: &mut dyn DataLinkReceiver,
+    table: &mut ArpTable,
+    config: &Config,
+    running: &AtomicBool,
+) {
+    while running.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading arp messages: {}", e),
+            },
+        };
+        let message = match ArpMessage::parse(frame) {
+            Some(message) => message,
+            None => continue,
+        };
+        if !config.quiet {
+            println!("{}", message);
+        }
+        for alert in table.add(&message) {
+            println!("{}", alert);
+        }
+    }
+}
+
+// send the configured number of announcements of ip on interface until
+// running is unset
+fn announce(
+    tx: &mut dyn DataLinkSender,
+    interface: &NetworkInterface,
+    ip: IpAddr,
+    config: &Config,
+    running: &AtomicBool,
+) {
+    let mac = match config.mac.or(interface.mac) {
+        Some(mac) => mac,
+        None => panic!("An error occurred when getting the mac address of the interface"),
+    };
+    let frame = create_announcement(mac, ip, config.reply, config.router);
+    let kind = match ip {
+        IpAddr::V4(_) if config.reply => "gratuitous arp reply",
+        IpAddr::V4(_) => "gratuitous arp request",
+        IpAddr::V6(_) => "unsolicited neighbor advertisement",
+    };
+    let interval = Duration::from_secs_f64(config.interval);
+    let mut next = Instant::now();
+    let mut sent = 0;
+    while sent < config.count && running.load(atomic::Ordering::Relaxed) {
+        let now = Instant::now();
+        if now < next {
+            std::thread::sleep(READ_TIMEOUT.min(next - now));
+            continue;
+        }
+        match tx.send_to(&frame, None) {
+            Some(Ok(())) => println!("sent {}: {} is-at {}", kind, ip, mac),
+            Some(Err(e)) => panic!("An error occurred when sending the announcement: {}", e),
+            None => panic!("An error occurred when sending the announcement"),
+        }
+        sent += 1;
+        next += interval;
+    }
+}
+
+// print the bindings in the table
+fn print_table(table: &ArpTable) {
+    println!("--- arp table ---");
+    println!(
+        "{:<15} {:<17} {:>8} {:>7} {:>10}",
+        "IP", "MAC", "MESSAGES", "CHANGES", "LAST SEEN"
+    );
+    for (ip, binding) in table.get_bindings() {
+        println!(
+            "{:<15} {:<17} {:>8} {:>7} {:>8.1} s",
+            ip.to_string(),
+            binding.mac.to_string(),
+            binding.messages,
+            binding.changes,
+            binding.last_seen.elapsed().as_secs_f64(),
+        );
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let (mut tx, mut rx) = match create_channel(&interface, READ_TIMEOUT, config.promiscuous) {
+        Ok(channel) => channel,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+
+    // stop on ctrl-c or sigterm and print the table
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    if let Some(ip) = config.announce {
+        announce(tx.as_mut(), &interface, ip, &config, &running);
+        return;
+    }
+
+    let mut table = ArpTable::new(&config);
+    monitor(rx.as_mut(), &mut table, &config, &running);
+    print_table(&table);
+}
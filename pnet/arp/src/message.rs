@@ -0,0 +1,100 @@
+use pnet::datalink::MacAddr;
+use pnet::packet::arp::{ArpHardwareTypes, ArpOperation, ArpOperations, ArpPacket};
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket};
+use pnet::packet::Packet;
+
+use std::fmt;
+use std::net::Ipv4Addr;
+
+// arp message for ipv4 over ethernet with the addresses of its ethernet
+// header
+pub struct ArpMessage {
+    pub ethernet_source: MacAddr,
+    pub ethernet_destination: MacAddr,
+    pub operation: ArpOperation,
+    pub sender_mac: MacAddr,
+    pub sender_ip: Ipv4Addr,
+    pub target_mac: MacAddr,
+    pub target_ip: Ipv4Addr,
+}
+
+impl ArpMessage {
+    // parse the ethernet frame and get the arp message inside it
+    pub fn parse(frame: &[u8]) -> Option<ArpMessage> {
+        let ethernet_packet = EthernetPacket::new(frame)?;
+        if ethernet_packet.get_ethertype() != EtherTypes::Arp {
+            return None;
+        }
+        let arp_packet = ArpPacket::new(ethernet_packet.payload())?;
+        if arp_packet.get_hardware_type() != ArpHardwareTypes::Ethernet
+            || arp_packet.get_protocol_type() != EtherTypes::Ipv4
+            || arp_packet.get_hw_addr_len() != 6
+            || arp_packet.get_proto_addr_len() != 4
+        {
+            return None;
+        }
+        Some(ArpMessage {
+            ethernet_source: ethernet_packet.get_source(),
+            ethernet_destination: ethernet_packet.get_destination(),
+            operation: arp_packet.get_operation(),
+            sender_mac: arp_packet.get_sender_hw_addr(),
+            sender_ip: arp_packet.get_sender_proto_addr(),
+            target_mac: arp_packet.get_target_hw_addr(),
+            target_ip: arp_packet.get_target_proto_addr(),
+        })
+    }
+
+    // check if the message is an arp probe that checks if the target ip
+    // address is in use; probes do not announce a binding of the sender
+    pub fn is_probe(&self) -> bool {
+        self.sender_ip.is_unspecified()
+    }
+
+    // check if the message is a gratuitous arp request or reply that
+    // announces the binding of the sender without being asked for it
+    pub fn is_gratuitous(&self) -> bool {
+        !self.is_probe() && self.sender_ip == self.target_ip
+    }
+}
+
+impl fmt::Display for ArpMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} -> {}: ",
+            self.ethernet_source, self.ethernet_destination
+        )?;
+        if self.is_probe() {
+            return write!(f, "arp probe for {}", self.target_ip);
+        }
+        if self.is_gratuitous() {
+            return write!(
+                f,
+                "gratuitous arp {} {} is-at {}",
+                match self.operation {
+                    ArpOperations::Reply => "reply",
+                    _ => "request",
+                },
+                self.sender_ip,
+                self.sender_mac
+            );
+        }
+        match self.operation {
+            ArpOperations::Request => write!(
+                f,
+                "arp request who-has {} tell {} ({})",
+                self.target_ip, self.sender_ip, self.sender_mac
+            ),
+            ArpOperations::Reply => write!(
+                f,
+                "arp reply {} is-at {} to {} ({})",
+                self.sender_ip, self.sender_mac, self.target_ip, self.target_mac
+            ),
+            operation => write!(
+                f,
+                "arp operation {} from {} ({}) to {} ({})",
+                operation.0, self.sender_ip, self.sender_mac, self.target_ip, self.target_mac
+            ),
+        }
+    }
+}
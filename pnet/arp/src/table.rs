@@ -0,0 +1,182 @@
+use pnet::datalink::MacAddr;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::message::ArpMessage;
+
+// maximum number of hosts whose gratuitous arp messages are counted before
+// the counters of hosts without recent ones are removed
+const MAX_SENDERS: usize = 4096;
+
+// binding of an ip address to a mac address learned from arp messages
+pub struct Binding {
+    pub mac: MacAddr,
+    pub last_seen: Instant,
+
+    // number of arp messages that announced the binding and number of times
+    // the ip address moved to another mac address
+    pub messages: u64,
+    pub changes: u64,
+}
+
+// suspicious arp traffic that indicates arp spoofing
+pub enum Alert {
+    // ip address was announced with another mac address than the one it is
+    // bound to
+    Conflict {
+        ip: Ipv4Addr,
+        old: MacAddr,
+        new: MacAddr,
+    },
+
+    // sender mac address in the arp message differs from the source mac
+    // address of its ethernet frame
+    Mismatch {
+        ip: Ipv4Addr,
+        sender: MacAddr,
+        source: MacAddr,
+    },
+
+    // host sent more gratuitous arp messages within the window than allowed
+    Storm {
+        mac: MacAddr,
+        count: u32,
+        window: Duration,
+    },
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alert::Conflict { ip, old, new } => write!(
+                f,
+                "ALERT: conflicting binding: {} moved from {} to {}",
+                ip, old, new
+            ),
+            Alert::Mismatch { ip, sender, source } => write!(
+                f,
+                "ALERT: sender mismatch: {} is-at {} sent from {}",
+                ip, sender, source
+            ),
+            Alert::Storm { mac, count, window } => write!(
+                f,
+                "ALERT: gratuitous arp storm: {} sent {} gratuitous arp messages \
+                within {:.3} s",
+                mac,
+                count,
+                window.as_secs_f64()
+            ),
+        }
+    }
+}
+
+// counter of the gratuitous arp messages of a host in the current window
+struct StormCounter {
+    start: Instant,
+    count: u32,
+    alerted: bool,
+}
+
+// table of the ip to mac address bindings announced in arp messages that
+// detects conflicting bindings and gratuitous arp storms
+pub struct ArpTable {
+    bindings: BTreeMap<Ipv4Addr, Binding>,
+    gratuitous: HashMap<MacAddr, StormCounter>,
+    storm_threshold: u32,
+    storm_window: Duration,
+}
+
+impl ArpTable {
+    pub fn new(config: &Config) -> ArpTable {
+        ArpTable {
+            bindings: BTreeMap::new(),
+            gratuitous: HashMap::new(),
+            storm_threshold: config.storm_threshold,
+            storm_window: Duration::from_secs_f64(config.storm_window),
+        }
+    }
+
+    // add the binding of the sender of the arp message to the table and get
+    // the alerts it caused; probes are ignored
+    pub fn add(&mut self, message: &ArpMessage) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        if message.is_probe() {
+            return alerts;
+        }
+        let now = Instant::now();
+        let (ip, mac) = (message.sender_ip, message.sender_mac);
+
+        if mac != message.ethernet_source {
+            alerts.push(Alert::Mismatch {
+                ip,
+                sender: mac,
+                source: message.ethernet_source,
+            });
+        }
+
+        let binding = self.bindings.entry(ip).or_insert(Binding {
+            mac,
+            last_seen: now,
+            messages: 0,
+            changes: 0,
+        });
+        if binding.mac != mac {
+            alerts.push(Alert::Conflict {
+                ip,
+                old: binding.mac,
+                new: mac,
+            });
+            binding.mac = mac;
+            binding.changes += 1;
+        }
+        binding.last_seen = now;
+        binding.messages += 1;
+
+        if message.is_gratuitous() {
+            alerts.extend(self.count_gratuitous(mac, now));
+        }
+        alerts
+    }
+
+    // count a gratuitous arp message of mac at time now and get the storm
+    // alert if it exceeds the threshold; a storm is only reported once per
+    // window
+    fn count_gratuitous(&mut self, mac: MacAddr, now: Instant) -> Option<Alert> {
+        if self.gratuitous.len() >= MAX_SENDERS {
+            let window = self.storm_window;
+            self.gratuitous
+                .retain(|_, c| now.duration_since(c.start) < window);
+        }
+        let counter = self.gratuitous.entry(mac).or_insert(StormCounter {
+            start: now,
+            count: 0,
+            alerted: false,
+        });
+        if now.duration_since(counter.start) >= self.storm_window {
+            *counter = StormCounter {
+                start: now,
+                count: 0,
+                alerted: false,
+            };
+        }
+        counter.count += 1;
+        if counter.count <= self.storm_threshold || counter.alerted {
+            return None;
+        }
+        counter.alerted = true;
+        Some(Alert::Storm {
+            mac,
+            count: counter.count,
+            window: self.storm_window,
+        })
+    }
+
+    // get the bindings sorted by ip address
+    pub fn get_bindings(&self) -> &BTreeMap<Ipv4Addr, Binding> {
+        &self.bindings
+    }
+}
@@ -0,0 +1,457 @@
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+// address family identifiers and subsequent address family identifiers
+pub const AFI_IPV4: u16 = 1;
+pub const AFI_IPV6: u16 = 2;
+const SAFI_UNICAST: u8 = 1;
+const SAFI_MULTICAST: u8 = 2;
+
+// path attribute flags
+const FLAG_EXTENDED_LENGTH: u8 = 0x10;
+
+// path attribute type codes
+const ATTR_ORIGIN: u8 = 1;
+const ATTR_AS_PATH: u8 = 2;
+const ATTR_NEXT_HOP: u8 = 3;
+const ATTR_MED: u8 = 4;
+const ATTR_LOCAL_PREF: u8 = 5;
+const ATTR_ATOMIC_AGGREGATE: u8 = 6;
+const ATTR_AGGREGATOR: u8 = 7;
+const ATTR_COMMUNITIES: u8 = 8;
+const ATTR_MP_REACH_NLRI: u8 = 14;
+const ATTR_MP_UNREACH_NLRI: u8 = 15;
+const ATTR_EXTENDED_COMMUNITIES: u8 = 16;
+const ATTR_AS4_PATH: u8 = 17;
+const ATTR_AS4_AGGREGATOR: u8 = 18;
+const ATTR_LARGE_COMMUNITIES: u8 = 32;
+
+// as path segment types
+const SEGMENT_AS_SET: u8 = 1;
+const SEGMENT_AS_SEQUENCE: u8 = 2;
+
+// format the address family and subsequent address family
+pub fn format_family(afi: u16, safi: u8) -> String {
+    let afi = match afi {
+        AFI_IPV4 => "ipv4".to_string(),
+        AFI_IPV6 => "ipv6".to_string(),
+        25 => "l2vpn".to_string(),
+        afi => format!("afi {}", afi),
+    };
+    let safi = match safi {
+        SAFI_UNICAST => "unicast".to_string(),
+        SAFI_MULTICAST => "multicast".to_string(),
+        4 => "labeled unicast".to_string(),
+        65 => "vpls".to_string(),
+        70 => "evpn".to_string(),
+        128 => "mpls vpn".to_string(),
+        133 => "flowspec".to_string(),
+        safi => format!("safi {}", safi),
+    };
+    format!("{} {}", afi, safi)
+}
+
+// format the items as list
+fn format_list<T: fmt::Display>(items: &[T]) -> String {
+    let items = items.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+    format!("[{}]", items.join(", "))
+}
+
+// ip prefix in withdrawn routes and nlri
+pub struct Prefix {
+    pub address: IpAddr,
+    pub length: u8,
+}
+
+impl fmt::Display for Prefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.length)
+    }
+}
+
+// parse the prefixes of the address family afi in bytes; each prefix is
+// encoded as its length in bits followed by the significant bytes
+pub fn parse_prefixes(bytes: &[u8], afi: u16) -> Option<Vec<Prefix>> {
+    let max_length = match afi {
+        AFI_IPV4 => 32,
+        _ => 128,
+    };
+    let mut offset = 0;
+    let mut prefixes = Vec::new();
+    while offset < bytes.len() {
+        let length = bytes[offset];
+        if length > max_length {
+            return None;
+        }
+        let size = usize::from(length).div_ceil(8);
+        let significant = bytes.get(offset + 1..offset + 1 + size)?;
+        offset += 1 + size;
+        let mut octets = [0; 16];
+        octets[..size].copy_from_slice(significant);
+        let address = match afi {
+            AFI_IPV4 => IpAddr::V4(Ipv4Addr::new(octets[0], octets[1], octets[2], octets[3])),
+            _ => IpAddr::V6(Ipv6Addr::from(octets)),
+        };
+        prefixes.push(Prefix { address, length });
+    }
+    Some(prefixes)
+}
+
+// segment of an as path
+pub struct AsSegment {
+    pub set: bool,
+    pub asns: Vec<u32>,
+}
+
+impl fmt::Display for AsSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let asns = self.asns.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        match self.set {
+            true => write!(f, "{{{}}}", asns.join(",")),
+            false => write!(f, "{}", asns.join(" ")),
+        }
+    }
+}
+
+// parse the as path in bytes with as numbers of size 2 or 4 bytes; the
+// segments must fill bytes exactly
+fn parse_as_path(bytes: &[u8], size: usize) -> Option<Vec<AsSegment>> {
+    let mut offset = 0;
+    let mut segments = Vec::new();
+    while offset < bytes.len() {
+        let header = bytes.get(offset..offset + 2)?;
+        let set = match header[0] {
+            SEGMENT_AS_SET => true,
+            SEGMENT_AS_SEQUENCE => false,
+            _ => return None,
+        };
+        let count = usize::from(header[1]);
+        let asns = bytes
+            .get(offset + 2..offset + 2 + count * size)?
+            .chunks_exact(size)
+            .map(|a| a.iter().fold(0, |asn, b| asn << 8 | u32::from(*b)))
+            .collect();
+        offset += 2 + count * size;
+        segments.push(AsSegment { set, asns });
+    }
+    Some(segments)
+}
+
+// format the as path segments
+fn format_as_path(segments: &[AsSegment]) -> String {
+    let segments = segments.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    segments.join(" ")
+}
+
+// format the standard community
+fn format_community(community: u32) -> String {
+    match community {
+        0xffff0000 => "graceful-shutdown".to_string(),
+        0xffff029a => "blackhole".to_string(),
+        0xffffff01 => "no-export".to_string(),
+        0xffffff02 => "no-advertise".to_string(),
+        0xffffff03 => "no-export-subconfed".to_string(),
+        c => format!("{}:{}", c >> 16, c & 0xffff),
+    }
+}
+
+// format the extended community; route targets and route origins are
+// formatted as type:administrator:value, the others as hex
+fn format_extended_community(community: &[u8]) -> String {
+    let name = match community[1] {
+        0x02 => "rt",
+        0x03 => "soo",
+        _ => "",
+    };
+    let read_u16 = |i: usize| u16::from_be_bytes([community[i], community[i + 1]]);
+    let read_u32 = |i: usize| u32::from_be_bytes(community[i..i + 4].try_into().unwrap());
+    match community[0] {
+        0x00 if !name.is_empty() => format!("{}:{}:{}", name, read_u16(2), read_u32(4)),
+        0x01 if !name.is_empty() => {
+            let address = Ipv4Addr::new(community[2], community[3], community[4], community[5]);
+            format!("{}:{}:{}", name, address, read_u16(6))
+        }
+        0x02 if !name.is_empty() => format!("{}:{}:{}", name, read_u32(2), read_u16(6)),
+        _ => community
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>(),
+    }
+}
+
+// nlri in mp reach and unreach attributes; only the prefixes of ipv4 and
+// ipv6 unicast and multicast are decoded, for other families only their
+// length is kept
+pub enum Nlri {
+    Prefixes(Vec<Prefix>),
+    Other(usize),
+}
+
+impl Nlri {
+    // check if the nlri contains no routes
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Nlri::Prefixes(prefixes) => prefixes.is_empty(),
+            Nlri::Other(length) => *length == 0,
+        }
+    }
+
+    fn parse(bytes: &[u8], afi: u16, safi: u8) -> Option<Nlri> {
+        match (afi, safi) {
+            (AFI_IPV4 | AFI_IPV6, SAFI_UNICAST | SAFI_MULTICAST) => {
+                Some(Nlri::Prefixes(parse_prefixes(bytes, afi)?))
+            }
+            _ => Some(Nlri::Other(bytes.len())),
+        }
+    }
+}
+
+impl fmt::Display for Nlri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Nlri::Prefixes(prefixes) => write!(f, "{}", format_list(prefixes)),
+            Nlri::Other(length) => write!(f, "<{} bytes>", length),
+        }
+    }
+}
+
+// path attribute in update messages
+pub enum PathAttribute {
+    Origin(u8),
+    AsPath(Vec<AsSegment>),
+    NextHop(Ipv4Addr),
+    MultiExitDisc(u32),
+    LocalPref(u32),
+    AtomicAggregate,
+    Aggregator {
+        asn: u32,
+        address: Ipv4Addr,
+    },
+    Communities(Vec<u32>),
+    MpReachNlri {
+        afi: u16,
+        safi: u8,
+        next_hops: Vec<IpAddr>,
+        nlri: Nlri,
+    },
+    MpUnreachNlri {
+        afi: u16,
+        safi: u8,
+        withdrawn: Nlri,
+    },
+    ExtendedCommunities(Vec<[u8; 8]>),
+    As4Path(Vec<AsSegment>),
+    As4Aggregator {
+        asn: u32,
+        address: Ipv4Addr,
+    },
+    LargeCommunities(Vec<(u32, u32, u32)>),
+    Unknown {
+        code: u8,
+        length: usize,
+    },
+}
+
+impl PathAttribute {
+    // parse the path attributes in bytes; four_octet specifies if the
+    // session uses four-octet as numbers in as paths, if it is unknown,
+    // both sizes are tried
+    pub fn parse_all(bytes: &[u8], four_octet: Option<bool>) -> Option<Vec<PathAttribute>> {
+        let mut offset = 0;
+        let mut attributes = Vec::new();
+        while offset < bytes.len() {
+            // Attribute Format:
+            // flags (1), type code (1), length (1 or 2 with extended
+            // length flag), value
+            let header = bytes.get(offset..offset + 2)?;
+            let (flags, code) = (header[0], header[1]);
+            let (length, header_length) = match flags & FLAG_EXTENDED_LENGTH {
+                0 => (usize::from(*bytes.get(offset + 2)?), 3),
+                _ => {
+                    let length = bytes.get(offset + 2..offset + 4)?;
+                    (usize::from(u16::from_be_bytes([length[0], length[1]])), 4)
+                }
+            };
+            let value = bytes.get(offset + header_length..offset + header_length + length)?;
+            offset += header_length + length;
+            attributes.push(PathAttribute::parse(code, value, four_octet)?);
+        }
+        Some(attributes)
+    }
+
+    // parse the value of the path attribute with type code
+    fn parse(code: u8, value: &[u8], four_octet: Option<bool>) -> Option<PathAttribute> {
+        let read_u32 = |v: &[u8]| Some(u32::from_be_bytes(v.try_into().ok()?));
+        let attribute = match code {
+            ATTR_ORIGIN => PathAttribute::Origin(*value.first()?),
+            ATTR_AS_PATH => {
+                let segments = match four_octet {
+                    Some(true) => parse_as_path(value, 4)?,
+                    Some(false) => parse_as_path(value, 2)?,
+                    None => parse_as_path(value, 4).or_else(|| parse_as_path(value, 2))?,
+                };
+                PathAttribute::AsPath(segments)
+            }
+            ATTR_NEXT_HOP => {
+                let octets: [u8; 4] = value.try_into().ok()?;
+                PathAttribute::NextHop(Ipv4Addr::from(octets))
+            }
+            ATTR_MED => PathAttribute::MultiExitDisc(read_u32(value)?),
+            ATTR_LOCAL_PREF => PathAttribute::LocalPref(read_u32(value)?),
+            ATTR_ATOMIC_AGGREGATE => PathAttribute::AtomicAggregate,
+            ATTR_AGGREGATOR | ATTR_AS4_AGGREGATOR => {
+                // aggregator as is 2 or 4 bytes followed by an ipv4 address
+                let size = value.len().checked_sub(4)?;
+                if size != 2 && size != 4 {
+                    return None;
+                }
+                let asn = value[..size]
+                    .iter()
+                    .fold(0, |asn, b| asn << 8 | u32::from(*b));
+                let address = Ipv4Addr::from(<[u8; 4]>::try_from(&value[size..]).ok()?);
+                match code {
+                    ATTR_AGGREGATOR => PathAttribute::Aggregator { asn, address },
+                    _ => PathAttribute::As4Aggregator { asn, address },
+                }
+            }
+            ATTR_COMMUNITIES => PathAttribute::Communities(
+                value
+                    .chunks_exact(4)
+                    .map(|c| u32::from_be_bytes(c.try_into().unwrap()))
+                    .collect(),
+            ),
+            ATTR_MP_REACH_NLRI => {
+                // afi (2), safi (1), next hop length (1), next hops,
+                // reserved (1), nlri
+                let header = value.get(..4)?;
+                let afi = u16::from_be_bytes([header[0], header[1]]);
+                let safi = header[2];
+                let length = usize::from(header[3]);
+                let next_hop = value.get(4..4 + length)?;
+                let next_hops = match length {
+                    4 => vec![IpAddr::V4(Ipv4Addr::from(
+                        <[u8; 4]>::try_from(next_hop).ok()?,
+                    ))],
+                    16 | 32 => next_hop
+                        .chunks_exact(16)
+                        .map(|n| IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(n).unwrap())))
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                let nlri = Nlri::parse(value.get(5 + length..)?, afi, safi)?;
+                PathAttribute::MpReachNlri {
+                    afi,
+                    safi,
+                    next_hops,
+                    nlri,
+                }
+            }
+            ATTR_MP_UNREACH_NLRI => {
+                let header = value.get(..3)?;
+                let afi = u16::from_be_bytes([header[0], header[1]]);
+                let safi = header[2];
+                PathAttribute::MpUnreachNlri {
+                    afi,
+                    safi,
+                    withdrawn: Nlri::parse(&value[3..], afi, safi)?,
+                }
+            }
+            ATTR_EXTENDED_COMMUNITIES => PathAttribute::ExtendedCommunities(
+                value
+                    .chunks_exact(8)
+                    .map(|c| c.try_into().unwrap())
+                    .collect(),
+            ),
+            ATTR_AS4_PATH => PathAttribute::As4Path(parse_as_path(value, 4)?),
+            ATTR_LARGE_COMMUNITIES => PathAttribute::LargeCommunities(
+                value
+                    .chunks_exact(12)
+                    .map(|c| {
+                        let read = |i: usize| u32::from_be_bytes(c[i..i + 4].try_into().unwrap());
+                        (read(0), read(4), read(8))
+                    })
+                    .collect(),
+            ),
+            code => PathAttribute::Unknown {
+                code,
+                length: value.len(),
+            },
+        };
+        Some(attribute)
+    }
+}
+
+impl fmt::Display for PathAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathAttribute::Origin(origin) => match origin {
+                0 => write!(f, "origin: igp"),
+                1 => write!(f, "origin: egp"),
+                2 => write!(f, "origin: incomplete"),
+                o => write!(f, "origin: {}", o),
+            },
+            PathAttribute::AsPath(segments) => {
+                write!(f, "as path: [{}]", format_as_path(segments))
+            }
+            PathAttribute::NextHop(next_hop) => write!(f, "next hop: {}", next_hop),
+            PathAttribute::MultiExitDisc(med) => write!(f, "med: {}", med),
+            PathAttribute::LocalPref(local_pref) => write!(f, "local pref: {}", local_pref),
+            PathAttribute::AtomicAggregate => write!(f, "atomic aggregate"),
+            PathAttribute::Aggregator { asn, address } => {
+                write!(f, "aggregator: {} {}", asn, address)
+            }
+            PathAttribute::Communities(communities) => {
+                let communities = communities
+                    .iter()
+                    .map(|c| format_community(*c))
+                    .collect::<Vec<_>>();
+                write!(f, "communities: {}", format_list(&communities))
+            }
+            PathAttribute::MpReachNlri {
+                afi,
+                safi,
+                next_hops,
+                nlri,
+            } => write!(
+                f,
+                "mp reach: {{family: {}, next hops: {}, nlri: {}}}",
+                format_family(*afi, *safi),
+                format_list(next_hops),
+                nlri
+            ),
+            PathAttribute::MpUnreachNlri {
+                afi,
+                safi,
+                withdrawn,
+            } => write!(
+                f,
+                "mp unreach: {{family: {}, withdrawn: {}}}",
+                format_family(*afi, *safi),
+                withdrawn
+            ),
+            PathAttribute::ExtendedCommunities(communities) => {
+                let communities = communities
+                    .iter()
+                    .map(|c| format_extended_community(c))
+                    .collect::<Vec<_>>();
+                write!(f, "extended communities: {}", format_list(&communities))
+            }
+            PathAttribute::As4Path(segments) => {
+                write!(f, "as4 path: [{}]", format_as_path(segments))
+            }
+            PathAttribute::As4Aggregator { asn, address } => {
+                write!(f, "as4 aggregator: {} {}", asn, address)
+            }
+            PathAttribute::LargeCommunities(communities) => {
+                let communities = communities
+                    .iter()
+                    .map(|(a, b, c)| format!("{}:{}:{}", a, b, c))
+                    .collect::<Vec<_>>();
+                write!(f, "large communities: {}", format_list(&communities))
+            }
+            PathAttribute::Unknown { code, length } => {
+                write!(f, "attribute {}: <{} bytes>", code, length)
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use clap::Parser;
+
+// command line configuration of the bgp tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Listen for bgp sessions and decode their messages")]
+pub struct Config {
+    // network interface to capture packets on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Capture packets on the network interface NAME; by default, \
+        the first interface that is up, is not a loopback and has an ip \
+        address is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., to see the bgp \
+        sessions of other routers on a mirrored port"
+    )]
+    pub promiscuous: bool,
+
+    // bgp ports
+    #[arg(
+        short,
+        long,
+        value_name = "PORT",
+        default_values_t = [179],
+        help = "Listen for bgp sessions to and from the port PORT; can be \
+        specified multiple times"
+    )]
+    pub port: Vec<u16>,
+
+    // also print keepalives
+    #[arg(
+        short,
+        long,
+        help = "Print keepalive messages in addition to the other messages"
+    )]
+    pub verbose: bool,
+}
@@ -0,0 +1,391 @@
+use packet_utils::bytes::read_be_u16;
+
+use std::fmt;
+use std::net::Ipv4Addr;
+
+use crate::attribute::{format_family, parse_prefixes, PathAttribute, Prefix, AFI_IPV4};
+
+// every bgp message starts with a marker of all ones
+pub const MARKER: [u8; 16] = [0xff; 16];
+
+// length of the bgp header: marker (16), length (2), type (1)
+pub const HEADER_LENGTH: usize = 19;
+
+// bgp message types
+const TYPE_OPEN: u8 = 1;
+const TYPE_UPDATE: u8 = 2;
+const TYPE_NOTIFICATION: u8 = 3;
+const TYPE_KEEPALIVE: u8 = 4;
+const TYPE_ROUTE_REFRESH: u8 = 5;
+
+// optional parameter type of capabilities and of the extended optional
+// parameters length
+const PARAMETER_CAPABILITIES: u8 = 2;
+const PARAMETER_EXTENDED_LENGTH: u8 = 255;
+
+// capability codes
+const CAPABILITY_MULTIPROTOCOL: u8 = 1;
+const CAPABILITY_ROUTE_REFRESH: u8 = 2;
+const CAPABILITY_EXTENDED_MESSAGE: u8 = 6;
+const CAPABILITY_GRACEFUL_RESTART: u8 = 64;
+const CAPABILITY_FOUR_OCTET_AS: u8 = 65;
+const CAPABILITY_ADD_PATH: u8 = 69;
+const CAPABILITY_ENHANCED_ROUTE_REFRESH: u8 = 70;
+
+// notification error codes with subcodes that have names
+const ERROR_OPEN: u8 = 2;
+const ERROR_CEASE: u8 = 6;
+
+// cease subcodes of administrative shutdowns and resets that can carry a
+// shutdown communication
+const CEASE_SHUTDOWN: u8 = 2;
+const CEASE_RESET: u8 = 4;
+
+// capability advertised in open messages
+pub enum Capability {
+    Multiprotocol { afi: u16, safi: u8 },
+    RouteRefresh,
+    ExtendedMessage,
+    GracefulRestart { restart_time: u16 },
+    FourOctetAs(u32),
+    AddPath,
+    EnhancedRouteRefresh,
+    Unknown(u8),
+}
+
+impl Capability {
+    // parse the capabilities in the capabilities optional parameter
+    fn parse_all(bytes: &[u8]) -> Option<Vec<Capability>> {
+        let mut offset = 0;
+        let mut capabilities = Vec::new();
+        while offset < bytes.len() {
+            let code = bytes[offset];
+            let length = usize::from(*bytes.get(offset + 1)?);
+            let value = bytes.get(offset + 2..offset + 2 + length)?;
+            offset += 2 + length;
+            let capability = match code {
+                CAPABILITY_MULTIPROTOCOL => Capability::Multiprotocol {
+                    afi: read_be_u16(value.get(..2)?),
+                    safi: *value.get(3)?,
+                },
+                CAPABILITY_ROUTE_REFRESH => Capability::RouteRefresh,
+                CAPABILITY_EXTENDED_MESSAGE => Capability::ExtendedMessage,
+                CAPABILITY_GRACEFUL_RESTART => Capability::GracefulRestart {
+                    restart_time: read_be_u16(value.get(..2)?) & 0xfff,
+                },
+                CAPABILITY_FOUR_OCTET_AS => {
+                    Capability::FourOctetAs(u32::from_be_bytes(value.try_into().ok()?))
+                }
+                CAPABILITY_ADD_PATH => Capability::AddPath,
+                CAPABILITY_ENHANCED_ROUTE_REFRESH => Capability::EnhancedRouteRefresh,
+                code => Capability::Unknown(code),
+            };
+            capabilities.push(capability);
+        }
+        Some(capabilities)
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Capability::Multiprotocol { afi, safi } => {
+                write!(f, "multiprotocol {}", format_family(*afi, *safi))
+            }
+            Capability::RouteRefresh => write!(f, "route refresh"),
+            Capability::ExtendedMessage => write!(f, "extended message"),
+            Capability::GracefulRestart { restart_time } => {
+                write!(f, "graceful restart {} s", restart_time)
+            }
+            Capability::FourOctetAs(asn) => write!(f, "four-octet as {}", asn),
+            Capability::AddPath => write!(f, "add-path"),
+            Capability::EnhancedRouteRefresh => write!(f, "enhanced route refresh"),
+            Capability::Unknown(code) => write!(f, "capability {}", code),
+        }
+    }
+}
+
+// open message
+pub struct Open {
+    pub version: u8,
+    pub my_as: u16,
+    pub hold_time: u16,
+    pub identifier: Ipv4Addr,
+    pub capabilities: Vec<Capability>,
+}
+
+impl Open {
+    fn parse(body: &[u8]) -> Option<Open> {
+        // Open Format:
+        // version (1), my as (2), hold time (2), bgp identifier (4),
+        // optional parameters length (1), optional parameters
+        let header = body.get(..10)?;
+        let mut parameters_length = usize::from(header[9]);
+        let mut offset = 10;
+        let mut extended = false;
+
+        // extended optional parameters use a two byte length
+        if parameters_length == usize::from(PARAMETER_EXTENDED_LENGTH)
+            && body.get(offset) == Some(&PARAMETER_EXTENDED_LENGTH)
+        {
+            parameters_length = usize::from(read_be_u16(body.get(offset + 1..offset + 3)?));
+            offset += 3;
+            extended = true;
+        }
+        let parameters = body.get(offset..offset + parameters_length)?;
+
+        // Optional Parameter Format:
+        // type (1), length (1 or 2 if extended), value
+        let mut capabilities = Vec::new();
+        let mut offset = 0;
+        while offset < parameters.len() {
+            let parameter_type = parameters[offset];
+            let (length, header_length) = match extended {
+                true => (
+                    usize::from(read_be_u16(parameters.get(offset + 1..offset + 3)?)),
+                    3,
+                ),
+                false => (usize::from(*parameters.get(offset + 1)?), 2),
+            };
+            let value = parameters.get(offset + header_length..offset + header_length + length)?;
+            offset += header_length + length;
+            if parameter_type == PARAMETER_CAPABILITIES {
+                capabilities.extend(Capability::parse_all(value)?);
+            }
+        }
+        Some(Open {
+            version: header[0],
+            my_as: read_be_u16(&header[1..3]),
+            hold_time: read_be_u16(&header[3..5]),
+            identifier: Ipv4Addr::new(header[5], header[6], header[7], header[8]),
+            capabilities,
+        })
+    }
+
+    // get the four-octet as number of the sender if it advertised the
+    // capability
+    pub fn get_four_octet_as(&self) -> Option<u32> {
+        self.capabilities.iter().find_map(|c| match c {
+            Capability::FourOctetAs(asn) => Some(*asn),
+            _ => None,
+        })
+    }
+}
+
+impl fmt::Display for Open {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let asn = self
+            .get_four_octet_as()
+            .unwrap_or_else(|| self.my_as.into());
+        let capabilities = self
+            .capabilities
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "{{version: {}, as: {}, hold time: {}, identifier: {}, capabilities: [{}]}}",
+            self.version,
+            asn,
+            self.hold_time,
+            self.identifier,
+            capabilities.join(", ")
+        )
+    }
+}
+
+// update message with the withdrawn routes, path attributes and nlri of
+// ipv4 unicast; other families are in the mp reach and unreach attributes
+pub struct Update {
+    pub withdrawn: Vec<Prefix>,
+    pub attributes: Vec<PathAttribute>,
+    pub nlri: Vec<Prefix>,
+}
+
+impl Update {
+    fn parse(body: &[u8], four_octet: Option<bool>) -> Option<Update> {
+        // Update Format:
+        // withdrawn routes length (2), withdrawn routes, total path
+        // attribute length (2), path attributes, nlri
+        let withdrawn_length = usize::from(read_be_u16(body.get(..2)?));
+        let withdrawn = body.get(2..2 + withdrawn_length)?;
+        let offset = 2 + withdrawn_length;
+        let attributes_length = usize::from(read_be_u16(body.get(offset..offset + 2)?));
+        let attributes = body.get(offset + 2..offset + 2 + attributes_length)?;
+        let nlri = &body[offset + 2 + attributes_length..];
+        Some(Update {
+            withdrawn: parse_prefixes(withdrawn, AFI_IPV4)?,
+            attributes: PathAttribute::parse_all(attributes, four_octet)?,
+            nlri: parse_prefixes(nlri, AFI_IPV4)?,
+        })
+    }
+
+    // check if the update is an end-of-rib marker
+    fn is_end_of_rib(&self) -> bool {
+        if !self.withdrawn.is_empty() || !self.nlri.is_empty() {
+            return false;
+        }
+        match self.attributes.as_slice() {
+            [] => true,
+            [PathAttribute::MpUnreachNlri { withdrawn, .. }] => withdrawn.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Update {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_end_of_rib() {
+            return match self.attributes.first() {
+                Some(PathAttribute::MpUnreachNlri { afi, safi, .. }) => {
+                    write!(f, "{{end of rib: {}}}", format_family(*afi, *safi))
+                }
+                _ => write!(f, "{{end of rib: ipv4 unicast}}"),
+            };
+        }
+        let mut fields = Vec::new();
+        if !self.withdrawn.is_empty() {
+            let withdrawn = self
+                .withdrawn
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>();
+            fields.push(format!("withdrawn: [{}]", withdrawn.join(", ")));
+        }
+        fields.extend(self.attributes.iter().map(|a| a.to_string()));
+        if !self.nlri.is_empty() {
+            let nlri = self.nlri.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+            fields.push(format!("nlri: [{}]", nlri.join(", ")));
+        }
+        write!(f, "{{{}}}", fields.join(", "))
+    }
+}
+
+// notification message
+pub struct Notification {
+    pub code: u8,
+    pub subcode: u8,
+    pub data: Vec<u8>,
+}
+
+impl Notification {
+    // get the name of the error code and subcode
+    fn get_error(&self) -> String {
+        let code = match self.code {
+            1 => "message header error",
+            ERROR_OPEN => "open message error",
+            3 => "update message error",
+            4 => "hold timer expired",
+            5 => "finite state machine error",
+            ERROR_CEASE => "cease",
+            7 => "route refresh message error",
+            _ => "unknown error",
+        };
+        let subcode = match (self.code, self.subcode) {
+            (ERROR_OPEN, 1) => "unsupported version number",
+            (ERROR_OPEN, 2) => "bad peer as",
+            (ERROR_OPEN, 3) => "bad bgp identifier",
+            (ERROR_OPEN, 4) => "unsupported optional parameter",
+            (ERROR_OPEN, 6) => "unacceptable hold time",
+            (ERROR_OPEN, 7) => "unsupported capability",
+            (ERROR_CEASE, 1) => "maximum number of prefixes reached",
+            (ERROR_CEASE, CEASE_SHUTDOWN) => "administrative shutdown",
+            (ERROR_CEASE, 3) => "peer de-configured",
+            (ERROR_CEASE, CEASE_RESET) => "administrative reset",
+            (ERROR_CEASE, 5) => "connection rejected",
+            (ERROR_CEASE, 6) => "other configuration change",
+            (ERROR_CEASE, 7) => "connection collision resolution",
+            (ERROR_CEASE, 8) => "out of resources",
+            (ERROR_CEASE, 9) => "hard reset",
+            _ => "",
+        };
+        match subcode {
+            "" => format!("{} ({}/{})", code, self.code, self.subcode),
+            subcode => format!("{}: {} ({}/{})", code, subcode, self.code, self.subcode),
+        }
+    }
+
+    // get the shutdown communication of administrative shutdowns and
+    // resets; it is prefixed with its length
+    fn get_communication(&self) -> Option<String> {
+        if self.code != ERROR_CEASE || !matches!(self.subcode, CEASE_SHUTDOWN | CEASE_RESET) {
+            return None;
+        }
+        let length = usize::from(*self.data.first()?);
+        let text = self.data.get(1..1 + length)?;
+        Some(String::from_utf8_lossy(text).into_owned())
+    }
+}
+
+impl fmt::Display for Notification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{error: {}", self.get_error())?;
+        match self.get_communication() {
+            Some(communication) => write!(f, ", communication: {:?}", communication)?,
+            None if !self.data.is_empty() => write!(f, ", data: <{} bytes>", self.data.len())?,
+            None => (),
+        }
+        write!(f, "}}")
+    }
+}
+
+// bgp message
+pub enum BgpMessage {
+    Open(Open),
+    Update(Update),
+    Notification(Notification),
+    Keepalive,
+    RouteRefresh { afi: u16, safi: u8 },
+    Unknown(u8),
+}
+
+impl BgpMessage {
+    // get the name of the message type
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            BgpMessage::Open(_) => "open",
+            BgpMessage::Update(_) => "update",
+            BgpMessage::Notification(_) => "notification",
+            BgpMessage::Keepalive => "keepalive",
+            BgpMessage::RouteRefresh { .. } => "route refresh",
+            BgpMessage::Unknown(_) => "message",
+        }
+    }
+
+    // parse the bgp message with header in message; four_octet specifies
+    // if the session uses four-octet as numbers
+    pub fn parse(message: &[u8], four_octet: Option<bool>) -> Option<BgpMessage> {
+        let body = message.get(HEADER_LENGTH..)?;
+        let parsed = match message[HEADER_LENGTH - 1] {
+            TYPE_OPEN => BgpMessage::Open(Open::parse(body)?),
+            TYPE_UPDATE => BgpMessage::Update(Update::parse(body, four_octet)?),
+            TYPE_NOTIFICATION => BgpMessage::Notification(Notification {
+                code: *body.first()?,
+                subcode: *body.get(1)?,
+                data: body[2..].to_vec(),
+            }),
+            TYPE_KEEPALIVE => BgpMessage::Keepalive,
+            TYPE_ROUTE_REFRESH => BgpMessage::RouteRefresh {
+                afi: read_be_u16(body.get(..2)?),
+                safi: *body.get(3)?,
+            },
+            message_type => BgpMessage::Unknown(message_type),
+        };
+        Some(parsed)
+    }
+}
+
+impl fmt::Display for BgpMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BgpMessage::Open(open) => write!(f, "{}", open),
+            BgpMessage::Update(update) => write!(f, "{}", update),
+            BgpMessage::Notification(notification) => write!(f, "{}", notification),
+            BgpMessage::Keepalive => write!(f, "{{}}"),
+            BgpMessage::RouteRefresh { afi, safi } => {
+                write!(f, "{{family: {}}}", format_family(*afi, *safi))
+            }
+            BgpMessage::Unknown(message_type) => write!(f, "{{type: {}}}", message_type),
+        }
+    }
+}
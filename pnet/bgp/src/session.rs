@@ -0,0 +1,148 @@
+use dns::{Stream, StreamKey};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+
+use crate::message::{BgpMessage, HEADER_LENGTH, MARKER};
+
+// bgp message or malformed message read from a session
+pub enum Event {
+    Message {
+        source: SocketAddr,
+        destination: SocketAddr,
+        message: BgpMessage,
+    },
+    Malformed {
+        source: SocketAddr,
+        destination: SocketAddr,
+        length: usize,
+    },
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Message {
+                source,
+                destination,
+                message,
+            } => write!(
+                f,
+                "got bgp {} from {} to {}: {}",
+                message.get_name(),
+                source,
+                destination,
+                message
+            ),
+            Event::Malformed {
+                source,
+                destination,
+                length,
+            } => write!(
+                f,
+                "got malformed bgp message with {} bytes from {} to {}",
+                length, source, destination
+            ),
+        }
+    }
+}
+
+// bgp session between a peer and the peer listening on the bgp port; the
+// session uses four-octet as numbers if both peers advertise them in their
+// open messages
+#[derive(Default)]
+struct Session {
+    client_four_octet: Option<bool>,
+    server_four_octet: Option<bool>,
+}
+
+impl Session {
+    // check if the session uses four-octet as numbers, none if not known
+    // because open messages were missed
+    fn is_four_octet(&self) -> Option<bool> {
+        match (self.client_four_octet, self.server_four_octet) {
+            (Some(false), _) | (_, Some(false)) => Some(false),
+            (Some(true), Some(true)) => Some(true),
+            _ => None,
+        }
+    }
+}
+
+// bgp sessions identified by client and server address
+#[derive(Default)]
+pub struct Sessions {
+    sessions: HashMap<(SocketAddr, SocketAddr), Session>,
+}
+
+impl Sessions {
+    // read the complete bgp messages in the stream with key of the session
+    pub fn read(&mut self, key: StreamKey, stream: &mut Stream, from_client: bool) -> Vec<Event> {
+        let (client, server) = match from_client {
+            true => (key.src, key.dst),
+            false => (key.dst, key.src),
+        };
+        let session = self.sessions.entry((client, server)).or_default();
+        let mut events = Vec::new();
+        loop {
+            let data = stream.data();
+            if data.len() < HEADER_LENGTH {
+                break;
+            }
+
+            // if the start of the stream was missed or data is lost,
+            // resynchronize on the next marker
+            if data[..MARKER.len()] != MARKER {
+                match data.windows(MARKER.len()).position(|w| w == MARKER) {
+                    Some(start) => stream.consume(start),
+                    None => stream.consume(data.len() - (MARKER.len() - 1)),
+                }
+                continue;
+            }
+            let length = usize::from(u16::from_be_bytes([data[16], data[17]]));
+            if length < HEADER_LENGTH {
+                stream.consume(1);
+                continue;
+            }
+            if data.len() < length {
+                break;
+            }
+
+            let message = BgpMessage::parse(&data[..length], session.is_four_octet());
+            stream.consume(length);
+            let message = match message {
+                Some(message) => message,
+                None => {
+                    events.push(Event::Malformed {
+                        source: key.src,
+                        destination: key.dst,
+                        length,
+                    });
+                    continue;
+                }
+            };
+            if let BgpMessage::Open(open) = &message {
+                let four_octet = Some(open.get_four_octet_as().is_some());
+                match from_client {
+                    true => session.client_four_octet = four_octet,
+                    false => session.server_four_octet = four_octet,
+                }
+            }
+            events.push(Event::Message {
+                source: key.src,
+                destination: key.dst,
+                message,
+            });
+        }
+        events
+    }
+
+    // remove the session of the stream with key
+    pub fn remove(&mut self, key: StreamKey, from_client: bool) {
+        let (client, server) = match from_client {
+            true => (key.src, key.dst),
+            false => (key.dst, key.src),
+        };
+        self.sessions.remove(&(client, server));
+    }
+}
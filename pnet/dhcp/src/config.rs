@@ -0,0 +1,86 @@
+use clap::Parser;
+use packet_utils::parse::parse_seconds;
+
+// command line configuration of the dhcp tool
+#[derive(Parser, Clone, Debug, Default)]
+#[command(about = "Listen for dhcp packets and parse them")]
+pub struct Config {
+    // network interface to capture packets on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Capture packets on the network interface NAME; by default, \
+        the first interface that is up, is not a loopback and has an ip \
+        address is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., to see unicast \
+        replies of servers to other clients on a mirrored port"
+    )]
+    pub promiscuous: bool,
+
+    // ip versions
+    #[arg(
+        short = '4',
+        long,
+        conflicts_with = "ipv6",
+        help = "Only listen for dhcp packets over ipv4"
+    )]
+    pub ipv4: bool,
+    #[arg(short = '6', long, help = "Only listen for dhcpv6 packets over ipv6")]
+    pub ipv6: bool,
+
+    // only print exchanges
+    #[arg(
+        short,
+        long,
+        help = "Only print the summaries of finished exchanges, e.g., \
+        DISCOVER -> OFFER -> REQUEST -> ACK or SOLICIT -> ADVERTISE -> \
+        REQUEST -> REPLY, instead of every packet"
+    )]
+    pub exchanges: bool,
+
+    // starvation and flood detection
+    #[arg(
+        short,
+        long,
+        conflicts_with = "ipv6",
+        help = "Detect dhcp starvation attacks: alert on high rates of \
+        DISCOVER messages and many new client mac addresses per relay agent \
+        and print the statistics of each relay agent on exit"
+    )]
+    pub analyze: bool,
+    #[arg(
+        long,
+        value_name = "COUNT",
+        default_value_t = 50,
+        requires = "analyze",
+        help = "Alert if more than COUNT DISCOVER messages are seen via a \
+        relay agent within the window"
+    )]
+    pub discover_threshold: u32,
+    #[arg(
+        long,
+        value_name = "COUNT",
+        default_value_t = 20,
+        requires = "analyze",
+        help = "Alert if more than COUNT previously unseen client mac \
+        addresses are seen via a relay agent within the window"
+    )]
+    pub churn_threshold: u32,
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 10.0,
+        value_parser = parse_seconds,
+        requires = "analyze",
+        help = "Length of the window in which DISCOVER messages and new \
+        clients are counted"
+    )]
+    pub window: f64,
+}
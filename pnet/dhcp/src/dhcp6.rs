@@ -0,0 +1,634 @@
+use std::fmt;
+use std::net::Ipv6Addr;
+
+use packet_utils::bytes::*;
+use packet_utils::hex::format_hex;
+
+use crate::error::*;
+
+// length of the header of client and server messages
+const HEADER_LENGTH: usize = 4;
+
+// length of the header of relay agent messages
+const RELAY_HEADER_LENGTH: usize = 34;
+
+// dhcpv6 option codes
+pub const OPTION_CLIENTID: u16 = 1;
+pub const OPTION_SERVERID: u16 = 2;
+pub const OPTION_IA_NA: u16 = 3;
+pub const OPTION_IA_TA: u16 = 4;
+pub const OPTION_IAADDR: u16 = 5;
+pub const OPTION_ORO: u16 = 6;
+pub const OPTION_PREFERENCE: u16 = 7;
+pub const OPTION_ELAPSED_TIME: u16 = 8;
+pub const OPTION_RELAY_MSG: u16 = 9;
+pub const OPTION_STATUS_CODE: u16 = 13;
+pub const OPTION_RAPID_COMMIT: u16 = 14;
+pub const OPTION_DNS_SERVERS: u16 = 23;
+pub const OPTION_DOMAIN_LIST: u16 = 24;
+pub const OPTION_IA_PD: u16 = 25;
+pub const OPTION_IAPREFIX: u16 = 26;
+
+// get the name of the dhcpv6 option with code
+pub fn get_option_name(code: u16) -> &'static str {
+    match code {
+        OPTION_CLIENTID => "client identifier",
+        OPTION_SERVERID => "server identifier",
+        OPTION_IA_NA => "ia_na",
+        OPTION_IA_TA => "ia_ta",
+        OPTION_IAADDR => "ia address",
+        OPTION_ORO => "option request",
+        OPTION_PREFERENCE => "preference",
+        OPTION_ELAPSED_TIME => "elapsed time",
+        OPTION_RELAY_MSG => "relay message",
+        11 => "authentication",
+        12 => "server unicast",
+        OPTION_STATUS_CODE => "status code",
+        OPTION_RAPID_COMMIT => "rapid commit",
+        15 => "user class",
+        16 => "vendor class",
+        17 => "vendor specific",
+        18 => "interface id",
+        19 => "reconfigure message",
+        20 => "reconfigure accept",
+        OPTION_DNS_SERVERS => "dns servers",
+        OPTION_DOMAIN_LIST => "domain list",
+        OPTION_IA_PD => "ia_pd",
+        OPTION_IAPREFIX => "ia prefix",
+        31 => "sntp servers",
+        32 => "information refresh time",
+        39 => "client fqdn",
+        56 => "ntp server",
+        82 => "solicit max rt",
+        _ => "unknown",
+    }
+}
+
+// dhcpv6 message type
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageType {
+    Solicit,
+    Advertise,
+    Request,
+    Confirm,
+    Renew,
+    Rebind,
+    Reply,
+    Release,
+    Decline,
+    Reconfigure,
+    InformationRequest,
+    RelayForw,
+    RelayRepl,
+    Unknown(u8),
+}
+
+impl From<u8> for MessageType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => MessageType::Solicit,
+            2 => MessageType::Advertise,
+            3 => MessageType::Request,
+            4 => MessageType::Confirm,
+            5 => MessageType::Renew,
+            6 => MessageType::Rebind,
+            7 => MessageType::Reply,
+            8 => MessageType::Release,
+            9 => MessageType::Decline,
+            10 => MessageType::Reconfigure,
+            11 => MessageType::InformationRequest,
+            12 => MessageType::RelayForw,
+            13 => MessageType::RelayRepl,
+            value => MessageType::Unknown(value),
+        }
+    }
+}
+
+impl MessageType {
+    // check if messages of this type are sent by servers
+    pub fn is_server_message(&self) -> bool {
+        matches!(
+            self,
+            MessageType::Advertise | MessageType::Reply | MessageType::Reconfigure
+        )
+    }
+}
+
+impl fmt::Display for MessageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageType::Solicit => write!(f, "SOLICIT"),
+            MessageType::Advertise => write!(f, "ADVERTISE"),
+            MessageType::Request => write!(f, "REQUEST"),
+            MessageType::Confirm => write!(f, "CONFIRM"),
+            MessageType::Renew => write!(f, "RENEW"),
+            MessageType::Rebind => write!(f, "REBIND"),
+            MessageType::Reply => write!(f, "REPLY"),
+            MessageType::Release => write!(f, "RELEASE"),
+            MessageType::Decline => write!(f, "DECLINE"),
+            MessageType::Reconfigure => write!(f, "RECONFIGURE"),
+            MessageType::InformationRequest => write!(f, "INFORMATION-REQUEST"),
+            MessageType::RelayForw => write!(f, "RELAY-FORW"),
+            MessageType::RelayRepl => write!(f, "RELAY-REPL"),
+            MessageType::Unknown(value) => write!(f, "{} (unknown)", value),
+        }
+    }
+}
+
+// dhcp unique identifier of clients and servers; it consists of a 16 bit
+// type followed by type specific fields:
+//
+// 1 (link-layer address plus time): hardware type (16 bits), time (32 bits),
+//   link-layer address
+// 2 (vendor-assigned, based on enterprise number): enterprise number
+//   (32 bits), identifier
+// 3 (link-layer address): hardware type (16 bits), link-layer address
+// 4 (uuid): uuid (128 bits)
+//
+// use methods to read fields from the duid
+#[derive(Clone, Copy)]
+pub struct Duid<'a> {
+    raw: &'a [u8],
+}
+
+impl<'a> Duid<'a> {
+    // create a new duid from raw option data
+    pub fn parse(raw: &'a [u8]) -> Result<Duid<'a>> {
+        let length = match raw.get(..2).map(read_be_u16) {
+            Some(1) => 8,
+            Some(2) => 6,
+            Some(3) => 4,
+            Some(4) => 18,
+            Some(_) => 2,
+            None => return Err(DhcpError::OptionLength),
+        };
+        if raw.len() < length {
+            return Err(DhcpError::OptionLength);
+        }
+        Ok(Duid { raw })
+    }
+
+    // get the raw bytes of the duid
+    pub fn get_raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    // get the duid type
+    pub fn get_type(&self) -> u16 {
+        read_be_u16(&self.raw[..2])
+    }
+}
+
+impl<'a> fmt::Display for Duid<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let raw = self.raw;
+        match self.get_type() {
+            1 => write!(
+                f,
+                "{{type: 1 (llt), hardware type: {}, time: {}, address: {}}}",
+                read_be_u16(&raw[2..4]),
+                read_be_u32(&raw[4..8]),
+                format_hex(&raw[8..]),
+            ),
+            2 => write!(
+                f,
+                "{{type: 2 (en), enterprise: {}, identifier: {}}}",
+                read_be_u32(&raw[2..6]),
+                format_hex(&raw[6..]),
+            ),
+            3 => write!(
+                f,
+                "{{type: 3 (ll), hardware type: {}, address: {}}}",
+                read_be_u16(&raw[2..4]),
+                format_hex(&raw[4..]),
+            ),
+            4 => write!(f, "{{type: 4 (uuid), uuid: {}}}", format_hex(&raw[2..])),
+            value => write!(
+                f,
+                "{{type: {} (unknown), data: {}}}",
+                value,
+                format_hex(&raw[2..]),
+            ),
+        }
+    }
+}
+
+// dhcpv6 option parsed from its code and data; options without a specific
+// variant keep their raw data
+pub enum Dhcp6Option<'a> {
+    ClientIdentifier(Duid<'a>),
+    ServerIdentifier(Duid<'a>),
+
+    // identity association for non-temporary or temporary addresses or for
+    // prefix delegation with its iaid, t1 and t2 and its options
+    IaNa(u32, u32, u32, Vec<Dhcp6Option<'a>>),
+    IaTa(u32, Vec<Dhcp6Option<'a>>),
+    IaPd(u32, u32, u32, Vec<Dhcp6Option<'a>>),
+
+    // address with its preferred and valid lifetimes and its options
+    IaAddress(Ipv6Addr, u32, u32, Vec<Dhcp6Option<'a>>),
+
+    // prefix and prefix length with their preferred and valid lifetimes and
+    // their options
+    IaPrefix(Ipv6Addr, u8, u32, u32, Vec<Dhcp6Option<'a>>),
+
+    OptionRequest(Vec<u16>),
+    Preference(u8),
+
+    // elapsed time in hundredths of a second
+    ElapsedTime(u16),
+
+    RelayMessage(Box<Dhcp6Packet<'a>>),
+    StatusCode(u16, String),
+    RapidCommit,
+    DnsServers(Vec<Ipv6Addr>),
+    DomainList(Vec<String>),
+    Other(u16, &'a [u8]),
+}
+
+// check that the option data has at least length bytes
+fn check_length(data: &[u8], length: usize) -> Result<()> {
+    if data.len() < length {
+        return Err(DhcpError::OptionLength);
+    }
+    Ok(())
+}
+
+// convert a 16 byte field to an ipv6 address
+fn read_ipv6(bytes: &[u8]) -> Ipv6Addr {
+    let octets: [u8; 16] = bytes.try_into().expect("slice with incorrect length");
+    Ipv6Addr::from(octets)
+}
+
+// parse the uncompressed domain names in data
+fn parse_domain_list(data: &[u8]) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    let mut labels = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let length = usize::from(data[offset]);
+        if length == 0 {
+            names.push(labels.join("."));
+            labels.clear();
+            offset += 1;
+            continue;
+        }
+        let label = match data.get(offset + 1..offset + 1 + length) {
+            Some(label) => label,
+            None => return Err(DhcpError::OptionLength),
+        };
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        offset += 1 + length;
+    }
+    if !labels.is_empty() {
+        names.push(labels.join("."));
+    }
+    Ok(names)
+}
+
+impl<'a> Dhcp6Option<'a> {
+    // parse the dhcpv6 option with code from its data
+    pub fn parse(code: u16, data: &'a [u8]) -> Result<Dhcp6Option<'a>> {
+        let option = match code {
+            OPTION_CLIENTID => Dhcp6Option::ClientIdentifier(Duid::parse(data)?),
+            OPTION_SERVERID => Dhcp6Option::ServerIdentifier(Duid::parse(data)?),
+            OPTION_IA_NA | OPTION_IA_PD => {
+                check_length(data, 12)?;
+                let iaid = read_be_u32(&data[0..4]);
+                let t1 = read_be_u32(&data[4..8]);
+                let t2 = read_be_u32(&data[8..12]);
+                let options = parse_options(&data[12..])?;
+                match code {
+                    OPTION_IA_NA => Dhcp6Option::IaNa(iaid, t1, t2, options),
+                    _ => Dhcp6Option::IaPd(iaid, t1, t2, options),
+                }
+            }
+            OPTION_IA_TA => {
+                check_length(data, 4)?;
+                Dhcp6Option::IaTa(read_be_u32(&data[0..4]), parse_options(&data[4..])?)
+            }
+            OPTION_IAADDR => {
+                check_length(data, 24)?;
+                Dhcp6Option::IaAddress(
+                    read_ipv6(&data[0..16]),
+                    read_be_u32(&data[16..20]),
+                    read_be_u32(&data[20..24]),
+                    parse_options(&data[24..])?,
+                )
+            }
+            OPTION_IAPREFIX => {
+                check_length(data, 25)?;
+                Dhcp6Option::IaPrefix(
+                    read_ipv6(&data[9..25]),
+                    data[8],
+                    read_be_u32(&data[0..4]),
+                    read_be_u32(&data[4..8]),
+                    parse_options(&data[25..])?,
+                )
+            }
+            OPTION_ORO => {
+                if !data.len().is_multiple_of(2) {
+                    return Err(DhcpError::OptionLength);
+                }
+                Dhcp6Option::OptionRequest(data.chunks(2).map(read_be_u16).collect())
+            }
+            OPTION_PREFERENCE => {
+                check_length(data, 1)?;
+                Dhcp6Option::Preference(data[0])
+            }
+            OPTION_ELAPSED_TIME => {
+                check_length(data, 2)?;
+                Dhcp6Option::ElapsedTime(read_be_u16(&data[0..2]))
+            }
+            OPTION_RELAY_MSG => Dhcp6Option::RelayMessage(Box::new(Dhcp6Packet::parse(data)?)),
+            OPTION_STATUS_CODE => {
+                check_length(data, 2)?;
+                Dhcp6Option::StatusCode(
+                    read_be_u16(&data[0..2]),
+                    String::from_utf8_lossy(&data[2..]).into_owned(),
+                )
+            }
+            OPTION_RAPID_COMMIT => Dhcp6Option::RapidCommit,
+            OPTION_DNS_SERVERS => {
+                if !data.len().is_multiple_of(16) {
+                    return Err(DhcpError::OptionLength);
+                }
+                Dhcp6Option::DnsServers(data.chunks(16).map(read_ipv6).collect())
+            }
+            OPTION_DOMAIN_LIST => Dhcp6Option::DomainList(parse_domain_list(data)?),
+            code => Dhcp6Option::Other(code, data),
+        };
+        Ok(option)
+    }
+
+    // get the code of the dhcpv6 option
+    pub fn get_code(&self) -> u16 {
+        match self {
+            Dhcp6Option::ClientIdentifier(_) => OPTION_CLIENTID,
+            Dhcp6Option::ServerIdentifier(_) => OPTION_SERVERID,
+            Dhcp6Option::IaNa(..) => OPTION_IA_NA,
+            Dhcp6Option::IaTa(..) => OPTION_IA_TA,
+            Dhcp6Option::IaPd(..) => OPTION_IA_PD,
+            Dhcp6Option::IaAddress(..) => OPTION_IAADDR,
+            Dhcp6Option::IaPrefix(..) => OPTION_IAPREFIX,
+            Dhcp6Option::OptionRequest(_) => OPTION_ORO,
+            Dhcp6Option::Preference(_) => OPTION_PREFERENCE,
+            Dhcp6Option::ElapsedTime(_) => OPTION_ELAPSED_TIME,
+            Dhcp6Option::RelayMessage(_) => OPTION_RELAY_MSG,
+            Dhcp6Option::StatusCode(..) => OPTION_STATUS_CODE,
+            Dhcp6Option::RapidCommit => OPTION_RAPID_COMMIT,
+            Dhcp6Option::DnsServers(_) => OPTION_DNS_SERVERS,
+            Dhcp6Option::DomainList(_) => OPTION_DOMAIN_LIST,
+            Dhcp6Option::Other(code, _) => *code,
+        }
+    }
+}
+
+// format the nested options of an option
+fn fmt_options(f: &mut fmt::Formatter<'_>, options: &[Dhcp6Option]) -> fmt::Result {
+    for option in options {
+        write!(f, ", option {}", option)?;
+    }
+    Ok(())
+}
+
+impl<'a> fmt::Display for Dhcp6Option<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = self.get_code();
+        write!(f, "{} ({}): ", code, get_option_name(code))?;
+        match self {
+            Dhcp6Option::ClientIdentifier(duid) | Dhcp6Option::ServerIdentifier(duid) => {
+                write!(f, "{}", duid)
+            }
+            Dhcp6Option::IaNa(iaid, t1, t2, options) | Dhcp6Option::IaPd(iaid, t1, t2, options) => {
+                write!(f, "{{iaid: {}, t1: {}s, t2: {}s", iaid, t1, t2)?;
+                fmt_options(f, options)?;
+                write!(f, "}}")
+            }
+            Dhcp6Option::IaTa(iaid, options) => {
+                write!(f, "{{iaid: {}", iaid)?;
+                fmt_options(f, options)?;
+                write!(f, "}}")
+            }
+            Dhcp6Option::IaAddress(address, preferred, valid, options) => {
+                write!(
+                    f,
+                    "{{address: {}, preferred: {}s, valid: {}s",
+                    address, preferred, valid
+                )?;
+                fmt_options(f, options)?;
+                write!(f, "}}")
+            }
+            Dhcp6Option::IaPrefix(prefix, length, preferred, valid, options) => {
+                write!(
+                    f,
+                    "{{prefix: {}/{}, preferred: {}s, valid: {}s",
+                    prefix, length, preferred, valid
+                )?;
+                fmt_options(f, options)?;
+                write!(f, "}}")
+            }
+            Dhcp6Option::OptionRequest(codes) => {
+                let codes = codes
+                    .iter()
+                    .map(|c| format!("{} ({})", c, get_option_name(*c)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "[{}]", codes)
+            }
+            Dhcp6Option::Preference(preference) => write!(f, "{}", preference),
+            Dhcp6Option::ElapsedTime(time) => write!(f, "{} ms", u32::from(*time) * 10),
+            Dhcp6Option::RelayMessage(packet) => write!(f, "{}", packet),
+            Dhcp6Option::StatusCode(status, message) => write!(f, "{} {:?}", status, message),
+            Dhcp6Option::RapidCommit => write!(f, "yes"),
+            Dhcp6Option::DnsServers(servers) => {
+                let servers = servers
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                write!(f, "[{}]", servers)
+            }
+            Dhcp6Option::DomainList(names) => write!(f, "[{}]", names.join(" ")),
+            Dhcp6Option::Other(_, data) => write!(f, "{}", format_hex(data)),
+        }
+    }
+}
+
+// parse the dhcpv6 options in data
+pub fn parse_options(data: &[u8]) -> Result<Vec<Dhcp6Option<'_>>> {
+    let mut options = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let header = match data.get(offset..offset + 4) {
+            Some(header) => header,
+            None => return Err(DhcpError::OptionLength),
+        };
+        let code = read_be_u16(&header[0..2]);
+        let length = usize::from(read_be_u16(&header[2..4]));
+        let start = offset + 4;
+        let value = match data.get(start..start + length) {
+            Some(value) => value,
+            None => return Err(DhcpError::OptionLength),
+        };
+        options.push(Dhcp6Option::parse(code, value)?);
+        offset = start + length;
+    }
+    Ok(options)
+}
+
+// dhcpv6 client and server messages consist of the following fields:
+//
+// msg-type (8 bits), transaction-id (24 bits), options
+//
+// relay agent messages consist of the following fields:
+//
+// msg-type (8 bits), hop-count (8 bits), link-address (128 bits),
+// peer-address (128 bits), options
+//
+// use methods to read fields from the packet
+pub struct Dhcp6Packet<'a> {
+    raw: &'a [u8],
+
+    // dhcpv6 options inside the packet
+    options: Vec<Dhcp6Option<'a>>,
+}
+
+impl<'a> Dhcp6Packet<'a> {
+    // create a new dhcpv6 packet from raw packet bytes
+    pub fn parse(raw: &'a [u8]) -> Result<Dhcp6Packet<'a>> {
+        let header_length = match raw.first().map(|t| MessageType::from(*t)) {
+            Some(MessageType::RelayForw | MessageType::RelayRepl) => RELAY_HEADER_LENGTH,
+            Some(_) => HEADER_LENGTH,
+            None => return Err(DhcpError::PacketLength),
+        };
+        if raw.len() < header_length {
+            return Err(DhcpError::PacketLength);
+        }
+        let options = parse_options(&raw[header_length..])?;
+        Ok(Dhcp6Packet { raw, options })
+    }
+
+    // get the message type
+    pub fn get_message_type(&self) -> MessageType {
+        MessageType::from(self.raw[0])
+    }
+
+    // check if the packet is a relay agent message
+    pub fn is_relay(&self) -> bool {
+        matches!(
+            self.get_message_type(),
+            MessageType::RelayForw | MessageType::RelayRepl
+        )
+    }
+
+    // get the transaction id of client and server messages
+    pub fn get_transaction_id(&self) -> Option<u32> {
+        if self.is_relay() {
+            return None;
+        }
+        Some(u32::from(self.raw[1]) << 16 | u32::from(read_be_u16(&self.raw[2..4])))
+    }
+
+    // get the hop count of relay agent messages
+    pub fn get_hop_count(&self) -> Option<u8> {
+        self.is_relay().then(|| self.raw[1])
+    }
+
+    // get the link address of relay agent messages
+    pub fn get_link_address(&self) -> Option<Ipv6Addr> {
+        self.is_relay().then(|| read_ipv6(&self.raw[2..18]))
+    }
+
+    // get the peer address of relay agent messages
+    pub fn get_peer_address(&self) -> Option<Ipv6Addr> {
+        self.is_relay().then(|| read_ipv6(&self.raw[18..34]))
+    }
+
+    // get the dhcpv6 options
+    pub fn get_options(&self) -> &[Dhcp6Option<'a>] {
+        &self.options
+    }
+
+    // get the client and server message relayed in relay agent messages
+    // or the packet itself
+    pub fn get_relayed_message(&self) -> &Dhcp6Packet<'a> {
+        self.options
+            .iter()
+            .find_map(|o| match o {
+                Dhcp6Option::RelayMessage(packet) => Some(packet.get_relayed_message()),
+                _ => None,
+            })
+            .unwrap_or(self)
+    }
+
+    // get the duid of the client
+    pub fn get_client_identifier(&self) -> Option<Duid<'a>> {
+        self.options.iter().find_map(|o| match o {
+            Dhcp6Option::ClientIdentifier(duid) => Some(*duid),
+            _ => None,
+        })
+    }
+
+    // get the duid of the server
+    pub fn get_server_identifier(&self) -> Option<Duid<'a>> {
+        self.options.iter().find_map(|o| match o {
+            Dhcp6Option::ServerIdentifier(duid) => Some(*duid),
+            _ => None,
+        })
+    }
+
+    // get the addresses with their valid lifetimes in the ia_na and ia_ta
+    // options and the prefixes with their lengths and valid lifetimes in
+    // the ia_pd options
+    pub fn get_leases(&self) -> Vec<(Ipv6Addr, u8, u32)> {
+        let mut leases = Vec::new();
+        for option in &self.options {
+            let options = match option {
+                Dhcp6Option::IaNa(_, _, _, options)
+                | Dhcp6Option::IaTa(_, options)
+                | Dhcp6Option::IaPd(_, _, _, options) => options,
+                _ => continue,
+            };
+            for option in options {
+                match option {
+                    Dhcp6Option::IaAddress(address, _, valid, _) => {
+                        leases.push((*address, 128, *valid))
+                    }
+                    Dhcp6Option::IaPrefix(prefix, length, _, valid, _) => {
+                        leases.push((*prefix, *length, *valid))
+                    }
+                    _ => (),
+                }
+            }
+        }
+        leases
+    }
+}
+
+impl<'a> fmt::Display for Dhcp6Packet<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // dhcpv6 header
+        write!(f, "{{msg-type: {}", self.get_message_type())?;
+        if let Some(transaction_id) = self.get_transaction_id() {
+            write!(f, ", transaction-id: {:#08x}", transaction_id)?;
+        }
+        if let (Some(hop_count), Some(link_address), Some(peer_address)) = (
+            self.get_hop_count(),
+            self.get_link_address(),
+            self.get_peer_address(),
+        ) {
+            write!(
+                f,
+                ", hop-count: {}, link-address: {}, peer-address: {}",
+                hop_count, link_address, peer_address
+            )?;
+        }
+
+        // dhcpv6 options
+        fmt_options(f, &self.options)?;
+
+        // closing brackets
+        write!(f, "}}")
+    }
+}
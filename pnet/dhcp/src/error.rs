@@ -0,0 +1,22 @@
+use std::fmt;
+
+// use dhcp error types in result
+pub type Result<T> = std::result::Result<T, DhcpError>;
+
+// dhcp error types
+#[derive(Debug)]
+pub enum DhcpError {
+    PacketLength,
+    MagicCookie,
+    OptionLength,
+}
+
+impl fmt::Display for DhcpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DhcpError::PacketLength => write!(f, "invalid length of packet"),
+            DhcpError::MagicCookie => write!(f, "invalid magic cookie"),
+            DhcpError::OptionLength => write!(f, "invalid length of option"),
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use packet_utils::hex::format_hex;
+
+use crate::dhcp6::{self, Dhcp6Packet};
+use crate::options::MessageType;
+use crate::packet::DhcpPacket;
+
+// time after which unfinished exchanges are removed
+const EXCHANGE_TIMEOUT: Duration = Duration::from_secs(60);
+
+// maximum number of unfinished exchanges before old ones are removed
+const MAX_EXCHANGES: usize = 4096;
+
+// dhcp message in an exchange with the addresses it carries and the server
+// that sent it or that it is meant for
+struct Step {
+    message_type: String,
+    from_server: bool,
+    addresses: Vec<String>,
+    server: Option<String>,
+    lease_time: Option<u32>,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message_type)?;
+        if !self.addresses.is_empty() {
+            write!(f, " {}", self.addresses.join(","))?;
+        }
+        if let Some(server) = &self.server {
+            match self.from_server {
+                true => write!(f, " from {}", server)?,
+                false => write!(f, " to {}", server)?,
+            }
+        }
+        if let Some(lease_time) = self.lease_time {
+            write!(f, " lease {}s", lease_time)?;
+        }
+        Ok(())
+    }
+}
+
+// dhcp or dhcpv6 messages of a client with the same transaction id
+pub struct Exchange {
+    version: u8,
+    xid: u32,
+    client: String,
+    steps: Vec<Step>,
+    start: Instant,
+    end: Instant,
+}
+
+impl fmt::Display for Exchange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let steps = self
+            .steps
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        write!(
+            f,
+            "{} exchange {:#010x} of client {}: {} ({:.3} ms)",
+            if self.version == 6 { "dhcpv6" } else { "dhcp" },
+            self.xid,
+            self.client,
+            steps,
+            self.end.duration_since(self.start).as_secs_f64() * 1000.0,
+        )
+    }
+}
+
+// tracker of dhcp and dhcpv6 exchanges that collects the messages of each
+// exchange until it is finished by an ack, nak or reply
+#[derive(Default)]
+pub struct Exchanges {
+    exchanges: HashMap<(u8, u32, Vec<u8>), Exchange>,
+}
+
+impl Exchanges {
+    // add the dhcp packet to its exchange and get the exchange if the
+    // packet finished it
+    pub fn add(&mut self, packet: &DhcpPacket) -> Option<Exchange> {
+        let message_type = packet.get_message_type()?;
+
+        // the address is the one requested by the client or the one
+        // assigned by the server
+        let address = match message_type {
+            MessageType::Offer | MessageType::Ack => Some(packet.get_yiaddr()),
+            MessageType::Request => packet
+                .get_requested_ip()
+                .or(Some(packet.get_ciaddr()).filter(|ip| !ip.is_unspecified())),
+            _ => packet.get_requested_ip(),
+        };
+        let step = Step {
+            message_type: message_type.to_string(),
+            from_server: matches!(
+                message_type,
+                MessageType::Offer | MessageType::Ack | MessageType::Nak
+            ),
+            addresses: address.iter().map(|a| a.to_string()).collect(),
+            server: packet.get_server_identifier().map(|s| s.to_string()),
+            lease_time: packet.get_lease_time(),
+        };
+        let finished = matches!(message_type, MessageType::Ack | MessageType::Nak);
+        self.add_step((4, packet.get_xid(), packet.get_chaddr()), step, finished)
+    }
+
+    // add the client or server message of the dhcpv6 packet to its
+    // exchange and get the exchange if the message finished it
+    pub fn add6(&mut self, packet: &Dhcp6Packet) -> Option<Exchange> {
+        let packet = packet.get_relayed_message();
+        let xid = packet.get_transaction_id()?;
+        let client = packet.get_client_identifier()?;
+        let message_type = packet.get_message_type();
+        let leases = packet.get_leases();
+        let step = Step {
+            message_type: message_type.to_string(),
+            from_server: message_type.is_server_message(),
+            addresses: leases
+                .iter()
+                .map(|(address, length, _)| match length {
+                    128 => address.to_string(),
+                    _ => format!("{}/{}", address, length),
+                })
+                .collect(),
+            server: packet
+                .get_server_identifier()
+                .map(|s| format_hex(s.get_raw())),
+            lease_time: leases
+                .first()
+                .filter(|_| message_type.is_server_message())
+                .map(|(_, _, valid)| *valid),
+        };
+        let finished = message_type == dhcp6::MessageType::Reply;
+        self.add_step((6, xid, client.get_raw()), step, finished)
+    }
+
+    // add step to the exchange of the client with the ip version and
+    // transaction id in key and get the exchange if it is finished
+    fn add_step(&mut self, key: (u8, u32, &[u8]), step: Step, finished: bool) -> Option<Exchange> {
+        let now = Instant::now();
+        self.prune(now);
+
+        let (version, xid, client) = key;
+        let key = (version, xid, client.to_vec());
+        let exchange = self
+            .exchanges
+            .entry(key.clone())
+            .or_insert_with(|| Exchange {
+                version,
+                xid,
+                client: format_hex(client),
+                steps: Vec::new(),
+                start: now,
+                end: now,
+            });
+        exchange.steps.push(step);
+        exchange.end = now;
+
+        match finished {
+            true => self.exchanges.remove(&key),
+            false => None,
+        }
+    }
+
+    // remove unfinished exchanges that timed out; if there are still too
+    // many, the oldest ones are removed
+    fn prune(&mut self, now: Instant) {
+        self.exchanges
+            .retain(|_, e| now.duration_since(e.end) < EXCHANGE_TIMEOUT);
+        while self.exchanges.len() >= MAX_EXCHANGES {
+            let oldest = self
+                .exchanges
+                .iter()
+                .min_by_key(|(_, e)| e.end)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => self.exchanges.remove(&key),
+                None => break,
+            };
+        }
+    }
+}
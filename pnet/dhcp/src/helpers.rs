@@ -0,0 +1,15 @@
+use std::net::Ipv4Addr;
+
+use packet_utils::bytes::read_be_u32;
+
+// convert a 4 byte field to an ipv4 address
+pub fn read_ipv4(bytes: &[u8]) -> Ipv4Addr {
+    Ipv4Addr::from(read_be_u32(bytes))
+}
+
+// get the string in the field bytes that ends with the first nul byte;
+// invalid utf8 is replaced
+pub fn read_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
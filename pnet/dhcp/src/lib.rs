@@ -0,0 +1,182 @@
+mod config;
+mod dhcp6;
+mod error;
+mod exchange;
+mod helpers;
+mod options;
+mod packet;
+mod starvation;
+
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::Duration;
+
+use packet_utils::channel::create_channel;
+use packet_utils::dissect::{parse_ip_frame, parse_udp, Datagram};
+use packet_utils::interface::find_interface;
+use pnet::datalink::DataLinkReceiver;
+
+pub use config::Config;
+pub use dhcp6::{Dhcp6Option, Dhcp6Packet, Duid};
+pub use error::{DhcpError, Result};
+use exchange::*;
+pub use options::{DhcpOption, MessageType};
+pub use packet::DhcpPacket;
+use starvation::StarvationDetector;
+
+// well-known ports of dhcp servers and clients
+const SERVER_PORT: u16 = 67;
+const CLIENT_PORT: u16 = 68;
+
+// well-known ports of dhcpv6 clients and servers
+const CLIENT6_PORT: u16 = 546;
+const SERVER6_PORT: u16 = 547;
+
+// interval in which the listener checks if it should stop
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// parse the ethernet frame and get the udp packet from or to a dhcp or
+// dhcpv6 port inside it if its ip version is enabled in config; fragmented
+// ipv4 packets and ipv6 packets with extension headers are ignored
+fn parse_frame<'a>(frame: &'a [u8], config: &Config) -> Option<Datagram<'a>> {
+    let ip = parse_ip_frame(frame, config.ipv4, config.ipv6)?;
+    match ip.source {
+        IpAddr::V4(_) => parse_udp(&ip, &[SERVER_PORT, CLIENT_PORT]),
+        IpAddr::V6(_) => parse_udp(&ip, &[SERVER6_PORT, CLIENT6_PORT]),
+    }
+}
+
+// handle the dhcp or dhcpv6 packet in datagram: print it unless only
+// exchanges are printed, print its exchange if the packet finished it and
+// print the starvation alerts it caused
+fn handle_datagram(
+    datagram: &Datagram,
+    exchanges: &mut Exchanges,
+    detector: &mut Option<StarvationDetector>,
+    config: &Config,
+) {
+    let mut alerts = Vec::new();
+    let (protocol, parsed) = match datagram.source {
+        SocketAddr::V4(_) => (
+            "dhcp",
+            DhcpPacket::parse(datagram.payload).map(|p| {
+                if let Some(detector) = detector {
+                    alerts = detector.add(&p);
+                }
+                (p.to_string(), exchanges.add(&p))
+            }),
+        ),
+        SocketAddr::V6(_) => (
+            "dhcpv6",
+            Dhcp6Packet::parse(datagram.payload).map(|p| (p.to_string(), exchanges.add6(&p))),
+        ),
+    };
+    let (packet, exchange) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            println!(
+                "got invalid {} packet from {} to {}: {}",
+                protocol, datagram.source, datagram.destination, e
+            );
+            return;
+        }
+    };
+    if !config.exchanges {
+        println!(
+            "got {} packet from {} to {}: {}",
+            protocol, datagram.source, datagram.destination, packet
+        );
+    }
+    if let Some(exchange) = exchange {
+        println!("{}", exchange);
+    }
+    for alert in alerts {
+        println!("{}", alert);
+    }
+}
+
+// listen for dhcp packets on the interface of config until running is unset
+fn listen(
+    mut rx: Box<dyn DataLinkReceiver>,
+    detector: &mut Option<StarvationDetector>,
+    config: &Config,
+    running: &AtomicBool,
+) {
+    let mut exchanges = Exchanges::default();
+    while running.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading packets: {}", e),
+            },
+        };
+        if let Some(datagram) = parse_frame(frame, config) {
+            handle_datagram(&datagram, &mut exchanges, detector, config);
+        }
+    }
+}
+
+// print the statistics of the relay agents seen by the detector
+fn print_relays(detector: &StarvationDetector) {
+    println!("--- dhcp relays ---");
+    println!(
+        "{:<15} {:>9} {:>7} {:>8} {:>6} {:>6} {:>7} {:>13} {:>12} {:>6}",
+        "RELAY",
+        "DISCOVERS",
+        "OFFERS",
+        "REQUESTS",
+        "ACKS",
+        "NAKS",
+        "CLIENTS",
+        "PEAK DISCOVER",
+        "PEAK CLIENTS",
+        "ALERTS"
+    );
+    for (relay, statistics) in detector.get_relays() {
+        println!(
+            "{:<15} {:>9} {:>7} {:>8} {:>6} {:>6} {:>7} {:>13} {:>12} {:>6}",
+            relay,
+            statistics.discovers,
+            statistics.offers,
+            statistics.requests,
+            statistics.acks,
+            statistics.naks,
+            statistics.clients,
+            statistics.peak_discovers,
+            statistics.peak_clients,
+            statistics.alerts,
+        );
+    }
+}
+
+// run the dhcp tool with config
+pub fn run(config: &Config) {
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let rx = match create_channel(&interface, POLL_INTERVAL, config.promiscuous) {
+        Ok((_, rx)) => rx,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+
+    let running = Arc::new(AtomicBool::new(true));
+    let handler_running = running.clone();
+    if let Err(e) = ctrlc::set_handler(move || {
+        handler_running.store(false, atomic::Ordering::Relaxed);
+    }) {
+        panic!("An error occurred when setting the signal handler: {}", e);
+    }
+
+    let mut detector = config.analyze.then(|| StarvationDetector::new(config));
+    listen(rx, &mut detector, config, &running);
+    if let Some(detector) = &detector {
+        print_relays(detector);
+    }
+}
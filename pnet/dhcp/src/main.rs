@@ -0,0 +1,5 @@
+use clap::Parser;
+
+fn main() {
+    dhcp::run(&dhcp::Config::parse());
+}
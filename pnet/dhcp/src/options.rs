@@ -0,0 +1,317 @@
+use std::fmt;
+use std::net::Ipv4Addr;
+
+use packet_utils::bytes::*;
+use packet_utils::hex::format_hex;
+
+use crate::error::*;
+use crate::helpers::*;
+
+// dhcp option codes
+pub const OPTION_PAD: u8 = 0;
+pub const OPTION_SUBNET_MASK: u8 = 1;
+pub const OPTION_ROUTER: u8 = 3;
+pub const OPTION_DOMAIN_NAME_SERVER: u8 = 6;
+pub const OPTION_HOST_NAME: u8 = 12;
+pub const OPTION_DOMAIN_NAME: u8 = 15;
+pub const OPTION_BROADCAST_ADDRESS: u8 = 28;
+pub const OPTION_REQUESTED_IP: u8 = 50;
+pub const OPTION_LEASE_TIME: u8 = 51;
+pub const OPTION_OVERLOAD: u8 = 52;
+pub const OPTION_MESSAGE_TYPE: u8 = 53;
+pub const OPTION_SERVER_IDENTIFIER: u8 = 54;
+pub const OPTION_PARAMETER_REQUEST_LIST: u8 = 55;
+pub const OPTION_MESSAGE: u8 = 56;
+pub const OPTION_MAX_MESSAGE_SIZE: u8 = 57;
+pub const OPTION_RENEWAL_TIME: u8 = 58;
+pub const OPTION_REBINDING_TIME: u8 = 59;
+pub const OPTION_VENDOR_CLASS: u8 = 60;
+pub const OPTION_CLIENT_IDENTIFIER: u8 = 61;
+pub const OPTION_END: u8 = 255;
+
+// get the name of the dhcp option with code
+pub fn get_option_name(code: u8) -> &'static str {
+    match code {
+        OPTION_PAD => "pad",
+        OPTION_SUBNET_MASK => "subnet mask",
+        2 => "time offset",
+        OPTION_ROUTER => "router",
+        4 => "time server",
+        OPTION_DOMAIN_NAME_SERVER => "domain name server",
+        7 => "log server",
+        OPTION_HOST_NAME => "host name",
+        OPTION_DOMAIN_NAME => "domain name",
+        26 => "interface mtu",
+        OPTION_BROADCAST_ADDRESS => "broadcast address",
+        33 => "static route",
+        42 => "ntp servers",
+        43 => "vendor specific",
+        44 => "netbios name server",
+        46 => "netbios node type",
+        47 => "netbios scope",
+        OPTION_REQUESTED_IP => "requested ip",
+        OPTION_LEASE_TIME => "lease time",
+        OPTION_OVERLOAD => "option overload",
+        OPTION_MESSAGE_TYPE => "message type",
+        OPTION_SERVER_IDENTIFIER => "server identifier",
+        OPTION_PARAMETER_REQUEST_LIST => "parameter request list",
+        OPTION_MESSAGE => "message",
+        OPTION_MAX_MESSAGE_SIZE => "maximum message size",
+        OPTION_RENEWAL_TIME => "renewal time",
+        OPTION_REBINDING_TIME => "rebinding time",
+        OPTION_VENDOR_CLASS => "vendor class identifier",
+        OPTION_CLIENT_IDENTIFIER => "client identifier",
+        66 => "tftp server name",
+        67 => "bootfile name",
+        81 => "client fqdn",
+        82 => "relay agent information",
+        119 => "domain search",
+        121 => "classless static route",
+        OPTION_END => "end",
+        _ => "unknown",
+    }
+}
+
+// dhcp message type in option 53
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageType {
+    Discover,
+    Offer,
+    Request,
+    Decline,
+    Ack,
+    Nak,
+    Release,
+    Inform,
+    Unknown(u8),
+}
+
+impl From<u8> for MessageType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => MessageType::Discover,
+            2 => MessageType::Offer,
+            3 => MessageType::Request,
+            4 => MessageType::Decline,
+            5 => MessageType::Ack,
+            6 => MessageType::Nak,
+            7 => MessageType::Release,
+            8 => MessageType::Inform,
+            value => MessageType::Unknown(value),
+        }
+    }
+}
+
+impl fmt::Display for MessageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageType::Discover => write!(f, "DISCOVER"),
+            MessageType::Offer => write!(f, "OFFER"),
+            MessageType::Request => write!(f, "REQUEST"),
+            MessageType::Decline => write!(f, "DECLINE"),
+            MessageType::Ack => write!(f, "ACK"),
+            MessageType::Nak => write!(f, "NAK"),
+            MessageType::Release => write!(f, "RELEASE"),
+            MessageType::Inform => write!(f, "INFORM"),
+            MessageType::Unknown(value) => write!(f, "{} (unknown)", value),
+        }
+    }
+}
+
+// dhcp option parsed from its code and data; options without a specific
+// variant keep their raw data
+pub enum DhcpOption<'a> {
+    SubnetMask(Ipv4Addr),
+    Router(Vec<Ipv4Addr>),
+    DomainNameServer(Vec<Ipv4Addr>),
+    HostName(String),
+    DomainName(String),
+    BroadcastAddress(Ipv4Addr),
+    RequestedIp(Ipv4Addr),
+    LeaseTime(u32),
+    Overload(u8),
+    MessageType(MessageType),
+    ServerIdentifier(Ipv4Addr),
+    ParameterRequestList(&'a [u8]),
+    Message(String),
+    MaxMessageSize(u16),
+    RenewalTime(u32),
+    RebindingTime(u32),
+    VendorClass(String),
+    ClientIdentifier(&'a [u8]),
+    Other(u8, &'a [u8]),
+}
+
+// check that the option data has exactly length bytes
+fn check_length(data: &[u8], length: usize) -> Result<()> {
+    if data.len() != length {
+        return Err(DhcpError::OptionLength);
+    }
+    Ok(())
+}
+
+// parse the option data as a non-empty list of ipv4 addresses
+fn parse_addresses(data: &[u8]) -> Result<Vec<Ipv4Addr>> {
+    if data.is_empty() || !data.len().is_multiple_of(4) {
+        return Err(DhcpError::OptionLength);
+    }
+    Ok(data.chunks(4).map(read_ipv4).collect())
+}
+
+impl<'a> DhcpOption<'a> {
+    // parse the dhcp option with code from its data
+    pub fn parse(code: u8, data: &'a [u8]) -> Result<DhcpOption<'a>> {
+        let option = match code {
+            OPTION_SUBNET_MASK => {
+                check_length(data, 4)?;
+                DhcpOption::SubnetMask(read_ipv4(data))
+            }
+            OPTION_ROUTER => DhcpOption::Router(parse_addresses(data)?),
+            OPTION_DOMAIN_NAME_SERVER => DhcpOption::DomainNameServer(parse_addresses(data)?),
+            OPTION_HOST_NAME => DhcpOption::HostName(read_string(data)),
+            OPTION_DOMAIN_NAME => DhcpOption::DomainName(read_string(data)),
+            OPTION_BROADCAST_ADDRESS => {
+                check_length(data, 4)?;
+                DhcpOption::BroadcastAddress(read_ipv4(data))
+            }
+            OPTION_REQUESTED_IP => {
+                check_length(data, 4)?;
+                DhcpOption::RequestedIp(read_ipv4(data))
+            }
+            OPTION_LEASE_TIME => {
+                check_length(data, 4)?;
+                DhcpOption::LeaseTime(read_be_u32(data))
+            }
+            OPTION_OVERLOAD => {
+                check_length(data, 1)?;
+                DhcpOption::Overload(data[0])
+            }
+            OPTION_MESSAGE_TYPE => {
+                check_length(data, 1)?;
+                DhcpOption::MessageType(MessageType::from(data[0]))
+            }
+            OPTION_SERVER_IDENTIFIER => {
+                check_length(data, 4)?;
+                DhcpOption::ServerIdentifier(read_ipv4(data))
+            }
+            OPTION_PARAMETER_REQUEST_LIST => DhcpOption::ParameterRequestList(data),
+            OPTION_MESSAGE => DhcpOption::Message(read_string(data)),
+            OPTION_MAX_MESSAGE_SIZE => {
+                check_length(data, 2)?;
+                DhcpOption::MaxMessageSize(read_be_u16(data))
+            }
+            OPTION_RENEWAL_TIME => {
+                check_length(data, 4)?;
+                DhcpOption::RenewalTime(read_be_u32(data))
+            }
+            OPTION_REBINDING_TIME => {
+                check_length(data, 4)?;
+                DhcpOption::RebindingTime(read_be_u32(data))
+            }
+            OPTION_VENDOR_CLASS => DhcpOption::VendorClass(read_string(data)),
+            OPTION_CLIENT_IDENTIFIER => DhcpOption::ClientIdentifier(data),
+            code => DhcpOption::Other(code, data),
+        };
+        Ok(option)
+    }
+
+    // get the code of the dhcp option
+    pub fn get_code(&self) -> u8 {
+        match self {
+            DhcpOption::SubnetMask(_) => OPTION_SUBNET_MASK,
+            DhcpOption::Router(_) => OPTION_ROUTER,
+            DhcpOption::DomainNameServer(_) => OPTION_DOMAIN_NAME_SERVER,
+            DhcpOption::HostName(_) => OPTION_HOST_NAME,
+            DhcpOption::DomainName(_) => OPTION_DOMAIN_NAME,
+            DhcpOption::BroadcastAddress(_) => OPTION_BROADCAST_ADDRESS,
+            DhcpOption::RequestedIp(_) => OPTION_REQUESTED_IP,
+            DhcpOption::LeaseTime(_) => OPTION_LEASE_TIME,
+            DhcpOption::Overload(_) => OPTION_OVERLOAD,
+            DhcpOption::MessageType(_) => OPTION_MESSAGE_TYPE,
+            DhcpOption::ServerIdentifier(_) => OPTION_SERVER_IDENTIFIER,
+            DhcpOption::ParameterRequestList(_) => OPTION_PARAMETER_REQUEST_LIST,
+            DhcpOption::Message(_) => OPTION_MESSAGE,
+            DhcpOption::MaxMessageSize(_) => OPTION_MAX_MESSAGE_SIZE,
+            DhcpOption::RenewalTime(_) => OPTION_RENEWAL_TIME,
+            DhcpOption::RebindingTime(_) => OPTION_REBINDING_TIME,
+            DhcpOption::VendorClass(_) => OPTION_VENDOR_CLASS,
+            DhcpOption::ClientIdentifier(_) => OPTION_CLIENT_IDENTIFIER,
+            DhcpOption::Other(code, _) => *code,
+        }
+    }
+}
+
+// format a list of ipv4 addresses
+fn format_addresses(addresses: &[Ipv4Addr]) -> String {
+    addresses
+        .iter()
+        .map(|a| a.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl<'a> fmt::Display for DhcpOption<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = self.get_code();
+        write!(f, "{} ({}): ", code, get_option_name(code))?;
+        match self {
+            DhcpOption::SubnetMask(ip)
+            | DhcpOption::BroadcastAddress(ip)
+            | DhcpOption::RequestedIp(ip)
+            | DhcpOption::ServerIdentifier(ip) => write!(f, "{}", ip),
+            DhcpOption::Router(ips) | DhcpOption::DomainNameServer(ips) => {
+                write!(f, "[{}]", format_addresses(ips))
+            }
+            DhcpOption::HostName(name)
+            | DhcpOption::DomainName(name)
+            | DhcpOption::Message(name)
+            | DhcpOption::VendorClass(name) => write!(f, "{:?}", name),
+            DhcpOption::LeaseTime(seconds)
+            | DhcpOption::RenewalTime(seconds)
+            | DhcpOption::RebindingTime(seconds) => write!(f, "{}s", seconds),
+            DhcpOption::Overload(value) => write!(f, "{}", value),
+            DhcpOption::MessageType(message_type) => write!(f, "{}", message_type),
+            DhcpOption::ParameterRequestList(codes) => {
+                let parameters = codes
+                    .iter()
+                    .map(|c| format!("{} ({})", c, get_option_name(*c)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "[{}]", parameters)
+            }
+            DhcpOption::MaxMessageSize(size) => write!(f, "{}", size),
+            DhcpOption::ClientIdentifier(data) | DhcpOption::Other(_, data) => {
+                write!(f, "{}", format_hex(data))
+            }
+        }
+    }
+}
+
+// parse the dhcp options in data until the end option or the end of data
+pub fn parse_options(data: &[u8]) -> Result<Vec<DhcpOption<'_>>> {
+    let mut options = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let code = data[offset];
+        match code {
+            OPTION_PAD => {
+                offset += 1;
+                continue;
+            }
+            OPTION_END => break,
+            _ => (),
+        }
+        let length = match data.get(offset + 1) {
+            Some(length) => usize::from(*length),
+            None => return Err(DhcpError::OptionLength),
+        };
+        let start = offset + 2;
+        let value = match data.get(start..start + length) {
+            Some(value) => value,
+            None => return Err(DhcpError::OptionLength),
+        };
+        options.push(DhcpOption::parse(code, value)?);
+        offset = start + length;
+    }
+    Ok(options)
+}
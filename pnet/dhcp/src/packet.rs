@@ -0,0 +1,265 @@
+use std::fmt;
+use std::net::Ipv4Addr;
+
+use packet_utils::bytes::*;
+use packet_utils::hex::format_hex;
+
+use crate::error::*;
+use crate::helpers::*;
+use crate::options::*;
+
+// length of the fixed bootp header before the magic cookie
+pub const DHCP_HEADER_LENGTH: usize = 236;
+
+// magic cookie in front of the dhcp options
+const MAGIC_COOKIE: [u8; 4] = [99, 130, 83, 99];
+const OPTIONS_OFFSET: usize = DHCP_HEADER_LENGTH + MAGIC_COOKIE.len();
+
+// offsets of the sname and file fields that may carry options if the
+// overload option is present
+const SNAME_OFFSET: usize = 44;
+const FILE_OFFSET: usize = 108;
+
+// overload option values
+const OVERLOAD_FILE: u8 = 1;
+const OVERLOAD_SNAME: u8 = 2;
+
+// broadcast bit in the flags field
+const FLAG_BROADCAST: u16 = 0x8000;
+
+// OpCode:
+// 1               BOOTREQUEST, sent from client to server
+// 2               BOOTREPLY, sent from server to client
+pub enum OpCode {
+    BootRequest,
+    BootReply,
+    Unknown(u8),
+}
+
+impl From<u8> for OpCode {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => OpCode::BootRequest,
+            2 => OpCode::BootReply,
+            value => OpCode::Unknown(value),
+        }
+    }
+}
+
+impl fmt::Display for OpCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpCode::BootRequest => write!(f, "1 (boot request)"),
+            OpCode::BootReply => write!(f, "2 (boot reply)"),
+            OpCode::Unknown(value) => write!(f, "{} (unknown)", value),
+        }
+    }
+}
+
+// dhcp packet consists of the bootp header with the following fields:
+//
+// op (8 bits), htype (8 bits), hlen (8 bits), hops (8 bits),
+// xid (32 bits),
+// secs (16 bits), flags (16 bits),
+// ciaddr (32 bits), yiaddr (32 bits), siaddr (32 bits), giaddr (32 bits),
+// chaddr (16 bytes), sname (64 bytes), file (128 bytes)
+//
+// followed by the magic cookie and the dhcp options
+//
+// use methods to read fields from the packet
+pub struct DhcpPacket<'a> {
+    raw: &'a [u8],
+
+    // dhcp options inside the packet including the ones in the sname and
+    // file fields if they are overloaded
+    options: Vec<DhcpOption<'a>>,
+
+    // value of the overload option or 0
+    overload: u8,
+}
+
+impl<'a> DhcpPacket<'a> {
+    // create a new dhcp packet from raw packet bytes
+    pub fn parse(raw: &'a [u8]) -> Result<DhcpPacket<'a>> {
+        if raw.len() < OPTIONS_OFFSET {
+            return Err(DhcpError::PacketLength);
+        }
+        if raw[DHCP_HEADER_LENGTH..OPTIONS_OFFSET] != MAGIC_COOKIE {
+            return Err(DhcpError::MagicCookie);
+        }
+
+        let mut options = parse_options(&raw[OPTIONS_OFFSET..])?;
+        let overload = options
+            .iter()
+            .find_map(|o| match o {
+                DhcpOption::Overload(value) => Some(*value),
+                _ => None,
+            })
+            .unwrap_or(0);
+        if overload & OVERLOAD_FILE != 0 {
+            options.extend(parse_options(&raw[FILE_OFFSET..DHCP_HEADER_LENGTH])?);
+        }
+        if overload & OVERLOAD_SNAME != 0 {
+            options.extend(parse_options(&raw[SNAME_OFFSET..FILE_OFFSET])?);
+        }
+
+        Ok(DhcpPacket {
+            raw,
+            options,
+            overload,
+        })
+    }
+
+    // get the message op code
+    pub fn get_op(&self) -> OpCode {
+        OpCode::from(self.raw[0])
+    }
+
+    // get the hardware address type
+    pub fn get_htype(&self) -> u8 {
+        self.raw[1]
+    }
+
+    // get the hardware address length
+    pub fn get_hlen(&self) -> u8 {
+        self.raw[2]
+    }
+
+    // get the number of relay agent hops
+    pub fn get_hops(&self) -> u8 {
+        self.raw[3]
+    }
+
+    // get the transaction id
+    pub fn get_xid(&self) -> u32 {
+        read_be_u32(&self.raw[4..8])
+    }
+
+    // get the seconds elapsed since the client began the exchange
+    pub fn get_secs(&self) -> u16 {
+        read_be_u16(&self.raw[8..10])
+    }
+
+    // get the flags
+    pub fn get_flags(&self) -> u16 {
+        read_be_u16(&self.raw[10..12])
+    }
+
+    // check if the client asked for broadcast replies
+    pub fn is_broadcast(&self) -> bool {
+        self.get_flags() & FLAG_BROADCAST != 0
+    }
+
+    // get the client ip address
+    pub fn get_ciaddr(&self) -> Ipv4Addr {
+        read_ipv4(&self.raw[12..16])
+    }
+
+    // get the "your" (client) ip address assigned by the server
+    pub fn get_yiaddr(&self) -> Ipv4Addr {
+        read_ipv4(&self.raw[16..20])
+    }
+
+    // get the ip address of the next server
+    pub fn get_siaddr(&self) -> Ipv4Addr {
+        read_ipv4(&self.raw[20..24])
+    }
+
+    // get the relay agent ip address
+    pub fn get_giaddr(&self) -> Ipv4Addr {
+        read_ipv4(&self.raw[24..28])
+    }
+
+    // get the first hlen bytes of the client hardware address field
+    pub fn get_chaddr(&self) -> &'a [u8] {
+        let length = usize::from(self.get_hlen()).min(16);
+        &self.raw[28..28 + length]
+    }
+
+    // get the server host name; empty if the field carries options
+    pub fn get_sname(&self) -> String {
+        if self.overload & OVERLOAD_SNAME != 0 {
+            return String::new();
+        }
+        read_string(&self.raw[SNAME_OFFSET..FILE_OFFSET])
+    }
+
+    // get the boot file name; empty if the field carries options
+    pub fn get_file(&self) -> String {
+        if self.overload & OVERLOAD_FILE != 0 {
+            return String::new();
+        }
+        read_string(&self.raw[FILE_OFFSET..DHCP_HEADER_LENGTH])
+    }
+
+    // get the dhcp options
+    pub fn get_options(&self) -> &[DhcpOption<'a>] {
+        &self.options
+    }
+
+    // get the dhcp message type
+    pub fn get_message_type(&self) -> Option<MessageType> {
+        self.options.iter().find_map(|o| match o {
+            DhcpOption::MessageType(message_type) => Some(*message_type),
+            _ => None,
+        })
+    }
+
+    // get the ip address requested by the client
+    pub fn get_requested_ip(&self) -> Option<Ipv4Addr> {
+        self.options.iter().find_map(|o| match o {
+            DhcpOption::RequestedIp(ip) => Some(*ip),
+            _ => None,
+        })
+    }
+
+    // get the lease time offered or assigned by the server in seconds
+    pub fn get_lease_time(&self) -> Option<u32> {
+        self.options.iter().find_map(|o| match o {
+            DhcpOption::LeaseTime(seconds) => Some(*seconds),
+            _ => None,
+        })
+    }
+
+    // get the server identifier
+    pub fn get_server_identifier(&self) -> Option<Ipv4Addr> {
+        self.options.iter().find_map(|o| match o {
+            DhcpOption::ServerIdentifier(ip) => Some(*ip),
+            _ => None,
+        })
+    }
+}
+
+impl<'a> fmt::Display for DhcpPacket<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // bootp header
+        write!(
+            f,
+            "{{op: {}, htype: {}, hlen: {}, hops: {}, xid: {:#010x}, secs: {}, \
+            flags: {:#06x}, ciaddr: {}, yiaddr: {}, siaddr: {}, giaddr: {}, \
+            chaddr: {}, sname: {:?}, file: {:?}",
+            self.get_op(),
+            self.get_htype(),
+            self.get_hlen(),
+            self.get_hops(),
+            self.get_xid(),
+            self.get_secs(),
+            self.get_flags(),
+            self.get_ciaddr(),
+            self.get_yiaddr(),
+            self.get_siaddr(),
+            self.get_giaddr(),
+            format_hex(self.get_chaddr()),
+            self.get_sname(),
+            self.get_file(),
+        )?;
+
+        // dhcp options
+        for option in &self.options {
+            write!(f, ", option {}", option)?;
+        }
+
+        // closing brackets
+        write!(f, "}}")
+    }
+}
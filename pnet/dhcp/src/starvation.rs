@@ -0,0 +1,230 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::options::MessageType;
+use crate::packet::DhcpPacket;
+
+// maximum number of clients remembered per relay before clients that have
+// not been seen within the window are removed
+const MAX_CLIENTS: usize = 65536;
+
+// format the relay agent address; clients on the local segment have none
+fn format_relay(relay: &Ipv4Addr) -> String {
+    match relay.is_unspecified() {
+        true => "local".to_string(),
+        false => relay.to_string(),
+    }
+}
+
+// suspicious dhcp traffic that indicates a starvation attack, i.e., a
+// client trying to exhaust the address pool of the servers
+pub enum Alert {
+    // more discover messages were seen within the window than allowed
+    DiscoverFlood {
+        relay: Ipv4Addr,
+        count: u32,
+        window: Duration,
+    },
+
+    // more previously unseen client mac addresses were seen within the
+    // window than allowed
+    ClientChurn {
+        relay: Ipv4Addr,
+        count: u32,
+        window: Duration,
+    },
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alert::DiscoverFlood {
+                relay,
+                count,
+                window,
+            } => write!(
+                f,
+                "ALERT: discover flood: {} DISCOVER messages via relay {} \
+                within {:.3} s",
+                count,
+                format_relay(relay),
+                window.as_secs_f64()
+            ),
+            Alert::ClientChurn {
+                relay,
+                count,
+                window,
+            } => write!(
+                f,
+                "ALERT: client churn: {} new client mac addresses via relay {} \
+                within {:.3} s",
+                count,
+                format_relay(relay),
+                window.as_secs_f64()
+            ),
+        }
+    }
+}
+
+// counters of a relay in the current window
+struct WindowCounter {
+    start: Instant,
+    discovers: u32,
+    new_clients: u32,
+    flood_alerted: bool,
+    churn_alerted: bool,
+}
+
+impl WindowCounter {
+    fn new(start: Instant) -> WindowCounter {
+        WindowCounter {
+            start,
+            discovers: 0,
+            new_clients: 0,
+            flood_alerted: false,
+            churn_alerted: false,
+        }
+    }
+}
+
+// statistics of the dhcp messages of a relay agent or of the local segment
+pub struct RelayStatistics {
+    pub discovers: u64,
+    pub offers: u64,
+    pub requests: u64,
+    pub acks: u64,
+    pub naks: u64,
+
+    // number of distinct client mac addresses
+    pub clients: u64,
+
+    // highest number of discover messages and new clients in a window
+    pub peak_discovers: u32,
+    pub peak_clients: u32,
+
+    pub alerts: u64,
+
+    // last time clients were seen, keyed by client mac address
+    seen: HashMap<Vec<u8>, Instant>,
+
+    window: WindowCounter,
+}
+
+impl RelayStatistics {
+    fn new(now: Instant) -> RelayStatistics {
+        RelayStatistics {
+            discovers: 0,
+            offers: 0,
+            requests: 0,
+            acks: 0,
+            naks: 0,
+            clients: 0,
+            peak_discovers: 0,
+            peak_clients: 0,
+            alerts: 0,
+            seen: HashMap::new(),
+            window: WindowCounter::new(now),
+        }
+    }
+}
+
+// detector of dhcp starvation attacks that counts discover messages and new
+// client mac addresses per relay agent within a window and alerts if they
+// exceed the thresholds
+pub struct StarvationDetector {
+    relays: BTreeMap<Ipv4Addr, RelayStatistics>,
+    discover_threshold: u32,
+    churn_threshold: u32,
+    window: Duration,
+}
+
+impl StarvationDetector {
+    pub fn new(config: &Config) -> StarvationDetector {
+        StarvationDetector {
+            relays: BTreeMap::new(),
+            discover_threshold: config.discover_threshold,
+            churn_threshold: config.churn_threshold,
+            window: Duration::from_secs_f64(config.window),
+        }
+    }
+
+    // add the dhcp packet to the statistics of its relay agent and get the
+    // alerts it caused
+    pub fn add(&mut self, packet: &DhcpPacket) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        let message_type = match packet.get_message_type() {
+            Some(message_type) => message_type,
+            None => return alerts,
+        };
+        let now = Instant::now();
+        let relay = packet.get_giaddr();
+        let window = self.window;
+        let statistics = self
+            .relays
+            .entry(relay)
+            .or_insert_with(|| RelayStatistics::new(now));
+        if now.duration_since(statistics.window.start) >= window {
+            statistics.window = WindowCounter::new(now);
+        }
+        match message_type {
+            MessageType::Discover => statistics.discovers += 1,
+            MessageType::Offer => statistics.offers += 1,
+            MessageType::Request => statistics.requests += 1,
+            MessageType::Ack => statistics.acks += 1,
+            MessageType::Nak => statistics.naks += 1,
+            _ => (),
+        }
+
+        // only messages of clients reveal their client mac addresses
+        if !matches!(
+            message_type,
+            MessageType::Discover | MessageType::Request | MessageType::Inform
+        ) {
+            return alerts;
+        }
+        if statistics.seen.len() >= MAX_CLIENTS {
+            statistics
+                .seen
+                .retain(|_, seen| now.duration_since(*seen) < window);
+        }
+        let client = packet.get_chaddr().to_vec();
+        if statistics.seen.insert(client, now).is_none() {
+            statistics.clients += 1;
+            statistics.window.new_clients += 1;
+        }
+        if message_type == MessageType::Discover {
+            statistics.window.discovers += 1;
+        }
+
+        let counter = &mut statistics.window;
+        statistics.peak_discovers = statistics.peak_discovers.max(counter.discovers);
+        statistics.peak_clients = statistics.peak_clients.max(counter.new_clients);
+        if counter.discovers > self.discover_threshold && !counter.flood_alerted {
+            counter.flood_alerted = true;
+            alerts.push(Alert::DiscoverFlood {
+                relay,
+                count: counter.discovers,
+                window,
+            });
+        }
+        if counter.new_clients > self.churn_threshold && !counter.churn_alerted {
+            counter.churn_alerted = true;
+            alerts.push(Alert::ClientChurn {
+                relay,
+                count: counter.new_clients,
+                window,
+            });
+        }
+        statistics.alerts += alerts.len() as u64;
+        alerts
+    }
+
+    // get the statistics of the relays sorted by relay address; the local
+    // segment comes first
+    pub fn get_relays(&self) -> impl Iterator<Item = (String, &RelayStatistics)> {
+        self.relays.iter().map(|(r, s)| (format_relay(r), s))
+    }
+}
@@ -0,0 +1,574 @@
+use std::io;
+use std::mem;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::os::fd::{AsRawFd, FromRawFd};
+use std::sync::atomic::{self, AtomicBool};
+use std::time::{Duration, Instant, SystemTime};
+
+use pnet::datalink::NetworkInterface;
+
+use crate::builder::*;
+use crate::error::*;
+use crate::helpers::*;
+use crate::mdns::*;
+use crate::output::*;
+use crate::packet::*;
+use crate::protocol::*;
+use crate::record::*;
+use crate::socket::*;
+
+// well-known mdns port
+const MDNS_PORT: u16 = 5353;
+
+// ttls of records as recommended in rfc 6762: records containing host
+// names and other records
+const HOST_RECORD_TTL: u32 = 120;
+const OTHER_RECORD_TTL: u32 = 4500;
+
+// maximum ttl in responses to legacy unicast queries, i.e., queries not
+// sent from the mdns port
+const LEGACY_UNICAST_TTL: u32 = 10;
+
+// cache-flush bit in the class field of unique records
+const CACHE_FLUSH: u16 = 0x8000;
+
+// ip ttl and hop limit of all mdns packets
+const MDNS_HOP_LIMIT: libc::c_int = 255;
+
+// name of the meta-query for all service types in the domain
+const SERVICES_NAME: &str = "_services._dns-sd._udp.local.";
+
+// number of announcements at startup and the interval between them
+const ANNOUNCEMENTS: u32 = 2;
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(1);
+
+// interval in which receivers and announcements check if they should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// get the host name of this system
+pub fn get_hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // safety: the buffer is valid for its length
+    let ret = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
+    if ret != 0 {
+        return None;
+    }
+    let length = buf.iter().position(|b| *b == 0)?;
+    String::from_utf8(buf[..length].to_vec()).ok()
+}
+
+// convert the dns error e into an io error of invalid data
+fn invalid_data(e: DnsError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+// get name in the mdns domain "local.", the domain is appended if needed
+fn get_local_name(name: &str) -> String {
+    let name = normalize_name(name);
+    match is_in_domain(&name, "local.") {
+        true => name,
+        false => format!("{}local.", name),
+    }
+}
+
+// record of an advertised service
+struct ServiceRecord {
+    name: String,
+    typ: Type,
+
+    // unique records have the cache-flush bit set, shared records do not
+    unique: bool,
+
+    ttl: u32,
+    data: Data<'static>,
+}
+
+impl ServiceRecord {
+    fn new(name: &str, unique: bool, ttl: u32, data: Data<'static>) -> ServiceRecord {
+        let typ = match data {
+            Data::A(..) => Type::A,
+            Data::Ptr(..) => Type::Ptr,
+            Data::Txt(..) => Type::Txt,
+            Data::Aaaa(..) => Type::Aaaa,
+            Data::Srv(..) => Type::Srv,
+            _ => unreachable!("unsupported service record data"),
+        };
+        ServiceRecord {
+            name: name.to_string(),
+            typ,
+            unique,
+            ttl,
+            data,
+        }
+    }
+
+    // get the class of the record with the cache-flush bit if it is set
+    fn get_class(&self, cache_flush: bool) -> Class {
+        match self.unique && cache_flush {
+            true => Class::from(u16::from(Class::In) | CACHE_FLUSH),
+            false => Class::In,
+        }
+    }
+
+    // check if the record answers a question for name with type typ
+    fn is_answer(&self, name: &str, typ: Type) -> bool {
+        (typ == self.typ || typ == Type::All) && normalize_name(name) == normalize_name(&self.name)
+    }
+
+    // check if the answer in a query already contains this record with at
+    // least half of its ttl, so it is not sent again (known-answer
+    // suppression)
+    fn is_known(&self, answer: &DnsAnswer) -> bool {
+        self.is_answer(&answer.get_name(), answer.get_type())
+            && answer.get_ttl() >= self.ttl / 2
+            && answer.get_data().get_presentation() == self.data.get_presentation()
+    }
+}
+
+// dns-sd service instance with the ptr, srv, txt and address records that
+// are advertised over mdns
+pub struct Service {
+    instance: String,
+    records: Vec<ServiceRecord>,
+}
+
+impl Service {
+    // create the service instance named instance of the service type typ,
+    // e.g., "_http._tcp", on port of host with the addresses addrs and
+    // txt entries, e.g., "path=/"; the domain "local." is appended to the
+    // service type and the host if needed
+    pub fn new(
+        instance: &str,
+        typ: &str,
+        port: u16,
+        txt: &[String],
+        host: &str,
+        addrs: &[IpAddr],
+    ) -> io::Result<Service> {
+        let invalid = |text: String| io::Error::new(io::ErrorKind::InvalidInput, text);
+        if instance.is_empty() || instance.contains('.') {
+            return Err(invalid(format!("invalid service instance: {}", instance)));
+        }
+        if !typ.starts_with('_') {
+            return Err(invalid(format!("invalid service type: {}", typ)));
+        }
+        if addrs.is_empty() {
+            return Err(invalid(format!("no addresses of host {}", host)));
+        }
+        let typ = get_local_name(typ);
+        let host = get_local_name(host);
+        let instance = format!("{}.{}", instance, typ);
+
+        // an empty txt record contains a single empty string
+        let txt = match txt.is_empty() {
+            true => vec![String::new()],
+            false => txt.to_vec(),
+        };
+
+        let mut records = vec![
+            ServiceRecord::new(
+                SERVICES_NAME,
+                false,
+                OTHER_RECORD_TTL,
+                Data::Ptr(typ.clone()),
+            ),
+            ServiceRecord::new(&typ, false, OTHER_RECORD_TTL, Data::Ptr(instance.clone())),
+            ServiceRecord::new(
+                &instance,
+                true,
+                HOST_RECORD_TTL,
+                Data::Srv(0, 0, port, host.clone()),
+            ),
+            ServiceRecord::new(&instance, true, OTHER_RECORD_TTL, Data::Txt(txt)),
+        ];
+        for addr in addrs {
+            let data = match addr {
+                IpAddr::V4(addr) => Data::A(*addr),
+                IpAddr::V6(addr) => Data::Aaaa(*addr),
+            };
+            records.push(ServiceRecord::new(&host, true, HOST_RECORD_TTL, data));
+        }
+
+        // check that all records can be sent
+        let service = Service { instance, records };
+        service.announcement(false).map_err(invalid_data)?;
+        Ok(service)
+    }
+
+    // get the indexes of the records answering the questions in query
+    // that are not in its known answers
+    fn get_answers(&self, query: &DnsPacket) -> Vec<usize> {
+        let known: Vec<&DnsAnswer> = (0..query.get_answers().into())
+            .filter_map(|i| query.get_answer(i))
+            .collect();
+        let mut answers = Vec::new();
+        for question in (0..query.get_questions().into()).filter_map(|i| query.get_question(i)) {
+            if question.get_class() != Class::In {
+                continue;
+            }
+            let (name, typ) = (question.get_name(), question.get_type());
+            for (i, record) in self.records.iter().enumerate() {
+                if record.is_answer(&name, typ)
+                    && !answers.contains(&i)
+                    && !known.iter().any(|answer| record.is_known(answer))
+                {
+                    answers.push(i);
+                }
+            }
+        }
+        answers
+    }
+
+    // get the indexes of the records that are sent as additional records
+    // with answers as recommended in rfc 6763: srv, txt and address
+    // records with ptr records of the service instance, address records
+    // with srv records
+    fn get_additionals(&self, answers: &[usize]) -> Vec<usize> {
+        let has_answer = |typ: Type, name: &str| {
+            answers.iter().any(|i| {
+                let record = &self.records[*i];
+                record.typ == typ && normalize_name(&record.name) == normalize_name(name)
+            })
+        };
+        let has_ptr = answers.iter().any(|i| match &self.records[*i].data {
+            Data::Ptr(domain) => *domain == self.instance,
+            _ => false,
+        });
+        let has_srv = has_ptr || has_answer(Type::Srv, &self.instance);
+        (0..self.records.len())
+            .filter(|i| !answers.contains(i))
+            .filter(|i| match self.records[*i].typ {
+                Type::Srv | Type::Txt => has_ptr,
+                Type::A | Type::Aaaa => has_srv,
+                _ => false,
+            })
+            .collect()
+    }
+
+    // get the response to the mdns query; legacy unicast responses contain
+    // the identification and questions of the query, shorter ttls and no
+    // cache-flush bits; returns none if there are no answers
+    pub fn answer(&self, query: &DnsPacket, legacy: bool) -> Result<Option<DnsBuilder>> {
+        if query.get_qr() != 0 || query.get_opcode() != OpCode::Query {
+            return Ok(None);
+        }
+        let answers = self.get_answers(query);
+        if answers.is_empty() {
+            return Ok(None);
+        }
+        let mut response = match legacy {
+            true => DnsBuilder::response(query)?,
+            false => {
+                let mut response = DnsBuilder::new(0);
+                response.set_qr(1);
+                response
+            }
+        };
+        response.set_aa(1);
+        let ttl = |record: &ServiceRecord| match legacy {
+            true => record.ttl.min(LEGACY_UNICAST_TTL),
+            false => record.ttl,
+        };
+        for record in answers.iter().map(|i| &self.records[*i]) {
+            let class = record.get_class(!legacy);
+            response.add_answer(&record.name, record.typ, class, ttl(record), &record.data)?;
+        }
+        for i in self.get_additionals(&answers) {
+            let record = &self.records[i];
+            let class = record.get_class(!legacy);
+            response.add_additional(&record.name, record.typ, class, ttl(record), &record.data)?;
+        }
+        Ok(Some(response))
+    }
+
+    // get the unsolicited response announcing all records or, if goodbye is
+    // set, announcing their removal with a ttl of zero
+    pub fn announcement(&self, goodbye: bool) -> Result<DnsBuilder> {
+        let mut response = DnsBuilder::new(0);
+        response.set_qr(1);
+        response.set_aa(1);
+        for record in &self.records {
+            let ttl = if goodbye { 0 } else { record.ttl };
+            let class = record.get_class(true);
+            response.add_answer(&record.name, record.typ, class, ttl, &record.data)?;
+        }
+        Ok(response)
+    }
+}
+
+impl std::fmt::Display for Service {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let records: Vec<String> = self
+            .records
+            .iter()
+            .map(|r| {
+                format!(
+                    "{} {} {} {}",
+                    r.name,
+                    r.ttl,
+                    r.typ.get_mnemonic(),
+                    r.data.get_presentation()
+                )
+            })
+            .collect();
+        write!(
+            f,
+            "{{instance: {}, records: [{}]}}",
+            self.instance,
+            records.join(", ")
+        )
+    }
+}
+
+// create an udp socket bound to the mdns port on the unspecified address
+// of the ip version; the port is shared with other mdns responders on the
+// host
+fn bind_shared(ipv6: bool) -> io::Result<UdpSocket> {
+    let domain = if ipv6 { libc::AF_INET6 } else { libc::AF_INET };
+    // safety: socket only creates a new file descriptor
+    let fd = unsafe { libc::socket(domain, libc::SOCK_DGRAM, 0) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // safety: fd is a new udp socket owned by the returned socket, so it is
+    // closed on errors
+    let socket = unsafe { UdpSocket::from_raw_fd(fd) };
+    let on: libc::c_int = 1;
+    set_option(fd, libc::SOL_SOCKET, libc::SO_REUSEADDR, &on.to_ne_bytes())?;
+    let hops = MDNS_HOP_LIMIT.to_ne_bytes();
+
+    // safety: the socket addresses are plain structs, zero is a valid
+    // value of all their fields
+    let ret = if ipv6 {
+        set_option(fd, libc::IPPROTO_IPV6, libc::IPV6_V6ONLY, &on.to_ne_bytes())?;
+        set_option(fd, libc::IPPROTO_IPV6, libc::IPV6_MULTICAST_HOPS, &hops)?;
+        set_option(fd, libc::IPPROTO_IPV6, libc::IPV6_UNICAST_HOPS, &hops)?;
+        let mut addr: libc::sockaddr_in6 = unsafe { mem::zeroed() };
+        addr.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+        addr.sin6_port = MDNS_PORT.to_be();
+        unsafe {
+            libc::bind(
+                fd,
+                &addr as *const libc::sockaddr_in6 as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_in6>() as libc::socklen_t,
+            )
+        }
+    } else {
+        set_option(fd, libc::IPPROTO_IP, libc::IP_MULTICAST_TTL, &hops)?;
+        set_option(fd, libc::IPPROTO_IP, libc::IP_TTL, &hops)?;
+        let mut addr: libc::sockaddr_in = unsafe { mem::zeroed() };
+        addr.sin_family = libc::AF_INET as libc::sa_family_t;
+        addr.sin_port = MDNS_PORT.to_be();
+        unsafe {
+            libc::bind(
+                fd,
+                &addr as *const libc::sockaddr_in as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_in>() as libc::socklen_t,
+            )
+        }
+    };
+    if ret != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(socket)
+}
+
+// create the ipv4 mdns socket joined to the mdns group on interface
+fn create_ipv4_socket(interface: Option<&NetworkInterface>) -> io::Result<(UdpSocket, SocketAddr)> {
+    let socket = bind_shared(false)?;
+    let addr = interface
+        .and_then(|i| {
+            i.ips.iter().find_map(|ip| match ip.ip() {
+                IpAddr::V4(addr) => Some(addr),
+                IpAddr::V6(_) => None,
+            })
+        })
+        .unwrap_or(Ipv4Addr::UNSPECIFIED);
+    if let Some(interface) = interface {
+        bind_to_interface(socket.as_raw_fd(), &interface.name)?;
+        set_option(
+            socket.as_raw_fd(),
+            libc::IPPROTO_IP,
+            libc::IP_MULTICAST_IF,
+            &addr.octets(),
+        )?;
+    }
+    socket.join_multicast_v4(&MDNS_IPV4_GROUP, &addr)?;
+    socket.set_read_timeout(Some(READ_TIMEOUT))?;
+    Ok((socket, SocketAddr::new(MDNS_IPV4_GROUP.into(), MDNS_PORT)))
+}
+
+// create the ipv6 mdns socket joined to the mdns group on interface
+fn create_ipv6_socket(interface: Option<&NetworkInterface>) -> io::Result<(UdpSocket, SocketAddr)> {
+    let socket = bind_shared(true)?;
+    let index = interface.map_or(0, |i| i.index);
+    if let Some(interface) = interface {
+        bind_to_interface(socket.as_raw_fd(), &interface.name)?;
+        set_option(
+            socket.as_raw_fd(),
+            libc::IPPROTO_IPV6,
+            libc::IPV6_MULTICAST_IF,
+            &(index as libc::c_int).to_ne_bytes(),
+        )?;
+    }
+    socket.join_multicast_v6(&MDNS_IPV6_GROUP, index)?;
+    socket.set_read_timeout(Some(READ_TIMEOUT))?;
+    let group = SocketAddr::new(IpAddr::V6(MDNS_IPV6_GROUP), MDNS_PORT);
+    Ok((socket, group))
+}
+
+// responder that advertises a service over mdns on ipv4 and ipv6
+pub struct Advertiser {
+    service: Service,
+    output: Output,
+
+    // sockets and the mdns groups announcements are sent to
+    sockets: Vec<(UdpSocket, SocketAddr)>,
+}
+
+impl Advertiser {
+    // create the advertiser of service on interface or, if not set, the
+    // default interface; ipv4 and ipv6 select the ip versions
+    pub fn new(
+        service: Service,
+        interface: Option<&NetworkInterface>,
+        ipv4: bool,
+        ipv6: bool,
+        output: Output,
+    ) -> io::Result<Advertiser> {
+        let mut sockets = Vec::new();
+        if ipv4 {
+            sockets.push(create_ipv4_socket(interface)?);
+        }
+        if ipv6 {
+            sockets.push(create_ipv6_socket(interface)?);
+        }
+        Ok(Advertiser {
+            service,
+            output,
+            sockets,
+        })
+    }
+
+    // send the announcement or goodbye of the service to the mdns groups
+    fn announce(&self, goodbye: bool) -> io::Result<()> {
+        let now = SystemTime::now();
+        let announcement = self
+            .service
+            .announcement(goodbye)
+            .map_err(invalid_data)?
+            .build();
+        for (socket, group) in &self.sockets {
+            socket.send_to(&announcement, group)?;
+        }
+        let kind = if goodbye {
+            "mdns goodbye"
+        } else {
+            "mdns announcement"
+        };
+        self.output
+            .event(now, kind, None, &self.service.to_string());
+        Ok(())
+    }
+
+    // answer queries received on socket until running is unset; responses
+    // are sent to the mdns group or, if requested or to legacy unicast
+    // queries, to the querier
+    fn serve(&self, socket: &UdpSocket, group: SocketAddr, running: &AtomicBool) -> io::Result<()> {
+        let mut buf = [0; 65535];
+        while running.load(atomic::Ordering::Relaxed) {
+            let (len, src) = match socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(e) => match e.kind() {
+                    io::ErrorKind::WouldBlock
+                    | io::ErrorKind::TimedOut
+                    | io::ErrorKind::Interrupted => continue,
+                    _ => return Err(e),
+                },
+            };
+            let now = SystemTime::now();
+            let query = match DnsPacket::parse_protocol(&buf[..len], Protocol::Mdns) {
+                Ok(query) if query.get_qr() == 0 => query,
+                Ok(_) => continue,
+                Err(e) => {
+                    let text = format!("malformed mdns packet: {}", e);
+                    self.output
+                        .event(now, "mdns query", Some(src.to_string()), &text);
+                    continue;
+                }
+            };
+            let legacy = src.port() != MDNS_PORT;
+            let response = match self.service.answer(&query, legacy) {
+                Ok(Some(response)) => response.build(),
+                Ok(None) => continue,
+                Err(e) => {
+                    let text = format!("could not answer query: {}", e);
+                    self.output
+                        .event(now, "mdns query", Some(src.to_string()), &text);
+                    continue;
+                }
+            };
+            let unicast = legacy
+                || (0..query.get_questions().into())
+                    .filter_map(|i| query.get_question(i))
+                    .all(|q| q.get_unicast_response() == 1);
+            let dst = if unicast { src } else { group };
+            socket.send_to(&response, dst)?;
+
+            // print the query with the answers
+            let questions: Vec<String> = (0..query.get_questions().into())
+                .filter_map(|i| query.get_question(i))
+                .map(|q| format!("{} {}", q.get_name(), q.get_type().get_mnemonic()))
+                .collect();
+            let answers =
+                DnsPacket::parse_protocol(&response, Protocol::Mdns).map_or(0, |r| r.get_answers());
+            let text = format!(
+                "{{questions: [{}], answers: {}, response to: {}}}",
+                questions.join(", "),
+                answers,
+                dst
+            );
+            self.output
+                .event(now, "mdns query", Some(src.to_string()), &text);
+        }
+        Ok(())
+    }
+
+    // announce the service, answer queries until running is unset and send
+    // goodbye packets; the receivers are stopped if announcing fails
+    pub fn run(&self, running: &AtomicBool) -> io::Result<()> {
+        let serving = &AtomicBool::new(true);
+        std::thread::scope(|s| {
+            let receivers: Vec<_> = self
+                .sockets
+                .iter()
+                .map(|(socket, group)| s.spawn(move || self.serve(socket, *group, serving)))
+                .collect();
+
+            // announce the service repeatedly at startup
+            let mut next = Instant::now();
+            let mut announcements = 0;
+            let mut result = Ok(());
+            while running.load(atomic::Ordering::Relaxed) {
+                if announcements < ANNOUNCEMENTS && Instant::now() >= next {
+                    result = self.announce(false);
+                    if result.is_err() {
+                        break;
+                    }
+                    announcements += 1;
+                    next += ANNOUNCE_INTERVAL;
+                }
+                std::thread::sleep(READ_TIMEOUT);
+            }
+            serving.store(false, atomic::Ordering::Relaxed);
+            for receiver in receivers {
+                match receiver.join() {
+                    Ok(receiver_result) => receiver_result?,
+                    Err(e) => std::panic::resume_unwind(e),
+                }
+            }
+            result?;
+            self.announce(true)
+        })
+    }
+}
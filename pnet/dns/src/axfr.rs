@@ -0,0 +1,104 @@
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+
+use crate::builder::*;
+use crate::error::*;
+use crate::packet::*;
+use crate::record::*;
+use crate::stream::*;
+use crate::summary::*;
+use crate::transfer::*;
+
+// time to wait for connecting to the server and for each read from it
+const AXFR_TIMEOUT: Duration = Duration::from_secs(10);
+
+// get an io error of kind with the dns error e
+fn io_error(kind: io::ErrorKind, e: DnsError) -> io::Error {
+    io::Error::new(kind, e.to_string())
+}
+
+// zone transferred from a server with its records in zone file format
+pub struct Zone {
+    pub transfer: ZoneTransfer,
+    pub records: Vec<String>,
+}
+
+impl Zone {
+    // add the records of the next message of the zone transfer
+    fn add(&mut self, packet: &DnsPacket) {
+        let answers = (0..packet.get_answers().into()).map_while(|i| packet.get_answer(i));
+        for answer in answers {
+            self.records.push(answer.get_presentation());
+        }
+        self.transfer.add(&Summary::new(packet));
+    }
+}
+
+// request a transfer of the entire zone (AXFR) from the server over tcp and
+// read the response messages until the transfer is complete or the server
+// closes the connection
+pub fn request_zone(server: SocketAddr, zone: &str) -> io::Result<Zone> {
+    let id = std::process::id() as u16;
+    let mut query = DnsBuilder::query(id, zone, Type::Axfr)
+        .map_err(|e| io_error(io::ErrorKind::InvalidInput, e))?;
+    query.set_rd(0);
+    let query = query.build();
+
+    // messages over tcp are prefixed with a two byte length field
+    let mut connection = TcpStream::connect_timeout(&server, AXFR_TIMEOUT)?;
+    connection.set_read_timeout(Some(AXFR_TIMEOUT))?;
+    let mut request = (query.len() as u16).to_be_bytes().to_vec();
+    request.extend_from_slice(&query);
+    connection.write_all(&request)?;
+
+    // reassemble the response messages from the received data
+    let key = StreamKey {
+        src: server,
+        dst: connection.local_addr()?,
+    };
+    let mut streams = Streams::new();
+    let mut seq = 0u32;
+    let mut zone: Option<Zone> = None;
+    let mut buf = [0; 65535];
+    loop {
+        let len = connection.read(&mut buf)?;
+        if len == 0 {
+            break;
+        }
+        let stream = streams.add(key, seq, false, &buf[..len]);
+        seq = seq.wrapping_add(len as u32);
+        while let Some(message) = stream.next_dns_message() {
+            let packet =
+                DnsPacket::parse(&message).map_err(|e| io_error(io::ErrorKind::InvalidData, e))?;
+            if packet.get_id() != id {
+                continue;
+            }
+            if packet.get_rcode() != RCode::NoError {
+                return Err(io::Error::other(format!(
+                    "server answered with rcode {}",
+                    packet.get_rcode()
+                )));
+            }
+            let zone = match &mut zone {
+                Some(zone) => zone,
+                None => zone.insert(Zone {
+                    transfer: ZoneTransfer::start(&Summary::new(&packet)).ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, "not a zone transfer")
+                    })?,
+                    records: Vec::new(),
+                }),
+            };
+            zone.add(&packet);
+        }
+        if zone.as_ref().is_some_and(|z| z.transfer.is_complete()) {
+            break;
+        }
+    }
+    zone.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "connection closed without a response",
+        )
+    })
+}
@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use packet_utils::bytes::*;
+
+use crate::error::*;
+use crate::packet::*;
+use crate::record::*;
+
+// maximum length of a label and a name
+const MAX_LABEL_LENGTH: usize = 63;
+const MAX_NAME_LENGTH: usize = 255;
+
+// maximum offset of names that can be referenced by compressed names
+const MAX_POINTER_OFFSET: usize = 0x3fff;
+
+// offsets of the record counters in the header
+const QUESTIONS_OFFSET: usize = 4;
+const ANSWERS_OFFSET: usize = 6;
+const ADDITIONALS_OFFSET: usize = 10;
+
+// builder of dns packets:
+// header fields can be set at any time, questions and records must be added
+// in the order of their sections, i.e., questions before answers; names are
+// compressed
+pub struct DnsBuilder {
+    raw: Vec<u8>,
+
+    // offset of the counter of the section records are currently added to
+    section: usize,
+
+    // offsets of names in the packet for compression, keyed by lower case
+    // name
+    names: HashMap<String, usize>,
+}
+
+impl DnsBuilder {
+    // create a builder of a packet with identification id and no records
+    pub fn new(id: u16) -> DnsBuilder {
+        let mut raw = vec![0; DNS_HEADER_LENGTH];
+        write_be_u16(&mut raw[0..2], id);
+        DnsBuilder {
+            raw,
+            section: QUESTIONS_OFFSET,
+            names: HashMap::new(),
+        }
+    }
+
+    // create a builder of a standard query for name with type typ and
+    // recursion desired
+    pub fn query(id: u16, name: &str, typ: Type) -> Result<DnsBuilder> {
+        let mut builder = DnsBuilder::new(id);
+        builder.set_rd(1);
+        builder.add_question(name, typ, Class::In)?;
+        Ok(builder)
+    }
+
+    // create a builder of a standard query for the host name of address
+    // addr with its reverse lookup name, e.g., "1.2.0.192.in-addr.arpa."
+    pub fn reverse_query(id: u16, addr: IpAddr) -> Result<DnsBuilder> {
+        let name = match addr {
+            IpAddr::V4(addr) => {
+                let mut octets = addr.octets();
+                octets.reverse();
+                let labels: Vec<String> = octets.iter().map(u8::to_string).collect();
+                format!("{}.in-addr.arpa.", labels.join("."))
+            }
+            IpAddr::V6(addr) => {
+                let nibbles = addr
+                    .octets()
+                    .into_iter()
+                    .rev()
+                    .flat_map(|o| [o & 0xf, o >> 4]);
+                let labels: Vec<String> = nibbles.map(|n| format!("{:x}", n)).collect();
+                format!("{}.ip6.arpa.", labels.join("."))
+            }
+        };
+        DnsBuilder::query(id, &name, Type::Ptr)
+    }
+
+    // create a builder of a response to query with its identification,
+    // opcode, recursion desired bit and questions
+    pub fn response(query: &DnsPacket) -> Result<DnsBuilder> {
+        let mut builder = DnsBuilder::new(query.get_id());
+        builder.set_qr(1);
+        builder.set_opcode(query.get_opcode());
+        builder.set_rd(query.get_rd());
+        for question in (0..query.get_questions().into()).filter_map(|i| query.get_question(i)) {
+            builder.add_question(
+                &question.get_name(),
+                question.get_type(),
+                question.get_class(),
+            )?;
+        }
+        Ok(builder)
+    }
+
+    // set bits selected by mask in the flags of the header to value
+    fn set_flags(&mut self, mask: u16, value: u16) {
+        let flags = read_be_u16(&self.raw[2..4]);
+        let flags = (flags & !mask) | ((value << mask.trailing_zeros()) & mask);
+        write_be_u16(&mut self.raw[2..4], flags);
+    }
+
+    // set Query (0)/Response (1) bit in packet
+    pub fn set_qr(&mut self, qr: u8) {
+        self.set_flags(0b10000000_00000000, qr.into());
+    }
+
+    // set OPCODE bits (4 bits) in packet
+    pub fn set_opcode(&mut self, opcode: OpCode) {
+        self.set_flags(0b01111000_00000000, u8::from(opcode).into());
+    }
+
+    // set Authoritative Answer (AA) bit in packet
+    pub fn set_aa(&mut self, aa: u8) {
+        self.set_flags(0b00000100_00000000, aa.into());
+    }
+
+    // set TrunCation (TC) bit in packet
+    pub fn set_tc(&mut self, tc: u8) {
+        self.set_flags(0b00000010_00000000, tc.into());
+    }
+
+    // set Recursion Desired (RD) bit in packet
+    pub fn set_rd(&mut self, rd: u8) {
+        self.set_flags(0b00000001_00000000, rd.into());
+    }
+
+    // set response code (RCODE) bits (4 bits) in packet
+    pub fn set_rcode(&mut self, rcode: RCode) {
+        self.set_flags(0b00000000_00001111, u8::from(rcode).into());
+    }
+
+    // switch to the section with the counter at offset section and count a
+    // new record in it
+    fn count(&mut self, section: usize) -> Result<()> {
+        self.section = section;
+        let count = read_be_u16(&self.raw[section..section + 2]);
+        let count = count.checked_add(1).ok_or(DnsError::RecordLength)?;
+        write_be_u16(&mut self.raw[section..section + 2], count);
+        Ok(())
+    }
+
+    // append name to the packet, compressed if possible
+    fn write_name(&mut self, name: &str) -> Result<()> {
+        let labels: Vec<&str> = name.split('.').filter(|l| !l.is_empty()).collect();
+        if labels.iter().map(|l| l.len() + 1).sum::<usize>() + 1 > MAX_NAME_LENGTH {
+            return Err(DnsError::LabelLength);
+        }
+        for (i, label) in labels.iter().enumerate() {
+            // reference the rest of the name if it is already in the packet
+            let suffix = labels[i..].join(".").to_lowercase();
+            if let Some(offset) = self.names.get(&suffix) {
+                let pointer = 0xc000 | *offset as u16;
+                self.raw.extend_from_slice(&pointer.to_be_bytes());
+                return Ok(());
+            }
+            if label.len() > MAX_LABEL_LENGTH {
+                return Err(DnsError::LabelLength);
+            }
+            if self.raw.len() <= MAX_POINTER_OFFSET {
+                self.names.insert(suffix, self.raw.len());
+            }
+            self.raw.push(label.len() as u8);
+            self.raw.extend_from_slice(label.as_bytes());
+        }
+        self.raw.push(0);
+        Ok(())
+    }
+
+    // append the record data to the packet
+    fn write_data(&mut self, data: &Data) -> Result<()> {
+        match data {
+            Data::A(addr) => self.raw.extend_from_slice(&addr.octets()),
+            Data::Ns(domain) | Data::Cname(domain) | Data::Ptr(domain) => {
+                self.write_name(domain)?
+            }
+            Data::Soa(mname, rname, serial, refresh, retry, expire, minimum) => {
+                self.write_name(mname)?;
+                self.write_name(rname)?;
+                for value in [serial, refresh, retry, expire, minimum] {
+                    self.raw.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+            Data::Mx(preference, domain) => {
+                self.raw.extend_from_slice(&preference.to_be_bytes());
+                self.write_name(domain)?;
+            }
+            Data::Txt(texts) => {
+                for text in texts {
+                    let length =
+                        u8::try_from(text.len()).map_err(|_| DnsError::CharactersLength)?;
+                    self.raw.push(length);
+                    self.raw.extend_from_slice(text.as_bytes());
+                }
+            }
+            Data::Aaaa(addr) => self.raw.extend_from_slice(&addr.octets()),
+            Data::Srv(priority, weight, port, target) => {
+                for value in [priority, weight, port] {
+                    self.raw.extend_from_slice(&value.to_be_bytes());
+                }
+                // names in srv records must not be compressed
+                let names = std::mem::take(&mut self.names);
+                let result = self.write_name(target);
+                self.names = names;
+                result?;
+            }
+            Data::Nb(entries) => {
+                for (flags, addr) in entries {
+                    self.raw.extend_from_slice(&flags.0.to_be_bytes());
+                    self.raw.extend_from_slice(&addr.octets());
+                }
+            }
+            Data::Nbstat(..) => return Err(DnsError::UnsupportedData),
+            Data::Unknown(raw) | Data::Invalid(raw) => self.raw.extend_from_slice(raw),
+        }
+        Ok(())
+    }
+
+    // append a question or record with write to the section with the
+    // counter at offset section; partially written records are removed on
+    // errors
+    fn add(&mut self, section: usize, write: impl FnOnce(&mut Self) -> Result<()>) -> Result<()> {
+        if section < self.section {
+            return Err(DnsError::RecordOrder);
+        }
+        let length = self.raw.len();
+        let names = self.names.clone();
+        if let Err(e) = write(self) {
+            self.raw.truncate(length);
+            self.names = names;
+            return Err(e);
+        }
+        self.count(section)
+    }
+
+    // append a record with its name, type, class, ttl and data
+    fn write_record(
+        &mut self,
+        name: &str,
+        typ: Type,
+        class: Class,
+        ttl: u32,
+        data: &Data,
+    ) -> Result<()> {
+        self.write_name(name)?;
+        self.raw.extend_from_slice(&u16::from(typ).to_be_bytes());
+        self.raw.extend_from_slice(&u16::from(class).to_be_bytes());
+        self.raw.extend_from_slice(&ttl.to_be_bytes());
+        let offset = self.raw.len();
+        self.raw.extend_from_slice(&[0, 0]);
+        self.write_data(data)?;
+        let length =
+            u16::try_from(self.raw.len() - offset - 2).map_err(|_| DnsError::DataLength)?;
+        write_be_u16(&mut self.raw[offset..offset + 2], length);
+        Ok(())
+    }
+
+    // add a question for name with type typ and class
+    pub fn add_question(&mut self, name: &str, typ: Type, class: Class) -> Result<()> {
+        self.add(QUESTIONS_OFFSET, |builder| {
+            builder.write_name(name)?;
+            builder.raw.extend_from_slice(&u16::from(typ).to_be_bytes());
+            builder
+                .raw
+                .extend_from_slice(&u16::from(class).to_be_bytes());
+            Ok(())
+        })
+    }
+
+    // add an answer record for name with type typ, class, ttl and data
+    pub fn add_answer(
+        &mut self,
+        name: &str,
+        typ: Type,
+        class: Class,
+        ttl: u32,
+        data: &Data,
+    ) -> Result<()> {
+        self.add(ANSWERS_OFFSET, |builder| {
+            builder.write_record(name, typ, class, ttl, data)
+        })
+    }
+
+    // add an additional record for name with type typ, class, ttl and data
+    pub fn add_additional(
+        &mut self,
+        name: &str,
+        typ: Type,
+        class: Class,
+        ttl: u32,
+        data: &Data,
+    ) -> Result<()> {
+        self.add(ADDITIONALS_OFFSET, |builder| {
+            builder.write_record(name, typ, class, ttl, data)
+        })
+    }
+
+    // get the length of the packet
+    pub fn get_length(&self) -> usize {
+        self.raw.len()
+    }
+
+    // get the raw bytes of the packet
+    pub fn build(self) -> Vec<u8> {
+        self.raw
+    }
+}
@@ -0,0 +1,390 @@
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+
+use clap::{ArgAction, ArgGroup, Parser};
+
+use crate::filter::Filter;
+use crate::output::Format;
+
+// command line configuration of the dns tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Listen for dns packets and parse them")]
+#[command(group(ArgGroup::new("serve").args(["respond", "forward"])))]
+pub struct Config {
+    // network interface to listen on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Only handle packets received on the network interface NAME"
+    )]
+    pub interface: Option<String>,
+
+    // ports of dns packets
+    #[arg(
+        short,
+        long,
+        value_name = "PORT",
+        value_delimiter = ',',
+        num_args = 1..,
+        default_value = "53",
+        help = "Handle packets from or to PORT as dns packets; nbns, mdns and \
+        llmnr packets are identified by their well-known ports"
+    )]
+    pub port: Vec<u16>,
+
+    // transport protocols
+    #[arg(long, conflicts_with = "tcp", help = "Only listen for udp packets")]
+    pub udp: bool,
+    #[arg(
+        long,
+        help = "Only listen for tcp packets; tcp is only supported on ipv4"
+    )]
+    pub tcp: bool,
+
+    // capture ethernet frames instead of ip packets
+    #[arg(
+        long,
+        requires = "interface",
+        help = "Capture ethernet frames on the interface instead of ip \
+        packets, e.g., to monitor mirrored trunk ports; 802.1q and qinq vlan \
+        tags are stripped and the vlans of messages are shown; tcp is also \
+        supported on ipv6"
+    )]
+    pub datalink: bool,
+
+    // ip versions
+    #[arg(
+        short = '4',
+        long,
+        conflicts_with = "ipv6",
+        help = "Only listen for ipv4 packets"
+    )]
+    pub ipv4: bool,
+    #[arg(short = '6', long, help = "Only listen for ipv6 packets")]
+    pub ipv6: bool,
+
+    // only handle messages matching filter
+    #[arg(
+        short,
+        long,
+        value_name = "EXPR",
+        value_parser = Filter::parse,
+        help = "Only handle messages matching the filter EXPR, e.g., \"src net \
+        192.0.2.0/24 and not (name example.com or type AAAA)\"; primitives are \
+        [src|dst] net NETWORK, [src|dst] port PORT, name DOMAIN and type TYPE \
+        combined with and, or, not and parentheses; names and types match \
+        questions"
+    )]
+    pub filter: Option<Filter>,
+
+    // parser threads and queue sizes
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 2,
+        value_parser = clap::value_parser!(u16).range(1..),
+        help = "Parse, filter and format messages in N worker threads"
+    )]
+    pub workers: u16,
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 10000,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+        help = "Queue up to N captured messages for the workers; messages \
+        are dropped and counted if the queue is full"
+    )]
+    pub queue_size: usize,
+
+    // receive buffers of capture sockets
+    #[arg(
+        long,
+        value_name = "BYTES",
+        default_value_t = 65535,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(512..=65535),
+        help = "Read captured packets into a buffer of BYTES bytes; larger \
+        packets are truncated, counted and not handled"
+    )]
+    pub buffer_size: usize,
+    #[arg(
+        long,
+        value_name = "BYTES",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+        help = "Set the kernel receive buffer of capture sockets to BYTES \
+        bytes to avoid drops at high packet rates; the kernel limits the \
+        size, e.g., with net.core.rmem_max on linux; drops are counted"
+    )]
+    pub socket_buffer: Option<usize>,
+
+    // verbosity
+    #[arg(
+        short,
+        long,
+        action = ArgAction::Count,
+        conflicts_with = "quiet",
+        help = "Also print the raw bytes of messages"
+    )]
+    pub verbose: u8,
+    #[arg(
+        short,
+        long,
+        help = "Do not print individual messages, only transactions, alerts \
+        and summaries"
+    )]
+    pub quiet: bool,
+
+    // output format
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Format::Text,
+        help = "Print output as human readable text or as one json object per \
+        line"
+    )]
+    pub format: Format,
+
+    // read packets from capture file instead of listening on the network
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["interface", "doh"],
+        help = "Read packets from the pcap file FILE instead of listening on \
+        the network"
+    )]
+    pub read: Option<PathBuf>,
+
+    // write packets to pcapng file
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Write the packets of all handled messages, i.e., messages \
+        matching the filter, to the pcapng file FILE; for tcp, the segment \
+        completing a message is written"
+    )]
+    pub pcapng: Option<PathBuf>,
+    #[arg(
+        long,
+        requires = "pcapng",
+        help = "Only write packets of messages that triggered watchlist \
+        alerts to the pcapng file"
+    )]
+    pub pcapng_alerts: bool,
+
+    // parse raw dns messages instead of listening on the network
+    #[arg(
+        long,
+        value_name = "FILE",
+        num_args = 1..,
+        help = "Parse raw dns messages (application/dns-message), e.g., exported \
+        from dns over https captures, from files instead of listening on the \
+        network; use - to read from stdin"
+    )]
+    pub doh: Vec<PathBuf>,
+
+    // answer queries from a hosts file instead of listening on the network
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["interface", "read", "doh"],
+        help = "Answer a, aaaa and ptr queries received over udp with the \
+        addresses and names in the hosts-style file FILE instead of \
+        listening on the network"
+    )]
+    pub respond: Option<PathBuf>,
+
+    // forward queries to an upstream resolver
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        conflicts_with_all = ["interface", "read", "doh", "respond"],
+        help = "Forward queries received over udp to the upstream resolver at \
+        ADDRESS, e.g., 192.0.2.53:53, relay its responses and print both \
+        instead of listening on the network"
+    )]
+    pub forward: Option<SocketAddr>,
+
+    // request a zone transfer from a server
+    #[arg(
+        long,
+        value_name = "ZONE",
+        requires = "server",
+        conflicts_with_all = ["interface", "read", "doh", "respond", "forward"],
+        help = "Request a transfer of the entire ZONE (AXFR) from the server \
+        over tcp and print its records in zone file format instead of \
+        listening on the network"
+    )]
+    pub axfr: Option<String>,
+
+    // server of zone transfers
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        requires = "axfr",
+        help = "Request zone transfers from the server at ADDRESS, e.g., \
+        192.0.2.53:53"
+    )]
+    pub server: Option<SocketAddr>,
+
+    // advertise a dns-sd service over mdns
+    #[arg(
+        long,
+        value_name = "INSTANCE",
+        requires = "service",
+        requires = "service_port",
+        conflicts_with_all = ["read", "doh", "respond", "forward", "axfr"],
+        help = "Advertise the dns-sd service instance INSTANCE, e.g., \"My \
+        Printer\", over mdns on the network interface or the default \
+        interface: announce its ptr, srv, txt and address records, answer \
+        matching queries and send goodbye packets on exit instead of \
+        listening for dns packets"
+    )]
+    pub advertise: Option<String>,
+
+    // service type, port and txt entries of the advertised service
+    #[arg(
+        long,
+        value_name = "TYPE",
+        requires = "advertise",
+        help = "Advertise the service instance as service type TYPE, e.g., \
+        _http._tcp"
+    )]
+    pub service: Option<String>,
+    #[arg(
+        long,
+        value_name = "PORT",
+        requires = "advertise",
+        help = "Advertise the service instance on PORT"
+    )]
+    pub service_port: Option<u16>,
+    #[arg(
+        long,
+        value_name = "ENTRY",
+        requires = "advertise",
+        help = "Add the key/value pair ENTRY, e.g., path=/, to the txt \
+        record of the advertised service instance; can be repeated"
+    )]
+    pub txt: Vec<String>,
+
+    // host of the advertised service
+    #[arg(
+        long,
+        value_name = "NAME",
+        requires = "advertise",
+        help = "Advertise the service instance on the host NAME in the \
+        local. domain; defaults to the host name of the system"
+    )]
+    pub hostname: Option<String>,
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        value_delimiter = ',',
+        num_args = 1..,
+        requires = "advertise",
+        help = "Advertise the addresses ADDRESS of the host; defaults to \
+        the addresses of the network interface"
+    )]
+    pub address: Vec<IpAddr>,
+
+    // local address for answering or forwarding queries
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        requires = "serve",
+        help = "Receive queries on the local ADDRESS, e.g., 127.0.0.1:5353; \
+        defaults to 0.0.0.0:53"
+    )]
+    pub listen: Option<SocketAddr>,
+
+    // print statistics periodically
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Print a summary of message statistics every SECONDS seconds; \
+        the summary is always printed on exit"
+    )]
+    pub stats_interval: Option<u64>,
+
+    // number of entries in top talkers reports
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 10,
+        help = "Show the top N clients and queried names in statistics"
+    )]
+    pub top: usize,
+
+    // time window of top talkers reports
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 60,
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Count queries of the last SECONDS seconds for top clients \
+        and queried names"
+    )]
+    pub top_window: u64,
+
+    // write passive dns records to file
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Record answers in a passive dns store and write it in passive \
+        dns common output format (json lines) to FILE on exit and with each \
+        statistics summary; use - to write to stdout"
+    )]
+    pub pdns: Option<PathBuf>,
+
+    // alert on names and addresses in watchlist
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Alert on queries and answers matching a domain suffix or ip \
+        network (cidr notation) listed in FILE, one entry per line"
+    )]
+    pub watchlist: Option<PathBuf>,
+
+    // write messages and transactions to sqlite database
+    #[cfg(feature = "sqlite")]
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Write all parsed messages, their records and transactions \
+        into the sqlite database FILE"
+    )]
+    pub sqlite: Option<PathBuf>,
+
+    // serve metrics over http
+    #[cfg(feature = "metrics")]
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        help = "Serve message counters in prometheus format over http at \
+        http://ADDRESS/metrics, e.g., 127.0.0.1:9153"
+    )]
+    pub metrics: Option<SocketAddr>,
+}
+
+// default configuration with the same defaults as the command line, e.g.,
+// the dns port
+impl Default for Config {
+    fn default() -> Config {
+        Config::parse_from(["dns"])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::Protocol;
+
+    #[test]
+    fn default_matches_dns_port() {
+        let config = Config::default();
+        assert_eq!(config.port, [53]);
+        let protocol = Protocol::from_ports(40000, 53, &config.port);
+        assert_eq!(protocol, Some(Protocol::Dns));
+    }
+}
@@ -0,0 +1,227 @@
+use std::ffi::CString;
+use std::io;
+use std::mem;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+use crate::pcap::ETHERTYPE_VLAN;
+use crate::socket::*;
+use crate::statistics::Drops;
+
+// length of a vlan tag in ethernet frames
+const VLAN_TAG_LENGTH: usize = 4;
+
+// offset of the ether type or the first vlan tag in ethernet frames
+const ETHERTYPE_OFFSET: usize = 12;
+
+// get the bytes of value for socket options
+fn as_bytes<T>(value: &T) -> &[u8] {
+    // safety: value is a valid reference to a value of size T
+    unsafe { std::slice::from_raw_parts(value as *const T as *const u8, mem::size_of::<T>()) }
+}
+
+// packet socket capturing all ethernet frames received on a network
+// interface in promiscuous mode; the kernel removes vlan tags from frames,
+// so they are inserted again from the auxiliary data of each frame
+pub struct DatalinkSocket {
+    fd: OwnedFd,
+
+    // receive buffer with room for a vlan tag in front of the frame
+    buffer: Vec<u8>,
+
+    // counters of truncated frames and frames dropped by the kernel
+    drops: Drops,
+    checked: Instant,
+}
+
+impl DatalinkSocket {
+    // open a packet socket on the network interface name that reads frames
+    // of up to buffer_size bytes and waits up to timeout for frames; the
+    // kernel receive buffer is set to socket_buffer bytes if present
+    pub fn open(
+        name: &str,
+        buffer_size: usize,
+        socket_buffer: Option<usize>,
+        timeout: Duration,
+        drops: Drops,
+    ) -> io::Result<DatalinkSocket> {
+        let protocol = (libc::ETH_P_ALL as u16).to_be();
+        let name = CString::new(name)?;
+
+        // safety: if_nametoindex gets a valid c string, socket returns a
+        // new file descriptor owned by OwnedFd
+        let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        if index == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let fd = unsafe { libc::socket(libc::AF_PACKET, libc::SOCK_RAW, protocol.into()) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        // only capture frames of the interface
+        // safety: addr is a valid link layer address of the given length
+        let mut addr: libc::sockaddr_ll = unsafe { mem::zeroed() };
+        addr.sll_family = libc::AF_PACKET as u16;
+        addr.sll_protocol = protocol;
+        addr.sll_ifindex = index as libc::c_int;
+        let ret = unsafe {
+            libc::bind(
+                fd.as_raw_fd(),
+                &addr as *const _ as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t,
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // capture all frames on the interface, e.g., of a mirrored port,
+        // and get vlan tags as auxiliary data
+        let membership = libc::packet_mreq {
+            mr_ifindex: index as libc::c_int,
+            mr_type: libc::PACKET_MR_PROMISC as u16,
+            mr_alen: 0,
+            mr_address: [0; 8],
+        };
+        set_option(
+            fd.as_raw_fd(),
+            libc::SOL_PACKET,
+            libc::PACKET_ADD_MEMBERSHIP,
+            as_bytes(&membership),
+        )?;
+        set_option(
+            fd.as_raw_fd(),
+            libc::SOL_PACKET,
+            libc::PACKET_AUXDATA,
+            &1i32.to_ne_bytes(),
+        )?;
+        let timeout = libc::timeval {
+            tv_sec: timeout.as_secs() as libc::time_t,
+            tv_usec: timeout.subsec_micros() as libc::suseconds_t,
+        };
+        set_option(
+            fd.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_RCVTIMEO,
+            as_bytes(&timeout),
+        )?;
+        if let Some(size) = socket_buffer {
+            set_receive_buffer(fd.as_raw_fd(), size)?;
+        }
+
+        Ok(DatalinkSocket {
+            fd,
+            buffer: vec![0; VLAN_TAG_LENGTH + buffer_size],
+            drops,
+            checked: Instant::now(),
+        })
+    }
+
+    // receive the next frame with its vlan tag; returns none if no frame
+    // was received before the timeout or the frame was not handled, i.e.,
+    // it was sent on a loopback interface and will also be received, or it
+    // was truncated
+    pub fn next(&mut self) -> io::Result<Option<&[u8]>> {
+        // safety: addr, iov and control are valid buffers of the given
+        // lengths; control is aligned for cmsghdr
+        let mut addr: libc::sockaddr_ll = unsafe { mem::zeroed() };
+        let mut iov = libc::iovec {
+            iov_base: self.buffer[VLAN_TAG_LENGTH..].as_mut_ptr() as *mut libc::c_void,
+            iov_len: self.buffer.len() - VLAN_TAG_LENGTH,
+        };
+        let mut control = [0u64; 16];
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_name = &mut addr as *mut _ as *mut libc::c_void;
+        msg.msg_namelen = mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t;
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+        msg.msg_controllen = mem::size_of_val(&control) as _;
+        let len = unsafe { libc::recvmsg(self.fd.as_raw_fd(), &mut msg, 0) };
+        if len < 0 {
+            let e = io::Error::last_os_error();
+            return match e.kind() {
+                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => Ok(None),
+                _ => Err(e),
+            };
+        }
+        let len = len as usize;
+        if addr.sll_pkttype == libc::PACKET_OUTGOING && addr.sll_hatype == libc::ARPHRD_LOOPBACK {
+            return Ok(None);
+        }
+        if msg.msg_flags & libc::MSG_TRUNC != 0 {
+            self.drops.truncated.fetch_add(1, Ordering::Relaxed);
+            return Ok(None);
+        }
+
+        // find the vlan tag in the auxiliary data
+        let mut tag = None;
+        // safety: the control messages were written by recvmsg
+        let mut cmsg = unsafe { libc::CMSG_FIRSTHDR(&msg) };
+        while !cmsg.is_null() {
+            let header = unsafe { &*cmsg };
+            if header.cmsg_level == libc::SOL_PACKET && header.cmsg_type == libc::PACKET_AUXDATA {
+                let aux = unsafe {
+                    (libc::CMSG_DATA(cmsg) as *const libc::tpacket_auxdata).read_unaligned()
+                };
+                if aux.tp_status & libc::TP_STATUS_VLAN_VALID != 0 {
+                    let tpid = match aux.tp_status & libc::TP_STATUS_VLAN_TPID_VALID {
+                        0 => ETHERTYPE_VLAN,
+                        _ => aux.tp_vlan_tpid,
+                    };
+                    tag = Some((tpid, aux.tp_vlan_tci));
+                }
+            }
+            cmsg = unsafe { libc::CMSG_NXTHDR(&msg, cmsg) };
+        }
+
+        // insert the vlan tag after the mac addresses
+        let (tpid, tci) = match tag {
+            Some(tag) if len >= ETHERTYPE_OFFSET => tag,
+            _ => return Ok(Some(&self.buffer[VLAN_TAG_LENGTH..VLAN_TAG_LENGTH + len])),
+        };
+        self.buffer
+            .copy_within(VLAN_TAG_LENGTH..VLAN_TAG_LENGTH + ETHERTYPE_OFFSET, 0);
+        self.buffer[ETHERTYPE_OFFSET..ETHERTYPE_OFFSET + 2].copy_from_slice(&tpid.to_be_bytes());
+        self.buffer[ETHERTYPE_OFFSET + 2..ETHERTYPE_OFFSET + 4].copy_from_slice(&tci.to_be_bytes());
+        Ok(Some(&self.buffer[..VLAN_TAG_LENGTH + len]))
+    }
+
+    // add frames the kernel dropped since the last check to the drop
+    // counter, if they were not read in the last interval before now
+    pub fn check_drops(&mut self, now: Instant) {
+        if now.duration_since(self.checked) < DROPS_INTERVAL {
+            return;
+        }
+        self.checked = now;
+        self.update_drops();
+    }
+
+    // add frames the kernel dropped since the last check to the drop
+    // counter; reading the statistics resets them in the kernel
+    pub fn update_drops(&mut self) {
+        // safety: stats is a valid buffer of the given length
+        let mut stats = libc::tpacket_stats {
+            tp_packets: 0,
+            tp_drops: 0,
+        };
+        let mut len = mem::size_of::<libc::tpacket_stats>() as libc::socklen_t;
+        let ret = unsafe {
+            libc::getsockopt(
+                self.fd.as_raw_fd(),
+                libc::SOL_PACKET,
+                libc::PACKET_STATISTICS,
+                &mut stats as *mut _ as *mut libc::c_void,
+                &mut len,
+            )
+        };
+        if ret == 0 {
+            self.drops
+                .socket
+                .fetch_add(stats.tp_drops.into(), Ordering::Relaxed);
+        }
+    }
+}
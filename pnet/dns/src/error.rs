@@ -15,6 +15,8 @@ pub enum DnsError {
     LabelLength,
     LabelReference,
     LabelUtf8(str::Utf8Error),
+    RecordOrder,
+    UnsupportedData,
 }
 
 impl fmt::Display for DnsError {
@@ -28,6 +30,8 @@ impl fmt::Display for DnsError {
             DnsError::LabelLength => write!(f, "invalid length of label"),
             DnsError::LabelReference => write!(f, "invalid reference in label"),
             DnsError::LabelUtf8(_) => write!(f, "invalid utf8 in label"),
+            DnsError::RecordOrder => write!(f, "record added after records of later sections"),
+            DnsError::UnsupportedData => write!(f, "unsupported data in record"),
         }
     }
 }
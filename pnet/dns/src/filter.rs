@@ -0,0 +1,191 @@
+use std::net::SocketAddr;
+
+use crate::helpers::*;
+use crate::message::*;
+use crate::record::*;
+use crate::watchlist::Network;
+
+// direction of address and port filters
+#[derive(Clone, Copy, Debug)]
+pub enum Direction {
+    Src,
+    Dst,
+    Any,
+}
+
+impl Direction {
+    // check if the source or destination address selected by the direction
+    // matches check
+    fn check(&self, msg: &Message, check: impl Fn(&SocketAddr) -> bool) -> bool {
+        match self {
+            Direction::Src => check(&msg.src),
+            Direction::Dst => check(&msg.dst),
+            Direction::Any => check(&msg.src) || check(&msg.dst),
+        }
+    }
+}
+
+// filter expression on captured dns messages, e.g.,
+// "src net 192.0.2.0/24 and not (name example.com or type AAAA)"; names and
+// types are matched against the questions of a message
+#[derive(Clone, Debug)]
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Net(Direction, Network),
+    Port(Direction, u16),
+    Name(String),
+    Type(Type),
+}
+
+impl Filter {
+    // parse the filter expression s; primitives are "[src|dst] net NETWORK",
+    // "[src|dst] port PORT", "name DOMAIN" and "type TYPE", they can be
+    // combined with "and", "or", "not" and parentheses
+    pub fn parse(s: &str) -> Result<Filter, String> {
+        let s = s.replace('(', " ( ").replace(')', " ) ");
+        let mut parser = Parser {
+            tokens: s.split_whitespace().collect(),
+            pos: 0,
+        };
+        let filter = parser.parse_or()?;
+        match parser.next() {
+            Some(token) => Err(format!("unexpected \"{}\" in filter", token)),
+            None => Ok(filter),
+        }
+    }
+
+    // check if the message msg matches the filter
+    pub fn matches(&self, msg: &Message) -> bool {
+        match self {
+            Filter::And(a, b) => a.matches(msg) && b.matches(msg),
+            Filter::Or(a, b) => a.matches(msg) || b.matches(msg),
+            Filter::Not(a) => !a.matches(msg),
+            Filter::Net(direction, network) => {
+                direction.check(msg, |addr| network.contains(addr.ip()))
+            }
+            Filter::Port(direction, port) => direction.check(msg, |addr| addr.port() == *port),
+            Filter::Name(domain) => Filter::check_questions(msg, |question| {
+                is_in_domain(&normalize_name(&question.get_name()), domain)
+            }),
+            Filter::Type(typ) => {
+                Filter::check_questions(msg, |question| question.get_type() == *typ)
+            }
+        }
+    }
+
+    // check if any question in the dns packet of msg matches check;
+    // malformed messages never match
+    fn check_questions(msg: &Message, check: impl Fn(&DnsQuestion) -> bool) -> bool {
+        let packet = match msg.parse() {
+            Ok(packet) => packet,
+            Err(_) => return false,
+        };
+        (0..packet.get_questions().into())
+            .filter_map(|i| packet.get_question(i))
+            .any(check)
+    }
+}
+
+// recursive descent parser of filter expressions
+struct Parser<'a> {
+    tokens: Vec<&'a str>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    // get the next token
+    fn next(&mut self) -> Option<&'a str> {
+        let token = self.tokens.get(self.pos).copied();
+        self.pos += 1;
+        token
+    }
+
+    // get the next token if it is token
+    fn accept(&mut self, token: &str) -> bool {
+        if self.tokens.get(self.pos) == Some(&token) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    // get the next token as the value of the primitive name
+    fn value(&mut self, name: &str) -> Result<&'a str, String> {
+        self.next()
+            .ok_or_else(|| format!("missing value of \"{}\" in filter", name))
+    }
+
+    // parse: and ["or" and]...
+    fn parse_or(&mut self) -> Result<Filter, String> {
+        let mut filter = self.parse_and()?;
+        while self.accept("or") {
+            filter = Filter::Or(Box::new(filter), Box::new(self.parse_and()?));
+        }
+        Ok(filter)
+    }
+
+    // parse: not ["and" not]...
+    fn parse_and(&mut self) -> Result<Filter, String> {
+        let mut filter = self.parse_not()?;
+        while self.accept("and") {
+            filter = Filter::And(Box::new(filter), Box::new(self.parse_not()?));
+        }
+        Ok(filter)
+    }
+
+    // parse: "not" not | "(" or ")" | primitive
+    fn parse_not(&mut self) -> Result<Filter, String> {
+        if self.accept("not") {
+            return Ok(Filter::Not(Box::new(self.parse_not()?)));
+        }
+        if self.accept("(") {
+            let filter = self.parse_or()?;
+            if !self.accept(")") {
+                return Err("missing \")\" in filter".to_string());
+            }
+            return Ok(filter);
+        }
+        self.parse_primitive()
+    }
+
+    // parse: ["src" | "dst"] ("net" NETWORK | "port" PORT) | "name" DOMAIN |
+    // "type" TYPE
+    fn parse_primitive(&mut self) -> Result<Filter, String> {
+        let direction = match () {
+            _ if self.accept("src") => Direction::Src,
+            _ if self.accept("dst") => Direction::Dst,
+            _ => Direction::Any,
+        };
+        let primitive = match self.next() {
+            Some(primitive) => primitive,
+            None => return Err("unexpected end of filter".to_string()),
+        };
+        match (primitive, direction) {
+            ("net", _) => {
+                let value = self.value(primitive)?;
+                match Network::parse(value) {
+                    Some(network) => Ok(Filter::Net(direction, network)),
+                    None => Err(format!("invalid network \"{}\" in filter", value)),
+                }
+            }
+            ("port", _) => {
+                let value = self.value(primitive)?;
+                match value.parse() {
+                    Ok(port) => Ok(Filter::Port(direction, port)),
+                    Err(_) => Err(format!("invalid port \"{}\" in filter", value)),
+                }
+            }
+            ("name", Direction::Any) => Ok(Filter::Name(normalize_name(self.value(primitive)?))),
+            ("type", Direction::Any) => {
+                let value = self.value(primitive)?;
+                match Type::from_mnemonic(value) {
+                    Some(typ) => Ok(Filter::Type(typ)),
+                    None => Err(format!("invalid type \"{}\" in filter", value)),
+                }
+            }
+            _ => Err(format!("unexpected \"{}\" in filter", primitive)),
+        }
+    }
+}
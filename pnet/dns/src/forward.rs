@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use packet_utils::bytes::*;
+
+use crate::builder::*;
+use crate::output::*;
+use crate::packet::*;
+
+// maximum number of pending queries, one for each identification
+const MAX_PENDING: usize = u16::MAX as usize + 1;
+
+// time after which queries without a response from the upstream resolver
+// are answered with a server failure
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(5);
+
+// interval in which the upstream socket checks for timed out queries
+const EXPIRE_INTERVAL: Duration = Duration::from_millis(100);
+
+// query forwarded to the upstream resolver that waits for its response
+struct Pending {
+    client: SocketAddr,
+
+    // identification of the query sent by the client
+    id: u16,
+
+    sent: Instant,
+    query: Vec<u8>,
+}
+
+// forwarded queries keyed by the identification used towards the upstream
+// resolver; the identification of the client is replaced, so queries of
+// different clients with the same identification do not collide
+#[derive(Default)]
+struct PendingQueries {
+    queries: HashMap<u16, Pending>,
+    next_id: u16,
+}
+
+impl PendingQueries {
+    // add the pending query and get its new identification; returns none if
+    // all identifications are in use
+    fn add(&mut self, pending: Pending) -> Option<u16> {
+        if self.queries.len() >= MAX_PENDING {
+            return None;
+        }
+        while self.queries.contains_key(&self.next_id) {
+            self.next_id = self.next_id.wrapping_add(1);
+        }
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.queries.insert(id, pending);
+        Some(id)
+    }
+
+    // remove and get the queries that timed out at time now
+    fn expire(&mut self, now: Instant) -> Vec<Pending> {
+        let expired: Vec<u16> = self
+            .queries
+            .iter()
+            .filter(|(_, p)| now.duration_since(p.sent) >= FORWARD_TIMEOUT)
+            .map(|(id, _)| *id)
+            .collect();
+        expired
+            .iter()
+            .filter_map(|id| self.queries.remove(id))
+            .collect()
+    }
+}
+
+// get the text of the dns packet in raw for output
+fn format_packet(raw: &[u8]) -> String {
+    match DnsPacket::parse(raw) {
+        Ok(packet) => format!("{}", packet),
+        Err(e) => format!("malformed dns packet: {}", e),
+    }
+}
+
+// replace the identification in the raw dns packet with id
+fn set_id(raw: &mut [u8], id: u16) {
+    write_be_u16(&mut raw[0..2], id);
+}
+
+// forwarder of dns queries received on a local socket to an upstream
+// resolver, that relays the responses back to the clients and prints both
+// legs of each query to output
+pub struct Forwarder {
+    local: UdpSocket,
+    upstream: UdpSocket,
+    upstream_addr: SocketAddr,
+    pending: Mutex<PendingQueries>,
+    output: Output,
+}
+
+impl Forwarder {
+    // create a forwarder that receives queries on the local address listen
+    // and forwards them to the upstream resolver address
+    pub fn new(listen: SocketAddr, upstream: SocketAddr, output: Output) -> io::Result<Forwarder> {
+        let unspecified = match upstream {
+            SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+        let local = UdpSocket::bind(listen)?;
+        let socket = UdpSocket::bind(SocketAddr::new(unspecified, 0))?;
+        socket.connect(upstream)?;
+        socket.set_read_timeout(Some(EXPIRE_INTERVAL))?;
+        Ok(Forwarder {
+            local,
+            upstream: socket,
+            upstream_addr: upstream,
+            pending: Mutex::new(PendingQueries::default()),
+            output,
+        })
+    }
+
+    // forward queries from clients to the upstream resolver
+    fn forward_queries(&self) -> io::Result<()> {
+        let mut buf = [0; 65535];
+        loop {
+            let (len, client) = self.local.recv_from(&mut buf)?;
+            let now = SystemTime::now();
+            let query = &mut buf[..len];
+            self.output.event(
+                now,
+                "dns query",
+                Some(client.to_string()),
+                &format_packet(query),
+            );
+            if len < DNS_HEADER_LENGTH {
+                continue;
+            }
+            let pending = Pending {
+                client,
+                id: read_be_u16(&query[0..2]),
+                sent: Instant::now(),
+                query: query.to_vec(),
+            };
+            let id = match self.pending.lock().unwrap().add(pending) {
+                Some(id) => id,
+                None => {
+                    self.output.event(
+                        now,
+                        "dns query",
+                        Some(client.to_string()),
+                        "could not forward query: too many pending queries",
+                    );
+                    continue;
+                }
+            };
+            set_id(query, id);
+            if let Err(e) = self.upstream.send(query) {
+                self.pending.lock().unwrap().queries.remove(&id);
+                let text = format!("could not forward query: {}", e);
+                self.output
+                    .event(now, "dns query", Some(client.to_string()), &text);
+            }
+        }
+    }
+
+    // answer queries that timed out with a server failure
+    fn expire_queries(&self) {
+        let expired = self.pending.lock().unwrap().expire(Instant::now());
+        for pending in expired {
+            let text = format!(
+                "{{client: {}, timeout: {:.3}s}}",
+                pending.client,
+                FORWARD_TIMEOUT.as_secs_f64()
+            );
+            let upstream = Some(self.upstream_addr.to_string());
+            self.output
+                .event(SystemTime::now(), "dns timeout", upstream, &text);
+            let query = match DnsPacket::parse(&pending.query) {
+                Ok(query) => query,
+                Err(_) => continue,
+            };
+            if let Ok(mut response) = DnsBuilder::response(&query) {
+                response.set_rcode(RCode::ServerFailure);
+                self.send_response(&response.build(), pending.client);
+            }
+        }
+    }
+
+    // send the response to the client
+    fn send_response(&self, response: &[u8], client: SocketAddr) {
+        if let Err(e) = self.local.send_to(response, client) {
+            let text = format!("could not relay response: {}", e);
+            self.output.event(
+                SystemTime::now(),
+                "dns response",
+                Some(client.to_string()),
+                &text,
+            );
+        }
+    }
+
+    // relay responses from the upstream resolver to the clients
+    fn relay_responses(&self) {
+        let mut buf = [0; 65535];
+        loop {
+            self.expire_queries();
+            let len = match self.upstream.recv(&mut buf) {
+                Ok(len) => len,
+                Err(e) => {
+                    if !matches!(
+                        e.kind(),
+                        io::ErrorKind::WouldBlock
+                            | io::ErrorKind::TimedOut
+                            | io::ErrorKind::Interrupted
+                    ) {
+                        let text = format!("could not receive response: {}", e);
+                        let upstream = Some(self.upstream_addr.to_string());
+                        self.output
+                            .event(SystemTime::now(), "dns response", upstream, &text);
+                    }
+                    continue;
+                }
+            };
+            let now = SystemTime::now();
+            let response = &mut buf[..len];
+            if len < DNS_HEADER_LENGTH {
+                continue;
+            }
+            let id = read_be_u16(&response[0..2]);
+            let pending = match self.pending.lock().unwrap().queries.remove(&id) {
+                Some(pending) => pending,
+                None => continue,
+            };
+            set_id(response, pending.id);
+            self.send_response(response, pending.client);
+            let text = format!(
+                "{{client: {}, latency: {:.3}ms, message: {}}}",
+                pending.client,
+                pending.sent.elapsed().as_secs_f64() * 1000.0,
+                format_packet(response)
+            );
+            let upstream = Some(self.upstream_addr.to_string());
+            self.output.event(now, "dns response", upstream, &text);
+        }
+    }
+
+    // forward queries and relay responses until receiving queries fails
+    pub fn run(self) -> io::Result<()> {
+        let forwarder = Arc::new(self);
+        let relay = forwarder.clone();
+        std::thread::spawn(move || relay.relay_responses());
+        forwarder.forward_queries()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(id: u16) -> Pending {
+        Pending {
+            client: "127.0.0.1:53".parse().unwrap(),
+            id,
+            sent: Instant::now(),
+            query: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn add_replaces_colliding_ids() {
+        let mut queries = PendingQueries::default();
+        assert_eq!(queries.add(pending(7)), Some(0));
+        assert_eq!(queries.add(pending(7)), Some(1));
+        queries.queries.remove(&0);
+        queries.next_id = 0;
+        assert_eq!(queries.add(pending(7)), Some(0));
+        assert_eq!(queries.add(pending(7)), Some(2));
+    }
+
+    #[test]
+    fn add_fails_when_all_ids_are_pending() {
+        let mut queries = PendingQueries::default();
+        for id in 0..=u16::MAX {
+            assert_eq!(queries.add(pending(0)), Some(id));
+        }
+        assert_eq!(queries.add(pending(0)), None);
+        queries.queries.remove(&1234);
+        assert_eq!(queries.add(pending(0)), Some(1234));
+    }
+
+    #[test]
+    fn expire_removes_timed_out_queries() {
+        let mut queries = PendingQueries::default();
+        queries.add(pending(1));
+        let now = Instant::now();
+        assert!(queries.expire(now).is_empty());
+        let expired = queries.expire(now + FORWARD_TIMEOUT);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id, 1);
+        assert!(queries.queries.is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::{BTreeMap, HashMap};
+use std::net::Ipv4Addr;
+use std::time::{Duration, SystemTime};
+
+use pnet::packet::ipv4::{self, Ipv4Flags, Ipv4Packet, MutableIpv4Packet};
+use pnet::packet::Packet;
+
+// maximum number of fragments kept per datagram
+const MAX_FRAGMENTS: usize = 64;
+
+// maximum number of incomplete datagrams
+const MAX_DATAGRAMS: usize = 1024;
+
+// time after which incomplete datagrams are removed
+const FRAGMENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+// maximum length of an ipv4 packet
+const MAX_PACKET_LENGTH: usize = 65535;
+
+// check if the ipv4 packet ip is a fragment of a larger datagram
+pub fn is_fragment(ip: &Ipv4Packet) -> bool {
+    ip.get_flags() & Ipv4Flags::MoreFragments != 0 || ip.get_fragment_offset() != 0
+}
+
+// ipv4 datagram identified by source and destination address,
+// identification and protocol
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct DatagramKey {
+    src: Ipv4Addr,
+    dst: Ipv4Addr,
+    id: u16,
+    protocol: u8,
+}
+
+// received fragments of a single ipv4 datagram
+struct Datagram {
+    // ip header of the first fragment
+    header: Option<Vec<u8>>,
+
+    // payloads of fragments, keyed by their offset in the datagram
+    fragments: BTreeMap<usize, Vec<u8>>,
+
+    // length of the datagram's payload, known from the last fragment
+    length: Option<usize>,
+
+    // capture time of the first received fragment
+    first_seen: SystemTime,
+}
+
+impl Datagram {
+    fn new(time: SystemTime) -> Datagram {
+        Datagram {
+            header: None,
+            fragments: BTreeMap::new(),
+            length: None,
+            first_seen: time,
+        }
+    }
+
+    // add the fragment ip to the datagram
+    fn add(&mut self, ip: &Ipv4Packet) {
+        let offset = usize::from(ip.get_fragment_offset()) * 8;
+        let payload = ip.payload();
+        if offset == 0 {
+            let header_length = usize::from(ip.get_header_length()) * 4;
+            match ip.packet().get(..header_length) {
+                Some(header) if header_length >= 20 => self.header = Some(header.to_vec()),
+                _ => return,
+            }
+        }
+        if ip.get_flags() & Ipv4Flags::MoreFragments == 0 {
+            self.length = Some(offset + payload.len());
+        }
+        if self.fragments.len() < MAX_FRAGMENTS {
+            self.fragments.insert(offset, payload.to_vec());
+        }
+    }
+
+    // get the reassembled ip packet if all fragments were received
+    fn reassemble(&self) -> Option<Vec<u8>> {
+        let header = self.header.as_ref()?;
+        let length = self.length?;
+        if header.len() + length > MAX_PACKET_LENGTH {
+            return None;
+        }
+
+        // check that the fragments cover the whole payload; overlapping
+        // fragments overwrite the data of previous fragments
+        let mut covered = 0;
+        for (offset, data) in &self.fragments {
+            if *offset > covered {
+                return None;
+            }
+            covered = covered.max(offset + data.len());
+        }
+        if covered < length {
+            return None;
+        }
+        let mut packet = header.clone();
+        packet.resize(header.len() + length, 0);
+        for (offset, data) in &self.fragments {
+            let start = header.len() + offset;
+            let end = packet.len().min(start + data.len());
+            if start >= end {
+                continue;
+            }
+            packet[start..end].copy_from_slice(&data[..end - start]);
+        }
+
+        // update header of the reassembled packet
+        let mut ip = MutableIpv4Packet::new(&mut packet)?;
+        ip.set_total_length((header.len() + length) as u16);
+        ip.set_flags(ip.get_flags() & !Ipv4Flags::MoreFragments);
+        ip.set_fragment_offset(0);
+        let checksum = ipv4::checksum(&ip.to_immutable());
+        ip.set_checksum(checksum);
+        Some(packet)
+    }
+}
+
+// cache of ipv4 fragments, that reassembles datagrams from their fragments
+pub struct Fragments {
+    datagrams: HashMap<DatagramKey, Datagram>,
+
+    // time incomplete datagrams were last removed
+    last_expire: SystemTime,
+}
+
+impl Default for Fragments {
+    fn default() -> Fragments {
+        Fragments::new()
+    }
+}
+
+impl Fragments {
+    pub fn new() -> Fragments {
+        Fragments {
+            datagrams: HashMap::new(),
+            last_expire: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    // add the fragment ip captured at time and return the reassembled ip
+    // packet if it completed its datagram
+    pub fn add(&mut self, time: SystemTime, ip: &Ipv4Packet) -> Option<Vec<u8>> {
+        self.expire(time);
+        let key = DatagramKey {
+            src: ip.get_source(),
+            dst: ip.get_destination(),
+            id: ip.get_identification(),
+            protocol: ip.get_next_level_protocol().0,
+        };
+        if !self.datagrams.contains_key(&key) && self.datagrams.len() >= MAX_DATAGRAMS {
+            return None;
+        }
+        let datagram = self
+            .datagrams
+            .entry(key)
+            .or_insert_with(|| Datagram::new(time));
+        datagram.add(ip);
+        let packet = datagram.reassemble()?;
+        self.datagrams.remove(&key);
+        Some(packet)
+    }
+
+    // remove incomplete datagrams that are too old at time now
+    fn expire(&mut self, now: SystemTime) {
+        let elapsed = |time| now.duration_since(time).unwrap_or_default();
+        if elapsed(self.last_expire) < Duration::from_secs(1) {
+            return;
+        }
+        self.last_expire = now;
+        self.datagrams
+            .retain(|_, d| elapsed(d.first_seen) < FRAGMENT_TIMEOUT);
+    }
+}
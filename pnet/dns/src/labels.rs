@@ -1,7 +1,8 @@
 use std::str;
 
+use packet_utils::bytes::*;
+
 use crate::error::*;
-use crate::helpers::*;
 
 // parse labels inside raw packet data starting at offset,
 // return list of label indexes and the index of the next message field
@@ -64,11 +65,11 @@ pub fn parse_labels(raw: &[u8], offset: usize) -> Result<(Vec<usize>, usize)> {
     }
 
     // parsing successful
-    return Ok((label_indexes, next_index));
+    Ok((label_indexes, next_index))
 }
 
 // get name from labels in raw packet
-pub fn get_name_from_labels(raw: &[u8], label_indexes: &Vec<usize>) -> Result<String> {
+pub fn get_name_from_labels(raw: &[u8], label_indexes: &[usize]) -> Result<String> {
     let mut name = String::new();
     for i in label_indexes {
         // get length of current label from first byte
@@ -76,11 +77,11 @@ pub fn get_name_from_labels(raw: &[u8], label_indexes: &Vec<usize>) -> Result<St
 
         // read domain name part from current label
         let j = i + 1;
-        let part = str::from_utf8(&raw[j..j + length]).map_err(|e| DnsError::LabelUtf8(e))?;
+        let part = str::from_utf8(&raw[j..j + length]).map_err(DnsError::LabelUtf8)?;
         name.push_str(part);
         name += ".";
     }
-    return Ok(name);
+    Ok(name)
 }
 
 // get the name directly from labels in raw packet starting at offset
@@ -1,118 +1,1436 @@
+mod advertise;
+mod axfr;
+mod builder;
 mod characters;
+mod config;
+#[cfg(target_os = "linux")]
+mod datalink;
 mod error;
+mod filter;
+mod forward;
+mod fragment;
 mod helpers;
 mod labels;
+#[cfg(feature = "async")]
+pub mod listener;
+mod mdns;
+mod message;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod netbios;
+mod output;
 mod packet;
+mod pcap;
+mod pcapng;
+mod pdns;
+mod protocol;
 mod record;
+mod reorder;
+mod respond;
+mod socket;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod statistics;
+mod stream;
+mod summary;
+mod talkers;
+mod tls;
+mod transaction;
+mod transfer;
+mod watchlist;
 
+use std::collections::hash_map::{Entry, HashMap};
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{self, AtomicBool, AtomicU64};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use packet_utils::interface::find_interface;
 use pnet::packet::ip::IpNextHeaderProtocols;
-use pnet::packet::tcp::TcpFlags;
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::tcp::{TcpFlags, TcpPacket};
+use pnet::packet::udp::UdpPacket;
 use pnet::packet::Packet;
-use pnet::transport::TransportChannelType::Layer4;
-use pnet::transport::TransportProtocol::Ipv4;
-use pnet::transport::{tcp_packet_iter, transport_channel, udp_packet_iter};
+use pnet::transport::TransportChannelType::{self, Layer3, Layer4};
+use pnet::transport::TransportProtocol::Ipv6;
+use pnet::transport::{ipv4_packet_iter, transport_channel, TransportReceiver};
 
-use helpers::*;
-use packet::*;
+use advertise::*;
+use axfr::*;
+pub use builder::DnsBuilder;
+pub use config::Config;
+#[cfg(target_os = "linux")]
+use datalink::*;
+use error::*;
+use forward::*;
+use fragment::*;
+#[cfg(feature = "async")]
+pub use listener::DnsListener;
+use mdns::*;
+use message::*;
+use output::*;
+pub use packet::DnsPacket;
+use pcap::*;
+use pcapng::*;
+use pdns::*;
+use protocol::*;
+pub use record::{Data, Type};
+use reorder::*;
+use respond::*;
+use socket::*;
+#[cfg(feature = "sqlite")]
+use sqlite::*;
+use statistics::*;
+pub use stream::{Stream, StreamKey, Streams};
+use summary::*;
+use talkers::*;
+use tls::*;
+use transaction::*;
+use transfer::*;
+use watchlist::*;
 
-const DNS_PORT: u16 = 53;
+const DOT_PORT: u16 = 853;
 
-// run udp listener and handle dns packets
-fn listen_udp() {
-    // create an udp channel
-    let protocol = Layer4(Ipv4(IpNextHeaderProtocols::Udp));
-    let (_, mut rx) = match transport_channel(4096, protocol) {
+// time after which idle dns over tls connections are removed, e.g., if
+// their teardown was not captured
+const DOT_TIMEOUT: Duration = Duration::from_secs(120);
+
+// interval of capture time in which idle dns over tls connections are
+// removed
+const DOT_EXPIRE_INTERVAL: Duration = Duration::from_secs(1);
+
+// maximum number of tracked dns over tls connections, so scans of the dns
+// over tls port cannot exhaust the memory
+const MAX_DOT_CONNECTIONS: usize = 4096;
+
+// interval in which listeners check if they should stop
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// time events of workers are held back while earlier events are missing,
+// e.g., because they were dropped
+const REORDER_DELAY: Duration = Duration::from_millis(100);
+
+// default local address for answering or forwarding queries
+const DEFAULT_LISTEN: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 53);
+
+// dns message analyzed by the workers: its parsed packet, output text and
+// watchlist matches
+struct Analysis {
+    summary: Summary,
+    text: Option<String>,
+    matches: Vec<String>,
+}
+
+// event sent from the listeners through the workers to the processing stage
+enum Event {
+    // dns message, the tcp stream it was received on and its analysis or
+    // parse error, added by the workers
+    Message(Message, Option<StreamKey>, Option<Result<Box<Analysis>>>),
+
+    // tcp stream was closed
+    StreamClosed(StreamKey),
+
+    // dns over tls connection was closed, with its statistics
+    DotConnection(String),
+
+    // dns message did not match the filter; sent instead of the message,
+    // so the processing stage does not wait for it
+    Skipped,
+}
+
+// event with its capture time and capture sequence number
+type Captured = (SystemTime, u64, Event);
+
+// sender of events to the next stage of the pipeline; events are numbered
+// in the order they were captured; if the queue of the next stage is full,
+// events are dropped and counted unless sending blocks, e.g., when reading
+// files
+#[derive(Clone)]
+struct Events {
+    sender: SyncSender<Captured>,
+    seq: Arc<AtomicU64>,
+    drops: Drops,
+    blocking: bool,
+}
+
+impl Events {
+    // send event captured at time
+    fn send(&self, time: SystemTime, event: Event) {
+        let seq = self.seq.fetch_add(1, atomic::Ordering::Relaxed);
+        if self.blocking {
+            let _ = self.sender.send((time, seq, event));
+            return;
+        }
+        if let Err(TrySendError::Full(_)) = self.sender.try_send((time, seq, event)) {
+            self.drops.queue.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+    }
+}
+
+// get the output of messages and events for the configuration
+fn get_output(config: &Config) -> Output {
+    let verbosity = match config.quiet {
+        true => -1,
+        false => i8::try_from(config.verbose).unwrap_or(i8::MAX),
+    };
+    Output::new(config.format, verbosity)
+}
+
+// state shared by the processing stage, statistics and metrics
+struct State {
+    output: Output,
+    mdns_queries: MdnsQueries,
+    transactions: Transactions,
+    statistics: Statistics,
+    talkers: TopTalkers,
+
+    // passive dns store and the file it is written to
+    passive_dns: PassiveDns,
+    pdns_file: Option<PathBuf>,
+
+    // pcapng file packets are written to, optionally only packets of
+    // messages that triggered alerts
+    pcapng: Option<PcapngWriter>,
+    pcapng_alerts: bool,
+
+    // database all messages and transactions are written to
+    #[cfg(feature = "sqlite")]
+    sqlite: Option<SqliteSink>,
+}
+
+impl State {
+    fn new(config: &Config) -> State {
+        #[cfg(feature = "sqlite")]
+        let sqlite = config
+            .sqlite
+            .as_ref()
+            .map(|path| match SqliteSink::open(path) {
+                Ok(sqlite) => sqlite,
+                Err(e) => panic!(
+                    "An error occurred when opening the database {}: {}",
+                    path.display(),
+                    e
+                ),
+            });
+        let pcapng = config.pcapng.as_ref().map(|path| {
+            let interface = match (&config.read, &config.interface) {
+                (Some(file), _) => file.display().to_string(),
+                (None, Some(interface)) => interface.clone(),
+                (None, None) => "any".to_string(),
+            };
+            match PcapngWriter::create(path, &interface) {
+                Ok(pcapng) => pcapng,
+                Err(e) => panic!(
+                    "An error occurred when creating the pcapng file {}: {}",
+                    path.display(),
+                    e
+                ),
+            }
+        });
+        State {
+            output: get_output(config),
+            mdns_queries: MdnsQueries::new(),
+            transactions: Transactions::new(),
+            statistics: Statistics::new(),
+            talkers: TopTalkers::new(config.top, Duration::from_secs(config.top_window)),
+            passive_dns: PassiveDns::new(),
+            pdns_file: config.pdns.clone(),
+            pcapng,
+            pcapng_alerts: config.pcapng_alerts,
+            #[cfg(feature = "sqlite")]
+            sqlite,
+        }
+    }
+
+    // write the packet of message msg to the pcapng file if configured
+    fn write_packet(&mut self, msg: &Message) {
+        if let Some(pcapng) = &mut self.pcapng {
+            if let Err(e) = pcapng.write(msg) {
+                println!("could not write packet to pcapng file: {}", e);
+            }
+        }
+    }
+}
+
+// handle a dns message analyzed by the workers: print it with its
+// prepared output text and alerts, update statistics and pair queries and
+// responses
+fn handle_message(msg: &Message, analysis: &Result<Box<Analysis>>, state: &Mutex<State>) {
+    let mut state = state.lock().unwrap();
+    let analysis = match analysis {
+        Ok(analysis) => analysis,
+        Err(e) => {
+            state.output.malformed(msg, e);
+            state.statistics.add_malformed(msg);
+            if !state.pcapng_alerts {
+                state.write_packet(msg);
+            }
+            return;
+        }
+    };
+    let dns = &analysis.summary;
+
+    // mark mdns responses that do not answer recent queries
+    let unsolicited =
+        msg.protocol == Protocol::Mdns && state.mdns_queries.is_unsolicited(msg.time, dns);
+    if let Some(text) = &analysis.text {
+        state.output.message(msg, text, unsolicited);
+    }
+
+    // alert on names and addresses in the watchlist
+    let matches = &analysis.matches;
+    if !matches.is_empty() {
+        state.output.alert(msg, matches);
+    }
+    if !state.pcapng_alerts || !matches.is_empty() {
+        state.write_packet(msg);
+    }
+
+    state.statistics.add(msg, dns);
+    state.talkers.add(msg, dns);
+    if state.pdns_file.is_some() {
+        state.passive_dns.add(msg, dns);
+    }
+    #[cfg(feature = "sqlite")]
+    if let Some(sqlite) = &mut state.sqlite {
+        if let Err(e) = sqlite.add_message(msg, dns) {
+            println!("could not write message to database: {}", e);
+        }
+    }
+
+    // print transactions finished or retransmitted by this message
+    for transaction in state.transactions.add(msg, dns) {
+        #[cfg(feature = "sqlite")]
+        if let Some(sqlite) = &mut state.sqlite {
+            if let Err(e) = sqlite.add_transaction(msg.time, &transaction) {
+                println!("could not write transaction to database: {}", e);
+            }
+        }
+        state.output.transaction(msg.time, &transaction);
+        if let Outcome::Retransmitted(_) = transaction.outcome {
+            let server = transaction.key.server;
+            if let Some(counters) = state.transactions.get_server(&server) {
+                let counters = counters.to_string();
+                state
+                    .output
+                    .event(msg.time, "dns server", Some(server.to_string()), &counters);
+            }
+        }
+    }
+}
+
+// capture time, vlan and packet of a captured packet
+struct Capture {
+    time: SystemTime,
+    vlan: Option<u16>,
+    frame: Option<Arc<Frame>>,
+}
+
+impl Capture {
+    // get a packet captured now without vlan tags in frame
+    fn now(frame: Option<Arc<Frame>>) -> Capture {
+        Capture {
+            time: SystemTime::now(),
+            vlan: None,
+            frame,
+        }
+    }
+}
+
+// get the captured packet data with linktype as frame if packets are
+// written to a pcapng file
+fn get_frame(config: &Config, linktype: u32, data: &[u8]) -> Option<Arc<Frame>> {
+    config.pcapng.as_ref()?;
+    Some(Arc::new(Frame {
+        linktype,
+        data: data.to_vec(),
+    }))
+}
+
+// handle an udp packet with payload sent from src to dst and captured as
+// described by capture if it contains a dns, nbns, mdns or llmnr message;
+// dns messages are identified by the configured ports
+fn handle_udp(
+    capture: Capture,
+    src: SocketAddr,
+    dst: SocketAddr,
+    payload: &[u8],
+    config: &Config,
+    events: &Events,
+) {
+    let protocol = match Protocol::from_ports(src.port(), dst.port(), &config.port) {
+        Some(protocol) => protocol,
+        None => return,
+    };
+    let msg = Message {
+        time: capture.time,
+        transport: Transport::Udp,
+        protocol,
+        src,
+        dst,
+        data: payload.to_vec(),
+        vlan: capture.vlan,
+        frame: capture.frame,
+    };
+    events.send(msg.time, Event::Message(msg, None, None));
+}
+
+// create a transport channel of type protocol, bound to the configured
+// interface and with the configured buffer sizes, and return its receiver
+// and the counter of its drops
+fn create_channel(
+    protocol: TransportChannelType,
+    config: &Config,
+    drops: &Drops,
+) -> (TransportReceiver, SocketDrops) {
+    let (_, rx) = match transport_channel(config.buffer_size, protocol) {
         Ok((tx, rx)) => (tx, rx),
         Err(e) => panic!(
             "An error occurred when creating the transport channel: {}",
             e
         ),
     };
+    if let Some(interface) = &config.interface {
+        if let Err(e) = bind_to_interface(rx.socket.fd, interface) {
+            panic!(
+                "An error occurred when binding to interface {}: {}",
+                interface, e
+            );
+        }
+    }
+    if let Some(size) = config.socket_buffer {
+        if let Err(e) = set_receive_buffer(rx.socket.fd, size) {
+            panic!("An error occurred when setting the receive buffer: {}", e);
+        }
+    }
+    let ipv6 = matches!(protocol, Layer4(Ipv6(_)));
+    let drops = SocketDrops::new(rx.socket.fd, ipv6, drops.socket.clone());
+    (rx, drops)
+}
 
-    // read udp packets from channel and handle dns packets
-    let mut iter = udp_packet_iter(&mut rx);
-    loop {
-        match iter.next() {
-            Ok((packet, addr)) => {
-                // only handle dns packets
-                if packet.get_source() != DNS_PORT && packet.get_destination() != DNS_PORT {
+// check if the ip packet was truncated by the receive buffer and count it
+fn is_truncated(ip: &Ipv4Packet, drops: &Drops) -> bool {
+    if usize::from(ip.get_total_length()) <= ip.packet().len() {
+        return false;
+    }
+    drops.truncated.fetch_add(1, atomic::Ordering::Relaxed);
+    true
+}
+
+// run udp listener on ipv4 and handle dns, nbns, mdns and llmnr packets
+fn listen_udp4(config: Arc<Config>, events: Events, running: Arc<AtomicBool>) {
+    // create an udp channel, ip headers are needed to identify servers
+    let (mut rx, mut socket_drops) =
+        create_channel(Layer3(IpNextHeaderProtocols::Udp), &config, &events.drops);
+
+    // read udp packets from channel and handle dns, nbns, mdns and llmnr
+    // packets
+    let mut iter = ipv4_packet_iter(&mut rx);
+    while running.load(atomic::Ordering::Relaxed) {
+        socket_drops.check(Instant::now());
+        match iter.next_with_timeout(POLL_INTERVAL) {
+            Ok(Some((ip, _))) => {
+                if is_truncated(&ip, &events.drops) {
                     continue;
                 }
-
-                // parse dns packet
-                print!("got udp dns packet from {}: ", addr);
-                match DnsPacket::parse(packet.payload()) {
-                    Ok(dns) => println!("{}", dns),
-                    Err(e) => println!("malformed dns packet: {}", e),
+                let packet = match UdpPacket::new(ip.payload()) {
+                    Some(packet) => packet,
+                    None => continue,
                 };
+                let src = SocketAddr::new(ip.get_source().into(), packet.get_source());
+                let dst = SocketAddr::new(ip.get_destination().into(), packet.get_destination());
+                let frame = get_frame(&config, LINKTYPE_RAW, ip.packet());
+                let payload = packet.payload();
+                handle_udp(Capture::now(frame), src, dst, payload, &config, &events);
             }
+            Ok(None) => (),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => (),
             Err(e) => {
                 panic!("An error occurred while reading: {}", e);
             }
         }
     }
+    socket_drops.update();
 }
 
-// run tcp listener and handle dns packets
-// note: only handles single dns packets that fit in a single tcp segment,
-// no tcp re-assembly
-fn listen_tcp() {
+// get an ipv6 packet from src to dst containing the udp packet; other
+// header fields that are unknown are set to zero
+fn get_ipv6_packet(src: Ipv6Addr, dst: Ipv6Addr, packet: &UdpPacket) -> Vec<u8> {
+    let udp = packet.packet();
+    let mut ip = Vec::with_capacity(40 + udp.len());
+    ip.extend_from_slice(&(6u32 << 28).to_be_bytes());
+    ip.extend_from_slice(&(udp.len() as u16).to_be_bytes());
+    ip.push(IpNextHeaderProtocols::Udp.0);
+    ip.push(0);
+    ip.extend_from_slice(&src.octets());
+    ip.extend_from_slice(&dst.octets());
+    ip.extend_from_slice(udp);
+    ip
+}
+
+// run udp listener on ipv6 and handle dns, nbns, mdns and llmnr packets;
+// ipv6 raw sockets do not provide ip headers, so the destination address
+// of packets is taken from their packet info; without it, it is set to the
+// unspecified address
+fn listen_udp6(config: Arc<Config>, events: Events, running: Arc<AtomicBool>) {
     // create an udp channel
-    let protocol = Layer4(Ipv4(IpNextHeaderProtocols::Tcp));
-    let (_, mut rx) = match transport_channel(4096, protocol) {
-        Ok((tx, rx)) => (tx, rx),
+    let (rx, mut socket_drops) = create_channel(
+        Layer4(Ipv6(IpNextHeaderProtocols::Udp)),
+        &config,
+        &events.drops,
+    );
+    let fd = rx.socket.fd;
+    let _ = set_receive_pktinfo6(fd);
+
+    // read udp packets from channel and handle dns, nbns, mdns and llmnr
+    // packets
+    let mut buffer = vec![0; config.buffer_size];
+    while running.load(atomic::Ordering::Relaxed) {
+        socket_drops.check(Instant::now());
+        let result = match poll_readable(fd, POLL_INTERVAL) {
+            Ok(true) => recv_with_destination(fd, &mut buffer),
+            Ok(false) => continue,
+            Err(e) => Err(e),
+        };
+        let (len, src, dst) = match result {
+            Ok(result) => result,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                panic!("An error occurred while reading: {}", e);
+            }
+        };
+        let packet = match UdpPacket::new(&buffer[..len]) {
+            Some(packet) => packet,
+            None => continue,
+        };
+        if usize::from(packet.get_length()) > packet.packet().len() {
+            events
+                .drops
+                .truncated
+                .fetch_add(1, atomic::Ordering::Relaxed);
+            continue;
+        }
+        let dst = dst.unwrap_or(Ipv6Addr::UNSPECIFIED.into());
+        let frame = match (src, dst) {
+            (IpAddr::V6(src), IpAddr::V6(dst)) if config.pcapng.is_some() => {
+                get_frame(&config, LINKTYPE_RAW, &get_ipv6_packet(src, dst, &packet))
+            }
+            _ => None,
+        };
+        let src = SocketAddr::new(src, packet.get_source());
+        let dst = SocketAddr::new(dst, packet.get_destination());
+        let payload = packet.payload();
+        handle_udp(Capture::now(frame), src, dst, payload, &config, &events);
+    }
+    socket_drops.update();
+}
+
+// track zone transfers on the tcp stream identified by key with the dns
+// packet received at time
+fn handle_zone_transfer(
+    time: SystemTime,
+    key: StreamKey,
+    dns: &Summary,
+    transfers: &mut HashMap<StreamKey, ZoneTransfer>,
+    state: &Mutex<State>,
+) {
+    // start a new zone transfer or add message to the current one
+    if let Entry::Vacant(entry) = transfers.entry(key) {
+        match ZoneTransfer::start(dns) {
+            Some(transfer) => entry.insert(transfer),
+            None => return,
+        };
+    }
+    if let Some(transfer) = transfers.get_mut(&key) {
+        transfer.add(dns);
+        if transfer.is_complete() {
+            print_zone_transfer(time, key, transfer, state);
+            transfers.remove(&key);
+        }
+    }
+}
+
+// print summary and all records of a zone transfer
+fn print_zone_transfer(
+    time: SystemTime,
+    key: StreamKey,
+    transfer: &ZoneTransfer,
+    state: &Mutex<State>,
+) {
+    let mut text = transfer.to_string();
+    for record in transfer.get_records() {
+        text.push_str(&format!("\n  {}", record));
+    }
+    let state = state.lock().unwrap();
+    state
+        .output
+        .event(time, "zone transfer", Some(key.src.ip().to_string()), &text);
+}
+
+// tcp state of a listener: reassembled tcp streams and dns over tls
+// connections; handled messages are sent to events
+struct TcpHandler {
+    config: Arc<Config>,
+    events: Events,
+    streams: Streams,
+    connections: HashMap<(SocketAddr, SocketAddr), TlsConnection>,
+
+    // capture time idle dns over tls connections were last removed
+    last_expire: SystemTime,
+}
+
+impl TcpHandler {
+    fn new(config: Arc<Config>, events: Events) -> TcpHandler {
+        TcpHandler {
+            config,
+            events,
+            streams: Streams::new(),
+            connections: HashMap::new(),
+            last_expire: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    // remove the dns over tls connections that are idle at capture time
+    // and print their statistics
+    fn expire_dot_connections(&mut self, time: SystemTime) {
+        let elapsed = |since| time.duration_since(since).unwrap_or_default();
+        if elapsed(self.last_expire) < DOT_EXPIRE_INTERVAL {
+            return;
+        }
+        self.last_expire = time;
+        let expired: Vec<_> = self
+            .connections
+            .iter()
+            .filter(|(_, connection)| elapsed(connection.get_last()) >= DOT_TIMEOUT)
+            .map(|(key, _)| *key)
+            .collect();
+        for (client, server) in expired {
+            if let Some(connection) = self.connections.remove(&(client, server)) {
+                let event = Event::DotConnection(connection.to_string());
+                self.events.send(time, event);
+            }
+            let key = StreamKey {
+                src: client,
+                dst: server,
+            };
+            self.streams.remove(&key);
+            self.streams.remove(&key.reverse());
+        }
+    }
+
+    // handle a tcp segment of a dns over tls connection identified by key
+    // and captured at time: update the connection's statistics and count
+    // its tls records
+    fn handle_dot_segment(&mut self, time: SystemTime, key: StreamKey, packet: &TcpPacket) {
+        let flags = packet.get_flags();
+        let payload = packet.payload();
+
+        self.expire_dot_connections(time);
+
+        // identify connection by client and server address, only start
+        // tracking connections on connection setup or data and if the
+        // maximum number of connections is not reached
+        let (client, server) = match key.dst.port() {
+            DOT_PORT => (key.src, key.dst),
+            _ => (key.dst, key.src),
+        };
+        let full = self.connections.len() >= MAX_DOT_CONNECTIONS;
+        let connection = match self.connections.entry((client, server)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                if full || (flags & TcpFlags::SYN == 0 && payload.is_empty()) {
+                    return;
+                }
+                entry.insert(TlsConnection::new(time, client, server))
+            }
+        };
+        connection.add_packet(time, key.src, payload.len(), flags & TcpFlags::FIN != 0);
+
+        // count all tls records that are complete now
+        let syn = flags & TcpFlags::SYN != 0;
+        let stream = self.streams.add(key, packet.get_sequence(), syn, payload);
+        while let Some(record) = TlsRecord::next(stream) {
+            connection.add_record(key.src, &record);
+        }
+
+        // print statistics of closed connections
+        if flags & TcpFlags::RST != 0 || connection.is_closed() {
+            let event = Event::DotConnection(connection.to_string());
+            self.events.send(time, event);
+            self.connections.remove(&(client, server));
+            self.streams.remove(&key);
+            self.streams.remove(&key.reverse());
+        }
+    }
+
+    // handle a tcp segment sent from src to dst and captured as described
+    // by capture; dns messages spanning multiple tcp segments are
+    // reassembled
+    fn handle(&mut self, capture: &Capture, src: IpAddr, dst: IpAddr, packet: &TcpPacket) {
+        let time = capture.time;
+        let key = StreamKey {
+            src: SocketAddr::new(src, packet.get_source()),
+            dst: SocketAddr::new(dst, packet.get_destination()),
+        };
+
+        // handle dns over tls packets
+        if packet.get_source() == DOT_PORT || packet.get_destination() == DOT_PORT {
+            self.handle_dot_segment(time, key, packet);
+            return;
+        }
+
+        // only handle dns packets
+        let ports = &self.config.port;
+        if !ports.contains(&packet.get_source()) && !ports.contains(&packet.get_destination()) {
+            return;
+        }
+
+        // add segment to its tcp stream and handle all dns messages that
+        // are complete now
+        let flags = packet.get_flags();
+        let syn = flags & TcpFlags::SYN != 0;
+        let stream = self
+            .streams
+            .add(key, packet.get_sequence(), syn, packet.payload());
+        while let Some(data) = stream.next_dns_message() {
+            let msg = Message {
+                time,
+                transport: Transport::Tcp,
+                protocol: Protocol::Dns,
+                src: key.src,
+                dst: key.dst,
+                data,
+                vlan: capture.vlan,
+                frame: capture.frame.clone(),
+            };
+            self.events.send(time, Event::Message(msg, Some(key), None));
+        }
+
+        // remove closed streams
+        if flags & (TcpFlags::FIN | TcpFlags::RST) != 0 {
+            self.streams.remove(&key);
+            self.events.send(time, Event::StreamClosed(key));
+        }
+    }
+}
+
+// run tcp listener and handle dns packets;
+// tcp streams are reassembled, so dns messages spanning multiple tcp
+// segments and zone transfers spanning multiple dns messages are handled;
+// dns over tls connections are not decrypted, but their traffic is counted
+fn listen_tcp(config: Arc<Config>, events: Events, running: Arc<AtomicBool>) {
+    // create a tcp channel, ip headers are needed to identify tcp streams
+    let drops = events.drops.clone();
+    let (mut rx, mut socket_drops) =
+        create_channel(Layer3(IpNextHeaderProtocols::Tcp), &config, &drops);
+
+    // read tcp packets from channel and handle dns packets
+    let mut tcp = TcpHandler::new(config.clone(), events);
+    let mut iter = ipv4_packet_iter(&mut rx);
+    while running.load(atomic::Ordering::Relaxed) {
+        socket_drops.check(Instant::now());
+        match iter.next_with_timeout(POLL_INTERVAL) {
+            Ok(Some((ip, _))) => {
+                if is_truncated(&ip, &drops) {
+                    continue;
+                }
+                let packet = match TcpPacket::new(ip.payload()) {
+                    Some(packet) => packet,
+                    None => continue,
+                };
+                let (src, dst) = (ip.get_source().into(), ip.get_destination().into());
+                let frame = get_frame(&config, LINKTYPE_RAW, ip.packet());
+                tcp.handle(&Capture::now(frame), src, dst, &packet);
+            }
+            Ok(None) => (),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => (),
+            Err(e) => {
+                panic!("An error occurred while reading: {}", e);
+            }
+        }
+    }
+    socket_drops.update();
+}
+
+// run datalink listener on the configured interface and handle dns, nbns,
+// mdns and llmnr packets in ethernet frames; ip and transport headers are
+// decoded from the frames, so vlan tags are stripped and the vlans of
+// messages are known, e.g., on mirrored trunk ports
+#[cfg(target_os = "linux")]
+fn listen_datalink(config: Arc<Config>, events: Events, running: Arc<AtomicBool>) {
+    let interface = config.interface.as_deref().unwrap_or_default();
+    let mut socket = match DatalinkSocket::open(
+        interface,
+        config.buffer_size,
+        config.socket_buffer,
+        POLL_INTERVAL,
+        events.drops.clone(),
+    ) {
+        Ok(socket) => socket,
         Err(e) => panic!(
-            "An error occurred when creating the transport channel: {}",
-            e
+            "An error occurred when opening the datalink socket on {}: {}",
+            interface, e
         ),
     };
 
-    // read udp packets from channel and handle dns packets
-    let mut iter = tcp_packet_iter(&mut rx);
+    // read ethernet frames from socket and handle dns packets
+    let mut tcp = TcpHandler::new(config.clone(), events.clone());
+    let mut fragments = Fragments::new();
+    while running.load(atomic::Ordering::Relaxed) {
+        socket.check_drops(Instant::now());
+        match socket.next() {
+            Ok(Some(frame)) => handle_capture_packet(
+                SystemTime::now(),
+                LINKTYPE_ETHERNET,
+                frame,
+                &mut tcp,
+                &mut fragments,
+                &config,
+                &events,
+            ),
+            Ok(None) => (),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => (),
+            Err(e) => {
+                panic!("An error occurred while reading: {}", e);
+            }
+        }
+    }
+    socket.update_drops();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn listen_datalink(_config: Arc<Config>, _events: Events, _running: Arc<AtomicBool>) {
+    panic!("An error occurred when opening the datalink socket: only supported on linux");
+}
+
+// handle an event of a listener
+fn handle_event(
+    time: SystemTime,
+    event: Event,
+    transfers: &mut HashMap<StreamKey, ZoneTransfer>,
+    state: &Mutex<State>,
+) {
+    match event {
+        Event::Message(msg, key, Some(analysis)) => {
+            handle_message(&msg, &analysis, state);
+            if let (Some(key), Ok(analysis)) = (key, &analysis) {
+                handle_zone_transfer(time, key, &analysis.summary, transfers, state);
+            }
+        }
+        // messages are analyzed by the workers before they are handled
+        Event::Message(_, _, None) => (),
+        Event::StreamClosed(key) => {
+            // report unfinished zone transfers
+            if let Some(transfer) = transfers.remove(&key) {
+                print_zone_transfer(time, key, &transfer, state);
+            }
+        }
+        Event::DotConnection(connection) => {
+            let state = state.lock().unwrap();
+            state
+                .output
+                .event(time, "dot connection", None, &connection);
+        }
+        Event::Skipped => (),
+    }
+}
+
+// filter and parse messages of events received from the capture stage,
+// prepare their output and check them against the watchlist; all events
+// are sent on to the processing stage
+fn run_worker(
+    config: &Config,
+    watchlist: &Watchlist,
+    captured: &Mutex<Receiver<Captured>>,
+    events: SyncSender<Captured>,
+) {
+    let output = get_output(config);
     loop {
-        match iter.next() {
-            Ok((packet, addr)) => {
-                // only handle dns packets
-                if packet.get_source() != DNS_PORT && packet.get_destination() != DNS_PORT {
-                    continue;
+        let (time, seq, event) = match captured.lock().unwrap().recv() {
+            Ok(captured) => captured,
+            Err(_) => break,
+        };
+        let event = match event {
+            Event::Message(msg, key, _) => {
+                if let Some(filter) = &config.filter {
+                    if !filter.matches(&msg) {
+                        let _ = events.send((time, seq, Event::Skipped));
+                        continue;
+                    }
                 }
+                let analysis = msg.parse().map(|dns| {
+                    Box::new(Analysis {
+                        summary: Summary::new(&dns),
+                        text: output.render_message(&msg, &dns),
+                        matches: watchlist.check(&dns),
+                    })
+                });
+                Event::Message(msg, key, Some(analysis))
+            }
+            event => event,
+        };
+        if events.send((time, seq, event)).is_err() {
+            break;
+        }
+    }
+}
 
-                // ignore syn and fin packets
-                let flags = packet.get_flags();
-                if flags & TcpFlags::SYN != 0 || flags & TcpFlags::FIN != 0 {
-                    continue;
-                }
+// start the configured number of workers between the capture stage and
+// the processing stage connected by bounded queues; dropped events are
+// counted in drops, if sending does not block; returns the sender of the
+// capture stage and the receiver of the processing stage
+fn start_workers(
+    config: &Arc<Config>,
+    drops: Drops,
+    blocking: bool,
+) -> (Events, Receiver<Captured>) {
+    let watchlist = Arc::new(match &config.watchlist {
+        Some(path) => match Watchlist::load(path) {
+            Ok(watchlist) => watchlist,
+            Err(e) => panic!(
+                "An error occurred when loading the watchlist {}: {}",
+                path.display(),
+                e
+            ),
+        },
+        None => Watchlist::default(),
+    });
+    let (sender, captured) = mpsc::sync_channel(config.queue_size);
+    let (processed, receiver) = mpsc::sync_channel(config.queue_size);
+    let captured = Arc::new(Mutex::new(captured));
+    for _ in 0..config.workers {
+        let (config, watchlist) = (config.clone(), watchlist.clone());
+        let (captured, processed) = (captured.clone(), processed.clone());
+        std::thread::spawn(move || run_worker(&config, &watchlist, &captured, processed));
+    }
+    let events = Events {
+        sender,
+        seq: Arc::new(AtomicU64::new(0)),
+        drops,
+        blocking,
+    };
+    (events, receiver)
+}
 
-                // get length of dns message from first two bytes and
-                // get message from remaining data
-                let data = packet.payload();
-                if data.len() < 2 + DNS_HEADER_LENGTH {
-                    continue;
-                }
-                let length = usize::from(read_be_u16(&data[..2]));
-                if data.len() < 2 + length {
-                    continue;
-                }
-                let msg = &data[2..2 + length];
+// handle the events of all workers in a single stage in the order they
+// were captured; events are held back for up to delay while earlier events
+// are missing
+fn process_events(events: Receiver<Captured>, state: Arc<Mutex<State>>, delay: Duration) {
+    let mut buffer = ReorderBuffer::new(delay);
+    let mut transfers = HashMap::new();
+    loop {
+        match events.recv_timeout(buffer.get_timeout(Instant::now())) {
+            Ok((time, seq, event)) => buffer.push(seq, (time, event)),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        let now = Instant::now();
+        while let Some((time, event)) = buffer.pop(now) {
+            handle_event(time, event, &mut transfers, &state);
+        }
+    }
+
+    // all listeners stopped, handle remaining events
+    for (time, event) in buffer.drain() {
+        handle_event(time, event, &mut transfers, &state);
+    }
+}
+
+// print the statistics summary and top talkers at time now and write the
+// passive dns store
+fn print_statistics(now: SystemTime, state: &Mutex<State>) {
+    let mut state = state.lock().unwrap();
+    let statistics = state.statistics.to_string();
+    state.output.event(now, "dns statistics", None, &statistics);
+    state.talkers.expire(now);
+    let talkers = state.talkers.to_string();
+    state.output.event(now, "dns top talkers", None, &talkers);
+    if let Some(path) = &state.pdns_file {
+        if let Err(e) = write_passive_dns(path, &state.passive_dns) {
+            println!("could not write passive dns to {}: {}", path.display(), e);
+        }
+    }
+}
+
+// write the passive dns store to file at path or to stdout if path is "-"
+fn write_passive_dns(path: &Path, passive_dns: &PassiveDns) -> std::io::Result<()> {
+    if path == Path::new("-") {
+        return passive_dns.write_cof(&mut std::io::stdout().lock());
+    }
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    passive_dns.write_cof(&mut file)?;
+    file.flush()
+}
+
+// run udp (ipv4 and ipv6) and tcp listener in separate threads and handle
+// their dns packets in the order they were captured until sigint or
+// sigterm; the configuration selects transport protocols and ip versions;
+// print statistics periodically if configured and on exit
+pub fn listen(config: &Config) {
+    // join mdns groups on the interface to receive multicast dns packets
+    let _groups = join_groups(config.interface.as_deref());
+
+    // state is shared, so queries and responses are paired across
+    // listeners
+    let state = Arc::new(Mutex::new(State::new(config)));
+
+    // print statistics periodically and on exit
+    if let Some(interval) = config.stats_interval {
+        let state = state.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(Duration::from_secs(interval));
+            print_statistics(SystemTime::now(), &state);
+        });
+    }
+
+    // serve metrics for monitoring
+    #[cfg(feature = "metrics")]
+    if let Some(addr) = config.metrics {
+        let listener = match std::net::TcpListener::bind(addr) {
+            Ok(listener) => listener,
+            Err(e) => panic!("An error occurred when binding to {}: {}", addr, e),
+        };
+        let state = state.clone();
+        std::thread::spawn(move || {
+            metrics::serve(listener, || state.lock().unwrap().statistics.get_metrics())
+        });
+    }
+
+    // stop listeners on sigint or sigterm, so all captured messages are
+    // handled before the summary is printed; stop immediately on a second
+    // signal
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            if !running.swap(false, atomic::Ordering::Relaxed) {
+                std::process::exit(1);
+            }
+        }) {
+            panic!("An error occurred when setting the signal handler: {}", e);
+        }
+    }
+
+    // start listeners; the datalink listener handles all packets, otherwise
+    // there are separate listeners and tcp is only supported on ipv4; all
+    // listeners send their events through the workers to a single
+    // processing stage
+    let config = Arc::new(config.clone());
+    let drops = state.lock().unwrap().statistics.get_drops();
+    let (tx, rx) = start_workers(&config, drops, false);
+    let datalink = config.datalink;
+    let (udp, tcp) = (!datalink && !config.tcp, !datalink && !config.udp);
+    let (ipv4, ipv6) = (!config.ipv6, !config.ipv4);
+    if datalink {
+        let (config, tx, running) = (config.clone(), tx.clone(), running.clone());
+        std::thread::spawn(move || listen_datalink(config, tx, running));
+    }
+    if udp && ipv4 {
+        let (config, tx, running) = (config.clone(), tx.clone(), running.clone());
+        std::thread::spawn(move || listen_udp4(config, tx, running));
+    }
+    if udp && ipv6 {
+        let (config, tx, running) = (config.clone(), tx.clone(), running.clone());
+        std::thread::spawn(move || listen_udp6(config, tx, running));
+    }
+    if tcp && ipv4 {
+        let (config, tx, running) = (config.clone(), tx.clone(), running.clone());
+        std::thread::spawn(move || listen_tcp(config, tx, running));
+    }
+    drop(tx);
+    process_events(rx, state.clone(), REORDER_DELAY);
+    print_statistics(SystemTime::now(), &state);
+}
+
+// handle the ip packet in the link layer frame data with linktype captured
+// at time, e.g., read from a capture file or a datalink socket, if it is
+// allowed by the configuration
+fn handle_capture_packet(
+    time: SystemTime,
+    linktype: u32,
+    data: &[u8],
+    tcp: &mut TcpHandler,
+    fragments: &mut Fragments,
+    config: &Config,
+    events: &Events,
+) {
+    let (ip, vlan) = match get_ip_packet(linktype, data) {
+        Some(ip) => ip,
+        None => return,
+    };
+
+    // packets are written to pcapng files as captured, reassembled ipv4
+    // packets are written without link layer header
+    let (mut linktype, mut frame) = (linktype, data);
+    let reassembled;
+
+    // get addresses, transport protocol and payload of ip packet
+    let (src, dst, protocol, payload): (IpAddr, IpAddr, _, _) = match ip[0] >> 4 {
+        4 if !config.ipv6 => {
+            let mut ip = match Ipv4Packet::new(ip) {
+                Some(ip) => ip,
+                None => return,
+            };
+
+            // reassemble fragmented ipv4 packets, fragments are only handled
+            // once all fragments of their datagram are received
+            if is_fragment(&ip) {
+                reassembled = match fragments.add(time, &ip) {
+                    Some(reassembled) => reassembled,
+                    None => return,
+                };
+                ip = match Ipv4Packet::new(&reassembled) {
+                    Some(ip) => ip,
+                    None => return,
+                };
+                (linktype, frame) = (LINKTYPE_RAW, reassembled.as_slice());
+            }
+            let payload = ip.payload().to_vec();
+            let protocol = ip.get_next_level_protocol();
+            (
+                ip.get_source().into(),
+                ip.get_destination().into(),
+                protocol,
+                payload,
+            )
+        }
+        6 if !config.ipv4 => {
+            let ip = match Ipv6Packet::new(ip) {
+                Some(ip) => ip,
+                None => return,
+            };
+            let payload = ip.payload().to_vec();
+            let protocol = ip.get_next_header();
+            (
+                ip.get_source().into(),
+                ip.get_destination().into(),
+                protocol,
+                payload,
+            )
+        }
+        _ => return,
+    };
 
-                // parse dns packet
-                print!("got tcp dns packet from {}: ", addr);
-                match DnsPacket::parse(msg) {
-                    Ok(dns) => println!("{}", dns),
-                    Err(e) => println!("malformed dns packet: {}: {:?}", e, packet.payload()),
+    // handle udp and tcp packets
+    match protocol {
+        IpNextHeaderProtocols::Udp if !config.tcp => {
+            if let Some(udp) = UdpPacket::new(&payload) {
+                let src = SocketAddr::new(src, udp.get_source());
+                let dst = SocketAddr::new(dst, udp.get_destination());
+                let capture = Capture {
+                    time,
+                    vlan,
+                    frame: get_frame(config, linktype, frame),
                 };
+                handle_udp(capture, src, dst, udp.payload(), config, events);
             }
+        }
+        IpNextHeaderProtocols::Tcp if !config.udp => {
+            if let Some(segment) = TcpPacket::new(&payload) {
+                let capture = Capture {
+                    time,
+                    vlan,
+                    frame: get_frame(config, linktype, frame),
+                };
+                tcp.handle(&capture, src, dst, &segment);
+            }
+        }
+        _ => (),
+    }
+}
+
+// read packets from the pcap file reader and handle them like captured
+// packets; returns the time of the last packet
+fn read_capture(
+    mut reader: PcapReader<impl Read>,
+    config: Arc<Config>,
+    events: Events,
+) -> Option<SystemTime> {
+    let mut tcp = TcpHandler::new(config.clone(), events.clone());
+    let mut fragments = Fragments::new();
+    let mut last = None;
+    loop {
+        let packet = match reader.next_packet() {
+            Ok(Some(packet)) => packet,
+            Ok(None) => break,
             Err(e) => {
-                panic!("An error occurred while reading: {}", e);
+                println!("could not read packet: {}", e);
+                break;
             }
+        };
+        handle_capture_packet(
+            packet.time,
+            reader.get_linktype(),
+            &packet.data,
+            &mut tcp,
+            &mut fragments,
+            &config,
+            &events,
+        );
+        last = Some(packet.time);
+    }
+    last
+}
+
+// handle the dns packets in the pcap file at path in the order they were
+// captured and print statistics at the end of the file
+pub fn read_file(path: &Path, config: &Config) {
+    let reader = match std::fs::File::open(path)
+        .and_then(|file| PcapReader::new(std::io::BufReader::new(file)))
+    {
+        Ok(reader) => reader,
+        Err(e) => panic!("An error occurred when reading {}: {}", path.display(), e),
+    };
+    let state = Arc::new(Mutex::new(State::new(config)));
+    let config = Arc::new(config.clone());
+    let drops = state.lock().unwrap().statistics.get_drops();
+    let (tx, rx) = start_workers(&config, drops, true);
+    let reader = std::thread::spawn(move || read_capture(reader, config, tx));
+    // no events are dropped, so always wait for missing events
+    process_events(rx, state.clone(), Duration::MAX);
+    let last = reader.join().ok().flatten();
+    print_statistics(last.unwrap_or_else(SystemTime::now), &state);
+}
+
+// answer a, aaaa and ptr queries received over udp on the configured
+// address with the host names and addresses in the hosts file at path
+pub fn respond(path: &Path, config: &Config) {
+    let hosts = match Hosts::load(path) {
+        Ok(hosts) => hosts,
+        Err(e) => panic!(
+            "An error occurred when loading the hosts file {}: {}",
+            path.display(),
+            e
+        ),
+    };
+    let listen = config.listen.unwrap_or(DEFAULT_LISTEN);
+    let socket = match UdpSocket::bind(listen) {
+        Ok(socket) => socket,
+        Err(e) => panic!("An error occurred when binding to {}: {}", listen, e),
+    };
+    if let Err(e) = serve(&socket, &hosts, &get_output(config)) {
+        panic!("An error occurred while answering queries: {}", e);
+    }
+}
+
+// forward queries received over udp on the configured address to the
+// upstream resolver and relay its responses
+pub fn forward(upstream: SocketAddr, config: &Config) {
+    let listen = config.listen.unwrap_or(DEFAULT_LISTEN);
+    let forwarder = match Forwarder::new(listen, upstream, get_output(config)) {
+        Ok(forwarder) => forwarder,
+        Err(e) => panic!(
+            "An error occurred when creating the forwarder on {}: {}",
+            listen, e
+        ),
+    };
+    if let Err(e) = forwarder.run() {
+        panic!("An error occurred while forwarding queries: {}", e);
+    }
+}
+
+// advertise the dns-sd service instance over mdns until sigint or sigterm
+// and send goodbye packets on exit
+pub fn advertise(instance: &str, config: &Config) {
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let hostname = match config.hostname.clone().or_else(get_hostname) {
+        Some(hostname) => hostname,
+        None => panic!("An error occurred when getting the host name"),
+    };
+    let addrs = match config.address.is_empty() {
+        true => interface.ips.iter().map(|ip| ip.ip()).collect(),
+        false => config.address.clone(),
+    };
+    let service = match Service::new(
+        instance,
+        config.service.as_deref().unwrap_or_default(),
+        config.service_port.unwrap_or_default(),
+        &config.txt,
+        hostname.split('.').next().unwrap_or_default(),
+        &addrs,
+    ) {
+        Ok(service) => service,
+        Err(e) => panic!("An error occurred when creating the service: {}", e),
+    };
+
+    // only use the configured interface, the default interface is selected
+    // by the routing table
+    let interface = config.interface.as_ref().map(|_| &interface);
+    let advertiser = match Advertiser::new(
+        service,
+        interface,
+        !config.ipv6,
+        !config.ipv4,
+        get_output(config),
+    ) {
+        Ok(advertiser) => advertiser,
+        Err(e) => panic!("An error occurred when creating the mdns sockets: {}", e),
+    };
+
+    // stop answering queries on sigint or sigterm and send goodbye packets
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the signal handler: {}", e);
         }
     }
+    if let Err(e) = advertiser.run(&running) {
+        panic!("An error occurred while advertising the service: {}", e);
+    }
 }
 
-// run udp and tcp listener in separate threads and handle dns packets
-pub fn listen() {
-    let udp = std::thread::spawn(|| listen_udp());
-    let tcp = std::thread::spawn(|| listen_tcp());
-    let _ = udp.join();
-    let _ = tcp.join();
+// request a transfer of zone from server and print it in zone file format
+pub fn axfr(zone: &str, server: SocketAddr) {
+    let zone = match request_zone(server, zone) {
+        Ok(zone) => zone,
+        Err(e) => panic!(
+            "An error occurred during the zone transfer of {} from {}: {}",
+            zone, server, e
+        ),
+    };
+    println!("; zone transfer from {}: {}", server, zone.transfer);
+    for record in &zone.records {
+        println!("{}", record);
+    }
+}
+
+// read a raw dns message from file at path or from stdin if path is "-"
+fn read_message(path: &Path) -> std::io::Result<Vec<u8>> {
+    if path == Path::new("-") {
+        let mut msg = Vec::new();
+        std::io::stdin().read_to_end(&mut msg)?;
+        return Ok(msg);
+    }
+    std::fs::read(path)
+}
+
+// parse raw dns messages (application/dns-message) from files, e.g.,
+// bodies of dns over https requests and responses exported from captures
+pub fn parse_files(paths: &[impl AsRef<Path>]) {
+    for path in paths {
+        let path = path.as_ref();
+        let msg = match read_message(path) {
+            Ok(msg) => msg,
+            Err(e) => {
+                println!("could not read dns message from {}: {}", path.display(), e);
+                continue;
+            }
+        };
+
+        // parse dns packet
+        print!("got dns message from {}: ", path.display());
+        match DnsPacket::parse(&msg) {
+            Ok(dns) => println!("{}", dns),
+            Err(e) => println!("malformed dns packet: {}: {:?}", e, msg),
+        };
+    }
+}
+
+// run the dns tool with configuration config
+pub fn run(config: &Config) {
+    if !config.doh.is_empty() {
+        parse_files(&config.doh);
+        return;
+    }
+    if let Some(path) = &config.respond {
+        respond(path, config);
+        return;
+    }
+    if let Some(upstream) = config.forward {
+        forward(upstream, config);
+        return;
+    }
+    if let Some(instance) = &config.advertise {
+        advertise(instance, config);
+        return;
+    }
+    if let (Some(zone), Some(server)) = (&config.axfr, config.server) {
+        axfr(zone, server);
+        return;
+    }
+    if let Some(path) = &config.read {
+        read_file(path, config);
+        return;
+    }
+    listen(config);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pnet::packet::tcp::MutableTcpPacket;
+
+    fn handler() -> (TcpHandler, Receiver<Captured>) {
+        let (sender, receiver) = mpsc::sync_channel(16);
+        let events = Events {
+            sender,
+            seq: Arc::new(AtomicU64::new(0)),
+            drops: Drops::default(),
+            blocking: false,
+        };
+        (
+            TcpHandler::new(Arc::new(Config::default()), events),
+            receiver,
+        )
+    }
+
+    // get a tcp syn segment from client port to the dns over tls port
+    fn syn(port: u16) -> Vec<u8> {
+        let mut buf = vec![0; 20];
+        let mut packet = MutableTcpPacket::new(&mut buf).unwrap();
+        packet.set_source(port);
+        packet.set_destination(DOT_PORT);
+        packet.set_data_offset(5);
+        packet.set_flags(TcpFlags::SYN);
+        buf
+    }
+
+    fn key(port: u16) -> StreamKey {
+        StreamKey {
+            src: SocketAddr::new([192, 0, 2, 1].into(), port),
+            dst: SocketAddr::new([192, 0, 2, 2].into(), DOT_PORT),
+        }
+    }
+
+    #[test]
+    fn expire_idle_dot_connections() {
+        let (mut handler, receiver) = handler();
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let packet = syn(40000);
+        handler.handle_dot_segment(start, key(40000), &TcpPacket::new(&packet).unwrap());
+        assert_eq!(handler.connections.len(), 1);
+
+        let later = start + DOT_TIMEOUT;
+        let packet = syn(40001);
+        handler.handle_dot_segment(later, key(40001), &TcpPacket::new(&packet).unwrap());
+        assert_eq!(handler.connections.len(), 1);
+        assert!(handler
+            .connections
+            .contains_key(&(key(40001).src, key(40001).dst)));
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok((_, _, Event::DotConnection(_)))
+        ));
+    }
+
+    #[test]
+    fn limit_dot_connections() {
+        let (mut handler, _receiver) = handler();
+        let time = SystemTime::UNIX_EPOCH;
+        for port in 0..=MAX_DOT_CONNECTIONS as u16 {
+            let packet = syn(port);
+            handler.handle_dot_segment(time, key(port), &TcpPacket::new(&packet).unwrap());
+        }
+        assert_eq!(handler.connections.len(), MAX_DOT_CONNECTIONS);
+    }
 }
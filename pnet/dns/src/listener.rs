@@ -0,0 +1,194 @@
+use std::future;
+use std::io;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::time::SystemTime;
+
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::udp::UdpPacket;
+use pnet::packet::Packet;
+use tokio::io::unix::AsyncFd;
+
+use crate::config::Config;
+use crate::socket::*;
+
+// types of received messages
+pub use crate::error::DnsError;
+pub use crate::message::{Frame, Message, Transport};
+pub use crate::packet::DnsPacket;
+pub use crate::protocol::Protocol;
+
+// size of receive buffers, large enough for udp packets with edns
+const BUFFER_SIZE: usize = 65535;
+
+// open a non-blocking raw socket for udp packets of address family,
+// optionally bound to interface and with kernel receive buffer size
+fn open_socket(
+    family: libc::c_int,
+    interface: Option<&str>,
+    socket_buffer: Option<usize>,
+) -> io::Result<AsyncFd<OwnedFd>> {
+    // safety: socket returns a new file descriptor owned by OwnedFd
+    let fd = unsafe { libc::socket(family, libc::SOCK_RAW, libc::IPPROTO_UDP) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    // safety: fd is a valid file descriptor
+    let flags = unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GETFL) };
+    if flags < 0
+        || unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_SETFL, flags | libc::O_NONBLOCK) } < 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+    if let Some(interface) = interface {
+        bind_to_interface(fd.as_raw_fd(), interface)?;
+    }
+    if let Some(size) = socket_buffer {
+        set_receive_buffer(fd.as_raw_fd(), size)?;
+    }
+
+    // ipv6 raw sockets do not provide ip headers, the destination addresses
+    // of packets are in their packet info; without it, they are unspecified
+    if family == libc::AF_INET6 {
+        let _ = set_receive_pktinfo6(fd.as_raw_fd());
+    }
+    AsyncFd::new(fd)
+}
+
+// receive the next packet from socket into buf, never returns if there is
+// no socket; returns its length, source and destination address
+async fn recv(
+    socket: Option<&AsyncFd<OwnedFd>>,
+    buf: &mut [u8],
+) -> io::Result<(usize, IpAddr, Option<IpAddr>)> {
+    let socket = match socket {
+        Some(socket) => socket,
+        None => return future::pending().await,
+    };
+    loop {
+        let mut guard = socket.readable().await?;
+        if let Ok(result) = guard.try_io(|fd| recv_with_destination(fd.get_ref().as_raw_fd(), buf))
+        {
+            return result;
+        }
+    }
+}
+
+// async listener for dns, nbns, mdns and llmnr messages over udp, for use
+// in tokio services; messages are read from raw ipv4 and ipv6 sockets as
+// selected by the configuration, which also sets interface, dns ports and
+// filter; the destination addresses of ipv6 messages are taken from their
+// packet info
+pub struct DnsListener {
+    config: Config,
+    udp4: Option<AsyncFd<OwnedFd>>,
+    udp6: Option<AsyncFd<OwnedFd>>,
+    buf4: Vec<u8>,
+    buf6: Vec<u8>,
+}
+
+impl DnsListener {
+    // create a listener with configuration config; must be called in a
+    // tokio runtime
+    pub fn bind(config: &Config) -> io::Result<DnsListener> {
+        let interface = config.interface.as_deref();
+        let udp4 = match config.ipv6 {
+            true => None,
+            false => Some(open_socket(libc::AF_INET, interface, config.socket_buffer)?),
+        };
+        let udp6 = match config.ipv4 {
+            true => None,
+            false => Some(open_socket(
+                libc::AF_INET6,
+                interface,
+                config.socket_buffer,
+            )?),
+        };
+        Ok(DnsListener {
+            config: config.clone(),
+            udp4,
+            udp6,
+            buf4: vec![0; BUFFER_SIZE],
+            buf6: vec![0; BUFFER_SIZE],
+        })
+    }
+
+    // get the message in the udp packet sent from src to dst
+    fn get_message(&self, src: IpAddr, dst: IpAddr, packet: &UdpPacket) -> Option<Message> {
+        let src = SocketAddr::new(src, packet.get_source());
+        let dst = SocketAddr::new(dst, packet.get_destination());
+        let msg = Message {
+            time: SystemTime::now(),
+            transport: Transport::Udp,
+            protocol: Protocol::from_ports(src.port(), dst.port(), &self.config.port)?,
+            src,
+            dst,
+            data: packet.payload().to_vec(),
+            vlan: None,
+            frame: None,
+        };
+        match &self.config.filter {
+            Some(filter) if !filter.matches(&msg) => None,
+            _ => Some(msg),
+        }
+    }
+
+    // wait for the next message
+    pub async fn next(&mut self) -> io::Result<Message> {
+        loop {
+            tokio::select! {
+                result = recv(self.udp4.as_ref(), &mut self.buf4) => {
+                    let (len, _, _) = result?;
+                    let ip = match Ipv4Packet::new(&self.buf4[..len]) {
+                        Some(ip) => ip,
+                        None => continue,
+                    };
+                    let (src, dst) = (ip.get_source().into(), ip.get_destination().into());
+                    if let Some(msg) = UdpPacket::new(ip.payload())
+                        .and_then(|packet| self.get_message(src, dst, &packet))
+                    {
+                        return Ok(msg);
+                    }
+                }
+                result = recv(self.udp6.as_ref(), &mut self.buf6) => {
+                    let (len, src, dst) = result?;
+                    let dst = dst.unwrap_or(Ipv6Addr::UNSPECIFIED.into());
+                    if let Some(msg) = UdpPacket::new(&self.buf6[..len])
+                        .and_then(|packet| self.get_message(src, dst, &packet))
+                    {
+                        return Ok(msg);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pnet::packet::udp::MutableUdpPacket;
+
+    #[test]
+    fn default_listener_classifies_dns_port() {
+        let listener = DnsListener {
+            config: Config::default(),
+            udp4: None,
+            udp6: None,
+            buf4: Vec::new(),
+            buf6: Vec::new(),
+        };
+        let mut buf = [0; 8];
+        let mut packet = MutableUdpPacket::new(&mut buf).unwrap();
+        packet.set_source(40000);
+        packet.set_destination(53);
+        let (src, dst) = ([192, 0, 2, 1].into(), [192, 0, 2, 2].into());
+        let msg = listener
+            .get_message(src, dst, &packet.to_immutable())
+            .unwrap();
+        assert_eq!(msg.protocol, Protocol::Dns);
+        assert_eq!(msg.dst.port(), 53);
+    }
+}
@@ -1,3 +1,5 @@
+use clap::Parser;
+
 fn main() {
-    dns::listen();
+    dns::run(&dns::Config::parse());
 }
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, UdpSocket};
+use std::time::{Duration, SystemTime};
+
+use packet_utils::interface::get_interface;
+use pnet::datalink::NetworkInterface;
+
+use crate::summary::*;
+
+pub const MDNS_IPV4_GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+pub const MDNS_IPV6_GROUP: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 0xfb);
+
+// time after a query in which responses are considered answers to it;
+// mdns responders delay their responses by up to 500ms
+const MDNS_RESPONSE_WINDOW: Duration = Duration::from_secs(1);
+
+// join the mdns multicast groups on the network interface name or on the
+// default interface, so mdns packets are delivered to the listener; the
+// group memberships last as long as the returned sockets exist
+pub fn join_groups(name: Option<&str>) -> Vec<UdpSocket> {
+    // join on the address and index of the interface; the unspecified
+    // address and index 0 let the kernel choose the default interface
+    let (address, index) = match name {
+        Some(name) => match get_interface(name) {
+            Some(interface) => (get_ipv4_address(&interface), interface.index),
+            None => {
+                println!("could not join mdns groups: unknown interface {}", name);
+                return Vec::new();
+            }
+        },
+        None => (Some(Ipv4Addr::UNSPECIFIED), 0),
+    };
+
+    let mut sockets = Vec::new();
+    if let Some(address) = address {
+        match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+            .and_then(|s| s.join_multicast_v4(&MDNS_IPV4_GROUP, &address).map(|_| s))
+        {
+            Ok(socket) => sockets.push(socket),
+            Err(e) => println!("could not join mdns group {}: {}", MDNS_IPV4_GROUP, e),
+        }
+    }
+    match UdpSocket::bind((Ipv6Addr::UNSPECIFIED, 0))
+        .and_then(|s| s.join_multicast_v6(&MDNS_IPV6_GROUP, index).map(|_| s))
+    {
+        Ok(socket) => sockets.push(socket),
+        Err(e) => println!("could not join mdns group {}: {}", MDNS_IPV6_GROUP, e),
+    }
+    sockets
+}
+
+// get the first ipv4 address of interface
+fn get_ipv4_address(interface: &NetworkInterface) -> Option<Ipv4Addr> {
+    interface.ips.iter().find_map(|ip| match ip.ip() {
+        IpAddr::V4(address) => Some(address),
+        IpAddr::V6(_) => None,
+    })
+}
+
+// recently seen mdns queries, used to tell responses to queries apart
+// from unsolicited responses, e.g., announcements of new records
+#[derive(Default)]
+pub struct MdnsQueries {
+    queries: HashMap<String, SystemTime>,
+}
+
+impl MdnsQueries {
+    pub fn new() -> MdnsQueries {
+        MdnsQueries::default()
+    }
+
+    // handle an mdns packet captured at now: remember the names in queries
+    // and check if a response is unsolicited, i.e., none of its answers has
+    // been queried recently
+    pub fn is_unsolicited(&mut self, now: SystemTime, packet: &Summary) -> bool {
+        self.queries
+            .retain(|_, time| now.duration_since(*time).unwrap_or_default() < MDNS_RESPONSE_WINDOW);
+
+        // remember queried names
+        if packet.qr == 0 {
+            for question in &packet.questions {
+                self.queries.insert(question.name.to_lowercase(), now);
+            }
+            return false;
+        }
+
+        // check if answers have been queried
+        !packet
+            .answers
+            .iter()
+            .any(|answer| self.queries.contains_key(&answer.name.to_lowercase()))
+    }
+}
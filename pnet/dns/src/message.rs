@@ -0,0 +1,59 @@
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::error::*;
+use crate::packet::*;
+use crate::protocol::*;
+
+// transport protocol a dns message was received with
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Transport {
+    Udp,
+    Tcp,
+}
+
+impl fmt::Display for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Transport::Udp => write!(f, "udp"),
+            Transport::Tcp => write!(f, "tcp"),
+        }
+    }
+}
+
+// raw packet captured on the network with the link type of its data, e.g.,
+// ethernet frames or ip packets
+pub struct Frame {
+    pub linktype: u32,
+    pub data: Vec<u8>,
+}
+
+// dns message captured on the network:
+// the raw message and information about when and how it was received;
+// addresses that cannot be determined are unspecified addresses
+pub struct Message {
+    pub time: SystemTime,
+    pub transport: Transport,
+    pub protocol: Protocol,
+    pub src: SocketAddr,
+    pub dst: SocketAddr,
+    pub data: Vec<u8>,
+
+    // id of the vlan the message was captured on, if it was captured with
+    // vlan tags
+    pub vlan: Option<u16>,
+
+    // packet the message was captured in; for tcp, the segment that
+    // completed the message, it is shared by all messages it completed;
+    // only kept if packets are written to a file
+    pub frame: Option<Arc<Frame>>,
+}
+
+impl Message {
+    // parse the dns packet in the message
+    pub fn parse(&self) -> Result<DnsPacket<'_>> {
+        DnsPacket::parse_protocol(&self.data, self.protocol)
+    }
+}
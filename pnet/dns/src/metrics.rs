@@ -0,0 +1,61 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+// time after which reading a request or writing a response is aborted, so a
+// stalled client does not block other clients
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+// handle a http request on stream: answer requests of /metrics with the
+// output of get_metrics and all other requests with an error
+fn handle_request(stream: TcpStream, get_metrics: &impl Fn() -> String) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+
+    // skip request headers until the empty line or the end of the request
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 && !line.trim().is_empty() {
+        line.clear();
+    }
+
+    let mut parts = request.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            let metrics = get_metrics();
+            format!(
+                "HTTP/1.1 200 OK\r\n\
+                Content-Type: text/plain; version=0.0.4\r\n\
+                Content-Length: {}\r\n\
+                Connection: close\r\n\r\n{}",
+                metrics.len(),
+                metrics
+            )
+        }
+        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+    };
+    let mut stream = &stream;
+    stream.write_all(response.as_bytes())
+}
+
+// serve metrics returned by get_metrics over http at the path /metrics on
+// the listener, e.g., for prometheus; each request is handled in its own
+// thread, so slow clients do not block others
+pub fn serve(listener: TcpListener, get_metrics: impl Fn() -> String + Sync) {
+    let get_metrics = &get_metrics;
+    std::thread::scope(|scope| {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => continue,
+            };
+            scope.spawn(move || {
+                if let Err(e) = handle_request(stream, get_metrics) {
+                    println!("could not handle metrics request: {}", e);
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+use std::net::Ipv4Addr;
+
+use packet_utils::bytes::*;
+
+use crate::error::*;
+use crate::labels::*;
+
+// length of a netbios name and of its first-level encoding
+pub const NETBIOS_NAME_LENGTH: usize = 16;
+const NETBIOS_ENCODED_NAME_LENGTH: usize = 32;
+
+// length of a name entry in a node status (NBSTAT) response
+const NBSTAT_NAME_LENGTH: usize = NETBIOS_NAME_LENGTH + 2;
+
+// length of the unit id (mac address) in a node status (NBSTAT) response
+const NBSTAT_UNIT_ID_LENGTH: usize = 6;
+
+// decode a netbios name in first-level encoding (rfc 1001, 1002): each
+// half-byte of the 16 byte netbios name is encoded as a character
+// from 'A' to 'P'
+pub fn decode_name(encoded: &[u8]) -> Option<[u8; NETBIOS_NAME_LENGTH]> {
+    if encoded.len() != NETBIOS_ENCODED_NAME_LENGTH {
+        return None;
+    }
+    let mut name = [0; NETBIOS_NAME_LENGTH];
+    for (i, pair) in encoded.chunks(2).enumerate() {
+        if !pair.iter().all(|c| (b'A'..=b'P').contains(c)) {
+            return None;
+        }
+        name[i] = (pair[0] - b'A') << 4 | (pair[1] - b'A');
+    }
+    Some(name)
+}
+
+// format a 16 byte netbios name as name<suffix>: the first 15 bytes are
+// the name padded with spaces (or zeros in the wildcard name "*"), the
+// last byte is the name's suffix that identifies the service
+pub fn format_name(name: &[u8]) -> String {
+    let (name, suffix) = name.split_at(NETBIOS_NAME_LENGTH - 1);
+    let end = name
+        .iter()
+        .rposition(|&c| c != b' ' && c != 0)
+        .map_or(0, |i| i + 1);
+    let name: String = name[..end]
+        .iter()
+        .map(|&c| match c {
+            0x20..=0x7e => char::from(c),
+            _ => '.',
+        })
+        .collect();
+    format!("{}<{:02x}>", name, suffix[0])
+}
+
+// get the name from labels in raw packet: the first label contains the
+// encoded netbios name, the other labels contain the optional scope id
+pub fn get_netbios_name_from_labels(raw: &[u8], label_indexes: &[usize]) -> Result<String> {
+    let (first, scope) = match label_indexes.split_first() {
+        Some((first, scope)) => (first, scope),
+        None => return Ok(String::new()),
+    };
+
+    // decode netbios name, use raw label if it is not encoded properly
+    let length = usize::from(raw[*first]);
+    let encoded = &raw[first + 1..first + 1 + length];
+    let mut name = match decode_name(encoded) {
+        Some(name) => format_name(&name),
+        None => get_name_from_labels(raw, &[*first])?,
+    };
+
+    // append scope id
+    if !scope.is_empty() {
+        name.push('.');
+        name.push_str(&get_name_from_labels(raw, scope)?);
+    }
+    Ok(name)
+}
+
+// NbFlags:
+// flags of a name in a netbios name (NB) record:
+// G (1 bit)       group name flag
+// ONT (2 bits)    owner node type: 0 b-node, 1 p-node, 2 m-node, 3 h-node
+pub struct NbFlags(pub u16);
+
+impl NbFlags {
+    // get group name flag
+    pub fn get_group(&self) -> u8 {
+        (self.0 >> 15) as u8
+    }
+
+    // get owner node type
+    pub fn get_node_type(&self) -> u8 {
+        ((self.0 >> 13) & 0b11) as u8
+    }
+}
+
+impl fmt::Display for NbFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let node_type = match self.get_node_type() {
+            0 => "b-node",
+            1 => "p-node",
+            2 => "m-node",
+            _ => "h-node",
+        };
+        write!(
+            f,
+            "group: {}, node type: {} ({})",
+            self.get_group(),
+            self.get_node_type(),
+            node_type
+        )
+    }
+}
+
+// parse netbios name (NB) record data: a list of entries consisting of
+// flags (16 bits) and an ipv4 address (32 bits)
+pub fn parse_nb(raw: &[u8]) -> Result<Vec<(NbFlags, Ipv4Addr)>> {
+    if !raw.len().is_multiple_of(6) {
+        return Err(DnsError::DataLength);
+    }
+    Ok(raw
+        .chunks(6)
+        .map(|e| (NbFlags(read_be_u16(&e[0..2])), read_be_u32(&e[2..6]).into()))
+        .collect())
+}
+
+// name entry in a node status (NBSTAT) response: name and name flags
+pub type NbstatName = (String, u16);
+
+// parse node status (NBSTAT) record data: number of names (8 bits), list
+// of name entries consisting of a 16 byte netbios name and name flags
+// (16 bits), and statistics starting with the unit id (48 bits)
+pub fn parse_nbstat(raw: &[u8]) -> Result<(Vec<NbstatName>, [u8; 6])> {
+    if raw.is_empty() {
+        return Err(DnsError::DataLength);
+    }
+    let count = usize::from(raw[0]);
+    let end = 1 + count * NBSTAT_NAME_LENGTH;
+    if raw.len() < end + NBSTAT_UNIT_ID_LENGTH {
+        return Err(DnsError::DataLength);
+    }
+
+    let names = raw[1..end]
+        .chunks(NBSTAT_NAME_LENGTH)
+        .map(|e| {
+            let flags = read_be_u16(&e[NETBIOS_NAME_LENGTH..]);
+            (format_name(&e[..NETBIOS_NAME_LENGTH]), flags)
+        })
+        .collect();
+    let mut unit_id = [0; NBSTAT_UNIT_ID_LENGTH];
+    unit_id.copy_from_slice(&raw[end..end + NBSTAT_UNIT_ID_LENGTH]);
+    Ok((names, unit_id))
+}
+
+// get the name of a netbios name service opcode
+pub fn opcode_name(opcode: u8) -> &'static str {
+    match opcode {
+        0 => "query",
+        5 => "registration",
+        6 => "release",
+        7 => "wack",
+        8 => "refresh",
+        _ => "reserved",
+    }
+}
@@ -0,0 +1,309 @@
+use std::io::IsTerminal;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::ValueEnum;
+use packet_utils::hex::to_hex;
+use packet_utils::json::json_string;
+
+use crate::error::*;
+use crate::message::*;
+use crate::packet::*;
+use crate::record::*;
+use crate::transaction::*;
+
+// output format of the dns tool
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, ValueEnum)]
+pub enum Format {
+    // human readable text
+    #[default]
+    Text,
+
+    // one json object per line
+    Json,
+}
+
+// seconds since the unix epoch of time
+fn get_timestamp(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+// get the time of day of time in utc as text, e.g., "12:34:56.789012"
+fn format_time(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs() % 86400;
+    format!(
+        "{:02}:{:02}:{:02}.{:06}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        since_epoch.subsec_micros()
+    )
+}
+
+// get the vlan id as text for messages, e.g., " on vlan 10"
+fn format_vlan(vlan: Option<u16>) -> String {
+    match vlan {
+        Some(vlan) => format!(" on vlan {}", vlan),
+        None => String::new(),
+    }
+}
+
+// get the vlan id as json field for messages, e.g., ",\"vlan\":10"
+fn json_vlan(vlan: Option<u16>) -> String {
+    match vlan {
+        Some(vlan) => format!(",\"vlan\":{}", vlan),
+        None => String::new(),
+    }
+}
+
+// get a json value or null if value is none
+fn json_option(value: Option<String>) -> String {
+    value.unwrap_or_else(|| "null".to_string())
+}
+
+// get the questions of packet as json array
+fn json_questions(packet: &DnsPacket) -> String {
+    let questions: Vec<String> = (0..packet.get_questions().into())
+        .filter_map(|i| packet.get_question(i))
+        .map(|q| {
+            format!(
+                "{{\"name\":{},\"type\":{},\"class\":{}}}",
+                json_string(&q.get_name()),
+                json_string(&q.get_type().get_mnemonic()),
+                u16::from(q.get_class()),
+            )
+        })
+        .collect();
+    format!("[{}]", questions.join(","))
+}
+
+// get the resource records as json array
+fn json_records<'a>(records: impl Iterator<Item = &'a DnsAnswer<'a>>) -> String {
+    let records: Vec<String> = records
+        .map(|r| {
+            format!(
+                "{{\"name\":{},\"type\":{},\"class\":{},\"ttl\":{},\"data\":{}}}",
+                json_string(&r.get_name()),
+                json_string(&r.get_type().get_mnemonic()),
+                u16::from(r.get_class()),
+                r.get_ttl(),
+                json_string(&r.get_data().get_presentation()),
+            )
+        })
+        .collect();
+    format!("[{}]", records.join(","))
+}
+
+// printer of messages and events in the configured format and verbosity
+pub struct Output {
+    format: Format,
+
+    // verbosity: below 0 only prints events, above 0 also prints raw
+    // messages
+    verbosity: i8,
+}
+
+impl Output {
+    pub fn new(format: Format, verbosity: i8) -> Output {
+        Output { format, verbosity }
+    }
+
+    // prepare the output of the dns packet in msg, so it can be formatted
+    // outside of the processing stage; returns none if messages are not
+    // printed
+    pub fn render_message(&self, msg: &Message, packet: &DnsPacket) -> Option<String> {
+        if self.verbosity < 0 {
+            return None;
+        }
+        if self.format == Format::Text {
+            let mut text = packet.to_string();
+            if self.verbosity > 0 {
+                text.push_str(&format!("\n  raw: {}", to_hex(&msg.data)));
+            }
+            return Some(text);
+        }
+        let mut json = format!(
+            "{{\"type\":\"message\",\"time\":{:.6},\"transport\":\"{}\",\"protocol\":\"{}\",\
+            \"src\":\"{}\",\"dst\":\"{}\",\"size\":{},\"id\":{},\"qr\":{},\"opcode\":{},\
+            \"aa\":{},\"tc\":{},\"rd\":{},\"ra\":{},\"z\":{},\"rcode\":{},\
+            \"questions\":{},\"answers\":{},\"authorities\":{},\"additionals\":{}",
+            get_timestamp(msg.time),
+            msg.transport,
+            msg.protocol,
+            msg.src,
+            msg.dst,
+            msg.data.len(),
+            packet.get_id(),
+            packet.get_qr(),
+            u8::from(packet.get_opcode()),
+            packet.get_aa(),
+            packet.get_tc(),
+            packet.get_rd(),
+            packet.get_ra(),
+            packet.get_z(),
+            u8::from(packet.get_rcode()),
+            json_questions(packet),
+            json_records((0..packet.get_answers().into()).filter_map(|i| packet.get_answer(i))),
+            json_records(
+                (0..packet.get_authorities().into()).filter_map(|i| packet.get_authority(i))
+            ),
+            json_records(
+                (0..packet.get_additionals().into()).filter_map(|i| packet.get_additional(i))
+            ),
+        );
+        json.push_str(&json_vlan(msg.vlan));
+        if self.verbosity > 0 {
+            json.push_str(&format!(",\"raw\":\"{}\"", to_hex(&msg.data)));
+        }
+        Some(json)
+    }
+
+    // print the dns message msg with its output prepared by render_message;
+    // unsolicited marks mdns responses that do not answer recent queries
+    pub fn message(&self, msg: &Message, text: &str, unsolicited: bool) {
+        if self.format == Format::Json {
+            println!("{},\"unsolicited\":{}}}", text, unsolicited);
+            return;
+        }
+        print!(
+            "{} got {} {} packet from {}{}: ",
+            format_time(msg.time),
+            msg.transport,
+            msg.protocol,
+            msg.src.ip(),
+            format_vlan(msg.vlan)
+        );
+        if unsolicited {
+            print!("unsolicited response: ");
+        }
+        println!("{}", text);
+    }
+
+    // print the malformed dns message msg that could not be parsed
+    pub fn malformed(&self, msg: &Message, error: &DnsError) {
+        if self.verbosity < 0 {
+            return;
+        }
+        match self.format {
+            Format::Text => println!(
+                "{} got {} {} packet from {}{}: malformed dns packet: {}: {:?}",
+                format_time(msg.time),
+                msg.transport,
+                msg.protocol,
+                msg.src.ip(),
+                format_vlan(msg.vlan),
+                error,
+                msg.data
+            ),
+            Format::Json => println!(
+                "{{\"type\":\"malformed\",\"time\":{:.6},\"transport\":\"{}\",\"protocol\":\"{}\",\
+                \"src\":\"{}\",\"dst\":\"{}\"{},\"error\":{},\"raw\":\"{}\"}}",
+                get_timestamp(msg.time),
+                msg.transport,
+                msg.protocol,
+                msg.src,
+                msg.dst,
+                json_vlan(msg.vlan),
+                json_string(&error.to_string()),
+                to_hex(&msg.data),
+            ),
+        }
+    }
+
+    // print the transaction finished or retransmitted at time
+    pub fn transaction(&self, time: SystemTime, transaction: &Transaction) {
+        if self.format == Format::Text {
+            let kind = match transaction.outcome {
+                Outcome::Retransmitted(_) => "retransmission",
+                _ => "transaction",
+            };
+            println!("{} got dns {}: {}", format_time(time), kind, transaction);
+            return;
+        }
+        let (result, rcode, latency) = match &transaction.outcome {
+            Outcome::Answered(rcode, latency) => (
+                "answered",
+                Some(u8::from(*rcode).to_string()),
+                Some(format!("{:.3}", latency.as_secs_f64() * 1000.0)),
+            ),
+            Outcome::Retransmitted(interval) => (
+                "retransmitted",
+                None,
+                Some(format!("{:.3}", interval.as_secs_f64() * 1000.0)),
+            ),
+            Outcome::Orphaned(rcode) => ("orphaned", Some(u8::from(*rcode).to_string()), None),
+            Outcome::Unanswered => ("unanswered", None, None),
+        };
+        let key = &transaction.key;
+        let question = key.question.as_ref().map(|q| {
+            format!(
+                "{{\"name\":{},\"type\":{},\"class\":{}}}",
+                json_string(&q.name),
+                json_string(&q.typ.get_mnemonic()),
+                u16::from(q.class),
+            )
+        });
+        println!(
+            "{{\"type\":\"transaction\",\"time\":{:.6},\"protocol\":\"{}\",\"client\":\"{}\",\
+            \"server\":\"{}\",\"id\":{},\"question\":{},\"result\":\"{}\",\"rcode\":{},\
+            \"latency\":{},\"retransmissions\":{}}}",
+            get_timestamp(time),
+            transaction.protocol,
+            key.client,
+            key.server,
+            key.id,
+            json_option(question),
+            result,
+            json_option(rcode),
+            json_option(latency),
+            transaction.retransmissions,
+        );
+    }
+
+    // print an alert for watchlist matches in msg, highlighted on terminals
+    pub fn alert(&self, msg: &Message, matches: &[String]) {
+        if self.format == Format::Json {
+            let matches: Vec<String> = matches.iter().map(|m| json_string(m)).collect();
+            println!(
+                "{{\"type\":\"alert\",\"time\":{:.6},\"src\":\"{}\",\"dst\":\"{}\",\"matches\":[{}]}}",
+                get_timestamp(msg.time),
+                msg.src,
+                msg.dst,
+                matches.join(","),
+            );
+            return;
+        }
+        let alert = format!(
+            "{} got dns alert from {}: {{{}}}",
+            format_time(msg.time),
+            msg.src.ip(),
+            matches.join(", ")
+        );
+        match std::io::stdout().is_terminal() {
+            true => println!("\x1b[1;31m{}\x1b[0m", alert),
+            false => println!("{}", alert),
+        }
+    }
+
+    // print an event of kind, e.g., "dns statistics", that happened at
+    // time with its source if known and its description text
+    pub fn event(&self, time: SystemTime, kind: &str, source: Option<String>, text: &str) {
+        if self.format == Format::Json {
+            println!(
+                "{{\"type\":{},\"time\":{:.6},\"src\":{},\"text\":{}}}",
+                json_string(&kind.replace(' ', "_")),
+                get_timestamp(time),
+                json_option(source.map(|s| json_string(&s))),
+                json_string(text),
+            );
+            return;
+        }
+        let time = format_time(time);
+        match source {
+            Some(source) => println!("{} got {} from {}: {}", time, kind, source, text),
+            None => println!("{} got {}: {}", time, kind, text),
+        }
+    }
+}
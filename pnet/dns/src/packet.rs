@@ -1,7 +1,10 @@
 use std::fmt;
 
+use packet_utils::bytes::*;
+
 use crate::error::*;
-use crate::helpers::*;
+use crate::netbios;
+use crate::protocol::*;
 use crate::record::*;
 
 pub const DNS_HEADER_LENGTH: usize = 12;
@@ -14,6 +17,7 @@ pub const DNS_HEADER_LENGTH: usize = 12;
 // 1               an inverse query (IQUERY)
 // 2               a server status request (STATUS)
 // 3-15            reserved for future use
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum OpCode {
     Query,
     IQuery,
@@ -80,6 +84,7 @@ impl fmt::Display for OpCode {
 //                 a particular operation (e.g., zone
 //                 transfer) for particular data.
 // 6-15            Reserved for future use.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum RCode {
     NoError,
     FormatError,
@@ -147,6 +152,9 @@ impl fmt::Display for RCode {
 pub struct DnsPacket<'a> {
     raw: &'a [u8],
 
+    // dns based protocol of the packet
+    protocol: Protocol,
+
     // dns questions inside the packet
     questions: Vec<DnsQuestion<'a>>,
 
@@ -163,12 +171,18 @@ pub struct DnsPacket<'a> {
 impl<'a> DnsPacket<'a> {
     // create a new dns packet from raw packet bytes
     pub fn parse(raw: &'a [u8]) -> Result<DnsPacket<'a>> {
+        DnsPacket::parse_protocol(raw, Protocol::Dns)
+    }
+
+    // create a new packet of a dns based protocol from raw packet bytes
+    pub fn parse_protocol(raw: &'a [u8], protocol: Protocol) -> Result<DnsPacket<'a>> {
         if raw.len() < DNS_HEADER_LENGTH {
             return Err(DnsError::PacketLength);
         }
 
         let mut packet = DnsPacket {
-            raw: raw,
+            raw,
+            protocol,
             questions: Vec::new(),
             answers: Vec::new(),
             authorities: Vec::new(),
@@ -186,33 +200,33 @@ impl<'a> DnsPacket<'a> {
         // parse questions
         let mut offset = DNS_HEADER_LENGTH;
         for _ in 0..self.get_questions() {
-            let q = DnsQuestion::parse(self.raw, offset)?;
+            let q = DnsQuestion::parse(self.raw, offset, self.protocol)?;
             offset += q.get_length();
             self.questions.push(q);
         }
 
         // parse answers
         for _ in 0..self.get_answers() {
-            let a = DnsAnswer::parse(self.raw, offset)?;
+            let a = DnsAnswer::parse(self.raw, offset, self.protocol)?;
             offset += a.get_length();
             self.answers.push(a);
         }
 
         // parse authorities
         for _ in 0..self.get_authorities() {
-            let a = DnsAuthority::parse(self.raw, offset)?;
+            let a = DnsAuthority::parse(self.raw, offset, self.protocol)?;
             offset += a.get_length();
             self.authorities.push(a);
         }
 
         // parse additionals
         for _ in 0..self.get_additionals() {
-            let a = DnsAdditional::parse(self.raw, offset)?;
+            let a = DnsAdditional::parse(self.raw, offset, self.protocol)?;
             offset += a.get_length();
             self.additionals.push(a);
         }
 
-        return Ok(());
+        Ok(())
     }
 
     // get identification field from packet
@@ -255,6 +269,26 @@ impl<'a> DnsPacket<'a> {
         (self.raw[3] & 0b01110000) >> 4
     }
 
+    // get nbns Broadcast (B) bit from packet
+    pub fn get_b(&self) -> u8 {
+        (self.raw[3] & 0b00010000) >> 4
+    }
+
+    // get llmnr Conflict (C) bit from packet
+    pub fn get_c(&self) -> u8 {
+        self.get_aa()
+    }
+
+    // get llmnr Tentative (T) bit from packet
+    pub fn get_t(&self) -> u8 {
+        self.get_rd()
+    }
+
+    // get llmnr reserved (Z) bits (4 bits) from packet
+    pub fn get_llmnr_z(&self) -> u8 {
+        (self.raw[3] & 0b11110000) >> 4
+    }
+
     // get response code (RCODE) bits (4 bits) from packet
     pub fn get_rcode(&self) -> RCode {
         (self.raw[3] & 0b00001111).into()
@@ -281,7 +315,7 @@ impl<'a> DnsPacket<'a> {
     }
 
     // get nth question from packet
-    pub fn get_question(&self, nth: usize) -> Option<&DnsQuestion> {
+    pub fn get_question(&self, nth: usize) -> Option<&DnsQuestion<'_>> {
         if nth >= self.questions.len() {
             return None;
         }
@@ -289,7 +323,7 @@ impl<'a> DnsPacket<'a> {
     }
 
     // get nth answer from packet
-    pub fn get_answer(&self, nth: usize) -> Option<&DnsAnswer> {
+    pub fn get_answer(&self, nth: usize) -> Option<&DnsAnswer<'_>> {
         if nth >= self.answers.len() {
             return None;
         }
@@ -297,7 +331,7 @@ impl<'a> DnsPacket<'a> {
     }
 
     // get nth authority from packet
-    pub fn get_authority(&self, nth: usize) -> Option<&DnsAuthority> {
+    pub fn get_authority(&self, nth: usize) -> Option<&DnsAuthority<'_>> {
         if nth >= self.authorities.len() {
             return None;
         }
@@ -305,7 +339,7 @@ impl<'a> DnsPacket<'a> {
     }
 
     // get nth additional from packet
-    pub fn get_additional(&self, nth: usize) -> Option<&DnsAdditional> {
+    pub fn get_additional(&self, nth: usize) -> Option<&DnsAdditional<'_>> {
         if nth >= self.additionals.len() {
             return None;
         }
@@ -313,22 +347,67 @@ impl<'a> DnsPacket<'a> {
     }
 }
 
+impl<'a> DnsPacket<'a> {
+    // format opcode of the packet based on its protocol
+    fn fmt_opcode(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.protocol {
+            Protocol::Nbns => {
+                let opcode = u8::from(self.get_opcode());
+                write!(f, "{} ({})", opcode, netbios::opcode_name(opcode))
+            }
+            _ => write!(f, "{}", self.get_opcode()),
+        }
+    }
+
+    // format header flags of the packet based on its protocol
+    fn fmt_flags(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.protocol {
+            Protocol::Dns | Protocol::Mdns => write!(
+                f,
+                "aa: {}, tc: {}, rd: {}, ra: {}, z: {}",
+                self.get_aa(),
+                self.get_tc(),
+                self.get_rd(),
+                self.get_ra(),
+                self.get_z(),
+            ),
+            Protocol::Nbns => write!(
+                f,
+                "aa: {}, tc: {}, rd: {}, ra: {}, b: {}",
+                self.get_aa(),
+                self.get_tc(),
+                self.get_rd(),
+                self.get_ra(),
+                self.get_b(),
+            ),
+            Protocol::Llmnr => write!(
+                f,
+                "c: {}, tc: {}, t: {}, z: {}",
+                self.get_c(),
+                self.get_tc(),
+                self.get_t(),
+                self.get_llmnr_z(),
+            ),
+        }
+    }
+}
+
 impl<'a> fmt::Display for DnsPacket<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // dns packet header
         write!(
             f,
-            "{{id: {}, qr: {}, opcode: {}, aa: {}, tc: {}, rd: {}, ra: {}, \
-            z: {}, rcode: {}, questions: {}, answers: {}, authorities: {}, \
-            additionals: {}",
+            "{{id: {}, qr: {}, opcode: ",
             self.get_id(),
-            self.get_qr(),
-            self.get_opcode(),
-            self.get_aa(),
-            self.get_tc(),
-            self.get_rd(),
-            self.get_ra(),
-            self.get_z(),
+            self.get_qr()
+        )?;
+        self.fmt_opcode(f)?;
+        write!(f, ", ")?;
+        self.fmt_flags(f)?;
+        write!(
+            f,
+            ", rcode: {}, questions: {}, answers: {}, authorities: {}, \
+            additionals: {}",
             self.get_rcode(),
             self.get_questions(),
             self.get_answers(),
@@ -0,0 +1,143 @@
+use std::io::{self, Read};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// magic numbers of pcap files with microsecond and nanosecond timestamps
+const PCAP_MAGIC_MICROS: u32 = 0xa1b2c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b23c4d;
+
+// link types of pcap files
+const LINKTYPE_NULL: u32 = 0;
+pub const LINKTYPE_ETHERNET: u32 = 1;
+pub const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_IPV4: u32 = 228;
+const LINKTYPE_IPV6: u32 = 229;
+
+// ether types of ip and vlan tagged frames
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+pub const ETHERTYPE_VLAN: u16 = 0x8100;
+const ETHERTYPE_QINQ: u16 = 0x88a8;
+
+// maximum length of a packet in a pcap file
+const MAX_PACKET_LENGTH: u32 = 262144;
+
+// packet read from a pcap file
+pub struct PcapPacket {
+    pub time: SystemTime,
+    pub data: Vec<u8>,
+}
+
+// reader of packets in a pcap file
+pub struct PcapReader<R: Read> {
+    reader: R,
+    big_endian: bool,
+    nanos: bool,
+    linktype: u32,
+}
+
+impl<R: Read> PcapReader<R> {
+    // create a new reader and read the file header from reader
+    pub fn new(mut reader: R) -> io::Result<PcapReader<R>> {
+        let mut header = [0; 24];
+        reader.read_exact(&mut header)?;
+        let magic = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let (big_endian, nanos) = match (magic, magic.swap_bytes()) {
+            (PCAP_MAGIC_MICROS, _) => (false, false),
+            (PCAP_MAGIC_NANOS, _) => (false, true),
+            (_, PCAP_MAGIC_MICROS) => (true, false),
+            (_, PCAP_MAGIC_NANOS) => (true, true),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "unsupported file format, only pcap files are supported",
+                ))
+            }
+        };
+        let mut pcap = PcapReader {
+            reader,
+            big_endian,
+            nanos,
+            linktype: 0,
+        };
+        pcap.linktype = pcap.read_u32(&header[20..24]) & 0xffff;
+        Ok(pcap)
+    }
+
+    // convert a 32 bit field in the byte order of the file
+    fn read_u32(&self, bytes: &[u8]) -> u32 {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        match self.big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        }
+    }
+
+    // get the link type of the packets in the file
+    pub fn get_linktype(&self) -> u32 {
+        self.linktype
+    }
+
+    // read the next packet, returns none at the end of the file
+    pub fn next_packet(&mut self) -> io::Result<Option<PcapPacket>> {
+        let mut header = [0; 16];
+        match self.reader.read_exact(&mut header) {
+            Ok(()) => (),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        let secs = self.read_u32(&header[0..4]);
+        let fraction = self.read_u32(&header[4..8]);
+        let length = self.read_u32(&header[8..12]);
+        if length > MAX_PACKET_LENGTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid packet length {}", length),
+            ));
+        }
+        let mut data = vec![0; length as usize];
+        self.reader.read_exact(&mut data)?;
+
+        let fraction = match self.nanos {
+            true => Duration::from_nanos(fraction.into()),
+            false => Duration::from_micros(fraction.into()),
+        };
+        let time = UNIX_EPOCH + Duration::from_secs(secs.into()) + fraction;
+        Ok(Some(PcapPacket { time, data }))
+    }
+}
+
+// get the ip packet in the link layer frame data with linktype and the id of
+// the innermost vlan the frame was tagged with; returns none if data does not
+// contain an ipv4 or ipv6 packet
+pub fn get_ip_packet(linktype: u32, data: &[u8]) -> Option<(&[u8], Option<u16>)> {
+    let ethertype = |bytes: &[u8]| u16::from_be_bytes([bytes[0], bytes[1]]);
+    let mut vlan = None;
+    let ip = match linktype {
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => data,
+        LINKTYPE_NULL => data.get(4..)?,
+        LINKTYPE_LINUX_SLL => match ethertype(data.get(14..16)?) {
+            ETHERTYPE_IPV4 | ETHERTYPE_IPV6 => data.get(16..)?,
+            _ => return None,
+        },
+        LINKTYPE_ETHERNET => {
+            // skip vlan tags
+            let mut offset = 12;
+            loop {
+                match ethertype(data.get(offset..offset + 2)?) {
+                    ETHERTYPE_VLAN | ETHERTYPE_QINQ => {
+                        vlan = Some(ethertype(data.get(offset + 2..offset + 4)?) & 0x0fff);
+                        offset += 4;
+                    }
+                    ETHERTYPE_IPV4 | ETHERTYPE_IPV6 => break data.get(offset + 2..)?,
+                    _ => return None,
+                }
+            }
+        }
+        _ => return None,
+    };
+    match ip.first()? >> 4 {
+        4 | 6 => Some((ip, vlan)),
+        _ => None,
+    }
+}
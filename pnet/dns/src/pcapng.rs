@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use crate::message::*;
+
+// block types
+const BLOCK_SECTION_HEADER: u32 = 0x0a0d0d0a;
+const BLOCK_INTERFACE_DESCRIPTION: u32 = 0x00000001;
+const BLOCK_ENHANCED_PACKET: u32 = 0x00000006;
+
+// byte order magic of section header blocks
+const BYTE_ORDER_MAGIC: u32 = 0x1a2b3c4d;
+
+// option codes
+const OPT_ENDOFOPT: u16 = 0;
+const OPT_SHB_USERAPPL: u16 = 4;
+const OPT_IF_NAME: u16 = 2;
+const OPT_IF_TSRESOL: u16 = 9;
+
+// maximum length of captured packets
+const SNAPLEN: u32 = 262144;
+
+// append option code with value to block, value is padded to 32 bits
+fn push_option(block: &mut Vec<u8>, code: u16, value: &[u8]) {
+    block.extend_from_slice(&code.to_le_bytes());
+    block.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    block.extend_from_slice(value);
+    block.resize(block.len() + (4 - value.len() % 4) % 4, 0);
+}
+
+// writer of captured packets to a pcapng file; packets are written with
+// microsecond timestamps and one interface per link type
+pub struct PcapngWriter {
+    writer: BufWriter<File>,
+
+    // name of the capture interface
+    interface: String,
+
+    // ids of interfaces per link type
+    interfaces: HashMap<u32, u32>,
+
+    // last written packet, to write packets shared by messages only once
+    last: Option<Arc<Frame>>,
+}
+
+impl PcapngWriter {
+    // create the pcapng file at path for packets captured on interface
+    pub fn create(path: &Path, interface: &str) -> io::Result<PcapngWriter> {
+        let mut writer = PcapngWriter {
+            writer: BufWriter::new(File::create(path)?),
+            interface: interface.to_string(),
+            interfaces: HashMap::new(),
+            last: None,
+        };
+
+        // write section header block with unknown section length
+        let mut body = Vec::new();
+        body.extend_from_slice(&BYTE_ORDER_MAGIC.to_le_bytes());
+        body.extend_from_slice(&1u16.to_le_bytes());
+        body.extend_from_slice(&0u16.to_le_bytes());
+        body.extend_from_slice(&(-1i64).to_le_bytes());
+        push_option(
+            &mut body,
+            OPT_SHB_USERAPPL,
+            env!("CARGO_PKG_NAME").as_bytes(),
+        );
+        push_option(&mut body, OPT_ENDOFOPT, &[]);
+        writer.write_block(BLOCK_SECTION_HEADER, &body)?;
+        writer.writer.flush()?;
+        Ok(writer)
+    }
+
+    // write a block of type with its body
+    fn write_block(&mut self, typ: u32, body: &[u8]) -> io::Result<()> {
+        let length = (body.len() + 12) as u32;
+        self.writer.write_all(&typ.to_le_bytes())?;
+        self.writer.write_all(&length.to_le_bytes())?;
+        self.writer.write_all(body)?;
+        self.writer.write_all(&length.to_le_bytes())
+    }
+
+    // get the id of the interface with linktype, write its interface
+    // description block if it is new
+    fn get_interface(&mut self, linktype: u32) -> io::Result<u32> {
+        if let Some(id) = self.interfaces.get(&linktype) {
+            return Ok(*id);
+        }
+        let mut body = Vec::new();
+        body.extend_from_slice(&(linktype as u16).to_le_bytes());
+        body.extend_from_slice(&0u16.to_le_bytes());
+        body.extend_from_slice(&SNAPLEN.to_le_bytes());
+        push_option(&mut body, OPT_IF_NAME, self.interface.as_bytes());
+        push_option(&mut body, OPT_IF_TSRESOL, &[6]);
+        push_option(&mut body, OPT_ENDOFOPT, &[]);
+        self.write_block(BLOCK_INTERFACE_DESCRIPTION, &body)?;
+
+        let id = self.interfaces.len() as u32;
+        self.interfaces.insert(linktype, id);
+        Ok(id)
+    }
+
+    // write the packet the message msg was captured in, if known
+    pub fn write(&mut self, msg: &Message) -> io::Result<()> {
+        let frame = match &msg.frame {
+            Some(frame) => frame,
+            None => return Ok(()),
+        };
+        if let Some(last) = &self.last {
+            if Arc::ptr_eq(last, frame) {
+                return Ok(());
+            }
+        }
+        self.last = Some(frame.clone());
+        let interface = self.get_interface(frame.linktype)?;
+        let micros = msg
+            .time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+        let length = frame.data.len() as u32;
+
+        let mut body = Vec::with_capacity(frame.data.len() + 24);
+        body.extend_from_slice(&interface.to_le_bytes());
+        body.extend_from_slice(&((micros >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(micros as u32).to_le_bytes());
+        body.extend_from_slice(&length.to_le_bytes());
+        body.extend_from_slice(&length.to_le_bytes());
+        body.extend_from_slice(&frame.data);
+        body.resize(body.len() + (4 - frame.data.len() % 4) % 4, 0);
+        self.write_block(BLOCK_ENHANCED_PACKET, &body)?;
+
+        // flush, so the file is complete when the tool is stopped
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use packet_utils::json::json_string;
+
+use crate::message::*;
+use crate::record::*;
+use crate::summary::*;
+
+// get the name without the trailing dot as used in passive dns records
+fn get_rrname(name: &str) -> String {
+    match name.strip_suffix('.') {
+        Some(name) if !name.is_empty() => name.to_lowercase(),
+        _ => name.to_lowercase(),
+    }
+}
+
+// seconds since the unix epoch of time
+fn get_timestamp(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+// record observed in dns answers identified by name, type and data
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+struct RecordKey {
+    name: String,
+    typ: Type,
+    data: String,
+}
+
+// time the record was first and last seen and how often it was seen
+struct Observation {
+    first: SystemTime,
+    last: SystemTime,
+    count: u64,
+}
+
+// passive dns store of deduplicated records seen in dns answers
+#[derive(Default)]
+pub struct PassiveDns {
+    observations: HashMap<RecordKey, Observation>,
+}
+
+impl PassiveDns {
+    pub fn new() -> PassiveDns {
+        PassiveDns::default()
+    }
+
+    // record the answers of the dns packet in msg
+    pub fn add(&mut self, msg: &Message, packet: &Summary) {
+        if packet.qr != 1 {
+            return;
+        }
+        for answer in &packet.answers {
+            let data = match &answer.presentation {
+                Some(data) => data,
+                None => continue,
+            };
+            let key = RecordKey {
+                name: get_rrname(&answer.name),
+                typ: answer.typ,
+                data: data.clone(),
+            };
+            let observation = self.observations.entry(key).or_insert(Observation {
+                first: msg.time,
+                last: msg.time,
+                count: 0,
+            });
+            observation.first = observation.first.min(msg.time);
+            observation.last = observation.last.max(msg.time);
+            observation.count += 1;
+        }
+    }
+
+    // write all records in the passive dns common output format (cof),
+    // one json object per line
+    pub fn write_cof(&self, w: &mut impl Write) -> io::Result<()> {
+        let mut records: Vec<_> = self.observations.iter().collect();
+        records.sort_by(|a, b| {
+            (&a.0.name, a.0.typ.get_mnemonic(), &a.0.data).cmp(&(
+                &b.0.name,
+                b.0.typ.get_mnemonic(),
+                &b.0.data,
+            ))
+        });
+        for (key, observation) in records {
+            writeln!(
+                w,
+                "{{\"rrname\":{},\"rrtype\":{},\"rdata\":{},\"time_first\":{},\
+                \"time_last\":{},\"count\":{}}}",
+                json_string(&key.name),
+                json_string(&key.typ.get_mnemonic()),
+                json_string(&key.data),
+                get_timestamp(observation.first),
+                get_timestamp(observation.last),
+                observation.count,
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt;
+
+pub const DNS_PORT: u16 = 53;
+pub const NBNS_PORT: u16 = 137;
+pub const MDNS_PORT: u16 = 5353;
+pub const LLMNR_PORT: u16 = 5355;
+
+// Protocol:
+// dns based protocols that share the dns message format:
+// dns             domain name system, port 53
+// nbns            netbios name service, port 137
+// mdns            multicast dns, port 5353
+// llmnr           link-local multicast name resolution, port 5355
+//
+// llmnr uses a different set of header flags than dns: the C (conflict)
+// bit replaces AA, the T (tentative) bit replaces RD, and there is no
+// recursion, so RA is part of the (4 bit) Z field.
+//
+// mdns uses the top bit of the class field as a flag: the unicast-response
+// bit in questions and the cache-flush bit in resource records.
+//
+// nbns uses a B (broadcast) bit in the Z field, encoded netbios names,
+// and netbios specific record types
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Protocol {
+    Dns,
+    Nbns,
+    Mdns,
+    Llmnr,
+}
+
+impl Protocol {
+    // get the protocol based on the source and destination port; dns uses
+    // the ports in dns_ports instead of its default port
+    pub fn from_ports(src: u16, dst: u16, dns_ports: &[u16]) -> Option<Protocol> {
+        if dns_ports.contains(&src) || dns_ports.contains(&dst) {
+            return Some(Protocol::Dns);
+        }
+        [Protocol::Nbns, Protocol::Mdns, Protocol::Llmnr]
+            .into_iter()
+            .find(|p| src == p.get_port() || dst == p.get_port())
+    }
+
+    // get the port of the protocol
+    pub fn get_port(&self) -> u16 {
+        match self {
+            Protocol::Dns => DNS_PORT,
+            Protocol::Nbns => NBNS_PORT,
+            Protocol::Mdns => MDNS_PORT,
+            Protocol::Llmnr => LLMNR_PORT,
+        }
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Protocol::Dns => write!(f, "dns"),
+            Protocol::Nbns => write!(f, "nbns"),
+            Protocol::Mdns => write!(f, "mdns"),
+            Protocol::Llmnr => write!(f, "llmnr"),
+        }
+    }
+}
@@ -1,9 +1,12 @@
 use std::fmt;
 
+use packet_utils::bytes::*;
+
 use crate::characters::*;
 use crate::error::*;
-use crate::helpers::*;
 use crate::labels::*;
+use crate::netbios::*;
+use crate::protocol::*;
 
 const DNS_MIN_ANSWER_LENGTH: usize = 11;
 const DNS_MIN_QUESTION_LENGTH: usize = 5;
@@ -34,10 +37,18 @@ const DNS_MIN_QUESTION_LENGTH: usize = 5;
 // superset of TYPEs, hence all TYPEs are valid QTYPEs.  In addition, the
 // following QTYPEs are defined:
 //
+// IXFR            251 A request for an incremental transfer of a zone
 // AXFR            252 A request for a transfer of an entire zone
 // MAILB           253 A request for mailbox-related records (MB, MG or MR)
 // MAILA           254 A request for mail agent RRs (Obsolete - see MX)
 // *               255 A request for all records
+//
+// the netbios name service uses the following types that overlap with
+// dns types:
+//
+// NB              32 netbios general name service resource record
+// NBSTAT          33 netbios node status resource record
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum Type {
     A,
     Ns,
@@ -57,6 +68,9 @@ pub enum Type {
     Txt,
     Aaaa,
     Srv,
+    Nb,
+    Nbstat,
+    Ixfr,
     Axfr,
     Mailb,
     Maila,
@@ -85,6 +99,7 @@ impl From<u16> for Type {
             16 => Type::Txt,
             28 => Type::Aaaa,
             33 => Type::Srv,
+            251 => Type::Ixfr,
             252 => Type::Axfr,
             253 => Type::Mailb,
             254 => Type::Maila,
@@ -94,6 +109,39 @@ impl From<u16> for Type {
     }
 }
 
+impl From<Type> for u16 {
+    fn from(typ: Type) -> u16 {
+        match typ {
+            Type::A => 1,
+            Type::Ns => 2,
+            Type::Md => 3,
+            Type::Mf => 4,
+            Type::Cname => 5,
+            Type::Soa => 6,
+            Type::Mb => 7,
+            Type::Mg => 8,
+            Type::Mr => 9,
+            Type::Null => 10,
+            Type::Wks => 11,
+            Type::Ptr => 12,
+            Type::Hinfo => 13,
+            Type::Minfo => 14,
+            Type::Mx => 15,
+            Type::Txt => 16,
+            Type::Aaaa => 28,
+            Type::Srv => 33,
+            Type::Nb => 32,
+            Type::Nbstat => 33,
+            Type::Ixfr => 251,
+            Type::Axfr => 252,
+            Type::Mailb => 253,
+            Type::Maila => 254,
+            Type::All => 255,
+            Type::Unknown(unknown) => unknown,
+        }
+    }
+}
+
 impl fmt::Display for Type {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -115,6 +163,9 @@ impl fmt::Display for Type {
             Type::Txt => write!(f, "16 (txt)"),
             Type::Aaaa => write!(f, "28 (aaaa)"),
             Type::Srv => write!(f, "33 (srv)"),
+            Type::Nb => write!(f, "32 (nb)"),
+            Type::Nbstat => write!(f, "33 (nbstat)"),
+            Type::Ixfr => write!(f, "251 (ixfr)"),
             Type::Axfr => write!(f, "252 (axfr)"),
             Type::Mailb => write!(f, "253 (mailb)"),
             Type::Maila => write!(f, "254 (maila)"),
@@ -124,6 +175,57 @@ impl fmt::Display for Type {
     }
 }
 
+impl Type {
+    // get the mnemonic of the type as used in zone files, e.g., "AAAA";
+    // unknown types are written as "TYPE" followed by their value
+    pub fn get_mnemonic(&self) -> String {
+        let mnemonic = match self {
+            Type::A => "A",
+            Type::Ns => "NS",
+            Type::Md => "MD",
+            Type::Mf => "MF",
+            Type::Cname => "CNAME",
+            Type::Soa => "SOA",
+            Type::Mb => "MB",
+            Type::Mg => "MG",
+            Type::Mr => "MR",
+            Type::Null => "NULL",
+            Type::Wks => "WKS",
+            Type::Ptr => "PTR",
+            Type::Hinfo => "HINFO",
+            Type::Minfo => "MINFO",
+            Type::Mx => "MX",
+            Type::Txt => "TXT",
+            Type::Aaaa => "AAAA",
+            Type::Srv => "SRV",
+            Type::Nb => "NB",
+            Type::Nbstat => "NBSTAT",
+            Type::Ixfr => "IXFR",
+            Type::Axfr => "AXFR",
+            Type::Mailb => "MAILB",
+            Type::Maila => "MAILA",
+            Type::All => "ANY",
+            Type::Unknown(unknown) => return format!("TYPE{}", unknown),
+        };
+        mnemonic.to_string()
+    }
+
+    // get the type with the mnemonic, e.g., "AAAA" or "TYPE65", or the
+    // numeric value s
+    pub fn from_mnemonic(s: &str) -> Option<Type> {
+        if let Ok(value) = s.parse::<u16>() {
+            return Some(Type::from(value));
+        }
+        let s = s.to_uppercase();
+        if let Some(value) = s.strip_prefix("TYPE") {
+            return value.parse::<u16>().ok().map(Type::from);
+        }
+        (0..=u8::MAX)
+            .map(|value| Type::from(u16::from(value)))
+            .find(|typ| typ.get_mnemonic() == s)
+    }
+}
+
 // Class/QClass:
 //
 // CLASS fields appear in resource records.  The following CLASS mnemonics
@@ -138,6 +240,7 @@ impl fmt::Display for Type {
 // are a superset of CLASS values; every CLASS is a valid QCLASS.  In
 // addition to CLASS values, the following QCLASSes are defined:
 // *               255 any class
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum Class {
     In,
     Cs,
@@ -158,6 +261,18 @@ impl From<u16> for Class {
     }
 }
 
+impl From<Class> for u16 {
+    fn from(class: Class) -> u16 {
+        match class {
+            Class::In => 1,
+            Class::Cs => 2,
+            Class::Ch => 3,
+            Class::Hs => 4,
+            Class::Unknown(unknown) => unknown,
+        }
+    }
+}
+
 impl fmt::Display for Class {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -170,6 +285,21 @@ impl fmt::Display for Class {
     }
 }
 
+impl Class {
+    // get the mnemonic of the class as used in zone files, e.g., "IN";
+    // unknown classes are written as "CLASS" followed by their value
+    pub fn get_mnemonic(&self) -> String {
+        let mnemonic = match self {
+            Class::In => "IN",
+            Class::Cs => "CS",
+            Class::Ch => "CH",
+            Class::Hs => "HS",
+            Class::Unknown(unknown) => return format!("CLASS{}", unknown),
+        };
+        mnemonic.to_string()
+    }
+}
+
 // Data:
 pub enum Data<'a> {
     // implemented types
@@ -182,6 +312,8 @@ pub enum Data<'a> {
     Txt(Vec<String>),
     Aaaa(std::net::Ipv6Addr),
     Srv(u16, u16, u16, String),
+    Nb(Vec<(NbFlags, std::net::Ipv4Addr)>),
+    Nbstat(Vec<NbstatName>, [u8; 6]),
 
     // non-existent types for:
     // unknown/not implemented data type, invalid/erroneous data
@@ -190,7 +322,13 @@ pub enum Data<'a> {
 }
 
 impl<'a> Data<'a> {
-    fn parse(raw: &[u8], offset: usize, length: usize, typ: Type, class: Class) -> Result<Data> {
+    fn parse(
+        raw: &[u8],
+        offset: usize,
+        length: usize,
+        typ: Type,
+        class: Class,
+    ) -> Result<Data<'_>> {
         let i = offset;
 
         // check offset and data length
@@ -260,16 +398,41 @@ impl<'a> Data<'a> {
                 let target = get_name(raw, i + 6)?;
                 Ok(Data::Srv(priority, weight, port, target))
             }
+            Type::Nb => Ok(Data::Nb(parse_nb(&raw[i..i + length])?)),
+            Type::Nbstat => {
+                let (names, unit_id) = parse_nbstat(&raw[i..i + length])?;
+                Ok(Data::Nbstat(names, unit_id))
+            }
             _ => Ok(Data::Unknown(&raw[i..i + length])),
         }
     }
 
-    fn get(raw: &[u8], offset: usize, length: usize, typ: Type, class: Class) -> Data {
+    fn get(raw: &[u8], offset: usize, length: usize, typ: Type, class: Class) -> Data<'_> {
         match Data::parse(raw, offset, length, typ, class) {
             Ok(data) => data,
             Err(_) => Data::Invalid(&raw[offset..offset + std::cmp::min(length, raw.len())]),
         }
     }
+
+    // get the data in presentation format as used in zone files
+    pub fn get_presentation(&self) -> String {
+        match self {
+            Data::Soa(mname, rname, serial, refresh, retry, expire, minimum) => format!(
+                "{} {} {} {} {} {} {}",
+                mname, rname, serial, refresh, retry, expire, minimum
+            ),
+            Data::Mx(preference, domain) => format!("{} {}", preference, domain),
+            Data::Txt(texts) => texts
+                .iter()
+                .map(|t| format!("{:?}", t))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Data::Srv(priority, weight, port, target) => {
+                format!("{} {} {} {}", priority, weight, port, target)
+            }
+            data => data.to_string(),
+        }
+    }
 }
 
 impl<'a> fmt::Display for Data<'a> {
@@ -288,6 +451,30 @@ impl<'a> fmt::Display for Data<'a> {
             Data::Aaaa(addr) => write!(f, "{}", addr),
             Data::Srv(priority, weight, port, target) => write!(f,
                 "{{priority: {}, weight: {}, port: {}, target: {}}}", priority, weight, port, target),
+            Data::Nb(entries) => {
+                write!(f, "[")?;
+                for (i, (flags, addr)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{{{}, address: {}}}", flags, addr)?;
+                }
+                write!(f, "]")
+            }
+            Data::Nbstat(names, unit_id) => {
+                write!(f, "{{names: [")?;
+                for (i, (name, flags)) in names.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{{name: {}, flags: {:#06x}}}", name, flags)?;
+                }
+                write!(
+                    f,
+                    "], unit id: {:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}}}",
+                    unit_id[0], unit_id[1], unit_id[2], unit_id[3], unit_id[4], unit_id[5]
+                )
+            }
             Data::Unknown(unknown) => write!(f, "unknown ({:?})", unknown),
             Data::Invalid(invalid) => write!(f, "invalid ({:?})", invalid),
         }
@@ -321,6 +508,9 @@ pub struct DnsRecord<'a> {
     // * data length (2 byte): next_index + 8
     // * data (data length bytes): next_index + 10
     next_index: usize,
+
+    // dns based protocol of the packet containing this record
+    protocol: Protocol,
 }
 
 impl<'a> DnsRecord<'a> {
@@ -330,7 +520,7 @@ impl<'a> DnsRecord<'a> {
     // * find first index of next fields in packet:
     //   type, class, ttl, data length, data.
     // TODO: add error handling
-    pub fn parse(raw: &'a [u8], offset: usize) -> Result<DnsRecord<'a>> {
+    pub fn parse(raw: &'a [u8], offset: usize, protocol: Protocol) -> Result<DnsRecord<'a>> {
         // check offset and minimum size
         if offset > raw.len() || raw.len() - offset < DNS_MIN_QUESTION_LENGTH {
             return Err(DnsError::RecordLength);
@@ -341,10 +531,11 @@ impl<'a> DnsRecord<'a> {
 
         // returm dns record
         Ok(DnsRecord {
-            raw: raw,
-            offset: offset,
-            label_indexes: label_indexes,
-            next_index: next_index,
+            raw,
+            offset,
+            label_indexes,
+            next_index,
+            protocol,
         })
     }
 
@@ -353,21 +544,49 @@ impl<'a> DnsRecord<'a> {
         self.next_index - self.offset
     }
 
-    // get the name from labels inside raw packet bytes
+    // get the name from labels inside raw packet bytes;
+    // in nbns, the name is an encoded netbios name
     pub fn get_name(&self) -> String {
-        get_name_from_labels(self.raw, &self.label_indexes).unwrap_or(String::from("<error>"))
+        match self.protocol {
+            Protocol::Nbns => get_netbios_name_from_labels(self.raw, &self.label_indexes),
+            _ => get_name_from_labels(self.raw, &self.label_indexes),
+        }
+        .unwrap_or(String::from("<error>"))
     }
 
     // get the type field from raw packet bytes
     pub fn get_type(&self) -> Type {
         let i = self.next_index;
-        read_be_u16(&self.raw[i..i + 2]).into()
+        match (self.protocol, read_be_u16(&self.raw[i..i + 2])) {
+            (Protocol::Nbns, 32) => Type::Nb,
+            (Protocol::Nbns, 33) => Type::Nbstat,
+            (_, typ) => typ.into(),
+        }
     }
 
-    // get the class field from raw packet bytes
+    // get the class field from raw packet bytes;
+    // in mdns, the top bit of the class field is a flag and not part of
+    // the class
     pub fn get_class(&self) -> Class {
         let i = self.next_index + 2;
-        read_be_u16(&self.raw[i..i + 2]).into()
+        let class = read_be_u16(&self.raw[i..i + 2]);
+        match self.protocol {
+            Protocol::Mdns => class & 0x7fff,
+            _ => class,
+        }
+        .into()
+    }
+
+    // get the mdns flag in the top bit of the class field from raw packet
+    // bytes: the unicast-response bit in questions and the cache-flush bit
+    // in resource records
+    pub fn get_mdns_flag(&self) -> u8 {
+        (self.raw[self.next_index + 2] & 0b10000000) >> 7
+    }
+
+    // get the dns based protocol of the record
+    pub fn get_protocol(&self) -> Protocol {
+        self.protocol
     }
 
     // get the ttl field from raw packet bytes;
@@ -386,7 +605,7 @@ impl<'a> DnsRecord<'a> {
 
     // get the data field from raw packet bytes;
     // note: do not use in dns question
-    pub fn get_data(&self) -> Data {
+    pub fn get_data(&self) -> Data<'_> {
         let i = self.next_index + 10;
         Data::get(
             self.raw,
@@ -412,10 +631,10 @@ pub struct DnsQuestion<'a> {
 impl<'a> DnsQuestion<'a> {
     // create a new dns question from raw packet bytes,
     // parse the question packet:
-    pub fn parse(raw: &'a [u8], offset: usize) -> Result<DnsQuestion<'a>> {
+    pub fn parse(raw: &'a [u8], offset: usize, protocol: Protocol) -> Result<DnsQuestion<'a>> {
         // create and return question
         Ok(DnsQuestion {
-            record: DnsRecord::parse(raw, offset)?,
+            record: DnsRecord::parse(raw, offset, protocol)?,
         })
     }
 
@@ -434,6 +653,11 @@ impl<'a> DnsQuestion<'a> {
         self.record.get_class()
     }
 
+    // get the mdns unicast-response (QU) bit from raw packet bytes
+    pub fn get_unicast_response(&self) -> u8 {
+        self.record.get_mdns_flag()
+    }
+
     // get the length of the question
     pub fn get_length(&self) -> usize {
         self.record.get_labels_length() + 4
@@ -444,11 +668,15 @@ impl<'a> fmt::Display for DnsQuestion<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{{name: {}, type: {}, class: {}}}",
+            "{{name: {}, type: {}, class: {}",
             self.get_name(),
             self.get_type(),
             self.get_class(),
-        )
+        )?;
+        if self.record.get_protocol() == Protocol::Mdns {
+            write!(f, ", unicast response: {}", self.get_unicast_response())?;
+        }
+        write!(f, "}}")
     }
 }
 
@@ -476,13 +704,13 @@ impl<'a> DnsAnswer<'a> {
     // find index of data length field,
     // find index of data field.
     // TODO: add error handling
-    pub fn parse(raw: &'a [u8], offset: usize) -> Result<DnsAnswer<'a>> {
+    pub fn parse(raw: &'a [u8], offset: usize, protocol: Protocol) -> Result<DnsAnswer<'a>> {
         if raw.len() - offset < DNS_MIN_ANSWER_LENGTH {
             return Err(DnsError::RecordLength);
         }
 
         Ok(DnsAnswer {
-            record: DnsRecord::parse(raw, offset)?,
+            record: DnsRecord::parse(raw, offset, protocol)?,
         })
     }
 
@@ -511,29 +739,50 @@ impl<'a> DnsAnswer<'a> {
         self.record.get_data_length()
     }
 
-    // get the data field from raw packet bytes;
-    fn get_data(&self) -> Data {
+    // get the data field from raw packet bytes
+    pub fn get_data(&self) -> Data<'_> {
         self.record.get_data()
     }
 
+    // get the mdns cache-flush bit from raw packet bytes
+    pub fn get_cache_flush(&self) -> u8 {
+        self.record.get_mdns_flag()
+    }
+
     // get the length of the answer
     pub fn get_length(&self) -> usize {
         self.record.get_labels_length() + 10 + usize::from(self.record.get_data_length())
     }
+
+    // get the answer in presentation format as a line of a zone file
+    pub fn get_presentation(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}",
+            self.get_name(),
+            self.get_ttl(),
+            self.get_class().get_mnemonic(),
+            self.get_type().get_mnemonic(),
+            self.get_data().get_presentation(),
+        )
+    }
 }
 
 impl<'a> fmt::Display for DnsAnswer<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{{name: {}, type: {}, class: {}, ttl: {}, data length: {}, data: {}}}",
+            "{{name: {}, type: {}, class: {}, ttl: {}, data length: {}, data: {}",
             self.get_name(),
             self.get_type(),
             self.get_class(),
             self.get_ttl(),
             self.get_data_length(),
             self.get_data(),
-        )
+        )?;
+        if self.record.get_protocol() == Protocol::Mdns {
+            write!(f, ", cache flush: {}", self.get_cache_flush())?;
+        }
+        write!(f, "}}")
     }
 }
 
@@ -0,0 +1,89 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::time::{Duration, Instant};
+
+// item in the reorder buffer, ordered by sequence number, with the time it
+// was added to the buffer
+struct Entry<T> {
+    seq: u64,
+    added: Instant,
+    item: T,
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.seq == other.seq
+    }
+}
+
+impl<T> Eq for Entry<T> {}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Entry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.seq.cmp(&other.seq)
+    }
+}
+
+// buffer that releases items ordered by their sequence numbers, so items
+// received from multiple threads are handled in the order they were
+// captured; items are held back for up to delay while items with lower
+// sequence numbers are missing, e.g., because they were dropped
+pub struct ReorderBuffer<T> {
+    delay: Duration,
+    next: u64,
+    entries: BinaryHeap<Reverse<Entry<T>>>,
+}
+
+impl<T> ReorderBuffer<T> {
+    pub fn new(delay: Duration) -> ReorderBuffer<T> {
+        ReorderBuffer {
+            delay,
+            next: 0,
+            entries: BinaryHeap::new(),
+        }
+    }
+
+    // add item with sequence number seq to the buffer
+    pub fn push(&mut self, seq: u64, item: T) {
+        self.entries.push(Reverse(Entry {
+            seq,
+            added: Instant::now(),
+            item,
+        }));
+    }
+
+    // remove and return the next item if no items before it are missing or
+    // it has been held long enough
+    pub fn pop(&mut self, now: Instant) -> Option<T> {
+        let Reverse(entry) = self.entries.peek()?;
+        if entry.seq > self.next && now.duration_since(entry.added) < self.delay {
+            return None;
+        }
+        self.next = self.next.max(entry.seq + 1);
+        self.entries.pop().map(|Reverse(entry)| entry.item)
+    }
+
+    // remove and return all items ordered by sequence number
+    pub fn drain(&mut self) -> Vec<T> {
+        let mut items = Vec::with_capacity(self.entries.len());
+        while let Some(Reverse(entry)) = self.entries.pop() {
+            items.push(entry.item);
+        }
+        items
+    }
+
+    // get the time until the next item is released if items before it are
+    // still missing
+    pub fn get_timeout(&self, now: Instant) -> Duration {
+        match self.entries.peek() {
+            Some(Reverse(entry)) => self.delay.saturating_sub(now.duration_since(entry.added)),
+            None => self.delay,
+        }
+    }
+}
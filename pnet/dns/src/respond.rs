@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, UdpSocket};
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::builder::*;
+use crate::error::*;
+use crate::helpers::*;
+use crate::output::*;
+use crate::packet::*;
+use crate::record::*;
+
+// ttl of records in responses
+const RESPONSE_TTL: u32 = 300;
+
+// maximum length of responses over udp, longer responses are truncated
+const MAX_UDP_LENGTH: usize = 512;
+
+// get the address in the reverse lookup name, e.g., "1.2.0.192.in-addr.arpa."
+fn get_reverse_address(name: &str) -> Option<IpAddr> {
+    let name = normalize_name(name);
+    if let Some(labels) = name.strip_suffix(".in-addr.arpa.") {
+        let mut octets: Vec<u8> = labels
+            .split('.')
+            .map(|l| l.parse().ok())
+            .collect::<Option<_>>()?;
+        octets.reverse();
+        let octets: [u8; 4] = octets.try_into().ok()?;
+        return Some(Ipv4Addr::from(octets).into());
+    }
+    let labels = name.strip_suffix(".ip6.arpa.")?;
+    let mut nibbles: Vec<u8> = labels
+        .split('.')
+        .map(|l| u8::from_str_radix(l, 16).ok().filter(|_| l.len() == 1))
+        .collect::<Option<_>>()?;
+    if nibbles.len() != 32 {
+        return None;
+    }
+    nibbles.reverse();
+    let addr = nibbles
+        .iter()
+        .fold(0u128, |addr, nibble| addr << 4 | u128::from(*nibble));
+    Some(Ipv6Addr::from(addr).into())
+}
+
+// table of host names and their addresses, e.g., from /etc/hosts
+#[derive(Default)]
+pub struct Hosts {
+    // addresses of host names
+    addrs: HashMap<String, Vec<IpAddr>>,
+
+    // first host name of addresses for reverse lookups
+    names: HashMap<IpAddr, String>,
+}
+
+impl Hosts {
+    // load hosts from file at path; the file contains an ip address followed
+    // by one or more host names per line, text after "#" is ignored
+    pub fn load(path: &Path) -> io::Result<Hosts> {
+        let mut hosts = Hosts::default();
+        for (i, line) in std::fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let mut fields = line.split_whitespace();
+            let addr = match fields.next() {
+                Some(addr) => addr,
+                None => continue,
+            };
+            let names: Vec<&str> = fields.collect();
+            let addr: IpAddr = match addr.parse() {
+                Ok(addr) if !names.is_empty() => addr,
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid entry in line {}: {}", i + 1, line.trim()),
+                    ))
+                }
+            };
+            for name in &names {
+                let addrs = hosts.addrs.entry(normalize_name(name)).or_default();
+                if !addrs.contains(&addr) {
+                    addrs.push(addr);
+                }
+            }
+            hosts
+                .names
+                .entry(addr)
+                .or_insert_with(|| normalize_name(names[0]));
+        }
+        Ok(hosts)
+    }
+
+    // get the answers to a question for name with type typ; returns none if
+    // the name does not exist
+    fn lookup(&self, name: &str, typ: Type) -> Option<Vec<(Type, Data<'_>)>> {
+        if let Some(addr) = get_reverse_address(name) {
+            let name = self.names.get(&addr)?;
+            return Some(match typ {
+                Type::Ptr | Type::All => vec![(Type::Ptr, Data::Ptr(name.clone()))],
+                _ => Vec::new(),
+            });
+        }
+        let addrs = self.addrs.get(&normalize_name(name))?;
+        Some(
+            addrs
+                .iter()
+                .filter_map(|addr| match (addr, typ) {
+                    (IpAddr::V4(addr), Type::A | Type::All) => Some((Type::A, Data::A(*addr))),
+                    (IpAddr::V6(addr), Type::Aaaa | Type::All) => {
+                        Some((Type::Aaaa, Data::Aaaa(*addr)))
+                    }
+                    _ => None,
+                })
+                .collect(),
+        )
+    }
+
+    // get the authoritative response to the query; only standard queries
+    // with a single question of class internet are answered
+    pub fn answer(&self, query: &DnsPacket) -> Result<DnsBuilder> {
+        let mut response = DnsBuilder::response(query)?;
+        response.set_aa(1);
+        let question = match query.get_question(0) {
+            Some(question) if query.get_questions() == 1 => question,
+            _ => {
+                response.set_rcode(RCode::FormatError);
+                return Ok(response);
+            }
+        };
+        if query.get_opcode() != OpCode::Query || question.get_class() != Class::In {
+            response.set_rcode(RCode::NotImplemented);
+            return Ok(response);
+        }
+        let name = question.get_name();
+        let answers = match self.lookup(&name, question.get_type()) {
+            Some(answers) => answers,
+            None => {
+                response.set_rcode(RCode::NameError);
+                return Ok(response);
+            }
+        };
+        for (typ, data) in &answers {
+            response.add_answer(&name, *typ, Class::In, RESPONSE_TTL, data)?;
+        }
+
+        // truncate responses that do not fit into an udp packet
+        if response.get_length() > MAX_UDP_LENGTH {
+            response = DnsBuilder::response(query)?;
+            response.set_aa(1);
+            response.set_tc(1);
+        }
+        Ok(response)
+    }
+}
+
+// answer dns queries received on socket with the hosts and print the
+// queries and the result to output
+pub fn serve(socket: &UdpSocket, hosts: &Hosts, output: &Output) -> io::Result<()> {
+    let mut buf = [0; 65535];
+    loop {
+        let (len, src) = socket.recv_from(&mut buf)?;
+        let now = SystemTime::now();
+        let query = match DnsPacket::parse(&buf[..len]) {
+            Ok(query) if query.get_qr() == 0 => query,
+            Ok(_) => continue,
+            Err(e) => {
+                let text = format!("malformed dns packet: {}", e);
+                output.event(now, "dns query", Some(src.to_string()), &text);
+                continue;
+            }
+        };
+        let response = match hosts.answer(&query) {
+            Ok(response) => response.build(),
+            Err(e) => {
+                let text = format!("could not answer query: {}", e);
+                output.event(now, "dns query", Some(src.to_string()), &text);
+                continue;
+            }
+        };
+        socket.send_to(&response, src)?;
+
+        // print the query with the result
+        let text = match (query.get_question(0), DnsPacket::parse(&response)) {
+            (Some(question), Ok(response)) => format!(
+                "{{question: {}, rcode: {}, answers: {}}}",
+                question,
+                response.get_rcode(),
+                response.get_answers()
+            ),
+            _ => format!("{}", query),
+        };
+        output.event(now, "dns query", Some(src.to_string()), &text);
+    }
+}
@@ -0,0 +1,233 @@
+use std::io;
+use std::mem;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// interval in which packet drops of sockets are read from the kernel
+pub const DROPS_INTERVAL: Duration = Duration::from_secs(1);
+
+// set socket option opt on level of socket fd to value
+pub fn set_option(
+    fd: libc::c_int,
+    level: libc::c_int,
+    opt: libc::c_int,
+    value: &[u8],
+) -> io::Result<()> {
+    // safety: the option value is a valid buffer of the given length
+    let ret = unsafe {
+        libc::setsockopt(
+            fd,
+            level,
+            opt,
+            value.as_ptr() as *const libc::c_void,
+            value.len() as libc::socklen_t,
+        )
+    };
+    match ret {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+// bind the socket fd to the network interface name, so only packets
+// received on it are handled
+#[cfg(target_os = "linux")]
+pub fn bind_to_interface(fd: libc::c_int, name: &str) -> io::Result<()> {
+    set_option(fd, libc::SOL_SOCKET, libc::SO_BINDTODEVICE, name.as_bytes())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn bind_to_interface(_fd: libc::c_int, _name: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "binding to interfaces is only supported on linux",
+    ))
+}
+
+// set the kernel receive buffer of socket fd to size bytes; the kernel
+// limits the size, e.g., on linux with net.core.rmem_max
+pub fn set_receive_buffer(fd: libc::c_int, size: usize) -> io::Result<()> {
+    let size = libc::c_int::try_from(size).unwrap_or(libc::c_int::MAX);
+    set_option(fd, libc::SOL_SOCKET, libc::SO_RCVBUF, &size.to_ne_bytes())
+}
+
+// enable packet info on the ipv6 socket fd, so the destination addresses
+// of received packets are passed along with them
+#[cfg(target_os = "linux")]
+pub fn set_receive_pktinfo6(fd: libc::c_int) -> io::Result<()> {
+    let on: libc::c_int = 1;
+    set_option(
+        fd,
+        libc::IPPROTO_IPV6,
+        libc::IPV6_RECVPKTINFO,
+        &on.to_ne_bytes(),
+    )
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_receive_pktinfo6(_fd: libc::c_int) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "packet info is only supported on linux",
+    ))
+}
+
+// wait until socket fd is readable or timeout passed; returns whether the
+// socket is readable
+pub fn poll_readable(fd: libc::c_int, timeout: Duration) -> io::Result<bool> {
+    let mut pollfd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let timeout = libc::c_int::try_from(timeout.as_millis()).unwrap_or(libc::c_int::MAX);
+
+    // safety: pollfd is a valid array of one entry
+    match unsafe { libc::poll(&mut pollfd, 1, timeout) } {
+        n if n < 0 => Err(io::Error::last_os_error()),
+        0 => Ok(false),
+        _ => Ok(true),
+    }
+}
+
+// get the ip address in the socket address addr
+fn get_address(addr: &libc::sockaddr_storage) -> IpAddr {
+    // safety: the address family identifies the type of the address
+    match addr.ss_family as libc::c_int {
+        libc::AF_INET6 => {
+            let addr = unsafe { *(addr as *const _ as *const libc::sockaddr_in6) };
+            IpAddr::V6(Ipv6Addr::from(addr.sin6_addr.s6_addr))
+        }
+        _ => {
+            let addr = unsafe { *(addr as *const _ as *const libc::sockaddr_in) };
+            IpAddr::V4(Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr)))
+        }
+    }
+}
+
+// get the destination address in the ipv6 packet info of the control
+// messages in msg
+#[cfg(target_os = "linux")]
+fn get_pktinfo_destination(msg: &libc::msghdr) -> Option<IpAddr> {
+    // safety: the control messages are within the control buffer of msg
+    unsafe {
+        let mut cmsg = libc::CMSG_FIRSTHDR(msg);
+        while !cmsg.is_null() {
+            if (*cmsg).cmsg_level == libc::IPPROTO_IPV6 && (*cmsg).cmsg_type == libc::IPV6_PKTINFO {
+                let info = (libc::CMSG_DATA(cmsg) as *const libc::in6_pktinfo).read_unaligned();
+                return Some(IpAddr::V6(Ipv6Addr::from(info.ipi6_addr.s6_addr)));
+            }
+            cmsg = libc::CMSG_NXTHDR(msg, cmsg);
+        }
+    }
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_pktinfo_destination(_msg: &libc::msghdr) -> Option<IpAddr> {
+    None
+}
+
+// receive a packet from socket fd into buf; returns its length, its source
+// address and its destination address if packet info is enabled
+pub fn recv_with_destination(
+    fd: libc::c_int,
+    buf: &mut [u8],
+) -> io::Result<(usize, IpAddr, Option<IpAddr>)> {
+    // safety: addr, control and buf are valid buffers of the given lengths
+    let mut addr: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let mut control = [0u64; 16];
+    let mut iov = libc::iovec {
+        iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+        iov_len: buf.len(),
+    };
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_name = &mut addr as *mut _ as *mut libc::c_void;
+    msg.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+    msg.msg_controllen = mem::size_of_val(&control) as _;
+    let len = unsafe { libc::recvmsg(fd, &mut msg, 0) };
+    if len < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok((
+        len as usize,
+        get_address(&addr),
+        get_pktinfo_destination(&msg),
+    ))
+}
+
+// get the inode of socket fd
+fn get_inode(fd: libc::c_int) -> Option<u64> {
+    // safety: stat is a valid buffer for the status of fd
+    let mut stat: libc::stat = unsafe { mem::zeroed() };
+    match unsafe { libc::fstat(fd, &mut stat) } {
+        0 => Some(stat.st_ino as u64),
+        _ => None,
+    }
+}
+
+// counter of packets the kernel dropped on a raw socket because its receive
+// buffer was full; the drops are read from /proc/net/raw or /proc/net/raw6,
+// so they are only counted on linux
+pub struct SocketDrops {
+    table: &'static str,
+    inode: Option<u64>,
+    dropped: u64,
+    checked: Instant,
+    counter: Arc<AtomicU64>,
+}
+
+impl SocketDrops {
+    // create a counter of drops on the ipv4 or ipv6 raw socket fd that adds
+    // new drops to counter
+    pub fn new(fd: libc::c_int, ipv6: bool, counter: Arc<AtomicU64>) -> SocketDrops {
+        SocketDrops {
+            table: match ipv6 {
+                true => "/proc/net/raw6",
+                false => "/proc/net/raw",
+            },
+            inode: get_inode(fd),
+            dropped: 0,
+            checked: Instant::now(),
+            counter,
+        }
+    }
+
+    // read the drops of the socket from the kernel
+    fn read(&self) -> Option<u64> {
+        let inode = self.inode?.to_string();
+        let table = std::fs::read_to_string(self.table).ok()?;
+
+        // fields: sl local_address rem_address st tx_queue:rx_queue
+        // tr:tm->when retrnsmt uid timeout inode ref pointer drops
+        table
+            .lines()
+            .skip(1)
+            .map(|line| line.split_whitespace().collect::<Vec<&str>>())
+            .find(|fields| fields.get(9) == Some(&inode.as_str()))
+            .and_then(|fields| fields.get(12)?.parse().ok())
+    }
+
+    // add new drops of the socket to the counter
+    pub fn update(&mut self) {
+        if let Some(dropped) = self.read() {
+            let new = dropped.saturating_sub(self.dropped);
+            self.counter.fetch_add(new, Ordering::Relaxed);
+            self.dropped = self.dropped.max(dropped);
+        }
+    }
+
+    // add new drops of the socket to the counter, if they were not read in
+    // the last interval before now
+    pub fn check(&mut self, now: Instant) {
+        if now.duration_since(self.checked) >= DROPS_INTERVAL {
+            self.checked = now;
+            self.update();
+        }
+    }
+}
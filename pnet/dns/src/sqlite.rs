@@ -0,0 +1,185 @@
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, Result};
+
+use crate::message::*;
+use crate::summary::*;
+use crate::transaction::*;
+
+// database schema: one row per message, one row per resource record of a
+// message and one row per finished or retransmitted transaction; times are
+// seconds since the unix epoch, latencies are milliseconds
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS messages (
+    id INTEGER PRIMARY KEY,
+    time REAL NOT NULL,
+    transport TEXT NOT NULL,
+    protocol TEXT NOT NULL,
+    src_addr TEXT NOT NULL,
+    src_port INTEGER NOT NULL,
+    dst_addr TEXT NOT NULL,
+    dst_port INTEGER NOT NULL,
+    size INTEGER NOT NULL,
+    dns_id INTEGER NOT NULL,
+    qr INTEGER NOT NULL,
+    opcode INTEGER NOT NULL,
+    aa INTEGER NOT NULL,
+    tc INTEGER NOT NULL,
+    rd INTEGER NOT NULL,
+    ra INTEGER NOT NULL,
+    rcode INTEGER NOT NULL,
+    qname TEXT,
+    qtype INTEGER,
+    qclass INTEGER,
+    raw BLOB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS messages_qname ON messages (qname);
+CREATE TABLE IF NOT EXISTS records (
+    message_id INTEGER NOT NULL REFERENCES messages (id),
+    section TEXT NOT NULL,
+    name TEXT NOT NULL,
+    type INTEGER NOT NULL,
+    class INTEGER NOT NULL,
+    ttl INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS records_message_id ON records (message_id);
+CREATE INDEX IF NOT EXISTS records_name ON records (name);
+CREATE TABLE IF NOT EXISTS transactions (
+    id INTEGER PRIMARY KEY,
+    time REAL NOT NULL,
+    protocol TEXT NOT NULL,
+    client_addr TEXT NOT NULL,
+    client_port INTEGER NOT NULL,
+    server_addr TEXT NOT NULL,
+    server_port INTEGER NOT NULL,
+    dns_id INTEGER NOT NULL,
+    qname TEXT,
+    qtype INTEGER,
+    qclass INTEGER,
+    result TEXT NOT NULL,
+    rcode INTEGER,
+    latency REAL,
+    retransmissions INTEGER NOT NULL
+);
+";
+
+// seconds since the unix epoch of time
+fn get_timestamp(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+// sink that writes dns messages and transactions into a sqlite database
+pub struct SqliteSink {
+    conn: Connection,
+}
+
+impl SqliteSink {
+    // open or create the database at path and create missing tables
+    pub fn open(path: &Path) -> Result<SqliteSink> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(SqliteSink { conn })
+    }
+
+    // write the message msg with its parsed dns packet and all its records
+    pub fn add_message(&mut self, msg: &Message, packet: &Summary) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        let question = packet.get_question();
+        tx.execute(
+            "INSERT INTO messages (time, transport, protocol, src_addr, src_port, \
+            dst_addr, dst_port, size, dns_id, qr, opcode, aa, tc, rd, ra, rcode, \
+            qname, qtype, qclass, raw) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, \
+            ?15, ?16, ?17, ?18, ?19, ?20)",
+            params![
+                get_timestamp(msg.time),
+                msg.transport.to_string(),
+                msg.protocol.to_string(),
+                msg.src.ip().to_string(),
+                msg.src.port(),
+                msg.dst.ip().to_string(),
+                msg.dst.port(),
+                msg.data.len(),
+                packet.id,
+                packet.qr,
+                u8::from(packet.opcode),
+                packet.aa,
+                packet.tc,
+                packet.rd,
+                packet.ra,
+                u8::from(packet.rcode),
+                question.map(|q| &q.name),
+                question.map(|q| u16::from(q.typ)),
+                question.map(|q| u16::from(q.class)),
+                msg.data,
+            ],
+        )?;
+        let message_id = tx.last_insert_rowid();
+
+        // write records of all sections
+        let answers = packet.answers.iter().map(|r| ("answer", r));
+        let authorities = packet.authorities.iter().map(|r| ("authority", r));
+        let additionals = packet.additionals.iter().map(|r| ("additional", r));
+        for (section, record) in answers.chain(authorities).chain(additionals) {
+            tx.execute(
+                "INSERT INTO records (message_id, section, name, type, class, ttl, data) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    message_id,
+                    section,
+                    record.name,
+                    u16::from(record.typ),
+                    u16::from(record.class),
+                    record.ttl,
+                    record.data,
+                ],
+            )?;
+        }
+        tx.commit()
+    }
+
+    // write the transaction finished or retransmitted at time
+    pub fn add_transaction(&mut self, time: SystemTime, transaction: &Transaction) -> Result<()> {
+        let (result, rcode, latency) = match &transaction.outcome {
+            Outcome::Answered(rcode, latency) => (
+                "answered",
+                Some(u8::from(*rcode)),
+                Some(latency.as_secs_f64() * 1000.0),
+            ),
+            Outcome::Retransmitted(_) => ("retransmitted", None, None),
+            Outcome::Orphaned(rcode) => ("orphaned", Some(u8::from(*rcode)), None),
+            Outcome::Unanswered => ("unanswered", None, None),
+        };
+        let key = &transaction.key;
+        let question = key.question.as_ref();
+        self.conn.execute(
+            "INSERT INTO transactions (time, protocol, client_addr, client_port, \
+            server_addr, server_port, dns_id, qname, qtype, qclass, result, rcode, \
+            latency, retransmissions) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                get_timestamp(time),
+                transaction.protocol.to_string(),
+                key.client.ip().to_string(),
+                key.client.port(),
+                key.server.ip().to_string(),
+                key.server.port(),
+                key.id,
+                question.map(|q| q.name.clone()),
+                question.map(|q| u16::from(q.typ)),
+                question.map(|q| u16::from(q.class)),
+                result,
+                rcode,
+                latency,
+                transaction.retransmissions,
+            ],
+        )?;
+        Ok(())
+    }
+}
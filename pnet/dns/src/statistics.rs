@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::message::*;
+use crate::packet::*;
+use crate::record::*;
+use crate::summary::*;
+
+// number of labels of a name used as its domain suffix, e.g.,
+// "example.com." for "www.example.com."
+const SUFFIX_LABELS: usize = 2;
+
+// get the domain suffix of name
+fn get_suffix(name: &str) -> String {
+    let labels: Vec<&str> = name.split('.').filter(|l| !l.is_empty()).collect();
+    let start = labels.len().saturating_sub(SUFFIX_LABELS);
+    format!("{}.", labels[start..].join(".")).to_lowercase()
+}
+
+// get the entries of map sorted by count in descending order; entries with
+// the same count are sorted by their name
+fn sort_by_count<K: ToString, V>(map: &HashMap<K, V>, count: fn(&V) -> u64) -> Vec<(String, &V)> {
+    let mut entries: Vec<(String, &V)> = map.iter().map(|(k, v)| (k.to_string(), v)).collect();
+    entries.sort_by(|a, b| count(b.1).cmp(&count(a.1)).then_with(|| a.0.cmp(&b.0)));
+    entries
+}
+
+// increase the counter of key in map
+fn count<K: Eq + Hash>(map: &mut HashMap<K, u64>, key: K) {
+    *map.entry(key).or_default() += 1;
+}
+
+// message statistics of a dns server
+#[derive(Default)]
+pub struct ServerStatistics {
+    queries: u64,
+    responses: u64,
+    nxdomain: u64,
+}
+
+impl fmt::Display for ServerStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{queries: {}, responses: {}, nxdomain: {}}}",
+            self.queries, self.responses, self.nxdomain
+        )
+    }
+}
+
+// response statistics of a domain suffix
+#[derive(Default)]
+pub struct SuffixStatistics {
+    responses: u64,
+    nxdomain: u64,
+}
+
+impl SuffixStatistics {
+    // get the share of responses that were nxdomain in percent
+    pub fn get_nxdomain_rate(&self) -> f64 {
+        match self.responses {
+            0 => 0.0,
+            responses => self.nxdomain as f64 * 100.0 / responses as f64,
+        }
+    }
+}
+
+impl fmt::Display for SuffixStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{responses: {}, nxdomain: {}, nxdomain rate: {:.2}%}}",
+            self.responses,
+            self.nxdomain,
+            self.get_nxdomain_rate()
+        )
+    }
+}
+
+// counters of packets and messages lost in the capture stage, shared with
+// the listeners and workers
+#[derive(Clone, Default)]
+pub struct Drops {
+    // messages dropped because the queue of the workers was full
+    pub queue: Arc<AtomicU64>,
+
+    // packets dropped by the kernel because socket receive buffers were full
+    pub socket: Arc<AtomicU64>,
+
+    // packets truncated because they were larger than the receive buffer
+    pub truncated: Arc<AtomicU64>,
+}
+
+// counters of dns messages per rcode, qtype, server and domain suffix
+#[derive(Default)]
+pub struct Statistics {
+    queries: u64,
+    responses: u64,
+    malformed: u64,
+    bytes: u64,
+
+    // packets and messages lost before they were handled, counted by the
+    // capture stage
+    drops: Drops,
+
+    // capture times of the first and last message
+    first: Option<SystemTime>,
+    last: Option<SystemTime>,
+
+    rcodes: HashMap<RCode, u64>,
+    qtypes: HashMap<Type, u64>,
+    servers: HashMap<IpAddr, ServerStatistics>,
+    suffixes: HashMap<String, SuffixStatistics>,
+}
+
+impl Statistics {
+    pub fn new() -> Statistics {
+        Statistics::default()
+    }
+
+    // get the counters of lost packets and messages
+    pub fn get_drops(&self) -> Drops {
+        self.drops.clone()
+    }
+
+    // get the time between the first and the last message in seconds
+    pub fn get_duration(&self) -> f64 {
+        match (self.first, self.last) {
+            (Some(first), Some(last)) => {
+                last.duration_since(first).unwrap_or_default().as_secs_f64()
+            }
+            _ => 0.0,
+        }
+    }
+
+    // count size and capture time of message msg
+    fn add_message(&mut self, msg: &Message) {
+        self.bytes += msg.data.len() as u64;
+        self.first.get_or_insert(msg.time);
+        self.last = self.last.max(Some(msg.time));
+    }
+
+    // count the malformed dns message msg
+    pub fn add_malformed(&mut self, msg: &Message) {
+        self.malformed += 1;
+        self.add_message(msg);
+    }
+
+    // count the dns packet in msg
+    pub fn add(&mut self, msg: &Message, packet: &Summary) {
+        self.add_message(msg);
+        let question = packet.get_question();
+
+        // count queries per qtype and server
+        if packet.qr == 0 {
+            self.queries += 1;
+            if let Some(question) = question {
+                count(&mut self.qtypes, question.typ);
+            }
+            self.servers.entry(msg.dst.ip()).or_default().queries += 1;
+            return;
+        }
+
+        // count responses per rcode, server and domain suffix
+        self.responses += 1;
+        let rcode = packet.rcode;
+        let nxdomain = matches!(rcode, RCode::NameError);
+        count(&mut self.rcodes, rcode);
+        let server = self.servers.entry(msg.src.ip()).or_default();
+        server.responses += 1;
+        server.nxdomain += u64::from(nxdomain);
+        if let Some(question) = question {
+            let suffix = self.suffixes.entry(get_suffix(&question.name)).or_default();
+            suffix.responses += 1;
+            suffix.nxdomain += u64::from(nxdomain);
+        }
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl Statistics {
+    // get the counters in the prometheus text exposition format
+    pub fn get_metrics(&self) -> String {
+        let mut metrics = String::new();
+        let mut add = |name: &str, help: &str, values: Vec<(String, u64)>| {
+            metrics.push_str(&format!(
+                "# HELP {} {}\n# TYPE {} counter\n",
+                name, help, name
+            ));
+            for (labels, value) in values {
+                metrics.push_str(&format!("{}{} {}\n", name, labels, value));
+            }
+        };
+        add(
+            "dns_queries_total",
+            "Number of dns queries.",
+            vec![(String::new(), self.queries)],
+        );
+        add(
+            "dns_responses_total",
+            "Number of dns responses.",
+            vec![(String::new(), self.responses)],
+        );
+        add(
+            "dns_parse_errors_total",
+            "Number of malformed dns messages.",
+            vec![(String::new(), self.malformed)],
+        );
+        add(
+            "dns_dropped_total",
+            "Number of dns messages dropped before they were handled.",
+            vec![(String::new(), self.drops.queue.load(Ordering::Relaxed))],
+        );
+        add(
+            "dns_socket_dropped_total",
+            "Number of packets dropped by the kernel because socket receive buffers were full.",
+            vec![(String::new(), self.drops.socket.load(Ordering::Relaxed))],
+        );
+        add(
+            "dns_truncated_total",
+            "Number of packets truncated because they were larger than the receive buffer.",
+            vec![(String::new(), self.drops.truncated.load(Ordering::Relaxed))],
+        );
+        add(
+            "dns_bytes_total",
+            "Number of bytes in dns messages.",
+            vec![(String::new(), self.bytes)],
+        );
+        add(
+            "dns_responses_by_rcode_total",
+            "Number of dns responses per rcode.",
+            self.rcodes
+                .iter()
+                .map(|(rcode, count)| (format!("{{rcode=\"{}\"}}", u8::from(*rcode)), *count))
+                .collect(),
+        );
+        add(
+            "dns_queries_by_qtype_total",
+            "Number of dns queries per qtype.",
+            self.qtypes
+                .iter()
+                .map(|(qtype, count)| (format!("{{qtype=\"{}\"}}", qtype.get_mnemonic()), *count))
+                .collect(),
+        );
+        metrics
+    }
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{messages: {}, queries: {}, responses: {}, malformed: {}, dropped: {}, \
+            socket dropped: {}, truncated: {}, bytes: {}, duration: {:.3}s}}",
+            self.queries + self.responses + self.malformed,
+            self.queries,
+            self.responses,
+            self.malformed,
+            self.drops.queue.load(Ordering::Relaxed),
+            self.drops.socket.load(Ordering::Relaxed),
+            self.drops.truncated.load(Ordering::Relaxed),
+            self.bytes,
+            self.get_duration()
+        )?;
+        for (rcode, count) in sort_by_count(&self.rcodes, |c| *c) {
+            write!(f, "\n  rcode {}: {}", rcode, count)?;
+        }
+        for (qtype, count) in sort_by_count(&self.qtypes, |c| *c) {
+            write!(f, "\n  qtype {}: {}", qtype, count)?;
+        }
+        for (server, stats) in sort_by_count(&self.servers, |s| s.queries + s.responses) {
+            write!(f, "\n  server {}: {}", server, stats)?;
+        }
+        for (suffix, stats) in sort_by_count(&self.suffixes, |s| s.responses) {
+            write!(f, "\n  suffix {}: {}", suffix, stats)?;
+        }
+        Ok(())
+    }
+}
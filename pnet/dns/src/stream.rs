@@ -0,0 +1,218 @@
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+// maximum number of out of order segments kept per stream
+const MAX_PENDING_SEGMENTS: usize = 64;
+
+// time after which idle streams are removed
+const STREAM_TIMEOUT: Duration = Duration::from_secs(120);
+
+// tcp stream identified by source and destination address/port;
+// each direction of a tcp connection is a separate stream
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct StreamKey {
+    pub src: SocketAddr,
+    pub dst: SocketAddr,
+}
+
+impl StreamKey {
+    // get the key of the stream in the opposite direction
+    pub fn reverse(&self) -> StreamKey {
+        StreamKey {
+            src: self.dst,
+            dst: self.src,
+        }
+    }
+}
+
+// reassembled data of a single tcp stream direction
+pub struct Stream {
+    // sequence number of the next expected in-order byte
+    next_seq: Option<u32>,
+
+    // in-order data that has not been consumed yet
+    buffer: Vec<u8>,
+
+    // out of order segments, keyed by their sequence number
+    pending: BTreeMap<u32, Vec<u8>>,
+
+    // time the stream was last updated
+    last_seen: Instant,
+}
+
+impl Stream {
+    fn new() -> Stream {
+        Stream {
+            next_seq: None,
+            buffer: Vec::new(),
+            pending: BTreeMap::new(),
+            last_seen: Instant::now(),
+        }
+    }
+
+    // add a tcp segment with sequence number seq to the stream
+    fn add(&mut self, seq: u32, syn: bool, data: &[u8]) {
+        self.last_seen = Instant::now();
+
+        // syn consumes one sequence number, data starts after it
+        if syn {
+            self.next_seq = Some(seq.wrapping_add(1));
+            return;
+        }
+        if data.is_empty() {
+            return;
+        }
+
+        // if the start of the stream was missed, start at this segment
+        let next = *self.next_seq.get_or_insert(seq);
+
+        // segment is ahead of expected data, keep it for later
+        let ahead = seq.wrapping_sub(next) as i32;
+        if ahead > 0 {
+            if self.pending.len() < MAX_PENDING_SEGMENTS {
+                self.pending.insert(seq, data.to_vec());
+            }
+            return;
+        }
+
+        // segment overlaps already received data (retransmission),
+        // only append the new part; an offset of i32::MIN cannot be negated
+        self.append(ahead.unsigned_abs() as usize, data);
+
+        // append pending segments that are in order now
+        while let Some(&seq) = self.pending.keys().next() {
+            let next = self.next_seq.unwrap_or(seq);
+            let ahead = seq.wrapping_sub(next) as i32;
+            if ahead > 0 {
+                break;
+            }
+            if let Some(data) = self.pending.remove(&seq) {
+                self.append(ahead.unsigned_abs() as usize, &data);
+            }
+        }
+    }
+
+    // append data to buffer skipping the first skip bytes
+    fn append(&mut self, skip: usize, data: &[u8]) {
+        if skip >= data.len() {
+            return;
+        }
+        let data = &data[skip..];
+        self.buffer.extend_from_slice(data);
+        if let Some(next) = self.next_seq {
+            self.next_seq = Some(next.wrapping_add(data.len() as u32));
+        }
+    }
+
+    // get in-order data of the stream that has not been consumed yet
+    pub fn data(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    // remove the first n bytes of in-order data from the stream
+    pub fn consume(&mut self, n: usize) {
+        self.buffer.drain(..std::cmp::min(n, self.buffer.len()));
+    }
+
+    // get the next dns message from the stream; messages over tcp are
+    // prefixed with a two byte length field
+    pub fn next_dns_message(&mut self) -> Option<Vec<u8>> {
+        let data = self.data();
+        if data.len() < 2 {
+            return None;
+        }
+        let length = usize::from(u16::from_be_bytes([data[0], data[1]]));
+        if data.len() < 2 + length {
+            return None;
+        }
+        let msg = data[2..2 + length].to_vec();
+        self.consume(2 + length);
+        Some(msg)
+    }
+}
+
+// collection of tcp streams
+pub struct Streams {
+    streams: HashMap<StreamKey, Stream>,
+
+    // time idle streams were last removed
+    last_expire: Instant,
+}
+
+impl Default for Streams {
+    fn default() -> Streams {
+        Streams::new()
+    }
+}
+
+impl Streams {
+    pub fn new() -> Streams {
+        Streams {
+            streams: HashMap::new(),
+            last_expire: Instant::now(),
+        }
+    }
+
+    // add a tcp segment to the stream identified by key and return the
+    // stream
+    pub fn add(&mut self, key: StreamKey, seq: u32, syn: bool, data: &[u8]) -> &mut Stream {
+        self.expire();
+        let stream = self.streams.entry(key).or_insert_with(Stream::new);
+        stream.add(seq, syn, data);
+        stream
+    }
+
+    // remove the stream identified by key
+    pub fn remove(&mut self, key: &StreamKey) -> Option<Stream> {
+        self.streams.remove(key)
+    }
+
+    // remove streams that have been idle for too long
+    fn expire(&mut self) {
+        let now = Instant::now();
+        if now.duration_since(self.last_expire) < Duration::from_secs(1) {
+            return;
+        }
+        self.last_expire = now;
+        self.streams
+            .retain(|_, s| now.duration_since(s.last_seen) < STREAM_TIMEOUT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_in_order_and_retransmitted_segments() {
+        let mut stream = Stream::new();
+        stream.add(100, true, &[]);
+        stream.add(101, false, b"abc");
+        stream.add(102, false, b"bcde");
+        assert_eq!(stream.data(), b"abcde");
+    }
+
+    #[test]
+    fn add_out_of_order_segments() {
+        let mut stream = Stream::new();
+        stream.add(u32::MAX, true, &[]);
+        stream.add(3, false, b"de");
+        stream.add(0, false, b"abc");
+        assert_eq!(stream.data(), b"abcde");
+    }
+
+    #[test]
+    fn add_segment_at_most_distant_offset() {
+        let mut stream = Stream::new();
+        stream.add(0, true, &[]);
+        stream.add(1, false, b"abc");
+
+        // the offset of this segment to the next expected byte is i32::MIN
+        let seq = 4u32.wrapping_add(1 << 31);
+        stream.add(seq, false, b"xyz");
+        assert_eq!(stream.data(), b"abc");
+        stream.add(4, false, b"def");
+        assert_eq!(stream.data(), b"abcdef");
+    }
+}
@@ -0,0 +1,130 @@
+use crate::packet::*;
+use crate::record::*;
+
+// question of a dns packet
+pub struct QuestionSummary {
+    pub name: String,
+    pub typ: Type,
+    pub class: Class,
+}
+
+// resource record of a dns packet
+pub struct RecordSummary {
+    pub name: String,
+    pub typ: Type,
+    #[cfg(feature = "sqlite")]
+    pub class: Class,
+    #[cfg(feature = "sqlite")]
+    pub ttl: u32,
+
+    // data of the record as written to the database, in presentation
+    // format unless it is invalid, and the serial number of soa records
+    #[cfg(feature = "sqlite")]
+    pub data: String,
+    pub presentation: Option<String>,
+    pub serial: Option<u32>,
+
+    // the whole record as printed in zone transfers
+    pub text: String,
+}
+
+impl RecordSummary {
+    fn new(record: &DnsAnswer) -> RecordSummary {
+        let data = record.get_data();
+        RecordSummary {
+            name: record.get_name(),
+            typ: record.get_type(),
+            #[cfg(feature = "sqlite")]
+            class: record.get_class(),
+            #[cfg(feature = "sqlite")]
+            ttl: record.get_ttl(),
+            #[cfg(feature = "sqlite")]
+            data: data.to_string(),
+            presentation: match data {
+                Data::Invalid(_) => None,
+                _ => Some(data.get_presentation()),
+            },
+            serial: match data {
+                Data::Soa(_, _, serial, _, _, _, _) => Some(serial),
+                _ => None,
+            },
+            text: record.to_string(),
+        }
+    }
+}
+
+// header fields, questions and resource records of a parsed dns packet;
+// unlike the packet, the summary does not borrow the message, so workers
+// can parse messages and send their summaries to the processing stage;
+// fields only written to the database are only kept if it is supported
+pub struct Summary {
+    pub id: u16,
+    pub qr: u8,
+    #[cfg(feature = "sqlite")]
+    pub opcode: OpCode,
+    #[cfg(feature = "sqlite")]
+    pub aa: u8,
+    #[cfg(feature = "sqlite")]
+    pub tc: u8,
+    #[cfg(feature = "sqlite")]
+    pub rd: u8,
+    #[cfg(feature = "sqlite")]
+    pub ra: u8,
+    pub b: u8,
+    pub rcode: RCode,
+    pub questions: Vec<QuestionSummary>,
+    pub answers: Vec<RecordSummary>,
+    #[cfg(feature = "sqlite")]
+    pub authorities: Vec<RecordSummary>,
+    #[cfg(feature = "sqlite")]
+    pub additionals: Vec<RecordSummary>,
+}
+
+impl Summary {
+    pub fn new(packet: &DnsPacket) -> Summary {
+        let questions = (0..packet.get_questions().into())
+            .map_while(|i| packet.get_question(i))
+            .map(|q| QuestionSummary {
+                name: q.get_name(),
+                typ: q.get_type(),
+                class: q.get_class(),
+            })
+            .collect();
+        Summary {
+            id: packet.get_id(),
+            qr: packet.get_qr(),
+            #[cfg(feature = "sqlite")]
+            opcode: packet.get_opcode(),
+            #[cfg(feature = "sqlite")]
+            aa: packet.get_aa(),
+            #[cfg(feature = "sqlite")]
+            tc: packet.get_tc(),
+            #[cfg(feature = "sqlite")]
+            rd: packet.get_rd(),
+            #[cfg(feature = "sqlite")]
+            ra: packet.get_ra(),
+            b: packet.get_b(),
+            rcode: packet.get_rcode(),
+            questions,
+            answers: (0..packet.get_answers().into())
+                .map_while(|i| packet.get_answer(i))
+                .map(RecordSummary::new)
+                .collect(),
+            #[cfg(feature = "sqlite")]
+            authorities: (0..packet.get_authorities().into())
+                .map_while(|i| packet.get_authority(i))
+                .map(RecordSummary::new)
+                .collect(),
+            #[cfg(feature = "sqlite")]
+            additionals: (0..packet.get_additionals().into())
+                .map_while(|i| packet.get_additional(i))
+                .map(RecordSummary::new)
+                .collect(),
+        }
+    }
+
+    // get the first question
+    pub fn get_question(&self) -> Option<&QuestionSummary> {
+        self.questions.first()
+    }
+}
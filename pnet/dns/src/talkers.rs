@@ -0,0 +1,130 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime};
+
+use crate::message::*;
+use crate::summary::*;
+
+// query sent by a client
+struct Query {
+    time: SystemTime,
+    client: IpAddr,
+    bytes: u64,
+    name: Option<String>,
+}
+
+// query statistics of a client
+#[derive(Default)]
+struct ClientCounters {
+    queries: u64,
+    bytes: u64,
+}
+
+// tracker of the clients sending the most queries and the most queried
+// names within a sliding time window
+pub struct TopTalkers {
+    // number of entries in reports and length of the time window
+    top: usize,
+    window: Duration,
+
+    // queries in the time window, oldest first
+    queries: VecDeque<Query>,
+
+    // counters of the queries in the time window
+    clients: HashMap<IpAddr, ClientCounters>,
+    names: HashMap<String, u64>,
+}
+
+impl TopTalkers {
+    pub fn new(top: usize, window: Duration) -> TopTalkers {
+        TopTalkers {
+            top,
+            window,
+            queries: VecDeque::new(),
+            clients: HashMap::new(),
+            names: HashMap::new(),
+        }
+    }
+
+    // count the dns packet in msg if it is a query
+    pub fn add(&mut self, msg: &Message, packet: &Summary) {
+        self.expire(msg.time);
+        if packet.qr != 0 {
+            return;
+        }
+        let query = Query {
+            time: msg.time,
+            client: msg.src.ip(),
+            bytes: msg.data.len() as u64,
+            name: packet.get_question().map(|q| q.name.to_lowercase()),
+        };
+        let client = self.clients.entry(query.client).or_default();
+        client.queries += 1;
+        client.bytes += query.bytes;
+        if let Some(name) = &query.name {
+            *self.names.entry(name.clone()).or_default() += 1;
+        }
+        self.queries.push_back(query);
+    }
+
+    // remove queries that are older than the time window
+    pub fn expire(&mut self, now: SystemTime) {
+        while let Some(query) = self.queries.front() {
+            if now.duration_since(query.time).unwrap_or_default() <= self.window {
+                break;
+            }
+            if let Some(client) = self.clients.get_mut(&query.client) {
+                client.queries -= 1;
+                client.bytes -= query.bytes;
+                if client.queries == 0 {
+                    self.clients.remove(&query.client);
+                }
+            }
+            if let Some(name) = &query.name {
+                if let Some(count) = self.names.get_mut(name) {
+                    *count -= 1;
+                    if *count == 0 {
+                        self.names.remove(name);
+                    }
+                }
+            }
+            self.queries.pop_front();
+        }
+    }
+}
+
+impl fmt::Display for TopTalkers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{window: {}s, queries: {}, clients: {}, names: {}}}",
+            self.window.as_secs(),
+            self.queries.len(),
+            self.clients.len(),
+            self.names.len(),
+        )?;
+
+        // top clients by number of queries
+        let mut clients: Vec<_> = self.clients.iter().collect();
+        clients.sort_by(|a, b| b.1.queries.cmp(&a.1.queries).then(a.0.cmp(b.0)));
+        for (client, counters) in clients.into_iter().take(self.top) {
+            write!(
+                f,
+                "\n  client {}: {{queries: {}, bytes: {}, rate: {:.2}/s}}",
+                client,
+                counters.queries,
+                counters.bytes,
+                counters.queries as f64 / self.window.as_secs_f64(),
+            )?;
+        }
+
+        // top queried names
+        let mut names: Vec<_> = self.names.iter().collect();
+        names.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        for (name, count) in names.into_iter().take(self.top) {
+            write!(f, "\n  name {}: {}", name, count)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::SystemTime;
+
+use packet_utils::bytes::*;
+
+use crate::stream::*;
+
+pub const TLS_RECORD_HEADER_LENGTH: usize = 5;
+
+// ContentType:
+// type of a tls record:
+// 20              change_cipher_spec
+// 21              alert
+// 22              handshake
+// 23              application_data
+// 24              heartbeat
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ContentType {
+    ChangeCipherSpec,
+    Alert,
+    Handshake,
+    ApplicationData,
+    Heartbeat,
+    Unknown(u8),
+}
+
+impl From<u8> for ContentType {
+    fn from(typ: u8) -> ContentType {
+        match typ {
+            20 => ContentType::ChangeCipherSpec,
+            21 => ContentType::Alert,
+            22 => ContentType::Handshake,
+            23 => ContentType::ApplicationData,
+            24 => ContentType::Heartbeat,
+            unknown => ContentType::Unknown(unknown),
+        }
+    }
+}
+
+impl fmt::Display for ContentType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContentType::ChangeCipherSpec => write!(f, "20 (change cipher spec)"),
+            ContentType::Alert => write!(f, "21 (alert)"),
+            ContentType::Handshake => write!(f, "22 (handshake)"),
+            ContentType::ApplicationData => write!(f, "23 (application data)"),
+            ContentType::Heartbeat => write!(f, "24 (heartbeat)"),
+            ContentType::Unknown(unknown) => write!(f, "{} (unknown)", unknown),
+        }
+    }
+}
+
+// tls record header consists of the following fields:
+//
+// Content Type (8 bits)
+// Legacy Record Version (16 bits)
+// Length (16 bits)
+//
+// the record's (encrypted) payload of Length bytes follows the header
+pub struct TlsRecord {
+    content_type: ContentType,
+    version: u16,
+    length: u16,
+}
+
+impl TlsRecord {
+    // get the next complete tls record from a reassembled tcp stream and
+    // remove it from the stream
+    pub fn next(stream: &mut Stream) -> Option<TlsRecord> {
+        let data = stream.data();
+        if data.len() < TLS_RECORD_HEADER_LENGTH {
+            return None;
+        }
+        let record = TlsRecord {
+            content_type: data[0].into(),
+            version: read_be_u16(&data[1..3]),
+            length: read_be_u16(&data[3..5]),
+        };
+
+        // if the start of the connection was missed, the stream is most
+        // likely not aligned to record boundaries; drop data that does
+        // not look like a record header
+        if matches!(record.content_type, ContentType::Unknown(_)) || record.version >> 8 != 3 {
+            stream.consume(data.len());
+            return None;
+        }
+        let length = TLS_RECORD_HEADER_LENGTH + usize::from(record.length);
+        if data.len() < length {
+            return None;
+        }
+        stream.consume(length);
+        Some(record)
+    }
+
+    // get the content type of the record
+    pub fn get_content_type(&self) -> ContentType {
+        self.content_type
+    }
+
+    // get the legacy record version of the record
+    pub fn get_version(&self) -> u16 {
+        self.version
+    }
+
+    // get the payload length of the record
+    pub fn get_length(&self) -> u16 {
+        self.length
+    }
+}
+
+// traffic statistics of one direction of a tls connection
+#[derive(Default)]
+pub struct TlsCounters {
+    packets: u64,
+    bytes: u64,
+    handshake: u64,
+    change_cipher_spec: u64,
+    alert: u64,
+    application_data: u64,
+    application_bytes: u64,
+    other: u64,
+}
+
+impl TlsCounters {
+    // count a tls record
+    fn add_record(&mut self, record: &TlsRecord) {
+        match record.get_content_type() {
+            ContentType::Handshake => self.handshake += 1,
+            ContentType::ChangeCipherSpec => self.change_cipher_spec += 1,
+            ContentType::Alert => self.alert += 1,
+            ContentType::ApplicationData => {
+                self.application_data += 1;
+                self.application_bytes += u64::from(record.get_length());
+            }
+            _ => self.other += 1,
+        }
+    }
+}
+
+impl fmt::Display for TlsCounters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{packets: {}, bytes: {}, handshake: {}, change cipher spec: {}, alert: {}, \
+            application data: {} ({} bytes), other: {}}}",
+            self.packets,
+            self.bytes,
+            self.handshake,
+            self.change_cipher_spec,
+            self.alert,
+            self.application_data,
+            self.application_bytes,
+            self.other,
+        )
+    }
+}
+
+// statistics of a tls connection (e.g., dns over tls) between a client
+// and a server
+pub struct TlsConnection {
+    client: SocketAddr,
+    server: SocketAddr,
+
+    // capture time of first and last packet
+    start: SystemTime,
+    last: SystemTime,
+
+    // record version of the first record
+    version: Option<u16>,
+
+    // statistics of client to server and server to client direction
+    to_server: TlsCounters,
+    to_client: TlsCounters,
+
+    // fin seen from client and server
+    client_fin: bool,
+    server_fin: bool,
+}
+
+impl TlsConnection {
+    pub fn new(time: SystemTime, client: SocketAddr, server: SocketAddr) -> TlsConnection {
+        TlsConnection {
+            client,
+            server,
+            start: time,
+            last: time,
+            version: None,
+            to_server: TlsCounters::default(),
+            to_client: TlsCounters::default(),
+            client_fin: false,
+            server_fin: false,
+        }
+    }
+
+    // count a tcp packet with length bytes of payload sent by src at time
+    pub fn add_packet(&mut self, time: SystemTime, src: SocketAddr, length: usize, fin: bool) {
+        self.last = self.last.max(time);
+        let counters = if src == self.client {
+            self.client_fin |= fin;
+            &mut self.to_server
+        } else {
+            self.server_fin |= fin;
+            &mut self.to_client
+        };
+        counters.packets += 1;
+        counters.bytes += length as u64;
+    }
+
+    // count a tls record sent by src
+    pub fn add_record(&mut self, src: SocketAddr, record: &TlsRecord) {
+        self.version.get_or_insert(record.get_version());
+        if src == self.client {
+            self.to_server.add_record(record);
+        } else {
+            self.to_client.add_record(record);
+        }
+    }
+
+    // get the capture time of the last packet
+    pub fn get_last(&self) -> SystemTime {
+        self.last
+    }
+
+    // check if both sides closed the connection
+    pub fn is_closed(&self) -> bool {
+        self.client_fin && self.server_fin
+    }
+}
+
+impl fmt::Display for TlsConnection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{client: {}, server: {}, duration: {:.3}s, version: ",
+            self.client,
+            self.server,
+            self.last
+                .duration_since(self.start)
+                .unwrap_or_default()
+                .as_secs_f64(),
+        )?;
+        match self.version {
+            Some(version) => write!(f, "{:#06x}", version)?,
+            None => write!(f, "none")?,
+        }
+        write!(
+            f,
+            ", to server: {}, to client: {}}}",
+            self.to_server, self.to_client
+        )
+    }
+}
@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, SystemTime};
+
+use crate::message::*;
+use crate::packet::*;
+use crate::protocol::*;
+use crate::record::*;
+use crate::summary::*;
+
+// time after which queries without a response are considered unanswered
+const TRANSACTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+// question of a dns transaction
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Question {
+    pub name: String,
+    pub typ: Type,
+    pub class: Class,
+}
+
+impl Question {
+    // get the first question of the dns packet
+    fn from_packet(packet: &Summary) -> Option<Question> {
+        let question = packet.get_question()?;
+        Some(Question {
+            name: question.name.to_lowercase(),
+            typ: question.typ,
+            class: question.class,
+        })
+    }
+}
+
+impl fmt::Display for Question {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{name: {}, type: {}, class: {}}}",
+            self.name, self.typ, self.class
+        )
+    }
+}
+
+// dns transaction identified by client, server, transaction id and
+// question; if queries are sent to a multicast or broadcast address, the
+// server is the unspecified address
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TransactionKey {
+    pub client: SocketAddr,
+    pub server: SocketAddr,
+    pub id: u16,
+    pub question: Option<Question>,
+}
+
+// result of a dns transaction
+pub enum Outcome {
+    // response to a query with its rcode and the resolution latency
+    // measured from the first query
+    Answered(RCode, Duration),
+
+    // query sent again before a response, with the time since the
+    // previous query; the transaction is still outstanding
+    Retransmitted(Duration),
+
+    // response without a query
+    Orphaned(RCode),
+
+    // query without a response
+    Unanswered,
+}
+
+// finished or retransmitted dns transaction
+pub struct Transaction {
+    pub key: TransactionKey,
+    pub protocol: Protocol,
+    pub outcome: Outcome,
+
+    // number of times the query was retransmitted
+    pub retransmissions: u32,
+}
+
+impl fmt::Display for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{protocol: {}, client: {}, server: {}, id: {}, question: ",
+            self.protocol, self.key.client, self.key.server, self.key.id,
+        )?;
+        match &self.key.question {
+            Some(question) => write!(f, "{}", question)?,
+            None => write!(f, "none")?,
+        }
+        match &self.outcome {
+            Outcome::Answered(rcode, latency) => write!(
+                f,
+                ", result: answered, rcode: {}, latency: {:.3}ms",
+                rcode,
+                latency.as_secs_f64() * 1000.0
+            )?,
+            Outcome::Retransmitted(interval) => write!(
+                f,
+                ", result: retransmitted, interval: {:.3}ms",
+                interval.as_secs_f64() * 1000.0
+            )?,
+            Outcome::Orphaned(rcode) => write!(f, ", result: orphaned, rcode: {}", rcode)?,
+            Outcome::Unanswered => write!(f, ", result: unanswered")?,
+        }
+        write!(f, ", retransmissions: {}}}", self.retransmissions)
+    }
+}
+
+// get the unspecified address in the address family of addr with port
+fn unspecified(addr: SocketAddr, port: u16) -> SocketAddr {
+    match addr.ip() {
+        IpAddr::V4(_) => SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), port),
+        IpAddr::V6(_) => SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), port),
+    }
+}
+
+// check if the query in msg is sent to a group of servers
+fn is_group_query(msg: &Message, packet: &Summary) -> bool {
+    match msg.dst.ip() {
+        IpAddr::V4(ip) if ip.is_broadcast() => true,
+        ip if ip.is_multicast() => true,
+        _ => msg.protocol == Protocol::Nbns && packet.b == 1,
+    }
+}
+
+// outstanding query of a dns transaction
+struct Query {
+    protocol: Protocol,
+
+    // time the query was first sent and last retransmitted
+    first: SystemTime,
+    last: SystemTime,
+
+    // number of times the query was retransmitted
+    retransmissions: u32,
+}
+
+// query statistics of a dns server
+#[derive(Default)]
+pub struct ServerCounters {
+    queries: u64,
+    retransmissions: u64,
+}
+
+impl ServerCounters {
+    // get the share of queries that were retransmissions in percent
+    pub fn get_retransmission_rate(&self) -> f64 {
+        match self.queries {
+            0 => 0.0,
+            queries => self.retransmissions as f64 * 100.0 / queries as f64,
+        }
+    }
+}
+
+impl fmt::Display for ServerCounters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{queries: {}, retransmissions: {}, retransmission rate: {:.2}%}}",
+            self.queries,
+            self.retransmissions,
+            self.get_retransmission_rate()
+        )
+    }
+}
+
+// tracker that pairs dns responses with their queries and detects
+// retransmitted queries
+#[derive(Default)]
+pub struct Transactions {
+    // outstanding queries
+    queries: HashMap<TransactionKey, Query>,
+
+    // query statistics per server
+    servers: HashMap<SocketAddr, ServerCounters>,
+}
+
+impl Transactions {
+    pub fn new() -> Transactions {
+        Transactions::default()
+    }
+
+    // handle the dns packet in msg and return all transactions finished or
+    // retransmitted by it, including queries that timed out without a
+    // response
+    pub fn add(&mut self, msg: &Message, packet: &Summary) -> Vec<Transaction> {
+        let mut transactions = self.expire(msg.time);
+
+        // mdns responses are sent to the multicast group and cannot be
+        // paired with queries
+        if msg.protocol == Protocol::Mdns {
+            return transactions;
+        }
+
+        // messages with an unknown destination, e.g., from ipv6 sockets
+        // without packet info, cannot be paired either
+        if msg.dst.ip().is_unspecified() {
+            return transactions;
+        }
+
+        // remember queries; a query with the same id and question as an
+        // outstanding query is a retransmission
+        if packet.qr == 0 {
+            let server = match is_group_query(msg, packet) {
+                true => unspecified(msg.dst, msg.dst.port()),
+                false => msg.dst,
+            };
+            let key = TransactionKey {
+                client: msg.src,
+                server,
+                id: packet.id,
+                question: Question::from_packet(packet),
+            };
+            let counters = self.servers.entry(server).or_default();
+            counters.queries += 1;
+            match self.queries.get_mut(&key) {
+                Some(query) => {
+                    counters.retransmissions += 1;
+                    query.retransmissions += 1;
+                    let interval = msg.time.duration_since(query.last).unwrap_or_default();
+                    query.last = msg.time;
+                    transactions.push(Transaction {
+                        key,
+                        protocol: msg.protocol,
+                        outcome: Outcome::Retransmitted(interval),
+                        retransmissions: query.retransmissions,
+                    });
+                }
+                None => {
+                    let query = Query {
+                        protocol: msg.protocol,
+                        first: msg.time,
+                        last: msg.time,
+                        retransmissions: 0,
+                    };
+                    self.queries.insert(key, query);
+                }
+            }
+            return transactions;
+        }
+
+        // pair responses with queries
+        let mut key = TransactionKey {
+            client: msg.dst,
+            server: msg.src,
+            id: packet.id,
+            question: Question::from_packet(packet),
+        };
+        let mut query = self.queries.remove(&key);
+        if query.is_none() {
+            let server = key.server;
+            key.server = unspecified(server, server.port());
+            query = self.queries.remove(&key);
+            key.server = server;
+        }
+        let transaction = match query {
+            Some(query) => Transaction {
+                key,
+                protocol: msg.protocol,
+                outcome: Outcome::Answered(
+                    packet.rcode,
+                    msg.time.duration_since(query.first).unwrap_or_default(),
+                ),
+                retransmissions: query.retransmissions,
+            },
+            None => Transaction {
+                key,
+                protocol: msg.protocol,
+                outcome: Outcome::Orphaned(packet.rcode),
+                retransmissions: 0,
+            },
+        };
+        transactions.push(transaction);
+        transactions
+    }
+
+    // get the query statistics of server
+    pub fn get_server(&self, server: &SocketAddr) -> Option<&ServerCounters> {
+        self.servers.get(server)
+    }
+
+    // remove queries that did not get a response in time
+    fn expire(&mut self, now: SystemTime) -> Vec<Transaction> {
+        let expired: Vec<TransactionKey> = self
+            .queries
+            .iter()
+            .filter(|(_, query)| {
+                now.duration_since(query.last).unwrap_or_default() > TRANSACTION_TIMEOUT
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        expired
+            .into_iter()
+            .filter_map(|key| {
+                let query = self.queries.remove(&key)?;
+                Some(Transaction {
+                    key,
+                    protocol: query.protocol,
+                    outcome: Outcome::Unanswered,
+                    retransmissions: query.retransmissions,
+                })
+            })
+            .collect()
+    }
+}
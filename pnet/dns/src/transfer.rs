@@ -0,0 +1,122 @@
+use std::fmt;
+
+use crate::record::*;
+use crate::summary::*;
+
+// zone transfer (AXFR or IXFR) spanning one or more dns messages on
+// a single tcp connection.
+//
+// an AXFR response starts with the zone's SOA record, followed by all
+// other records of the zone, and is terminated by the same SOA record.
+//
+// an IXFR response starts with the zone's current SOA record. It is
+// either followed by
+// * nothing, if the client is up to date,
+// * all records of the zone like an AXFR (terminated by the SOA), or
+// * one or more difference sequences, each consisting of the old SOA,
+//   deleted records, the new SOA, and added records, and is terminated
+//   by the current SOA record.
+//
+// in all cases, the transfer ends with the first SOA record carrying the
+// opening SOA's serial that appears where a new difference sequence
+// (i.e., an old SOA) would be expected.
+pub struct ZoneTransfer {
+    // transferred zone and kind of transfer (AXFR or IXFR)
+    zone: String,
+    kind: Type,
+
+    // number of messages and transferred records
+    messages: usize,
+    records: Vec<String>,
+
+    // serial number of the opening SOA record
+    serial: Option<u32>,
+
+    // number of SOA records seen after the opening SOA record
+    soas: usize,
+
+    // transfer is complete
+    complete: bool,
+}
+
+impl ZoneTransfer {
+    // start a new zone transfer if packet is a response to a zone
+    // transfer request
+    pub fn start(packet: &Summary) -> Option<ZoneTransfer> {
+        if packet.qr != 1 {
+            return None;
+        }
+        let question = packet.get_question()?;
+        let kind = question.typ;
+        match kind {
+            Type::Axfr | Type::Ixfr => Some(ZoneTransfer {
+                zone: question.name.clone(),
+                kind,
+                messages: 0,
+                records: Vec::new(),
+                serial: None,
+                soas: 0,
+                complete: false,
+            }),
+            _ => None,
+        }
+    }
+
+    // add the records of the next message of the zone transfer
+    pub fn add(&mut self, packet: &Summary) {
+        self.messages += 1;
+        for answer in &packet.answers {
+            self.records.push(answer.text.clone());
+
+            // check soa records for start and end of transfer
+            let serial = match answer.serial {
+                Some(serial) => serial,
+                None => continue,
+            };
+            match self.serial {
+                None => self.serial = Some(serial),
+                Some(opening) => {
+                    self.soas += 1;
+                    if self.soas % 2 == 1 && serial == opening {
+                        self.complete = true;
+                        return;
+                    }
+                }
+            }
+        }
+
+        // an IXFR response with only the current SOA record means the
+        // client is already up to date
+        if self.kind == Type::Ixfr && self.messages == 1 && self.records.len() == 1 {
+            self.complete = self.serial.is_some();
+        }
+    }
+
+    // check if zone transfer is complete
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    // get the transferred records
+    pub fn get_records(&self) -> &[String] {
+        &self.records
+    }
+}
+
+impl fmt::Display for ZoneTransfer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{zone: {}, type: {}, complete: {}, messages: {}, records: {}, serial: ",
+            self.zone,
+            self.kind,
+            self.complete,
+            self.messages,
+            self.records.len(),
+        )?;
+        match self.serial {
+            Some(serial) => write!(f, "{}}}", serial),
+            None => write!(f, "none}}"),
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt;
+use std::io;
+use std::net::IpAddr;
+use std::path::Path;
+
+use crate::helpers::*;
+use crate::packet::*;
+use crate::record::*;
+
+// ip network given by an address and a prefix length
+#[derive(Clone, Debug)]
+pub struct Network {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl Network {
+    // parse a network in cidr notation, e.g., "192.0.2.0/24", or a single
+    // address
+    pub fn parse(s: &str) -> Option<Network> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr.parse().ok()?, Some(prefix.parse().ok()?)),
+            None => (s.parse().ok()?, None),
+        };
+        let max = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        let prefix = prefix.unwrap_or(max);
+        if prefix > max {
+            return None;
+        }
+        Some(Network { addr, prefix })
+    }
+
+    // check if addr is in the network
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.addr, addr) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(net) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(net) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+// watchlist of domain suffixes and ip networks
+#[derive(Default)]
+pub struct Watchlist {
+    suffixes: Vec<String>,
+    networks: Vec<Network>,
+}
+
+impl Watchlist {
+    // load a watchlist from file at path; the file contains one domain
+    // suffix or ip network per line, empty lines and lines starting with
+    // "#" are ignored
+    pub fn load(path: &Path) -> io::Result<Watchlist> {
+        let mut watchlist = Watchlist::default();
+        for (i, line) in std::fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(network) = Network::parse(line) {
+                watchlist.networks.push(network);
+                continue;
+            }
+            if line.contains('/') || line.contains(char::is_whitespace) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid entry in line {}: {}", i + 1, line),
+                ));
+            }
+            watchlist.suffixes.push(normalize_name(line));
+        }
+        Ok(watchlist)
+    }
+
+    // get the suffix that matches name
+    fn match_name(&self, name: &str) -> Option<&str> {
+        let name = normalize_name(name);
+        self.suffixes
+            .iter()
+            .find(|suffix| is_in_domain(&name, suffix))
+            .map(|suffix| suffix.as_str())
+    }
+
+    // get the network that contains addr
+    fn match_addr(&self, addr: IpAddr) -> Option<&Network> {
+        self.networks.iter().find(|network| network.contains(addr))
+    }
+
+    // check the names in questions and the names and data of answers in
+    // the dns packet against the watchlist and return all matches
+    pub fn check(&self, packet: &DnsPacket) -> Vec<String> {
+        let mut matches = Vec::new();
+        let add = |matches: &mut Vec<String>, m: String| {
+            if !matches.contains(&m) {
+                matches.push(m);
+            }
+        };
+        let check_name = |matches: &mut Vec<String>, name: &str| {
+            if let Some(suffix) = self.match_name(name) {
+                add(
+                    matches,
+                    format!("name {} matches {}", normalize_name(name), suffix),
+                );
+            }
+        };
+        for i in 0..packet.get_questions().into() {
+            if let Some(question) = packet.get_question(i) {
+                check_name(&mut matches, &question.get_name());
+            }
+        }
+        for i in 0..packet.get_answers().into() {
+            let answer = match packet.get_answer(i) {
+                Some(answer) => answer,
+                None => break,
+            };
+            check_name(&mut matches, &answer.get_name());
+            let addr = match answer.get_data() {
+                Data::A(addr) => IpAddr::from(addr),
+                Data::Aaaa(addr) => IpAddr::from(addr),
+                Data::Ns(name) | Data::Cname(name) | Data::Ptr(name) | Data::Mx(_, name) => {
+                    check_name(&mut matches, &name);
+                    continue;
+                }
+                Data::Srv(_, _, _, target) => {
+                    check_name(&mut matches, &target);
+                    continue;
+                }
+                _ => continue,
+            };
+            if let Some(network) = self.match_addr(addr) {
+                add(
+                    &mut matches,
+                    format!("address {} matches {}", addr, network),
+                );
+            }
+        }
+        matches
+    }
+}
@@ -0,0 +1,44 @@
+use clap::Parser;
+
+// command line configuration of the geneve tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Listen for geneve tunnel traffic and decode the inner packets")]
+pub struct Config {
+    // network interface to capture packets on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Capture packets on the network interface NAME; by default, \
+        the first interface that is up, is not a loopback and has an ip \
+        address is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., to see the \
+        tunnel traffic of other hosts on a mirrored port"
+    )]
+    pub promiscuous: bool,
+
+    // geneve ports
+    #[arg(
+        short,
+        long,
+        value_name = "PORT",
+        default_values_t = [6081],
+        help = "Decode udp traffic to the port PORT as geneve; can be \
+        specified multiple times"
+    )]
+    pub port: Vec<u16>,
+
+    // only print the tunnel table
+    #[arg(
+        short,
+        long,
+        help = "Only print the table of tunnels on exit instead of every packet"
+    )]
+    pub quiet: bool,
+}
@@ -0,0 +1,146 @@
+use packet_utils::dissect::{Inner, UdpPayload};
+use packet_utils::hex::to_hex;
+use pnet::packet::ethernet::EtherType;
+
+use std::fmt;
+
+// geneve version supported by this parser
+const VERSION: u8 = 0;
+
+// geneve protocol type of ethernet frames; ip packets use their ethertype
+const PROTOCOL_ETHERNET: u16 = 0x6558;
+
+// length of the geneve header without options
+const HEADER_LENGTH: usize = 8;
+
+// flags in the geneve header
+const FLAG_OAM: u8 = 0x80;
+const FLAG_CRITICAL: u8 = 0x40;
+
+// critical bit in the option type
+const OPTION_CRITICAL: u8 = 0x80;
+
+// option tlv in the geneve header
+pub struct GeneveOption {
+    pub class: u16,
+    pub option_type: u8,
+    pub data: Vec<u8>,
+}
+
+impl GeneveOption {
+    // check if the option must be understood by the receiver
+    pub fn is_critical(&self) -> bool {
+        self.option_type & OPTION_CRITICAL != 0
+    }
+}
+
+impl fmt::Display for GeneveOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{class: {:#06x}, type: {:#04x}",
+            self.class, self.option_type
+        )?;
+        if self.is_critical() {
+            write!(f, ", critical")?;
+        }
+        write!(f, ", data: {}}}", to_hex(&self.data))
+    }
+}
+
+// geneve header
+pub struct GeneveHeader {
+    pub oam: bool,
+    pub critical: bool,
+    pub protocol_type: u16,
+    pub vni: u32,
+    pub options: Vec<GeneveOption>,
+}
+
+impl GeneveHeader {
+    // parse the geneve header in bytes and get it with the inner packet
+    pub fn parse(bytes: &[u8]) -> Option<(GeneveHeader, &[u8])> {
+        // Header Format:
+        // version (2 bits), options length in 4 byte words (6 bits), oam
+        // flag (1 bit), critical options flag (1 bit), reserved (6 bits),
+        // protocol type (2), vni (3), reserved (1), options
+        let header = bytes.get(..HEADER_LENGTH)?;
+        if header[0] >> 6 != VERSION {
+            return None;
+        }
+        let options_length = usize::from(header[0] & 0x3f) * 4;
+        let options = bytes.get(HEADER_LENGTH..HEADER_LENGTH + options_length)?;
+
+        // Option Format:
+        // class (2), type (1), reserved (3 bits), length in 4 byte words (5
+        // bits), data
+        let mut parsed = Vec::new();
+        let mut offset = 0;
+        while offset < options.len() {
+            let option = options.get(offset..offset + 4)?;
+            let length = usize::from(option[3] & 0x1f) * 4;
+            let data = options.get(offset + 4..offset + 4 + length)?;
+            offset += 4 + length;
+            parsed.push(GeneveOption {
+                class: u16::from_be_bytes([option[0], option[1]]),
+                option_type: option[2],
+                data: data.to_vec(),
+            });
+        }
+
+        let header = GeneveHeader {
+            oam: header[1] & FLAG_OAM != 0,
+            critical: header[1] & FLAG_CRITICAL != 0,
+            protocol_type: u16::from_be_bytes([header[2], header[3]]),
+            vni: u32::from_be_bytes([0, header[4], header[5], header[6]]),
+            options: parsed,
+        };
+        Some((header, &bytes[HEADER_LENGTH + options_length..]))
+    }
+}
+
+impl fmt::Display for GeneveHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "geneve {{vni: {}, protocol: {:#06x}",
+            self.vni, self.protocol_type
+        )?;
+        if self.oam {
+            write!(f, ", oam")?;
+        }
+        if self.critical {
+            write!(f, ", critical")?;
+        }
+        if !self.options.is_empty() {
+            let options = self
+                .options
+                .iter()
+                .map(|o| o.to_string())
+                .collect::<Vec<_>>();
+            write!(f, ", options: [{}]", options.join(", "))?;
+        }
+        write!(f, "}}")
+    }
+}
+
+// decode the udp payload in bytes to destination port as geneve header with
+// its inner packet if the port is one of the geneve ports
+pub fn decode_geneve<'a>(
+    ports: &[u16],
+    destination: u16,
+    bytes: &'a [u8],
+) -> Option<UdpPayload<'a, GeneveHeader>> {
+    if !ports.contains(&destination) {
+        return None;
+    }
+    let (header, inner) = match GeneveHeader::parse(bytes) {
+        Some(parsed) => parsed,
+        None => return Some(UdpPayload::Undecoded("invalid geneve".to_string())),
+    };
+    let inner = match header.protocol_type {
+        PROTOCOL_ETHERNET => Inner::Ethernet(inner),
+        ethertype => Inner::Packet(EtherType(ethertype), inner),
+    };
+    Some(UdpPayload::Tunnel(header, inner))
+}
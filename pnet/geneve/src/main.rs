@@ -0,0 +1,143 @@
+extern crate pnet;
+
+mod config;
+mod geneve;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::dissect::{format_layers, Dissector, Layer, UdpDecoder};
+use packet_utils::interface::find_interface;
+use pnet::datalink::DataLinkReceiver;
+
+use std::collections::BTreeMap;
+use std::io;
+use std::net::IpAddr;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use config::Config;
+use geneve::{decode_geneve, GeneveHeader};
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// geneve tunnel identified by its outer source and destination address and
+// its vni
+type TunnelKey = (IpAddr, IpAddr, u32);
+
+// traffic seen in a geneve tunnel
+struct Tunnel {
+    packets: u64,
+    bytes: u64,
+    last_seen: Instant,
+}
+
+// get the tunnel of the outermost geneve layer in layers
+fn get_tunnel(layers: &[Layer<GeneveHeader>]) -> Option<TunnelKey> {
+    let mut addresses = None;
+    for layer in layers {
+        match layer {
+            Layer::Ip {
+                source,
+                destination,
+            } => addresses = Some((*source, *destination)),
+            Layer::Application(header) => {
+                let (source, destination) = addresses?;
+                return Some((source, destination, header.vni));
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+// receive packets, decode the geneve tunnel traffic and count it per tunnel
+// until running is unset
+fn listen(
+    rx: &mut dyn DataLinkReceiver,
+    tunnels: &mut BTreeMap<TunnelKey, Tunnel>,
+    config: &Config,
+    running: &AtomicBool,
+) {
+    let decode_udp: &UdpDecoder<GeneveHeader> =
+        &|_, destination, bytes| decode_geneve(&config.port, destination, bytes);
+    let dissector = Dissector::new(decode_udp);
+    while running.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading packets: {}", e),
+            },
+        };
+        let layers = dissector.dissect(frame);
+        let key = match get_tunnel(&layers) {
+            Some(key) => key,
+            None => continue,
+        };
+        if !config.quiet {
+            println!("{}", format_layers(&layers));
+        }
+        let now = Instant::now();
+        let tunnel = tunnels.entry(key).or_insert(Tunnel {
+            packets: 0,
+            bytes: 0,
+            last_seen: now,
+        });
+        tunnel.packets += 1;
+        tunnel.bytes += frame.len() as u64;
+        tunnel.last_seen = now;
+    }
+}
+
+// print the geneve tunnels
+fn print_tunnels(tunnels: &BTreeMap<TunnelKey, Tunnel>) {
+    println!("--- geneve tunnels ---");
+    println!(
+        "{:<39} {:<39} {:>8} {:>8} {:>10} {:>10}",
+        "SOURCE", "DESTINATION", "VNI", "PACKETS", "BYTES", "LAST SEEN"
+    );
+    for ((source, destination, vni), tunnel) in tunnels {
+        println!(
+            "{:<39} {:<39} {:>8} {:>8} {:>10} {:>8.1} s",
+            source.to_string(),
+            destination.to_string(),
+            vni,
+            tunnel.packets,
+            tunnel.bytes,
+            tunnel.last_seen.elapsed().as_secs_f64(),
+        );
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let mut rx = match create_channel(&interface, READ_TIMEOUT, config.promiscuous) {
+        Ok((_, rx)) => rx,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+
+    // stop on ctrl-c or sigterm and print the tunnels
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    let mut tunnels = BTreeMap::new();
+    listen(rx.as_mut(), &mut tunnels, &config, &running);
+    print_tunnels(&tunnels);
+}
@@ -0,0 +1,45 @@
+use clap::Parser;
+
+// command line configuration of the http tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Listen for http/1.x traffic and log its transactions")]
+pub struct Config {
+    // network interface to capture packets on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Capture packets on the network interface NAME; by default, \
+        the first interface that is up, is not a loopback and has an ip \
+        address is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., to see the http \
+        traffic of other hosts on a mirrored port"
+    )]
+    pub promiscuous: bool,
+
+    // server ports
+    #[arg(
+        short,
+        long,
+        value_name = "PORT",
+        default_values_t = [80],
+        help = "Listen for http traffic to and from the server port PORT; \
+        can be specified multiple times"
+    )]
+    pub port: Vec<u16>,
+
+    // also print requests and responses
+    #[arg(
+        short,
+        long,
+        help = "Print every request and response in addition to the \
+        transactions"
+    )]
+    pub verbose: bool,
+}
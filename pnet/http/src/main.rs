@@ -0,0 +1,129 @@
+extern crate pnet;
+
+mod config;
+mod message;
+mod transaction;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::dissect::parse_ip_frame;
+use packet_utils::interface::find_interface;
+use pnet::datalink::DataLinkReceiver;
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::tcp::{TcpFlags, TcpPacket};
+use pnet::packet::Packet;
+
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dns::{StreamKey, Streams};
+
+use config::Config;
+use transaction::Connections;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// reassembled tcp streams and http connections
+struct Tracker {
+    streams: Streams,
+    connections: Connections,
+}
+
+impl Tracker {
+    // add the tcp segment from source to destination to its stream and
+    // print the events of the http messages that are complete now
+    fn handle(&mut self, config: &Config, source: IpAddr, destination: IpAddr, segment: &[u8]) {
+        let tcp_packet = match TcpPacket::new(segment) {
+            Some(tcp_packet) => tcp_packet,
+            None => return,
+        };
+        let from_client = config.port.contains(&tcp_packet.get_destination());
+        if !from_client && !config.port.contains(&tcp_packet.get_source()) {
+            return;
+        }
+        let key = StreamKey {
+            src: SocketAddr::new(source, tcp_packet.get_source()),
+            dst: SocketAddr::new(destination, tcp_packet.get_destination()),
+        };
+
+        let now = Instant::now();
+        let flags = tcp_packet.get_flags();
+        let syn = flags & TcpFlags::SYN != 0;
+        let stream = self
+            .streams
+            .add(key, tcp_packet.get_sequence(), syn, tcp_packet.payload());
+        let mut events = self.connections.read(key, stream, from_client, now);
+
+        // remove closed connections and their streams
+        if flags & (TcpFlags::FIN | TcpFlags::RST) != 0 {
+            let reset = flags & TcpFlags::RST != 0;
+            let (mut closed_events, closed) = self.connections.shutdown(key, from_client, reset);
+            events.append(&mut closed_events);
+            if closed {
+                self.streams.remove(&key);
+                self.streams.remove(&key.reverse());
+            }
+        }
+
+        for event in events {
+            if config.verbose || matches!(event, transaction::Event::Transaction(_)) {
+                println!("{}", event);
+            }
+        }
+    }
+}
+
+// receive tcp segments, reassemble the http streams and print their
+// transactions until running is unset
+fn listen(rx: &mut dyn DataLinkReceiver, config: &Config, running: &AtomicBool) {
+    let mut tracker = Tracker {
+        streams: Streams::new(),
+        connections: Connections::default(),
+    };
+    while running.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading packets: {}", e),
+            },
+        };
+        let ip = parse_ip_frame(frame, false, false);
+        if let Some(ip) = ip.filter(|ip| ip.protocol == IpNextHeaderProtocols::Tcp) {
+            tracker.handle(config, ip.source, ip.destination, ip.payload);
+        }
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let mut rx = match create_channel(&interface, READ_TIMEOUT, config.promiscuous) {
+        Ok((_, rx)) => rx,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+
+    // stop on ctrl-c or sigterm
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    listen(rx.as_mut(), &config, &running);
+}
@@ -0,0 +1,215 @@
+use std::fmt;
+
+// maximum length of the start line and headers of a message; streams with
+// longer heads are not considered http
+pub const MAX_HEAD_LENGTH: usize = 64 * 1024;
+
+// find the position of the first crlf in data
+fn find_crlf(data: &[u8]) -> Option<usize> {
+    data.windows(2).position(|w| w == b"\r\n")
+}
+
+// get the length of the message head at the start of data including the
+// empty line that ends it, if the head is complete
+pub fn get_head_length(data: &[u8]) -> Option<usize> {
+    data.windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .map(|p| p + 4)
+}
+
+// start line and headers of an http/1.x request or response
+#[derive(Clone)]
+pub struct Head {
+    start: [String; 3],
+    headers: Vec<(String, String)>,
+}
+
+impl Head {
+    // parse the message head in bytes; the start line of a request consists
+    // of method, target and version, the start line of a response of
+    // version, status code and reason phrase
+    pub fn parse(bytes: &[u8]) -> Option<Head> {
+        let text = std::str::from_utf8(bytes).ok()?;
+        let mut lines = text.split("\r\n").filter(|l| !l.is_empty());
+
+        let mut parts = lines.next()?.splitn(3, ' ');
+        let start = [
+            parts.next()?.to_string(),
+            parts.next()?.to_string(),
+            parts.next().unwrap_or("").to_string(),
+        ];
+        let head = Head {
+            start,
+            headers: lines
+                .filter_map(|line| line.split_once(':'))
+                .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+                .collect(),
+        };
+        let valid = match head.is_response() {
+            true => head.get_status().is_some(),
+            false => {
+                head.start[2].starts_with("HTTP/1.")
+                    && head.start[0].bytes().all(|b| b.is_ascii_uppercase())
+            }
+        };
+        valid.then_some(head)
+    }
+
+    // check if the head is the head of a response
+    pub fn is_response(&self) -> bool {
+        self.start[0].starts_with("HTTP/1.")
+    }
+
+    // get the method of a request
+    pub fn get_method(&self) -> &str {
+        &self.start[0]
+    }
+
+    // get the status code of a response
+    pub fn get_status(&self) -> Option<u16> {
+        match self.is_response() {
+            true => self.start[1]
+                .parse()
+                .ok()
+                .filter(|s| (100..1000).contains(s)),
+            false => None,
+        }
+    }
+
+    // get the value of the first header with name
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    // get the length of the body in the content-length header
+    pub fn get_content_length(&self) -> Option<u64> {
+        self.get_header("Content-Length")?.parse().ok()
+    }
+
+    // check if the body uses the chunked transfer coding
+    pub fn is_chunked(&self) -> bool {
+        self.get_header("Transfer-Encoding")
+            .map(|v| v.to_ascii_lowercase().ends_with("chunked"))
+            .unwrap_or(false)
+    }
+
+    // get the start line
+    pub fn get_start_line(&self) -> String {
+        self.start.join(" ").trim_end().to_string()
+    }
+}
+
+impl fmt::Display for Head {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{{:?}", self.get_start_line())?;
+        for (name, value) in &self.headers {
+            write!(f, ", {}: {}", name, value)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+// state of a chunked body
+#[derive(Clone, Copy, Debug)]
+pub enum Chunk {
+    Size,
+    Data(u64),
+    DataEnd,
+    Trailer,
+}
+
+// framing of a message body that determines where the body ends
+#[derive(Clone, Copy, Debug)]
+pub enum Body {
+    Length(u64),
+    Chunked(Chunk),
+    UntilClose,
+}
+
+impl Body {
+    // get the framing of the body of the request head
+    pub fn of_request(head: &Head) -> Body {
+        match head.is_chunked() {
+            true => Body::Chunked(Chunk::Size),
+            false => Body::Length(head.get_content_length().unwrap_or(0)),
+        }
+    }
+
+    // get the framing of the body of the response head to a request with
+    // method; responses without length end when the connection is closed
+    pub fn of_response(head: &Head, method: Option<&str>) -> Body {
+        let status = head.get_status().unwrap_or(0);
+        if method == Some("HEAD") || status < 200 || status == 204 || status == 304 {
+            return Body::Length(0);
+        }
+        match (head.is_chunked(), head.get_content_length()) {
+            (true, _) => Body::Chunked(Chunk::Size),
+            (false, Some(length)) => Body::Length(length),
+            (false, None) => Body::UntilClose,
+        }
+    }
+
+    // read the body in data and get the number of bytes that belong to the
+    // body, the number of content bytes in them and if the body is complete
+    pub fn read(&mut self, data: &[u8]) -> (usize, u64, bool) {
+        let mut consumed = 0;
+        let mut content = 0;
+        loop {
+            let rest = &data[consumed..];
+            match *self {
+                Body::Length(length) => {
+                    let n = length.min(rest.len() as u64);
+                    *self = Body::Length(length - n);
+                    return (consumed + n as usize, content + n, length == n);
+                }
+                Body::UntilClose => {
+                    return (data.len(), content + rest.len() as u64, false);
+                }
+                Body::Chunked(Chunk::Size) => {
+                    let end = match find_crlf(rest) {
+                        Some(end) => end,
+                        None => return (consumed, content, false),
+                    };
+                    let line = String::from_utf8_lossy(&rest[..end]);
+                    let size = line.split(';').next().unwrap_or("").trim();
+                    *self = match u64::from_str_radix(size, 16) {
+                        Ok(0) => Body::Chunked(Chunk::Trailer),
+                        Ok(size) => Body::Chunked(Chunk::Data(size)),
+                        Err(_) => Body::UntilClose,
+                    };
+                    consumed += end + 2;
+                }
+                Body::Chunked(Chunk::Data(size)) => {
+                    let n = size.min(rest.len() as u64);
+                    consumed += n as usize;
+                    content += n;
+                    if n < size {
+                        *self = Body::Chunked(Chunk::Data(size - n));
+                        return (consumed, content, false);
+                    }
+                    *self = Body::Chunked(Chunk::DataEnd);
+                }
+                Body::Chunked(Chunk::DataEnd) => {
+                    if rest.len() < 2 {
+                        return (consumed, content, false);
+                    }
+                    consumed += 2;
+                    *self = Body::Chunked(Chunk::Size);
+                }
+                Body::Chunked(Chunk::Trailer) => {
+                    let end = match find_crlf(rest) {
+                        Some(end) => end,
+                        None => return (consumed, content, false),
+                    };
+                    consumed += end + 2;
+                    if end == 0 {
+                        return (consumed, content, true);
+                    }
+                }
+            }
+        }
+    }
+}
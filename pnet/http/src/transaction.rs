@@ -0,0 +1,380 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use dns::{Stream, StreamKey};
+
+use crate::message::{get_head_length, Body, Head, MAX_HEAD_LENGTH};
+
+// time after which idle connections are removed
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(120);
+
+// maximum number of connections before old ones are removed
+const MAX_CONNECTIONS: usize = 4096;
+
+// request of a client and the response of the server to it, if any
+pub struct Transaction {
+    client: SocketAddr,
+    server: SocketAddr,
+    request: Head,
+    response: Option<Head>,
+    length: u64,
+    start: Instant,
+    end: Instant,
+}
+
+impl fmt::Display for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "http transaction of client {} with server {}: {:?} host {}",
+            self.client,
+            self.server,
+            self.request.get_start_line(),
+            self.request.get_header("Host").unwrap_or("-"),
+        )?;
+        match &self.response {
+            Some(response) => write!(
+                f,
+                " -> {:?}, {} bytes ({:.3} ms)",
+                response.get_start_line(),
+                self.length,
+                self.end.duration_since(self.start).as_secs_f64() * 1000.0
+            ),
+            None => write!(f, " -> no response"),
+        }
+    }
+}
+
+// event in an http connection
+pub enum Event {
+    Request(StreamKey, Head),
+    Response(StreamKey, Head),
+    Transaction(Transaction),
+    NotHttp(StreamKey),
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Request(key, head) => {
+                write!(
+                    f,
+                    "got http request from {} to {}: {}",
+                    key.src, key.dst, head
+                )
+            }
+            Event::Response(key, head) => {
+                write!(
+                    f,
+                    "got http response from {} to {}: {}",
+                    key.src, key.dst, head
+                )
+            }
+            Event::Transaction(transaction) => write!(f, "{}", transaction),
+            Event::NotHttp(key) => write!(
+                f,
+                "got non-http data from {} to {}, ignoring stream",
+                key.src, key.dst
+            ),
+        }
+    }
+}
+
+// parsing state of one direction of a connection
+#[derive(Default)]
+struct Direction {
+    // framing of the body that is currently read, none when expecting a head
+    body: Option<Body>,
+
+    // direction does not contain http messages, e.g., after protocol
+    // switches or invalid data
+    ignore: bool,
+
+    // direction was closed with a fin
+    closed: bool,
+}
+
+impl Direction {
+    // read the next head from stream or skip the body that is currently
+    // read; returns the head, if a head was read, and if the body that was
+    // read is complete
+    fn read(&mut self, stream: &mut Stream, key: StreamKey, events: &mut Vec<Event>) -> Step {
+        if self.ignore {
+            stream.consume(stream.data().len());
+            return Step::Wait;
+        }
+        if let Some(body) = &mut self.body {
+            let (consumed, content, complete) = body.read(stream.data());
+            stream.consume(consumed);
+            if complete {
+                self.body = None;
+            }
+            return match (complete, content) {
+                (true, content) => Step::BodyEnd(content),
+                (false, 0) => Step::Wait,
+                (false, content) => Step::Body(content),
+            };
+        }
+        let length = match get_head_length(stream.data()) {
+            Some(length) => length,
+            None => {
+                if stream.data().len() > MAX_HEAD_LENGTH {
+                    self.ignore = true;
+                    events.push(Event::NotHttp(key));
+                }
+                return Step::Wait;
+            }
+        };
+        let head = Head::parse(&stream.data()[..length]);
+        stream.consume(length);
+        match head {
+            Some(head) => Step::Head(head),
+            None => {
+                self.ignore = true;
+                events.push(Event::NotHttp(key));
+                Step::Wait
+            }
+        }
+    }
+}
+
+// result of reading a stream
+enum Step {
+    Head(Head),
+    Body(u64),
+    BodyEnd(u64),
+    Wait,
+}
+
+// request that is waiting for its response
+struct Pending {
+    request: Head,
+    start: Instant,
+}
+
+// http/1.x connection of a client with a server; requests are matched with
+// responses in order, so pipelined requests are supported
+struct Connection {
+    client: Direction,
+    server: Direction,
+    requests: VecDeque<Pending>,
+    response: Option<(Head, Pending, u64)>,
+    last_seen: Instant,
+}
+
+impl Connection {
+    fn new(now: Instant) -> Connection {
+        Connection {
+            client: Direction::default(),
+            server: Direction::default(),
+            requests: VecDeque::new(),
+            response: None,
+            last_seen: now,
+        }
+    }
+
+    // read the requests of the client in stream
+    fn read_requests(&mut self, key: StreamKey, stream: &mut Stream, events: &mut Vec<Event>) {
+        loop {
+            match self.client.read(stream, key, events) {
+                Step::Head(head) => {
+                    self.client.body = Some(Body::of_request(&head));
+                    events.push(Event::Request(key, head.clone()));
+                    self.requests.push_back(Pending {
+                        request: head,
+                        start: self.last_seen,
+                    });
+                }
+                Step::Body(_) | Step::BodyEnd(_) => (),
+                Step::Wait => return,
+            }
+        }
+    }
+
+    // read the responses of the server in stream and get the transactions
+    // they finished
+    fn read_responses(&mut self, key: StreamKey, stream: &mut Stream, events: &mut Vec<Event>) {
+        loop {
+            match self.server.read(stream, key, events) {
+                Step::Head(head) => {
+                    events.push(Event::Response(key, head.clone()));
+
+                    // informational responses precede the final response
+                    let status = head.get_status().unwrap_or(0);
+                    if (100..200).contains(&status) && status != 101 {
+                        continue;
+                    }
+                    let pending = match self.requests.pop_front() {
+                        Some(pending) => pending,
+                        None => {
+                            self.server.ignore = true;
+                            events.push(Event::NotHttp(key));
+                            continue;
+                        }
+                    };
+                    let method = pending.request.get_method().to_string();
+                    self.server.body = Some(Body::of_response(&head, Some(&method)));
+                    self.response = Some((head, pending, 0));
+
+                    // the connection does not carry http after switching
+                    // protocols
+                    if status == 101 {
+                        self.client.ignore = true;
+                        self.server.ignore = true;
+                        self.server.body = None;
+                        self.finish(key.dst, key.src, events);
+                    }
+                }
+                Step::Body(content) => {
+                    if let Some((_, _, length)) = &mut self.response {
+                        *length += content;
+                    }
+                }
+                Step::BodyEnd(content) => {
+                    if let Some((_, _, length)) = &mut self.response {
+                        *length += content;
+                    }
+                    self.finish(key.dst, key.src, events);
+                }
+                Step::Wait => return,
+            }
+        }
+    }
+
+    // finish the transaction of the current response
+    fn finish(&mut self, client: SocketAddr, server: SocketAddr, events: &mut Vec<Event>) {
+        if let Some((response, pending, length)) = self.response.take() {
+            events.push(Event::Transaction(Transaction {
+                client,
+                server,
+                request: pending.request,
+                response: Some(response),
+                length,
+                start: pending.start,
+                end: self.last_seen,
+            }));
+        }
+    }
+
+    // finish all transactions when the connection is closed; responses
+    // without length end here and requests without responses are reported
+    fn close(&mut self, client: SocketAddr, server: SocketAddr, events: &mut Vec<Event>) {
+        self.finish(client, server, events);
+        for pending in self.requests.drain(..) {
+            events.push(Event::Transaction(Transaction {
+                client,
+                server,
+                request: pending.request,
+                response: None,
+                length: 0,
+                start: pending.start,
+                end: self.last_seen,
+            }));
+        }
+    }
+}
+
+// tracker of the http connections between clients and servers
+#[derive(Default)]
+pub struct Connections {
+    connections: HashMap<(SocketAddr, SocketAddr), Connection>,
+}
+
+impl Connections {
+    // read the messages in the stream identified by key that was updated at
+    // now and get the resulting events
+    pub fn read(
+        &mut self,
+        key: StreamKey,
+        stream: &mut Stream,
+        from_client: bool,
+        now: Instant,
+    ) -> Vec<Event> {
+        let mut events = Vec::new();
+        self.prune(now, &mut events);
+
+        let (client, server) = match from_client {
+            true => (key.src, key.dst),
+            false => (key.dst, key.src),
+        };
+        let connection = self
+            .connections
+            .entry((client, server))
+            .or_insert_with(|| Connection::new(now));
+        connection.last_seen = now;
+        match from_client {
+            true => connection.read_requests(key, stream, &mut events),
+            false => connection.read_responses(key, stream, &mut events),
+        }
+        events
+    }
+
+    // close the direction of the connection identified by key, or the
+    // whole connection if it was reset, and get the resulting events and if
+    // the connection is closed in both directions now; a server closing its
+    // direction ends a response without length
+    pub fn shutdown(
+        &mut self,
+        key: StreamKey,
+        from_client: bool,
+        reset: bool,
+    ) -> (Vec<Event>, bool) {
+        let (client, server) = match from_client {
+            true => (key.src, key.dst),
+            false => (key.dst, key.src),
+        };
+        let connection = match self.connections.get_mut(&(client, server)) {
+            Some(connection) => connection,
+            None => return (Vec::new(), true),
+        };
+        let mut events = Vec::new();
+        match from_client {
+            true => connection.client.closed = true,
+            false => {
+                connection.server.closed = true;
+                connection.finish(client, server, &mut events);
+            }
+        }
+        if reset || (connection.client.closed && connection.server.closed) {
+            events.append(&mut self.close(client, server));
+            return (events, true);
+        }
+        (events, false)
+    }
+
+    // close the connection of client and server
+    fn close(&mut self, client: SocketAddr, server: SocketAddr) -> Vec<Event> {
+        let mut events = Vec::new();
+        if let Some(mut connection) = self.connections.remove(&(client, server)) {
+            connection.close(client, server, &mut events);
+        }
+        events
+    }
+
+    // remove connections that have been idle for too long; if there are
+    // still too many, the oldest ones are removed
+    fn prune(&mut self, now: Instant, events: &mut Vec<Event>) {
+        let expired = self
+            .connections
+            .iter()
+            .filter(|(_, c)| now.duration_since(c.last_seen) >= CONNECTION_TIMEOUT)
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+        for (client, server) in expired {
+            events.append(&mut self.close(client, server));
+        }
+        while self.connections.len() >= MAX_CONNECTIONS {
+            let oldest = self
+                .connections
+                .iter()
+                .min_by_key(|(_, c)| c.last_seen)
+                .map(|(key, _)| *key);
+            match oldest {
+                Some((client, server)) => events.append(&mut self.close(client, server)),
+                None => break,
+            };
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use clap::Parser;
+
+// command line configuration of the icmpdump tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Print every icmp and icmpv6 message with its type, code and embedded datagram")]
+pub struct Config {
+    // network interface to capture packets on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Capture packets on the network interface NAME; by default, \
+        the first interface that is up, is not a loopback and has an ip \
+        address is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., to see the icmp \
+        traffic of other hosts on a mirrored port"
+    )]
+    pub promiscuous: bool,
+
+    // ip versions
+    #[arg(
+        short = '4',
+        long,
+        conflicts_with = "ipv6",
+        help = "Only print icmp messages over ipv4"
+    )]
+    pub ipv4: bool,
+    #[arg(short = '6', long, help = "Only print icmpv6 messages over ipv6")]
+    pub ipv6: bool,
+
+    // only print error messages
+    #[arg(
+        short,
+        long,
+        help = "Only print error messages like destination unreachable and \
+        time exceeded and omit informational messages like echo requests"
+    )]
+    pub errors: bool,
+}
@@ -0,0 +1,172 @@
+extern crate pnet;
+
+mod config;
+mod message;
+mod names;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::interface::find_interface;
+use pnet::datalink::DataLinkReceiver;
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket};
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::ipv6::Ipv6Packet;
+
+use std::collections::BTreeMap;
+use std::io;
+use std::net::IpAddr;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::Duration;
+
+use config::Config;
+use message::{IcmpMessage, Version};
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// skip the ipv6 extension headers before the icmpv6 message in payload;
+// mld messages are sent with a hop-by-hop options header
+fn skip_extension_headers(next_header: IpNextHeaderProtocol, payload: &[u8]) -> Option<&[u8]> {
+    let mut next_header = next_header;
+    let mut payload = payload;
+    loop {
+        match next_header {
+            IpNextHeaderProtocols::Icmpv6 => return Some(payload),
+            IpNextHeaderProtocols::Hopopt
+            | IpNextHeaderProtocols::Ipv6Route
+            | IpNextHeaderProtocols::Ipv6Opts => {
+                let length = (usize::from(*payload.get(1)?) + 1) * 8;
+                next_header = IpNextHeaderProtocol::new(*payload.first()?);
+                payload = payload.get(length..)?;
+            }
+            _ => return None,
+        }
+    }
+}
+
+// parse the ethernet frame and get the icmp or icmpv6 message inside it
+// with its addresses if its ip version is enabled in config; fragmented
+// ipv4 packets are ignored
+fn parse_frame(frame: &[u8], config: &Config) -> Option<(IpAddr, IpAddr, IcmpMessage)> {
+    let ethernet_packet = EthernetPacket::new(frame)?;
+    let ip = &frame[EthernetPacket::minimum_packet_size()..];
+    match ethernet_packet.get_ethertype() {
+        EtherTypes::Ipv4 if !config.ipv6 => {
+            let ipv4_packet = Ipv4Packet::new(ip)?;
+            if ipv4_packet.get_next_level_protocol() != IpNextHeaderProtocols::Icmp
+                || ipv4_packet.get_fragment_offset() != 0
+                || ipv4_packet.get_flags() & 0x1 != 0
+            {
+                return None;
+            }
+            let header_length = usize::from(ipv4_packet.get_header_length()) * 4;
+            let total_length = usize::from(ipv4_packet.get_total_length());
+            let message = IcmpMessage::parse_icmp(ip.get(header_length..total_length)?)?;
+            Some((
+                IpAddr::V4(ipv4_packet.get_source()),
+                IpAddr::V4(ipv4_packet.get_destination()),
+                message,
+            ))
+        }
+        EtherTypes::Ipv6 if !config.ipv4 => {
+            let ipv6_packet = Ipv6Packet::new(ip)?;
+            let header_length = Ipv6Packet::minimum_packet_size();
+            let payload_length = usize::from(ipv6_packet.get_payload_length());
+            let payload = ip.get(header_length..header_length + payload_length)?;
+            let icmpv6 = skip_extension_headers(ipv6_packet.get_next_header(), payload)?;
+            let (source, destination) = (ipv6_packet.get_source(), ipv6_packet.get_destination());
+            let message = IcmpMessage::parse_icmpv6(icmpv6, &source, &destination)?;
+            Some((IpAddr::V6(source), IpAddr::V6(destination), message))
+        }
+        _ => None,
+    }
+}
+
+// number of messages per version, type and code
+type Counts = BTreeMap<(Version, u8, u8), u64>;
+
+// receive icmp and icmpv6 messages, print and count them until running is
+// unset
+fn listen(
+    rx: &mut dyn DataLinkReceiver,
+    counts: &mut Counts,
+    config: &Config,
+    running: &AtomicBool,
+) {
+    while running.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading packets: {}", e),
+            },
+        };
+        let (source, destination, message) = match parse_frame(frame, config) {
+            Some(parsed) => parsed,
+            None => continue,
+        };
+        *counts
+            .entry((message.version, message.icmp_type, message.code))
+            .or_default() += 1;
+        if config.errors && !message.is_error() {
+            continue;
+        }
+        println!(
+            "got {} message from {} to {}: {}",
+            message.version, source, destination, message
+        );
+    }
+}
+
+// print the number of messages per version, type and code
+fn print_counts(counts: &Counts) {
+    println!("--- icmp messages ---");
+    println!(
+        "{:<7} {:>4} {:<40} {:>4} {:<45} {:>8}",
+        "VERSION", "TYPE", "TYPE NAME", "CODE", "CODE NAME", "MESSAGES"
+    );
+    for ((version, icmp_type, code), count) in counts {
+        println!(
+            "{:<7} {:>4} {:<40} {:>4} {:<45} {:>8}",
+            version.to_string(),
+            icmp_type,
+            message::get_type_name(*version, *icmp_type),
+            code,
+            message::get_code_name(*version, *icmp_type, *code).unwrap_or("-"),
+            count,
+        );
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let mut rx = match create_channel(&interface, READ_TIMEOUT, config.promiscuous) {
+        Ok((_, rx)) => rx,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+
+    // stop on ctrl-c or sigterm and print the message counts
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    let mut counts = Counts::new();
+    listen(rx.as_mut(), &mut counts, &config, &running);
+    print_counts(&counts);
+}
@@ -0,0 +1,346 @@
+use packet_utils::bytes::{read_be_u16_at, read_be_u32_at};
+use pnet::packet::icmp::IcmpPacket;
+use pnet::packet::icmpv6::Icmpv6Packet;
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::names;
+
+// length of the icmp header before the message body
+const HEADER_LENGTH: usize = 8;
+
+// ip protocols of embedded datagrams
+const PROTOCOL_ICMP: u8 = 1;
+const PROTOCOL_TCP: u8 = 6;
+const PROTOCOL_UDP: u8 = 17;
+const PROTOCOL_ICMPV6: u8 = 58;
+
+// ipv6 extension headers that are skipped in embedded datagrams
+const IPV6_HOP_BY_HOP: u8 = 0;
+const IPV6_ROUTING: u8 = 43;
+const IPV6_DESTINATION: u8 = 60;
+
+// get the ipv4 address at offset in bytes
+fn read_ipv4(bytes: &[u8], offset: usize) -> Option<Ipv4Addr> {
+    read_be_u32_at(bytes, offset).map(Ipv4Addr::from)
+}
+
+// get the ipv6 address at offset in bytes
+fn read_ipv6(bytes: &[u8], offset: usize) -> Option<Ipv6Addr> {
+    let octets: [u8; 16] = bytes.get(offset..offset + 16)?.try_into().ok()?;
+    Some(Ipv6Addr::from(octets))
+}
+
+// get the named field of a message if its value is present
+fn field<T: ToString>(name: &'static str, value: Option<T>) -> Option<(&'static str, String)> {
+    value.map(|v| (name, v.to_string()))
+}
+
+// icmp version of a message
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Version {
+    Icmp,
+    Icmpv6,
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Version::Icmp => write!(f, "icmp"),
+            Version::Icmpv6 => write!(f, "icmpv6"),
+        }
+    }
+}
+
+// get the name of the type and code of a message in version
+pub fn get_type_name(version: Version, icmp_type: u8) -> &'static str {
+    let name = match version {
+        Version::Icmp => names::get_icmp_type_name(icmp_type),
+        Version::Icmpv6 => names::get_icmpv6_type_name(icmp_type),
+    };
+    name.unwrap_or("unknown")
+}
+
+pub fn get_code_name(version: Version, icmp_type: u8, code: u8) -> Option<&'static str> {
+    match version {
+        Version::Icmp => names::get_icmp_code_name(icmp_type, code),
+        Version::Icmpv6 => names::get_icmpv6_code_name(icmp_type, code),
+    }
+}
+
+// original datagram embedded in an error message; it is usually truncated
+// after the first bytes of its payload
+pub struct Original {
+    pub source: IpAddr,
+    pub destination: IpAddr,
+    pub protocol: u8,
+    pub length: usize,
+    pub transport: Option<String>,
+}
+
+impl Original {
+    // parse the embedded ipv4 datagram in bytes
+    fn parse_ipv4(bytes: &[u8]) -> Option<Original> {
+        if bytes.len() < 20 || bytes[0] >> 4 != 4 {
+            return None;
+        }
+        let header_length = usize::from(bytes[0] & 0xf) * 4;
+        let protocol = bytes[9];
+        let payload = bytes.get(header_length..).unwrap_or_default();
+        Some(Original {
+            source: IpAddr::V4(read_ipv4(bytes, 12)?),
+            destination: IpAddr::V4(read_ipv4(bytes, 16)?),
+            protocol,
+            length: usize::from(read_be_u16_at(bytes, 2)?),
+            transport: Original::parse_transport(protocol, payload),
+        })
+    }
+
+    // parse the embedded ipv6 datagram in bytes and skip its extension
+    // headers
+    fn parse_ipv6(bytes: &[u8]) -> Option<Original> {
+        if bytes.len() < 40 || bytes[0] >> 4 != 6 {
+            return None;
+        }
+        let mut protocol = bytes[6];
+        let mut payload = &bytes[40..];
+        while matches!(protocol, IPV6_HOP_BY_HOP | IPV6_ROUTING | IPV6_DESTINATION) {
+            let length = (usize::from(*payload.get(1)?) + 1) * 8;
+            protocol = *payload.first()?;
+            payload = payload.get(length..)?;
+        }
+        Some(Original {
+            source: IpAddr::V6(read_ipv6(bytes, 8)?),
+            destination: IpAddr::V6(read_ipv6(bytes, 24)?),
+            protocol,
+            length: usize::from(read_be_u16_at(bytes, 4)?) + 40,
+            transport: Original::parse_transport(protocol, payload),
+        })
+    }
+
+    // parse the beginning of the transport header of the embedded datagram
+    fn parse_transport(protocol: u8, payload: &[u8]) -> Option<String> {
+        match protocol {
+            PROTOCOL_TCP | PROTOCOL_UDP => Some(format!(
+                "ports: {} -> {}",
+                read_be_u16_at(payload, 0)?,
+                read_be_u16_at(payload, 2)?
+            )),
+            PROTOCOL_ICMP | PROTOCOL_ICMPV6 => {
+                let version = match protocol {
+                    PROTOCOL_ICMP => Version::Icmp,
+                    _ => Version::Icmpv6,
+                };
+                let icmp_type = *payload.first()?;
+                let mut transport = format!(
+                    "{}: {} ({})",
+                    version,
+                    get_type_name(version, icmp_type),
+                    icmp_type
+                );
+                if let (Some(id), Some(sequence)) =
+                    (read_be_u16_at(payload, 4), read_be_u16_at(payload, 6))
+                {
+                    transport.push_str(&format!(", id: {}, sequence: {}", id, sequence));
+                }
+                Some(transport)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Original {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{source: {}, destination: {}, protocol: {} ({}), length: {}",
+            self.source,
+            self.destination,
+            names::get_protocol_name(self.protocol).unwrap_or("unknown"),
+            self.protocol,
+            self.length
+        )?;
+        if let Some(transport) = &self.transport {
+            write!(f, ", {}", transport)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+// icmp or icmpv6 message with the decoded fields of its type
+pub struct IcmpMessage {
+    pub version: Version,
+    pub icmp_type: u8,
+    pub code: u8,
+    pub length: usize,
+    pub checksum_valid: bool,
+    pub fields: Vec<(&'static str, String)>,
+    pub original: Option<Original>,
+}
+
+impl IcmpMessage {
+    // parse the icmp message in bytes
+    pub fn parse_icmp(bytes: &[u8]) -> Option<IcmpMessage> {
+        let icmp_packet = IcmpPacket::new(bytes)?;
+        if bytes.len() < HEADER_LENGTH {
+            return None;
+        }
+        let checksum_valid =
+            pnet::packet::icmp::checksum(&icmp_packet) == icmp_packet.get_checksum();
+        let (icmp_type, code) = (bytes[0], bytes[1]);
+        let mut fields = Vec::new();
+        let mut original = None;
+        match icmp_type {
+            0 | 8 | 13 | 14 | 15 | 16 | 17 | 18 => {
+                fields.extend(field("id", read_be_u16_at(bytes, 4)));
+                fields.extend(field("sequence", read_be_u16_at(bytes, 6)));
+                match icmp_type {
+                    13 | 14 => {
+                        fields.extend(field("originate", read_be_u32_at(bytes, 8)));
+                        fields.extend(field("receive", read_be_u32_at(bytes, 12)));
+                        fields.extend(field("transmit", read_be_u32_at(bytes, 16)));
+                    }
+                    17 | 18 => {
+                        fields.extend(field("mask", read_ipv4(bytes, 8)));
+                    }
+                    _ => (),
+                }
+            }
+            9 => {
+                fields.extend(field("lifetime", read_be_u16_at(bytes, 6)));
+                let count = usize::from(bytes[4]);
+                let entry_size = usize::from(bytes[5]) * 4;
+                let addresses = (0..count)
+                    .filter_map(|i| read_ipv4(bytes, HEADER_LENGTH + i * entry_size))
+                    .map(|a| a.to_string())
+                    .collect::<Vec<_>>();
+                fields.push(("routers", format!("[{}]", addresses.join(", "))));
+            }
+            3 | 4 | 5 | 11 | 12 => {
+                match (icmp_type, code) {
+                    (3, 4) => fields.extend(field("mtu", read_be_u16_at(bytes, 6))),
+                    (5, _) => fields.extend(field("gateway", read_ipv4(bytes, 4))),
+                    (12, _) => fields.push(("pointer", bytes[4].to_string())),
+                    _ => (),
+                }
+                original = Original::parse_ipv4(&bytes[HEADER_LENGTH..]);
+            }
+            _ => (),
+        }
+        Some(IcmpMessage {
+            version: Version::Icmp,
+            icmp_type,
+            code,
+            length: bytes.len(),
+            checksum_valid,
+            fields,
+            original,
+        })
+    }
+
+    // parse the icmpv6 message in bytes from source to destination
+    pub fn parse_icmpv6(
+        bytes: &[u8],
+        source: &Ipv6Addr,
+        destination: &Ipv6Addr,
+    ) -> Option<IcmpMessage> {
+        let icmpv6_packet = Icmpv6Packet::new(bytes)?;
+        if bytes.len() < HEADER_LENGTH {
+            return None;
+        }
+        let checksum = pnet::packet::icmpv6::checksum(&icmpv6_packet, source, destination);
+        let checksum_valid = checksum == icmpv6_packet.get_checksum();
+        let (icmp_type, code) = (bytes[0], bytes[1]);
+        let mut fields = Vec::new();
+        let mut original = None;
+        match icmp_type {
+            1..=4 => {
+                match icmp_type {
+                    2 => fields.extend(field("mtu", read_be_u32_at(bytes, 4))),
+                    4 => fields.extend(field("pointer", read_be_u32_at(bytes, 4))),
+                    _ => (),
+                }
+                original = Original::parse_ipv6(&bytes[HEADER_LENGTH..]);
+            }
+            128 | 129 => {
+                fields.extend(field("id", read_be_u16_at(bytes, 4)));
+                fields.extend(field("sequence", read_be_u16_at(bytes, 6)));
+            }
+            130..=132 => {
+                fields.extend(field("max delay", read_be_u16_at(bytes, 4)));
+                fields.extend(field("group", read_ipv6(bytes, 8)));
+            }
+            134 => {
+                fields.push(("hop limit", bytes[4].to_string()));
+                let flags = [(0x80, "managed"), (0x40, "other")]
+                    .iter()
+                    .filter(|(bit, _)| bytes[5] & bit != 0)
+                    .map(|(_, name)| *name)
+                    .collect::<Vec<_>>();
+                fields.push(("flags", format!("[{}]", flags.join(", "))));
+                fields.extend(field("lifetime", read_be_u16_at(bytes, 6)));
+            }
+            135..=137 => {
+                fields.extend(field("target", read_ipv6(bytes, 8)));
+                match icmp_type {
+                    136 => {
+                        let flags = [(0x80, "router"), (0x40, "solicited"), (0x20, "override")]
+                            .iter()
+                            .filter(|(bit, _)| bytes[4] & bit != 0)
+                            .map(|(_, name)| *name)
+                            .collect::<Vec<_>>();
+                        fields.push(("flags", format!("[{}]", flags.join(", "))));
+                    }
+                    137 => fields.extend(field("destination", read_ipv6(bytes, 24))),
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+        Some(IcmpMessage {
+            version: Version::Icmpv6,
+            icmp_type,
+            code,
+            length: bytes.len(),
+            checksum_valid,
+            fields,
+            original,
+        })
+    }
+
+    // check if the message is an error message
+    pub fn is_error(&self) -> bool {
+        match self.version {
+            Version::Icmp => matches!(self.icmp_type, 3 | 4 | 5 | 11 | 12),
+            Version::Icmpv6 => self.icmp_type < 128,
+        }
+    }
+}
+
+impl fmt::Display for IcmpMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{type: {} ({}), code: ",
+            get_type_name(self.version, self.icmp_type),
+            self.icmp_type
+        )?;
+        match get_code_name(self.version, self.icmp_type, self.code) {
+            Some(name) => write!(f, "{} ({})", name, self.code)?,
+            None => write!(f, "{}", self.code)?,
+        }
+        write!(f, ", length: {}", self.length)?;
+        if !self.checksum_valid {
+            write!(f, ", checksum: invalid")?;
+        }
+        for (name, value) in &self.fields {
+            write!(f, ", {}: {}", name, value)?;
+        }
+        if let Some(original) = &self.original {
+            write!(f, ", original: {}", original)?;
+        }
+        write!(f, "}}")
+    }
+}
@@ -0,0 +1,126 @@
+// get the name of the icmp type
+pub fn get_icmp_type_name(icmp_type: u8) -> Option<&'static str> {
+    let name = match icmp_type {
+        0 => "echo reply",
+        3 => "destination unreachable",
+        4 => "source quench",
+        5 => "redirect",
+        8 => "echo request",
+        9 => "router advertisement",
+        10 => "router solicitation",
+        11 => "time exceeded",
+        12 => "parameter problem",
+        13 => "timestamp",
+        14 => "timestamp reply",
+        15 => "information request",
+        16 => "information reply",
+        17 => "address mask request",
+        18 => "address mask reply",
+        42 => "extended echo request",
+        43 => "extended echo reply",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// get the name of the code of the icmp type
+pub fn get_icmp_code_name(icmp_type: u8, code: u8) -> Option<&'static str> {
+    let name = match (icmp_type, code) {
+        (3, 0) => "net unreachable",
+        (3, 1) => "host unreachable",
+        (3, 2) => "protocol unreachable",
+        (3, 3) => "port unreachable",
+        (3, 4) => "fragmentation needed",
+        (3, 5) => "source route failed",
+        (3, 6) => "destination network unknown",
+        (3, 7) => "destination host unknown",
+        (3, 8) => "source host isolated",
+        (3, 9) => "network administratively prohibited",
+        (3, 10) => "host administratively prohibited",
+        (3, 11) => "network unreachable for tos",
+        (3, 12) => "host unreachable for tos",
+        (3, 13) => "communication administratively prohibited",
+        (3, 14) => "host precedence violation",
+        (3, 15) => "precedence cutoff in effect",
+        (5, 0) => "redirect for network",
+        (5, 1) => "redirect for host",
+        (5, 2) => "redirect for tos and network",
+        (5, 3) => "redirect for tos and host",
+        (11, 0) => "ttl exceeded in transit",
+        (11, 1) => "fragment reassembly time exceeded",
+        (12, 0) => "pointer indicates the error",
+        (12, 1) => "missing a required option",
+        (12, 2) => "bad length",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// get the name of the icmpv6 type
+pub fn get_icmpv6_type_name(icmpv6_type: u8) -> Option<&'static str> {
+    let name = match icmpv6_type {
+        1 => "destination unreachable",
+        2 => "packet too big",
+        3 => "time exceeded",
+        4 => "parameter problem",
+        128 => "echo request",
+        129 => "echo reply",
+        130 => "multicast listener query",
+        131 => "multicast listener report",
+        132 => "multicast listener done",
+        133 => "router solicitation",
+        134 => "router advertisement",
+        135 => "neighbor solicitation",
+        136 => "neighbor advertisement",
+        137 => "redirect",
+        138 => "router renumbering",
+        141 => "inverse neighbor discovery solicitation",
+        142 => "inverse neighbor discovery advertisement",
+        143 => "multicast listener report v2",
+        160 => "extended echo request",
+        161 => "extended echo reply",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// get the name of the code of the icmpv6 type
+pub fn get_icmpv6_code_name(icmpv6_type: u8, code: u8) -> Option<&'static str> {
+    let name = match (icmpv6_type, code) {
+        (1, 0) => "no route to destination",
+        (1, 1) => "communication administratively prohibited",
+        (1, 2) => "beyond scope of source address",
+        (1, 3) => "address unreachable",
+        (1, 4) => "port unreachable",
+        (1, 5) => "source address failed ingress/egress policy",
+        (1, 6) => "reject route to destination",
+        (1, 7) => "error in source routing header",
+        (3, 0) => "hop limit exceeded in transit",
+        (3, 1) => "fragment reassembly time exceeded",
+        (4, 0) => "erroneous header field",
+        (4, 1) => "unrecognized next header type",
+        (4, 2) => "unrecognized ipv6 option",
+        (4, 3) => "incomplete first fragment header chain",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// get the name of the ip protocol of embedded datagrams
+pub fn get_protocol_name(protocol: u8) -> Option<&'static str> {
+    let name = match protocol {
+        1 => "icmp",
+        2 => "igmp",
+        6 => "tcp",
+        17 => "udp",
+        41 => "ipv6",
+        47 => "gre",
+        50 => "esp",
+        51 => "ah",
+        58 => "icmpv6",
+        89 => "ospf",
+        132 => "sctp",
+        _ => return None,
+    };
+    Some(name)
+}
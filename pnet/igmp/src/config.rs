@@ -0,0 +1,34 @@
+use clap::Parser;
+
+// command line configuration of the igmp tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Monitor igmp and mld messages and multicast group memberships")]
+pub struct Config {
+    // network interface to capture igmp and mld messages on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Capture igmp and mld messages on the network interface NAME; \
+        by default, the first interface that is up, is not a loopback and has \
+        an ip address is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., to see the \
+        membership reports hosts send to groups this host did not join"
+    )]
+    pub promiscuous: bool,
+
+    // only print membership changes
+    #[arg(
+        short,
+        long,
+        help = "Only print membership changes and new queriers instead of \
+        every igmp and mld message"
+    )]
+    pub quiet: bool,
+}
@@ -0,0 +1,124 @@
+extern crate pnet;
+
+mod config;
+mod message;
+mod table;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::interface::find_interface;
+use pnet::datalink::DataLinkReceiver;
+
+use std::io;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::Duration;
+
+use config::Config;
+use message::{format_addresses, GroupMessage};
+use table::GroupTable;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// receive igmp and mld messages, add them to the table and print the
+// messages and the events they caused until running is unset
+fn monitor(
+    rx: &mut dyn DataLinkReceiver,
+    table: &mut GroupTable,
+    config: &Config,
+    running: &AtomicBool,
+) {
+    while running.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading igmp messages: {}", e),
+            },
+        };
+        let message = match GroupMessage::parse(frame) {
+            Some(message) => message,
+            None => continue,
+        };
+        if !config.quiet {
+            println!("{}", message);
+        }
+        for event in table.add(&message) {
+            println!("{}", event);
+        }
+    }
+}
+
+// print the multicast groups with their members and the queriers in the
+// table
+fn print_table(table: &GroupTable) {
+    println!("--- multicast groups ---");
+    println!(
+        "{:<39} {:<39} {:<17} {:<7} {:<7} {:>7} {:>10}  SOURCES",
+        "GROUP", "HOST", "MAC", "VERSION", "MODE", "REPORTS", "LAST SEEN"
+    );
+    for (group, members) in table.get_groups() {
+        for (host, member) in members {
+            let sources = member.sources.iter().copied().collect::<Vec<_>>();
+            println!(
+                "{:<39} {:<39} {:<17} {:<7} {:<7} {:>7} {:>8.1} s  {}",
+                group.to_string(),
+                host.to_string(),
+                member.mac.to_string(),
+                member.version.to_string(),
+                member.mode.to_string(),
+                member.reports,
+                member.last_seen.elapsed().as_secs_f64(),
+                format_addresses(&sources),
+            );
+        }
+    }
+
+    println!("--- queriers ---");
+    println!(
+        "{:<39} {:<17} {:<7} {:>7} {:>10}",
+        "QUERIER", "MAC", "VERSION", "QUERIES", "LAST SEEN"
+    );
+    for (ip, querier) in table.get_queriers() {
+        println!(
+            "{:<39} {:<17} {:<7} {:>7} {:>8.1} s",
+            ip.to_string(),
+            querier.mac.to_string(),
+            querier.version.to_string(),
+            querier.queries,
+            querier.last_seen.elapsed().as_secs_f64(),
+        );
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let mut rx = match create_channel(&interface, READ_TIMEOUT, config.promiscuous) {
+        Ok((_, rx)) => rx,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+
+    // stop on ctrl-c or sigterm and print the table
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    let mut table = GroupTable::default();
+    monitor(rx.as_mut(), &mut table, &config, &running);
+    print_table(&table);
+}
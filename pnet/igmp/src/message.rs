@@ -0,0 +1,430 @@
+use packet_utils::bytes::read_be_u16;
+use pnet::datalink::MacAddr;
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket};
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::Packet;
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+// igmp message types
+const IGMP_QUERY: u8 = 0x11;
+const IGMPV1_REPORT: u8 = 0x12;
+const IGMPV2_REPORT: u8 = 0x16;
+const IGMPV2_LEAVE: u8 = 0x17;
+const IGMPV3_REPORT: u8 = 0x22;
+
+// icmpv6 types of mld messages
+const MLD_QUERY: u8 = 130;
+const MLDV1_REPORT: u8 = 131;
+const MLDV1_DONE: u8 = 132;
+const MLDV2_REPORT: u8 = 143;
+
+// length of igmpv1/v2 messages and of mldv1 messages; longer queries are
+// igmpv3 and mldv2 queries
+const IGMPV2_LENGTH: usize = 8;
+const MLDV1_LENGTH: usize = 24;
+
+// get the ipv4 address in the 4 bytes of bytes
+fn read_ipv4(bytes: &[u8]) -> IpAddr {
+    let octets: [u8; 4] = bytes.try_into().expect("slice with incorrect length");
+    IpAddr::V4(Ipv4Addr::from(octets))
+}
+
+// get the ipv6 address in the 16 bytes of bytes
+fn read_ipv6(bytes: &[u8]) -> IpAddr {
+    let octets: [u8; 16] = bytes.try_into().expect("slice with incorrect length");
+    IpAddr::V6(Ipv6Addr::from(octets))
+}
+
+// get the value of the 8 bit igmpv3 or 16 bit mldv2 maximum response code
+// or querier's query interval code; values with the most significant bit
+// set are floating point numbers with an exponent and a mantissa
+fn decode_code(code: u32, bits: u32) -> u32 {
+    let mantissa_bits = bits - 4;
+    if code < 1 << (bits - 1) {
+        return code;
+    }
+    let mantissa = code & ((1 << mantissa_bits) - 1);
+    let exponent = (code >> mantissa_bits) & 0x7;
+    (mantissa | 1 << mantissa_bits) << (exponent + 3)
+}
+
+// protocol version of a message
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Version {
+    Igmpv1,
+    Igmpv2,
+    Igmpv3,
+    Mldv1,
+    Mldv2,
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Version::Igmpv1 => write!(f, "igmpv1"),
+            Version::Igmpv2 => write!(f, "igmpv2"),
+            Version::Igmpv3 => write!(f, "igmpv3"),
+            Version::Mldv1 => write!(f, "mldv1"),
+            Version::Mldv2 => write!(f, "mldv2"),
+        }
+    }
+}
+
+// Record Type:
+// type of a group record in igmpv3 and mldv2 reports
+// 1               MODE_IS_INCLUDE
+// 2               MODE_IS_EXCLUDE
+// 3               CHANGE_TO_INCLUDE_MODE
+// 4               CHANGE_TO_EXCLUDE_MODE
+// 5               ALLOW_NEW_SOURCES
+// 6               BLOCK_OLD_SOURCES
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RecordType {
+    IsInclude,
+    IsExclude,
+    ToInclude,
+    ToExclude,
+    Allow,
+    Block,
+}
+
+impl RecordType {
+    fn from_u8(value: u8) -> Option<RecordType> {
+        match value {
+            1 => Some(RecordType::IsInclude),
+            2 => Some(RecordType::IsExclude),
+            3 => Some(RecordType::ToInclude),
+            4 => Some(RecordType::ToExclude),
+            5 => Some(RecordType::Allow),
+            6 => Some(RecordType::Block),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RecordType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordType::IsInclude => write!(f, "is include"),
+            RecordType::IsExclude => write!(f, "is exclude"),
+            RecordType::ToInclude => write!(f, "to include"),
+            RecordType::ToExclude => write!(f, "to exclude"),
+            RecordType::Allow => write!(f, "allow"),
+            RecordType::Block => write!(f, "block"),
+        }
+    }
+}
+
+// format the ip addresses as list
+pub fn format_addresses(addresses: &[IpAddr]) -> String {
+    let addresses = addresses.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+    format!("[{}]", addresses.join(" "))
+}
+
+// group record in igmpv3 and mldv2 reports
+pub struct GroupRecord {
+    pub record_type: RecordType,
+    pub group: IpAddr,
+    pub sources: Vec<IpAddr>,
+}
+
+impl fmt::Display for GroupRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.record_type, self.group)?;
+        if !self.sources.is_empty() {
+            write!(f, " sources {}", format_addresses(&self.sources))?;
+        }
+        Ok(())
+    }
+}
+
+// type of igmp or mld message with its fields
+pub enum MessageType {
+    // general query without group or group-specific query with the maximum
+    // response time in milliseconds and the sources, robustness variable
+    // and query interval of igmpv3 and mldv2 queries
+    Query {
+        group: Option<IpAddr>,
+        max_response: u32,
+        sources: Vec<IpAddr>,
+        robustness: Option<u8>,
+        interval: Option<u32>,
+    },
+
+    // igmpv1/v2 and mldv1 report and leave of a group
+    Report(IpAddr),
+    Leave(IpAddr),
+
+    // igmpv3 and mldv2 report with group records
+    RecordReport(Vec<GroupRecord>),
+}
+
+// igmp or mld message with the addresses of its ethernet and ip headers
+pub struct GroupMessage {
+    pub ethernet_source: MacAddr,
+    pub source: IpAddr,
+    pub destination: IpAddr,
+    pub version: Version,
+    pub message_type: MessageType,
+}
+
+impl GroupMessage {
+    // parse the igmp or mld message in the ethernet frame
+    pub fn parse(frame: &[u8]) -> Option<GroupMessage> {
+        let ethernet_packet = EthernetPacket::new(frame)?;
+        let (source, destination, version, message_type) = match ethernet_packet.get_ethertype() {
+            EtherTypes::Ipv4 => {
+                let ipv4_packet = Ipv4Packet::new(ethernet_packet.payload())?;
+                if ipv4_packet.get_next_level_protocol() != IpNextHeaderProtocols::Igmp {
+                    return None;
+                }
+                // igmp packets carry the router alert option
+                let header_length = usize::from(ipv4_packet.get_header_length()) * 4;
+                let total_length = usize::from(ipv4_packet.get_total_length());
+                let igmp = ethernet_packet.payload().get(header_length..total_length)?;
+                let (version, message_type) = GroupMessage::parse_igmp(igmp)?;
+                (
+                    IpAddr::V4(ipv4_packet.get_source()),
+                    IpAddr::V4(ipv4_packet.get_destination()),
+                    version,
+                    message_type,
+                )
+            }
+            EtherTypes::Ipv6 => {
+                let ipv6_packet = Ipv6Packet::new(ethernet_packet.payload())?;
+                let length = usize::from(ipv6_packet.get_payload_length());
+                let payload = ipv6_packet.payload().get(..length)?;
+                let icmp =
+                    GroupMessage::skip_extension_headers(ipv6_packet.get_next_header(), payload)?;
+                let (version, message_type) = GroupMessage::parse_mld(icmp)?;
+                (
+                    IpAddr::V6(ipv6_packet.get_source()),
+                    IpAddr::V6(ipv6_packet.get_destination()),
+                    version,
+                    message_type,
+                )
+            }
+            _ => return None,
+        };
+        Some(GroupMessage {
+            ethernet_source: ethernet_packet.get_source(),
+            source,
+            destination,
+            version,
+            message_type,
+        })
+    }
+
+    // skip the ipv6 extension headers before the icmpv6 message in payload;
+    // mld messages are sent with a hop-by-hop options header that contains
+    // the router alert option
+    fn skip_extension_headers(next_header: IpNextHeaderProtocol, payload: &[u8]) -> Option<&[u8]> {
+        let mut next_header = next_header;
+        let mut payload = payload;
+        loop {
+            match next_header {
+                IpNextHeaderProtocols::Icmpv6 => return Some(payload),
+                IpNextHeaderProtocols::Hopopt
+                | IpNextHeaderProtocols::Ipv6Route
+                | IpNextHeaderProtocols::Ipv6Opts => {
+                    let length = (usize::from(*payload.get(1)?) + 1) * 8;
+                    next_header = IpNextHeaderProtocol::new(*payload.first()?);
+                    payload = payload.get(length..)?;
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    // parse the group records with the number of records in count in
+    // records; the address length is 4 in igmpv3 and 16 in mldv2
+    fn parse_records(
+        records: &[u8],
+        count: u16,
+        address_length: usize,
+    ) -> Option<Vec<GroupRecord>> {
+        let read_address = match address_length {
+            4 => read_ipv4,
+            _ => read_ipv6,
+        };
+        let mut offset = 0;
+        let mut parsed = Vec::new();
+        for _ in 0..count {
+            // record type, aux data length in 32 bit words, number of
+            // sources, multicast address and sources
+            let header = records.get(offset..offset + 4 + address_length)?;
+            let aux_length = usize::from(header[1]) * 4;
+            let sources = usize::from(read_be_u16(&header[2..4]));
+            let group = read_address(&header[4..]);
+            offset += header.len();
+            let sources = records
+                .get(offset..offset + sources * address_length)?
+                .chunks_exact(address_length)
+                .map(read_address)
+                .collect::<Vec<_>>();
+            offset += sources.len() * address_length + aux_length;
+
+            // records of unknown types are skipped
+            if let Some(record_type) = RecordType::from_u8(header[0]) {
+                parsed.push(GroupRecord {
+                    record_type,
+                    group,
+                    sources,
+                });
+            }
+        }
+        Some(parsed)
+    }
+
+    // parse the igmp message in igmp
+    fn parse_igmp(igmp: &[u8]) -> Option<(Version, MessageType)> {
+        if igmp.len() < IGMPV2_LENGTH {
+            return None;
+        }
+        let group = read_ipv4(&igmp[4..8]);
+        let group = Some(group).filter(|g| !g.is_unspecified());
+        let parsed = match igmp[0] {
+            IGMP_QUERY if igmp.len() == IGMPV2_LENGTH => {
+                // igmpv1 queries have no maximum response time and use 10 s
+                let version = match igmp[1] {
+                    0 => Version::Igmpv1,
+                    _ => Version::Igmpv2,
+                };
+                let max_response = match igmp[1] {
+                    0 => 10_000,
+                    code => u32::from(code) * 100,
+                };
+                let query = MessageType::Query {
+                    group,
+                    max_response,
+                    sources: Vec::new(),
+                    robustness: None,
+                    interval: None,
+                };
+                (version, query)
+            }
+            IGMP_QUERY => {
+                let header = igmp.get(..12)?;
+                let sources = usize::from(read_be_u16(&header[10..12]));
+                let sources = igmp
+                    .get(12..12 + sources * 4)?
+                    .chunks_exact(4)
+                    .map(read_ipv4)
+                    .collect();
+                let query = MessageType::Query {
+                    group,
+                    max_response: decode_code(u32::from(igmp[1]), 8) * 100,
+                    sources,
+                    robustness: Some(header[8] & 0x7),
+                    interval: Some(decode_code(u32::from(header[9]), 8)),
+                };
+                (Version::Igmpv3, query)
+            }
+            IGMPV1_REPORT => (Version::Igmpv1, MessageType::Report(group?)),
+            IGMPV2_REPORT => (Version::Igmpv2, MessageType::Report(group?)),
+            IGMPV2_LEAVE => (Version::Igmpv2, MessageType::Leave(group?)),
+            IGMPV3_REPORT => {
+                let count = read_be_u16(&igmp[6..8]);
+                let records = GroupMessage::parse_records(&igmp[8..], count, 4)?;
+                (Version::Igmpv3, MessageType::RecordReport(records))
+            }
+            _ => return None,
+        };
+        Some(parsed)
+    }
+
+    // parse the mld message in the icmpv6 message icmp
+    fn parse_mld(icmp: &[u8]) -> Option<(Version, MessageType)> {
+        let message_type = *icmp.first()?;
+        if message_type == MLDV2_REPORT {
+            let count = read_be_u16(icmp.get(6..8)?);
+            let records = GroupMessage::parse_records(&icmp[8..], count, 16)?;
+            return Some((Version::Mldv2, MessageType::RecordReport(records)));
+        }
+
+        let header = icmp.get(..MLDV1_LENGTH)?;
+        let group = read_ipv6(&header[8..24]);
+        let group = Some(group).filter(|g| !g.is_unspecified());
+        let parsed = match message_type {
+            MLD_QUERY if icmp.len() == MLDV1_LENGTH => {
+                let query = MessageType::Query {
+                    group,
+                    max_response: u32::from(read_be_u16(&header[4..6])),
+                    sources: Vec::new(),
+                    robustness: None,
+                    interval: None,
+                };
+                (Version::Mldv1, query)
+            }
+            MLD_QUERY => {
+                let flags = icmp.get(24..28)?;
+                let sources = usize::from(read_be_u16(&flags[2..4]));
+                let sources = icmp
+                    .get(28..28 + sources * 16)?
+                    .chunks_exact(16)
+                    .map(read_ipv6)
+                    .collect();
+                let query = MessageType::Query {
+                    group,
+                    max_response: decode_code(u32::from(read_be_u16(&header[4..6])), 16),
+                    sources,
+                    robustness: Some(flags[0] & 0x7),
+                    interval: Some(decode_code(u32::from(flags[1]), 8)),
+                };
+                (Version::Mldv2, query)
+            }
+            MLDV1_REPORT => (Version::Mldv1, MessageType::Report(group?)),
+            MLDV1_DONE => (Version::Mldv1, MessageType::Leave(group?)),
+            _ => return None,
+        };
+        Some(parsed)
+    }
+}
+
+impl fmt::Display for GroupMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}) -> {}: {} ",
+            self.source, self.ethernet_source, self.destination, self.version
+        )?;
+        match &self.message_type {
+            MessageType::Query {
+                group,
+                max_response,
+                sources,
+                robustness,
+                interval,
+            } => {
+                match group {
+                    Some(group) => write!(f, "group-specific query for {}", group)?,
+                    None => write!(f, "general query")?,
+                }
+                if !sources.is_empty() {
+                    write!(f, " sources {}", format_addresses(sources))?;
+                }
+                write!(f, " max response {} ms", max_response)?;
+                if let Some(robustness) = robustness {
+                    write!(f, " robustness {}", robustness)?;
+                }
+                if let Some(interval) = interval {
+                    write!(f, " interval {} s", interval)?;
+                }
+                Ok(())
+            }
+            MessageType::Report(group) => write!(f, "report {}", group),
+            MessageType::Leave(group) => match self.version {
+                Version::Mldv1 => write!(f, "done {}", group),
+                _ => write!(f, "leave {}", group),
+            },
+            MessageType::RecordReport(records) => {
+                let records = records.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+                write!(f, "report [{}]", records.join(", "))
+            }
+        }
+    }
+}
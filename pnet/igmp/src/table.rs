@@ -0,0 +1,253 @@
+use pnet::datalink::MacAddr;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use crate::message::{GroupMessage, GroupRecord, MessageType, RecordType, Version};
+
+// time after which a member that did not report the group again is removed,
+// this is the default group membership interval of igmpv3 and mldv2
+const MEMBERSHIP_TIMEOUT: Duration = Duration::from_secs(260);
+
+// filter mode of a membership; include only receives traffic from the
+// sources, exclude receives traffic from all but the sources
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FilterMode {
+    Include,
+    Exclude,
+}
+
+impl fmt::Display for FilterMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterMode::Include => write!(f, "include"),
+            FilterMode::Exclude => write!(f, "exclude"),
+        }
+    }
+}
+
+// host that joined a multicast group
+pub struct Member {
+    pub mac: MacAddr,
+    pub version: Version,
+    pub mode: FilterMode,
+    pub sources: BTreeSet<IpAddr>,
+    pub last_seen: Instant,
+    pub reports: u64,
+}
+
+// router that sent queries
+pub struct Querier {
+    pub mac: MacAddr,
+    pub version: Version,
+    pub last_seen: Instant,
+    pub queries: u64,
+}
+
+// membership change or new querier caused by a message
+pub enum Event {
+    Joined {
+        host: IpAddr,
+        group: IpAddr,
+        version: Version,
+    },
+    Left {
+        host: IpAddr,
+        group: IpAddr,
+    },
+    Expired {
+        host: IpAddr,
+        group: IpAddr,
+    },
+    NewQuerier {
+        querier: IpAddr,
+        version: Version,
+    },
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Joined {
+                host,
+                group,
+                version,
+            } => write!(f, "{} joined {} ({})", host, group, version),
+            Event::Left { host, group } => write!(f, "{} left {}", host, group),
+            Event::Expired { host, group } => {
+                write!(f, "{} expired from {}", host, group)
+            }
+            Event::NewQuerier { querier, version } => {
+                write!(f, "new querier {} ({})", querier, version)
+            }
+        }
+    }
+}
+
+// table of the multicast groups with the hosts that joined them and of the
+// queriers learned from igmp and mld messages
+#[derive(Default)]
+pub struct GroupTable {
+    groups: BTreeMap<IpAddr, BTreeMap<IpAddr, Member>>,
+    queriers: BTreeMap<IpAddr, Querier>,
+}
+
+impl GroupTable {
+    // add the membership change or querier in the message to the table and
+    // get the events it caused
+    pub fn add(&mut self, message: &GroupMessage) -> Vec<Event> {
+        let now = Instant::now();
+        let mut events = self.expire(now);
+        match &message.message_type {
+            MessageType::Query { .. } => {
+                let querier = self.queriers.entry(message.source).or_insert_with(|| {
+                    events.push(Event::NewQuerier {
+                        querier: message.source,
+                        version: message.version,
+                    });
+                    Querier {
+                        mac: message.ethernet_source,
+                        version: message.version,
+                        last_seen: now,
+                        queries: 0,
+                    }
+                });
+                querier.mac = message.ethernet_source;
+                querier.version = message.version;
+                querier.last_seen = now;
+                querier.queries += 1;
+            }
+            MessageType::Report(group) => {
+                let record = GroupRecord {
+                    record_type: RecordType::IsExclude,
+                    group: *group,
+                    sources: Vec::new(),
+                };
+                self.add_record(message, &record, now, &mut events);
+            }
+            MessageType::Leave(group) => self.remove(message.source, *group, &mut events),
+            MessageType::RecordReport(records) => {
+                for record in records {
+                    self.add_record(message, record, now, &mut events);
+                }
+            }
+        }
+        events
+    }
+
+    // add the group record reported in message to the table
+    fn add_record(
+        &mut self,
+        message: &GroupMessage,
+        record: &GroupRecord,
+        now: Instant,
+        events: &mut Vec<Event>,
+    ) {
+        let host = message.source;
+        let group = record.group;
+        let sources = record.sources.iter().copied().collect::<BTreeSet<_>>();
+
+        // an empty include list and blocking all included sources are leaves
+        let member = self.groups.get(&group).and_then(|m| m.get(&host));
+        let (mode, sources) = match (record.record_type, member) {
+            (RecordType::IsExclude | RecordType::ToExclude, _) => (FilterMode::Exclude, sources),
+            (RecordType::IsInclude | RecordType::ToInclude, _) | (RecordType::Allow, None) => {
+                (FilterMode::Include, sources)
+            }
+            (RecordType::Allow, Some(member)) => match member.mode {
+                FilterMode::Include => (
+                    FilterMode::Include,
+                    member.sources.union(&sources).copied().collect(),
+                ),
+                FilterMode::Exclude => (
+                    FilterMode::Exclude,
+                    member.sources.difference(&sources).copied().collect(),
+                ),
+            },
+            (RecordType::Block, None) => return,
+            (RecordType::Block, Some(member)) => match member.mode {
+                FilterMode::Include => (
+                    FilterMode::Include,
+                    member.sources.difference(&sources).copied().collect(),
+                ),
+                FilterMode::Exclude => (
+                    FilterMode::Exclude,
+                    member.sources.union(&sources).copied().collect(),
+                ),
+            },
+        };
+        if mode == FilterMode::Include && sources.is_empty() {
+            self.remove(host, group, events);
+            return;
+        }
+
+        let members = self.groups.entry(group).or_default();
+        let member = members.entry(host).or_insert_with(|| {
+            events.push(Event::Joined {
+                host,
+                group,
+                version: message.version,
+            });
+            Member {
+                mac: message.ethernet_source,
+                version: message.version,
+                mode,
+                sources: BTreeSet::new(),
+                last_seen: now,
+                reports: 0,
+            }
+        });
+        member.mac = message.ethernet_source;
+        member.version = message.version;
+        member.mode = mode;
+        member.sources = sources;
+        member.last_seen = now;
+        member.reports += 1;
+    }
+
+    // remove host from group
+    fn remove(&mut self, host: IpAddr, group: IpAddr, events: &mut Vec<Event>) {
+        let members = match self.groups.get_mut(&group) {
+            Some(members) => members,
+            None => return,
+        };
+        if members.remove(&host).is_some() {
+            events.push(Event::Left { host, group });
+        }
+        if members.is_empty() {
+            self.groups.remove(&group);
+        }
+    }
+
+    // remove the members that did not report their groups within the
+    // membership timeout
+    fn expire(&mut self, now: Instant) -> Vec<Event> {
+        let mut events = Vec::new();
+        for (group, members) in self.groups.iter_mut() {
+            members.retain(|host, member| {
+                if now.duration_since(member.last_seen) < MEMBERSHIP_TIMEOUT {
+                    return true;
+                }
+                events.push(Event::Expired {
+                    host: *host,
+                    group: *group,
+                });
+                false
+            });
+        }
+        self.groups.retain(|_, members| !members.is_empty());
+        events
+    }
+
+    // get the multicast groups with their members sorted by address
+    pub fn get_groups(&self) -> &BTreeMap<IpAddr, BTreeMap<IpAddr, Member>> {
+        &self.groups
+    }
+
+    // get the queriers sorted by address
+    pub fn get_queriers(&self) -> &BTreeMap<IpAddr, Querier> {
+        &self.queriers
+    }
+}
@@ -0,0 +1,45 @@
+use clap::Parser;
+
+// command line configuration of the ipsec tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Listen for ikev2 and esp traffic and track the ipsec security associations")]
+pub struct Config {
+    // network interface to capture packets on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Capture packets on the network interface NAME; by default, \
+        the first interface that is up, is not a loopback and has an ip \
+        address is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., to see the \
+        ipsec traffic of other hosts on a mirrored port"
+    )]
+    pub promiscuous: bool,
+
+    // ip versions
+    #[arg(
+        short = '4',
+        long,
+        conflicts_with = "ipv6",
+        help = "Only listen for ipsec traffic over ipv4"
+    )]
+    pub ipv4: bool,
+    #[arg(short = '6', long, help = "Only listen for ipsec traffic over ipv6")]
+    pub ipv6: bool,
+
+    // also print esp packets
+    #[arg(
+        short,
+        long,
+        help = "Print every esp packet in addition to the ike messages and \
+        the security association events"
+    )]
+    pub verbose: bool,
+}
@@ -0,0 +1,392 @@
+use packet_utils::hex::to_hex;
+
+use std::fmt;
+
+// length of the ike header
+const HEADER_LENGTH: usize = 28;
+
+// ike major version 2
+const VERSION_2: u8 = 2;
+
+// exchange types
+const IKE_SA_INIT: u8 = 34;
+const IKE_AUTH: u8 = 35;
+const CREATE_CHILD_SA: u8 = 36;
+const INFORMATIONAL: u8 = 37;
+
+// header flags
+const FLAG_INITIATOR: u8 = 0x08;
+const FLAG_RESPONSE: u8 = 0x20;
+
+// payload types with decoded contents; the encrypted payloads end the
+// payload chain
+const PAYLOAD_NONE: u8 = 0;
+const PAYLOAD_SA: u8 = 33;
+const PAYLOAD_KE: u8 = 34;
+const PAYLOAD_NOTIFY: u8 = 41;
+const PAYLOAD_DELETE: u8 = 42;
+const PAYLOAD_VENDOR_ID: u8 = 43;
+const PAYLOAD_ENCRYPTED: u8 = 46;
+const PAYLOAD_ENCRYPTED_FRAGMENT: u8 = 53;
+
+// get the name of the payload type
+fn get_payload_name(payload_type: u8) -> String {
+    let name = match payload_type {
+        PAYLOAD_SA => "sa",
+        PAYLOAD_KE => "ke",
+        35 => "idi",
+        36 => "idr",
+        37 => "cert",
+        38 => "certreq",
+        39 => "auth",
+        40 => "nonce",
+        PAYLOAD_NOTIFY => "notify",
+        PAYLOAD_DELETE => "delete",
+        PAYLOAD_VENDOR_ID => "vendor id",
+        44 => "tsi",
+        45 => "tsr",
+        PAYLOAD_ENCRYPTED => "sk",
+        47 => "cp",
+        48 => "eap",
+        PAYLOAD_ENCRYPTED_FRAGMENT => "skf",
+        t => return format!("payload {}", t),
+    };
+    name.to_string()
+}
+
+// get the name of the ipsec protocol id
+pub fn get_protocol_name(protocol: u8) -> String {
+    match protocol {
+        1 => "ike".to_string(),
+        2 => "ah".to_string(),
+        3 => "esp".to_string(),
+        p => format!("protocol {}", p),
+    }
+}
+
+// get the name of the notify message type
+fn get_notify_name(notify_type: u16) -> String {
+    let name = match notify_type {
+        1 => "unsupported critical payload",
+        4 => "invalid ike spi",
+        5 => "invalid major version",
+        7 => "invalid syntax",
+        9 => "invalid message id",
+        11 => "invalid spi",
+        14 => "no proposal chosen",
+        17 => "invalid ke payload",
+        24 => "authentication failed",
+        34 => "single pair required",
+        35 => "no additional sas",
+        36 => "internal address failure",
+        37 => "failed cp required",
+        38 => "ts unacceptable",
+        39 => "invalid selectors",
+        43 => "temporary failure",
+        44 => "child sa not found",
+        16384 => "initial contact",
+        16385 => "set window size",
+        16386 => "additional ts possible",
+        16387 => "ipcomp supported",
+        16388 => "nat detection source ip",
+        16389 => "nat detection destination ip",
+        16390 => "cookie",
+        16391 => "use transport mode",
+        16393 => "rekey sa",
+        16394 => "esp tfc padding not supported",
+        16395 => "non first fragments also",
+        16404 => "mobike supported",
+        16418 => "eap only authentication",
+        16430 => "ikev2 fragmentation supported",
+        16431 => "signature hash algorithms",
+        t => return format!("notify {}", t),
+    };
+    name.to_string()
+}
+
+// get the name of the exchange type
+fn get_exchange_name(exchange_type: u8) -> String {
+    match exchange_type {
+        IKE_SA_INIT => "ike_sa_init".to_string(),
+        IKE_AUTH => "ike_auth".to_string(),
+        CREATE_CHILD_SA => "create_child_sa".to_string(),
+        INFORMATIONAL => "informational".to_string(),
+        t => format!("exchange {}", t),
+    }
+}
+
+// proposal in an sa payload
+pub struct Proposal {
+    pub number: u8,
+    pub protocol: u8,
+    pub spi: Vec<u8>,
+    pub transforms: u8,
+}
+
+// decoded ike payload
+pub enum Payload {
+    Sa(Vec<Proposal>),
+    Ke {
+        group: u16,
+    },
+    Notify {
+        protocol: u8,
+        spi: Vec<u8>,
+        notify_type: u16,
+    },
+    Delete {
+        protocol: u8,
+        spis: Vec<Vec<u8>>,
+    },
+    VendorId(Vec<u8>),
+
+    // encrypted payload with the type of the first encrypted payload and,
+    // for fragments, the fragment number and total number of fragments
+    Encrypted {
+        first: u8,
+        fragment: Option<(u16, u16)>,
+        length: usize,
+    },
+    Other(u8),
+}
+
+impl Payload {
+    // parse the payload with payload_type and body
+    fn parse(payload_type: u8, next: u8, body: &[u8]) -> Option<Payload> {
+        let payload = match payload_type {
+            PAYLOAD_SA => {
+                // Proposal Format:
+                // last (1), reserved (1), length (2), number (1), protocol
+                // id (1), spi size (1), transforms (1), spi, transforms
+                let mut proposals = Vec::new();
+                let mut offset = 0;
+                while offset < body.len() {
+                    let header = body.get(offset..offset + 8)?;
+                    let length = usize::from(u16::from_be_bytes([header[2], header[3]]));
+                    let spi_size = usize::from(header[6]);
+                    if length < 8 + spi_size {
+                        return None;
+                    }
+                    proposals.push(Proposal {
+                        number: header[4],
+                        protocol: header[5],
+                        spi: body.get(offset + 8..offset + 8 + spi_size)?.to_vec(),
+                        transforms: header[7],
+                    });
+                    offset += length;
+                    if header[0] == 0 {
+                        break;
+                    }
+                }
+                Payload::Sa(proposals)
+            }
+            PAYLOAD_KE => Payload::Ke {
+                group: u16::from_be_bytes(body.get(..2)?.try_into().ok()?),
+            },
+            PAYLOAD_NOTIFY => {
+                // protocol id (1), spi size (1), notify type (2), spi, data
+                let header = body.get(..4)?;
+                let spi_size = usize::from(header[1]);
+                Payload::Notify {
+                    protocol: header[0],
+                    spi: body.get(4..4 + spi_size)?.to_vec(),
+                    notify_type: u16::from_be_bytes([header[2], header[3]]),
+                }
+            }
+            PAYLOAD_DELETE => {
+                // protocol id (1), spi size (1), number of spis (2), spis
+                let header = body.get(..4)?;
+                let spi_size = usize::from(header[1]);
+                let count = usize::from(u16::from_be_bytes([header[2], header[3]]));
+                let spis = match spi_size {
+                    0 => Vec::new(),
+                    _ => body
+                        .get(4..4 + count * spi_size)?
+                        .chunks_exact(spi_size)
+                        .map(|s| s.to_vec())
+                        .collect(),
+                };
+                Payload::Delete {
+                    protocol: header[0],
+                    spis,
+                }
+            }
+            PAYLOAD_VENDOR_ID => Payload::VendorId(body.to_vec()),
+            PAYLOAD_ENCRYPTED => Payload::Encrypted {
+                first: next,
+                fragment: None,
+                length: body.len(),
+            },
+            PAYLOAD_ENCRYPTED_FRAGMENT => {
+                // fragment number (2), total fragments (2), encrypted data
+                let header = body.get(..4)?;
+                Payload::Encrypted {
+                    first: next,
+                    fragment: Some((
+                        u16::from_be_bytes([header[0], header[1]]),
+                        u16::from_be_bytes([header[2], header[3]]),
+                    )),
+                    length: body.len() - 4,
+                }
+            }
+            payload_type => Payload::Other(payload_type),
+        };
+        Some(payload)
+    }
+}
+
+impl fmt::Display for Payload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Payload::Sa(proposals) => {
+                let proposals = proposals
+                    .iter()
+                    .map(|p| {
+                        let mut proposal =
+                            format!("#{} {}", p.number, get_protocol_name(p.protocol));
+                        if !p.spi.is_empty() {
+                            proposal += &format!(" spi {}", to_hex(&p.spi));
+                        }
+                        proposal + &format!(" with {} transforms", p.transforms)
+                    })
+                    .collect::<Vec<_>>();
+                write!(f, "sa [{}]", proposals.join(", "))
+            }
+            Payload::Ke { group } => write!(f, "ke group {}", group),
+            Payload::Notify {
+                protocol,
+                spi,
+                notify_type,
+            } => {
+                write!(f, "notify {}", get_notify_name(*notify_type))?;
+                if !spi.is_empty() {
+                    write!(f, " {} spi {}", get_protocol_name(*protocol), to_hex(spi))?;
+                }
+                Ok(())
+            }
+            Payload::Delete { protocol, spis } => {
+                let spis = spis.iter().map(|s| to_hex(s)).collect::<Vec<_>>();
+                write!(f, "delete {}", get_protocol_name(*protocol))?;
+                if !spis.is_empty() {
+                    write!(f, " spis [{}]", spis.join(", "))?;
+                }
+                Ok(())
+            }
+            Payload::VendorId(id) => write!(f, "vendor id {}", to_hex(id)),
+            Payload::Encrypted {
+                first,
+                fragment,
+                length,
+            } => {
+                let name = match fragment {
+                    Some(_) => "skf",
+                    None => "sk",
+                };
+                write!(f, "{} ({} bytes", name, length)?;
+                if let Some((number, total)) = fragment {
+                    write!(f, ", fragment {}/{}", number, total)?;
+                }
+                if *first != PAYLOAD_NONE {
+                    write!(f, ", first payload {}", get_payload_name(*first))?;
+                }
+                write!(f, ")")
+            }
+            Payload::Other(payload_type) => write!(f, "{}", get_payload_name(*payload_type)),
+        }
+    }
+}
+
+// ikev2 message
+pub struct IkeMessage {
+    pub initiator_spi: u64,
+    pub responder_spi: u64,
+    pub exchange_type: u8,
+    pub flags: u8,
+    pub message_id: u32,
+    pub payloads: Vec<Payload>,
+}
+
+impl IkeMessage {
+    // parse the ikev2 message in bytes
+    pub fn parse(bytes: &[u8]) -> Option<IkeMessage> {
+        // Header Format:
+        // initiator spi (8), responder spi (8), next payload (1), version
+        // (1), exchange type (1), flags (1), message id (4), length (4)
+        let header = bytes.get(..HEADER_LENGTH)?;
+        if header[17] >> 4 != VERSION_2 {
+            return None;
+        }
+        let length = u32::from_be_bytes(header[24..28].try_into().ok()?) as usize;
+        let body = bytes.get(HEADER_LENGTH..length)?;
+
+        // Generic Payload Header:
+        // next payload (1), critical (1 bit), reserved (7 bits), length (2)
+        let mut payloads = Vec::new();
+        let mut payload_type = header[16];
+        let mut offset = 0;
+        while payload_type != PAYLOAD_NONE {
+            let generic = body.get(offset..offset + 4)?;
+            let length = usize::from(u16::from_be_bytes([generic[2], generic[3]]));
+            let payload = body.get(offset + 4..offset + length.max(4))?;
+            payloads.push(Payload::parse(payload_type, generic[0], payload)?);
+            offset += length.max(4);
+
+            // the next payload field of encrypted payloads is the type of
+            // the first payload inside them
+            if matches!(payload_type, PAYLOAD_ENCRYPTED | PAYLOAD_ENCRYPTED_FRAGMENT) {
+                break;
+            }
+            payload_type = generic[0];
+        }
+        Some(IkeMessage {
+            initiator_spi: u64::from_be_bytes(header[0..8].try_into().ok()?),
+            responder_spi: u64::from_be_bytes(header[8..16].try_into().ok()?),
+            exchange_type: header[18],
+            flags: header[19],
+            message_id: u32::from_be_bytes(header[20..24].try_into().ok()?),
+            payloads,
+        })
+    }
+
+    // check if the message is a response
+    pub fn is_response(&self) -> bool {
+        self.flags & FLAG_RESPONSE != 0
+    }
+
+    // check if the message was sent by the original initiator of the ike sa
+    pub fn is_initiator(&self) -> bool {
+        self.flags & FLAG_INITIATOR != 0
+    }
+
+    // check if the message is a create_child_sa exchange
+    pub fn is_create_child_sa(&self) -> bool {
+        self.exchange_type == CREATE_CHILD_SA
+    }
+
+    // get the name of the exchange
+    pub fn get_exchange(&self) -> String {
+        get_exchange_name(self.exchange_type)
+    }
+}
+
+impl fmt::Display for IkeMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let payloads = self
+            .payloads
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "{{spis: {:016x}/{:016x}, message id: {}, {}, payloads: [{}]}}",
+            self.initiator_spi,
+            self.responder_spi,
+            self.message_id,
+            match self.is_initiator() {
+                true => "initiator",
+                false => "responder",
+            },
+            payloads.join(", ")
+        )
+    }
+}
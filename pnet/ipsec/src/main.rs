@@ -0,0 +1,280 @@
+extern crate pnet;
+
+mod config;
+mod ike;
+mod sa;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::dissect::{parse_ip_frame, parse_udp, Datagram};
+use packet_utils::interface::find_interface;
+use pnet::datalink::DataLinkReceiver;
+use pnet::packet::ip::IpNextHeaderProtocols;
+
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::Duration;
+
+use config::Config;
+use ike::IkeMessage;
+use sa::SecurityAssociations;
+
+// well-known ports of ike and of ike and esp with nat traversal
+const IKE_PORT: u16 = 500;
+const NAT_T_PORT: u16 = 4500;
+
+// length of the esp header with spi and sequence number
+const ESP_HEADER_LENGTH: usize = 8;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// ipsec packet found in an ip packet
+enum IpsecPacket<'a> {
+    Ike {
+        source: SocketAddr,
+        destination: SocketAddr,
+        message: &'a [u8],
+    },
+    Esp {
+        source: IpAddr,
+        destination: IpAddr,
+        spi: u32,
+        sequence: u32,
+        length: usize,
+        udp_encapsulated: bool,
+    },
+}
+
+// parse the esp header at the start of bytes
+fn parse_esp(
+    bytes: &[u8],
+    source: IpAddr,
+    destination: IpAddr,
+    udp_encapsulated: bool,
+) -> Option<IpsecPacket<'_>> {
+    if bytes.len() < ESP_HEADER_LENGTH {
+        return None;
+    }
+    let spi = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let sequence = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+
+    // spis 1 to 255 are reserved and 0 is only used locally
+    if spi < 256 {
+        return None;
+    }
+    Some(IpsecPacket::Esp {
+        source,
+        destination,
+        spi,
+        sequence,
+        length: bytes.len(),
+        udp_encapsulated,
+    })
+}
+
+// get the ike message or esp packet in the udp packet; on the nat traversal
+// port, ike messages start with a non-esp marker of four zero bytes and nat
+// keepalives are ignored
+fn parse_datagram(datagram: Datagram<'_>) -> Option<IpsecPacket<'_>> {
+    let (source, destination) = (datagram.source, datagram.destination);
+    let ike = |message| IpsecPacket::Ike {
+        source,
+        destination,
+        message,
+    };
+    if [source.port(), destination.port()].contains(&IKE_PORT) {
+        return Some(ike(datagram.payload));
+    }
+    if datagram.payload == [0xff] {
+        return None;
+    }
+    match datagram.payload.strip_prefix(&[0, 0, 0, 0]) {
+        Some(message) => Some(ike(message)),
+        None => parse_esp(datagram.payload, source.ip(), destination.ip(), true),
+    }
+}
+
+// parse the ethernet frame and get the ipsec packet inside it if its ip
+// version is enabled in config; fragmented ipv4 packets and ipv6 packets
+// with extension headers are ignored
+fn parse_frame<'a>(frame: &'a [u8], config: &Config) -> Option<IpsecPacket<'a>> {
+    let ip = parse_ip_frame(frame, config.ipv4, config.ipv6)?;
+    match ip.protocol {
+        IpNextHeaderProtocols::Udp => parse_datagram(parse_udp(&ip, &[IKE_PORT, NAT_T_PORT])?),
+        IpNextHeaderProtocols::Esp => parse_esp(ip.payload, ip.source, ip.destination, false),
+        _ => None,
+    }
+}
+
+// handle the ipsec packet: print it and the events it caused
+fn handle_packet(packet: IpsecPacket, sas: &mut SecurityAssociations, config: &Config) {
+    let events = match packet {
+        IpsecPacket::Ike {
+            source,
+            destination,
+            message,
+        } => {
+            let message = match IkeMessage::parse(message) {
+                Some(message) => message,
+                None => {
+                    println!("got invalid ike message from {} to {}", source, destination);
+                    return;
+                }
+            };
+            let direction = match message.is_response() {
+                true => "response",
+                false => "request",
+            };
+            println!(
+                "got ikev2 {} {} from {} to {}: {}",
+                message.get_exchange(),
+                direction,
+                source,
+                destination,
+                message
+            );
+            sas.add_ike(source, destination, &message)
+        }
+        IpsecPacket::Esp {
+            source,
+            destination,
+            spi,
+            sequence,
+            length,
+            udp_encapsulated,
+        } => {
+            if config.verbose {
+                println!(
+                    "got esp packet from {} to {}: {{spi: {:08x}, sequence: {}, length: {}}}",
+                    source, destination, spi, sequence, length
+                );
+            }
+            let key = (source, destination, spi);
+            sas.add_esp(key, sequence, length, udp_encapsulated)
+        }
+    };
+    for event in events {
+        println!("{}", event);
+    }
+}
+
+// receive ipsec packets and track their security associations until running
+// is unset
+fn listen(
+    rx: &mut dyn DataLinkReceiver,
+    sas: &mut SecurityAssociations,
+    config: &Config,
+    running: &AtomicBool,
+) {
+    while running.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading packets: {}", e),
+            },
+        };
+        if let Some(packet) = parse_frame(frame, config) {
+            handle_packet(packet, sas, config);
+        }
+    }
+}
+
+// print the ike and esp security associations
+fn print_sas(sas: &SecurityAssociations) {
+    println!("--- ike sas ---");
+    println!(
+        "{:<16} {:<16} {:<45} {:<45} {:>8} {:>6} {:>8} {:>10}",
+        "INITIATOR SPI",
+        "RESPONDER SPI",
+        "INITIATOR",
+        "RESPONDER",
+        "MESSAGES",
+        "CHILD",
+        "RETRANS",
+        "LAST SEEN"
+    );
+    for (spi, sa) in sas.get_ike_sas() {
+        println!(
+            "{:016x} {:016x} {:<45} {:<45} {:>8} {:>6} {:>8} {:>8.1} s",
+            spi,
+            sa.responder_spi,
+            sa.initiator.to_string(),
+            sa.responder.to_string(),
+            sa.messages,
+            sa.create_child_sas,
+            sa.retransmissions,
+            sa.last_seen.elapsed().as_secs_f64(),
+        );
+    }
+
+    println!("--- esp sas ---");
+    println!(
+        "{:<39} {:<39} {:<8} {:<3} {:>8} {:>10} {:>10} {:>6} {:>6} {:>6} {:>10}",
+        "SOURCE",
+        "DESTINATION",
+        "SPI",
+        "UDP",
+        "PACKETS",
+        "BYTES",
+        "SEQUENCE",
+        "LOST",
+        "REPLAY",
+        "OLD",
+        "LAST SEEN"
+    );
+    for ((source, destination, spi), sa) in sas.get_esp_sas() {
+        let udp = match sa.udp_encapsulated {
+            true => "yes",
+            false => "no",
+        };
+        println!(
+            "{:<39} {:<39} {:08x} {:<3} {:>8} {:>10} {:>10} {:>6} {:>6} {:>6} {:>8.1} s",
+            source.to_string(),
+            destination.to_string(),
+            spi,
+            udp,
+            sa.packets,
+            sa.bytes,
+            sa.highest_sequence,
+            sa.lost,
+            sa.replays,
+            sa.outside_window,
+            sa.last_seen.elapsed().as_secs_f64(),
+        );
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let mut rx = match create_channel(&interface, READ_TIMEOUT, config.promiscuous) {
+        Ok((_, rx)) => rx,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+
+    // stop on ctrl-c or sigterm and print the security associations
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    let mut sas = SecurityAssociations::default();
+    listen(rx.as_mut(), &mut sas, &config, &running);
+    print_sas(&sas);
+}
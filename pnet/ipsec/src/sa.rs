@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Instant;
+
+use crate::ike::IkeMessage;
+
+// size of the esp anti-replay window in packets
+const REPLAY_WINDOW: u32 = 64;
+
+// ike sa identified by the spi of its initiator
+pub struct IkeSa {
+    pub initiator: SocketAddr,
+    pub responder: SocketAddr,
+    pub responder_spi: u64,
+    pub messages: u64,
+    pub create_child_sas: u64,
+    pub retransmissions: u64,
+    pub last_seen: Instant,
+
+    // message ids of the last requests sent by the initiator and the
+    // responder to detect retransmissions
+    initiator_request: Option<u32>,
+    responder_request: Option<u32>,
+}
+
+// esp sa identified by its source and destination address and spi
+pub type EspKey = (IpAddr, IpAddr, u32);
+
+// esp sa with the anti-replay window of its receiver
+pub struct EspSa {
+    pub udp_encapsulated: bool,
+    pub packets: u64,
+    pub bytes: u64,
+    pub highest_sequence: u32,
+    pub last_seen: Instant,
+
+    // number of sequence numbers that were skipped and not received later,
+    // of replayed sequence numbers and of sequence numbers that were too old
+    // for the window
+    pub lost: u64,
+    pub replays: u64,
+    pub outside_window: u64,
+
+    // bitmap of the received sequence numbers in the window; bit 0 is the
+    // highest sequence number
+    window: u64,
+}
+
+impl EspSa {
+    fn new(sequence: u32, udp_encapsulated: bool, now: Instant) -> EspSa {
+        EspSa {
+            udp_encapsulated,
+            packets: 0,
+            bytes: 0,
+            highest_sequence: sequence,
+            last_seen: now,
+            lost: 0,
+            replays: 0,
+            outside_window: 0,
+            window: 1,
+        }
+    }
+
+    // check the sequence number against the anti-replay window and update
+    // the window; get the event if the sequence number is a replay or
+    // outside the window
+    fn check_sequence(&mut self, key: EspKey, sequence: u32) -> Option<Event> {
+        if sequence > self.highest_sequence {
+            let shift = sequence - self.highest_sequence;
+            self.window = match shift {
+                s if s >= REPLAY_WINDOW => 1,
+                s => self.window << s | 1,
+            };
+            self.lost += u64::from(shift - 1);
+            self.highest_sequence = sequence;
+            return None;
+        }
+        let offset = self.highest_sequence - sequence;
+        if offset >= REPLAY_WINDOW {
+            self.outside_window += 1;
+            return Some(Event::OutsideWindow {
+                key,
+                sequence,
+                highest: self.highest_sequence,
+            });
+        }
+        if self.window & 1 << offset != 0 {
+            self.replays += 1;
+            return Some(Event::Replay { key, sequence });
+        }
+
+        // late packet that was counted as lost before
+        self.window |= 1 << offset;
+        self.lost = self.lost.saturating_sub(1);
+        None
+    }
+}
+
+// ike and esp sa events that show new sas, rekeys and replay anomalies
+pub enum Event {
+    NewIkeSa {
+        spi: u64,
+        initiator: SocketAddr,
+        responder: SocketAddr,
+    },
+    CreateChildSa {
+        spi: u64,
+        message_id: u32,
+    },
+    Retransmission {
+        spi: u64,
+        message_id: u32,
+    },
+
+    // new esp sa between the same addresses as an existing one replaces it
+    NewEspSa {
+        key: EspKey,
+        replaces: Option<u32>,
+    },
+    Replay {
+        key: EspKey,
+        sequence: u32,
+    },
+    OutsideWindow {
+        key: EspKey,
+        sequence: u32,
+        highest: u32,
+    },
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::NewIkeSa {
+                spi,
+                initiator,
+                responder,
+            } => write!(
+                f,
+                "new ike sa {:016x} between {} and {}",
+                spi, initiator, responder
+            ),
+            Event::CreateChildSa { spi, message_id } => write!(
+                f,
+                "create_child_sa exchange {} on ike sa {:016x}: rekey or new child sa",
+                message_id, spi
+            ),
+            Event::Retransmission { spi, message_id } => write!(
+                f,
+                "retransmission of request {} on ike sa {:016x}",
+                message_id, spi
+            ),
+            Event::NewEspSa {
+                key: (source, destination, spi),
+                replaces,
+            } => {
+                write!(
+                    f,
+                    "new esp sa {:08x} from {} to {}",
+                    spi, source, destination
+                )?;
+                if let Some(old) = replaces {
+                    write!(f, ": rekey of esp sa {:08x}", old)?;
+                }
+                Ok(())
+            }
+            Event::Replay {
+                key: (source, destination, spi),
+                sequence,
+            } => write!(
+                f,
+                "ALERT: replayed sequence number {} on esp sa {:08x} from {} to {}",
+                sequence, spi, source, destination
+            ),
+            Event::OutsideWindow {
+                key: (source, destination, spi),
+                sequence,
+                highest,
+            } => write!(
+                f,
+                "ALERT: sequence number {} outside of replay window (highest {}) on esp sa \
+                {:08x} from {} to {}",
+                sequence, highest, spi, source, destination
+            ),
+        }
+    }
+}
+
+// ike and esp security associations
+#[derive(Default)]
+pub struct SecurityAssociations {
+    ike_sas: BTreeMap<u64, IkeSa>,
+    esp_sas: BTreeMap<EspKey, EspSa>,
+}
+
+impl SecurityAssociations {
+    // add the ike message from source to destination and get the events it
+    // caused
+    pub fn add_ike(
+        &mut self,
+        source: SocketAddr,
+        destination: SocketAddr,
+        message: &IkeMessage,
+    ) -> Vec<Event> {
+        let mut events = Vec::new();
+        let spi = message.initiator_spi;
+        let (initiator, responder) = match message.is_initiator() {
+            true => (source, destination),
+            false => (destination, source),
+        };
+        let now = Instant::now();
+        let sa = self.ike_sas.entry(spi).or_insert_with(|| {
+            events.push(Event::NewIkeSa {
+                spi,
+                initiator,
+                responder,
+            });
+            IkeSa {
+                initiator,
+                responder,
+                responder_spi: 0,
+                messages: 0,
+                create_child_sas: 0,
+                retransmissions: 0,
+                last_seen: now,
+                initiator_request: None,
+                responder_request: None,
+            }
+        });
+        sa.messages += 1;
+        sa.last_seen = now;
+        if message.responder_spi != 0 {
+            sa.responder_spi = message.responder_spi;
+        }
+        if message.is_response() {
+            return events;
+        }
+
+        let last_request = match message.is_initiator() {
+            true => &mut sa.initiator_request,
+            false => &mut sa.responder_request,
+        };
+        if *last_request == Some(message.message_id) {
+            sa.retransmissions += 1;
+            events.push(Event::Retransmission {
+                spi,
+                message_id: message.message_id,
+            });
+            return events;
+        }
+        *last_request = Some(message.message_id);
+        if message.is_create_child_sa() {
+            sa.create_child_sas += 1;
+            events.push(Event::CreateChildSa {
+                spi,
+                message_id: message.message_id,
+            });
+        }
+        events
+    }
+
+    // add the esp packet with spi and sequence number from source to
+    // destination and get the events it caused
+    pub fn add_esp(
+        &mut self,
+        key: EspKey,
+        sequence: u32,
+        length: usize,
+        udp_encapsulated: bool,
+    ) -> Vec<Event> {
+        let mut events = Vec::new();
+        let now = Instant::now();
+        if !self.esp_sas.contains_key(&key) {
+            // the most recent other sa between the addresses is replaced
+            let (source, destination, _) = key;
+            let replaces = self
+                .esp_sas
+                .iter()
+                .filter(|((s, d, _), _)| *s == source && *d == destination)
+                .max_by_key(|(_, sa)| sa.last_seen)
+                .map(|((_, _, spi), _)| *spi);
+            events.push(Event::NewEspSa { key, replaces });
+            self.esp_sas
+                .insert(key, EspSa::new(sequence, udp_encapsulated, now));
+        } else if let Some(event) = self
+            .esp_sas
+            .get_mut(&key)
+            .and_then(|sa| sa.check_sequence(key, sequence))
+        {
+            events.push(event);
+        }
+
+        if let Some(sa) = self.esp_sas.get_mut(&key) {
+            sa.packets += 1;
+            sa.bytes += length as u64;
+            sa.last_seen = now;
+        }
+        events
+    }
+
+    // get the ike sas sorted by initiator spi
+    pub fn get_ike_sas(&self) -> &BTreeMap<u64, IkeSa> {
+        &self.ike_sas
+    }
+
+    // get the esp sas sorted by addresses and spi
+    pub fn get_esp_sas(&self) -> &BTreeMap<EspKey, EspSa> {
+        &self.esp_sas
+    }
+}
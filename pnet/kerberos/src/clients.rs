@@ -0,0 +1,312 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::message::*;
+use crate::names::*;
+
+// maximum number of as-reqs waiting for their replies before the oldest
+// ones are removed
+const MAX_PENDING: usize = 4096;
+
+// time after which as-reqs without a reply are removed
+const PENDING_TIMEOUT: Duration = Duration::from_secs(30);
+
+// suspicious kerberos traffic of a client
+pub enum Alert {
+    // client sent more as-reqs within the window than allowed
+    Storm {
+        client: IpAddr,
+        count: u32,
+        window: Duration,
+    },
+
+    // client failed to authenticate more often within the window than
+    // allowed, e.g., in password guessing or spraying
+    Failures {
+        client: IpAddr,
+        count: u32,
+        principals: usize,
+        window: Duration,
+    },
+
+    // client only requested weak encryption types
+    WeakRequest {
+        client: IpAddr,
+        principal: String,
+        etypes: Vec<i64>,
+    },
+
+    // kdc replied with a weak encryption type
+    WeakReply {
+        client: IpAddr,
+        principal: String,
+        etype: i64,
+    },
+
+    // kdc issued a ticket without pre-authentication, so the reply can be
+    // attacked offline (as-rep roasting)
+    NoPreauth {
+        client: IpAddr,
+        principal: String,
+    },
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alert::Storm {
+                client,
+                count,
+                window,
+            } => write!(
+                f,
+                "ALERT: authentication storm: {} sent {} AS-REQs within {:.3} s",
+                client,
+                count,
+                window.as_secs_f64()
+            ),
+            Alert::Failures {
+                client,
+                count,
+                principals,
+                window,
+            } => write!(
+                f,
+                "ALERT: authentication failures: {} failed {} times for {} \
+                principals within {:.3} s",
+                client,
+                count,
+                principals,
+                window.as_secs_f64()
+            ),
+            Alert::WeakRequest {
+                client,
+                principal,
+                etypes,
+            } => write!(
+                f,
+                "ALERT: downgrade: {} requested only weak etypes {} for {}",
+                client,
+                format_etypes(etypes),
+                principal
+            ),
+            Alert::WeakReply {
+                client,
+                principal,
+                etype,
+            } => write!(
+                f,
+                "ALERT: weak etype: {} got a reply encrypted with {} ({}) for {}",
+                client,
+                etype,
+                get_etype_name(*etype),
+                principal
+            ),
+            Alert::NoPreauth { client, principal } => write!(
+                f,
+                "ALERT: no pre-authentication: {} got an AS-REP for {} without \
+                pre-authentication",
+                client, principal
+            ),
+        }
+    }
+}
+
+// counters of a client in the current window
+struct WindowCounter {
+    start: Instant,
+    requests: u32,
+    failures: u32,
+    failed_principals: Vec<String>,
+    storm_alerted: bool,
+    failures_alerted: bool,
+}
+
+impl WindowCounter {
+    fn new(start: Instant) -> WindowCounter {
+        WindowCounter {
+            start,
+            requests: 0,
+            failures: 0,
+            failed_principals: Vec::new(),
+            storm_alerted: false,
+            failures_alerted: false,
+        }
+    }
+}
+
+// statistics of the kerberos messages of a client
+pub struct Client {
+    pub as_requests: u64,
+    pub as_replies: u64,
+    pub tgs_requests: u64,
+    pub tgs_replies: u64,
+    pub errors: u64,
+    pub failures: u64,
+    pub principals: HashSet<String>,
+    pub alerts: u64,
+    window: WindowCounter,
+}
+
+impl Client {
+    fn new(now: Instant) -> Client {
+        Client {
+            as_requests: 0,
+            as_replies: 0,
+            tgs_requests: 0,
+            tgs_replies: 0,
+            errors: 0,
+            failures: 0,
+            principals: HashSet::new(),
+            alerts: 0,
+            window: WindowCounter::new(now),
+        }
+    }
+}
+
+// kerberos clients keyed by ip address that detects authentication storms,
+// repeated failures, downgrades to weak encryption types and tickets issued
+// without pre-authentication
+pub struct Clients {
+    clients: BTreeMap<IpAddr, Client>,
+
+    // as-reqs waiting for their replies and whether they contained
+    // pre-authentication data, keyed by client and principal
+    pending: HashMap<(IpAddr, String), (Instant, bool)>,
+
+    storm_threshold: u32,
+    failure_threshold: u32,
+    window: Duration,
+}
+
+impl Clients {
+    pub fn new(config: &Config) -> Clients {
+        Clients {
+            clients: BTreeMap::new(),
+            pending: HashMap::new(),
+            storm_threshold: config.storm_threshold,
+            failure_threshold: config.failure_threshold,
+            window: Duration::from_secs_f64(config.window),
+        }
+    }
+
+    // remove pending as-reqs that timed out; if there are still too many,
+    // the oldest ones are removed
+    fn prune(&mut self, now: Instant) {
+        self.pending
+            .retain(|_, (sent, _)| now.duration_since(*sent) < PENDING_TIMEOUT);
+        while self.pending.len() >= MAX_PENDING {
+            let oldest = self
+                .pending
+                .iter()
+                .min_by_key(|(_, (sent, _))| *sent)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => self.pending.remove(&key),
+                None => break,
+            };
+        }
+    }
+
+    // add the message exchanged between client and the kdc and get the
+    // alerts it caused
+    pub fn add(&mut self, client: IpAddr, message: &KerberosMessage) -> Vec<Alert> {
+        let now = Instant::now();
+        self.prune(now);
+        let mut alerts = Vec::new();
+        let window = self.window;
+        let statistics = self
+            .clients
+            .entry(client)
+            .or_insert_with(|| Client::new(now));
+        if now.duration_since(statistics.window.start) >= window {
+            statistics.window = WindowCounter::new(now);
+        }
+        match message {
+            KerberosMessage::Request(request) => {
+                let principal = format_principal(&request.cname, &request.realm);
+                if request.message_type == MessageType::TgsReq {
+                    statistics.tgs_requests += 1;
+                } else {
+                    statistics.as_requests += 1;
+                    statistics.window.requests += 1;
+                    statistics.principals.insert(principal.clone());
+                    self.pending.insert(
+                        (client, principal.to_lowercase()),
+                        (now, request.has_preauth()),
+                    );
+                }
+                if request.is_weak() {
+                    alerts.push(Alert::WeakRequest {
+                        client,
+                        principal,
+                        etypes: request.etypes.clone(),
+                    });
+                }
+                let counter = &mut statistics.window;
+                if counter.requests > self.storm_threshold && !counter.storm_alerted {
+                    counter.storm_alerted = true;
+                    alerts.push(Alert::Storm {
+                        client,
+                        count: counter.requests,
+                        window,
+                    });
+                }
+            }
+            KerberosMessage::Reply(reply) => {
+                let principal = format!("{}@{}", reply.cname, reply.crealm);
+                if reply.message_type == MessageType::TgsRep {
+                    statistics.tgs_replies += 1;
+                } else {
+                    statistics.as_replies += 1;
+                    let key = (client, principal.to_lowercase());
+                    if let Some((_, false)) = self.pending.remove(&key) {
+                        alerts.push(Alert::NoPreauth {
+                            client,
+                            principal: principal.clone(),
+                        });
+                    }
+                }
+                if let Some(etype) = reply.get_weak_etype() {
+                    alerts.push(Alert::WeakReply {
+                        client,
+                        principal,
+                        etype,
+                    });
+                }
+            }
+            KerberosMessage::Error(error) => {
+                statistics.errors += 1;
+                if is_failure(error.error_code) {
+                    let crealm = error.crealm.as_deref().unwrap_or(&error.realm);
+                    let principal = format_principal(&error.cname, crealm);
+                    statistics.failures += 1;
+                    let counter = &mut statistics.window;
+                    counter.failures += 1;
+                    if !counter.failed_principals.contains(&principal) {
+                        counter.failed_principals.push(principal);
+                    }
+                    if counter.failures > self.failure_threshold && !counter.failures_alerted {
+                        counter.failures_alerted = true;
+                        alerts.push(Alert::Failures {
+                            client,
+                            count: counter.failures,
+                            principals: counter.failed_principals.len(),
+                            window,
+                        });
+                    }
+                }
+            }
+        }
+        statistics.alerts += alerts.len() as u64;
+        alerts
+    }
+
+    // get the clients sorted by ip address
+    pub fn get_clients(&self) -> &BTreeMap<IpAddr, Client> {
+        &self.clients
+    }
+}
@@ -0,0 +1,76 @@
+use clap::Parser;
+use packet_utils::parse::parse_seconds;
+
+// command line configuration of the kerberos tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Listen for kerberos messages and detect suspicious authentications")]
+pub struct Config {
+    // network interface to capture packets on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Capture packets on the network interface NAME; by default, \
+        the first interface that is up, is not a loopback and has an ip \
+        address is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., to see the \
+        kerberos traffic of other hosts on a mirrored port"
+    )]
+    pub promiscuous: bool,
+
+    // ip versions
+    #[arg(
+        short = '4',
+        long,
+        conflicts_with = "ipv6",
+        help = "Only listen for kerberos messages over ipv4"
+    )]
+    pub ipv4: bool,
+    #[arg(
+        short = '6',
+        long,
+        help = "Only listen for kerberos messages over ipv6"
+    )]
+    pub ipv6: bool,
+
+    // only print alerts
+    #[arg(
+        short,
+        long,
+        help = "Only print alerts instead of every kerberos message"
+    )]
+    pub quiet: bool,
+
+    // storm and failure detection
+    #[arg(
+        long,
+        value_name = "COUNT",
+        default_value_t = 20,
+        help = "Alert if a client sends more than COUNT AS-REQs within the \
+        window"
+    )]
+    pub storm_threshold: u32,
+    #[arg(
+        long,
+        value_name = "COUNT",
+        default_value_t = 10,
+        help = "Alert if a client fails to authenticate more than COUNT times \
+        within the window, e.g., with wrong passwords or unknown principals"
+    )]
+    pub failure_threshold: u32,
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 10.0,
+        value_parser = parse_seconds,
+        help = "Length of the window in which AS-REQs and failures of a \
+        client are counted"
+    )]
+    pub window: f64,
+}
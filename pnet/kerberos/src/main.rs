@@ -0,0 +1,335 @@
+extern crate pnet;
+
+mod clients;
+mod config;
+mod message;
+mod names;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::dissect::parse_ip_frame;
+use packet_utils::interface::find_interface;
+use pnet::datalink::DataLinkReceiver;
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
+use pnet::packet::tcp::{TcpFlags, TcpPacket};
+use pnet::packet::udp::UdpPacket;
+use pnet::packet::Packet;
+
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::Duration;
+
+use dns::{Stream, StreamKey, Streams};
+
+use clients::Clients;
+use config::Config;
+use message::KerberosMessage;
+
+// well-known port of the kdc
+const KERBEROS_PORT: u16 = 88;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// length of the record mark before kerberos messages over tcp
+const RECORD_MARK_LENGTH: usize = 4;
+
+// maximum length of kerberos messages over tcp; the highest bit of the
+// record mark is reserved, so longer records are invalid
+const MAX_RECORD_LENGTH: usize = 0x7fff_ffff;
+
+// udp datagram or tcp segment from or to the kerberos port with its source
+// and destination addresses and ports
+enum Transport<'a> {
+    Udp {
+        source: SocketAddr,
+        destination: SocketAddr,
+        payload: &'a [u8],
+    },
+    Tcp {
+        key: StreamKey,
+        packet: TcpPacket<'a>,
+    },
+}
+
+// get the address of the client, i.e., the side of source and destination
+// that does not use the kerberos port
+fn get_client(source: SocketAddr, destination: SocketAddr) -> IpAddr {
+    match destination.port() {
+        KERBEROS_PORT => source.ip(),
+        _ => destination.ip(),
+    }
+}
+
+// parse the udp or tcp packet in ip_payload and get it if it is from or to
+// the kerberos port
+fn parse_transport(
+    ip_payload: &[u8],
+    protocol: IpNextHeaderProtocol,
+    source: IpAddr,
+    destination: IpAddr,
+) -> Option<Transport<'_>> {
+    let is_kerberos = |ports: (u16, u16)| ports.0 == KERBEROS_PORT || ports.1 == KERBEROS_PORT;
+    match protocol {
+        IpNextHeaderProtocols::Udp => {
+            let udp_packet = UdpPacket::new(ip_payload)?;
+            let udp_length = usize::from(udp_packet.get_length());
+            let payload = ip_payload.get(UdpPacket::minimum_packet_size()..udp_length)?;
+            let ports = (udp_packet.get_source(), udp_packet.get_destination());
+            is_kerberos(ports).then_some(Transport::Udp {
+                source: SocketAddr::new(source, ports.0),
+                destination: SocketAddr::new(destination, ports.1),
+                payload,
+            })
+        }
+        IpNextHeaderProtocols::Tcp => {
+            let packet = TcpPacket::new(ip_payload)?;
+            let ports = (packet.get_source(), packet.get_destination());
+            let key = StreamKey {
+                src: SocketAddr::new(source, ports.0),
+                dst: SocketAddr::new(destination, ports.1),
+            };
+            is_kerberos(ports).then_some(Transport::Tcp { key, packet })
+        }
+        _ => None,
+    }
+}
+
+// parse the ethernet frame and get the kerberos udp datagram or tcp segment
+// inside it if its ip version is enabled in config; fragmented ipv4 packets
+// and ipv6 packets with extension headers are ignored
+fn parse_frame<'a>(frame: &'a [u8], config: &Config) -> Option<Transport<'a>> {
+    let ip = parse_ip_frame(frame, config.ipv4, config.ipv6)?;
+    parse_transport(ip.payload, ip.protocol, ip.source, ip.destination)
+}
+
+// record of a kerberos message read from a tcp stream
+enum Record {
+    Message(Vec<u8>),
+
+    // record mark with the reserved bit set or of an empty message; the
+    // stream cannot be resynchronized, so its data is dropped
+    Invalid,
+}
+
+// get the next record from the tcp stream; kerberos messages over tcp are
+// prefixed with a four byte record mark that contains their length
+fn next_record(stream: &mut Stream) -> Option<Record> {
+    let data = stream.data();
+    let mark = u32::from_be_bytes(data.get(..RECORD_MARK_LENGTH)?.try_into().ok()?);
+    let length = usize::try_from(mark).ok()?;
+    if length == 0 || length > MAX_RECORD_LENGTH {
+        stream.consume(data.len());
+        return Some(Record::Invalid);
+    }
+    let message = data.get(RECORD_MARK_LENGTH..RECORD_MARK_LENGTH + length)?;
+    let message = message.to_vec();
+    stream.consume(RECORD_MARK_LENGTH + length);
+    Some(Record::Message(message))
+}
+
+// reassembled tcp streams and the kerberos clients
+struct Tracker<'a> {
+    config: &'a Config,
+    streams: Streams,
+    clients: Clients,
+}
+
+impl Tracker<'_> {
+    // handle the udp datagram or tcp segment: parse the kerberos messages
+    // in it, add them to the clients and print them and their alerts
+    fn handle(&mut self, transport: Transport) {
+        match transport {
+            Transport::Udp {
+                source,
+                destination,
+                payload,
+            } => {
+                if !payload.is_empty() {
+                    self.handle_message("udp", source, destination, Some(payload));
+                }
+            }
+            Transport::Tcp { key, packet } => {
+                let flags = packet.get_flags();
+                let syn = flags & TcpFlags::SYN != 0;
+                let stream = self
+                    .streams
+                    .add(key, packet.get_sequence(), syn, packet.payload());
+                let mut records = Vec::new();
+                while let Some(record) = next_record(stream) {
+                    records.push(record);
+                }
+                for record in records {
+                    let message = match &record {
+                        Record::Message(message) => Some(message.as_slice()),
+                        Record::Invalid => None,
+                    };
+                    self.handle_message("tcp", key.src, key.dst, message);
+                }
+
+                // remove the streams of closed connections
+                if flags & (TcpFlags::FIN | TcpFlags::RST) != 0 {
+                    self.streams.remove(&key);
+                    self.streams.remove(&key.reverse());
+                }
+            }
+        }
+    }
+
+    // parse the kerberos message sent over transport from source to
+    // destination, add it to the clients and print it and the alerts it
+    // caused; invalid messages are none
+    fn handle_message(
+        &mut self,
+        transport: &str,
+        source: SocketAddr,
+        destination: SocketAddr,
+        message: Option<&[u8]>,
+    ) {
+        let message = match message.and_then(KerberosMessage::parse) {
+            Some(message) => message,
+            None => {
+                if !self.config.quiet {
+                    println!(
+                        "got invalid kerberos message over {} from {} to {}",
+                        transport, source, destination
+                    );
+                }
+                return;
+            }
+        };
+        if !self.config.quiet {
+            println!(
+                "got kerberos message over {} from {} to {}: {}",
+                transport, source, destination, message
+            );
+        }
+        for alert in self.clients.add(get_client(source, destination), &message) {
+            println!("{}", alert);
+        }
+    }
+}
+
+// receive kerberos messages, reassemble the tcp streams and handle their
+// messages until running is unset
+fn listen(rx: &mut dyn DataLinkReceiver, tracker: &mut Tracker, running: &AtomicBool) {
+    while running.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading packets: {}", e),
+            },
+        };
+        if let Some(transport) = parse_frame(frame, tracker.config) {
+            tracker.handle(transport);
+        }
+    }
+}
+
+// print the statistics of the clients
+fn print_clients(clients: &Clients) {
+    println!("--- kerberos clients ---");
+    println!(
+        "{:<39} {:>6} {:>6} {:>7} {:>7} {:>6} {:>8} {:>10} {:>6}",
+        "CLIENT",
+        "AS-REQ",
+        "AS-REP",
+        "TGS-REQ",
+        "TGS-REP",
+        "ERRORS",
+        "FAILURES",
+        "PRINCIPALS",
+        "ALERTS"
+    );
+    for (ip, client) in clients.get_clients() {
+        println!(
+            "{:<39} {:>6} {:>6} {:>7} {:>7} {:>6} {:>8} {:>10} {:>6}",
+            ip.to_string(),
+            client.as_requests,
+            client.as_replies,
+            client.tgs_requests,
+            client.tgs_replies,
+            client.errors,
+            client.failures,
+            client.principals.len(),
+            client.alerts,
+        );
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let mut rx = match create_channel(&interface, READ_TIMEOUT, config.promiscuous) {
+        Ok((_, rx)) => rx,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+
+    // stop on ctrl-c or sigterm and print the clients
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    let mut tracker = Tracker {
+        config: &config,
+        streams: Streams::new(),
+        clients: Clients::new(&config),
+    };
+    listen(rx.as_mut(), &mut tracker, &running);
+    print_clients(&tracker.clients);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> StreamKey {
+        StreamKey {
+            src: "192.0.2.1:88".parse().unwrap(),
+            dst: "192.0.2.2:40000".parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn next_record_from_multiple_segments() {
+        let mut streams = Streams::new();
+        streams.add(key(), 1000, true, &[]);
+        let stream = streams.add(key(), 1001, false, &[0, 0, 0, 5, 1, 2]);
+        assert!(next_record(stream).is_none());
+        let stream = streams.add(key(), 1007, false, &[3, 4]);
+        assert!(next_record(stream).is_none());
+
+        // the last segment completes the first and starts the next record
+        let stream = streams.add(key(), 1009, false, &[5, 0, 0, 0]);
+        match next_record(stream) {
+            Some(Record::Message(message)) => assert_eq!(message, [1, 2, 3, 4, 5]),
+            _ => panic!("expected a complete record"),
+        }
+        assert!(next_record(stream).is_none());
+        assert_eq!(stream.data(), [0, 0, 0]);
+    }
+
+    #[test]
+    fn next_record_with_reserved_bit() {
+        let mut streams = Streams::new();
+        let stream = streams.add(key(), 1, false, &[0x80, 0, 0, 1, 1]);
+        assert!(matches!(next_record(stream), Some(Record::Invalid)));
+        assert!(stream.data().is_empty());
+    }
+}
@@ -0,0 +1,334 @@
+use std::fmt;
+
+use packet_utils::ber::*;
+
+use crate::names::*;
+
+// application tags of kerberos messages
+const TAG_TICKET: u32 = 1;
+const TAG_AS_REQ: u32 = 10;
+const TAG_AS_REP: u32 = 11;
+const TAG_TGS_REQ: u32 = 12;
+const TAG_TGS_REP: u32 = 13;
+const TAG_KRB_ERROR: u32 = 30;
+
+// kdc options in requests
+const OPTION_FORWARDABLE: u32 = 0x4000_0000;
+const OPTION_RENEWABLE: u32 = 0x0080_0000;
+const OPTION_CANONICALIZE: u32 = 0x0001_0000;
+
+// type of a kerberos message exchanged with the kdc
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageType {
+    AsReq,
+    AsRep,
+    TgsReq,
+    TgsRep,
+    Error,
+}
+
+impl MessageType {
+    // get the message type from the application tag of the message
+    fn from_tag(tag: Tag) -> Option<MessageType> {
+        if tag.class != Class::Application || !tag.constructed {
+            return None;
+        }
+        match tag.number {
+            TAG_AS_REQ => Some(MessageType::AsReq),
+            TAG_AS_REP => Some(MessageType::AsRep),
+            TAG_TGS_REQ => Some(MessageType::TgsReq),
+            TAG_TGS_REP => Some(MessageType::TgsRep),
+            TAG_KRB_ERROR => Some(MessageType::Error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MessageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageType::AsReq => write!(f, "AS-REQ"),
+            MessageType::AsRep => write!(f, "AS-REP"),
+            MessageType::TgsReq => write!(f, "TGS-REQ"),
+            MessageType::TgsRep => write!(f, "TGS-REP"),
+            MessageType::Error => write!(f, "KRB-ERROR"),
+        }
+    }
+}
+
+// name of a kerberos principal with its name type, e.g., "alice" or
+// "krbtgt/EXAMPLE.COM"
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrincipalName {
+    pub name_type: i64,
+    pub names: Vec<String>,
+}
+
+impl PrincipalName {
+    // parse the principal name in tlv
+    fn parse(tlv: &Tlv) -> Option<PrincipalName> {
+        let names = tlv.get_field(1)?.get_children()?;
+        Some(PrincipalName {
+            name_type: tlv.get_field(0)?.get_integer()?,
+            names: names.iter().map(Tlv::get_string).collect(),
+        })
+    }
+}
+
+impl fmt::Display for PrincipalName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.names.join("/"))
+    }
+}
+
+// format the optional principal name in realm
+pub fn format_principal(name: &Option<PrincipalName>, realm: &str) -> String {
+    match name {
+        Some(name) => format!("{}@{}", name, realm),
+        None => format!("-@{}", realm),
+    }
+}
+
+// get the types of the pre-authentication data in the sequence in tlv
+fn parse_padata(tlv: Option<Tlv>) -> Option<Vec<i64>> {
+    let tlv = match tlv {
+        Some(tlv) => tlv,
+        None => return Some(Vec::new()),
+    };
+    tlv.get_children()?
+        .iter()
+        .map(|padata| padata.get_field(1)?.get_integer())
+        .collect()
+}
+
+// format the pre-authentication data types with their names
+fn format_padata(padata: &[i64]) -> String {
+    let padata: Vec<String> = padata
+        .iter()
+        .map(|p| format!("{} ({})", p, get_padata_name(*p)))
+        .collect();
+    format!("[{}]", padata.join(", "))
+}
+
+// get the encryption type of the encrypted data in tlv
+fn parse_encrypted_etype(tlv: &Tlv) -> Option<i64> {
+    tlv.get_field(0)?.get_integer()
+}
+
+// kdc request: as-req or tgs-req
+pub struct KdcRequest {
+    pub message_type: MessageType,
+    pub padata: Vec<i64>,
+    pub kdc_options: u32,
+    pub cname: Option<PrincipalName>,
+    pub realm: String,
+    pub sname: Option<PrincipalName>,
+    pub nonce: i64,
+    pub etypes: Vec<i64>,
+}
+
+impl KdcRequest {
+    // parse the kdc-req sequence in tlv
+    fn parse(message_type: MessageType, tlv: &Tlv) -> Option<KdcRequest> {
+        let body = tlv.get_field(4)?;
+        Some(KdcRequest {
+            message_type,
+            padata: parse_padata(tlv.get_field(3))?,
+            kdc_options: body.get_field(0)?.get_bits()?,
+            cname: body.get_field(1).and_then(|n| PrincipalName::parse(&n)),
+            realm: body.get_field(2)?.get_string(),
+            sname: body.get_field(3).and_then(|n| PrincipalName::parse(&n)),
+            nonce: body.get_field(7)?.get_integer()?,
+            etypes: body
+                .get_field(8)?
+                .get_children()?
+                .iter()
+                .map(Tlv::get_integer)
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    // check if the request contains pre-authentication data that proves
+    // the knowledge of the client's key
+    pub fn has_preauth(&self) -> bool {
+        self.padata.iter().any(|p| is_preauth(*p))
+    }
+
+    // check if all requested encryption types are weak
+    pub fn is_weak(&self) -> bool {
+        !self.etypes.is_empty() && self.etypes.iter().all(|e| is_weak_etype(*e))
+    }
+}
+
+impl fmt::Display for KdcRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut options = Vec::new();
+        for (option, name) in [
+            (OPTION_FORWARDABLE, "forwardable"),
+            (OPTION_RENEWABLE, "renewable"),
+            (OPTION_CANONICALIZE, "canonicalize"),
+        ] {
+            if self.kdc_options & option != 0 {
+                options.push(name);
+            }
+        }
+        write!(
+            f,
+            "{{type: {}, client: {}, server: {}, options: {:#010x} [{}], \
+            padata: {}, etypes: {}, nonce: {}}}",
+            self.message_type,
+            format_principal(&self.cname, &self.realm),
+            format_principal(&self.sname, &self.realm),
+            self.kdc_options,
+            options.join(","),
+            format_padata(&self.padata),
+            format_etypes(&self.etypes),
+            self.nonce,
+        )
+    }
+}
+
+// kdc reply: as-rep or tgs-rep
+pub struct KdcReply {
+    pub message_type: MessageType,
+    pub padata: Vec<i64>,
+    pub crealm: String,
+    pub cname: PrincipalName,
+    pub ticket_realm: String,
+    pub sname: PrincipalName,
+
+    // encryption types of the ticket and of the part encrypted for the
+    // client
+    pub ticket_etype: i64,
+    pub etype: i64,
+}
+
+impl KdcReply {
+    // parse the kdc-rep sequence in tlv
+    fn parse(message_type: MessageType, tlv: &Tlv) -> Option<KdcReply> {
+        let ticket = tlv.get_field(5)?;
+        let tag = ticket.get_tag();
+        if tag.class != Class::Application || tag.number != TAG_TICKET {
+            return None;
+        }
+        let ticket = Tlv::parse_all(ticket.get_value())?;
+        Some(KdcReply {
+            message_type,
+            padata: parse_padata(tlv.get_field(2))?,
+            crealm: tlv.get_field(3)?.get_string(),
+            cname: PrincipalName::parse(&tlv.get_field(4)?)?,
+            ticket_realm: ticket.get_field(1)?.get_string(),
+            sname: PrincipalName::parse(&ticket.get_field(2)?)?,
+            ticket_etype: parse_encrypted_etype(&ticket.get_field(3)?)?,
+            etype: parse_encrypted_etype(&tlv.get_field(6)?)?,
+        })
+    }
+
+    // get the weak encryption type used in the reply if there is one
+    pub fn get_weak_etype(&self) -> Option<i64> {
+        [self.etype, self.ticket_etype]
+            .into_iter()
+            .find(|e| is_weak_etype(*e))
+    }
+}
+
+impl fmt::Display for KdcReply {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{type: {}, client: {}@{}, server: {}@{}, padata: {}, \
+            ticket etype: {} ({}), etype: {} ({})}}",
+            self.message_type,
+            self.cname,
+            self.crealm,
+            self.sname,
+            self.ticket_realm,
+            format_padata(&self.padata),
+            self.ticket_etype,
+            get_etype_name(self.ticket_etype),
+            self.etype,
+            get_etype_name(self.etype),
+        )
+    }
+}
+
+// kerberos error message
+pub struct KrbError {
+    pub error_code: i64,
+    pub crealm: Option<String>,
+    pub cname: Option<PrincipalName>,
+    pub realm: String,
+    pub sname: PrincipalName,
+    pub e_text: Option<String>,
+}
+
+impl KrbError {
+    // parse the krb-error sequence in tlv
+    fn parse(tlv: &Tlv) -> Option<KrbError> {
+        Some(KrbError {
+            error_code: tlv.get_field(6)?.get_integer()?,
+            crealm: tlv.get_field(7).map(|r| r.get_string()),
+            cname: tlv.get_field(8).and_then(|n| PrincipalName::parse(&n)),
+            realm: tlv.get_field(9)?.get_string(),
+            sname: PrincipalName::parse(&tlv.get_field(10)?)?,
+            e_text: tlv.get_field(11).map(|t| t.get_string()),
+        })
+    }
+}
+
+impl fmt::Display for KrbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let crealm = self.crealm.as_deref().unwrap_or(&self.realm);
+        write!(
+            f,
+            "{{type: KRB-ERROR, error: {} ({}), client: {}, server: {}@{}",
+            self.error_code,
+            get_error_name(self.error_code),
+            format_principal(&self.cname, crealm),
+            self.sname,
+            self.realm,
+        )?;
+        if let Some(e_text) = &self.e_text {
+            write!(f, ", text: {:?}", e_text)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+// kerberos message exchanged with the kdc
+pub enum KerberosMessage {
+    Request(KdcRequest),
+    Reply(KdcReply),
+    Error(KrbError),
+}
+
+impl KerberosMessage {
+    // parse the kerberos message in bytes
+    pub fn parse(bytes: &[u8]) -> Option<KerberosMessage> {
+        let message = Tlv::parse_all(bytes)?;
+        let message_type = MessageType::from_tag(message.get_tag())?;
+        let sequence = Tlv::parse_all(message.get_value())?;
+        if !sequence.get_tag().is_universal(TAG_SEQUENCE) {
+            return None;
+        }
+        match message_type {
+            MessageType::AsReq | MessageType::TgsReq => {
+                KdcRequest::parse(message_type, &sequence).map(KerberosMessage::Request)
+            }
+            MessageType::AsRep | MessageType::TgsRep => {
+                KdcReply::parse(message_type, &sequence).map(KerberosMessage::Reply)
+            }
+            MessageType::Error => KrbError::parse(&sequence).map(KerberosMessage::Error),
+        }
+    }
+}
+
+impl fmt::Display for KerberosMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KerberosMessage::Request(request) => write!(f, "{}", request),
+            KerberosMessage::Reply(reply) => write!(f, "{}", reply),
+            KerberosMessage::Error(error) => write!(f, "{}", error),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+// get the name of the encryption type etype
+pub fn get_etype_name(etype: i64) -> &'static str {
+    match etype {
+        1 => "des-cbc-crc",
+        2 => "des-cbc-md4",
+        3 => "des-cbc-md5",
+        5 => "des3-cbc-md5",
+        7 => "des3-cbc-sha1",
+        16 => "des3-cbc-sha1-kd",
+        17 => "aes128-cts-hmac-sha1-96",
+        18 => "aes256-cts-hmac-sha1-96",
+        19 => "aes128-cts-hmac-sha256-128",
+        20 => "aes256-cts-hmac-sha384-192",
+        23 => "rc4-hmac",
+        24 => "rc4-hmac-exp",
+        25 => "camellia128-cts-cmac",
+        26 => "camellia256-cts-cmac",
+        _ => "unknown",
+    }
+}
+
+// check if the encryption type etype is weak, i.e., des, triple des or rc4,
+// which are deprecated and are requested in downgrade attacks
+pub fn is_weak_etype(etype: i64) -> bool {
+    matches!(etype, 1..=7 | 16 | 23 | 24)
+}
+
+// format the encryption types with their names
+pub fn format_etypes(etypes: &[i64]) -> String {
+    let etypes: Vec<String> = etypes
+        .iter()
+        .map(|e| format!("{} ({})", e, get_etype_name(*e)))
+        .collect();
+    format!("[{}]", etypes.join(", "))
+}
+
+// get the name of the pre-authentication data type
+pub fn get_padata_name(padata_type: i64) -> &'static str {
+    match padata_type {
+        1 => "pa-tgs-req",
+        2 => "pa-enc-timestamp",
+        3 => "pa-pw-salt",
+        11 => "pa-etype-info",
+        16 => "pa-pk-as-req",
+        17 => "pa-pk-as-rep",
+        19 => "pa-etype-info2",
+        128 => "pa-pac-request",
+        133 => "pa-fx-cookie",
+        136 => "pa-fx-fast",
+        137 => "pa-fx-error",
+        138 => "pa-encrypted-challenge",
+        149 => "pa-req-enc-pa-rep",
+        150 => "pa-as-freshness",
+        165 => "pa-supported-etypes",
+        167 => "pa-pac-options",
+        _ => "unknown",
+    }
+}
+
+// check if the pre-authentication data type proves the knowledge of the
+// client's key, i.e., an encrypted timestamp or challenge, fast or pkinit
+pub fn is_preauth(padata_type: i64) -> bool {
+    matches!(padata_type, 2 | 16 | 136 | 138)
+}
+
+// get the name of the kerberos error code
+pub fn get_error_name(error_code: i64) -> &'static str {
+    match error_code {
+        0 => "KDC_ERR_NONE",
+        3 => "KDC_ERR_BAD_PVNO",
+        6 => "KDC_ERR_C_PRINCIPAL_UNKNOWN",
+        7 => "KDC_ERR_S_PRINCIPAL_UNKNOWN",
+        12 => "KDC_ERR_POLICY",
+        14 => "KDC_ERR_ETYPE_NOSUPP",
+        18 => "KDC_ERR_CLIENT_REVOKED",
+        23 => "KDC_ERR_KEY_EXPIRED",
+        24 => "KDC_ERR_PREAUTH_FAILED",
+        25 => "KDC_ERR_PREAUTH_REQUIRED",
+        31 => "KRB_AP_ERR_BAD_INTEGRITY",
+        32 => "KRB_AP_ERR_TKT_EXPIRED",
+        37 => "KRB_AP_ERR_SKEW",
+        41 => "KRB_AP_ERR_MODIFIED",
+        52 => "KRB_ERR_RESPONSE_TOO_BIG",
+        60 => "KRB_ERR_GENERIC",
+        68 => "KDC_ERR_WRONG_REALM",
+        _ => "unknown",
+    }
+}
+
+// check if the error code reports a failed authentication of the client,
+// e.g., a wrong password or an unknown principal
+pub fn is_failure(error_code: i64) -> bool {
+    matches!(error_code, 6 | 18 | 23 | 24)
+}
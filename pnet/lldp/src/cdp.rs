@@ -0,0 +1,195 @@
+use pnet::datalink::MacAddr;
+use pnet::packet::ethernet::EthernetPacket;
+use pnet::packet::Packet;
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::lldp::{format_capabilities, format_text};
+
+// cdp frames are sent with an 802.2 llc and snap header with the cisco oui
+// and the cdp protocol id
+const SNAP_HEADER: [u8; 8] = [0xaa, 0xaa, 0x03, 0x00, 0x00, 0x0c, 0x20, 0x00];
+
+// largest value of the ethertype field that is a length of an 802.3 frame
+const MAX_LENGTH: u16 = 1500;
+
+// cdp tlv types
+const TLV_DEVICE_ID: u16 = 0x0001;
+const TLV_ADDRESSES: u16 = 0x0002;
+const TLV_PORT_ID: u16 = 0x0003;
+const TLV_CAPABILITIES: u16 = 0x0004;
+const TLV_SOFTWARE_VERSION: u16 = 0x0005;
+const TLV_PLATFORM: u16 = 0x0006;
+const TLV_NATIVE_VLAN: u16 = 0x000a;
+const TLV_SYSTEM_NAME: u16 = 0x0014;
+const TLV_MANAGEMENT_ADDRESSES: u16 = 0x0016;
+
+// protocols of addresses: nlpid ip and 802.2 snap ipv6
+const PROTOCOL_IPV4: &[u8] = &[0xcc];
+const PROTOCOL_IPV6: &[u8] = &[0xaa, 0xaa, 0x03, 0x00, 0x00, 0x00, 0x86, 0xdd];
+
+// names of the capabilities bits
+pub const CAPABILITIES: [&str; 11] = [
+    "router",
+    "transparent bridge",
+    "source route bridge",
+    "switch",
+    "host",
+    "igmp capable",
+    "repeater",
+    "voip phone",
+    "remotely managed",
+    "cvta",
+    "two-port mac relay",
+];
+
+// parse the ip addresses in the addresses or management addresses tlv
+fn parse_addresses(value: &[u8]) -> Option<Vec<IpAddr>> {
+    // Address Format:
+    // number of addresses (4 bytes), then for each address the protocol
+    // type (1 byte), protocol length (1 byte), protocol, address length (2
+    // bytes) and the address
+    let count = u32::from_be_bytes(value.get(..4)?.try_into().ok()?);
+    let mut offset = 4;
+    let mut addresses = Vec::new();
+    for _ in 0..count {
+        let protocol_length = usize::from(*value.get(offset + 1)?);
+        let protocol = value.get(offset + 2..offset + 2 + protocol_length)?;
+        offset += 2 + protocol_length;
+        let length = value.get(offset..offset + 2)?;
+        let length = usize::from(u16::from_be_bytes([length[0], length[1]]));
+        let address = value.get(offset + 2..offset + 2 + length)?;
+        offset += 2 + length;
+        match protocol {
+            PROTOCOL_IPV4 => {
+                let octets: [u8; 4] = address.try_into().ok()?;
+                addresses.push(IpAddr::V4(Ipv4Addr::from(octets)));
+            }
+            PROTOCOL_IPV6 => {
+                let octets: [u8; 16] = address.try_into().ok()?;
+                addresses.push(IpAddr::V6(Ipv6Addr::from(octets)));
+            }
+            _ => (),
+        }
+    }
+    Some(addresses)
+}
+
+// cdp frame with the neighbor information it announces
+#[derive(Default)]
+pub struct CdpFrame {
+    pub source: MacAddr,
+    pub version: u8,
+    pub ttl: u8,
+    pub device_id: String,
+    pub port_id: String,
+    pub capabilities: Option<u32>,
+    pub software_version: Option<String>,
+    pub platform: Option<String>,
+    pub native_vlan: Option<u16>,
+    pub system_name: Option<String>,
+    pub addresses: Vec<IpAddr>,
+    pub management_addresses: Vec<IpAddr>,
+}
+
+impl CdpFrame {
+    // parse the cdp frame in the ethernet frame
+    pub fn parse(frame: &[u8]) -> Option<CdpFrame> {
+        // cdp uses 802.3 frames, so the ethertype field contains the length
+        let ethernet_packet = EthernetPacket::new(frame)?;
+        let length = ethernet_packet.get_ethertype().0;
+        if length > MAX_LENGTH {
+            return None;
+        }
+        let payload = ethernet_packet.payload().get(..usize::from(length))?;
+        if payload.get(..SNAP_HEADER.len())? != SNAP_HEADER {
+            return None;
+        }
+
+        // Cdp Header:
+        // version (1 byte), ttl (1 byte), checksum (2 bytes)
+        let cdp = &payload[SNAP_HEADER.len()..];
+        let header = cdp.get(..4)?;
+        let mut frame = CdpFrame {
+            source: ethernet_packet.get_source(),
+            version: header[0],
+            ttl: header[1],
+            ..Default::default()
+        };
+
+        // Tlv Format:
+        // type (2 bytes), length including the header (2 bytes), value
+        let mut offset = 4;
+        while offset + 4 <= cdp.len() {
+            let tlv_type = u16::from_be_bytes([cdp[offset], cdp[offset + 1]]);
+            let length = usize::from(u16::from_be_bytes([cdp[offset + 2], cdp[offset + 3]]));
+            if length < 4 {
+                return None;
+            }
+            let value = cdp.get(offset + 4..offset + length)?;
+            offset += length;
+            match tlv_type {
+                TLV_DEVICE_ID => frame.device_id = format_text(value),
+                TLV_ADDRESSES => frame.addresses = parse_addresses(value)?,
+                TLV_PORT_ID => frame.port_id = format_text(value),
+                TLV_CAPABILITIES => {
+                    frame.capabilities = Some(u32::from_be_bytes(value.try_into().ok()?));
+                }
+                TLV_SOFTWARE_VERSION => frame.software_version = Some(format_text(value)),
+                TLV_PLATFORM => frame.platform = Some(format_text(value)),
+                TLV_NATIVE_VLAN => {
+                    frame.native_vlan = Some(u16::from_be_bytes(value.try_into().ok()?));
+                }
+                TLV_SYSTEM_NAME => frame.system_name = Some(format_text(value)),
+                TLV_MANAGEMENT_ADDRESSES => {
+                    frame.management_addresses = parse_addresses(value)?;
+                }
+                _ => (),
+            }
+        }
+        if frame.device_id.is_empty() {
+            return None;
+        }
+        Some(frame)
+    }
+}
+
+impl fmt::Display for CdpFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cdpv{} from {}: {{device id: {}, port id: {}, ttl: {}",
+            self.version, self.source, self.device_id, self.port_id, self.ttl
+        )?;
+        if let Some(name) = &self.system_name {
+            write!(f, ", system name: {}", name)?;
+        }
+        if let Some(platform) = &self.platform {
+            write!(f, ", platform: {}", platform)?;
+        }
+        if let Some(version) = &self.software_version {
+            write!(f, ", software version: {}", version)?;
+        }
+        if let Some(capabilities) = self.capabilities {
+            write!(
+                f,
+                ", capabilities: {}",
+                format_capabilities(capabilities, &CAPABILITIES)
+            )?;
+        }
+        if let Some(vlan) = self.native_vlan {
+            write!(f, ", native vlan: {}", vlan)?;
+        }
+        for (name, addresses) in [
+            ("addresses", &self.addresses),
+            ("management addresses", &self.management_addresses),
+        ] {
+            if !addresses.is_empty() {
+                let addresses = addresses.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+                write!(f, ", {}: [{}]", name, addresses.join(", "))?;
+            }
+        }
+        write!(f, "}}")
+    }
+}
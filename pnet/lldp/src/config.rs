@@ -0,0 +1,33 @@
+use clap::Parser;
+
+// command line configuration of the lldp tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Listen for lldp and cdp frames and show the neighbors they announce")]
+pub struct Config {
+    // network interface to listen on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Listen for neighbor announcements on the network interface \
+        NAME; by default, the first interface that is up, is not a loopback \
+        and has an ip address is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., if the network \
+        card drops frames sent to the multicast addresses of lldp and cdp"
+    )]
+    pub promiscuous: bool,
+
+    // only print neighbor changes
+    #[arg(
+        short,
+        long,
+        help = "Only print neighbor changes instead of every received frame"
+    )]
+    pub quiet: bool,
+}
@@ -0,0 +1,241 @@
+use packet_utils::hex::format_hex;
+use pnet::datalink::MacAddr;
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket};
+use pnet::packet::Packet;
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+// lldp tlv types
+const TLV_END: u8 = 0;
+const TLV_CHASSIS_ID: u8 = 1;
+const TLV_PORT_ID: u8 = 2;
+const TLV_TTL: u8 = 3;
+const TLV_PORT_DESCRIPTION: u8 = 4;
+const TLV_SYSTEM_NAME: u8 = 5;
+const TLV_SYSTEM_DESCRIPTION: u8 = 6;
+const TLV_CAPABILITIES: u8 = 7;
+const TLV_MANAGEMENT_ADDRESS: u8 = 8;
+const TLV_ORGANIZATION: u8 = 127;
+
+// chassis id subtypes that contain a mac or network address
+const CHASSIS_MAC_ADDRESS: u8 = 4;
+const CHASSIS_NETWORK_ADDRESS: u8 = 5;
+
+// port id subtypes that contain a mac or network address
+const PORT_MAC_ADDRESS: u8 = 3;
+const PORT_NETWORK_ADDRESS: u8 = 4;
+
+// iana address families used in network and management addresses
+const FAMILY_IPV4: u8 = 1;
+const FAMILY_IPV6: u8 = 2;
+const FAMILY_MAC: u8 = 6;
+
+// ieee 802.1 organizationally specific tlv with the port vlan id
+const OUI_IEEE_8021: [u8; 3] = [0x00, 0x80, 0xc2];
+const IEEE_8021_PORT_VLAN: u8 = 1;
+
+// names of the system capabilities bits
+pub const CAPABILITIES: [&str; 11] = [
+    "other",
+    "repeater",
+    "bridge",
+    "wlan access point",
+    "router",
+    "telephone",
+    "docsis cable device",
+    "station",
+    "c-vlan component",
+    "s-vlan component",
+    "tpmr",
+];
+
+// format bytes as string if they are printable text, otherwise as hex;
+// descriptions often span multiple lines, so line breaks and tabs become
+// spaces
+pub fn format_text(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) if !text.chars().any(|c| c.is_control() && !c.is_whitespace()) => {
+            text.replace(['\r', '\n', '\t'], " ")
+        }
+        _ => format_hex(bytes),
+    }
+}
+
+// format the names of the capabilities bits set in capabilities; names
+// contains the name of each bit starting with the least significant one
+pub fn format_capabilities(capabilities: u32, names: &[&str]) -> String {
+    let names = names
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| capabilities & 1 << i != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>();
+    format!("[{}]", names.join(", "))
+}
+
+// get the address of the iana address family in bytes
+fn parse_address(family: u8, bytes: &[u8]) -> Option<String> {
+    match family {
+        FAMILY_IPV4 => {
+            let octets: [u8; 4] = bytes.try_into().ok()?;
+            Some(IpAddr::V4(Ipv4Addr::from(octets)).to_string())
+        }
+        FAMILY_IPV6 => {
+            let octets: [u8; 16] = bytes.try_into().ok()?;
+            Some(IpAddr::V6(Ipv6Addr::from(octets)).to_string())
+        }
+        FAMILY_MAC => parse_mac(bytes),
+        _ => None,
+    }
+}
+
+// get the mac address in bytes
+fn parse_mac(bytes: &[u8]) -> Option<String> {
+    let octets: [u8; 6] = bytes.try_into().ok()?;
+    Some(MacAddr::from(octets).to_string())
+}
+
+// get the chassis or port id with subtype in bytes as string; mac and
+// network addresses are formatted as addresses, other ids as text
+fn parse_id(subtype: u8, bytes: &[u8], mac: u8, network: u8) -> String {
+    let address = match subtype {
+        s if s == mac => parse_mac(bytes),
+        s if s == network => bytes
+            .split_first()
+            .and_then(|(family, address)| parse_address(*family, address)),
+        _ => None,
+    };
+    address.unwrap_or_else(|| format_text(bytes))
+}
+
+// lldp data unit with the neighbor information it announces
+#[derive(Default)]
+pub struct LldpFrame {
+    pub source: MacAddr,
+    pub chassis_id: String,
+    pub port_id: String,
+    pub ttl: u16,
+    pub port_description: Option<String>,
+    pub system_name: Option<String>,
+    pub system_description: Option<String>,
+
+    // supported and enabled system capabilities
+    pub capabilities: Option<(u16, u16)>,
+
+    pub management_addresses: Vec<String>,
+    pub port_vlan: Option<u16>,
+}
+
+impl LldpFrame {
+    // parse the lldp data unit in the ethernet frame
+    pub fn parse(frame: &[u8]) -> Option<LldpFrame> {
+        let ethernet_packet = EthernetPacket::new(frame)?;
+        if ethernet_packet.get_ethertype() != EtherTypes::Lldp {
+            return None;
+        }
+        let mut lldp = LldpFrame {
+            source: ethernet_packet.get_source(),
+            ..Default::default()
+        };
+
+        // Tlv Format:
+        // 7 bit type, 9 bit length, value; the first three tlvs are the
+        // mandatory chassis id, port id and ttl
+        let payload = ethernet_packet.payload();
+        let mut offset = 0;
+        let mut mandatory = 0;
+        while offset + 2 <= payload.len() {
+            let header = u16::from_be_bytes([payload[offset], payload[offset + 1]]);
+            let tlv_type = (header >> 9) as u8;
+            let length = usize::from(header & 0x1ff);
+            let value = payload.get(offset + 2..offset + 2 + length)?;
+            offset += 2 + length;
+            match tlv_type {
+                TLV_END => break,
+                TLV_CHASSIS_ID => {
+                    let (subtype, id) = value.split_first()?;
+                    lldp.chassis_id =
+                        parse_id(*subtype, id, CHASSIS_MAC_ADDRESS, CHASSIS_NETWORK_ADDRESS);
+                    mandatory += 1;
+                }
+                TLV_PORT_ID => {
+                    let (subtype, id) = value.split_first()?;
+                    lldp.port_id = parse_id(*subtype, id, PORT_MAC_ADDRESS, PORT_NETWORK_ADDRESS);
+                    mandatory += 1;
+                }
+                TLV_TTL => {
+                    lldp.ttl = u16::from_be_bytes(value.try_into().ok()?);
+                    mandatory += 1;
+                }
+                TLV_PORT_DESCRIPTION => lldp.port_description = Some(format_text(value)),
+                TLV_SYSTEM_NAME => lldp.system_name = Some(format_text(value)),
+                TLV_SYSTEM_DESCRIPTION => lldp.system_description = Some(format_text(value)),
+                TLV_CAPABILITIES if value.len() == 4 => {
+                    let supported = u16::from_be_bytes([value[0], value[1]]);
+                    let enabled = u16::from_be_bytes([value[2], value[3]]);
+                    lldp.capabilities = Some((supported, enabled));
+                }
+                TLV_MANAGEMENT_ADDRESS => {
+                    // address string length includes the address subtype
+                    let length = usize::from(*value.first()?);
+                    let family = *value.get(1)?;
+                    let address = value.get(2..1 + length)?;
+                    if let Some(address) = parse_address(family, address) {
+                        lldp.management_addresses.push(address);
+                    }
+                }
+                TLV_ORGANIZATION
+                    if value.len() >= 6
+                        && value[..3] == OUI_IEEE_8021
+                        && value[3] == IEEE_8021_PORT_VLAN =>
+                {
+                    lldp.port_vlan = Some(u16::from_be_bytes([value[4], value[5]]));
+                }
+                _ => (),
+            }
+        }
+        if mandatory < 3 {
+            return None;
+        }
+        Some(lldp)
+    }
+}
+
+impl fmt::Display for LldpFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "lldp from {}: {{chassis id: {}, port id: {}, ttl: {}",
+            self.source, self.chassis_id, self.port_id, self.ttl
+        )?;
+        if let Some(description) = &self.port_description {
+            write!(f, ", port description: {}", description)?;
+        }
+        if let Some(name) = &self.system_name {
+            write!(f, ", system name: {}", name)?;
+        }
+        if let Some(description) = &self.system_description {
+            write!(f, ", system description: {}", description)?;
+        }
+        if let Some((supported, enabled)) = self.capabilities {
+            write!(
+                f,
+                ", capabilities: {}, enabled: {}",
+                format_capabilities(supported.into(), &CAPABILITIES),
+                format_capabilities(enabled.into(), &CAPABILITIES)
+            )?;
+        }
+        if !self.management_addresses.is_empty() {
+            write!(
+                f,
+                ", management addresses: [{}]",
+                self.management_addresses.join(", ")
+            )?;
+        }
+        if let Some(vlan) = self.port_vlan {
+            write!(f, ", port vlan: {}", vlan)?;
+        }
+        write!(f, "}}")
+    }
+}
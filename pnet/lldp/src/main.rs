@@ -0,0 +1,131 @@
+extern crate pnet;
+
+mod cdp;
+mod config;
+mod lldp;
+mod table;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::interface::find_interface;
+use pnet::datalink::DataLinkReceiver;
+
+use std::io;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use cdp::CdpFrame;
+use config::Config;
+use lldp::LldpFrame;
+use table::NeighborTable;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// receive lldp and cdp frames, add them to the table and print the frames,
+// the changes they caused and the updated table until running is unset
+fn monitor(
+    rx: &mut dyn DataLinkReceiver,
+    table: &mut NeighborTable,
+    config: &Config,
+    running: &AtomicBool,
+) {
+    while running.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => Some(frame),
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => None,
+                _ => panic!("An error occurred when reading frames: {}", e),
+            },
+        };
+
+        // neighbors also expire while no frames are received
+        let mut events = table.expire();
+        if let Some(lldp) = frame.and_then(LldpFrame::parse) {
+            if !config.quiet {
+                println!("{}", lldp);
+            }
+            events.extend(table.add_lldp(&lldp));
+        } else if let Some(cdp) = frame.and_then(CdpFrame::parse) {
+            if !config.quiet {
+                println!("{}", cdp);
+            }
+            events.extend(table.add_cdp(&cdp));
+        }
+        if events.is_empty() {
+            continue;
+        }
+        for event in events {
+            println!("{}", event);
+        }
+        print_table(table);
+    }
+}
+
+// print the neighbors in the table
+fn print_table(table: &NeighborTable) {
+    let now = Instant::now();
+    println!("--- neighbors ---");
+    println!(
+        "{:<5} {:<20} {:<20} {:<17} {:<20} {:<20} {:<20} {:>4} {:<24} {:>6} CAPABILITIES",
+        "PROTO",
+        "ID",
+        "PORT ID",
+        "MAC",
+        "SYSTEM NAME",
+        "PORT DESCRIPTION",
+        "PLATFORM",
+        "VLAN",
+        "MANAGEMENT",
+        "TTL"
+    );
+    for ((id, port), entry) in table.get_neighbors() {
+        let neighbor = &entry.neighbor;
+        println!(
+            "{:<5} {:<20} {:<20} {:<17} {:<20} {:<20} {:<20} {:>4} {:<24} {:>4} s {}",
+            neighbor.protocol.to_string(),
+            id,
+            port,
+            neighbor.source.to_string(),
+            neighbor.system_name.as_deref().unwrap_or("-"),
+            neighbor.port_description.as_deref().unwrap_or("-"),
+            neighbor.platform.as_deref().unwrap_or("-"),
+            neighbor.vlan.map_or("-".to_string(), |v| v.to_string()),
+            neighbor.management_addresses.join(","),
+            entry.expires.saturating_duration_since(now).as_secs(),
+            neighbor.capabilities.as_deref().unwrap_or("-"),
+        );
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let mut rx = match create_channel(&interface, READ_TIMEOUT, config.promiscuous) {
+        Ok((_, rx)) => rx,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+
+    // stop on ctrl-c or sigterm and print the table
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    let mut table = NeighborTable::default();
+    monitor(rx.as_mut(), &mut table, &config, &running);
+    print_table(&table);
+}
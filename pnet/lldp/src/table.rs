@@ -0,0 +1,183 @@
+use pnet::datalink::MacAddr;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::cdp::{self, CdpFrame};
+use crate::lldp::{self, format_capabilities, LldpFrame};
+
+// discovery protocol a neighbor was learned from
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Protocol {
+    Lldp,
+    Cdp,
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Protocol::Lldp => write!(f, "lldp"),
+            Protocol::Cdp => write!(f, "cdp"),
+        }
+    }
+}
+
+// neighbor learned from lldp or cdp frames
+#[derive(PartialEq, Eq)]
+pub struct Neighbor {
+    pub protocol: Protocol,
+    pub source: MacAddr,
+    pub system_name: Option<String>,
+    pub port_description: Option<String>,
+    pub platform: Option<String>,
+    pub capabilities: Option<String>,
+    pub management_addresses: Vec<String>,
+    pub vlan: Option<u16>,
+}
+
+impl Neighbor {
+    // get the neighbor announced in the lldp frame
+    fn from_lldp(lldp: &LldpFrame) -> Neighbor {
+        Neighbor {
+            protocol: Protocol::Lldp,
+            source: lldp.source,
+            system_name: lldp.system_name.clone(),
+            port_description: lldp.port_description.clone(),
+            platform: lldp.system_description.clone(),
+            capabilities: lldp
+                .capabilities
+                .map(|(_, enabled)| format_capabilities(enabled.into(), &lldp::CAPABILITIES)),
+            management_addresses: lldp.management_addresses.clone(),
+            vlan: lldp.port_vlan,
+        }
+    }
+
+    // get the neighbor announced in the cdp frame; neighbors without
+    // management addresses are reached via their interface addresses
+    fn from_cdp(cdp: &CdpFrame) -> Neighbor {
+        let addresses = match cdp.management_addresses.is_empty() {
+            true => &cdp.addresses,
+            false => &cdp.management_addresses,
+        };
+        Neighbor {
+            protocol: Protocol::Cdp,
+            source: cdp.source,
+            system_name: cdp.system_name.clone(),
+            port_description: None,
+            platform: cdp.platform.clone(),
+            capabilities: cdp
+                .capabilities
+                .map(|c| format_capabilities(c, &cdp::CAPABILITIES)),
+            management_addresses: addresses.iter().map(|a| a.to_string()).collect(),
+            vlan: cdp.native_vlan,
+        }
+    }
+}
+
+// neighbor in the table with the time it expires
+pub struct Entry {
+    pub neighbor: Neighbor,
+    pub expires: Instant,
+    pub last_seen: Instant,
+    pub frames: u64,
+}
+
+// neighbors are identified by their lldp chassis id or cdp device id and
+// their port id
+pub type NeighborKey = (String, String);
+
+// change of the neighbor table
+pub enum Event {
+    New(NeighborKey),
+    Changed(NeighborKey),
+    Shutdown(NeighborKey),
+    Expired(NeighborKey),
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (event, (id, port)) = match self {
+            Event::New(key) => ("new", key),
+            Event::Changed(key) => ("changed", key),
+            Event::Shutdown(key) => ("shut down", key),
+            Event::Expired(key) => ("expired", key),
+        };
+        write!(f, "neighbor {}: id {}, port id {}", event, id, port)
+    }
+}
+
+// table of the neighbors announced in lldp and cdp frames
+#[derive(Default)]
+pub struct NeighborTable {
+    neighbors: BTreeMap<NeighborKey, Entry>,
+}
+
+impl NeighborTable {
+    // add the neighbor announced in the lldp frame to the table and get the
+    // event it caused
+    pub fn add_lldp(&mut self, lldp: &LldpFrame) -> Option<Event> {
+        let key = (lldp.chassis_id.clone(), lldp.port_id.clone());
+        self.add(key, lldp.ttl, Neighbor::from_lldp(lldp))
+    }
+
+    // add the neighbor announced in the cdp frame to the table and get the
+    // event it caused
+    pub fn add_cdp(&mut self, cdp: &CdpFrame) -> Option<Event> {
+        let key = (cdp.device_id.clone(), cdp.port_id.clone());
+        self.add(key, cdp.ttl.into(), Neighbor::from_cdp(cdp))
+    }
+
+    // add the neighbor with key and ttl to the table and get the event it
+    // caused
+    fn add(&mut self, key: NeighborKey, ttl: u16, neighbor: Neighbor) -> Option<Event> {
+        // a ttl of zero announces that the neighbor shuts down
+        if ttl == 0 {
+            return self.neighbors.remove(&key).map(|_| Event::Shutdown(key));
+        }
+
+        let now = Instant::now();
+        let expires = now + Duration::from_secs(ttl.into());
+        match self.neighbors.get_mut(&key) {
+            Some(entry) => {
+                entry.expires = expires;
+                entry.last_seen = now;
+                entry.frames += 1;
+                if entry.neighbor == neighbor {
+                    return None;
+                }
+                entry.neighbor = neighbor;
+                Some(Event::Changed(key))
+            }
+            None => {
+                let entry = Entry {
+                    neighbor,
+                    expires,
+                    last_seen: now,
+                    frames: 1,
+                };
+                self.neighbors.insert(key.clone(), entry);
+                Some(Event::New(key))
+            }
+        }
+    }
+
+    // remove the neighbors whose ttl expired and get the events it caused
+    pub fn expire(&mut self) -> Vec<Event> {
+        let now = Instant::now();
+        let mut events = Vec::new();
+        self.neighbors.retain(|key, entry| {
+            if entry.expires > now {
+                return true;
+            }
+            events.push(Event::Expired(key.clone()));
+            false
+        });
+        events
+    }
+
+    // get the neighbors sorted by their ids and port ids
+    pub fn get_neighbors(&self) -> &BTreeMap<NeighborKey, Entry> {
+        &self.neighbors
+    }
+}
@@ -0,0 +1,73 @@
+use pnet::datalink::MacAddr;
+use pnet::ipnetwork::Ipv6Network;
+
+use std::fmt;
+use std::net::Ipv6Addr;
+
+// suspicious ndp traffic that indicates neighbor spoofing or rogue routers
+pub enum Alert {
+    // ip address was announced with another mac address than the one it is
+    // bound to
+    Conflict {
+        ip: Ipv6Addr,
+        old: MacAddr,
+        new: MacAddr,
+    },
+
+    // source link-layer address option differs from the source mac address
+    // of the ethernet frame
+    Mismatch {
+        ip: Ipv6Addr,
+        sender: MacAddr,
+        source: MacAddr,
+    },
+
+    // router advertisement was sent by a router that is not expected
+    RogueRouter {
+        ip: Ipv6Addr,
+        mac: MacAddr,
+    },
+
+    // router advertisement contains a prefix or dns server that is not
+    // expected
+    RoguePrefix {
+        router: Ipv6Addr,
+        prefix: Ipv6Network,
+    },
+    RogueDns {
+        router: Ipv6Addr,
+        server: Ipv6Addr,
+    },
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alert::Conflict { ip, old, new } => write!(
+                f,
+                "ALERT: conflicting binding: {} moved from {} to {}",
+                ip, old, new
+            ),
+            Alert::Mismatch { ip, sender, source } => write!(
+                f,
+                "ALERT: sender mismatch: {} is-at {} sent from {}",
+                ip, sender, source
+            ),
+            Alert::RogueRouter { ip, mac } => write!(
+                f,
+                "ALERT: rogue router advertisement: {} ({}) is not an expected router",
+                ip, mac
+            ),
+            Alert::RoguePrefix { router, prefix } => write!(
+                f,
+                "ALERT: unexpected prefix: {} advertised by {}",
+                prefix, router
+            ),
+            Alert::RogueDns { router, server } => write!(
+                f,
+                "ALERT: unexpected dns server: {} advertised by {}",
+                server, router
+            ),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use clap::Parser;
+
+use pnet::datalink::MacAddr;
+use pnet::ipnetwork::Ipv6Network;
+
+use std::net::Ipv6Addr;
+
+use crate::rogue::Router;
+
+// parse a router given by its ipv6 or mac address
+fn parse_router(s: &str) -> Result<Router, String> {
+    if let Ok(ip) = s.parse::<Ipv6Addr>() {
+        return Ok(Router::Ip(ip));
+    }
+    s.parse::<MacAddr>()
+        .map(Router::Mac)
+        .map_err(|_| format!("invalid router address: {}", s))
+}
+
+// parse an ipv6 prefix
+fn parse_prefix(s: &str) -> Result<Ipv6Network, String> {
+    s.parse::<Ipv6Network>()
+        .map_err(|_| format!("invalid prefix: {}", s))
+}
+
+// command line configuration of the ndp tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Monitor ipv6 neighbor discovery messages")]
+pub struct Config {
+    // network interface to capture ndp messages on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Capture ndp messages on the network interface NAME; by \
+        default, the first interface that is up, is not a loopback and has an \
+        ip address is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., to see ndp \
+        messages sent to the solicited-node multicast groups of other hosts"
+    )]
+    pub promiscuous: bool,
+
+    // only print alerts
+    #[arg(short, long, help = "Only print alerts instead of every ndp message")]
+    pub quiet: bool,
+
+    // expected routers, prefixes and dns servers in router advertisements
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        value_parser = parse_router,
+        help = "Alert on router advertisements that are not sent by the \
+        router with the ipv6 or mac address ADDRESS; can be given multiple \
+        times; by default, the sender of the first router advertisement is \
+        expected"
+    )]
+    pub router: Vec<Router>,
+    #[arg(
+        long,
+        value_name = "PREFIX",
+        value_parser = parse_prefix,
+        help = "Alert on router advertisements with prefixes outside of \
+        PREFIX; can be given multiple times; by default, the prefixes of the \
+        first router advertisement are expected"
+    )]
+    pub prefix: Vec<Ipv6Network>,
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        help = "Alert on router advertisements with other dns servers than \
+        ADDRESS; can be given multiple times; by default, the dns servers of \
+        the first router advertisement are expected"
+    )]
+    pub dns: Vec<Ipv6Addr>,
+}
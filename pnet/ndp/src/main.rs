@@ -0,0 +1,110 @@
+extern crate pnet;
+
+mod alert;
+mod config;
+mod message;
+mod rogue;
+mod table;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::interface::find_interface;
+use pnet::datalink::DataLinkReceiver;
+
+use std::io;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::Duration;
+
+use config::Config;
+use message::NdpMessage;
+use rogue::RaGuard;
+use table::NeighborTable;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// receive ndp messages, add them to the table, check router advertisements
+// with the guard and print the messages and the alerts they caused until
+// running is unset
+fn monitor(
+    rx: &mut dyn DataLinkReceiver,
+    table: &mut NeighborTable,
+    guard: &mut RaGuard,
+    config: &Config,
+    running: &AtomicBool,
+) {
+    while running.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading ndp messages: {}", e),
+            },
+        };
+        let message = match NdpMessage::parse(frame) {
+            Some(message) => message,
+            None => continue,
+        };
+        if !config.quiet {
+            println!("{}", message);
+        }
+        let mut alerts = table.add(&message);
+        alerts.extend(guard.check(&message));
+        for alert in alerts {
+            println!("{}", alert);
+        }
+    }
+}
+
+// print the neighbors in the table
+fn print_table(table: &NeighborTable) {
+    println!("--- neighbor table ---");
+    println!(
+        "{:<39} {:<17} {:<6} {:>8} {:>7} {:>10}",
+        "IP", "MAC", "ROUTER", "MESSAGES", "CHANGES", "LAST SEEN"
+    );
+    for (ip, neighbor) in table.get_neighbors() {
+        println!(
+            "{:<39} {:<17} {:<6} {:>8} {:>7} {:>8.1} s",
+            ip.to_string(),
+            neighbor.mac.to_string(),
+            if neighbor.router { "yes" } else { "no" },
+            neighbor.messages,
+            neighbor.changes,
+            neighbor.last_seen.elapsed().as_secs_f64(),
+        );
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let mut rx = match create_channel(&interface, READ_TIMEOUT, config.promiscuous) {
+        Ok((_, rx)) => rx,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+
+    // stop on ctrl-c or sigterm and print the table
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    let mut table = NeighborTable::default();
+    let mut guard = RaGuard::new(&config);
+    monitor(rx.as_mut(), &mut table, &mut guard, &config, &running);
+    print_table(&table);
+}
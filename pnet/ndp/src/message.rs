@@ -0,0 +1,408 @@
+use packet_utils::bytes::read_be_u32;
+use pnet::datalink::MacAddr;
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::Packet;
+
+use std::fmt;
+use std::net::Ipv6Addr;
+
+// icmpv6 types of ndp messages
+const ROUTER_SOLICITATION: u8 = 133;
+const ROUTER_ADVERTISEMENT: u8 = 134;
+const NEIGHBOR_SOLICITATION: u8 = 135;
+const NEIGHBOR_ADVERTISEMENT: u8 = 136;
+const REDIRECT: u8 = 137;
+
+// ndp option types
+const OPTION_SOURCE_LINK_LAYER: u8 = 1;
+const OPTION_TARGET_LINK_LAYER: u8 = 2;
+const OPTION_PREFIX_INFORMATION: u8 = 3;
+const OPTION_MTU: u8 = 5;
+const OPTION_ROUTE_INFORMATION: u8 = 24;
+const OPTION_RDNSS: u8 = 25;
+const OPTION_DNSSL: u8 = 31;
+
+// hop limit of valid ndp messages; other messages may come from outside the
+// link and are ignored
+const NDP_HOP_LIMIT: u8 = 255;
+
+// get the ipv6 address in the 16 bytes of bytes
+fn read_ipv6(bytes: &[u8]) -> Ipv6Addr {
+    let octets: [u8; 16] = bytes.try_into().expect("slice with incorrect length");
+    Ipv6Addr::from(octets)
+}
+
+// get the prefix of length bits in bytes; missing bytes are zero
+fn read_prefix(bytes: &[u8]) -> Ipv6Addr {
+    let mut octets = [0u8; 16];
+    let length = bytes.len().min(16);
+    octets[..length].copy_from_slice(&bytes[..length]);
+    Ipv6Addr::from(octets)
+}
+
+// router preference in router advertisements and route information options
+fn format_preference(flags: u8) -> &'static str {
+    match (flags >> 3) & 0x3 {
+        0 => "medium",
+        1 => "high",
+        3 => "low",
+        _ => "reserved",
+    }
+}
+
+// parse the domain names in data that is padded with zeros
+fn read_domains(data: &[u8]) -> Vec<String> {
+    let mut domains = Vec::new();
+    let mut labels = Vec::new();
+    let mut offset = 0;
+    while let Some(length) = data.get(offset).map(|l| usize::from(*l)) {
+        if length == 0 {
+            if !labels.is_empty() {
+                domains.push(labels.join("."));
+                labels.clear();
+            }
+            offset += 1;
+            continue;
+        }
+        match data.get(offset + 1..offset + 1 + length) {
+            Some(label) => labels.push(String::from_utf8_lossy(label).into_owned()),
+            None => break,
+        }
+        offset += 1 + length;
+    }
+    domains
+}
+
+// ndp option
+pub enum NdpOption {
+    SourceLinkLayer(MacAddr),
+    TargetLinkLayer(MacAddr),
+
+    // prefix with its length, on-link and autonomous flags and valid and
+    // preferred lifetimes
+    PrefixInformation {
+        prefix: Ipv6Addr,
+        length: u8,
+        on_link: bool,
+        autonomous: bool,
+        valid: u32,
+        preferred: u32,
+    },
+
+    Mtu(u32),
+
+    // route with its prefix length, preference flags and lifetime
+    RouteInformation {
+        prefix: Ipv6Addr,
+        length: u8,
+        flags: u8,
+        lifetime: u32,
+    },
+
+    // recursive dns servers and dns search list with their lifetime
+    Rdnss(u32, Vec<Ipv6Addr>),
+    Dnssl(u32, Vec<String>),
+
+    Other(u8, Vec<u8>),
+}
+
+impl NdpOption {
+    // parse the ndp option with type and data without its type and length
+    // fields; returns none if data is too short for the type
+    fn parse(option_type: u8, data: &[u8]) -> Option<NdpOption> {
+        let option = match option_type {
+            OPTION_SOURCE_LINK_LAYER | OPTION_TARGET_LINK_LAYER => {
+                let mac = data.get(..6)?;
+                let mac = MacAddr::new(mac[0], mac[1], mac[2], mac[3], mac[4], mac[5]);
+                match option_type {
+                    OPTION_SOURCE_LINK_LAYER => NdpOption::SourceLinkLayer(mac),
+                    _ => NdpOption::TargetLinkLayer(mac),
+                }
+            }
+            OPTION_PREFIX_INFORMATION => NdpOption::PrefixInformation {
+                prefix: read_ipv6(data.get(14..30)?),
+                length: data[0],
+                on_link: data[1] & 0x80 != 0,
+                autonomous: data[1] & 0x40 != 0,
+                valid: read_be_u32(&data[2..6]),
+                preferred: read_be_u32(&data[6..10]),
+            },
+            OPTION_MTU => NdpOption::Mtu(read_be_u32(data.get(2..6)?)),
+            OPTION_ROUTE_INFORMATION => NdpOption::RouteInformation {
+                lifetime: read_be_u32(data.get(2..6)?),
+                prefix: read_prefix(&data[6..]),
+                length: data[0],
+                flags: data[1],
+            },
+            OPTION_RDNSS => NdpOption::Rdnss(
+                read_be_u32(data.get(2..6)?),
+                data[6..].chunks_exact(16).map(read_ipv6).collect(),
+            ),
+            OPTION_DNSSL => {
+                NdpOption::Dnssl(read_be_u32(data.get(2..6)?), read_domains(&data[6..]))
+            }
+            option_type => NdpOption::Other(option_type, data.to_vec()),
+        };
+        Some(option)
+    }
+}
+
+impl fmt::Display for NdpOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NdpOption::SourceLinkLayer(mac) => write!(f, "source link-layer address {}", mac),
+            NdpOption::TargetLinkLayer(mac) => write!(f, "target link-layer address {}", mac),
+            NdpOption::PrefixInformation {
+                prefix,
+                length,
+                on_link,
+                autonomous,
+                valid,
+                preferred,
+            } => {
+                write!(f, "prefix {}/{}", prefix, length)?;
+                if *on_link {
+                    write!(f, " on-link")?;
+                }
+                if *autonomous {
+                    write!(f, " autonomous")?;
+                }
+                write!(f, " valid {} s preferred {} s", valid, preferred)
+            }
+            NdpOption::Mtu(mtu) => write!(f, "mtu {}", mtu),
+            NdpOption::RouteInformation {
+                prefix,
+                length,
+                flags,
+                lifetime,
+            } => write!(
+                f,
+                "route {}/{} preference {} lifetime {} s",
+                prefix,
+                length,
+                format_preference(*flags),
+                lifetime
+            ),
+            NdpOption::Rdnss(lifetime, servers) => {
+                let servers = servers
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                write!(f, "rdnss [{}] lifetime {} s", servers, lifetime)
+            }
+            NdpOption::Dnssl(lifetime, domains) => {
+                write!(f, "dnssl [{}] lifetime {} s", domains.join(" "), lifetime)
+            }
+            NdpOption::Other(option_type, data) => {
+                write!(f, "option {} ({} bytes)", option_type, data.len())
+            }
+        }
+    }
+}
+
+// type of ndp message with its fixed fields
+pub enum NdpType {
+    RouterSolicitation,
+
+    // router advertisement with the hop limit, flags, router lifetime in
+    // seconds and the reachable time and retransmission timer in
+    // milliseconds it advertises
+    RouterAdvertisement {
+        hop_limit: u8,
+        flags: u8,
+        lifetime: u16,
+        reachable: u32,
+        retransmit: u32,
+    },
+
+    NeighborSolicitation {
+        target: Ipv6Addr,
+    },
+
+    // neighbor advertisement with its router, solicited and override flags
+    NeighborAdvertisement {
+        target: Ipv6Addr,
+        router: bool,
+        solicited: bool,
+        override_flag: bool,
+    },
+
+    Redirect {
+        target: Ipv6Addr,
+        destination: Ipv6Addr,
+    },
+}
+
+// ndp message with the addresses of its ethernet and ipv6 headers
+pub struct NdpMessage {
+    pub ethernet_source: MacAddr,
+    pub source: Ipv6Addr,
+    pub destination: Ipv6Addr,
+    pub ndp_type: NdpType,
+    pub options: Vec<NdpOption>,
+}
+
+impl NdpMessage {
+    // parse the ethernet frame and get the ndp message inside it; ipv6
+    // packets with extension headers are ignored
+    pub fn parse(frame: &[u8]) -> Option<NdpMessage> {
+        let ethernet_packet = EthernetPacket::new(frame)?;
+        if ethernet_packet.get_ethertype() != EtherTypes::Ipv6 {
+            return None;
+        }
+        let ipv6_packet = Ipv6Packet::new(ethernet_packet.payload())?;
+        if ipv6_packet.get_next_header() != IpNextHeaderProtocols::Icmpv6
+            || ipv6_packet.get_hop_limit() != NDP_HOP_LIMIT
+        {
+            return None;
+        }
+        let length = usize::from(ipv6_packet.get_payload_length());
+        let icmp = ipv6_packet.payload().get(..length)?;
+        let (ndp_type, options_offset) = match *icmp.first()? {
+            ROUTER_SOLICITATION => (NdpType::RouterSolicitation, 8),
+            ROUTER_ADVERTISEMENT => (
+                NdpType::RouterAdvertisement {
+                    hop_limit: *icmp.get(4)?,
+                    flags: *icmp.get(5)?,
+                    lifetime: u16::from_be_bytes([*icmp.get(6)?, *icmp.get(7)?]),
+                    reachable: read_be_u32(icmp.get(8..12)?),
+                    retransmit: read_be_u32(icmp.get(12..16)?),
+                },
+                16,
+            ),
+            NEIGHBOR_SOLICITATION => (
+                NdpType::NeighborSolicitation {
+                    target: read_ipv6(icmp.get(8..24)?),
+                },
+                24,
+            ),
+            NEIGHBOR_ADVERTISEMENT => (
+                NdpType::NeighborAdvertisement {
+                    target: read_ipv6(icmp.get(8..24)?),
+                    router: icmp[4] & 0x80 != 0,
+                    solicited: icmp[4] & 0x40 != 0,
+                    override_flag: icmp[4] & 0x20 != 0,
+                },
+                24,
+            ),
+            REDIRECT => (
+                NdpType::Redirect {
+                    target: read_ipv6(icmp.get(8..24)?),
+                    destination: read_ipv6(icmp.get(24..40)?),
+                },
+                40,
+            ),
+            _ => return None,
+        };
+        Some(NdpMessage {
+            ethernet_source: ethernet_packet.get_source(),
+            source: ipv6_packet.get_source(),
+            destination: ipv6_packet.get_destination(),
+            ndp_type,
+            options: NdpMessage::parse_options(icmp.get(options_offset..)?)?,
+        })
+    }
+
+    // parse the ndp options in data; returns none if an option is invalid
+    fn parse_options(data: &[u8]) -> Option<Vec<NdpOption>> {
+        let mut options = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            let option_type = *data.get(offset)?;
+            let length = usize::from(*data.get(offset + 1)?) * 8;
+            if length == 0 {
+                return None;
+            }
+            let option = data.get(offset + 2..offset + length)?;
+            options.push(NdpOption::parse(option_type, option)?);
+            offset += length;
+        }
+        Some(options)
+    }
+
+    // get the link-layer address in the source link-layer address option
+    pub fn get_source_mac(&self) -> Option<MacAddr> {
+        self.options.iter().find_map(|o| match o {
+            NdpOption::SourceLinkLayer(mac) => Some(*mac),
+            _ => None,
+        })
+    }
+
+    // get the link-layer address in the target link-layer address option
+    pub fn get_target_mac(&self) -> Option<MacAddr> {
+        self.options.iter().find_map(|o| match o {
+            NdpOption::TargetLinkLayer(mac) => Some(*mac),
+            _ => None,
+        })
+    }
+}
+
+impl fmt::Display for NdpMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}) -> {}: ",
+            self.source, self.ethernet_source, self.destination
+        )?;
+        match &self.ndp_type {
+            NdpType::RouterSolicitation => write!(f, "router solicitation")?,
+            NdpType::RouterAdvertisement {
+                hop_limit,
+                flags,
+                lifetime,
+                reachable,
+                retransmit,
+            } => {
+                write!(f, "router advertisement hop limit {}", hop_limit)?;
+                if flags & 0x80 != 0 {
+                    write!(f, " managed")?;
+                }
+                if flags & 0x40 != 0 {
+                    write!(f, " other")?;
+                }
+                write!(
+                    f,
+                    " preference {} lifetime {} s reachable {} ms retransmit {} ms",
+                    format_preference(*flags),
+                    lifetime,
+                    reachable,
+                    retransmit
+                )?
+            }
+            NdpType::NeighborSolicitation { target } if self.source.is_unspecified() => {
+                write!(f, "duplicate address detection for {}", target)?
+            }
+            NdpType::NeighborSolicitation { target } => {
+                write!(f, "neighbor solicitation who-has {}", target)?
+            }
+            NdpType::NeighborAdvertisement {
+                target,
+                router,
+                solicited,
+                override_flag,
+            } => {
+                write!(f, "neighbor advertisement {}", target)?;
+                if *router {
+                    write!(f, " router")?;
+                }
+                if *solicited {
+                    write!(f, " solicited")?;
+                }
+                if *override_flag {
+                    write!(f, " override")?;
+                }
+            }
+            NdpType::Redirect {
+                target,
+                destination,
+            } => write!(f, "redirect {} to {}", destination, target)?,
+        }
+        for option in &self.options {
+            write!(f, ", {}", option)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+use pnet::datalink::MacAddr;
+use pnet::ipnetwork::Ipv6Network;
+
+use std::net::Ipv6Addr;
+
+use crate::alert::Alert;
+use crate::config::Config;
+use crate::message::{NdpMessage, NdpOption, NdpType};
+
+// router identified by the ipv6 address or the mac address it sends router
+// advertisements from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Router {
+    Ip(Ipv6Addr),
+    Mac(MacAddr),
+}
+
+impl Router {
+    // check if the ndp message was sent by the router
+    fn is_sender(&self, message: &NdpMessage) -> bool {
+        match self {
+            Router::Ip(ip) => *ip == message.source,
+            Router::Mac(mac) => *mac == message.ethernet_source,
+        }
+    }
+}
+
+// detector of rogue router advertisements from unexpected routers or with
+// unexpected prefixes or dns servers; the expected values that are not
+// configured are learned from the first router advertisement
+pub struct RaGuard {
+    routers: Vec<Router>,
+    prefixes: Vec<Ipv6Network>,
+    dns: Vec<Ipv6Addr>,
+
+    // which of the routers, prefixes and dns servers are learned and if
+    // they were learned already
+    learn: (bool, bool, bool),
+    learned: bool,
+}
+
+impl RaGuard {
+    pub fn new(config: &Config) -> RaGuard {
+        RaGuard {
+            routers: config.router.clone(),
+            prefixes: config.prefix.clone(),
+            dns: config.dns.clone(),
+            learn: (
+                config.router.is_empty(),
+                config.prefix.is_empty(),
+                config.dns.is_empty(),
+            ),
+            learned: false,
+        }
+    }
+
+    // learn the expected values that are not configured from the router
+    // advertisement; routers learned this way are identified by their mac
+    // address
+    fn learn(&mut self, message: &NdpMessage) {
+        let (routers, prefixes, dns) = self.learn;
+        if routers {
+            self.routers.push(Router::Mac(message.ethernet_source));
+        }
+        for option in &message.options {
+            match option {
+                NdpOption::PrefixInformation { prefix, length, .. } if prefixes => {
+                    self.prefixes.extend(Ipv6Network::new(*prefix, *length));
+                }
+                NdpOption::Rdnss(_, servers) if dns => self.dns.extend(servers),
+                _ => (),
+            }
+        }
+        self.learned = true;
+    }
+
+    // check the ndp message if it is a router advertisement and get the
+    // alerts it caused
+    pub fn check(&mut self, message: &NdpMessage) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        if !matches!(message.ndp_type, NdpType::RouterAdvertisement { .. }) {
+            return alerts;
+        }
+        if !self.learned {
+            self.learn(message);
+        }
+
+        if !self.routers.iter().any(|r| r.is_sender(message)) {
+            alerts.push(Alert::RogueRouter {
+                ip: message.source,
+                mac: message.ethernet_source,
+            });
+        }
+        for option in &message.options {
+            match option {
+                NdpOption::PrefixInformation { prefix, length, .. } => {
+                    let prefix = match Ipv6Network::new(*prefix, *length) {
+                        Ok(prefix) => prefix,
+                        Err(_) => continue,
+                    };
+                    if !self
+                        .prefixes
+                        .iter()
+                        .any(|p| p.contains(prefix.network()) && p.prefix() <= prefix.prefix())
+                    {
+                        alerts.push(Alert::RoguePrefix {
+                            router: message.source,
+                            prefix,
+                        });
+                    }
+                }
+                NdpOption::Rdnss(_, servers) => {
+                    for server in servers.iter().filter(|s| !self.dns.contains(s)) {
+                        alerts.push(Alert::RogueDns {
+                            router: message.source,
+                            server: *server,
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    // create a router advertisement from the router with ip and mac address
+    // that advertises the prefix and the dns server
+    fn advertisement(ip: &str, mac: &str, prefix: &str, dns: &str) -> NdpMessage {
+        let prefix: Ipv6Network = prefix.parse().unwrap();
+        NdpMessage {
+            ethernet_source: mac.parse().unwrap(),
+            source: ip.parse().unwrap(),
+            destination: "ff02::1".parse().unwrap(),
+            ndp_type: NdpType::RouterAdvertisement {
+                hop_limit: 64,
+                flags: 0,
+                lifetime: 1800,
+                reachable: 0,
+                retransmit: 0,
+            },
+            options: vec![
+                NdpOption::PrefixInformation {
+                    prefix: prefix.network(),
+                    length: prefix.prefix(),
+                    on_link: true,
+                    autonomous: true,
+                    valid: 86400,
+                    preferred: 14400,
+                },
+                NdpOption::Rdnss(600, vec![dns.parse().unwrap()]),
+            ],
+        }
+    }
+
+    #[test]
+    fn check_learns_first_advertisement() {
+        let mut guard = RaGuard::new(&Config::parse_from(["ndp"]));
+        let router = advertisement(
+            "fe80::1",
+            "00:00:5e:00:53:01",
+            "2001:db8::/64",
+            "2001:db8::53",
+        );
+        assert!(guard.check(&router).is_empty());
+        assert!(guard.check(&router).is_empty());
+
+        // learned routers are identified by their mac address
+        let rogue = advertisement(
+            "fe80::1",
+            "00:00:5e:00:53:02",
+            "2001:db8::/64",
+            "2001:db8::53",
+        );
+        let alerts = guard.check(&rogue);
+        assert_eq!(alerts.len(), 1);
+        assert!(matches!(alerts[0], Alert::RogueRouter { .. }));
+    }
+
+    #[test]
+    fn check_configured_values() {
+        let config = Config::parse_from([
+            "ndp",
+            "--router",
+            "fe80::1",
+            "--prefix",
+            "2001:db8::/48",
+            "--dns",
+            "2001:db8::53",
+        ]);
+        let mut guard = RaGuard::new(&config);
+
+        // prefixes inside the configured prefixes are expected
+        let router = advertisement(
+            "fe80::1",
+            "00:00:5e:00:53:01",
+            "2001:db8:0:1::/64",
+            "2001:db8::53",
+        );
+        assert!(guard.check(&router).is_empty());
+
+        let rogue = advertisement(
+            "fe80::2",
+            "00:00:5e:00:53:01",
+            "2001:db9::/64",
+            "2001:db9::53",
+        );
+        let alerts = guard.check(&rogue);
+        assert_eq!(alerts.len(), 3);
+        assert!(matches!(alerts[0], Alert::RogueRouter { .. }));
+        assert!(matches!(alerts[1], Alert::RoguePrefix { .. }));
+        assert!(matches!(alerts[2], Alert::RogueDns { .. }));
+
+        // prefixes shorter than the configured prefixes are not expected
+        let rogue = advertisement(
+            "fe80::1",
+            "00:00:5e:00:53:01",
+            "2001:db8::/32",
+            "2001:db8::53",
+        );
+        let alerts = guard.check(&rogue);
+        assert_eq!(alerts.len(), 1);
+        assert!(matches!(alerts[0], Alert::RoguePrefix { .. }));
+    }
+
+    #[test]
+    fn check_ignores_other_messages() {
+        let mut guard = RaGuard::new(&Config::parse_from(["ndp", "--router", "fe80::1"]));
+        let mut message = advertisement(
+            "fe80::2",
+            "00:00:5e:00:53:02",
+            "2001:db8::/64",
+            "2001:db8::53",
+        );
+        message.ndp_type = NdpType::RouterSolicitation;
+        assert!(guard.check(&message).is_empty());
+    }
+}
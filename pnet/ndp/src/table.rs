@@ -0,0 +1,98 @@
+use pnet::datalink::MacAddr;
+
+use std::collections::BTreeMap;
+use std::net::Ipv6Addr;
+use std::time::Instant;
+
+use crate::alert::Alert;
+use crate::message::{NdpMessage, NdpType};
+
+// neighbor with the mac address bound to its ip address learned from ndp
+// messages
+pub struct Neighbor {
+    pub mac: MacAddr,
+    pub router: bool,
+    pub last_seen: Instant,
+
+    // number of ndp messages that announced the binding and number of times
+    // the ip address moved to another mac address
+    pub messages: u64,
+    pub changes: u64,
+}
+
+// get the ip to mac address binding announced in the ndp message and if it
+// is the one of a router
+fn get_binding(message: &NdpMessage) -> Option<(Ipv6Addr, MacAddr, Option<bool>)> {
+    match &message.ndp_type {
+        NdpType::RouterSolicitation | NdpType::NeighborSolicitation { .. }
+            if !message.source.is_unspecified() =>
+        {
+            Some((message.source, message.get_source_mac()?, None))
+        }
+        NdpType::RouterAdvertisement { .. } => {
+            Some((message.source, message.get_source_mac()?, Some(true)))
+        }
+        NdpType::NeighborAdvertisement { target, router, .. } => {
+            Some((*target, message.get_target_mac()?, Some(*router)))
+        }
+        NdpType::Redirect { target, .. } => Some((*target, message.get_target_mac()?, None)),
+        _ => None,
+    }
+}
+
+// table of the neighbors announced in ndp messages that detects conflicting
+// bindings
+#[derive(Default)]
+pub struct NeighborTable {
+    neighbors: BTreeMap<Ipv6Addr, Neighbor>,
+}
+
+impl NeighborTable {
+    // add the binding announced in the ndp message to the table and get the
+    // alerts it caused
+    pub fn add(&mut self, message: &NdpMessage) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        if let Some(mac) = message.get_source_mac() {
+            if mac != message.ethernet_source {
+                alerts.push(Alert::Mismatch {
+                    ip: message.source,
+                    sender: mac,
+                    source: message.ethernet_source,
+                });
+            }
+        }
+
+        let (ip, mac, router) = match get_binding(message) {
+            Some(binding) => binding,
+            None => return alerts,
+        };
+        let now = Instant::now();
+        let neighbor = self.neighbors.entry(ip).or_insert(Neighbor {
+            mac,
+            router: false,
+            last_seen: now,
+            messages: 0,
+            changes: 0,
+        });
+        if neighbor.mac != mac {
+            alerts.push(Alert::Conflict {
+                ip,
+                old: neighbor.mac,
+                new: mac,
+            });
+            neighbor.mac = mac;
+            neighbor.changes += 1;
+        }
+        if let Some(router) = router {
+            neighbor.router = router;
+        }
+        neighbor.last_seen = now;
+        neighbor.messages += 1;
+        alerts
+    }
+
+    // get the neighbors sorted by ip address
+    pub fn get_neighbors(&self) -> &BTreeMap<Ipv6Addr, Neighbor> {
+        &self.neighbors
+    }
+}
@@ -0,0 +1,38 @@
+use clap::Parser;
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+// command line configuration of the netflow tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Collect netflow v5/v9 and ipfix flow records over udp and print them")]
+pub struct Config {
+    // address to receive flow exports on
+    #[arg(
+        short,
+        long,
+        value_name = "ADDRESS",
+        default_value = "0.0.0.0:2055",
+        help = "Receive flow exports on the udp socket address ADDRESS, \
+        e.g., [::]:4739 for ipfix over ipv6"
+    )]
+    pub listen: SocketAddr,
+
+    // output format
+    #[arg(
+        short,
+        long,
+        help = "Write flow and options records as json objects, one per line, \
+        and omit templates"
+    )]
+    pub json: bool,
+
+    // output file
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        help = "Append records to FILE instead of writing them to stdout"
+    )]
+    pub output: Option<PathBuf>,
+}
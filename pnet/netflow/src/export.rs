@@ -0,0 +1,506 @@
+use packet_utils::bytes::{read_be_u16_at, read_be_u32_at};
+use packet_utils::json::json_string;
+
+use std::fmt;
+use std::net::SocketAddr;
+
+use crate::field::{self, Value};
+use crate::template::{FieldSpec, Template, Templates, VARIABLE_LENGTH};
+
+// netflow versions; ipfix uses version 10
+pub const VERSION_5: u16 = 5;
+pub const VERSION_9: u16 = 9;
+pub const VERSION_IPFIX: u16 = 10;
+
+// header lengths
+const V5_HEADER_LENGTH: usize = 24;
+const V9_HEADER_LENGTH: usize = 20;
+const IPFIX_HEADER_LENGTH: usize = 16;
+
+// length of netflow v5 flow records
+const V5_RECORD_LENGTH: usize = 48;
+
+// fields of netflow v5 records with their field type, offset and length
+const V5_FIELDS: [(u16, usize, usize); 18] = [
+    (8, 0, 4),
+    (12, 4, 4),
+    (15, 8, 4),
+    (10, 12, 2),
+    (14, 14, 2),
+    (2, 16, 4),
+    (1, 20, 4),
+    (22, 24, 4),
+    (21, 28, 4),
+    (7, 32, 2),
+    (11, 34, 2),
+    (6, 37, 1),
+    (4, 38, 1),
+    (5, 39, 1),
+    (16, 40, 2),
+    (17, 42, 2),
+    (9, 44, 1),
+    (13, 45, 1),
+];
+
+// set ids of templates and options templates
+const V9_TEMPLATE_SET: u16 = 0;
+const V9_OPTIONS_TEMPLATE_SET: u16 = 1;
+const IPFIX_TEMPLATE_SET: u16 = 2;
+const IPFIX_OPTIONS_TEMPLATE_SET: u16 = 3;
+
+// smallest set id of data sets
+const MIN_DATA_SET: u16 = 256;
+
+// enterprise bit in ipfix field types
+const ENTERPRISE_BIT: u16 = 0x8000;
+
+// flow or options data record with its decoded fields
+pub struct Record {
+    pub template: Option<u16>,
+    pub options: bool,
+    pub fields: Vec<(String, Value)>,
+}
+
+impl Record {
+    // get the record as json object with the exporter, the export and the
+    // receive time in seconds since the unix epoch
+    pub fn to_json(&self, exporter: &SocketAddr, export: &Export, received: f64) -> String {
+        let mut json = format!(
+            "{{\"received\":{:.6},\"exporter\":{},\"version\":{},\"domain\":{},\
+            \"sequence\":{},\"template\":{},\"options\":{}",
+            received,
+            json_string(&exporter.to_string()),
+            export.version,
+            export.domain,
+            export.sequence,
+            self.template
+                .map(|t| t.to_string())
+                .unwrap_or_else(|| "null".into()),
+            self.options,
+        );
+        for (name, value) in &self.fields {
+            json.push_str(&format!(",{}:{}", json_string(name), value.to_json()));
+        }
+        json.push('}');
+        json
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields = self
+            .fields
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(f, "{{{}}}", fields)
+    }
+}
+
+// flow export packet with the templates and records it contains
+pub struct Export {
+    pub version: u16,
+    pub sequence: u32,
+    pub domain: u32,
+    pub templates: Vec<(u16, Template)>,
+    pub withdrawals: Vec<u16>,
+    pub records: Vec<Record>,
+
+    // ids of data sets that were skipped because their template is unknown
+    pub missing_templates: Vec<u16>,
+}
+
+impl Export {
+    // parse the flow export packet from exporter in bytes; templates in the
+    // packet are added to templates before the data sets are decoded
+    pub fn parse(
+        bytes: &[u8],
+        exporter: SocketAddr,
+        templates: &mut Templates,
+    ) -> Result<Export, String> {
+        let version = read_be_u16_at(bytes, 0).ok_or("packet too short")?;
+        match version {
+            VERSION_5 => Export::parse_v5(bytes),
+            VERSION_9 | VERSION_IPFIX => Export::parse_sets(bytes, version, exporter, templates),
+            _ => Err(format!("unsupported version {}", version)),
+        }
+    }
+
+    // get the name of the export protocol and version
+    pub fn get_protocol(&self) -> &'static str {
+        match self.version {
+            VERSION_5 => "netflow v5",
+            VERSION_9 => "netflow v9",
+            _ => "ipfix",
+        }
+    }
+
+    // parse the netflow v5 packet in bytes
+    fn parse_v5(bytes: &[u8]) -> Result<Export, String> {
+        if bytes.len() < V5_HEADER_LENGTH {
+            return Err("packet too short".into());
+        }
+        let count = usize::from(read_be_u16_at(bytes, 2).unwrap_or(0));
+        let sequence = read_be_u32_at(bytes, 16).unwrap_or(0);
+        let domain = u32::from(bytes[20]) << 8 | u32::from(bytes[21]);
+        let records_bytes = &bytes[V5_HEADER_LENGTH..];
+        if records_bytes.len() < count * V5_RECORD_LENGTH {
+            return Err(format!("packet too short for {} records", count));
+        }
+
+        let records = records_bytes
+            .chunks_exact(V5_RECORD_LENGTH)
+            .take(count)
+            .map(|record| Record {
+                template: None,
+                options: false,
+                fields: V5_FIELDS
+                    .iter()
+                    .map(|(id, offset, length)| {
+                        let value = Value::parse(*id, None, &record[*offset..offset + length]);
+                        (field::get_field_name(*id, None), value)
+                    })
+                    .collect(),
+            })
+            .collect();
+        Ok(Export {
+            version: VERSION_5,
+            sequence,
+            domain,
+            templates: Vec::new(),
+            withdrawals: Vec::new(),
+            records,
+            missing_templates: Vec::new(),
+        })
+    }
+
+    // parse the netflow v9 or ipfix packet in bytes with its sets
+    fn parse_sets(
+        bytes: &[u8],
+        version: u16,
+        exporter: SocketAddr,
+        templates: &mut Templates,
+    ) -> Result<Export, String> {
+        let (header_length, sequence, domain, end) = match version {
+            VERSION_9 => (
+                V9_HEADER_LENGTH,
+                read_be_u32_at(bytes, 12),
+                read_be_u32_at(bytes, 16),
+                bytes.len(),
+            ),
+            _ => (
+                IPFIX_HEADER_LENGTH,
+                read_be_u32_at(bytes, 8),
+                read_be_u32_at(bytes, 12),
+                usize::from(read_be_u16_at(bytes, 2).unwrap_or(0)),
+            ),
+        };
+        let (sequence, domain) = match (sequence, domain) {
+            (Some(sequence), Some(domain)) if end <= bytes.len() => (sequence, domain),
+            _ => return Err("packet too short".into()),
+        };
+        let mut export = Export {
+            version,
+            sequence,
+            domain,
+            templates: Vec::new(),
+            withdrawals: Vec::new(),
+            records: Vec::new(),
+            missing_templates: Vec::new(),
+        };
+
+        let mut offset = header_length;
+        while offset + 4 <= end {
+            let set_id = read_be_u16_at(bytes, offset).unwrap_or(0);
+            let set_length = usize::from(read_be_u16_at(bytes, offset + 2).unwrap_or(0));
+            if set_length < 4 || offset + set_length > end {
+                return Err(format!("invalid length of set {}", set_id));
+            }
+            let set = &bytes[offset + 4..offset + set_length];
+            offset += set_length;
+
+            match (version, set_id) {
+                (VERSION_9, V9_TEMPLATE_SET) | (VERSION_IPFIX, IPFIX_TEMPLATE_SET) => {
+                    let parsed = parse_templates(set, version, false)?;
+                    export.add_templates(parsed, exporter, templates);
+                }
+                (VERSION_9, V9_OPTIONS_TEMPLATE_SET)
+                | (VERSION_IPFIX, IPFIX_OPTIONS_TEMPLATE_SET) => {
+                    let parsed = parse_templates(set, version, true)?;
+                    export.add_templates(parsed, exporter, templates);
+                }
+                (_, id) if id >= MIN_DATA_SET => {
+                    match templates.get(&(exporter, version, domain, id)) {
+                        Some(template) => {
+                            let records = parse_data(set, id, template);
+                            export.records.extend(records);
+                        }
+                        None => export.missing_templates.push(id),
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(export)
+    }
+
+    // add the parsed templates of this export to templates; templates
+    // without fields withdraw earlier templates with the same id
+    fn add_templates(
+        &mut self,
+        parsed: Vec<(u16, Template)>,
+        exporter: SocketAddr,
+        templates: &mut Templates,
+    ) {
+        for (id, template) in parsed {
+            let key = (exporter, self.version, self.domain, id);
+            if template.fields.is_empty() {
+                templates.remove(&key);
+                self.withdrawals.push(id);
+                continue;
+            }
+            templates.add(key, template.clone());
+            self.templates.push((id, template));
+        }
+    }
+}
+
+// parse the field specifier at offset in the template set and get it and its
+// length
+fn parse_field_spec(set: &[u8], offset: usize, version: u16) -> Option<(FieldSpec, usize)> {
+    let field_type = read_be_u16_at(set, offset)?;
+    let length = read_be_u16_at(set, offset + 2)?;
+    if version == VERSION_IPFIX && field_type & ENTERPRISE_BIT != 0 {
+        let enterprise = read_be_u32_at(set, offset + 4)?;
+        let spec = FieldSpec {
+            id: field_type & !ENTERPRISE_BIT,
+            enterprise: Some(enterprise),
+            length,
+            v9_scope: false,
+        };
+        return Some((spec, 8));
+    }
+    let spec = FieldSpec {
+        id: field_type,
+        enterprise: None,
+        length,
+        v9_scope: false,
+    };
+    Some((spec, 4))
+}
+
+// parse the templates or options templates in the template set of a netflow
+// v9 or ipfix export
+fn parse_templates(
+    set: &[u8],
+    version: u16,
+    options: bool,
+) -> Result<Vec<(u16, Template)>, String> {
+    let mut templates = Vec::new();
+    let header_length = match options {
+        true => 6,
+        false => 4,
+    };
+
+    // the set may end with padding
+    let mut offset = 0;
+    while offset + header_length <= set.len() {
+        let id = read_be_u16_at(set, offset).unwrap_or(0);
+        let (count, scope_fields) = match (version, options) {
+            (_, false) => (usize::from(read_be_u16_at(set, offset + 2).unwrap_or(0)), 0),
+            (VERSION_9, true) => {
+                // v9 options templates contain the lengths of the scope and
+                // option fields in bytes
+                let scope_length = usize::from(read_be_u16_at(set, offset + 2).unwrap_or(0));
+                let option_length = usize::from(read_be_u16_at(set, offset + 4).unwrap_or(0));
+                ((scope_length + option_length) / 4, scope_length / 4)
+            }
+            (_, true) => (
+                usize::from(read_be_u16_at(set, offset + 2).unwrap_or(0)),
+                usize::from(read_be_u16_at(set, offset + 4).unwrap_or(0)),
+            ),
+        };
+        if id == 0 && count == 0 {
+            break;
+        }
+        offset += header_length;
+
+        let mut fields = Vec::with_capacity(count);
+        for i in 0..count {
+            let (mut spec, length) = parse_field_spec(set, offset, version)
+                .ok_or_else(|| format!("template {} too short", id))?;
+            spec.v9_scope = version == VERSION_9 && i < scope_fields;
+            fields.push(spec);
+            offset += length;
+        }
+        if scope_fields > fields.len() {
+            return Err(format!("invalid scope field count in template {}", id));
+        }
+        templates.push((
+            id,
+            Template {
+                fields,
+                scope_fields,
+            },
+        ));
+    }
+    Ok(templates)
+}
+
+// parse the value of a field with length at offset in the data record and get
+// it and its length in the record
+fn parse_field_value(record: &[u8], offset: usize, length: u16) -> Option<(&[u8], usize)> {
+    if length != VARIABLE_LENGTH {
+        let length = usize::from(length);
+        return Some((record.get(offset..offset + length)?, length));
+    }
+
+    // variable-length fields start with their length in one byte or, if the
+    // byte is 255, in the following two bytes
+    let (length, prefix) = match *record.get(offset)? {
+        255 => (usize::from(read_be_u16_at(record, offset + 1)?), 3),
+        length => (usize::from(length), 1),
+    };
+    let start = offset + prefix;
+    Some((record.get(start..start + length)?, prefix + length))
+}
+
+// parse the data records in the data set with template id; the set may end
+// with padding shorter than a record
+fn parse_data(set: &[u8], id: u16, template: &Template) -> Vec<Record> {
+    let mut records = Vec::new();
+    let min_length = template.get_min_length();
+    let mut offset = 0;
+    'records: while min_length > 0 && offset + min_length <= set.len() {
+        let mut fields = Vec::with_capacity(template.fields.len());
+        for spec in &template.fields {
+            let (bytes, length) = match parse_field_value(set, offset, spec.length) {
+                Some(value) => value,
+                None => break 'records,
+            };
+            offset += length;
+
+            // netflow v9 scope fields have their own field types
+            let field = match spec.v9_scope {
+                true => (spec.get_name(), Value::parse_unsigned(bytes)),
+                false => (
+                    spec.get_name(),
+                    Value::parse(spec.id, spec.enterprise, bytes),
+                ),
+            };
+            fields.push(field);
+        }
+        records.push(Record {
+            template: Some(id),
+            options: template.is_options(),
+            fields,
+        });
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // create an ipfix message with observation domain 1 and the sets
+    fn ipfix_message(sets: &[&[u8]]) -> Vec<u8> {
+        let length = IPFIX_HEADER_LENGTH + sets.iter().map(|s| s.len()).sum::<usize>();
+        let mut message = vec![0, 10];
+        message.extend_from_slice(&(length as u16).to_be_bytes());
+        message.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 7, 0, 0, 0, 1]);
+        for set in sets {
+            message.extend_from_slice(set);
+        }
+        message
+    }
+
+    // ipfix template set with template 256 containing the ipv4 source
+    // address and the packet count
+    const TEMPLATE_SET: [u8; 16] = [0, 2, 0, 16, 1, 0, 0, 2, 0, 8, 0, 4, 0, 2, 0, 4];
+
+    // data set of template 256 with one record and padding
+    const DATA_SET: [u8; 14] = [1, 0, 0, 14, 10, 0, 0, 1, 0, 0, 0, 5, 0, 0];
+
+    #[test]
+    fn parse_data_with_template_from_earlier_export() {
+        let exporter = "192.0.2.1:4739".parse().unwrap();
+        let mut templates = Templates::default();
+
+        let export =
+            Export::parse(&ipfix_message(&[&TEMPLATE_SET]), exporter, &mut templates).unwrap();
+        assert_eq!(export.templates.len(), 1);
+        assert_eq!(export.templates[0].0, 256);
+        assert!(export.records.is_empty());
+
+        let export = Export::parse(&ipfix_message(&[&DATA_SET]), exporter, &mut templates).unwrap();
+        assert!(export.missing_templates.is_empty());
+        assert_eq!(export.records.len(), 1);
+        assert_eq!(
+            export.records[0].to_string(),
+            "{ipv4_src_addr: 10.0.0.1, in_pkts: 5}"
+        );
+    }
+
+    #[test]
+    fn parse_data_without_template() {
+        let mut templates = Templates::default();
+        let exporter = "192.0.2.1:4739".parse().unwrap();
+        Export::parse(&ipfix_message(&[&TEMPLATE_SET]), exporter, &mut templates).unwrap();
+
+        // templates are not shared between exporters
+        let other = "192.0.2.2:4739".parse().unwrap();
+        let export = Export::parse(&ipfix_message(&[&DATA_SET]), other, &mut templates).unwrap();
+        assert!(export.records.is_empty());
+        assert_eq!(export.missing_templates, vec![256]);
+    }
+
+    #[test]
+    fn parse_template_withdrawal() {
+        let exporter = "192.0.2.1:4739".parse().unwrap();
+        let mut templates = Templates::default();
+        Export::parse(&ipfix_message(&[&TEMPLATE_SET]), exporter, &mut templates).unwrap();
+
+        let withdrawal = [0, 2, 0, 8, 1, 0, 0, 0];
+        let export = Export::parse(
+            &ipfix_message(&[&withdrawal, &DATA_SET]),
+            exporter,
+            &mut templates,
+        )
+        .unwrap();
+        assert_eq!(export.withdrawals, vec![256]);
+        assert!(export.records.is_empty());
+        assert_eq!(export.missing_templates, vec![256]);
+    }
+
+    #[test]
+    fn parse_v9_options_template() {
+        // options template 257 with the interface scope and the packet count
+        let set = [0, 1, 0, 16, 1, 1, 0, 4, 0, 4, 0, 2, 0, 4, 0, 2, 0, 4];
+        let templates = parse_templates(&set[4..], VERSION_9, true).unwrap();
+        assert_eq!(templates.len(), 1);
+
+        let (id, template) = &templates[0];
+        assert_eq!(*id, 257);
+        assert!(template.is_options());
+        assert_eq!(template.scope_fields, 1);
+        assert_eq!(
+            template.to_string(),
+            "{scope fields: [scope_interface(4)], fields: [in_pkts(4)]}"
+        );
+    }
+
+    #[test]
+    fn parse_variable_length_field() {
+        assert_eq!(
+            parse_field_value(&[2, 1, 2, 3], 0, VARIABLE_LENGTH),
+            Some((&[1, 2][..], 3))
+        );
+        assert_eq!(
+            parse_field_value(&[255, 0, 1, 9], 0, VARIABLE_LENGTH),
+            Some((&[9][..], 4))
+        );
+        assert_eq!(parse_field_value(&[3, 1, 2], 0, VARIABLE_LENGTH), None);
+    }
+}
@@ -0,0 +1,184 @@
+use packet_utils::json::json_string;
+
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+// Field types:
+// information elements shared by netflow v9 and ipfix with their netflow v9
+// names; the types of other fields are derived from their lengths
+const FIELD_TYPES: [(u16, &str, Kind); 52] = [
+    (1, "in_bytes", Kind::Unsigned),
+    (2, "in_pkts", Kind::Unsigned),
+    (3, "flows", Kind::Unsigned),
+    (4, "protocol", Kind::Unsigned),
+    (5, "src_tos", Kind::Unsigned),
+    (6, "tcp_flags", Kind::Unsigned),
+    (7, "l4_src_port", Kind::Unsigned),
+    (8, "ipv4_src_addr", Kind::Address),
+    (9, "src_mask", Kind::Unsigned),
+    (10, "input_snmp", Kind::Unsigned),
+    (11, "l4_dst_port", Kind::Unsigned),
+    (12, "ipv4_dst_addr", Kind::Address),
+    (13, "dst_mask", Kind::Unsigned),
+    (14, "output_snmp", Kind::Unsigned),
+    (15, "ipv4_next_hop", Kind::Address),
+    (16, "src_as", Kind::Unsigned),
+    (17, "dst_as", Kind::Unsigned),
+    (18, "bgp_ipv4_next_hop", Kind::Address),
+    (19, "mul_dst_pkts", Kind::Unsigned),
+    (20, "mul_dst_bytes", Kind::Unsigned),
+    (21, "last_switched", Kind::Unsigned),
+    (22, "first_switched", Kind::Unsigned),
+    (23, "out_bytes", Kind::Unsigned),
+    (24, "out_pkts", Kind::Unsigned),
+    (27, "ipv6_src_addr", Kind::Address),
+    (28, "ipv6_dst_addr", Kind::Address),
+    (29, "ipv6_src_mask", Kind::Unsigned),
+    (30, "ipv6_dst_mask", Kind::Unsigned),
+    (31, "ipv6_flow_label", Kind::Unsigned),
+    (32, "icmp_type", Kind::Unsigned),
+    (34, "sampling_interval", Kind::Unsigned),
+    (35, "sampling_algorithm", Kind::Unsigned),
+    (38, "engine_type", Kind::Unsigned),
+    (39, "engine_id", Kind::Unsigned),
+    (56, "in_src_mac", Kind::Mac),
+    (57, "out_dst_mac", Kind::Mac),
+    (58, "src_vlan", Kind::Unsigned),
+    (59, "dst_vlan", Kind::Unsigned),
+    (60, "ip_protocol_version", Kind::Unsigned),
+    (61, "direction", Kind::Unsigned),
+    (62, "ipv6_next_hop", Kind::Address),
+    (80, "in_dst_mac", Kind::Mac),
+    (81, "out_src_mac", Kind::Mac),
+    (82, "if_name", Kind::Text),
+    (83, "if_desc", Kind::Text),
+    (85, "in_permanent_bytes", Kind::Unsigned),
+    (96, "application_name", Kind::Text),
+    (136, "flow_end_reason", Kind::Unsigned),
+    (150, "flow_start_seconds", Kind::Unsigned),
+    (151, "flow_end_seconds", Kind::Unsigned),
+    (152, "flow_start_milliseconds", Kind::Unsigned),
+    (153, "flow_end_milliseconds", Kind::Unsigned),
+];
+
+// type of the value of a field
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Unsigned,
+    Address,
+    Mac,
+    Text,
+    Bytes,
+}
+
+// get the name and kind of the field; enterprise-specific ipfix fields are
+// named after the enterprise number
+fn get_field_type(id: u16, enterprise: Option<u32>) -> (String, Kind) {
+    if let Some(enterprise) = enterprise {
+        return (format!("enterprise_{}_{}", enterprise, id), Kind::Bytes);
+    }
+    match FIELD_TYPES.iter().find(|(i, _, _)| *i == id) {
+        Some((_, name, kind)) => (name.to_string(), *kind),
+        None => (format!("field_{}", id), Kind::Bytes),
+    }
+}
+
+// get the name of the scope field type of netflow v9 options templates
+pub fn get_v9_scope_name(id: u16) -> String {
+    let name = match id {
+        1 => "system",
+        2 => "interface",
+        3 => "line_card",
+        4 => "cache",
+        5 => "template",
+        _ => return format!("scope_{}", id),
+    };
+    format!("scope_{}", name)
+}
+
+// get the name of the field
+pub fn get_field_name(id: u16, enterprise: Option<u32>) -> String {
+    get_field_type(id, enterprise).0
+}
+
+// decoded value of a field
+#[derive(Clone)]
+pub enum Value {
+    Unsigned(u64),
+    Ipv4(Ipv4Addr),
+    Ipv6(Ipv6Addr),
+    Mac([u8; 6]),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl Value {
+    // decode the value of field id from bytes; values that do not match the
+    // length of their kind are kept as bytes
+    pub fn parse(id: u16, enterprise: Option<u32>, bytes: &[u8]) -> Value {
+        let (_, kind) = get_field_type(id, enterprise);
+        match (kind, bytes.len()) {
+            (Kind::Unsigned, _) => Value::parse_unsigned(bytes),
+            (Kind::Address, 4) => {
+                Value::Ipv4(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]))
+            }
+            (Kind::Address, 16) => {
+                let mut address = [0; 16];
+                address.copy_from_slice(bytes);
+                Value::Ipv6(Ipv6Addr::from(address))
+            }
+            (Kind::Mac, 6) => {
+                let mut mac = [0; 6];
+                mac.copy_from_slice(bytes);
+                Value::Mac(mac)
+            }
+            (Kind::Text, _) => {
+                let text = bytes.split(|b| *b == 0).next().unwrap_or_default();
+                Value::Text(String::from_utf8_lossy(text).into_owned())
+            }
+            _ => Value::Bytes(bytes.to_vec()),
+        }
+    }
+
+    // decode the unsigned number in bytes; longer values are kept as bytes
+    pub fn parse_unsigned(bytes: &[u8]) -> Value {
+        match bytes.len() {
+            1..=8 => Value::Unsigned(
+                bytes
+                    .iter()
+                    .fold(0, |value, byte| value << 8 | u64::from(*byte)),
+            ),
+            _ => Value::Bytes(bytes.to_vec()),
+        }
+    }
+
+    // get the value as json value
+    pub fn to_json(&self) -> String {
+        match self {
+            Value::Unsigned(value) => value.to_string(),
+            value => json_string(&value.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Unsigned(value) => write!(f, "{}", value),
+            Value::Ipv4(address) => write!(f, "{}", address),
+            Value::Ipv6(address) => write!(f, "{}", address),
+            Value::Mac(mac) => write!(
+                f,
+                "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+                mac[0], mac[1], mac[2], mac[3], mac[4], mac[5]
+            ),
+            Value::Text(text) => write!(f, "{}", text),
+            Value::Bytes(bytes) => {
+                for byte in bytes {
+                    write!(f, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+mod config;
+mod export;
+mod field;
+mod template;
+
+use clap::Parser;
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use config::Config;
+use export::Export;
+use template::Templates;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// maximum size of flow exports
+const BUFFER_SIZE: usize = 65535;
+
+// create the udp socket that receives flow exports
+fn create_socket(config: &Config) -> io::Result<UdpSocket> {
+    let socket = UdpSocket::bind(config.listen)?;
+    socket.set_read_timeout(Some(READ_TIMEOUT))?;
+    Ok(socket)
+}
+
+// create the output the records are written to
+fn create_output(config: &Config) -> io::Result<Box<dyn Write>> {
+    match &config.output {
+        Some(path) => {
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            Ok(Box::new(file))
+        }
+        None => Ok(Box::new(io::stdout())),
+    }
+}
+
+// format the export from exporter as output lines
+fn format_export(export: &Export, exporter: &SocketAddr, config: &Config) -> Vec<String> {
+    let received = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    if config.json {
+        return export
+            .records
+            .iter()
+            .map(|record| record.to_json(exporter, export, received))
+            .collect();
+    }
+
+    let protocol = export.get_protocol();
+    let domain = export.domain;
+    let mut lines = Vec::new();
+    for (id, template) in &export.templates {
+        let kind = match template.is_options() {
+            true => "options template",
+            false => "template",
+        };
+        lines.push(format!(
+            "got {} {} {} from {} (domain {}): {}",
+            protocol, kind, id, exporter, domain, template
+        ));
+    }
+    for id in &export.withdrawals {
+        lines.push(format!(
+            "got {} template withdrawal {} from {} (domain {})",
+            protocol, id, exporter, domain
+        ));
+    }
+    for record in &export.records {
+        let kind = match record.options {
+            true => "options record",
+            false => "flow",
+        };
+        lines.push(format!(
+            "got {} {} from {} (domain {}): {}",
+            protocol, kind, exporter, domain, record
+        ));
+    }
+    for id in &export.missing_templates {
+        lines.push(format!(
+            "got {} data set from {} (domain {}) with unknown template {}",
+            protocol, exporter, domain, id
+        ));
+    }
+    lines
+}
+
+// receive flow exports and write their records to output until running is
+// unset
+fn listen(socket: &UdpSocket, output: &mut dyn Write, config: &Config, running: &AtomicBool) {
+    let mut templates = Templates::default();
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+    while running.load(atomic::Ordering::Relaxed) {
+        let (size, exporter) = match socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(e) => match e.kind() {
+                io::ErrorKind::WouldBlock
+                | io::ErrorKind::TimedOut
+                | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading flow exports: {}", e),
+            },
+        };
+
+        // invalid exports are not written as json records
+        let lines = match Export::parse(&buffer[..size], exporter, &mut templates) {
+            Ok(export) => format_export(&export, &exporter, config),
+            Err(e) if config.json => {
+                eprintln!("Could not parse flow export from {}: {}", exporter, e);
+                continue;
+            }
+            Err(e) => vec![format!("got invalid flow export from {}: {}", exporter, e)],
+        };
+        for line in lines {
+            if let Err(e) = writeln!(output, "{}", line) {
+                panic!("An error occurred when writing flow records: {}", e);
+            }
+        }
+        if let Err(e) = output.flush() {
+            panic!("An error occurred when writing flow records: {}", e);
+        }
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let socket = match create_socket(&config) {
+        Ok(socket) => socket,
+        Err(e) => panic!(
+            "An error occurred when creating the collector socket: {}",
+            e
+        ),
+    };
+    let mut output = match create_output(&config) {
+        Ok(output) => output,
+        Err(e) => panic!("An error occurred when opening the output file: {}", e),
+    };
+
+    // stop on ctrl-c or sigterm
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    listen(&socket, output.as_mut(), &config, &running);
+}
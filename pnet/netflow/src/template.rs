@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+
+use crate::field;
+
+// length of variable-length ipfix fields
+pub const VARIABLE_LENGTH: u16 = 65535;
+
+// specifier of a field in a template
+#[derive(Clone)]
+pub struct FieldSpec {
+    pub id: u16,
+    pub enterprise: Option<u32>,
+    pub length: u16,
+
+    // scope fields of netflow v9 options templates have their own types
+    pub v9_scope: bool,
+}
+
+impl FieldSpec {
+    // get the name of the field
+    pub fn get_name(&self) -> String {
+        match self.v9_scope {
+            true => field::get_v9_scope_name(self.id),
+            false => field::get_field_name(self.id, self.enterprise),
+        }
+    }
+}
+
+impl fmt::Display for FieldSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.get_name();
+        match self.length {
+            VARIABLE_LENGTH => write!(f, "{}(variable)", name),
+            length => write!(f, "{}({})", name, length),
+        }
+    }
+}
+
+// template or options template that describes the data records of a set;
+// the first scope_fields fields of options templates are scope fields
+#[derive(Clone)]
+pub struct Template {
+    pub fields: Vec<FieldSpec>,
+    pub scope_fields: usize,
+}
+
+impl Template {
+    // check if the template is an options template
+    pub fn is_options(&self) -> bool {
+        self.scope_fields > 0
+    }
+
+    // get the minimum length of a data record; variable-length fields are
+    // at least one byte long
+    pub fn get_min_length(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|f| match f.length {
+                VARIABLE_LENGTH => 1,
+                length => usize::from(length),
+            })
+            .sum()
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields = |specs: &[FieldSpec]| {
+            specs
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        if self.is_options() {
+            write!(
+                f,
+                "{{scope fields: [{}], fields: [{}]}}",
+                fields(&self.fields[..self.scope_fields]),
+                fields(&self.fields[self.scope_fields..])
+            )
+        } else {
+            write!(f, "{{fields: [{}]}}", fields(&self.fields))
+        }
+    }
+}
+
+// template identified by the exporter, the netflow version, the source id or
+// observation domain and the template id
+pub type TemplateKey = (SocketAddr, u16, u32, u16);
+
+// templates received from all exporters
+#[derive(Default)]
+pub struct Templates {
+    templates: HashMap<TemplateKey, Template>,
+}
+
+impl Templates {
+    // add or replace the template with key
+    pub fn add(&mut self, key: TemplateKey, template: Template) {
+        self.templates.insert(key, template);
+    }
+
+    // remove the withdrawn template with key
+    pub fn remove(&mut self, key: &TemplateKey) {
+        self.templates.remove(key);
+    }
+
+    // get the template with key
+    pub fn get(&self, key: &TemplateKey) -> Option<&Template> {
+        self.templates.get(key)
+    }
+}
@@ -0,0 +1,45 @@
+use clap::Parser;
+
+// command line configuration of the ntp tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Listen for ntp packets and parse them")]
+pub struct Config {
+    // network interface to capture packets on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Capture packets on the network interface NAME; by default, \
+        the first interface that is up, is not a loopback and has an ip \
+        address is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., to see the ntp \
+        traffic of other hosts on a mirrored port"
+    )]
+    pub promiscuous: bool,
+
+    // ip versions
+    #[arg(
+        short = '4',
+        long,
+        conflicts_with = "ipv6",
+        help = "Only listen for ntp packets over ipv4"
+    )]
+    pub ipv4: bool,
+    #[arg(short = '6', long, help = "Only listen for ntp packets over ipv6")]
+    pub ipv6: bool,
+
+    // only print exchanges
+    #[arg(
+        short,
+        long,
+        help = "Only print the offsets and delays of client/server exchanges \
+        instead of every packet"
+    )]
+    pub exchanges: bool,
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::packet::{Mode, NtpPacket, NtpTimestamp};
+
+// time after which unanswered requests are removed
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// maximum number of unanswered requests before timed out ones are removed
+const MAX_REQUESTS: usize = 4096;
+
+// client/server exchange with the offset of the server's clock to the
+// local clock and the round-trip delay in seconds
+pub struct Exchange {
+    client: SocketAddr,
+    server: SocketAddr,
+    stratum: u8,
+    offset: f64,
+    delay: f64,
+}
+
+impl fmt::Display for Exchange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ntp exchange of client {} with server {}: stratum {}, offset {:+.3} ms, \
+            delay {:.3} ms",
+            self.client,
+            self.server,
+            self.stratum,
+            self.offset * 1000.0,
+            self.delay * 1000.0
+        )
+    }
+}
+
+// seconds since the unix epoch of time
+fn get_unix_secs(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+// tracker of client requests that matches them with the server replies
+// that carry their transmit timestamp as origin timestamp
+#[derive(Default)]
+pub struct Exchanges {
+    // capture times of requests, keyed by client, server and transmit
+    // timestamp
+    requests: HashMap<(SocketAddr, SocketAddr, NtpTimestamp), SystemTime>,
+}
+
+impl Exchanges {
+    // add the ntp packet from source to destination captured at time
+    // captured and get the exchange if it is the reply to a request; the
+    // capture times of the request and the reply are used as the client's
+    // send and receive times, so the offset is the one of the server's clock
+    // to the local clock and the delay is the one seen from this host
+    pub fn add(
+        &mut self,
+        packet: &NtpPacket,
+        addresses: (SocketAddr, SocketAddr),
+        captured: SystemTime,
+    ) -> Option<Exchange> {
+        let (source, destination) = addresses;
+        match packet.get_mode() {
+            Mode::Client => {
+                if self.requests.len() >= MAX_REQUESTS {
+                    self.requests.retain(|_, sent| {
+                        captured.duration_since(*sent).unwrap_or_default() < REQUEST_TIMEOUT
+                    });
+                }
+                let key = (source, destination, packet.get_transmit_timestamp());
+                self.requests.insert(key, captured);
+                None
+            }
+            Mode::Server => {
+                let key = (destination, source, packet.get_origin_timestamp());
+                let sent = self.requests.remove(&key)?;
+                let (receive, transmit) = (
+                    packet.get_receive_timestamp(),
+                    packet.get_transmit_timestamp(),
+                );
+                if receive.is_zero() || transmit.is_zero() {
+                    return None;
+                }
+                let t1 = get_unix_secs(sent);
+                let t2 = receive.get_unix_secs();
+                let t3 = transmit.get_unix_secs();
+                let t4 = get_unix_secs(captured);
+                Some(Exchange {
+                    client: destination,
+                    server: source,
+                    stratum: packet.get_stratum(),
+                    offset: ((t2 - t1) + (t3 - t4)) / 2.0,
+                    delay: (t4 - t1) - (t3 - t2),
+                })
+            }
+            _ => None,
+        }
+    }
+}
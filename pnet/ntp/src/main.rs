@@ -0,0 +1,103 @@
+extern crate pnet;
+
+mod config;
+mod exchange;
+mod packet;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::dissect::{parse_ip_frame, parse_udp, Datagram};
+use packet_utils::interface::find_interface;
+use pnet::datalink::DataLinkReceiver;
+
+use std::io;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use config::Config;
+use exchange::Exchanges;
+use packet::NtpPacket;
+
+// well-known port of ntp
+const NTP_PORT: u16 = 123;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// parse the ethernet frame and get the ntp packet inside it if its ip
+// version is enabled in config; fragmented ipv4 packets and ipv6 packets
+// with extension headers are ignored
+fn parse_frame<'a>(frame: &'a [u8], config: &Config) -> Option<Datagram<'a>> {
+    let ip = parse_ip_frame(frame, config.ipv4, config.ipv6)?;
+    parse_udp(&ip, &[NTP_PORT])
+}
+
+// receive ntp packets, print them and the exchanges they finished until
+// running is unset
+fn listen(rx: &mut dyn DataLinkReceiver, config: &Config, running: &AtomicBool) {
+    let mut exchanges = Exchanges::default();
+    while running.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading packets: {}", e),
+            },
+        };
+        let captured = SystemTime::now();
+        let datagram = match parse_frame(frame, config) {
+            Some(datagram) => datagram,
+            None => continue,
+        };
+        let ntp_packet = match NtpPacket::parse(datagram.payload) {
+            Some(ntp_packet) => ntp_packet,
+            None => {
+                println!(
+                    "got invalid ntp packet from {} to {}: invalid length of packet",
+                    datagram.source, datagram.destination
+                );
+                continue;
+            }
+        };
+        if !config.exchanges {
+            println!(
+                "got ntp packet from {} to {}: {}",
+                datagram.source, datagram.destination, ntp_packet
+            );
+        }
+        let addresses = (datagram.source, datagram.destination);
+        if let Some(exchange) = exchanges.add(&ntp_packet, addresses, captured) {
+            println!("{}", exchange);
+        }
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let mut rx = match create_channel(&interface, READ_TIMEOUT, config.promiscuous) {
+        Ok((_, rx)) => rx,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+
+    // stop on ctrl-c or sigterm
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    listen(rx.as_mut(), &config, &running);
+}
@@ -0,0 +1,328 @@
+use std::fmt;
+use std::net::Ipv4Addr;
+
+// length of the ntp header without extension fields and message digest
+pub const NTP_HEADER_LENGTH: usize = 48;
+
+// seconds between the ntp era 0 epoch in 1900 and the unix epoch in 1970
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+
+// convert a 32 bit field from big endian to native byte order
+fn read_be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes(bytes.try_into().expect("slice with incorrect length"))
+}
+
+// convert a 64 bit field from big endian to native byte order
+fn read_be_u64(bytes: &[u8]) -> u64 {
+    u64::from_be_bytes(bytes.try_into().expect("slice with incorrect length"))
+}
+
+// convert a 32 bit ntp short format value to seconds
+fn short_to_secs(value: u32) -> f64 {
+    f64::from(value) / 65536.0
+}
+
+// get the date and time of the seconds since the unix epoch in utc as text,
+// e.g., "2024-01-02 12:34:56.789012"
+fn format_date(secs: u64, micros: u64) -> String {
+    // convert days since the unix epoch to the civil date
+    let days = (secs / 86400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    let time = secs % 86400;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:06}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60,
+        micros
+    )
+}
+
+// ntp timestamp with 32 bits of seconds since the start of the ntp era and
+// 32 bits of fractions of a second
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NtpTimestamp(pub u64);
+
+impl NtpTimestamp {
+    // check if the timestamp is unset
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    // get the seconds since the unix epoch; seconds with the most
+    // significant bit unset are in era 1 starting in 2036, era 0 seconds
+    // before 1970 are negative
+    pub fn get_unix_secs(&self) -> f64 {
+        let mut secs = self.0 >> 32;
+        if secs & 0x8000_0000 == 0 {
+            secs += 1 << 32;
+        }
+        let fraction = (self.0 & 0xffff_ffff) as f64 / 4_294_967_296.0;
+        (secs as i64 - NTP_UNIX_OFFSET) as f64 + fraction
+    }
+}
+
+impl fmt::Display for NtpTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // dates before the unix epoch cannot be formatted, print the raw
+        // value instead
+        let secs = self.get_unix_secs();
+        if secs < 0.0 {
+            return write!(f, "{:#018x}", self.0);
+        }
+        let micros = ((secs.fract() * 1_000_000.0) as u64).min(999_999);
+        write!(f, "{}", format_date(secs as u64, micros))
+    }
+}
+
+// Leap indicator:
+// 0               no warning
+// 1               last minute of the day has 61 seconds
+// 2               last minute of the day has 59 seconds
+// 3               unknown (clock unsynchronized)
+pub enum LeapIndicator {
+    NoWarning,
+    LastMinute61,
+    LastMinute59,
+    Unsynchronized,
+}
+
+impl From<u8> for LeapIndicator {
+    fn from(value: u8) -> Self {
+        match value & 0x3 {
+            0 => LeapIndicator::NoWarning,
+            1 => LeapIndicator::LastMinute61,
+            2 => LeapIndicator::LastMinute59,
+            _ => LeapIndicator::Unsynchronized,
+        }
+    }
+}
+
+impl fmt::Display for LeapIndicator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LeapIndicator::NoWarning => write!(f, "0 (no warning)"),
+            LeapIndicator::LastMinute61 => write!(f, "1 (last minute has 61 seconds)"),
+            LeapIndicator::LastMinute59 => write!(f, "2 (last minute has 59 seconds)"),
+            LeapIndicator::Unsynchronized => write!(f, "3 (unsynchronized)"),
+        }
+    }
+}
+
+// Mode:
+// 0               reserved
+// 1               symmetric active
+// 2               symmetric passive
+// 3               client
+// 4               server
+// 5               broadcast
+// 6               ntp control message
+// 7               reserved for private use
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Reserved,
+    SymmetricActive,
+    SymmetricPassive,
+    Client,
+    Server,
+    Broadcast,
+    Control,
+    Private,
+}
+
+impl From<u8> for Mode {
+    fn from(value: u8) -> Self {
+        match value & 0x7 {
+            1 => Mode::SymmetricActive,
+            2 => Mode::SymmetricPassive,
+            3 => Mode::Client,
+            4 => Mode::Server,
+            5 => Mode::Broadcast,
+            6 => Mode::Control,
+            7 => Mode::Private,
+            _ => Mode::Reserved,
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mode::Reserved => write!(f, "0 (reserved)"),
+            Mode::SymmetricActive => write!(f, "1 (symmetric active)"),
+            Mode::SymmetricPassive => write!(f, "2 (symmetric passive)"),
+            Mode::Client => write!(f, "3 (client)"),
+            Mode::Server => write!(f, "4 (server)"),
+            Mode::Broadcast => write!(f, "5 (broadcast)"),
+            Mode::Control => write!(f, "6 (control)"),
+            Mode::Private => write!(f, "7 (private)"),
+        }
+    }
+}
+
+// ntp packet consists of the following fields:
+//
+// LI (2 bits), VN (3 bits), Mode (3 bits), Stratum (8 bits),
+// Poll (8 bits), Precision (8 bits),
+// Root Delay (32 bits), Root Dispersion (32 bits),
+// Reference ID (32 bits),
+// Reference Timestamp (64 bits), Origin Timestamp (64 bits),
+// Receive Timestamp (64 bits), Transmit Timestamp (64 bits)
+//
+// followed by optional extension fields and a message digest; control
+// messages (mode 6) and private messages (mode 7) use other formats, so
+// only their first byte is valid
+//
+// use methods to read fields from the packet
+pub struct NtpPacket<'a> {
+    raw: &'a [u8],
+}
+
+impl<'a> NtpPacket<'a> {
+    // create a new ntp packet from raw packet bytes
+    pub fn parse(raw: &'a [u8]) -> Option<NtpPacket<'a>> {
+        let mode = Mode::from(*raw.first()?);
+        if !matches!(mode, Mode::Control | Mode::Private) && raw.len() < NTP_HEADER_LENGTH {
+            return None;
+        }
+        Some(NtpPacket { raw })
+    }
+
+    // check if the packet has the header with the timestamps
+    fn has_header(&self) -> bool {
+        !matches!(self.get_mode(), Mode::Control | Mode::Private)
+    }
+
+    // get the leap indicator
+    pub fn get_leap_indicator(&self) -> LeapIndicator {
+        LeapIndicator::from(self.raw[0] >> 6)
+    }
+
+    // get the version number
+    pub fn get_version(&self) -> u8 {
+        (self.raw[0] >> 3) & 0x7
+    }
+
+    // get the mode
+    pub fn get_mode(&self) -> Mode {
+        Mode::from(self.raw[0])
+    }
+
+    // get the stratum
+    pub fn get_stratum(&self) -> u8 {
+        self.raw[1]
+    }
+
+    // get the poll interval as log2 seconds
+    pub fn get_poll(&self) -> i8 {
+        self.raw[2] as i8
+    }
+
+    // get the precision of the clock as log2 seconds
+    pub fn get_precision(&self) -> i8 {
+        self.raw[3] as i8
+    }
+
+    // get the round-trip delay to the reference clock in seconds
+    pub fn get_root_delay(&self) -> f64 {
+        short_to_secs(read_be_u32(&self.raw[4..8]))
+    }
+
+    // get the dispersion to the reference clock in seconds
+    pub fn get_root_dispersion(&self) -> f64 {
+        short_to_secs(read_be_u32(&self.raw[8..12]))
+    }
+
+    // get the reference id as text; it is a kiss code in stratum 0 packets,
+    // the name of the reference clock in stratum 1 packets and the ipv4
+    // address or the hash of the ipv6 address of the server's reference in
+    // other packets
+    pub fn get_reference_id(&self) -> String {
+        let id = &self.raw[12..16];
+        match self.get_stratum() {
+            0 | 1 => {
+                let end = id.iter().position(|b| *b == 0).unwrap_or(id.len());
+                let text = String::from_utf8_lossy(&id[..end]).into_owned();
+                match self.get_stratum() {
+                    0 => format!("{:?} (kiss code)", text),
+                    _ => format!("{:?}", text),
+                }
+            }
+            _ => Ipv4Addr::new(id[0], id[1], id[2], id[3]).to_string(),
+        }
+    }
+
+    // get the time the clock was last set or corrected
+    pub fn get_reference_timestamp(&self) -> NtpTimestamp {
+        NtpTimestamp(read_be_u64(&self.raw[16..24]))
+    }
+
+    // get the time the request departed the client in replies
+    pub fn get_origin_timestamp(&self) -> NtpTimestamp {
+        NtpTimestamp(read_be_u64(&self.raw[24..32]))
+    }
+
+    // get the time the request arrived at the server in replies
+    pub fn get_receive_timestamp(&self) -> NtpTimestamp {
+        NtpTimestamp(read_be_u64(&self.raw[32..40]))
+    }
+
+    // get the time the packet departed its sender
+    pub fn get_transmit_timestamp(&self) -> NtpTimestamp {
+        NtpTimestamp(read_be_u64(&self.raw[40..48]))
+    }
+}
+
+impl<'a> fmt::Display for NtpPacket<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{li: {}, version: {}, mode: {}",
+            self.get_leap_indicator(),
+            self.get_version(),
+            self.get_mode()
+        )?;
+        if !self.has_header() {
+            return write!(f, ", length: {}}}", self.raw.len());
+        }
+        write!(
+            f,
+            ", stratum: {}, poll: {}, precision: {}, root delay: {:.3} ms, \
+            root dispersion: {:.3} ms, reference id: {}, reference: {}, \
+            origin: {}, receive: {}, transmit: {}",
+            self.get_stratum(),
+            self.get_poll(),
+            self.get_precision(),
+            self.get_root_delay() * 1000.0,
+            self.get_root_dispersion() * 1000.0,
+            self.get_reference_id(),
+            self.get_reference_timestamp(),
+            self.get_origin_timestamp(),
+            self.get_receive_timestamp(),
+            self.get_transmit_timestamp(),
+        )?;
+        if self.raw.len() > NTP_HEADER_LENGTH {
+            write!(
+                f,
+                ", extensions: {} bytes",
+                self.raw.len() - NTP_HEADER_LENGTH
+            )?;
+        }
+        write!(f, "}}")
+    }
+}
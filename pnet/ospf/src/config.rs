@@ -0,0 +1,29 @@
+use clap::Parser;
+
+// command line configuration of the ospf tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Decode ospf packets and show the routers and neighbors in hellos")]
+pub struct Config {
+    // network interface to capture ospf packets on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Capture ospf packets on the network interface NAME; by \
+        default, the first interface that is up, is not a loopback and has an \
+        ip address is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., to see ospf \
+        packets sent to multicast groups this host did not join"
+    )]
+    pub promiscuous: bool,
+
+    // only print alerts
+    #[arg(short, long, help = "Only print alerts instead of every ospf packet")]
+    pub quiet: bool,
+}
@@ -0,0 +1,135 @@
+extern crate pnet;
+
+mod config;
+mod ospf;
+mod routers;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::interface::find_interface;
+use pnet::datalink::DataLinkReceiver;
+
+use std::io;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::Duration;
+
+use config::Config;
+use ospf::OspfPacket;
+use routers::RouterTable;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// receive ospf packets, add them to the table and print the packets and the
+// alerts they caused until running is unset
+fn monitor(
+    rx: &mut dyn DataLinkReceiver,
+    table: &mut RouterTable,
+    config: &Config,
+    running: &AtomicBool,
+) {
+    while running.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading ospf packets: {}", e),
+            },
+        };
+        let packet = match OspfPacket::parse(frame) {
+            Some(packet) => packet,
+            None => continue,
+        };
+        if !config.quiet {
+            println!("{}", packet);
+        }
+        for alert in table.add(&packet) {
+            println!("{}", alert);
+        }
+    }
+}
+
+// print the routers in the table; neighbors that do not list the router in
+// their hellos are marked as one-way
+fn print_table(table: &RouterTable) {
+    println!("--- ospf routers ---");
+    println!(
+        "{:<7} {:<15} {:<39} {:<15} {:>5} {:>5} {:>4} {:<15} {:<15} {:>5} {:>7}  NEIGHBORS",
+        "VERSION",
+        "ROUTER ID",
+        "SOURCE",
+        "AREA",
+        "HELLO",
+        "DEAD",
+        "PRIO",
+        "DR",
+        "BDR",
+        "MTU",
+        "PACKETS"
+    );
+    for (key, router) in table.get_routers() {
+        let neighbors = router
+            .neighbors
+            .iter()
+            .map(|n| match table.is_two_way(*key, *n) {
+                true => n.to_string(),
+                false => format!("{} (one-way)", n),
+            })
+            .collect::<Vec<_>>();
+        let (area, hello, dead) = match &router.hello {
+            Some(h) => (
+                h.area.to_string(),
+                h.hello_interval.to_string(),
+                h.dead_interval.to_string(),
+            ),
+            None => ("-".to_string(), "-".to_string(), "-".to_string()),
+        };
+        println!(
+            "{:<7} {:<15} {:<39} {:<15} {:>5} {:>5} {:>4} {:<15} {:<15} {:>5} {:>7}  [{}]",
+            format!("ospfv{}", key.0),
+            key.1.to_string(),
+            router.source.to_string(),
+            area,
+            hello,
+            dead,
+            router.priority,
+            router.designated_router.to_string(),
+            router.backup_designated_router.to_string(),
+            router.mtu.map_or("-".to_string(), |m| m.to_string()),
+            router.packets,
+            neighbors.join(", "),
+        );
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let mut rx = match create_channel(&interface, READ_TIMEOUT, config.promiscuous) {
+        Ok((_, rx)) => rx,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+
+    // stop on ctrl-c or sigterm and print the table
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    let mut table = RouterTable::default();
+    monitor(rx.as_mut(), &mut table, &config, &running);
+    print_table(&table);
+}
@@ -0,0 +1,338 @@
+use packet_utils::bytes::{read_be_u16, read_be_u32};
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::Packet;
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr};
+
+// ospf packet types
+const TYPE_HELLO: u8 = 1;
+const TYPE_DATABASE_DESCRIPTION: u8 = 2;
+const TYPE_LINK_STATE_REQUEST: u8 = 3;
+const TYPE_LINK_STATE_UPDATE: u8 = 4;
+const TYPE_LINK_STATE_ACK: u8 = 5;
+
+// header lengths of ospfv2 and ospfv3 packets
+const OSPFV2_HEADER_LENGTH: usize = 24;
+const OSPFV3_HEADER_LENGTH: usize = 16;
+
+// lengths of link state request entries and lsa headers
+const REQUEST_LENGTH: usize = 12;
+const LSA_HEADER_LENGTH: usize = 20;
+
+// database description flags
+const FLAG_MASTER: u8 = 0x01;
+const FLAG_MORE: u8 = 0x02;
+const FLAG_INIT: u8 = 0x04;
+
+// get the ipv4 address or router id in the 4 bytes of bytes
+fn read_ipv4(bytes: &[u8]) -> Ipv4Addr {
+    let octets: [u8; 4] = bytes.try_into().expect("slice with incorrect length");
+    Ipv4Addr::from(octets)
+}
+
+// format the addresses or router ids as list
+fn format_ids(ids: &[Ipv4Addr]) -> String {
+    let ids = ids.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+    format!("[{}]", ids.join(", "))
+}
+
+// hello packet; the network mask only exists in ospfv2 and the interface id
+// only in ospfv3; designated routers are interface addresses in ospfv2 and
+// router ids in ospfv3
+pub struct Hello {
+    pub network_mask: Option<Ipv4Addr>,
+    pub interface_id: Option<u32>,
+    pub hello_interval: u16,
+    pub dead_interval: u32,
+    pub options: u32,
+    pub priority: u8,
+    pub designated_router: Ipv4Addr,
+    pub backup_designated_router: Ipv4Addr,
+    pub neighbors: Vec<Ipv4Addr>,
+}
+
+impl Hello {
+    fn parse(version: u8, body: &[u8]) -> Option<Hello> {
+        // Hello Format:
+        // ospfv2: network mask (4), hello interval (2), options (1),
+        // priority (1), dead interval (4), dr (4), bdr (4), neighbors
+        // ospfv3: interface id (4), priority (1), options (3), hello
+        // interval (2), dead interval (2), dr (4), bdr (4), neighbors
+        let header = body.get(..20)?;
+        let neighbors = body[20..].chunks_exact(4).map(read_ipv4).collect();
+        let hello = match version {
+            2 => Hello {
+                network_mask: Some(read_ipv4(&header[0..4])),
+                interface_id: None,
+                hello_interval: read_be_u16(&header[4..6]),
+                dead_interval: read_be_u32(&header[8..12]),
+                options: header[6].into(),
+                priority: header[7],
+                designated_router: read_ipv4(&header[12..16]),
+                backup_designated_router: read_ipv4(&header[16..20]),
+                neighbors,
+            },
+            _ => Hello {
+                network_mask: None,
+                interface_id: Some(read_be_u32(&header[0..4])),
+                hello_interval: read_be_u16(&header[8..10]),
+                dead_interval: read_be_u16(&header[10..12]).into(),
+                options: read_be_u32(&header[4..8]) & 0xffffff,
+                priority: header[4],
+                designated_router: read_ipv4(&header[12..16]),
+                backup_designated_router: read_ipv4(&header[16..20]),
+                neighbors,
+            },
+        };
+        Some(hello)
+    }
+}
+
+impl fmt::Display for Hello {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(mask) = self.network_mask {
+            write!(f, ", network mask: {}", mask)?;
+        }
+        if let Some(id) = self.interface_id {
+            write!(f, ", interface id: {}", id)?;
+        }
+        write!(
+            f,
+            ", hello interval: {}, dead interval: {}, options: {:#x}, priority: {}, \
+            dr: {}, bdr: {}, neighbors: {}",
+            self.hello_interval,
+            self.dead_interval,
+            self.options,
+            self.priority,
+            self.designated_router,
+            self.backup_designated_router,
+            format_ids(&self.neighbors)
+        )
+    }
+}
+
+// database description packet
+pub struct DatabaseDescription {
+    pub mtu: u16,
+    pub options: u32,
+    pub flags: u8,
+    pub sequence: u32,
+    pub lsa_headers: usize,
+}
+
+impl DatabaseDescription {
+    fn parse(version: u8, body: &[u8]) -> Option<DatabaseDescription> {
+        // Database Description Format:
+        // ospfv2: mtu (2), options (1), flags (1), sequence number (4)
+        // ospfv3: reserved (1), options (3), mtu (2), reserved (1), flags
+        // (1), sequence number (4)
+        // both followed by lsa headers
+        let (mtu, options, flags, sequence, rest) = match version {
+            2 => {
+                let header = body.get(..8)?;
+                (
+                    read_be_u16(&header[0..2]),
+                    header[2].into(),
+                    header[3],
+                    read_be_u32(&header[4..8]),
+                    &body[8..],
+                )
+            }
+            _ => {
+                let header = body.get(..12)?;
+                (
+                    read_be_u16(&header[4..6]),
+                    read_be_u32(&header[0..4]) & 0xffffff,
+                    header[7],
+                    read_be_u32(&header[8..12]),
+                    &body[12..],
+                )
+            }
+        };
+        Some(DatabaseDescription {
+            mtu,
+            options,
+            flags,
+            sequence,
+            lsa_headers: rest.len() / LSA_HEADER_LENGTH,
+        })
+    }
+}
+
+impl fmt::Display for DatabaseDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = [
+            (FLAG_INIT, "init"),
+            (FLAG_MORE, "more"),
+            (FLAG_MASTER, "master"),
+        ]
+        .iter()
+        .filter(|(flag, _)| self.flags & flag != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>();
+        write!(
+            f,
+            ", mtu: {}, options: {:#x}, flags: [{}], sequence: {}, lsa headers: {}",
+            self.mtu,
+            self.options,
+            flags.join(", "),
+            self.sequence,
+            self.lsa_headers
+        )
+    }
+}
+
+// type of an ospf packet with its fields
+pub enum PacketType {
+    Hello(Hello),
+    DatabaseDescription(DatabaseDescription),
+    LinkStateRequest { requests: usize },
+    LinkStateUpdate { lsas: u32 },
+    LinkStateAck { lsa_headers: usize },
+}
+
+impl fmt::Display for PacketType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PacketType::Hello(_) => write!(f, "hello"),
+            PacketType::DatabaseDescription(_) => write!(f, "database description"),
+            PacketType::LinkStateRequest { .. } => write!(f, "link state request"),
+            PacketType::LinkStateUpdate { .. } => write!(f, "link state update"),
+            PacketType::LinkStateAck { .. } => write!(f, "link state ack"),
+        }
+    }
+}
+
+// ospfv2 or ospfv3 packet with the addresses of its ip header
+pub struct OspfPacket {
+    pub source: IpAddr,
+    pub destination: IpAddr,
+    pub version: u8,
+    pub router_id: Ipv4Addr,
+    pub area: Ipv4Addr,
+
+    // authentication type of ospfv2 and instance id of ospfv3
+    pub auth_type: Option<u16>,
+    pub instance_id: Option<u8>,
+
+    pub packet_type: PacketType,
+}
+
+impl OspfPacket {
+    // parse the ospf packet in the ethernet frame
+    pub fn parse(frame: &[u8]) -> Option<OspfPacket> {
+        let ethernet_packet = EthernetPacket::new(frame)?;
+        match ethernet_packet.get_ethertype() {
+            EtherTypes::Ipv4 => {
+                let ipv4_packet = Ipv4Packet::new(ethernet_packet.payload())?;
+                if ipv4_packet.get_next_level_protocol() != IpNextHeaderProtocols::OspfigP {
+                    return None;
+                }
+                let header_length = usize::from(ipv4_packet.get_header_length()) * 4;
+                let total_length = usize::from(ipv4_packet.get_total_length());
+                let ospf = ethernet_packet.payload().get(header_length..total_length)?;
+                OspfPacket::parse_ospf(
+                    IpAddr::V4(ipv4_packet.get_source()),
+                    IpAddr::V4(ipv4_packet.get_destination()),
+                    ospf,
+                )
+            }
+            EtherTypes::Ipv6 => {
+                let ipv6_packet = Ipv6Packet::new(ethernet_packet.payload())?;
+                if ipv6_packet.get_next_header() != IpNextHeaderProtocols::OspfigP {
+                    return None;
+                }
+                let length = usize::from(ipv6_packet.get_payload_length());
+                let ospf = ipv6_packet.payload().get(..length)?;
+                OspfPacket::parse_ospf(
+                    IpAddr::V6(ipv6_packet.get_source()),
+                    IpAddr::V6(ipv6_packet.get_destination()),
+                    ospf,
+                )
+            }
+            _ => None,
+        }
+    }
+
+    // parse the ospf packet sent from source to destination in ospf
+    fn parse_ospf(source: IpAddr, destination: IpAddr, ospf: &[u8]) -> Option<OspfPacket> {
+        // Header Format:
+        // version (1), type (1), packet length (2), router id (4), area id
+        // (4), checksum (2), then in ospfv2 authentication type (2) and
+        // authentication (8) and in ospfv3 instance id (1) and reserved (1)
+        let version = *ospf.first()?;
+        let header_length = match version {
+            2 => OSPFV2_HEADER_LENGTH,
+            3 => OSPFV3_HEADER_LENGTH,
+            _ => return None,
+        };
+        let header = ospf.get(..header_length)?;
+        let length = usize::from(read_be_u16(&header[2..4]));
+        let body = ospf.get(header_length..length)?;
+        let packet_type = match header[1] {
+            TYPE_HELLO => PacketType::Hello(Hello::parse(version, body)?),
+            TYPE_DATABASE_DESCRIPTION => {
+                PacketType::DatabaseDescription(DatabaseDescription::parse(version, body)?)
+            }
+            TYPE_LINK_STATE_REQUEST => PacketType::LinkStateRequest {
+                requests: body.len() / REQUEST_LENGTH,
+            },
+            TYPE_LINK_STATE_UPDATE => PacketType::LinkStateUpdate {
+                lsas: read_be_u32(body.get(..4)?),
+            },
+            TYPE_LINK_STATE_ACK => PacketType::LinkStateAck {
+                lsa_headers: body.len() / LSA_HEADER_LENGTH,
+            },
+            _ => return None,
+        };
+        let (auth_type, instance_id) = match version {
+            2 => (Some(read_be_u16(&header[14..16])), None),
+            _ => (None, Some(header[14])),
+        };
+        Some(OspfPacket {
+            source,
+            destination,
+            version,
+            router_id: read_ipv4(&header[4..8]),
+            area: read_ipv4(&header[8..12]),
+            auth_type,
+            instance_id,
+            packet_type,
+        })
+    }
+}
+
+impl fmt::Display for OspfPacket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ospfv{} {} from {} to {}: {{router id: {}, area: {}",
+            self.version,
+            self.packet_type,
+            self.source,
+            self.destination,
+            self.router_id,
+            self.area
+        )?;
+        if let Some(auth_type) = self.auth_type {
+            write!(f, ", auth type: {}", auth_type)?;
+        }
+        if let Some(instance_id) = self.instance_id {
+            write!(f, ", instance id: {}", instance_id)?;
+        }
+        match &self.packet_type {
+            PacketType::Hello(hello) => write!(f, "{}", hello)?,
+            PacketType::DatabaseDescription(dd) => write!(f, "{}", dd)?,
+            PacketType::LinkStateRequest { requests } => write!(f, ", requests: {}", requests)?,
+            PacketType::LinkStateUpdate { lsas } => write!(f, ", lsas: {}", lsas)?,
+            PacketType::LinkStateAck { lsa_headers } => {
+                write!(f, ", lsa headers: {}", lsa_headers)?
+            }
+        }
+        write!(f, "}}")
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Instant;
+
+use crate::ospf::{OspfPacket, PacketType};
+
+// parameters in hellos that must match on a link to form an adjacency
+#[derive(Clone, PartialEq, Eq)]
+pub struct HelloParameters {
+    pub area: Ipv4Addr,
+    pub hello_interval: u16,
+    pub dead_interval: u32,
+    pub network_mask: Option<Ipv4Addr>,
+}
+
+impl HelloParameters {
+    // get the names and values of the parameters that differ from other
+    fn get_mismatches(&self, other: &HelloParameters) -> Vec<(&'static str, String, String)> {
+        let mut mismatches = Vec::new();
+        if self.area != other.area {
+            mismatches.push(("area", self.area.to_string(), other.area.to_string()));
+        }
+        if self.hello_interval != other.hello_interval {
+            mismatches.push((
+                "hello interval",
+                self.hello_interval.to_string(),
+                other.hello_interval.to_string(),
+            ));
+        }
+        if self.dead_interval != other.dead_interval {
+            mismatches.push((
+                "dead interval",
+                self.dead_interval.to_string(),
+                other.dead_interval.to_string(),
+            ));
+        }
+        if let (Some(mask), Some(other_mask)) = (self.network_mask, other.network_mask) {
+            if mask != other_mask {
+                mismatches.push(("network mask", mask.to_string(), other_mask.to_string()));
+            }
+        }
+        mismatches
+    }
+}
+
+// problems that prevent or break adjacencies
+pub enum Alert {
+    // router uses other hello parameters or another mtu than a router on
+    // the same link
+    Mismatch {
+        router: Ipv4Addr,
+        other: Ipv4Addr,
+        parameter: &'static str,
+        value: String,
+        other_value: String,
+    },
+
+    // router no longer lists neighbor in its hellos
+    NeighborLost {
+        router: Ipv4Addr,
+        neighbor: Ipv4Addr,
+    },
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alert::Mismatch {
+                router,
+                other,
+                parameter,
+                value,
+                other_value,
+            } => write!(
+                f,
+                "ALERT: {} mismatch: router {} uses {}, router {} uses {}",
+                parameter, router, value, other, other_value
+            ),
+            Alert::NeighborLost { router, neighbor } => write!(
+                f,
+                "ALERT: neighbor lost: router {} no longer lists {}",
+                router, neighbor
+            ),
+        }
+    }
+}
+
+// router that sent ospf packets
+pub struct Router {
+    pub source: IpAddr,
+    pub hello: Option<HelloParameters>,
+    pub priority: u8,
+    pub designated_router: Ipv4Addr,
+    pub backup_designated_router: Ipv4Addr,
+    pub neighbors: Vec<Ipv4Addr>,
+    pub mtu: Option<u16>,
+    pub packets: u64,
+    pub last_seen: Instant,
+}
+
+// routers are identified by their ospf version and router id
+pub type RouterKey = (u8, Ipv4Addr);
+
+// table of the routers that sent ospf packets that detects adjacency
+// problems
+#[derive(Default)]
+pub struct RouterTable {
+    routers: BTreeMap<RouterKey, Router>,
+}
+
+impl RouterTable {
+    // add the ospf packet to the table and get the alerts it caused
+    pub fn add(&mut self, packet: &OspfPacket) -> Vec<Alert> {
+        let key = (packet.version, packet.router_id);
+        let now = Instant::now();
+        let router = self.routers.entry(key).or_insert(Router {
+            source: packet.source,
+            hello: None,
+            priority: 0,
+            designated_router: Ipv4Addr::UNSPECIFIED,
+            backup_designated_router: Ipv4Addr::UNSPECIFIED,
+            neighbors: Vec::new(),
+            mtu: None,
+            packets: 0,
+            last_seen: now,
+        });
+        router.source = packet.source;
+        router.packets += 1;
+        router.last_seen = now;
+
+        let mut alerts = Vec::new();
+        match &packet.packet_type {
+            PacketType::Hello(hello) => {
+                for neighbor in &router.neighbors {
+                    if !hello.neighbors.contains(neighbor) {
+                        alerts.push(Alert::NeighborLost {
+                            router: packet.router_id,
+                            neighbor: *neighbor,
+                        });
+                    }
+                }
+                router.neighbors = hello.neighbors.clone();
+                router.priority = hello.priority;
+                router.designated_router = hello.designated_router;
+                router.backup_designated_router = hello.backup_designated_router;
+
+                // only check parameters when they change to alert once
+                let parameters = HelloParameters {
+                    area: packet.area,
+                    hello_interval: hello.hello_interval,
+                    dead_interval: hello.dead_interval,
+                    network_mask: hello.network_mask,
+                };
+                if router.hello.as_ref() != Some(&parameters) {
+                    router.hello = Some(parameters.clone());
+                    alerts.extend(self.check_hello(key, &parameters));
+                }
+            }
+            PacketType::DatabaseDescription(dd) if router.mtu != Some(dd.mtu) => {
+                router.mtu = Some(dd.mtu);
+                alerts.extend(self.check_mtu(key, dd.mtu));
+            }
+            _ => (),
+        }
+        alerts
+    }
+
+    // get the routers with the same ospf version as the router with key
+    fn get_others(&self, key: RouterKey) -> impl Iterator<Item = (&RouterKey, &Router)> {
+        self.routers
+            .iter()
+            .filter(move |(k, _)| k.0 == key.0 && k.1 != key.1)
+    }
+
+    // check the hello parameters of the router with key against the ones of
+    // the other routers
+    fn check_hello(&self, key: RouterKey, parameters: &HelloParameters) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        for ((_, other), router) in self.get_others(key) {
+            let other_parameters = match &router.hello {
+                Some(other_parameters) => other_parameters,
+                None => continue,
+            };
+            for (parameter, value, other_value) in parameters.get_mismatches(other_parameters) {
+                alerts.push(Alert::Mismatch {
+                    router: key.1,
+                    other: *other,
+                    parameter,
+                    value,
+                    other_value,
+                });
+            }
+        }
+        alerts
+    }
+
+    // check the mtu of the router with key against the ones of the other
+    // routers
+    fn check_mtu(&self, key: RouterKey, mtu: u16) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        for ((_, other), router) in self.get_others(key) {
+            match router.mtu {
+                Some(other_mtu) if other_mtu != mtu => alerts.push(Alert::Mismatch {
+                    router: key.1,
+                    other: *other,
+                    parameter: "mtu",
+                    value: mtu.to_string(),
+                    other_value: other_mtu.to_string(),
+                }),
+                _ => (),
+            }
+        }
+        alerts
+    }
+
+    // check if the router with key lists neighbor in its hellos and the
+    // neighbor lists the router in its hellos
+    pub fn is_two_way(&self, key: RouterKey, neighbor: Ipv4Addr) -> bool {
+        match self.routers.get(&(key.0, neighbor)) {
+            Some(router) => router.neighbors.contains(&key.1),
+            None => false,
+        }
+    }
+
+    // get the routers sorted by ospf version and router id
+    pub fn get_routers(&self) -> &BTreeMap<RouterKey, Router> {
+        &self.routers
+    }
+}
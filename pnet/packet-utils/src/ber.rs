@@ -0,0 +1,306 @@
+use std::fmt;
+
+use crate::hex::to_hex;
+
+// Tag Class:
+// class of a ber tag in the two most significant bits of the identifier
+// 0               universal
+// 1               application
+// 2               context-specific
+// 3               private
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Class {
+    Universal,
+    Application,
+    Context,
+    Private,
+}
+
+impl From<u8> for Class {
+    fn from(value: u8) -> Self {
+        match value & 0x3 {
+            0 => Class::Universal,
+            1 => Class::Application,
+            2 => Class::Context,
+            _ => Class::Private,
+        }
+    }
+}
+
+// universal tag numbers
+pub const TAG_INTEGER: u32 = 2;
+pub const TAG_OCTET_STRING: u32 = 4;
+pub const TAG_NULL: u32 = 5;
+pub const TAG_OID: u32 = 6;
+pub const TAG_SEQUENCE: u32 = 16;
+
+// tag of a ber encoded value
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Tag {
+    pub class: Class,
+    pub constructed: bool,
+    pub number: u32,
+}
+
+impl Tag {
+    // check if the tag is the universal tag with number
+    pub fn is_universal(&self, number: u32) -> bool {
+        self.class == Class::Universal && self.number == number
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let class = match self.class {
+            Class::Universal => "universal",
+            Class::Application => "application",
+            Class::Context => "context",
+            Class::Private => "private",
+        };
+        write!(f, "[{} {}]", class, self.number)
+    }
+}
+
+// ber encoded tag-length-value triple; the value of constructed types
+// contains further tlvs. only the definite length form is supported, which
+// is the only one allowed in der, snmp and kerberos
+//
+// use methods to read the value
+#[derive(Clone, Copy)]
+pub struct Tlv<'a> {
+    tag: Tag,
+    value: &'a [u8],
+}
+
+impl<'a> Tlv<'a> {
+    // parse the tlv at the start of bytes and get it with the rest of the
+    // bytes after it
+    pub fn parse(bytes: &'a [u8]) -> Option<(Tlv<'a>, &'a [u8])> {
+        // identifier octets; tag numbers of 31 and higher follow the first
+        // octet in base 128 with the most significant bit as continuation
+        let first = *bytes.first()?;
+        let mut offset = 1;
+        let mut number = u32::from(first & 0x1f);
+        if number == 0x1f {
+            number = 0;
+            loop {
+                let byte = *bytes.get(offset)?;
+                offset += 1;
+                number = number.checked_mul(128)? | u32::from(byte & 0x7f);
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+        }
+        let tag = Tag {
+            class: Class::from(first >> 6),
+            constructed: first & 0x20 != 0,
+            number,
+        };
+
+        // length octets in short form or long form with the number of
+        // length octets in the first octet
+        let first = *bytes.get(offset)?;
+        offset += 1;
+        let length = match first {
+            0x00..=0x7f => usize::from(first),
+            0x80 => return None,
+            _ => {
+                let count = usize::from(first & 0x7f);
+                if count > std::mem::size_of::<usize>() {
+                    return None;
+                }
+                let mut length = 0;
+                for byte in bytes.get(offset..offset + count)? {
+                    length = (length << 8) | usize::from(*byte);
+                }
+                offset += count;
+                length
+            }
+        };
+        let end = offset.checked_add(length)?;
+        let value = bytes.get(offset..end)?;
+        Some((Tlv { tag, value }, &bytes[end..]))
+    }
+
+    // parse bytes that must contain exactly one tlv
+    pub fn parse_all(bytes: &'a [u8]) -> Option<Tlv<'a>> {
+        match Tlv::parse(bytes)? {
+            (tlv, []) => Some(tlv),
+            _ => None,
+        }
+    }
+
+    // get the tag
+    pub fn get_tag(&self) -> Tag {
+        self.tag
+    }
+
+    // get the raw value
+    pub fn get_value(&self) -> &'a [u8] {
+        self.value
+    }
+
+    // get the tlvs inside the value of a constructed type
+    pub fn get_children(&self) -> Option<Vec<Tlv<'a>>> {
+        if !self.tag.constructed {
+            return None;
+        }
+        let mut children = Vec::new();
+        let mut rest = self.value;
+        while !rest.is_empty() {
+            let (child, next) = Tlv::parse(rest)?;
+            children.push(child);
+            rest = next;
+        }
+        Some(children)
+    }
+
+    // get the value as signed integer; integers longer than 64 bits are
+    // not supported
+    pub fn get_integer(&self) -> Option<i64> {
+        if self.value.is_empty() || self.value.len() > 8 {
+            return None;
+        }
+        let sign = if self.value[0] & 0x80 != 0 { -1 } else { 0 };
+        Some(
+            self.value
+                .iter()
+                .fold(sign, |value, byte| (value << 8) | i64::from(*byte)),
+        )
+    }
+
+    // get the value as text, e.g., of a general string or kerberos time;
+    // invalid utf8 is replaced
+    pub fn get_string(&self) -> String {
+        String::from_utf8_lossy(self.value).into_owned()
+    }
+
+    // get the first 32 bits of the value of a bit string, e.g., of kerberos
+    // flags; the first octet contains the number of unused bits
+    pub fn get_bits(&self) -> Option<u32> {
+        let bits = self.value.get(1..)?;
+        Some(
+            (0..4)
+                .map(|i| bits.get(i).copied().unwrap_or(0))
+                .fold(0, |value, byte| (value << 8) | u32::from(byte)),
+        )
+    }
+
+    // get the explicitly tagged field with the context-specific tag number
+    // in the children of a constructed type, i.e., the tlv inside it
+    pub fn get_field(&self, number: u32) -> Option<Tlv<'a>> {
+        self.get_children()?
+            .into_iter()
+            .find(|c| c.tag.class == Class::Context && c.tag.number == number)
+            .and_then(|c| Tlv::parse_all(c.value))
+    }
+
+    // get the value as unsigned integer, e.g., of a counter; unsigned
+    // values may have a leading zero octet
+    pub fn get_unsigned(&self) -> Option<u64> {
+        let value = match self.value {
+            [0, rest @ ..] => rest,
+            value => value,
+        };
+        if self.value.is_empty() || value.len() > 8 {
+            return None;
+        }
+        Some(
+            value
+                .iter()
+                .fold(0, |value, byte| (value << 8) | u64::from(*byte)),
+        )
+    }
+
+    // get the value as object identifier in dotted notation; the first
+    // octet encodes the first two arcs
+    pub fn get_oid(&self) -> Option<String> {
+        let mut arcs = Vec::new();
+        let mut arc: u64 = 0;
+        for byte in self.value {
+            arc = arc.checked_mul(128)? | u64::from(byte & 0x7f);
+            if byte & 0x80 != 0 {
+                continue;
+            }
+            if arcs.is_empty() {
+                let first = (arc / 40).min(2);
+                arcs.push(first);
+                arcs.push(arc - first * 40);
+            } else {
+                arcs.push(arc);
+            }
+            arc = 0;
+        }
+        if arcs.is_empty() || self.value.last()? & 0x80 != 0 {
+            return None;
+        }
+        Some(
+            arcs.iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>()
+                .join("."),
+        )
+    }
+}
+
+// get the octets as quoted text if they are printable, as hex otherwise
+pub fn format_octets(octets: &[u8]) -> String {
+    match std::str::from_utf8(octets) {
+        Ok(text)
+            if !text
+                .chars()
+                .any(|c| c.is_control() && c != '\n' && c != '\r' && c != '\t') =>
+        {
+            format!("{:?}", text)
+        }
+        _ => format!("0x{}", to_hex(octets)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_sequence_with_long_form_length() {
+        let mut bytes = vec![0x30, 0x81, 0x83, 0x02, 0x01, 0xff, 0x04, 0x7e];
+        bytes.extend([b'a'; 0x7e]);
+        let tlv = Tlv::parse_all(&bytes).unwrap();
+        assert!(tlv.get_tag().is_universal(TAG_SEQUENCE));
+        let children = tlv.get_children().unwrap();
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0].get_integer(), Some(-1));
+        assert_eq!(children[1].get_value().len(), 0x7e);
+    }
+
+    #[test]
+    fn parse_high_tag_number_and_truncated() {
+        let (tlv, rest) = Tlv::parse(&[0x7f, 0x81, 0x00, 0x01, 0x00, 0xaa]).unwrap();
+        assert_eq!(tlv.get_tag().class, Class::Application);
+        assert_eq!(tlv.get_tag().number, 128);
+        assert_eq!(rest, [0xaa]);
+        assert!(Tlv::parse(&[0x04, 0x05, 0x00]).is_none());
+        assert!(Tlv::parse(&[0x30, 0x80]).is_none());
+    }
+
+    #[test]
+    fn get_values() {
+        let tlv = Tlv::parse_all(&[0x02, 0x03, 0x00, 0xff, 0xff]).unwrap();
+        assert_eq!(tlv.get_unsigned(), Some(0xffff));
+        let tlv = Tlv::parse_all(&[0x06, 0x06, 0x2b, 0x06, 0x01, 0x02, 0x81, 0x01]).unwrap();
+        assert_eq!(tlv.get_oid().as_deref(), Some("1.3.6.1.2.129"));
+        let tlv = Tlv::parse_all(&[0x03, 0x03, 0x00, 0x40, 0x81]).unwrap();
+        assert_eq!(tlv.get_bits(), Some(0x4081_0000));
+        assert_eq!(format_octets(b"public"), "\"public\"");
+        assert_eq!(format_octets(&[0, 1]), "0x0001");
+    }
+
+    #[test]
+    fn get_field_of_explicit_tag() {
+        let bytes = [0x30, 0x05, 0xa1, 0x03, 0x02, 0x01, 0x05];
+        let tlv = Tlv::parse_all(&bytes).unwrap();
+        assert_eq!(tlv.get_field(1).and_then(|f| f.get_integer()), Some(5));
+        assert!(tlv.get_field(0).is_none());
+    }
+}
@@ -0,0 +1,63 @@
+use std::convert::TryInto;
+
+// convert a 16 bit field from big endian to native byte order
+pub fn read_be_u16(bytes: &[u8]) -> u16 {
+    u16::from_be_bytes(bytes.try_into().expect("slice with incorrect length"))
+}
+
+// convert a 32 bit field from big endian to native byte order
+pub fn read_be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes(bytes.try_into().expect("slice with incorrect length"))
+}
+
+// convert a 64 bit field from big endian to native byte order
+pub fn read_be_u64(bytes: &[u8]) -> u64 {
+    u64::from_be_bytes(bytes.try_into().expect("slice with incorrect length"))
+}
+
+// convert a 128 bit field from big endian to native byte order
+pub fn read_be_u128(bytes: &[u8]) -> u128 {
+    u128::from_be_bytes(bytes.try_into().expect("slice with incorrect length"))
+}
+
+// get the 16 bit field in big endian byte order at offset in bytes if bytes
+// is long enough
+pub fn read_be_u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    bytes.get(offset..offset.checked_add(2)?).map(read_be_u16)
+}
+
+// get the 32 bit field in big endian byte order at offset in bytes if bytes
+// is long enough
+pub fn read_be_u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    bytes.get(offset..offset.checked_add(4)?).map(read_be_u32)
+}
+
+// write value as 16 bit field in big endian byte order to bytes
+pub fn write_be_u16(bytes: &mut [u8], value: u16) {
+    bytes.copy_from_slice(&value.to_be_bytes());
+}
+
+// write value as 32 bit field in big endian byte order to bytes
+pub fn write_be_u32(bytes: &mut [u8], value: u32) {
+    bytes.copy_from_slice(&value.to_be_bytes());
+}
+
+// write value as 64 bit field in big endian byte order to bytes
+pub fn write_be_u64(bytes: &mut [u8], value: u64) {
+    bytes.copy_from_slice(&value.to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_at_offset() {
+        let bytes = [0x12, 0x34, 0x56, 0x78, 0x9a];
+        assert_eq!(read_be_u16_at(&bytes, 3), Some(0x789a));
+        assert_eq!(read_be_u16_at(&bytes, 4), None);
+        assert_eq!(read_be_u32_at(&bytes, 1), Some(0x3456_789a));
+        assert_eq!(read_be_u32_at(&bytes, 2), None);
+        assert_eq!(read_be_u32_at(&bytes, usize::MAX), None);
+    }
+}
@@ -0,0 +1,28 @@
+use pnet::datalink::{Channel, DataLinkReceiver, DataLinkSender, NetworkInterface};
+
+use std::io;
+use std::time::Duration;
+
+// size of the receive buffer; large enough for an ethernet header and the
+// largest ip packet, since segmentation offloading can hand over packets
+// larger than the mtu
+const READ_BUFFER_SIZE: usize = 14 + 65535;
+
+// create the ethernet datalink channel on interface with read timeout and
+// promiscuous mode
+pub fn create_channel(
+    interface: &NetworkInterface,
+    read_timeout: Duration,
+    promiscuous: bool,
+) -> io::Result<(Box<dyn DataLinkSender>, Box<dyn DataLinkReceiver>)> {
+    let channel_config = pnet::datalink::Config {
+        read_timeout: Some(read_timeout),
+        read_buffer_size: READ_BUFFER_SIZE,
+        promiscuous,
+        ..Default::default()
+    };
+    match pnet::datalink::channel(interface, channel_config)? {
+        Channel::Ethernet(tx, rx) => Ok((tx, rx)),
+        _ => Err(io::Error::other("unknown channel type")),
+    }
+}
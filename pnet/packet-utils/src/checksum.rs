@@ -0,0 +1,27 @@
+use crate::bytes::*;
+
+// offsets of the checksum fields in the icmp, icmpv6 and ipv4 headers
+pub const ICMP_CHECKSUM_OFFSET: usize = 2;
+pub const IPV4_CHECKSUM_OFFSET: usize = 10;
+
+// get the internet checksum of bytes without the 16 bit checksum field at
+// offset
+pub fn get_checksum(bytes: &[u8], offset: usize) -> u16 {
+    pnet::util::checksum(bytes, offset / 2)
+}
+
+// calculate the internet checksum of bytes and write it to the 16 bit
+// checksum field at offset
+pub fn set_checksum(bytes: &mut [u8], offset: usize) {
+    let checksum = get_checksum(bytes, offset);
+    write_be_u16(&mut bytes[offset..offset + 2], checksum);
+}
+
+// check if the 16 bit checksum field at offset contains the internet
+// checksum of bytes
+pub fn is_checksum_valid(bytes: &[u8], offset: usize) -> bool {
+    match bytes.get(offset..offset + 2) {
+        Some(checksum) => get_checksum(bytes, offset) == read_be_u16(checksum),
+        None => false,
+    }
+}
@@ -0,0 +1,477 @@
+use pnet::datalink::MacAddr;
+use pnet::packet::ethernet::{EtherType, EtherTypes, EthernetPacket};
+use pnet::packet::icmp::IcmpPacket;
+use pnet::packet::icmpv6::Icmpv6Packet;
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::tcp::TcpPacket;
+use pnet::packet::udp::UdpPacket;
+use pnet::packet::vlan::VlanPacket;
+use pnet::packet::Packet;
+
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+
+// maximum number of nested tunnels that are decoded in a packet
+const MAX_DEPTH: usize = 8;
+
+// maximum number of vlan tags that are decoded in a frame
+const MAX_VLAN_TAGS: usize = 2;
+
+// ip packet in an ethernet frame with its addresses, transport protocol and
+// payload
+pub struct IpPayload<'a> {
+    pub source: IpAddr,
+    pub destination: IpAddr,
+    pub protocol: IpNextHeaderProtocol,
+    pub payload: &'a [u8],
+}
+
+// get the ip packet in the ethernet frame unless its ip version is excluded
+// by only_ipv4 or only_ipv6; fragmented ipv4 packets are ignored, the
+// protocol of ipv6 packets with extension headers is the first extension
+// header
+pub fn parse_ip_frame(frame: &[u8], only_ipv4: bool, only_ipv6: bool) -> Option<IpPayload<'_>> {
+    let ethernet_packet = EthernetPacket::new(frame)?;
+    let ip = &frame[EthernetPacket::minimum_packet_size()..];
+    match ethernet_packet.get_ethertype() {
+        EtherTypes::Ipv4 if !only_ipv6 => {
+            let ipv4_packet = Ipv4Packet::new(ip)?;
+            if ipv4_packet.get_fragment_offset() != 0 || ipv4_packet.get_flags() & 0x1 != 0 {
+                return None;
+            }
+            let header_length = usize::from(ipv4_packet.get_header_length()) * 4;
+            let total_length = usize::from(ipv4_packet.get_total_length());
+            Some(IpPayload {
+                source: IpAddr::V4(ipv4_packet.get_source()),
+                destination: IpAddr::V4(ipv4_packet.get_destination()),
+                protocol: ipv4_packet.get_next_level_protocol(),
+                payload: ip.get(header_length..total_length)?,
+            })
+        }
+        EtherTypes::Ipv6 if !only_ipv4 => {
+            let ipv6_packet = Ipv6Packet::new(ip)?;
+            let header_length = Ipv6Packet::minimum_packet_size();
+            let payload_length = usize::from(ipv6_packet.get_payload_length());
+            Some(IpPayload {
+                source: IpAddr::V6(ipv6_packet.get_source()),
+                destination: IpAddr::V6(ipv6_packet.get_destination()),
+                protocol: ipv6_packet.get_next_header(),
+                payload: ip.get(header_length..header_length + payload_length)?,
+            })
+        }
+        _ => None,
+    }
+}
+
+// udp packet with its source and destination addresses and ports
+pub struct Datagram<'a> {
+    pub source: SocketAddr,
+    pub destination: SocketAddr,
+    pub payload: &'a [u8],
+}
+
+// get the udp packet in the ip packet if it is from or to one of the ports;
+// all udp packets are returned if ports is empty
+pub fn parse_udp<'a>(ip: &IpPayload<'a>, ports: &[u16]) -> Option<Datagram<'a>> {
+    if ip.protocol != IpNextHeaderProtocols::Udp {
+        return None;
+    }
+    let udp_packet = UdpPacket::new(ip.payload)?;
+    let (source, destination) = (udp_packet.get_source(), udp_packet.get_destination());
+    if !ports.is_empty() && !ports.contains(&source) && !ports.contains(&destination) {
+        return None;
+    }
+    let udp_length = usize::from(udp_packet.get_length());
+    Some(Datagram {
+        source: SocketAddr::new(ip.source, source),
+        destination: SocketAddr::new(ip.destination, destination),
+        payload: ip
+            .payload
+            .get(UdpPacket::minimum_packet_size()..udp_length)?,
+    })
+}
+
+// decoded protocol layer of a packet with the decoded udp payloads of type T
+pub enum Layer<T> {
+    Ethernet {
+        source: MacAddr,
+        destination: MacAddr,
+    },
+    Vlan {
+        id: u16,
+    },
+    Ip {
+        source: IpAddr,
+        destination: IpAddr,
+    },
+    Udp {
+        source: u16,
+        destination: u16,
+    },
+    Tcp {
+        source: u16,
+        destination: u16,
+        flags: u16,
+    },
+    Icmp {
+        icmp_type: u8,
+        code: u8,
+    },
+    Application(T),
+
+    // undecoded rest of the packet with its protocol and length; the length
+    // of truncated packets is the captured length
+    Payload {
+        protocol: String,
+        length: usize,
+    },
+}
+
+// format the tcp flags
+fn format_tcp_flags(flags: u16) -> String {
+    const NAMES: [&str; 8] = ["fin", "syn", "rst", "psh", "ack", "urg", "ece", "cwr"];
+    let names = NAMES
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| flags & 1 << i != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>();
+    names.join(",")
+}
+
+impl<T: fmt::Display> fmt::Display for Layer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Layer::Ethernet {
+                source,
+                destination,
+            } => write!(f, "ethernet {} -> {}", source, destination),
+            Layer::Vlan { id } => write!(f, "vlan {}", id),
+            Layer::Ip {
+                source,
+                destination,
+            } => write!(f, "ip {} -> {}", source, destination),
+            Layer::Udp {
+                source,
+                destination,
+            } => write!(f, "udp {} -> {}", source, destination),
+            Layer::Tcp {
+                source,
+                destination,
+                flags,
+            } => write!(
+                f,
+                "tcp {} -> {} [{}]",
+                source,
+                destination,
+                format_tcp_flags(*flags)
+            ),
+            Layer::Icmp { icmp_type, code } => {
+                write!(f, "icmp type {} code {}", icmp_type, code)
+            }
+            Layer::Application(application) => write!(f, "{}", application),
+            Layer::Payload { protocol, length } => {
+                write!(f, "{} payload ({} bytes)", protocol, length)
+            }
+        }
+    }
+}
+
+// format the layers of a packet
+pub fn format_layers<T: fmt::Display>(layers: &[Layer<T>]) -> String {
+    let layers = layers.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+    layers.join(" | ")
+}
+
+// packet inside a tunnel: an ethernet frame or a packet with its ethertype
+pub enum Inner<'a> {
+    Ethernet(&'a [u8]),
+    Packet(EtherType, &'a [u8]),
+}
+
+// udp payload decoded by the udp decoder of a dissector
+pub enum UdpPayload<'a, T> {
+    // application header or message
+    Application(T),
+
+    // tunnel header and the packet inside the tunnel that is dissected
+    // further
+    Tunnel(T, Inner<'a>),
+
+    // payload of protocol that could not be decoded
+    Undecoded(String),
+}
+
+// decoder of the udp payloads with source and destination port; returns
+// none for payloads of unknown protocols
+pub type UdpDecoder<'a, T> = dyn for<'p> Fn(u16, u16, &'p [u8]) -> Option<UdpPayload<'p, T>> + 'a;
+
+// dissector that decodes packets into their layers and udp payloads with
+// its udp decoder
+pub struct Dissector<'a, T> {
+    decode_udp: &'a UdpDecoder<'a, T>,
+}
+
+impl<'a, T> Dissector<'a, T> {
+    pub fn new(decode_udp: &'a UdpDecoder<'a, T>) -> Dissector<'a, T> {
+        Dissector { decode_udp }
+    }
+
+    // decode the ethernet frame into its layers
+    pub fn dissect(&self, frame: &[u8]) -> Vec<Layer<T>> {
+        let mut layers = Vec::new();
+        self.dissect_ethernet(frame, 0, &mut layers);
+        layers
+    }
+
+    // decode the packet with ethertype into its layers
+    pub fn dissect_packet(&self, ethertype: EtherType, packet: &[u8]) -> Vec<Layer<T>> {
+        let mut layers = Vec::new();
+        self.dissect_ip(ethertype, packet, 0, &mut layers);
+        layers
+    }
+
+    // decode the ethernet frame in bytes with its vlan tags
+    fn dissect_ethernet(&self, bytes: &[u8], depth: usize, layers: &mut Vec<Layer<T>>) {
+        let ethernet_packet = match EthernetPacket::new(bytes) {
+            Some(ethernet_packet) => ethernet_packet,
+            None => return,
+        };
+        layers.push(Layer::Ethernet {
+            source: ethernet_packet.get_source(),
+            destination: ethernet_packet.get_destination(),
+        });
+        let mut ethertype = ethernet_packet.get_ethertype();
+        let mut payload = ethernet_packet.payload();
+        for _ in 0..MAX_VLAN_TAGS {
+            if ethertype != EtherTypes::Vlan && ethertype != EtherTypes::QinQ {
+                break;
+            }
+            let vlan_packet = match VlanPacket::new(payload) {
+                Some(vlan_packet) => vlan_packet,
+                None => return,
+            };
+            layers.push(Layer::Vlan {
+                id: vlan_packet.get_vlan_identifier(),
+            });
+            ethertype = vlan_packet.get_ethertype();
+            payload = &payload[VlanPacket::minimum_packet_size()..];
+        }
+        self.dissect_ip(ethertype, payload, depth, layers);
+    }
+
+    // decode the packet with ethertype in bytes; the packet can be
+    // truncated, so lengths in the headers are only used if the bytes
+    // contain them; fragments and ipv6 extension headers are not decoded
+    fn dissect_ip(
+        &self,
+        ethertype: EtherType,
+        bytes: &[u8],
+        depth: usize,
+        layers: &mut Vec<Layer<T>>,
+    ) {
+        match ethertype {
+            EtherTypes::Ipv4 => {
+                let ipv4_packet = match Ipv4Packet::new(bytes) {
+                    Some(ipv4_packet) => ipv4_packet,
+                    None => return,
+                };
+                layers.push(Layer::Ip {
+                    source: IpAddr::V4(ipv4_packet.get_source()),
+                    destination: IpAddr::V4(ipv4_packet.get_destination()),
+                });
+                let header_length = usize::from(ipv4_packet.get_header_length()) * 4;
+                let total_length = usize::from(ipv4_packet.get_total_length()).min(bytes.len());
+                let payload = bytes.get(header_length..total_length).unwrap_or_default();
+                if ipv4_packet.get_fragment_offset() != 0 || ipv4_packet.get_flags() & 0x1 != 0 {
+                    layers.push(Layer::Payload {
+                        protocol: "fragment".to_string(),
+                        length: payload.len(),
+                    });
+                    return;
+                }
+                let protocol = ipv4_packet.get_next_level_protocol();
+                self.dissect_transport(protocol, payload, depth, layers);
+            }
+            EtherTypes::Ipv6 => {
+                let ipv6_packet = match Ipv6Packet::new(bytes) {
+                    Some(ipv6_packet) => ipv6_packet,
+                    None => return,
+                };
+                layers.push(Layer::Ip {
+                    source: IpAddr::V6(ipv6_packet.get_source()),
+                    destination: IpAddr::V6(ipv6_packet.get_destination()),
+                });
+                let payload = &bytes[Ipv6Packet::minimum_packet_size()..];
+                let length = usize::from(ipv6_packet.get_payload_length()).min(payload.len());
+                let protocol = ipv6_packet.get_next_header();
+                self.dissect_transport(protocol, &payload[..length], depth, layers);
+            }
+            ethertype => layers.push(Layer::Payload {
+                protocol: format!("{}", ethertype).to_lowercase(),
+                length: bytes.len(),
+            }),
+        }
+    }
+
+    // decode the transport protocol packet in bytes
+    fn dissect_transport(
+        &self,
+        protocol: IpNextHeaderProtocol,
+        bytes: &[u8],
+        depth: usize,
+        layers: &mut Vec<Layer<T>>,
+    ) {
+        match protocol {
+            IpNextHeaderProtocols::Udp => {
+                let udp_packet = match UdpPacket::new(bytes) {
+                    Some(udp_packet) => udp_packet,
+                    None => return,
+                };
+                let source = udp_packet.get_source();
+                let destination = udp_packet.get_destination();
+                layers.push(Layer::Udp {
+                    source,
+                    destination,
+                });
+                let payload = &bytes[UdpPacket::minimum_packet_size()..];
+                self.dissect_udp_payload(source, destination, payload, depth, layers);
+            }
+            IpNextHeaderProtocols::Tcp => {
+                if let Some(tcp_packet) = TcpPacket::new(bytes) {
+                    layers.push(Layer::Tcp {
+                        source: tcp_packet.get_source(),
+                        destination: tcp_packet.get_destination(),
+                        flags: tcp_packet.get_flags(),
+                    });
+                }
+            }
+            IpNextHeaderProtocols::Icmp => {
+                if let Some(icmp_packet) = IcmpPacket::new(bytes) {
+                    layers.push(Layer::Icmp {
+                        icmp_type: icmp_packet.get_icmp_type().0,
+                        code: icmp_packet.get_icmp_code().0,
+                    });
+                }
+            }
+            IpNextHeaderProtocols::Icmpv6 => {
+                if let Some(icmpv6_packet) = Icmpv6Packet::new(bytes) {
+                    layers.push(Layer::Icmp {
+                        icmp_type: icmpv6_packet.get_icmpv6_type().0,
+                        code: icmpv6_packet.get_icmpv6_code().0,
+                    });
+                }
+            }
+            protocol => layers.push(Layer::Payload {
+                protocol: format!("{}", protocol).to_lowercase(),
+                length: bytes.len(),
+            }),
+        }
+    }
+
+    // decode the udp payload in bytes from source to destination port with
+    // the udp decoder and dissect the inner packets of tunnels
+    fn dissect_udp_payload(
+        &self,
+        source: u16,
+        destination: u16,
+        bytes: &[u8],
+        depth: usize,
+        layers: &mut Vec<Layer<T>>,
+    ) {
+        let payload = match depth < MAX_DEPTH {
+            true => (self.decode_udp)(source, destination, bytes),
+            false => None,
+        };
+        match payload {
+            Some(UdpPayload::Application(application)) => {
+                layers.push(Layer::Application(application))
+            }
+            Some(UdpPayload::Tunnel(header, inner)) => {
+                layers.push(Layer::Application(header));
+                match inner {
+                    Inner::Ethernet(frame) => self.dissect_ethernet(frame, depth + 1, layers),
+                    Inner::Packet(ethertype, packet) => {
+                        self.dissect_ip(ethertype, packet, depth + 1, layers)
+                    }
+                }
+            }
+            Some(UdpPayload::Undecoded(protocol)) => layers.push(Layer::Payload {
+                protocol,
+                length: bytes.len(),
+            }),
+            None => layers.push(Layer::Payload {
+                protocol: "udp".to_string(),
+                length: bytes.len(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pnet::packet::ethernet::MutableEthernetPacket;
+    use pnet::packet::ipv4::MutableIpv4Packet;
+
+    // get an ethernet frame with an ipv4 packet with flags, fragment offset
+    // and payload
+    fn ipv4_frame(flags: u8, offset: u16, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0; 14 + 20 + payload.len() + 4];
+        let mut ethernet_packet = MutableEthernetPacket::new(&mut frame).unwrap();
+        ethernet_packet.set_ethertype(EtherTypes::Ipv4);
+        let mut ipv4_packet = MutableIpv4Packet::new(&mut frame[14..]).unwrap();
+        ipv4_packet.set_version(4);
+        ipv4_packet.set_header_length(5);
+        ipv4_packet.set_total_length((20 + payload.len()) as u16);
+        ipv4_packet.set_flags(flags);
+        ipv4_packet.set_fragment_offset(offset);
+        ipv4_packet.set_next_level_protocol(IpNextHeaderProtocols::Udp);
+        ipv4_packet.set_source([192, 0, 2, 1].into());
+        ipv4_packet.set_destination([192, 0, 2, 2].into());
+        ipv4_packet.set_payload(payload);
+        frame
+    }
+
+    #[test]
+    fn parse_ip_frame_without_padding() {
+        let frame = ipv4_frame(0, 0, b"data");
+        let ip = parse_ip_frame(&frame, false, false).unwrap();
+        assert_eq!(ip.source, IpAddr::from([192, 0, 2, 1]));
+        assert_eq!(ip.destination, IpAddr::from([192, 0, 2, 2]));
+        assert_eq!(ip.protocol, IpNextHeaderProtocols::Udp);
+        assert_eq!(ip.payload, b"data");
+        assert!(parse_ip_frame(&frame, false, true).is_none());
+    }
+
+    #[test]
+    fn parse_ip_frame_ignores_fragments() {
+        assert!(parse_ip_frame(&ipv4_frame(0x1, 0, b"data"), false, false).is_none());
+        assert!(parse_ip_frame(&ipv4_frame(0, 8, b"data"), false, false).is_none());
+    }
+
+    #[test]
+    fn parse_udp_with_ports() {
+        let udp = [
+            0x04, 0xd2, 0x00, 0x35, 0x00, 0x0c, 0x00, 0x00, b'd', b'a', b't', b'a',
+        ];
+        let frame = ipv4_frame(0, 0, &udp);
+        let ip = parse_ip_frame(&frame, false, false).unwrap();
+
+        let datagram = parse_udp(&ip, &[53]).unwrap();
+        assert_eq!(datagram.source, "192.0.2.1:1234".parse().unwrap());
+        assert_eq!(datagram.destination, "192.0.2.2:53".parse().unwrap());
+        assert_eq!(datagram.payload, b"data");
+        assert!(parse_udp(&ip, &[]).is_some());
+        assert!(parse_udp(&ip, &[123]).is_none());
+
+        // udp length beyond the ip payload
+        let mut udp = udp;
+        udp[5] = 0x0d;
+        let frame = ipv4_frame(0, 0, &udp);
+        let ip = parse_ip_frame(&frame, false, false).unwrap();
+        assert!(parse_udp(&ip, &[]).is_none());
+    }
+}
@@ -0,0 +1,52 @@
+// number of bytes in a line of a hexdump
+const HEXDUMP_WIDTH: usize = 16;
+
+// get bytes as hex string
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// get bytes as hex string with bytes separated by colons, e.g., of
+// hardware addresses
+pub fn format_hex(bytes: &[u8]) -> String {
+    let hex: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    hex.join(":")
+}
+
+// parse the hex string s, e.g., "c0ffee", into bytes
+pub fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) || !s.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
+// get bytes as hexdump with offsets, hex bytes and printable characters,
+// e.g., "0000  68 65 6c 6c 6f  |hello|"
+pub fn hexdump(bytes: &[u8]) -> String {
+    let lines: Vec<String> = bytes
+        .chunks(HEXDUMP_WIDTH)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            let text: String = chunk
+                .iter()
+                .map(|b| match b.is_ascii_graphic() || *b == b' ' {
+                    true => *b as char,
+                    false => '.',
+                })
+                .collect();
+            format!(
+                "{:04x}  {:<width$}  |{}|",
+                i * HEXDUMP_WIDTH,
+                hex.join(" "),
+                text,
+                width = HEXDUMP_WIDTH * 3 - 1
+            )
+        })
+        .collect();
+    lines.join("\n")
+}
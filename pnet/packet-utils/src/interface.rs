@@ -0,0 +1,166 @@
+use pnet::datalink::NetworkInterface;
+use pnet::ipnetwork::{IpNetwork, Ipv4Network};
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, ToSocketAddrs};
+
+// mtu of ethernet interfaces
+const ETHERNET_MTU: usize = 1500;
+
+// get the first interface that is up, is not a loopback and has an ip
+// address
+pub fn get_default_interface() -> Option<NetworkInterface> {
+    pnet::datalink::interfaces()
+        .into_iter()
+        .find(|e| e.is_up() && !e.is_loopback() && !e.ips.is_empty())
+}
+
+// get the interface with name or ip address interface; if it is an ip
+// address, the interface is restricted to it as its only address of the
+// same ip version, so it is used as source address
+pub fn get_interface(interface: &str) -> Option<NetworkInterface> {
+    let interfaces = pnet::datalink::interfaces();
+    let address = match interface.parse::<IpAddr>() {
+        Ok(address) => address,
+        Err(_) => return interfaces.into_iter().find(|e| e.name == interface),
+    };
+    let mut interface = interfaces
+        .into_iter()
+        .find(|e| e.ips.iter().any(|ip| ip.ip() == address))?;
+    interface
+        .ips
+        .retain(|ip| ip.ip() == address || ip.is_ipv4() != address.is_ipv4());
+    Some(interface)
+}
+
+// get the interface with name or ip address if it is set, the default
+// interface otherwise
+pub fn find_interface(interface: Option<&str>) -> Option<NetworkInterface> {
+    match interface {
+        Some(interface) => get_interface(interface),
+        None => get_default_interface(),
+    }
+}
+
+// get the first ipv4 address of interface
+pub fn get_interface_ip(interface: &NetworkInterface) -> Option<Ipv4Addr> {
+    interface.ips.iter().find_map(|ip| match ip.ip() {
+        IpAddr::V4(ip) => Some(ip),
+        _ => None,
+    })
+}
+
+// get interface ipv4 network
+pub fn get_interface_network(interface: &NetworkInterface) -> Option<Ipv4Network> {
+    interface.ips.iter().find_map(|ip| match ip {
+        IpNetwork::V4(network) => Some(*network),
+        _ => None,
+    })
+}
+
+// get the default gateway of interface from the routing table of the kernel
+fn get_default_gateway(interface: &NetworkInterface) -> Option<Ipv4Addr> {
+    // fields: Iface Destination Gateway Flags ..., addresses are hex
+    // numbers in host byte order
+    let routes = std::fs::read_to_string("/proc/net/route").ok()?;
+    routes.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields[..] {
+            [iface, "00000000", gateway, ..] if iface == interface.name => {
+                let gateway = u32::from_str_radix(gateway, 16).ok()?;
+                Some(Ipv4Addr::from(gateway.to_ne_bytes()))
+            }
+            _ => None,
+        }
+    })
+}
+
+// check if destination is a broadcast address on interface
+pub fn is_broadcast(interface: &NetworkInterface, destination: Ipv4Addr) -> bool {
+    destination == Ipv4Addr::BROADCAST
+        || get_interface_network(interface)
+            .is_some_and(|network| network.prefix() < 31 && network.broadcast() == destination)
+}
+
+// get the next hop of packets to destination on interface: the destination
+// itself if it is in the network of the interface, the default gateway
+// otherwise
+pub fn get_next_hop(interface: &NetworkInterface, destination: Ipv4Addr) -> Option<Ipv4Addr> {
+    match get_interface_network(interface) {
+        Some(network) if network.contains(destination) => Some(destination),
+        _ => get_default_gateway(interface),
+    }
+}
+
+// check if ip is an ipv6 link-local unicast address
+fn is_link_local(ip: Ipv6Addr) -> bool {
+    ip.segments()[0] & 0xffc0 == 0xfe80
+}
+
+// get interface ipv6 address used for packets to destination: the
+// link-local address for link-local destinations, a global address otherwise
+pub fn get_interface_ipv6(interface: &NetworkInterface, destination: Ipv6Addr) -> Option<Ipv6Addr> {
+    let mut ips = interface.ips.iter().filter_map(|ip| match ip {
+        IpNetwork::V6(network) => Some(network.ip()),
+        _ => None,
+    });
+    let link_local = is_link_local(destination);
+    ips.clone()
+        .find(|ip| is_link_local(*ip) == link_local)
+        .or_else(|| ips.next())
+}
+
+// get the ipv6 default gateway of interface from the routing table of the
+// kernel
+fn get_default_gateway6(interface: &NetworkInterface) -> Option<Ipv6Addr> {
+    // fields: destination prefix_length source prefix_length next_hop
+    // metric reference_count use flags interface, addresses are hex numbers
+    let routes = std::fs::read_to_string("/proc/net/ipv6_route").ok()?;
+    routes.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields[..] {
+            [destination, "00", _, _, gateway, _, _, _, _, iface]
+                if iface == interface.name && u128::from_str_radix(destination, 16) == Ok(0) =>
+            {
+                let gateway = u128::from_str_radix(gateway, 16).ok()?;
+                Some(Ipv6Addr::from(gateway)).filter(|gateway| !gateway.is_unspecified())
+            }
+            _ => None,
+        }
+    })
+}
+
+// get the next hop of packets to ipv6 destination on interface: the
+// destination itself if it is link-local or in a network of the interface,
+// the default gateway otherwise
+pub fn get_next_hop6(interface: &NetworkInterface, destination: Ipv6Addr) -> Option<Ipv6Addr> {
+    let on_link = interface.ips.iter().any(|ip| match ip {
+        IpNetwork::V6(network) => network.contains(destination),
+        _ => false,
+    });
+    match on_link || is_link_local(destination) {
+        true => Some(destination),
+        false => get_default_gateway6(interface),
+    }
+}
+
+// get the mtu of interface from sysfs; assumes the ethernet mtu if it
+// cannot be read
+pub fn get_interface_mtu(interface: &NetworkInterface) -> usize {
+    std::fs::read_to_string(format!("/sys/class/net/{}/mtu", interface.name))
+        .ok()
+        .and_then(|mtu| mtu.trim().parse().ok())
+        .unwrap_or(ETHERNET_MTU)
+}
+
+// get the ip address of destination, an ip address or a host name; host
+// names are resolved to ipv6 addresses if ipv6 is set, ipv4 otherwise
+pub fn resolve_destination(destination: &str, ipv6: bool) -> Option<IpAddr> {
+    if let Ok(ip) = destination.parse() {
+        return Some(ip);
+    }
+    (destination, 0)
+        .to_socket_addrs()
+        .ok()?
+        .map(|addr| addr.ip())
+        .find(|ip| ip.is_ipv6() == ipv6)
+}
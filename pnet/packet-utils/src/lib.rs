@@ -0,0 +1,15 @@
+// helpers shared by the packet tools: ber decoding, byte readers and
+// writers, internet checksums, packet dissection, hex and json formatting,
+// command line parsers, network interface selection, datalink channels and
+// packet pacing
+
+pub mod ber;
+pub mod bytes;
+pub mod channel;
+pub mod checksum;
+pub mod dissect;
+pub mod hex;
+pub mod interface;
+pub mod json;
+pub mod pacer;
+pub mod parse;
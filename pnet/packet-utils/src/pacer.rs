@@ -0,0 +1,66 @@
+use std::time::{Duration, Instant};
+
+// time of sending at the full rate the token bucket holds by default, so
+// short delays of the sender do not lower the rate
+const BURST_TIME: Duration = Duration::from_millis(10);
+
+// token bucket that paces or limits packets to a rate in packets per second
+pub struct TokenBucket {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    // create a full token bucket for rate packets per second that holds the
+    // tokens for the default burst time
+    pub fn new(rate: f64) -> TokenBucket {
+        TokenBucket::with_burst(rate, rate * BURST_TIME.as_secs_f64())
+    }
+
+    // create a full token bucket for rate packets per second and bursts of
+    // up to burst packets; the bucket holds at least one token
+    pub fn with_burst(rate: f64, burst: f64) -> TokenBucket {
+        let burst = burst.max(1.0);
+        TokenBucket {
+            rate,
+            burst,
+            tokens: burst,
+            last: Instant::now(),
+        }
+    }
+
+    // add the tokens for the time since the last refill
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.last = now;
+    }
+
+    // take a token for a packet; returns false if there is none
+    pub fn try_take(&mut self) -> bool {
+        self.refill();
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+
+    // get the time until the next token is available
+    pub fn get_wait(&self) -> Duration {
+        let missing = 1.0 - self.tokens;
+        if missing <= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(missing / self.rate).saturating_sub(self.last.elapsed())
+    }
+
+    // check if the bucket refilled completely
+    pub fn is_full(&mut self) -> bool {
+        self.refill();
+        self.tokens >= self.burst
+    }
+}
@@ -0,0 +1,40 @@
+use pnet::datalink::MacAddr;
+
+// parse an interval in seconds
+pub fn parse_interval(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(interval) if interval.is_finite() && interval > 0.0 => Ok(interval),
+        _ => Err(format!("invalid interval: {}", s)),
+    }
+}
+
+// parse a rate in packets per second
+pub fn parse_rate(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(rate) if rate.is_finite() && rate > 0.0 => Ok(rate),
+        _ => Err(format!("invalid rate: {}", s)),
+    }
+}
+
+// parse the type of service as decimal or hex number
+pub fn parse_tos(s: &str) -> Result<u8, String> {
+    let tos = match s.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => s.parse::<u8>(),
+    };
+    tos.map_err(|_| format!("invalid type of service: {}", s))
+}
+
+// parse a positive number of seconds
+pub fn parse_seconds(seconds: &str) -> Result<f64, String> {
+    match seconds.parse::<f64>() {
+        Ok(seconds) if seconds > 0.0 && seconds.is_finite() => Ok(seconds),
+        _ => Err(format!("invalid number of seconds: {}", seconds)),
+    }
+}
+
+// parse a mac address
+pub fn parse_mac(s: &str) -> Result<MacAddr, String> {
+    s.parse::<MacAddr>()
+        .map_err(|_| format!("invalid mac address: {}", s))
+}
@@ -0,0 +1,106 @@
+use pnet::datalink::{DataLinkReceiver, DataLinkSender, MacAddr};
+use pnet::packet::arp::{ArpHardwareTypes, ArpOperations, ArpPacket, MutableArpPacket};
+use pnet::packet::ethernet::EtherTypes;
+use pnet::packet::ethernet::{EthernetPacket, MutableEthernetPacket};
+use pnet::packet::{MutablePacket, Packet};
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::{Duration, Instant};
+
+use crate::cache::NeighborCache;
+use crate::vlan::*;
+
+// packet constants
+const ARP_SIZE: usize = MutableArpPacket::minimum_packet_size();
+const PACKET_SIZE: usize = MutableEthernetPacket::minimum_packet_size() + ARP_SIZE;
+
+// number of arp requests sent before giving up and the time to wait for a
+// reply to each of them
+const ARP_RETRIES: usize = 3;
+pub const ARP_TIMEOUT: Duration = Duration::from_secs(1);
+
+// create arp request packet for target ip address
+pub fn create_arp_packet(
+    source_mac: MacAddr,
+    source_ip: Ipv4Addr,
+    target_ip: Ipv4Addr,
+) -> [u8; PACKET_SIZE] {
+    // create arp packet
+    let mut arp_buffer = [0u8; ARP_SIZE];
+    let mut arp_packet = MutableArpPacket::new(&mut arp_buffer).unwrap();
+    arp_packet.set_hardware_type(ArpHardwareTypes::Ethernet);
+    arp_packet.set_protocol_type(EtherTypes::Ipv4);
+    arp_packet.set_hw_addr_len(6);
+    arp_packet.set_proto_addr_len(4);
+    arp_packet.set_operation(ArpOperations::Request);
+    arp_packet.set_sender_hw_addr(source_mac);
+    arp_packet.set_sender_proto_addr(source_ip);
+    arp_packet.set_target_hw_addr(MacAddr::zero());
+    arp_packet.set_target_proto_addr(target_ip);
+
+    // create ethernet packet
+    let mut ethernet_buffer = [0u8; PACKET_SIZE];
+    let mut ethernet_packet = MutableEthernetPacket::new(&mut ethernet_buffer).unwrap();
+    ethernet_packet.set_source(source_mac);
+    ethernet_packet.set_destination(MacAddr::broadcast());
+    ethernet_packet.set_ethertype(EtherTypes::Arp);
+    ethernet_packet.set_payload(arp_packet.packet_mut());
+
+    ethernet_buffer
+}
+
+// parse the ethernet frame and get the ip and mac address of the sender of
+// the arp reply
+pub fn parse_arp_reply(frame: &[u8]) -> Option<(Ipv4Addr, MacAddr)> {
+    let ethernet_packet = EthernetPacket::new(frame)?;
+    if ethernet_packet.get_ethertype() != EtherTypes::Arp {
+        return None;
+    }
+    let arp_packet = ArpPacket::new(ethernet_packet.payload())?;
+    if arp_packet.get_operation() != ArpOperations::Reply {
+        return None;
+    }
+    Some((
+        arp_packet.get_sender_proto_addr(),
+        arp_packet.get_sender_hw_addr(),
+    ))
+}
+
+// resolve the mac address of target ip address with arp requests sent from
+// source mac and ip address with the optional vlan tag; resolved addresses
+// are added to cache
+pub fn resolve(
+    tx: &mut dyn DataLinkSender,
+    rx: &mut dyn DataLinkReceiver,
+    source: (MacAddr, Ipv4Addr),
+    target_ip: Ipv4Addr,
+    cache: &mut NeighborCache,
+    vlan: Option<VlanTag>,
+) -> Option<MacAddr> {
+    if let Some(mac) = cache.get(IpAddr::V4(target_ip)) {
+        return Some(mac);
+    }
+    let ethernet_buffer = insert_vlan_tag(
+        create_arp_packet(source.0, source.1, target_ip).to_vec(),
+        vlan,
+    );
+    for _ in 0..ARP_RETRIES {
+        tx.send_to(&ethernet_buffer, None).unwrap().unwrap();
+        let sent = Instant::now();
+        while sent.elapsed() < ARP_TIMEOUT {
+            let frame = match rx.next() {
+                Ok(frame) => frame,
+                Err(e) => match e.kind() {
+                    io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                    _ => panic!("Error happened {}", e),
+                },
+            };
+            if let Some((_, mac)) = parse_arp_reply(frame).filter(|(ip, _)| *ip == target_ip) {
+                cache.insert(IpAddr::V4(target_ip), mac);
+                return Some(mac);
+            }
+        }
+    }
+    None
+}
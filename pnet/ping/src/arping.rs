@@ -0,0 +1,142 @@
+use packet_utils::interface::*;
+use packet_utils::pacer::TokenBucket;
+use pnet::datalink::{DataLinkReceiver, DataLinkSender, NetworkInterface};
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::arp;
+use crate::config::Config;
+use crate::output::Output;
+use crate::stats::Statistics;
+
+// interval in which the send loop checks the reply timeout after sending the
+// last arp request
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// arp requests sent to the target and the statistics of the run, shared
+// between the sending and the receiving thread
+#[derive(Default)]
+struct Probes {
+    // index and send time of the last arp request and whether it was
+    // answered; arp replies do not identify the request, so they are
+    // matched to the last one
+    last: Option<(u64, Instant, bool)>,
+
+    stats: Statistics,
+}
+
+// receive arp replies from target ip address and print them to output
+// until receiving is unset
+fn receive_replies(
+    mut rx: Box<dyn DataLinkReceiver>,
+    target_ip: Ipv4Addr,
+    probes: &Mutex<Probes>,
+    receiving: &AtomicBool,
+    output: &Output,
+) {
+    while receiving.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("Error happened {}", e),
+            },
+        };
+        let mac = match arp::parse_arp_reply(frame) {
+            Some((ip, mac)) if ip == target_ip => mac,
+            _ => continue,
+        };
+        let mut probes = probes.lock().unwrap();
+        let (index, sent, answered) = match &mut probes.last {
+            Some(last) => last,
+            None => continue,
+        };
+        let index = *index;
+        let rtt = sent.elapsed();
+        let duplicate = *answered;
+        *answered = true;
+        probes.stats.add_reply(rtt, duplicate);
+        output.arp_reply(IpAddr::V4(target_ip), mac, index, rtt, duplicate);
+    }
+}
+
+// send arp requests for target ip address on the channel paced by the
+// interval or rate of config and print the replies to output until all
+// requests are sent or running is unset; returns the statistics of the run
+pub fn arping(
+    (mut tx, rx): (Box<dyn DataLinkSender>, Box<dyn DataLinkReceiver>),
+    interface: &NetworkInterface,
+    target_ip: Ipv4Addr,
+    config: &Config,
+    output: &Output,
+    running: &AtomicBool,
+) -> Statistics {
+    let target = IpAddr::V4(target_ip);
+    let source_mac = interface.mac.unwrap();
+    let source_ip = match get_interface_ip(interface) {
+        Some(source_ip) => source_ip,
+        None => panic!("No ipv4 address on interface {}", interface.name),
+    };
+    let ethernet_buffer = arp::create_arp_packet(source_mac, source_ip, target_ip);
+    let mut pacer = match config.rate {
+        Some(rate) => TokenBucket::new(rate),
+        None => TokenBucket::new(1.0 / config.interval),
+    };
+    let mut last_send = Instant::now();
+    let probes = Mutex::new(Probes::default());
+    let receiving = AtomicBool::new(true);
+    std::thread::scope(|s| {
+        s.spawn(|| receive_replies(rx, target_ip, &probes, &receiving, output));
+        while running.load(atomic::Ordering::Relaxed) {
+            let (transmitted, received) = {
+                let probes = probes.lock().unwrap();
+                (probes.stats.get_transmitted(), probes.stats.get_received())
+            };
+            if config.count.is_some_and(|count| transmitted >= count) {
+                if last_send.elapsed() >= arp::ARP_TIMEOUT || received >= transmitted {
+                    break;
+                }
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            if !pacer.try_take() {
+                std::thread::sleep(pacer.get_wait().min(POLL_INTERVAL));
+                continue;
+            }
+
+            // add the request before sending it, so the receiving thread
+            // does not miss a fast reply
+            last_send = Instant::now();
+            {
+                let mut probes = probes.lock().unwrap();
+                if let Some((index, _, false)) = probes.last {
+                    output.timeout(target, index);
+                }
+                probes.last = Some((transmitted, last_send, false));
+                probes.stats.add_request();
+            }
+            if let Err(e) = tx.send_to(&ethernet_buffer, None).unwrap() {
+                let mut probes = probes.lock().unwrap();
+                probes.last = None;
+                probes.stats.remove_request();
+                drop(probes);
+                output.send_error(target, transmitted, &e);
+            }
+        }
+        {
+            let mut probes = probes.lock().unwrap();
+            if let Some((index, sent, false)) = probes.last {
+                if sent.elapsed() >= arp::ARP_TIMEOUT {
+                    output.timeout(target, index);
+                }
+            }
+            probes.stats.finish();
+        }
+        receiving.store(false, atomic::Ordering::Relaxed);
+    });
+    probes.into_inner().unwrap().stats
+}
@@ -0,0 +1,40 @@
+use pnet::datalink::MacAddr;
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+// maximum number of entries in the neighbor cache and the time after which
+// they expire
+const CACHE_SIZE: usize = 256;
+const CACHE_TIMEOUT: Duration = Duration::from_secs(60);
+
+// cache of mac addresses of neighbors resolved with arp or neighbor
+// discovery
+#[derive(Default)]
+pub struct NeighborCache {
+    entries: HashMap<IpAddr, (MacAddr, Instant)>,
+}
+
+impl NeighborCache {
+    // get the cached mac address of ip if it did not expire
+    pub fn get(&self, ip: IpAddr) -> Option<MacAddr> {
+        match self.entries.get(&ip) {
+            Some((mac, added)) if added.elapsed() < CACHE_TIMEOUT => Some(*mac),
+            _ => None,
+        }
+    }
+
+    // add the mac address of ip to the cache; expired entries are removed
+    // if the cache is full, the entry is not added if it is still full
+    pub fn insert(&mut self, ip: IpAddr, mac: MacAddr) {
+        if self.entries.len() >= CACHE_SIZE && !self.entries.contains_key(&ip) {
+            self.entries
+                .retain(|_, (_, added)| added.elapsed() < CACHE_TIMEOUT);
+            if self.entries.len() >= CACHE_SIZE {
+                return;
+            }
+        }
+        self.entries.insert(ip, (mac, Instant::now()));
+    }
+}
@@ -0,0 +1,263 @@
+use pnet::datalink::{DataLinkReceiver, DataLinkSender, MacAddr, NetworkInterface};
+use pnet::packet::icmp::IcmpPacket;
+use pnet::packet::icmpv6::Icmpv6Packet;
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::transport::transport_channel;
+use pnet::transport::TransportChannelType::Layer4;
+use pnet::transport::TransportProtocol::{Ipv4, Ipv6};
+use pnet::transport::{TransportReceiver, TransportSender};
+
+use socket2::{Domain, Protocol, Socket, Type};
+
+use std::io;
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
+
+use crate::echo::*;
+use crate::errors::*;
+use crate::fragment::Reassembler;
+use crate::timestamp::*;
+
+// interval in which the receive loop checks if it should stop
+pub const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// size of the receive buffer of transport channels
+const TRANSPORT_BUFFER_SIZE: usize = 65535;
+
+// open the icmp or icmpv6 transport channel for packets to destination
+// with ttl; the kernel builds the ip header and selects the interface
+pub fn open_transport_channel(
+    destination: IpAddr,
+    ttl: u8,
+) -> io::Result<(TransportSender, TransportReceiver)> {
+    let protocol = match destination {
+        IpAddr::V4(_) => Layer4(Ipv4(IpNextHeaderProtocols::Icmp)),
+        IpAddr::V6(_) => Layer4(Ipv6(IpNextHeaderProtocols::Icmpv6)),
+    };
+    let (mut tx, rx) = transport_channel(TRANSPORT_BUFFER_SIZE, protocol)?;
+    if let Err(e) = tx.set_ttl(ttl) {
+        panic!("Could not set ttl: {}", e);
+    }
+    enable_timestamps(rx.socket.fd);
+    Ok((tx, rx))
+}
+
+// create the transport channel for echo requests to destination with ttl
+pub fn create_transport_channel(destination: IpAddr, ttl: u8) -> io::Result<(Sender, Receiver)> {
+    let (tx, rx) = open_transport_channel(destination, ttl)?;
+    Ok((Sender::Transport(tx), Receiver::Transport(rx)))
+}
+
+// create the unprivileged icmp or icmpv6 datagram socket, a linux ping
+// socket, for echo requests to destination with ttl; it does not need raw
+// socket privileges, but only if the group of the process is in the
+// net.ipv4.ping_group_range sysctl
+pub fn create_datagram_channel(destination: IpAddr, ttl: u8) -> (Sender, Receiver) {
+    let (domain, protocol) = match destination {
+        IpAddr::V4(_) => (Domain::IPV4, Protocol::ICMPV4),
+        IpAddr::V6(_) => (Domain::IPV6, Protocol::ICMPV6),
+    };
+    let socket = match Socket::new(domain, Type::DGRAM, Some(protocol)) {
+        Ok(socket) => socket,
+        Err(e) => panic!("Could not create icmp datagram socket: {}", e),
+    };
+    let result = match destination {
+        IpAddr::V4(_) => socket.set_ttl(u32::from(ttl)),
+        IpAddr::V6(_) => socket.set_unicast_hops_v6(u32::from(ttl)),
+    };
+    if let Err(e) = result {
+        panic!("Could not set ttl: {}", e);
+    }
+    let socket = UdpSocket::from(socket);
+    if let Err(e) = socket.set_read_timeout(Some(READ_TIMEOUT)) {
+        panic!("Could not set read timeout: {}", e);
+    }
+    enable_timestamps(socket.as_raw_fd());
+    let tx = match socket.try_clone() {
+        Ok(tx) => tx,
+        Err(e) => panic!("Could not clone icmp datagram socket: {}", e),
+    };
+    (Sender::Datagram(tx), Receiver::Datagram(socket))
+}
+
+// sender of echo requests
+pub enum Sender {
+    // datalink channel on interface with the ethernet and ip headers built
+    // from the mac address of the next hop, the source address and the ip
+    // header fields
+    Datalink {
+        tx: Box<dyn DataLinkSender>,
+        interface: NetworkInterface,
+        destination_mac: MacAddr,
+        source_ip: IpAddr,
+        fields: IpFields,
+    },
+
+    // transport channel with the ip header built by the kernel
+    Transport(TransportSender),
+
+    // datagram socket with the ip header built by the kernel that also
+    // sets the identifier and the checksum of echo requests
+    Datagram(UdpSocket),
+}
+
+impl Sender {
+    // send the echo request with identifier, sequence number and payload to
+    // destination
+    pub fn send(
+        &mut self,
+        destination: IpAddr,
+        identifier: u16,
+        sequence: u16,
+        payload: &[u8],
+    ) -> io::Result<()> {
+        match self {
+            Sender::Datalink {
+                tx,
+                interface,
+                destination_mac,
+                source_ip,
+                fields,
+            } => {
+                let frames = match (destination, source_ip) {
+                    (IpAddr::V4(destination), IpAddr::V4(source_ip)) => create_ping_packet(
+                        (interface, *source_ip),
+                        destination,
+                        *destination_mac,
+                        identifier,
+                        sequence,
+                        payload,
+                        fields,
+                    ),
+                    (IpAddr::V6(destination), IpAddr::V6(source_ip)) => vec![create_ping6_packet(
+                        (interface.mac.unwrap(), *source_ip),
+                        destination,
+                        *destination_mac,
+                        identifier,
+                        sequence,
+                        payload,
+                        fields,
+                    )],
+                    _ => unreachable!(),
+                };
+                frames
+                    .iter()
+                    .try_for_each(|frame| tx.send_to(frame, None).unwrap())
+            }
+
+            // the kernel sets the checksum of icmpv6 packets
+            Sender::Transport(tx) => {
+                let result = match destination {
+                    IpAddr::V4(_) => {
+                        let echo_buffer = create_echo_request(identifier, sequence, payload);
+                        tx.send_to(IcmpPacket::new(&echo_buffer).unwrap(), destination)
+                    }
+                    IpAddr::V6(_) => {
+                        let echo_buffer = create_echo6_request(identifier, sequence, payload);
+                        tx.send_to(Icmpv6Packet::new(&echo_buffer).unwrap(), destination)
+                    }
+                };
+                result.map(|_| ())
+            }
+
+            Sender::Datagram(socket) => {
+                let echo_buffer = match destination {
+                    IpAddr::V4(_) => create_echo_request(identifier, sequence, payload),
+                    IpAddr::V6(_) => create_echo6_request(identifier, sequence, payload),
+                };
+                socket
+                    .send_to(&echo_buffer, SocketAddr::new(destination, 0))
+                    .map(|_| ())
+            }
+        }
+    }
+}
+
+// message received in reply to an echo request
+pub enum Message {
+    Reply(EchoReply),
+    Error(IcmpError),
+}
+
+// receiver of echo replies and icmp error messages; fragmented packets
+// received on datalink channels are reassembled
+pub enum Receiver {
+    Datalink(Box<dyn DataLinkReceiver>, Reassembler),
+    Transport(TransportReceiver),
+    Datagram(UdpSocket),
+}
+
+impl Receiver {
+    // receive the next echo reply or icmp error message caused by an echo
+    // request with identifier; returns none if no such message was received
+    // within the read timeout
+    pub fn receive(&mut self, identifier: u16) -> io::Result<Option<Message>> {
+        match self {
+            Receiver::Datalink(rx, fragments) => {
+                let frame = match rx.next() {
+                    Ok(frame) => frame,
+                    Err(e) if e.kind() == io::ErrorKind::TimedOut => return Ok(None),
+                    Err(e) => return Err(e),
+                };
+                let frame = match fragments.add(frame) {
+                    Some(frame) => frame,
+                    None => return Ok(None),
+                };
+                Ok(parse_echo_reply(&frame, identifier)
+                    .map(Message::Reply)
+                    .or_else(|| parse_icmp_error(&frame, identifier).map(Message::Error)))
+            }
+            Receiver::Transport(rx) => match rx.channel_type {
+                Layer4(Ipv4(_)) => receive_icmp(rx, identifier),
+                _ => receive_icmpv6(rx, identifier),
+            },
+            Receiver::Datagram(socket) => receive_datagram(socket),
+        }
+    }
+}
+
+// receive the next icmp message on the transport channel rx; the kernel
+// passes on icmp packets with their ipv4 header
+fn receive_icmp(rx: &mut TransportReceiver, identifier: u16) -> io::Result<Option<Message>> {
+    let (size, received) = match receive(rx.socket.fd, &mut rx.buffer, READ_TIMEOUT)? {
+        Some((size, _, received)) => (size, received),
+        None => return Ok(None),
+    };
+    let packet = &rx.buffer[..size];
+    Ok(parse_echo4_reply(packet, MacAddr::zero())
+        .filter(|reply| reply.identifier == identifier)
+        .map(|reply| Message::Reply(EchoReply { received, ..reply }))
+        .or_else(|| parse_icmp4_error(packet, identifier).map(Message::Error)))
+}
+
+// receive the next icmpv6 message on the transport channel rx; the kernel
+// removes the ipv6 header
+fn receive_icmpv6(rx: &mut TransportReceiver, identifier: u16) -> io::Result<Option<Message>> {
+    let (size, source, received) = match receive(rx.socket.fd, &mut rx.buffer, READ_TIMEOUT)? {
+        Some((size, IpAddr::V6(source), received)) => (size, source, received),
+        _ => return Ok(None),
+    };
+    let message = &rx.buffer[..size];
+    Ok(parse_echo6_message(message, source)
+        .filter(|reply| reply.identifier == identifier)
+        .map(|reply| Message::Reply(EchoReply { received, ..reply }))
+        .or_else(|| parse_icmp6_message(message, source, identifier).map(Message::Error)))
+}
+
+// receive the next echo reply on the datagram socket; the kernel removes
+// the ip header and only passes on echo replies with the identifier it set
+// in the echo requests, but no icmp error messages
+fn receive_datagram(socket: &UdpSocket) -> io::Result<Option<Message>> {
+    let mut buffer = [0u8; TRANSPORT_BUFFER_SIZE];
+    let (size, source, received) = match receive(socket.as_raw_fd(), &mut buffer, READ_TIMEOUT)? {
+        Some(packet) => packet,
+        None => return Ok(None),
+    };
+    let message = &buffer[..size];
+    Ok(match source {
+        IpAddr::V4(source) => parse_echo4_message(message, source),
+        IpAddr::V6(source) => parse_echo6_message(message, source),
+    }
+    .map(|reply| Message::Reply(EchoReply { received, ..reply })))
+}
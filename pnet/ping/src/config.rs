@@ -0,0 +1,373 @@
+use clap::Parser;
+use packet_utils::hex::parse_hex;
+use packet_utils::parse::{parse_interval, parse_rate, parse_tos};
+
+use crate::echo::{Ecn, SizeRange, MAX_PAYLOAD_SIZE};
+use crate::export::{parse_export_target, ExportTarget};
+use crate::options::TimestampMode;
+use crate::probe::{parse_interface_id, InterfaceId};
+
+// command line configuration of the ping tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Send icmp echo requests to a host and print its replies")]
+pub struct Config {
+    // destination of echo requests
+    #[arg(
+        value_name = "DESTINATION",
+        help = "Send echo requests to DESTINATION, an ip address or a host \
+        name, or a network in sweep mode"
+    )]
+    pub destination: String,
+
+    // additional destinations
+    #[arg(
+        value_name = "DESTINATIONS",
+        conflicts_with_all = [
+            "sweep", "arp", "layer4", "monitor", "json", "flood", "rate",
+            "record_route", "timestamp", "probe", "size_sweep", "export"
+        ],
+        help = "Send echo requests to DESTINATIONS concurrently with \
+        DESTINATION, print the replies of all of them and a status table at \
+        the end"
+    )]
+    pub destinations: Vec<String>,
+
+    // file with additional destinations
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = [
+            "sweep", "arp", "layer4", "monitor", "json", "flood", "rate",
+            "record_route", "timestamp", "probe", "size_sweep", "export"
+        ],
+        help = "Read additional destinations from FILE, one per line; empty \
+        lines and lines starting with # are ignored"
+    )]
+    pub targets: Option<std::path::PathBuf>,
+
+    // source interface or address
+    #[arg(
+        short = 'I',
+        long,
+        value_name = "INTERFACE",
+        help = "Send packets on INTERFACE, an interface name or an ip \
+        address used as source address; by default, the first interface \
+        that is up, not a loopback and has an ip address is used"
+    )]
+    pub interface: Option<String>,
+
+    // json output
+    #[arg(
+        long,
+        conflicts_with_all = ["flood", "sweep"],
+        help = "Print one json object per request and a summary object \
+        instead of text"
+    )]
+    pub json: bool,
+
+    // quiet output
+    #[arg(
+        short,
+        long,
+        conflicts_with = "sweep",
+        help = "Only print the summary and the periodic statistics of \
+        --monitor instead of each reply"
+    )]
+    pub quiet: bool,
+
+    // exit status
+    #[arg(
+        long,
+        conflicts_with = "sweep",
+        help = "Exit with status 0 only if all requests to all destinations \
+        were answered; by default, one reply is enough"
+    )]
+    pub require_all: bool,
+
+    // export of measurements
+    #[arg(
+        long,
+        value_name = "TARGET",
+        value_parser = parse_export_target,
+        conflicts_with = "sweep",
+        help = "Export the round-trip times and losses of requests and the \
+        statistics of --monitor intervals to a time-series database, \
+        influx://HOST:PORT for the influxdb line protocol over udp or \
+        graphite://HOST:PORT for the graphite plaintext protocol over tcp"
+    )]
+    pub export: Option<ExportTarget>,
+
+    // subnet sweep mode
+    #[arg(
+        long,
+        conflicts_with_all = ["ipv6", "count", "interval", "flood"],
+        help = "Treat DESTINATION as an ipv4 network in CIDR notation like \
+        192.168.1.0/24, send an echo request to every host in it and print \
+        a table of the hosts that answered; requests are sent with 100 \
+        packets per second unless --rate is set; exits with status 1 if no \
+        host answered"
+    )]
+    pub sweep: bool,
+
+    // arp ping mode
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "ipv6", "flood", "sweep", "size", "pattern", "ttl", "tos", "ecn"
+        ],
+        help = "Send arp requests for DESTINATION, an ipv4 address on the \
+        local network, instead of echo requests and print the mac addresses \
+        in the replies"
+    )]
+    pub arp: bool,
+
+    // interface probing mode
+    #[arg(
+        long,
+        value_name = "INTERFACE",
+        value_parser = parse_interface_id,
+        conflicts_with_all = [
+            "sweep", "arp", "monitor", "flood", "layer4", "interface", "size",
+            "pattern", "tos", "ecn", "record_route", "timestamp", "vlan"
+        ],
+        help = "Send rfc 8335 extended echo requests that probe INTERFACE \
+        on DESTINATION, an interface name, index or address, and print its \
+        state in the replies"
+    )]
+    pub probe: Option<InterfaceId>,
+
+    // monitoring mode
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        value_parser = parse_interval,
+        conflicts_with_all = ["count", "flood", "sweep", "arp"],
+        help = "Send echo requests until interrupted, print the loss and \
+        round-trip times of the echo requests completed in the last SECONDS \
+        seconds every SECONDS seconds and print each outage of consecutive \
+        missed echo requests when it ends"
+    )]
+    pub monitor: Option<f64>,
+
+    // reverse lookups
+    #[arg(
+        long,
+        conflicts_with_all = ["sweep", "arp"],
+        help = "Look up the host names of the sources of replies and icmp \
+        error messages with reverse dns queries to the first name server in \
+        /etc/resolv.conf and show them with the addresses"
+    )]
+    pub rdns: bool,
+
+    // layer4 mode
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "interface", "sweep", "arp", "tos", "ecn", "record_route", "timestamp",
+            "vlan"
+        ],
+        help = "Send echo requests on an icmp socket and let the kernel build \
+        the ip header and select the interface; works on interfaces without \
+        a mac address like tun devices, but the hop limit of ipv6 replies is \
+        not shown"
+    )]
+    pub layer4: bool,
+
+    // use ipv6
+    #[arg(
+        short = '6',
+        long,
+        help = "Resolve host names to ipv6 addresses and send icmpv6 echo \
+        requests; ipv6 addresses are always pinged with icmpv6"
+    )]
+    pub ipv6: bool,
+
+    // number of echo requests
+    #[arg(
+        short,
+        long,
+        value_name = "COUNT",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Stop after sending COUNT echo requests and receiving their \
+        replies; by default, echo requests are sent until interrupted"
+    )]
+    pub count: Option<u64>,
+
+    // interval between echo requests
+    #[arg(
+        short,
+        long,
+        value_name = "SECONDS",
+        default_value_t = 1.0,
+        value_parser = parse_interval,
+        help = "Wait SECONDS seconds between sending echo requests"
+    )]
+    pub interval: f64,
+
+    // rate of echo requests
+    #[arg(
+        long,
+        value_name = "PPS",
+        value_parser = parse_rate,
+        conflicts_with = "interval",
+        help = "Send PPS echo requests per second instead of waiting an \
+        interval between them"
+    )]
+    pub rate: Option<f64>,
+
+    // flood mode
+    #[arg(
+        short,
+        long,
+        conflicts_with_all = ["interval", "rate"],
+        help = "Send echo requests as fast as possible and print a dot for \
+        each request and a backspace for each reply instead of the replies"
+    )]
+    pub flood: bool,
+
+    // size of the echo payload
+    #[arg(
+        short,
+        long,
+        value_name = "BYTES",
+        default_value_t = 56,
+        value_parser = parse_size,
+        help = "Send BYTES bytes of payload in each echo request; payloads \
+        of at least 12 bytes start with the send time; ipv4 echo requests \
+        larger than the mtu of the interface are sent in fragments"
+    )]
+    pub size: usize,
+
+    // payload size sweep
+    #[arg(
+        long,
+        value_name = "MIN:MAX:STEP",
+        value_parser = parse_size_range,
+        conflicts_with_all = ["size", "sweep", "arp", "probe"],
+        help = "Vary the payload size of consecutive echo requests from MIN \
+        to MAX bytes in steps of STEP bytes, starting over after MAX, and \
+        print the loss and round-trip times of each size at the end"
+    )]
+    pub size_sweep: Option<SizeRange>,
+
+    // pattern of the echo payload; the full path of vec keeps clap from
+    // treating the pattern as multiple values
+    #[arg(
+        short,
+        long,
+        value_name = "PATTERN",
+        value_parser = parse_pattern,
+        help = "Fill the payload with PATTERN, up to 16 bytes in hex \
+        notation like ff00; by default, incrementing byte values are used"
+    )]
+    pub pattern: Option<std::vec::Vec<u8>>,
+
+    // ttl or hop limit
+    #[arg(
+        long,
+        value_name = "TTL",
+        default_value_t = 64,
+        value_parser = clap::value_parser!(u8).range(1..),
+        help = "Set the ttl of ipv4 or the hop limit of ipv6 echo requests"
+    )]
+    pub ttl: u8,
+
+    // type of service or traffic class
+    #[arg(
+        long,
+        value_name = "TOS",
+        default_value_t = 0,
+        value_parser = parse_tos,
+        help = "Set the type of service of ipv4 or the traffic class of ipv6 \
+        echo requests, a decimal or hex number like 0xb8 with the dscp in \
+        the upper 6 bits and the ecn in the lower 2 bits"
+    )]
+    pub tos: u8,
+
+    // ecn codepoint
+    #[arg(
+        long,
+        value_name = "CODEPOINT",
+        help = "Set the ecn bits of echo requests, overrides the lower 2 \
+        bits of --tos"
+    )]
+    pub ecn: Option<Ecn>,
+
+    // record route ip option
+    #[arg(
+        short = 'R',
+        long,
+        conflicts_with_all = ["timestamp", "arp", "sweep"],
+        help = "Add the record route option to ipv4 echo requests and print \
+        the recorded route of the replies"
+    )]
+    pub record_route: bool,
+
+    // timestamp ip option
+    #[arg(
+        short = 'T',
+        long,
+        value_name = "MODE",
+        conflicts_with_all = ["arp", "sweep"],
+        help = "Add the timestamp option to ipv4 echo requests and print the \
+        recorded timestamps of the replies; the hops record timestamps only \
+        or their addresses and timestamps"
+    )]
+    pub timestamp: Option<TimestampMode>,
+
+    // 802.1q vlan id
+    #[arg(
+        long,
+        value_name = "ID",
+        value_parser = clap::value_parser!(u16).range(0..4095),
+        conflicts_with_all = ["arp", "sweep"],
+        help = "Insert an 802.1q tag with the vlan id into the ethernet \
+        frames of echo requests and address resolution, e.g., to test \
+        connectivity on a vlan from a trunk port"
+    )]
+    pub vlan: Option<u16>,
+
+    // 802.1q priority code point
+    #[arg(
+        long,
+        value_name = "PCP",
+        default_value_t = 0,
+        value_parser = clap::value_parser!(u8).range(0..8),
+        requires = "vlan",
+        help = "Set the priority code point of the vlan tag"
+    )]
+    pub pcp: u8,
+}
+
+// parse the size of the echo payload in bytes
+fn parse_size(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(size) if size <= MAX_PAYLOAD_SIZE => Ok(size),
+        _ => Err(format!(
+            "invalid size: {}, maximum is {}",
+            s, MAX_PAYLOAD_SIZE
+        )),
+    }
+}
+
+// parse the range of payload sizes in the format min:max:step
+fn parse_size_range(s: &str) -> Result<SizeRange, String> {
+    let error = || format!("invalid size range: {}", s);
+    let sizes: Vec<&str> = s.split(':').collect();
+    let (min, max, step) = match sizes[..] {
+        [min, max, step] => (parse_size(min)?, parse_size(max)?, step),
+        _ => return Err(error()),
+    };
+    match step.parse::<usize>() {
+        Ok(step) if step > 0 && min <= max => Ok(SizeRange { min, max, step }),
+        _ => Err(error()),
+    }
+}
+
+// parse the payload pattern of up to 16 bytes in hex notation
+fn parse_pattern(s: &str) -> Result<Vec<u8>, String> {
+    match parse_hex(s) {
+        Some(pattern) if !pattern.is_empty() && pattern.len() <= 16 => Ok(pattern),
+        _ => Err(format!("invalid pattern: {}", s)),
+    }
+}
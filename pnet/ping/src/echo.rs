@@ -0,0 +1,412 @@
+use clap::ValueEnum;
+
+use packet_utils::bytes::*;
+use packet_utils::checksum::*;
+use packet_utils::interface::get_interface_mtu;
+use pnet::datalink::MacAddr;
+use pnet::datalink::NetworkInterface;
+use pnet::packet::ethernet::EtherTypes;
+use pnet::packet::ethernet::{EthernetPacket, MutableEthernetPacket};
+use pnet::packet::icmp::echo_reply::EchoReplyPacket;
+use pnet::packet::icmp::echo_request::MutableEchoRequestPacket;
+use pnet::packet::icmp::IcmpTypes;
+use pnet::packet::icmpv6::{self, Icmpv6Packet, Icmpv6Types};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv4::{Ipv4Packet, MutableIpv4Packet};
+use pnet::packet::ipv6::{Ipv6Packet, MutableIpv6Packet};
+use pnet::packet::{MutablePacket, Packet};
+
+use std::convert::TryFrom;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+use crate::fragment::fragment_packet;
+use crate::options::*;
+use crate::vlan::*;
+
+// packet header constants; the echo payload follows the headers
+const ECHO_SIZE: usize = MutableEchoRequestPacket::minimum_packet_size();
+const IPV4_SIZE: usize = MutableIpv4Packet::minimum_packet_size();
+const ECHO6_SIZE: usize = icmpv6::echo_request::MutableEchoRequestPacket::minimum_packet_size();
+const IPV6_SIZE: usize = MutableIpv6Packet::minimum_packet_size();
+const ETHERNET_SIZE: usize = MutableEthernetPacket::minimum_packet_size();
+
+// maximum size of the echo payload that fits into an ipv4 packet
+pub const MAX_PAYLOAD_SIZE: usize = u16::MAX as usize - IPV4_SIZE - ECHO_SIZE;
+
+// size of the send time at the start of the echo payload: seconds and
+// nanoseconds since the unix epoch
+const TIMESTAMP_SIZE: usize = 12;
+
+// echo reply received from a host
+pub struct EchoReply {
+    pub source: IpAddr,
+    pub source_mac: MacAddr,
+    pub identifier: u16,
+    pub sequence: u16,
+
+    // ttl or hop limit of the ip packet; the hop limit is unknown if the
+    // kernel removed the ipv6 header
+    pub ttl: Option<u8>,
+
+    // size of the icmp packet
+    pub size: usize,
+
+    // payload of the echo reply
+    pub payload: Vec<u8>,
+
+    // whether the icmp checksum of the echo reply is correct
+    pub checksum_valid: bool,
+
+    // record route and timestamp options of the ipv4 packet
+    pub options: Vec<IpOption>,
+
+    // time the kernel received the packet if it is known
+    pub received: Option<SystemTime>,
+}
+
+// explicit congestion notification codepoint in the ip header
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum Ecn {
+    // not ecn-capable transport
+    #[value(name = "not-ect")]
+    NotEct = 0,
+
+    // ecn-capable transport
+    #[value(name = "ect1")]
+    Ect1 = 1,
+    #[value(name = "ect0")]
+    Ect0 = 2,
+
+    // congestion experienced
+    #[value(name = "ce")]
+    Ce = 3,
+}
+
+// configurable fields of the ip header and the vlan tag of the ethernet
+// frame of echo requests
+pub struct IpFields {
+    // ttl or hop limit
+    pub ttl: u8,
+
+    // type of service or traffic class with the dscp and ecn bits
+    pub tos: u8,
+
+    // ipv4 options padded to a multiple of 4 bytes
+    pub options: Vec<u8>,
+
+    // 802.1q tag inserted into the ethernet frame
+    pub vlan: Option<VlanTag>,
+}
+
+impl IpFields {
+    // get the ip header fields configured in config
+    pub fn new(config: &Config) -> IpFields {
+        let tos = match config.ecn {
+            Some(ecn) => config.tos & !0b11 | ecn as u8,
+            None => config.tos,
+        };
+        let options = match (config.record_route, config.timestamp) {
+            (true, _) => create_record_route(),
+            (false, Some(mode)) => create_timestamp(mode),
+            (false, None) => Vec::new(),
+        };
+        IpFields {
+            ttl: config.ttl,
+            tos,
+            options,
+            vlan: config.vlan.map(|id| VlanTag {
+                id,
+                pcp: config.pcp,
+            }),
+        }
+    }
+}
+
+// range of payload sizes from min to max in steps of step bytes
+#[derive(Clone, Copy, Debug)]
+pub struct SizeRange {
+    pub min: usize,
+    pub max: usize,
+    pub step: usize,
+}
+
+impl SizeRange {
+    // get all sizes in the range
+    pub fn get_sizes(&self) -> Vec<usize> {
+        (self.min..=self.max).step_by(self.step).collect()
+    }
+}
+
+// create the echo payload of size bytes filled with the repeated pattern;
+// without a pattern, the payload is filled with incrementing byte values
+pub fn create_payload(size: usize, pattern: Option<&[u8]>) -> Vec<u8> {
+    match pattern {
+        Some(pattern) if !pattern.is_empty() => {
+            pattern.iter().copied().cycle().take(size).collect()
+        }
+        _ => (0..size).map(|i| i as u8).collect(),
+    }
+}
+
+// write the send time to the start of the payload, if it is large enough
+pub fn set_timestamp(payload: &mut [u8], time: SystemTime) {
+    if payload.len() < TIMESTAMP_SIZE {
+        return;
+    }
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    write_be_u64(&mut payload[0..8], since_epoch.as_secs());
+    write_be_u32(&mut payload[8..12], since_epoch.subsec_nanos());
+}
+
+// get the send time at the start of the payload
+pub fn get_timestamp(payload: &[u8]) -> Option<SystemTime> {
+    let secs = read_be_u64(payload.get(0..8)?);
+    let nanos = read_be_u32(payload.get(8..12)?);
+    if nanos >= 1_000_000_000 {
+        return None;
+    }
+    UNIX_EPOCH.checked_add(Duration::new(secs, nanos))
+}
+
+// check if the received payload is the sent payload; the send times at the
+// start of the payloads are not compared
+pub fn is_payload_intact(sent: &[u8], received: &[u8]) -> bool {
+    let start = TIMESTAMP_SIZE.min(sent.len());
+    sent.len() == received.len() && sent[start..] == received[start..]
+}
+
+// create icmp echo request packet with identifier, sequence number and
+// payload
+pub fn create_echo_request(identifier: u16, sequence: u16, payload: &[u8]) -> Vec<u8> {
+    let mut echo_buffer = vec![0u8; ECHO_SIZE + payload.len()];
+    let mut echo_packet = MutableEchoRequestPacket::new(&mut echo_buffer).unwrap();
+    echo_packet.set_icmp_type(IcmpTypes::EchoRequest);
+    echo_packet.set_identifier(identifier);
+    echo_packet.set_sequence_number(sequence);
+    echo_packet.set_payload(payload);
+    echo_packet.set_checksum(get_checksum(echo_packet.packet(), ICMP_CHECKSUM_OFFSET));
+    echo_buffer
+}
+
+// create icmpv6 echo request packet with identifier, sequence number and
+// payload; the checksum includes the ipv6 pseudo-header, so it is not set
+pub fn create_echo6_request(identifier: u16, sequence: u16, payload: &[u8]) -> Vec<u8> {
+    let mut echo_buffer = vec![0u8; ECHO6_SIZE + payload.len()];
+    let mut echo_packet =
+        icmpv6::echo_request::MutableEchoRequestPacket::new(&mut echo_buffer).unwrap();
+    echo_packet.set_icmpv6_type(Icmpv6Types::EchoRequest);
+    echo_packet.set_identifier(identifier);
+    echo_packet.set_sequence_number(sequence);
+    echo_packet.set_payload(payload);
+    echo_buffer
+}
+
+// create the ethernet frames of the ping/echo request packet from interface
+// and source ip address with identifier, sequence number and payload and the
+// ip header fields; packets larger than the mtu of interface are split into
+// fragments identified by the sequence number
+pub fn create_ping_packet(
+    source: (&NetworkInterface, Ipv4Addr),
+    destination: Ipv4Addr,
+    destination_mac: MacAddr,
+    identifier: u16,
+    sequence: u16,
+    payload: &[u8],
+    fields: &IpFields,
+) -> Vec<Vec<u8>> {
+    let (interface, source_ip) = source;
+    let echo_size = ECHO_SIZE + payload.len();
+    let header_size = IPV4_SIZE + fields.options.len();
+    let ipv4_size = header_size + echo_size;
+
+    // create echo request packet
+    let echo_buffer = create_echo_request(identifier, sequence, payload);
+
+    // create ipv4 packet
+    let mut ipv4_buffer = vec![0u8; ipv4_size];
+    let mut ipv4_packet = MutableIpv4Packet::new(&mut ipv4_buffer).unwrap();
+    ipv4_packet.set_version(4);
+    ipv4_packet.set_header_length(u8::try_from(header_size / 4).unwrap());
+    ipv4_packet.set_total_length(u16::try_from(ipv4_size).unwrap());
+    ipv4_packet.set_dscp(fields.tos >> 2);
+    ipv4_packet.set_ecn(fields.tos & 0b11);
+    ipv4_packet.set_identification(sequence);
+    ipv4_packet.set_ttl(fields.ttl);
+    ipv4_packet.set_next_level_protocol(IpNextHeaderProtocols::Icmp);
+    ipv4_packet.set_source(source_ip);
+    ipv4_packet.set_destination(destination);
+    ipv4_packet.packet_mut()[IPV4_SIZE..header_size].copy_from_slice(&fields.options);
+    ipv4_packet.set_checksum(get_checksum(ipv4_packet.packet(), IPV4_CHECKSUM_OFFSET));
+    ipv4_packet.set_payload(&echo_buffer);
+
+    // create ethernet packets
+    let fragments = fragment_packet(ipv4_packet.packet(), get_interface_mtu(interface));
+    fragments
+        .iter()
+        .map(|fragment| {
+            let mut ethernet_buffer = vec![0u8; ETHERNET_SIZE + fragment.len()];
+            let mut ethernet_packet = MutableEthernetPacket::new(&mut ethernet_buffer).unwrap();
+            ethernet_packet.set_source(interface.mac.unwrap());
+            ethernet_packet.set_destination(destination_mac);
+            ethernet_packet.set_ethertype(EtherTypes::Ipv4);
+            ethernet_packet.set_payload(fragment);
+            insert_vlan_tag(ethernet_buffer, fields.vlan)
+        })
+        .collect()
+}
+
+// create icmpv6 ping/echo request packet from source mac and ip address
+// with identifier, sequence number and payload and the ttl and tos of the ip
+// header fields as hop limit and traffic class
+pub fn create_ping6_packet(
+    source: (MacAddr, Ipv6Addr),
+    destination: Ipv6Addr,
+    destination_mac: MacAddr,
+    identifier: u16,
+    sequence: u16,
+    payload: &[u8],
+    fields: &IpFields,
+) -> Vec<u8> {
+    let (source_mac, source_ip) = source;
+    let echo_size = ECHO6_SIZE + payload.len();
+    let ipv6_size = IPV6_SIZE + echo_size;
+
+    // create echo request packet; the checksum includes the ipv6
+    // pseudo-header
+    let mut echo_buffer = create_echo6_request(identifier, sequence, payload);
+    let mut echo_packet =
+        icmpv6::echo_request::MutableEchoRequestPacket::new(&mut echo_buffer).unwrap();
+    let checksum = icmpv6::checksum(
+        &Icmpv6Packet::new(echo_packet.packet()).unwrap(),
+        &source_ip,
+        &destination,
+    );
+    echo_packet.set_checksum(checksum);
+
+    // create ipv6 packet
+    let mut ipv6_buffer = vec![0u8; ipv6_size];
+    let mut ipv6_packet = MutableIpv6Packet::new(&mut ipv6_buffer).unwrap();
+    ipv6_packet.set_version(6);
+    ipv6_packet.set_traffic_class(fields.tos);
+    ipv6_packet.set_payload_length(u16::try_from(echo_size).unwrap());
+    ipv6_packet.set_next_header(IpNextHeaderProtocols::Icmpv6);
+    ipv6_packet.set_hop_limit(fields.ttl);
+    ipv6_packet.set_source(source_ip);
+    ipv6_packet.set_destination(destination);
+    ipv6_packet.set_payload(echo_packet.packet_mut());
+
+    // create ethernet packet
+    let mut ethernet_buffer = vec![0u8; ETHERNET_SIZE + ipv6_size];
+    let mut ethernet_packet = MutableEthernetPacket::new(&mut ethernet_buffer).unwrap();
+    ethernet_packet.set_source(source_mac);
+    ethernet_packet.set_destination(destination_mac);
+    ethernet_packet.set_ethertype(EtherTypes::Ipv6);
+    ethernet_packet.set_payload(ipv6_packet.packet_mut());
+
+    insert_vlan_tag(ethernet_buffer, fields.vlan)
+}
+
+// parse the icmp message from source and get the echo reply
+pub fn parse_echo4_message(message: &[u8], source: Ipv4Addr) -> Option<EchoReply> {
+    let echo_packet = EchoReplyPacket::new(message)?;
+    if echo_packet.get_icmp_type() != IcmpTypes::EchoReply {
+        return None;
+    }
+    let checksum_valid = is_checksum_valid(echo_packet.packet(), ICMP_CHECKSUM_OFFSET);
+    Some(EchoReply {
+        source: IpAddr::V4(source),
+        source_mac: MacAddr::zero(),
+        identifier: echo_packet.get_identifier(),
+        sequence: echo_packet.get_sequence_number(),
+        ttl: None,
+        size: message.len(),
+        payload: echo_packet.payload().to_vec(),
+        checksum_valid,
+        options: Vec::new(),
+        received: None,
+    })
+}
+
+// parse the ipv4 packet from source mac address and get the echo reply
+pub fn parse_echo4_reply(packet: &[u8], source_mac: MacAddr) -> Option<EchoReply> {
+    let ipv4_packet = Ipv4Packet::new(packet)?;
+    if ipv4_packet.get_next_level_protocol() != IpNextHeaderProtocols::Icmp {
+        return None;
+    }
+    let reply = parse_echo4_message(ipv4_packet.payload(), ipv4_packet.get_source())?;
+    Some(EchoReply {
+        source_mac,
+        ttl: Some(ipv4_packet.get_ttl()),
+        options: parse_options(
+            ipv4_packet
+                .packet()
+                .get(IPV4_SIZE..usize::from(ipv4_packet.get_header_length()) * 4)
+                .unwrap_or_default(),
+        ),
+        ..reply
+    })
+}
+
+// parse the icmpv6 message from source and get the echo reply; the kernel
+// verifies the checksum of icmpv6 messages it passes on without the ipv6
+// header, so it is assumed valid
+pub fn parse_echo6_message(message: &[u8], source: Ipv6Addr) -> Option<EchoReply> {
+    let echo_packet = icmpv6::echo_reply::EchoReplyPacket::new(message)?;
+    if echo_packet.get_icmpv6_type() != Icmpv6Types::EchoReply {
+        return None;
+    }
+    Some(EchoReply {
+        source: IpAddr::V6(source),
+        source_mac: MacAddr::zero(),
+        identifier: echo_packet.get_identifier(),
+        sequence: echo_packet.get_sequence_number(),
+        ttl: None,
+        size: message.len(),
+        payload: echo_packet.payload().to_vec(),
+        checksum_valid: true,
+        options: Vec::new(),
+        received: None,
+    })
+}
+
+// parse the ipv6 packet from source mac address and get the icmpv6 echo
+// reply
+fn parse_echo6_reply(packet: &[u8], source_mac: MacAddr) -> Option<EchoReply> {
+    let ipv6_packet = Ipv6Packet::new(packet)?;
+    if ipv6_packet.get_next_header() != IpNextHeaderProtocols::Icmpv6 {
+        return None;
+    }
+    let message = ipv6_packet.payload();
+    let reply = parse_echo6_message(message, ipv6_packet.get_source())?;
+    let icmp_packet = Icmpv6Packet::new(message)?;
+    let checksum = icmpv6::checksum(
+        &icmp_packet,
+        &ipv6_packet.get_source(),
+        &ipv6_packet.get_destination(),
+    );
+    Some(EchoReply {
+        source_mac,
+        ttl: Some(ipv6_packet.get_hop_limit()),
+        checksum_valid: checksum == icmp_packet.get_checksum(),
+        ..reply
+    })
+}
+
+// parse the ethernet frame and get the echo reply with any identifier
+pub fn parse_any_echo_reply(frame: &[u8]) -> Option<EchoReply> {
+    let ethernet_packet = EthernetPacket::new(frame)?;
+    let source_mac = ethernet_packet.get_source();
+    match ethernet_packet.get_ethertype() {
+        EtherTypes::Ipv4 => parse_echo4_reply(ethernet_packet.payload(), source_mac),
+        EtherTypes::Ipv6 => parse_echo6_reply(ethernet_packet.payload(), source_mac),
+        _ => None,
+    }
+}
+
+// parse the ethernet frame and get the echo reply to an echo request with
+// identifier
+pub fn parse_echo_reply(frame: &[u8], identifier: u16) -> Option<EchoReply> {
+    parse_any_echo_reply(frame).filter(|reply| reply.identifier == identifier)
+}
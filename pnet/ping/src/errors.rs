@@ -0,0 +1,196 @@
+use packet_utils::bytes::*;
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket};
+use pnet::packet::icmp::echo_request::EchoRequestPacket;
+use pnet::packet::icmp::{IcmpPacket, IcmpTypes};
+use pnet::packet::icmpv6::{self, Icmpv6Packet, Icmpv6Types};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::Packet;
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+// icmp error message received from a host in response to an echo request
+pub struct IcmpError {
+    pub source: IpAddr,
+
+    // sequence number of the echo request that caused the error
+    pub sequence: u16,
+
+    // description of the error
+    pub message: String,
+
+    // whether the error is a redirect; the echo request was still forwarded
+    pub redirect: bool,
+}
+
+// get the description of the icmp destination unreachable code
+fn get_unreachable_message(code: u8, next_hop_mtu: u16) -> String {
+    let message = match code {
+        0 => "Destination Net Unreachable",
+        1 => "Destination Host Unreachable",
+        2 => "Destination Protocol Unreachable",
+        3 => "Destination Port Unreachable",
+        4 => return format!("Frag needed and DF set (mtu = {})", next_hop_mtu),
+        5 => "Source Route Failed",
+        6 => "Destination Net Unknown",
+        7 => "Destination Host Unknown",
+        8 => "Source Host Isolated",
+        9 => "Destination Net Prohibited",
+        10 => "Destination Host Prohibited",
+        11 => "Destination Net Unreachable for Type of Service",
+        12 => "Destination Host Unreachable for Type of Service",
+        13 => "Packet filtered",
+        14 => "Precedence Violation",
+        15 => "Precedence Cutoff",
+        _ => return format!("Dest Unreachable, Bad Code: {}", code),
+    };
+    message.to_string()
+}
+
+// get the description of the icmpv6 destination unreachable code
+fn get_unreachable6_message(code: u8) -> String {
+    let message = match code {
+        0 => "Destination unreachable: No route",
+        1 => "Destination unreachable: Administratively prohibited",
+        2 => "Destination unreachable: Beyond scope of source address",
+        3 => "Destination unreachable: Address unreachable",
+        4 => "Destination unreachable: Port unreachable",
+        5 => "Destination unreachable: Source address failed ingress/egress policy",
+        6 => "Destination unreachable: Reject route to destination",
+        _ => return format!("Destination unreachable: Unknown code {}", code),
+    };
+    message.to_string()
+}
+
+// get the sequence number of the echo request with identifier in the
+// original ipv4 packet embedded in an icmp error message
+fn get_original_sequence(original: &[u8], identifier: u16) -> Option<u16> {
+    let ipv4_packet = Ipv4Packet::new(original)?;
+    if ipv4_packet.get_next_level_protocol() != IpNextHeaderProtocols::Icmp {
+        return None;
+    }
+
+    // only the first 8 bytes of the echo request are included
+    let header_length = usize::from(ipv4_packet.get_header_length()) * 4;
+    let echo_packet = EchoRequestPacket::new(original.get(header_length..)?)?;
+    if echo_packet.get_icmp_type() != IcmpTypes::EchoRequest
+        || echo_packet.get_identifier() != identifier
+    {
+        return None;
+    }
+    Some(echo_packet.get_sequence_number())
+}
+
+// get the sequence number of the icmpv6 echo request with identifier in the
+// original ipv6 packet embedded in an icmpv6 error message
+fn get_original_sequence6(original: &[u8], identifier: u16) -> Option<u16> {
+    let ipv6_packet = Ipv6Packet::new(original)?;
+    if ipv6_packet.get_next_header() != IpNextHeaderProtocols::Icmpv6 {
+        return None;
+    }
+    let echo_packet = icmpv6::echo_request::EchoRequestPacket::new(ipv6_packet.payload())?;
+    if echo_packet.get_icmpv6_type() != Icmpv6Types::EchoRequest
+        || echo_packet.get_identifier() != identifier
+    {
+        return None;
+    }
+    Some(echo_packet.get_sequence_number())
+}
+
+// parse the ipv4 packet and get the icmp error message caused by an echo
+// request with identifier
+pub fn parse_icmp4_error(packet: &[u8], identifier: u16) -> Option<IcmpError> {
+    let ipv4_packet = Ipv4Packet::new(packet)?;
+    if ipv4_packet.get_next_level_protocol() != IpNextHeaderProtocols::Icmp {
+        return None;
+    }
+    let icmp_packet = IcmpPacket::new(ipv4_packet.payload())?;
+
+    // the payload starts with 4 bytes of the icmp header that depend on
+    // the type, followed by the original packet
+    let rest = icmp_packet.payload().get(0..4)?;
+    let original = &icmp_packet.payload()[4..];
+    let code = icmp_packet.get_icmp_code().0;
+    let (message, redirect) = match icmp_packet.get_icmp_type() {
+        IcmpTypes::DestinationUnreachable => {
+            let next_hop_mtu = read_be_u16(&rest[2..4]);
+            (get_unreachable_message(code, next_hop_mtu), false)
+        }
+        IcmpTypes::TimeExceeded => match code {
+            0 => ("Time to live exceeded".to_string(), false),
+            1 => ("Frag reassembly time exceeded".to_string(), false),
+            _ => (format!("Time exceeded, Bad Code: {}", code), false),
+        },
+        IcmpTypes::RedirectMessage => {
+            let gateway = Ipv4Addr::new(rest[0], rest[1], rest[2], rest[3]);
+            let message = match code {
+                0 => "Redirect Network",
+                1 => "Redirect Host",
+                2 => "Redirect Type of Service and Network",
+                3 => "Redirect Type of Service and Host",
+                _ => "Redirect, Bad Code",
+            };
+            (format!("{} (New nexthop: {})", message, gateway), true)
+        }
+        _ => return None,
+    };
+    Some(IcmpError {
+        source: IpAddr::V4(ipv4_packet.get_source()),
+        sequence: get_original_sequence(original, identifier)?,
+        message,
+        redirect,
+    })
+}
+
+// parse the icmpv6 message from source and get the icmpv6 error message
+// caused by an echo request with identifier
+pub fn parse_icmp6_message(message: &[u8], source: Ipv6Addr, identifier: u16) -> Option<IcmpError> {
+    let icmp_packet = Icmpv6Packet::new(message)?;
+
+    // the payload starts with 4 bytes of the icmpv6 header that depend on
+    // the type, followed by the original packet
+    let rest = icmp_packet.payload().get(0..4)?;
+    let original = &icmp_packet.payload()[4..];
+    let code = icmp_packet.get_icmpv6_code().0;
+    let message = match icmp_packet.get_icmpv6_type() {
+        Icmpv6Types::DestinationUnreachable => get_unreachable6_message(code),
+        Icmpv6Types::PacketTooBig => {
+            let mtu = read_be_u32(rest);
+            format!("Packet too big: mtu={}", mtu)
+        }
+        Icmpv6Types::TimeExceeded => match code {
+            0 => "Time exceeded: Hop limit".to_string(),
+            1 => "Time exceeded: Defragmentation failure".to_string(),
+            _ => format!("Time exceeded: Unknown code {}", code),
+        },
+        _ => return None,
+    };
+    Some(IcmpError {
+        source: IpAddr::V6(source),
+        sequence: get_original_sequence6(original, identifier)?,
+        message,
+        redirect: false,
+    })
+}
+
+// parse the ipv6 packet and get the icmpv6 error message caused by an echo
+// request with identifier
+fn parse_icmp6_error(packet: &[u8], identifier: u16) -> Option<IcmpError> {
+    let ipv6_packet = Ipv6Packet::new(packet)?;
+    if ipv6_packet.get_next_header() != IpNextHeaderProtocols::Icmpv6 {
+        return None;
+    }
+    parse_icmp6_message(ipv6_packet.payload(), ipv6_packet.get_source(), identifier)
+}
+
+// parse the ethernet frame and get the icmp or icmpv6 error message caused
+// by an echo request with identifier
+pub fn parse_icmp_error(frame: &[u8], identifier: u16) -> Option<IcmpError> {
+    let ethernet_packet = EthernetPacket::new(frame)?;
+    match ethernet_packet.get_ethertype() {
+        EtherTypes::Ipv4 => parse_icmp4_error(ethernet_packet.payload(), identifier),
+        EtherTypes::Ipv6 => parse_icmp6_error(ethernet_packet.payload(), identifier),
+        _ => None,
+    }
+}
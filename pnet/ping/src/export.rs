@@ -0,0 +1,157 @@
+use std::io::{self, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::stats::Statistics;
+
+// time to wait for the connection to the graphite server
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// format of exported measurements: influxdb line protocol over udp or
+// graphite plaintext protocol over tcp
+#[derive(Clone, Copy, Debug)]
+pub enum Format {
+    Influx,
+    Graphite,
+}
+
+// time-series database measurements are exported to
+#[derive(Clone, Debug)]
+pub struct ExportTarget {
+    pub format: Format,
+    pub address: String,
+}
+
+// parse the export target in the format influx://host:port or
+// graphite://host:port
+pub fn parse_export_target(s: &str) -> Result<ExportTarget, String> {
+    let (format, address) = match s.split_once("://") {
+        Some(("influx", address)) => (Format::Influx, address),
+        Some(("graphite", address)) => (Format::Graphite, address),
+        _ => return Err(format!("invalid export target: {}", s)),
+    };
+    if address.is_empty() {
+        return Err(format!("invalid export target: {}", s));
+    }
+    Ok(ExportTarget {
+        format,
+        address: address.to_string(),
+    })
+}
+
+// connection to the time-series database
+enum Connection {
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+}
+
+// exporter of round-trip times and losses to a time-series database; the
+// measurements are named ping for single requests and ping_window for
+// monitoring intervals, in graphite followed by the target and the field
+pub struct Exporter {
+    format: Format,
+    connection: Connection,
+
+    // whether an export failed, so only the first error is reported
+    failed: bool,
+}
+
+impl Exporter {
+    // connect to the export target
+    pub fn new(target: &ExportTarget) -> io::Result<Exporter> {
+        let server = match target.address.to_socket_addrs()?.next() {
+            Some(server) => server,
+            None => return Err(io::ErrorKind::NotFound.into()),
+        };
+        let connection = match target.format {
+            Format::Influx => {
+                let unspecified = match server {
+                    SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                    SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+                };
+                let socket = UdpSocket::bind(SocketAddr::new(unspecified, 0))?;
+                socket.connect(server)?;
+                Connection::Udp(socket)
+            }
+            Format::Graphite => {
+                Connection::Tcp(TcpStream::connect_timeout(&server, CONNECT_TIMEOUT)?)
+            }
+        };
+        Ok(Exporter {
+            format: target.format,
+            connection,
+            failed: false,
+        })
+    }
+
+    // send the fields of measurement of target measured now; influxdb gets
+    // them as one line, graphite as one line per field
+    fn send(&mut self, measurement: &str, target: IpAddr, fields: &[(&str, f64)]) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let lines = match self.format {
+            Format::Influx => {
+                let fields: Vec<String> =
+                    fields.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+                format!(
+                    "{},target={} {} {}\n",
+                    measurement,
+                    target,
+                    fields.join(","),
+                    now.as_nanos()
+                )
+            }
+            Format::Graphite => {
+                // dots and colons separate the parts of metric names
+                let target = target.to_string().replace(['.', ':'], "_");
+                fields
+                    .iter()
+                    .map(|(k, v)| {
+                        format!("{}.{}.{} {} {}\n", measurement, target, k, v, now.as_secs())
+                    })
+                    .collect()
+            }
+        };
+        let result = match &mut self.connection {
+            Connection::Udp(socket) => socket.send(lines.as_bytes()).map(|_| ()),
+            Connection::Tcp(stream) => stream.write_all(lines.as_bytes()),
+        };
+        if let Err(e) = result {
+            if !self.failed {
+                println!("Could not export measurements: {}", e);
+            }
+            self.failed = true;
+        }
+    }
+
+    // export the round-trip time in milliseconds of a reply from target
+    pub fn reply(&mut self, target: IpAddr, rtt: Duration) {
+        let rtt = rtt.as_micros() as f64 / 1000.0;
+        self.send("ping", target, &[("rtt", rtt), ("lost", 0.0)]);
+    }
+
+    // export a request to target without a reply
+    pub fn timeout(&mut self, target: IpAddr) {
+        self.send("ping", target, &[("lost", 1.0)]);
+    }
+
+    // export the loss and the round-trip times of the requests to target
+    // completed in the last monitoring interval
+    pub fn window(&mut self, target: IpAddr, stats: &Statistics) {
+        let mut fields = vec![
+            ("transmitted", stats.get_transmitted() as f64),
+            ("received", stats.get_received() as f64),
+            ("loss", stats.get_loss()),
+        ];
+        if let Some((min, avg, max, mdev)) = stats.get_rtt() {
+            fields.extend([
+                ("rtt_min", min),
+                ("rtt_avg", avg),
+                ("rtt_max", max),
+                ("rtt_mdev", mdev),
+            ]);
+        }
+        self.send("ping_window", target, &fields);
+    }
+}
@@ -0,0 +1,154 @@
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket, MutableEthernetPacket};
+use pnet::packet::ipv4::{self, Ipv4Flags, Ipv4Packet, MutableIpv4Packet};
+use pnet::packet::Packet;
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::net::Ipv4Addr;
+use std::time::{Duration, Instant};
+
+// packet header constants
+const IPV4_SIZE: usize = MutableIpv4Packet::minimum_packet_size();
+const ETHERNET_SIZE: usize = MutableEthernetPacket::minimum_packet_size();
+
+// time after which incomplete packets are dropped
+const REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+// split the ipv4 packet into fragments that fit into mtu; the fragment
+// offset is counted in 8 byte units, so the data of all fragments but the
+// last is a multiple of 8 bytes; the record route and timestamp options
+// are not copied into fragments, so only the first fragment contains the
+// ip options
+pub fn fragment_packet(packet: &[u8], mtu: usize) -> Vec<Vec<u8>> {
+    if packet.len() <= mtu {
+        return vec![packet.to_vec()];
+    }
+    let ipv4_packet = Ipv4Packet::new(packet).unwrap();
+    let header_size = usize::from(ipv4_packet.get_header_length()) * 4;
+    let data = &packet[header_size..];
+    let mut fragments = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let header = match offset {
+            0 => &packet[..header_size],
+            _ => &packet[..IPV4_SIZE],
+        };
+        let size = ((mtu - header.len()) & !7).min(data.len() - offset);
+        let mut fragment = [header, &data[offset..offset + size]].concat();
+        let mut fragment_packet = MutableIpv4Packet::new(&mut fragment).unwrap();
+        fragment_packet.set_header_length(u8::try_from(header.len() / 4).unwrap());
+        fragment_packet.set_total_length(u16::try_from(header.len() + size).unwrap());
+        fragment_packet.set_fragment_offset(u16::try_from(offset / 8).unwrap());
+        if offset + size < data.len() {
+            fragment_packet.set_flags(Ipv4Flags::MoreFragments);
+        }
+        fragment_packet.set_checksum(ipv4::checksum(&fragment_packet.to_immutable()));
+        fragments.push(fragment);
+        offset += size;
+    }
+    fragments
+}
+
+// received fragments of an ipv4 packet
+struct Fragments {
+    received: Instant,
+
+    // ethernet header of the frames and ip header of the first fragment
+    ethernet_header: Vec<u8>,
+    ip_header: Option<Vec<u8>>,
+
+    // data of the fragments by their offset and the size of all data once
+    // the last fragment was received
+    data: BTreeMap<usize, Vec<u8>>,
+    size: Option<usize>,
+}
+
+impl Fragments {
+    // get the data of the packet if the fragments cover all of it
+    fn get_data(&self) -> Option<Vec<u8>> {
+        let size = self.size?;
+        let mut data = Vec::with_capacity(size);
+        for (offset, fragment) in &self.data {
+            if *offset > data.len() {
+                return None;
+            }
+            let end = offset + fragment.len();
+            if end > data.len() {
+                data.extend_from_slice(&fragment[data.len() - offset..]);
+            }
+        }
+        match data.len() == size {
+            true => Some(data),
+            false => None,
+        }
+    }
+}
+
+// reassembler of fragmented ipv4 packets in received ethernet frames
+#[derive(Default)]
+pub struct Reassembler {
+    // fragments by source, destination, identification and protocol
+    packets: HashMap<(Ipv4Addr, Ipv4Addr, u16, u8), Fragments>,
+}
+
+impl Reassembler {
+    // add the ethernet frame; returns the frame if it does not contain a
+    // fragment, a frame with the reassembled packet if the fragment
+    // completes it and none otherwise
+    pub fn add<'a>(&mut self, frame: &'a [u8]) -> Option<Cow<'a, [u8]>> {
+        let ethernet_packet = match EthernetPacket::new(frame) {
+            Some(packet) if packet.get_ethertype() == EtherTypes::Ipv4 => packet,
+            _ => return Some(Cow::Borrowed(frame)),
+        };
+        let ipv4_packet = match Ipv4Packet::new(ethernet_packet.payload()) {
+            Some(packet) => packet,
+            None => return Some(Cow::Borrowed(frame)),
+        };
+        let more_fragments = ipv4_packet.get_flags() & Ipv4Flags::MoreFragments != 0;
+        let offset = usize::from(ipv4_packet.get_fragment_offset()) * 8;
+        if !more_fragments && offset == 0 {
+            return Some(Cow::Borrowed(frame));
+        }
+
+        // the payload of frames can contain padding after the packet
+        let header_size = usize::from(ipv4_packet.get_header_length()) * 4;
+        let total_length = usize::from(ipv4_packet.get_total_length());
+        let fragment = ipv4_packet.packet().get(header_size..total_length)?;
+        self.packets
+            .retain(|_, fragments| fragments.received.elapsed() < REASSEMBLY_TIMEOUT);
+        let key = (
+            ipv4_packet.get_source(),
+            ipv4_packet.get_destination(),
+            ipv4_packet.get_identification(),
+            ipv4_packet.get_next_level_protocol().0,
+        );
+        let fragments = self.packets.entry(key).or_insert_with(|| Fragments {
+            received: Instant::now(),
+            ethernet_header: frame[..ETHERNET_SIZE].to_vec(),
+            ip_header: None,
+            data: BTreeMap::new(),
+            size: None,
+        });
+        if offset == 0 {
+            fragments.ip_header = Some(ipv4_packet.packet()[..header_size].to_vec());
+        }
+        if !more_fragments {
+            fragments.size = Some(offset + fragment.len());
+        }
+        fragments.data.insert(offset, fragment.to_vec());
+        fragments.ip_header.as_ref()?;
+        let data = fragments.get_data()?;
+        let fragments = self.packets.remove(&key).unwrap();
+
+        // rebuild the frame with the ip header of the first fragment
+        // updated for the whole packet
+        let mut packet = [fragments.ip_header.unwrap(), data].concat();
+        let mut reassembled = MutableIpv4Packet::new(&mut packet)?;
+        reassembled.set_total_length(u16::try_from(reassembled.packet().len()).ok()?);
+        reassembled.set_flags(reassembled.get_flags() & !Ipv4Flags::MoreFragments);
+        reassembled.set_fragment_offset(0);
+        reassembled.set_checksum(ipv4::checksum(&reassembled.to_immutable()));
+        Some(Cow::Owned([fragments.ethernet_header, packet].concat()))
+    }
+}
@@ -1,101 +1,777 @@
 extern crate pnet;
 
-use pnet::datalink::Channel;
+mod arp;
+mod arping;
+mod cache;
+mod channel;
+mod config;
+mod echo;
+mod errors;
+mod export;
+mod fragment;
+mod monitor;
+mod multi;
+mod ndp;
+mod options;
+mod output;
+mod probe;
+mod rdns;
+mod stats;
+mod sweep;
+mod timestamp;
+mod vlan;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::interface::*;
+use packet_utils::pacer::TokenBucket;
 use pnet::datalink::MacAddr;
 use pnet::datalink::NetworkInterface;
-use pnet::packet::ethernet::EtherTypes;
-use pnet::packet::ethernet::MutableEthernetPacket;
-use pnet::packet::icmp::echo_request::MutableEchoRequestPacket;
-use pnet::packet::icmp::IcmpTypes;
-use pnet::packet::ip::IpNextHeaderProtocols;
-use pnet::packet::ipv4::MutableIpv4Packet;
-use pnet::packet::{MutablePacket, Packet};
-
-use std::convert::TryFrom;
-use std::net::{IpAddr, Ipv4Addr};
-
-// packet constants
-const ECHO_SIZE: usize = MutableEchoRequestPacket::minimum_packet_size();
-const IPV4_SIZE: usize = MutableIpv4Packet::minimum_packet_size() + ECHO_SIZE;
-const PACKET_SIZE: usize = MutableEthernetPacket::minimum_packet_size() + IPV4_SIZE;
-
-// get default interface
-fn get_default_interface() -> NetworkInterface {
-    let interfaces = pnet::datalink::interfaces();
-    let interface = interfaces
-        .iter()
-        .find(|e| e.is_up() && !e.is_loopback() && !e.ips.is_empty())
-        .unwrap();
-    interface.clone()
+use pnet::datalink::{DataLinkReceiver, DataLinkSender};
+use pnet::ipnetwork::Ipv4Network;
+
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::net::IpAddr;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use cache::NeighborCache;
+use channel::*;
+use config::Config;
+use echo::*;
+use errors::*;
+use fragment::Reassembler;
+use monitor::Monitor;
+use multi::Target;
+use output::Output;
+use probe::InterfaceId;
+use stats::Statistics;
+use sweep::MIN_SWEEP_PREFIX;
+use vlan::VlanTag;
+
+// time to wait for echo replies after sending the last echo request
+const REPLY_TIMEOUT: Duration = Duration::from_secs(1);
+
+// interval in which the send loop checks the reply timeout after sending the
+// last echo request
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// state of a sent echo request
+#[derive(Clone, Copy)]
+enum State {
+    Pending,
+
+    // answered with the round-trip time of the first reply
+    Answered(Duration),
+
+    // answered with an icmp error message
+    Failed,
 }
 
-// get interface ip address
-fn get_interface_ip(interface: &NetworkInterface) -> Ipv4Addr {
-    let ip = interface
-        .ips
-        .iter()
-        .find(|ip| ip.is_ipv4())
-        .map(|ip| match ip.ip() {
-            IpAddr::V4(ip) => ip,
-            _ => unreachable!(),
-        })
-        .unwrap();
-    return ip;
+// echo requests sent to the destination and the statistics of the run,
+// shared between the sending and the receiving thread
+#[derive(Default)]
+struct Requests {
+    // send times and states of echo requests keyed by their sequence number
+    sent: HashMap<u16, (Instant, State)>,
+
+    // sequence numbers of the sent echo requests in send order, until they
+    // are answered or time out
+    pending: VecDeque<u16>,
+
+    stats: Statistics,
+
+    // index of the payload size of the echo requests keyed by their
+    // sequence number and the statistics of each payload size
+    sizes: HashMap<u16, usize>,
+    size_stats: Vec<Statistics>,
+
+    // monitor of the run in monitoring mode
+    monitor: Option<Monitor>,
 }
 
-// create ping/echo request packet
-fn create_ping_packet(interface: &NetworkInterface) -> [u8; PACKET_SIZE] {
+impl Requests {
+    // remove and get the sequence numbers of the pending echo requests
+    // without a reply within the reply timeout; the completed echo requests
+    // are added to the monitor in send order, so outages are not ended by
+    // replies to later echo requests
+    fn expire(&mut self) -> Vec<u16> {
+        let mut expired = Vec::new();
+        while let Some(&sequence) = self.pending.front() {
+            let (sent, state) = match self.sent.get(&sequence) {
+                Some(request) => *request,
+                None => {
+                    self.pending.pop_front();
+                    continue;
+                }
+            };
+            if matches!(state, State::Pending) {
+                if sent.elapsed() < REPLY_TIMEOUT {
+                    break;
+                }
+                expired.push(sequence);
+            }
+            self.pending.pop_front();
+            if let Some(monitor) = &mut self.monitor {
+                match state {
+                    State::Answered(rtt) => monitor.add_reply(rtt),
+                    State::Pending | State::Failed => monitor.add_missed(sequence, sent),
+                }
+            }
+        }
+        expired
+    }
+}
+
+// get the interface configured in config or the default interface
+fn select_interface(config: &Config) -> NetworkInterface {
+    match &config.interface {
+        Some(name) => match get_interface(name) {
+            Some(interface) => interface,
+            None => panic!("Could not find interface {}", name),
+        },
+        None => match get_default_interface() {
+            Some(interface) => interface,
+            None => panic!("Could not find a default interface"),
+        },
+    }
+}
+
+// get the mac address of the next hop of packets to destination with arp or
+// neighbor discovery with the optional vlan tag; broadcasts and multicasts
+// are sent to their mac addresses; returns an error message if there is no
+// route to destination or the mac address could not be resolved
+fn get_destination_mac(
+    tx: &mut dyn DataLinkSender,
+    rx: &mut dyn DataLinkReceiver,
+    interface: &NetworkInterface,
+    destination: IpAddr,
+    cache: &mut NeighborCache,
+    vlan: Option<VlanTag>,
+) -> Result<MacAddr, String> {
+    let source_mac = interface.mac.unwrap();
+    let (next_hop, mac) = match destination {
+        IpAddr::V4(destination) if is_broadcast(interface, destination) => {
+            return Ok(MacAddr::broadcast())
+        }
+        IpAddr::V6(destination) if destination.is_multicast() => {
+            return Ok(ndp::get_multicast_mac(destination))
+        }
+        IpAddr::V4(destination) => {
+            let next_hop = get_next_hop(interface, destination);
+            let mac = next_hop.and_then(|next_hop| {
+                let source = (source_mac, get_interface_ip(interface)?);
+                arp::resolve(tx, rx, source, next_hop, cache, vlan)
+            });
+            (next_hop.map(IpAddr::V4), mac)
+        }
+        IpAddr::V6(destination) => {
+            let next_hop = get_next_hop6(interface, destination);
+            let mac = next_hop.and_then(|next_hop| {
+                let source = (source_mac, get_interface_ipv6(interface, next_hop)?);
+                ndp::resolve(tx, rx, source, next_hop, cache, vlan)
+            });
+            (next_hop.map(IpAddr::V6), mac)
+        }
+    };
+    match (next_hop, mac) {
+        (None, _) => Err(format!("No route to destination {}", destination)),
+        (Some(next_hop), None) => Err(format!("Could not resolve mac address of {}", next_hop)),
+        (_, Some(mac)) => Ok(mac),
+    }
+}
+
+// receive echo replies from destination to the echo requests with
+// identifier and payloads and print them to output until receiving is
+// unset; the payloads are sent with the send time at their start
+fn receive_replies(
+    mut rx: Receiver,
+    destination: IpAddr,
+    (identifier, payloads): (u16, &[Vec<u8>]),
+    requests: &Mutex<Requests>,
+    receiving: &AtomicBool,
+    output: &Output,
+) {
+    while receiving.load(atomic::Ordering::Relaxed) {
+        let reply = match rx.receive(identifier) {
+            Ok(Some(Message::Reply(reply))) => reply,
+            Ok(Some(Message::Error(error))) => {
+                receive_error(error, destination, requests, output);
+                continue;
+            }
+            Ok(None) => continue,
+            Err(e) => match e.kind() {
+                io::ErrorKind::Interrupted => continue,
+                _ => panic!("Error happened {}", e),
+            },
+        };
+        // the round-trip time is measured with the send time in the payload
+        // up to the kernel receive timestamp if there is one, so replies to
+        // requests that are not in the sent requests, e.g., of an earlier
+        // run, are measured as well
+        let received = reply.received.unwrap_or_else(SystemTime::now);
+        let payload_rtt =
+            get_timestamp(&reply.payload).and_then(|sent| received.duration_since(sent).ok());
+
+        // the sent requests are only used to detect duplicates and to count
+        // replies in the statistics; replies with payloads too small for the
+        // send time use the send time of the request
+        let (size, rtt, duplicate) = {
+            let mut requests = requests.lock().unwrap();
+            let size = requests.sizes.get(&reply.sequence).copied();
+            match requests.sent.get_mut(&reply.sequence) {
+                Some((sent, state)) => {
+                    let rtt = payload_rtt.unwrap_or_else(|| sent.elapsed());
+                    let duplicate = !matches!(state, State::Pending);
+                    if !duplicate {
+                        *state = State::Answered(rtt);
+                    }
+                    requests.stats.add_reply(rtt, duplicate);
+                    if let Some(size) = size {
+                        requests.size_stats[size].add_reply(rtt, duplicate);
+                    }
+                    (size, Some(rtt), duplicate)
+                }
+                None => (size, payload_rtt, false),
+            }
+        };
+        let rtt = match rtt {
+            Some(rtt) => rtt,
+            None => continue,
+        };
+
+        // print the reply without holding the lock, so the sender is not
+        // blocked by the output
+        let corrupted = match size {
+            Some(size) => !is_payload_intact(&payloads[size], &reply.payload),
+            None => !payloads
+                .iter()
+                .any(|payload| is_payload_intact(payload, &reply.payload)),
+        };
+        output.echo_reply(destination, &reply, rtt, duplicate, corrupted);
+    }
+}
+
+// handle the icmp error message if it was caused by one of the sent echo
+// requests; except for redirects, the echo request will not be answered, so
+// it is marked as failed without counting a reply
+fn receive_error(
+    error: IcmpError,
+    destination: IpAddr,
+    requests: &Mutex<Requests>,
+    output: &Output,
+) {
+    {
+        let mut requests = requests.lock().unwrap();
+        let (_, state) = match requests.sent.get_mut(&error.sequence) {
+            Some(request) => request,
+            None => return,
+        };
+        if !error.redirect {
+            *state = State::Failed;
+            requests.stats.add_error();
+        }
+    }
+    output.icmp_error(destination, &error);
+}
+
+// create the datalink sender and receiver of echo requests to destination on
+// the interface selected in config; returns an error if the datalink channel
+// cannot be opened
+fn create_datalink_channel(
+    config: &Config,
+    output: &Output,
+    destination: IpAddr,
+) -> io::Result<(Sender, Receiver, bool)> {
+    let interface = select_interface(config);
+    if interface.mac.is_none() {
+        panic!(
+            "Interface {} has no mac address, use --layer4 instead",
+            interface.name
+        );
+    }
+    let (mut tx, mut rx) = create_channel(&interface, READ_TIMEOUT, false)?;
+    output.start(&format!(
+        "Sending echo requests to {} on interface {}",
+        destination, interface.name
+    ));
+
     // get source ip address
-    let source_ip = get_interface_ip(&interface);
-
-    // create echo request packet
-    let mut echo_buffer = [0u8; ECHO_SIZE];
-    let mut echo_packet = MutableEchoRequestPacket::new(&mut echo_buffer).unwrap();
-    echo_packet.set_icmp_type(IcmpTypes::EchoRequest);
-    echo_packet.set_checksum(pnet::util::checksum(echo_packet.packet(), 1));
-
-    // create ipv4 packet
-    let mut ipv4_buffer = [0u8; IPV4_SIZE];
-    let mut ipv4_packet = MutableIpv4Packet::new(&mut ipv4_buffer).unwrap();
-    ipv4_packet.set_version(4);
-    ipv4_packet.set_header_length(5);
-    ipv4_packet.set_total_length(u16::try_from(IPV4_SIZE).unwrap());
-    ipv4_packet.set_ttl(64);
-    ipv4_packet.set_next_level_protocol(IpNextHeaderProtocols::Icmp);
-    ipv4_packet.set_source(source_ip);
-    ipv4_packet.set_destination(Ipv4Addr::BROADCAST);
-    ipv4_packet.set_checksum(pnet::util::checksum(ipv4_packet.packet(), 5));
-    ipv4_packet.set_payload(echo_packet.packet_mut());
-
-    // create ethernet packet
-    let mut ethernet_buffer = [0u8; PACKET_SIZE];
-    let mut ethernet_packet = MutableEthernetPacket::new(&mut ethernet_buffer).unwrap();
-    ethernet_packet.set_source(interface.mac.unwrap());
-    ethernet_packet.set_destination(MacAddr::broadcast());
-    ethernet_packet.set_ethertype(EtherTypes::Ipv4);
-    ethernet_packet.set_payload(ipv4_packet.packet_mut());
-
-    return ethernet_buffer;
-}
-
-// send a ping packet
-fn send_ping() {
-    // get default interface
-    let interface = get_default_interface();
-    println!("Sending echo request on interface {}", interface.name);
-
-    // create channel
-    let (mut tx, _) = match pnet::datalink::channel(&interface, Default::default()) {
-        Ok(Channel::Ethernet(tx, rx)) => (tx, rx),
-        Ok(_) => panic!("Unknown channel type"),
+    let source_ip = match destination {
+        IpAddr::V4(_) => get_interface_ip(&interface).map(IpAddr::V4),
+        IpAddr::V6(destination) => get_interface_ipv6(&interface, destination).map(IpAddr::V6),
+    };
+    let source_ip = match source_ip {
+        Some(source_ip) => source_ip,
+        None if destination.is_ipv4() => {
+            panic!("No ipv4 address on interface {}", interface.name)
+        }
+        None => panic!("No ipv6 address on interface {}", interface.name),
+    };
+
+    // get mac address of the next hop
+    let fields = IpFields::new(config);
+    let mut neighbors = NeighborCache::default();
+    let destination_mac = match get_destination_mac(
+        &mut *tx,
+        &mut *rx,
+        &interface,
+        destination,
+        &mut neighbors,
+        fields.vlan,
+    ) {
+        Ok(mac) => mac,
+        Err(e) => panic!("{}", e),
+    };
+    // more replies can follow the first one from other hosts if the
+    // destination is a broadcast or multicast address
+    let multiple_replies = match destination {
+        IpAddr::V4(destination) => is_broadcast(&interface, destination),
+        IpAddr::V6(destination) => destination.is_multicast(),
+    };
+    let tx = Sender::Datalink {
+        tx,
+        interface,
+        destination_mac,
+        source_ip,
+        fields,
+    };
+    Ok((
+        tx,
+        Receiver::Datalink(rx, Reassembler::default()),
+        multiple_replies,
+    ))
+}
+
+// send ping packets and print the echo replies to output until all packets
+// are sent or running is unset; returns the statistics of the run
+fn send_ping(config: &Config, output: &Output, running: &AtomicBool) -> Statistics {
+    // get destination ip address
+    let destination = match resolve_destination(&config.destination, config.ipv6) {
+        Some(destination) => destination,
+        None => panic!("Could not resolve destination {}", config.destination),
+    };
+    output.resolve(destination);
+
+    // in layer4 mode, the kernel builds the ethernet and ip headers, so
+    // interfaces without a mac address can be used; without the privileges
+    // for raw sockets, echo requests are sent on an unprivileged icmp
+    // datagram socket
+    let channel = match config.layer4 {
+        true => create_transport_channel(destination, config.ttl).map(|(tx, rx)| {
+            output.start(&format!("Sending echo requests to {}", destination));
+            (tx, rx, destination.is_multicast())
+        }),
+        false => create_datalink_channel(config, output, destination),
+    };
+    let (mut tx, rx, multiple_replies) = match channel {
+        Ok(channel) => channel,
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+            output.start(&format!(
+                "No permission for raw sockets, sending echo requests to {} on an \
+                unprivileged icmp socket without interface selection, ip header fields, \
+                ttl of replies and icmp error messages",
+                destination
+            ));
+            let (tx, rx) = create_datagram_channel(destination, config.ttl);
+            (tx, rx, false)
+        }
+        Err(e) => panic!("Error happened {}", e),
+    };
+
+    // send echo requests with the identifier derived from the process id
+    // and incrementing sequence numbers paced by the interval or rate, and
+    // print the echo replies received in a separate thread; there can be
+    // multiple replies to echo requests sent to broadcast addresses; in a
+    // size sweep, the echo requests cycle through the payload sizes
+    let identifier = std::process::id() as u16;
+    let sizes = match config.size_sweep {
+        Some(range) => range.get_sizes(),
+        None => vec![config.size],
+    };
+    let payloads: Vec<Vec<u8>> = sizes
+        .iter()
+        .map(|size| create_payload(*size, config.pattern.as_deref()))
+        .collect();
+    let max_size = sizes.iter().copied().max().unwrap_or(0);
+    let fields = IpFields::new(config);
+    if destination.is_ipv4() && max_size + fields.options.len() > MAX_PAYLOAD_SIZE {
+        panic!(
+            "Payload size {} is too large with ip options, maximum is {}",
+            max_size,
+            MAX_PAYLOAD_SIZE - fields.options.len()
+        );
+    }
+    let mut pacer = match (config.flood, config.rate) {
+        (true, _) => None,
+        (false, Some(rate)) => Some(TokenBucket::new(rate)),
+        (false, None) => Some(TokenBucket::new(1.0 / config.interval)),
+    };
+    let mut sequence: u16 = 0;
+    let mut last_send = Instant::now();
+    let requests = Mutex::new(Requests {
+        size_stats: sizes.iter().map(|_| Statistics::default()).collect(),
+        monitor: config
+            .monitor
+            .map(|m| Monitor::new(Duration::from_secs_f64(m))),
+        ..Default::default()
+    });
+    let receiving = AtomicBool::new(true);
+    std::thread::scope(|s| {
+        s.spawn(|| {
+            let request = (identifier, payloads.as_slice());
+            receive_replies(rx, destination, request, &requests, &receiving, output)
+        });
+        while running.load(atomic::Ordering::Relaxed) {
+            let (transmitted, received) = {
+                let mut requests = requests.lock().unwrap();
+                for sequence in requests.expire() {
+                    output.timeout(destination, sequence.into());
+                }
+                if let Some(monitor) = &mut requests.monitor {
+                    for outage in monitor.take_outages() {
+                        output.outage(destination, &outage);
+                    }
+                    if let Some(window) = monitor.next_window() {
+                        output.window(destination, &window);
+                    }
+                }
+                (
+                    requests.stats.get_transmitted(),
+                    requests.stats.get_received(),
+                )
+            };
+            if config.count.is_some_and(|count| transmitted >= count) {
+                if last_send.elapsed() >= REPLY_TIMEOUT
+                    || (received >= transmitted && !multiple_replies)
+                {
+                    break;
+                }
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            if let Some(pacer) = &mut pacer {
+                if !pacer.try_take() {
+                    std::thread::sleep(pacer.get_wait().min(READ_TIMEOUT));
+                    continue;
+                }
+            }
+
+            let size = transmitted as usize % payloads.len();
+            let mut request_payload = payloads[size].clone();
+            set_timestamp(&mut request_payload, SystemTime::now());
+
+            // add the request before sending it, so the receiving thread
+            // does not miss a fast reply
+            last_send = Instant::now();
+            {
+                let mut requests = requests.lock().unwrap();
+                requests.sent.insert(sequence, (last_send, State::Pending));
+                requests.pending.push_back(sequence);
+                requests.stats.add_request();
+                requests.sizes.insert(sequence, size);
+                requests.size_stats[size].add_request();
+            }
+            match tx.send(destination, identifier, sequence, &request_payload) {
+                Ok(()) => output.request(),
+                Err(e) => {
+                    // failed requests do not count as sent
+                    let mut requests = requests.lock().unwrap();
+                    requests.sent.remove(&sequence);
+                    requests.sizes.remove(&sequence);
+                    requests.stats.remove_request();
+                    requests.size_stats[size].remove_request();
+                    drop(requests);
+                    output.send_error(destination, sequence.into(), &e);
+                }
+            }
+            sequence = sequence.wrapping_add(1);
+        }
+        {
+            let mut requests = requests.lock().unwrap();
+            requests.stats.finish();
+            for stats in &mut requests.size_stats {
+                stats.finish();
+            }
+        }
+        receiving.store(false, atomic::Ordering::Relaxed);
+    });
+    let mut requests = requests.into_inner().unwrap();
+    if let Some(monitor) = &mut requests.monitor {
+        for outage in monitor.finish() {
+            output.outage(destination, &outage);
+        }
+    }
+    if config.size_sweep.is_some() {
+        output.sizes(destination, &sizes, &requests.size_stats);
+    }
+    requests.stats
+}
+
+// send echo requests to every host in the destination network and print
+// the hosts that answered until all hosts are swept or running is unset;
+// returns if the sweep was successful, i.e., at least one host answered
+fn sweep_network(config: &Config, running: &AtomicBool) -> bool {
+    let network: Ipv4Network = match config.destination.parse() {
+        Ok(network) => network,
+        Err(_) => panic!("Invalid network {}", config.destination),
+    };
+    if network.prefix() < MIN_SWEEP_PREFIX {
+        panic!(
+            "Network {} is too large, the prefix must be at least /{}",
+            network, MIN_SWEEP_PREFIX
+        );
+    }
+
+    let interface = select_interface(config);
+    println!("Sweeping {} on interface {}", network, interface.name);
+
+    let (tx, rx) = match create_channel(&interface, READ_TIMEOUT, false) {
+        Ok(channel) => channel,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+    let start = Instant::now();
+    let hosts = sweep::sweep(tx, rx, &interface, network, config, running);
+
+    println!("{:<15}  {:<17}  TIME", "HOST", "MAC");
+    for host in &hosts {
+        println!(
+            "{:<15}  {:<17}  {:.3} ms",
+            host.ip.to_string(),
+            host.mac.to_string(),
+            host.rtt.as_secs_f64() * 1000.0
+        );
+    }
+    println!(
+        "\n{} hosts up, time {}ms",
+        hosts.len(),
+        start.elapsed().as_millis()
+    );
+    !hosts.is_empty()
+}
+
+// get the names of all destinations in config including the ones in the
+// targets file
+fn get_destinations(config: &Config) -> Vec<String> {
+    let mut destinations = vec![config.destination.clone()];
+    destinations.extend(config.destinations.iter().cloned());
+    if let Some(path) = &config.targets {
+        let file = match std::fs::read_to_string(path) {
+            Ok(file) => file,
+            Err(e) => panic!("Could not read targets file {}: {}", path.display(), e),
+        };
+        let lines = file.lines().map(str::trim);
+        destinations.extend(
+            lines
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(String::from),
+        );
+    }
+    destinations
+}
+
+// send echo requests to all destinations concurrently, print their replies
+// and a status table of the destinations until all requests are sent or
+// running is unset; returns if the run was successful
+fn ping_targets(config: &Config, running: &AtomicBool) -> bool {
+    let interface = select_interface(config);
+    let destinations = get_destinations(config);
+    let count = destinations.len();
+    if !config.quiet {
+        println!(
+            "Sending echo requests to {} destinations on interface {}",
+            count, interface.name
+        );
+    }
+
+    // resolve the destinations and the mac addresses of their next hops
+    let (mut tx, mut rx) = match create_channel(&interface, READ_TIMEOUT, false) {
+        Ok(channel) => channel,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+    let vlan = IpFields::new(config).vlan;
+    let mut neighbors = NeighborCache::default();
+    let mut targets = Vec::new();
+    for name in destinations {
+        let ip = match resolve_destination(&name, config.ipv6) {
+            Some(ip) => ip,
+            None => {
+                println!("Could not resolve destination {}", name);
+                continue;
+            }
+        };
+        let mac = match ip {
+            IpAddr::V6(ip) if get_interface_ipv6(&interface, ip).is_none() => {
+                Err(format!("No ipv6 address on interface {}", interface.name))
+            }
+            _ => get_destination_mac(&mut *tx, &mut *rx, &interface, ip, &mut neighbors, vlan),
+        };
+        targets.push(Target {
+            name,
+            ip,
+            mac,
+            stats: Statistics::default(),
+        });
+    }
+
+    let targets = multi::ping(tx, rx, &interface, targets, config, running);
+
+    println!(
+        "\n{:<24}  {:<11}  {:>5}  {:>5}  {:>5}  {:>9}  {:>9}  {:>9}",
+        "TARGET", "STATUS", "SENT", "RECV", "LOSS", "MIN", "AVG", "MAX"
+    );
+    for target in &targets {
+        let status = match &target.mac {
+            Ok(_) if target.stats.get_received() > 0 => "alive",
+            Ok(_) => "unreachable",
+            Err(_) => "unresolved",
+        };
+        let (min, avg, max) = match target.stats.get_rtt() {
+            Some((min, avg, max, _)) => (
+                format!("{:.3}", min),
+                format!("{:.3}", avg),
+                format!("{:.3}", max),
+            ),
+            None => ("-".to_string(), "-".to_string(), "-".to_string()),
+        };
+        println!(
+            "{:<24}  {:<11}  {:>5}  {:>5}  {:>4}%  {:>9}  {:>9}  {:>9}",
+            target.name,
+            status,
+            target.stats.get_transmitted(),
+            target.stats.get_received(),
+            target.stats.get_loss().round(),
+            min,
+            avg,
+            max
+        );
+    }
+    for target in &targets {
+        if let Err(e) = &target.mac {
+            println!("{}: {}", target.name, e);
+        }
+    }
+
+    // with require all, unresolved destinations fail the run
+    match config.require_all {
+        true => targets.len() == count && targets.iter().all(|t| is_successful(config, &t.stats)),
+        false => targets.iter().any(|t| is_successful(config, &t.stats)),
+    }
+}
+
+// send arp requests for the destination and print the replies to output
+// until all requests are sent or running is unset; returns the statistics of
+// the run
+fn send_arp(config: &Config, output: &Output, running: &AtomicBool) -> Statistics {
+    // get destination ipv4 address
+    let destination = match resolve_destination(&config.destination, false) {
+        Some(IpAddr::V4(destination)) => destination,
+        Some(IpAddr::V6(_)) | None => {
+            panic!("Could not resolve destination {}", config.destination)
+        }
+    };
+
+    let interface = select_interface(config);
+    if get_next_hop(&interface, destination) != Some(destination) {
+        output.start(&format!(
+            "Destination {} is not on the local network",
+            destination
+        ));
+    }
+    output.start(&format!(
+        "Sending arp requests for {} on interface {}",
+        destination, interface.name
+    ));
+
+    let (tx, rx) = match create_channel(&interface, READ_TIMEOUT, false) {
+        Ok(channel) => channel,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+    arping::arping((tx, rx), &interface, destination, config, output, running)
+}
+
+// send extended echo requests that probe interface on the destination and
+// print the replies to output until all requests are sent or running is
+// unset; returns the statistics of the run
+fn send_probe(
+    config: &Config,
+    interface: &InterfaceId,
+    output: &Output,
+    running: &AtomicBool,
+) -> Statistics {
+    let destination = match resolve_destination(&config.destination, config.ipv6) {
+        Some(destination) => destination,
+        None => panic!("Could not resolve destination {}", config.destination),
+    };
+    let channel = match open_transport_channel(destination, config.ttl) {
+        Ok(channel) => channel,
         Err(e) => panic!("Error happened {}", e),
     };
+    output.start(&format!(
+        "Sending extended echo requests for interface {} to {}",
+        interface, destination
+    ));
+    probe::probe(channel, destination, interface, config, output, running)
+}
 
-    // send packet
-    let ethernet_buffer = create_ping_packet(&interface);
-    tx.send_to(&ethernet_buffer, None).unwrap().unwrap();
+// check if the requests with stats were successful: at least one or, with
+// require all in config, all of them were answered
+fn is_successful(config: &Config, stats: &Statistics) -> bool {
+    match config.require_all {
+        true => stats.get_transmitted() > 0 && stats.get_received() == stats.get_transmitted(),
+        false => stats.get_received() > 0,
+    }
 }
 
 fn main() {
-    send_ping();
+    let config = Config::parse();
+
+    // stop sending echo requests on ctrl-c and print the statistics; exit
+    // immediately on a second ctrl-c
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            if !running.swap(false, atomic::Ordering::Relaxed) {
+                std::process::exit(1);
+            }
+        }) {
+            panic!("Error happened {}", e);
+        }
+    }
+
+    if config.sweep {
+        if !sweep_network(&config, &running) {
+            std::process::exit(1);
+        }
+        return;
+    }
+    if !config.destinations.is_empty() || config.targets.is_some() {
+        if !ping_targets(&config, &running) {
+            std::process::exit(1);
+        }
+        return;
+    }
+    let output = Output::new(&config);
+    let stats = match (config.arp, &config.probe) {
+        (true, _) => send_arp(&config, &output, &running),
+        (false, Some(interface)) => send_probe(&config, interface, &output, &running),
+        (false, None) => send_ping(&config, &output, &running),
+    };
+    output.summary(&config.destination, &stats);
+    if !is_successful(&config, &stats) {
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,103 @@
+use std::time::{Duration, Instant};
+
+use crate::stats::Statistics;
+
+// interval of consecutive echo requests without a reply
+pub struct Outage {
+    // sequence numbers of the first and last missed echo requests
+    pub first: u16,
+    pub last: u16,
+
+    // number of missed echo requests
+    pub missed: u64,
+
+    // send times of the first and last missed echo requests
+    pub start: Instant,
+    pub end: Instant,
+}
+
+impl Outage {
+    // get the time between sending the first and the last missed echo
+    // request
+    pub fn get_duration(&self) -> Duration {
+        self.end.duration_since(self.start)
+    }
+}
+
+// monitor of a continuous run that collects the statistics of the echo
+// requests completed in the current interval and the outages
+pub struct Monitor {
+    interval: Duration,
+    window_start: Instant,
+    window: Statistics,
+    outage: Option<Outage>,
+
+    // outages that ended since they were last taken
+    ended: Vec<Outage>,
+}
+
+impl Monitor {
+    pub fn new(interval: Duration) -> Monitor {
+        Monitor {
+            interval,
+            window_start: Instant::now(),
+            window: Statistics::default(),
+            outage: None,
+            ended: Vec::new(),
+        }
+    }
+
+    // count an answered echo request with its round-trip time; it ends the
+    // current outage
+    pub fn add_reply(&mut self, rtt: Duration) {
+        self.window.add_request();
+        self.window.add_reply(rtt, false);
+        if let Some(outage) = self.outage.take() {
+            self.ended.push(outage);
+        }
+    }
+
+    // count echo request sequence sent at time sent as missed
+    pub fn add_missed(&mut self, sequence: u16, sent: Instant) {
+        self.window.add_request();
+        match &mut self.outage {
+            Some(outage) => {
+                outage.last = sequence;
+                outage.missed += 1;
+                outage.end = sent;
+            }
+            None => {
+                self.outage = Some(Outage {
+                    first: sequence,
+                    last: sequence,
+                    missed: 1,
+                    start: sent,
+                    end: sent,
+                })
+            }
+        }
+    }
+
+    // remove and get the ended outages
+    pub fn take_outages(&mut self) -> Vec<Outage> {
+        std::mem::take(&mut self.ended)
+    }
+
+    // get the statistics of the current interval and start the next one if
+    // the interval is over
+    pub fn next_window(&mut self) -> Option<Statistics> {
+        if self.window_start.elapsed() < self.interval {
+            return None;
+        }
+        self.window.finish();
+        self.window_start = Instant::now();
+        Some(std::mem::take(&mut self.window))
+    }
+
+    // end the run and get the outages that were not taken yet including the
+    // one that lasted until its end
+    pub fn finish(&mut self) -> Vec<Outage> {
+        self.ended.extend(self.outage.take());
+        self.take_outages()
+    }
+}
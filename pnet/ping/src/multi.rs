@@ -0,0 +1,209 @@
+use packet_utils::interface::*;
+use packet_utils::pacer::TokenBucket;
+use pnet::datalink::{DataLinkReceiver, DataLinkSender, MacAddr, NetworkInterface};
+
+use std::collections::HashMap;
+use std::io;
+use std::net::IpAddr;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::config::Config;
+use crate::echo::*;
+use crate::fragment::Reassembler;
+use crate::stats::Statistics;
+
+// time to wait for echo replies after sending the last round of echo
+// requests
+const REPLY_TIMEOUT: Duration = Duration::from_secs(1);
+
+// interval in which the send loop checks if it should stop while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// destination of echo requests with the statistics of its echo replies
+pub struct Target {
+    pub name: String,
+    pub ip: IpAddr,
+
+    // mac address of the next hop or why it is unknown; echo requests are
+    // only sent to targets with a known next hop
+    pub mac: Result<MacAddr, String>,
+
+    pub stats: Statistics,
+}
+
+// targets and the echo requests sent to them, shared between the sending
+// and the receiving thread
+struct Probes {
+    targets: Vec<Target>,
+
+    // send times of echo requests and whether they were answered, keyed by
+    // the index of their target and their sequence number
+    sent: HashMap<(usize, u16), (Instant, bool)>,
+}
+
+impl Probes {
+    // check if all sent echo requests were answered
+    fn is_answered(&self) -> bool {
+        self.sent.values().all(|(_, answered)| *answered)
+    }
+}
+
+// receive echo replies to the echo requests sent to the targets until
+// receiving is unset; each target uses its own identifier starting at the
+// base identifier, so replies are assigned to targets by their identifier;
+// replies are not printed in quiet mode
+fn receive_replies(
+    mut rx: Box<dyn DataLinkReceiver>,
+    base_identifier: u16,
+    probes: &Mutex<Probes>,
+    receiving: &AtomicBool,
+    quiet: bool,
+) {
+    let mut fragments = Reassembler::default();
+    while receiving.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("Error happened {}", e),
+            },
+        };
+        let reply = match fragments.add(frame).and_then(|f| parse_any_echo_reply(&f)) {
+            Some(reply) => reply,
+            None => continue,
+        };
+        let index = usize::from(reply.identifier.wrapping_sub(base_identifier));
+        let mut probes = probes.lock().unwrap();
+        if probes
+            .targets
+            .get(index)
+            .is_none_or(|t| t.ip != reply.source)
+        {
+            continue;
+        }
+        let (sent, answered) = match probes.sent.get_mut(&(index, reply.sequence)) {
+            Some(request) => request,
+            None => continue,
+        };
+        let rtt = get_timestamp(&reply.payload)
+            .and_then(|sent| SystemTime::now().duration_since(sent).ok())
+            .unwrap_or_else(|| sent.elapsed());
+        let duplicate = *answered;
+        *answered = true;
+        let target = &mut probes.targets[index];
+        target.stats.add_reply(rtt, duplicate);
+        if quiet {
+            continue;
+        }
+        println!(
+            "{} : [{}], {} bytes, {:.3} ms{}",
+            target.name,
+            reply.sequence,
+            reply.size,
+            rtt.as_secs_f64() * 1000.0,
+            if duplicate { " (DUP!)" } else { "" },
+        );
+    }
+}
+
+// send rounds of echo requests with the payload and ip header fields of
+// config to all targets with a known next hop concurrently and get the
+// targets with the statistics of their replies; rounds are sent with the
+// interval of config until the count of config is reached or running is
+// unset
+pub fn ping(
+    mut tx: Box<dyn DataLinkSender>,
+    rx: Box<dyn DataLinkReceiver>,
+    interface: &NetworkInterface,
+    targets: Vec<Target>,
+    config: &Config,
+    running: &AtomicBool,
+) -> Vec<Target> {
+    let payload = create_payload(config.size, config.pattern.as_deref());
+    let fields = IpFields::new(config);
+    let base_identifier = std::process::id() as u16;
+    let probes = Mutex::new(Probes {
+        targets,
+        sent: HashMap::new(),
+    });
+    let receiving = AtomicBool::new(true);
+    std::thread::scope(|s| {
+        s.spawn(|| receive_replies(rx, base_identifier, &probes, &receiving, config.quiet));
+        let mut pacer = TokenBucket::new(1.0 / config.interval);
+        let mut rounds = 0;
+        let mut last_send = Instant::now();
+        while running.load(atomic::Ordering::Relaxed) {
+            if config.count.is_some_and(|count| rounds >= count) {
+                if last_send.elapsed() >= REPLY_TIMEOUT || probes.lock().unwrap().is_answered() {
+                    break;
+                }
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            if !pacer.try_take() {
+                std::thread::sleep(pacer.get_wait().min(POLL_INTERVAL));
+                continue;
+            }
+
+            // send one echo request to every target with the round as
+            // sequence number
+            let sequence = rounds as u16;
+            let mut probes = probes.lock().unwrap();
+            for index in 0..probes.targets.len() {
+                let target = &mut probes.targets[index];
+                let destination_mac = match target.mac {
+                    Ok(mac) => mac,
+                    Err(_) => continue,
+                };
+                let identifier = base_identifier.wrapping_add(index as u16);
+                let mut request_payload = payload.clone();
+                set_timestamp(&mut request_payload, SystemTime::now());
+                let frames = match target.ip {
+                    IpAddr::V4(ip) => match get_interface_ip(interface) {
+                        Some(source_ip) => create_ping_packet(
+                            (interface, source_ip),
+                            ip,
+                            destination_mac,
+                            identifier,
+                            sequence,
+                            &request_payload,
+                            &fields,
+                        ),
+                        None => continue,
+                    },
+                    IpAddr::V6(ip) => match get_interface_ipv6(interface, ip) {
+                        Some(source_ip) => vec![create_ping6_packet(
+                            (interface.mac.unwrap(), source_ip),
+                            ip,
+                            destination_mac,
+                            identifier,
+                            sequence,
+                            &request_payload,
+                            &fields,
+                        )],
+                        None => continue,
+                    },
+                };
+                let sent = frames
+                    .iter()
+                    .try_for_each(|frame| tx.send_to(frame, None).unwrap());
+                if let Err(e) = sent {
+                    println!("Could not send echo request to {}: {}", target.name, e);
+                    continue;
+                }
+                target.stats.add_request();
+                last_send = Instant::now();
+                probes.sent.insert((index, sequence), (last_send, false));
+            }
+            rounds += 1;
+        }
+        receiving.store(false, atomic::Ordering::Relaxed);
+    });
+    let mut targets = probes.into_inner().unwrap().targets;
+    for target in &mut targets {
+        target.stats.finish();
+    }
+    targets
+}
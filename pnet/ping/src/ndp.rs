@@ -0,0 +1,156 @@
+use pnet::datalink::{DataLinkReceiver, DataLinkSender, MacAddr};
+use pnet::packet::ethernet::EtherTypes;
+use pnet::packet::ethernet::{EthernetPacket, MutableEthernetPacket};
+use pnet::packet::icmpv6::ndp::{
+    MutableNeighborSolicitPacket, NdpOptionTypes, NeighborAdvertPacket, NeighborSolicitPacket,
+};
+use pnet::packet::icmpv6::{self, Icmpv6Packet, Icmpv6Types};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv6::{Ipv6Packet, MutableIpv6Packet};
+use pnet::packet::{MutablePacket, Packet};
+
+use std::convert::TryFrom;
+use std::io;
+use std::net::{IpAddr, Ipv6Addr};
+use std::time::{Duration, Instant};
+
+use crate::cache::NeighborCache;
+use crate::vlan::*;
+
+// packet constants; the neighbor solicitation contains the source
+// link-layer address option
+const OPTION_SIZE: usize = 8;
+const SOLICIT_SIZE: usize = NeighborSolicitPacket::minimum_packet_size() + OPTION_SIZE;
+const IPV6_SIZE: usize = MutableIpv6Packet::minimum_packet_size() + SOLICIT_SIZE;
+const PACKET_SIZE: usize = MutableEthernetPacket::minimum_packet_size() + IPV6_SIZE;
+
+// number of neighbor solicitations sent before giving up and the time to
+// wait for an advertisement after each of them
+const NDP_RETRIES: usize = 3;
+const NDP_TIMEOUT: Duration = Duration::from_secs(1);
+
+// get the mac address of the ipv6 multicast address ip
+pub fn get_multicast_mac(ip: Ipv6Addr) -> MacAddr {
+    let octets = ip.octets();
+    MacAddr::new(0x33, 0x33, octets[12], octets[13], octets[14], octets[15])
+}
+
+// get the solicited-node multicast address of ip
+fn get_solicited_node(ip: Ipv6Addr) -> Ipv6Addr {
+    let octets = ip.octets();
+    let mut solicited = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 1, 0xff00, 0).octets();
+    solicited[13..].copy_from_slice(&octets[13..]);
+    Ipv6Addr::from(solicited)
+}
+
+// create neighbor solicitation packet for target ip address
+fn create_solicit_packet(
+    source_mac: MacAddr,
+    source_ip: Ipv6Addr,
+    target_ip: Ipv6Addr,
+) -> [u8; PACKET_SIZE] {
+    let destination = get_solicited_node(target_ip);
+
+    // create neighbor solicitation packet with source link-layer address
+    let mut solicit_buffer = [0u8; SOLICIT_SIZE];
+    let mut solicit_packet = MutableNeighborSolicitPacket::new(&mut solicit_buffer).unwrap();
+    solicit_packet.set_icmpv6_type(Icmpv6Types::NeighborSolicit);
+    solicit_packet.set_target_addr(target_ip);
+    let option = &mut solicit_packet.packet_mut()[SOLICIT_SIZE - OPTION_SIZE..];
+    option[0] = NdpOptionTypes::SourceLLAddr.0;
+    option[1] = 1;
+    option[2..].copy_from_slice(&source_mac.octets());
+    let checksum = icmpv6::checksum(
+        &Icmpv6Packet::new(solicit_packet.packet()).unwrap(),
+        &source_ip,
+        &destination,
+    );
+    solicit_packet.set_checksum(checksum);
+
+    // create ipv6 packet; neighbor discovery requires a hop limit of 255
+    let mut ipv6_buffer = [0u8; IPV6_SIZE];
+    let mut ipv6_packet = MutableIpv6Packet::new(&mut ipv6_buffer).unwrap();
+    ipv6_packet.set_version(6);
+    ipv6_packet.set_payload_length(u16::try_from(SOLICIT_SIZE).unwrap());
+    ipv6_packet.set_next_header(IpNextHeaderProtocols::Icmpv6);
+    ipv6_packet.set_hop_limit(255);
+    ipv6_packet.set_source(source_ip);
+    ipv6_packet.set_destination(destination);
+    ipv6_packet.set_payload(solicit_packet.packet_mut());
+
+    // create ethernet packet
+    let mut ethernet_buffer = [0u8; PACKET_SIZE];
+    let mut ethernet_packet = MutableEthernetPacket::new(&mut ethernet_buffer).unwrap();
+    ethernet_packet.set_source(source_mac);
+    ethernet_packet.set_destination(get_multicast_mac(destination));
+    ethernet_packet.set_ethertype(EtherTypes::Ipv6);
+    ethernet_packet.set_payload(ipv6_packet.packet_mut());
+
+    ethernet_buffer
+}
+
+// parse the ethernet frame and get the mac address in the neighbor
+// advertisement for target ip address; if the advertisement does not
+// contain the target link-layer address, the source of the frame is used
+fn parse_advert_packet(frame: &[u8], target_ip: Ipv6Addr) -> Option<MacAddr> {
+    let ethernet_packet = EthernetPacket::new(frame)?;
+    if ethernet_packet.get_ethertype() != EtherTypes::Ipv6 {
+        return None;
+    }
+    let ipv6_packet = Ipv6Packet::new(ethernet_packet.payload())?;
+    if ipv6_packet.get_next_header() != IpNextHeaderProtocols::Icmpv6 {
+        return None;
+    }
+    let advert_packet = NeighborAdvertPacket::new(ipv6_packet.payload())?;
+    if advert_packet.get_icmpv6_type() != Icmpv6Types::NeighborAdvert
+        || advert_packet.get_target_addr() != target_ip
+    {
+        return None;
+    }
+    let mac = advert_packet
+        .get_options_iter()
+        .filter(|option| option.get_option_type() == NdpOptionTypes::TargetLLAddr)
+        .find_map(|option| match option.payload().get(..6)? {
+            [a, b, c, d, e, f] => Some(MacAddr::new(*a, *b, *c, *d, *e, *f)),
+            _ => None,
+        });
+    Some(mac.unwrap_or_else(|| ethernet_packet.get_source()))
+}
+
+// resolve the mac address of target ip address with neighbor solicitations
+// sent from source mac and ip address with the optional vlan tag; resolved
+// addresses are added to cache
+pub fn resolve(
+    tx: &mut dyn DataLinkSender,
+    rx: &mut dyn DataLinkReceiver,
+    source: (MacAddr, Ipv6Addr),
+    target_ip: Ipv6Addr,
+    cache: &mut NeighborCache,
+    vlan: Option<VlanTag>,
+) -> Option<MacAddr> {
+    if let Some(mac) = cache.get(IpAddr::V6(target_ip)) {
+        return Some(mac);
+    }
+    let ethernet_buffer = insert_vlan_tag(
+        create_solicit_packet(source.0, source.1, target_ip).to_vec(),
+        vlan,
+    );
+    for _ in 0..NDP_RETRIES {
+        tx.send_to(&ethernet_buffer, None).unwrap().unwrap();
+        let sent = Instant::now();
+        while sent.elapsed() < NDP_TIMEOUT {
+            let frame = match rx.next() {
+                Ok(frame) => frame,
+                Err(e) => match e.kind() {
+                    io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                    _ => panic!("Error happened {}", e),
+                },
+            };
+            if let Some(mac) = parse_advert_packet(frame, target_ip) {
+                cache.insert(IpAddr::V6(target_ip), mac);
+                return Some(mac);
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,161 @@
+use clap::ValueEnum;
+
+use packet_utils::bytes::read_be_u32;
+
+use std::fmt;
+use std::net::Ipv4Addr;
+
+// ipv4 option types
+const OPTION_EOL: u8 = 0;
+const OPTION_NOP: u8 = 1;
+const OPTION_RR: u8 = 7;
+const OPTION_TS: u8 = 68;
+
+// maximum size of the options in the ipv4 header
+pub const MAX_OPTIONS_SIZE: usize = 40;
+
+// timestamp option flags
+const TS_ONLY: u8 = 0;
+const TS_AND_ADDR: u8 = 1;
+const TS_PRESPEC: u8 = 3;
+
+// contents of the timestamp ip option
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum TimestampMode {
+    // timestamps only
+    #[value(name = "tsonly")]
+    TsOnly,
+
+    // addresses and timestamps
+    #[value(name = "tsandaddr")]
+    TsAndAddr,
+}
+
+// create the ipv4 record route option with room for as many addresses as
+// fit into the ipv4 header; the option is padded to a multiple of 4 bytes
+pub fn create_record_route() -> Vec<u8> {
+    let mut option = vec![0u8; MAX_OPTIONS_SIZE];
+    option[0] = OPTION_RR;
+    option[1] = (MAX_OPTIONS_SIZE - 1) as u8;
+    option[2] = 4;
+    option
+}
+
+// create the ipv4 timestamp option in mode with room for as many entries as
+// fit into the ipv4 header
+pub fn create_timestamp(mode: TimestampMode) -> Vec<u8> {
+    let (flags, length) = match mode {
+        TimestampMode::TsOnly => (TS_ONLY, MAX_OPTIONS_SIZE),
+        TimestampMode::TsAndAddr => (TS_AND_ADDR, 4 + 4 * 8),
+    };
+    let mut option = vec![0u8; length];
+    option[0] = OPTION_TS;
+    option[1] = length as u8;
+    option[2] = 5;
+    option[3] = flags;
+    option
+}
+
+// ipv4 option decoded from a received packet
+pub enum IpOption {
+    // addresses recorded by the hops
+    RecordRoute(Vec<Ipv4Addr>),
+
+    // timestamps in milliseconds since midnight utc with the addresses of
+    // the hops if recorded, and the number of hops that could not add a
+    // timestamp
+    Timestamp(Vec<(Option<Ipv4Addr>, u32)>, u8),
+}
+
+// get the ipv4 addresses in data
+fn get_addresses(data: &[u8]) -> Vec<Ipv4Addr> {
+    data.chunks_exact(4)
+        .map(|a| Ipv4Addr::new(a[0], a[1], a[2], a[3]))
+        .collect()
+}
+
+// parse the record route or timestamp option in option; the pointer in
+// the option points behind the last recorded entry
+fn parse_option(option: &[u8]) -> Option<IpOption> {
+    let pointer = usize::from(*option.get(2)?).clamp(1, option.len() + 1);
+    match option[0] {
+        OPTION_RR => {
+            let recorded = option.get(3..pointer - 1)?;
+            Some(IpOption::RecordRoute(get_addresses(recorded)))
+        }
+        OPTION_TS => {
+            let overflow = option.get(3)? >> 4;
+            let recorded = option.get(4..pointer - 1)?;
+            let timestamps = match option[3] & 0x0f {
+                TS_ONLY => recorded
+                    .chunks_exact(4)
+                    .map(|t| (None, read_be_u32(t)))
+                    .collect(),
+                TS_AND_ADDR | TS_PRESPEC => recorded
+                    .chunks_exact(8)
+                    .map(|e| {
+                        let address = Ipv4Addr::new(e[0], e[1], e[2], e[3]);
+                        (Some(address), read_be_u32(&e[4..8]))
+                    })
+                    .collect(),
+                _ => return None,
+            };
+            Some(IpOption::Timestamp(timestamps, overflow))
+        }
+        _ => None,
+    }
+}
+
+// parse the record route and timestamp options in the options of an ipv4
+// header; other options are skipped
+pub fn parse_options(options: &[u8]) -> Vec<IpOption> {
+    let mut parsed = Vec::new();
+    let mut i = 0;
+    while i < options.len() {
+        let length = match options[i] {
+            OPTION_EOL => break,
+            OPTION_NOP => 1,
+            _ => match options.get(i + 1) {
+                Some(length) if *length >= 2 && i + usize::from(*length) <= options.len() => {
+                    usize::from(*length)
+                }
+                _ => break,
+            },
+        };
+        if let Some(option) = parse_option(&options[i..i + length]) {
+            parsed.push(option);
+        }
+        i += length;
+    }
+    parsed
+}
+
+impl fmt::Display for IpOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IpOption::RecordRoute(addresses) => {
+                write!(f, "RR:")?;
+                for (i, address) in addresses.iter().enumerate() {
+                    let separator = if i == 0 { "" } else { "\n" };
+                    write!(f, "{}\t{}", separator, address)?;
+                }
+                Ok(())
+            }
+            IpOption::Timestamp(timestamps, overflow) => {
+                write!(f, "TS:")?;
+                for (i, (address, timestamp)) in timestamps.iter().enumerate() {
+                    let separator = if i == 0 { "" } else { "\n" };
+                    write!(f, "{}\t", separator)?;
+                    if let Some(address) = address {
+                        write!(f, "{}\t", address)?;
+                    }
+                    write!(f, "{} ms", timestamp)?;
+                }
+                if *overflow > 0 {
+                    write!(f, "\n\t({} hops not recorded)", overflow)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,463 @@
+use packet_utils::json::json_string;
+use pnet::datalink::MacAddr;
+
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::echo::EchoReply;
+use crate::errors::IcmpError;
+use crate::export::Exporter;
+use crate::monitor::Outage;
+use crate::probe::ProbeReply;
+use crate::rdns::Resolver;
+use crate::stats::Statistics;
+
+// get the round-trip time in milliseconds
+fn get_millis(rtt: Duration) -> f64 {
+    rtt.as_secs_f64() * 1000.0
+}
+
+// get the round-trip times in stats as json object or null without replies
+fn json_rtt(stats: &Statistics) -> String {
+    match stats.get_rtt() {
+        Some((min, avg, max, mdev)) => format!(
+            "{{\"min\":{:.3},\"avg\":{:.3},\"max\":{:.3},\"mdev\":{:.3}}}",
+            min, avg, max, mdev
+        ),
+        None => "null".to_string(),
+    }
+}
+
+// printer of the results of the ping tool as text or as one json object
+// per line; in flood mode, text output only shows a dot for each request
+// and removes it for each reply; with a resolver, the host names of the
+// sources of replies are shown; in quiet mode, only the summary and the
+// statistics of monitoring intervals and outages are printed; with an
+// exporter, round-trip times and losses are also sent to a time-series
+// database
+pub struct Output {
+    json: bool,
+    flood: bool,
+    quiet: bool,
+    resolver: Option<Resolver>,
+    exporter: Option<Mutex<Exporter>>,
+}
+
+impl Output {
+    pub fn new(config: &Config) -> Output {
+        Output {
+            json: config.json,
+            flood: config.flood,
+            quiet: config.quiet,
+            resolver: config.rdns.then(Resolver::new),
+            exporter: config
+                .export
+                .as_ref()
+                .map(|target| match Exporter::new(target) {
+                    Ok(exporter) => Mutex::new(exporter),
+                    Err(e) => panic!("Could not connect to {}: {}", target.address, e),
+                }),
+        }
+    }
+
+    // export measurements with the exporter if there is one
+    fn export(&self, f: impl FnOnce(&mut Exporter)) {
+        if let Some(exporter) = &self.exporter {
+            f(&mut exporter.lock().unwrap());
+        }
+    }
+
+    // look up the host name of ip and wait for it if reverse lookups are
+    // enabled, so it is known before the first reply
+    pub fn resolve(&self, ip: IpAddr) {
+        if let Some(resolver) = &self.resolver {
+            resolver.resolve(ip);
+        }
+    }
+
+    // get the host name of ip if reverse lookups are enabled and it is known
+    fn get_name(&self, ip: IpAddr) -> Option<String> {
+        self.resolver.as_ref()?.get_name(ip)
+    }
+
+    // get the source ip address with its host name for text output
+    fn format_source(&self, ip: IpAddr) -> String {
+        match self.get_name(ip) {
+            Some(name) => format!("{} ({})", name, ip),
+            None => ip.to_string(),
+        }
+    }
+
+    // get the host name field of the source ip address for json output;
+    // empty if reverse lookups are disabled
+    fn json_name(&self, ip: IpAddr) -> String {
+        if self.resolver.is_none() {
+            return String::new();
+        }
+        match self.get_name(ip) {
+            Some(name) => format!(",\"name\":{}", json_string(&name)),
+            None => ",\"name\":null".to_string(),
+        }
+    }
+
+    // print the flood mode progress character c
+    fn print_flood(&self, c: char) {
+        print!("{}", c);
+        io::stdout().flush().unwrap();
+    }
+
+    // print the text about the start of the run; there is no json object
+    // for it
+    pub fn start(&self, text: &str) {
+        if !self.json && !self.quiet {
+            println!("{}", text);
+        }
+    }
+
+    // print that a request was sent
+    pub fn request(&self) {
+        if self.flood && !self.json && !self.quiet {
+            self.print_flood('.');
+        }
+    }
+
+    // print that sending request sequence to target failed with error
+    pub fn send_error(&self, target: IpAddr, sequence: u64, error: &io::Error) {
+        if self.json {
+            println!(
+                "{{\"type\":\"probe\",\"target\":\"{}\",\"seq\":{},\"result\":\"error\",\
+                \"error\":{}}}",
+                target,
+                sequence,
+                json_string(&error.to_string())
+            );
+            return;
+        }
+        println!("Could not send request: {}", error);
+    }
+
+    // print that request sequence to target was not answered in time; only
+    // printed as json
+    pub fn timeout(&self, target: IpAddr, sequence: u64) {
+        self.export(|exporter| exporter.timeout(target));
+        if self.json && !self.quiet {
+            println!(
+                "{{\"type\":\"probe\",\"target\":\"{}\",\"seq\":{},\"result\":\"timeout\"}}",
+                target, sequence
+            );
+        }
+    }
+
+    // print the echo reply to target with its round-trip time; duplicate
+    // replies, replies with a different payload than the request and
+    // replies with a wrong checksum are marked
+    pub fn echo_reply(
+        &self,
+        target: IpAddr,
+        reply: &EchoReply,
+        rtt: Duration,
+        duplicate: bool,
+        corrupted: bool,
+    ) {
+        if !duplicate {
+            self.export(|exporter| exporter.reply(target, rtt));
+        }
+        if self.quiet {
+            return;
+        }
+        let result = match (duplicate, corrupted, reply.checksum_valid) {
+            (_, _, false) => "bad_checksum",
+            (_, true, true) => "corrupted",
+            (true, false, true) => "duplicate",
+            (false, false, true) => "reply",
+        };
+        if self.json {
+            println!(
+                "{{\"type\":\"probe\",\"target\":\"{}\",\"seq\":{},\"result\":\"{}\",\
+                \"source\":\"{}\"{},\"size\":{},\"ttl\":{},\"rtt\":{:.3}}}",
+                target,
+                reply.sequence,
+                result,
+                reply.source,
+                self.json_name(reply.source),
+                reply.size,
+                match reply.ttl {
+                    Some(ttl) => ttl.to_string(),
+                    None => "null".to_string(),
+                },
+                get_millis(rtt)
+            );
+            return;
+        }
+        if self.flood {
+            if !duplicate {
+                self.print_flood('\x08');
+            }
+            return;
+        }
+        println!(
+            "{} bytes from {}: icmp_seq={}{} time={:.3} ms{}{}{}",
+            reply.size,
+            self.format_source(reply.source),
+            reply.sequence,
+            match reply.ttl {
+                Some(ttl) => format!(" ttl={}", ttl),
+                None => String::new(),
+            },
+            get_millis(rtt),
+            if duplicate { " (DUP!)" } else { "" },
+            if corrupted { " (BAD PAYLOAD)" } else { "" },
+            if reply.checksum_valid {
+                ""
+            } else {
+                " (BAD CHECKSUM!)"
+            },
+        );
+        for option in &reply.options {
+            println!("{}", option);
+        }
+    }
+
+    // print the icmp error message caused by a request to target
+    pub fn icmp_error(&self, target: IpAddr, error: &IcmpError) {
+        if self.quiet {
+            return;
+        }
+        if self.json {
+            println!(
+                "{{\"type\":\"probe\",\"target\":\"{}\",\"seq\":{},\"result\":\"{}\",\
+                \"source\":\"{}\"{},\"error\":{}}}",
+                target,
+                error.sequence,
+                if error.redirect {
+                    "redirect"
+                } else {
+                    "icmp_error"
+                },
+                error.source,
+                self.json_name(error.source),
+                json_string(&error.message)
+            );
+            return;
+        }
+        if self.flood {
+            if !error.redirect {
+                self.print_flood('E');
+            }
+            return;
+        }
+        println!(
+            "From {} icmp_seq={} {}",
+            self.format_source(error.source),
+            error.sequence,
+            error.message
+        );
+    }
+
+    // print the arp reply from target with mac address to request sequence
+    // with its round-trip time; duplicate replies are marked
+    pub fn arp_reply(
+        &self,
+        target: IpAddr,
+        mac: MacAddr,
+        sequence: u64,
+        rtt: Duration,
+        duplicate: bool,
+    ) {
+        if !duplicate {
+            self.export(|exporter| exporter.reply(target, rtt));
+        }
+        if self.quiet {
+            return;
+        }
+        if self.json {
+            println!(
+                "{{\"type\":\"probe\",\"target\":\"{}\",\"seq\":{},\"result\":\"{}\",\
+                \"mac\":\"{}\",\"rtt\":{:.3}}}",
+                target,
+                sequence,
+                if duplicate { "duplicate" } else { "reply" },
+                mac,
+                get_millis(rtt)
+            );
+            return;
+        }
+        println!(
+            "Reply from {} [{}]: arp_seq={} time={:.3} ms{}",
+            target,
+            mac,
+            sequence,
+            get_millis(rtt),
+            if duplicate { " (DUP!)" } else { "" },
+        );
+    }
+
+    // print the extended echo reply from target with the state of the
+    // probed interface and its round-trip time; duplicate replies are marked
+    pub fn probe_reply(&self, target: IpAddr, reply: &ProbeReply, rtt: Duration, duplicate: bool) {
+        if !duplicate {
+            self.export(|exporter| exporter.reply(target, rtt));
+        }
+        if self.quiet {
+            return;
+        }
+        if self.json {
+            println!(
+                "{{\"type\":\"probe\",\"target\":\"{}\",\"seq\":{},\"result\":\"{}\",\
+                \"source\":\"{}\"{},\"error\":{},\"active\":{},\"ipv4\":{},\"ipv6\":{},\
+                \"rtt\":{:.3}}}",
+                target,
+                reply.sequence,
+                if duplicate { "duplicate" } else { "reply" },
+                reply.source,
+                self.json_name(reply.source),
+                match reply.get_error() {
+                    Some(error) => json_string(error),
+                    None => "null".to_string(),
+                },
+                reply.active,
+                reply.ipv4,
+                reply.ipv6,
+                get_millis(rtt)
+            );
+            return;
+        }
+        println!(
+            "Extended echo reply from {}: icmp_seq={} {} time={:.3} ms{}",
+            self.format_source(reply.source),
+            reply.sequence,
+            reply.get_status(),
+            get_millis(rtt),
+            if duplicate { " (DUP!)" } else { "" },
+        );
+    }
+
+    // print the statistics of the echo requests to target completed in the
+    // last monitoring interval
+    pub fn window(&self, target: IpAddr, stats: &Statistics) {
+        self.export(|exporter| exporter.window(target, stats));
+        if self.json {
+            println!(
+                "{{\"type\":\"window\",\"target\":\"{}\",\"transmitted\":{},\
+                \"received\":{},\"loss\":{:.1},\"time\":{},\"rtt\":{}}}",
+                target,
+                stats.get_transmitted(),
+                stats.get_received(),
+                stats.get_loss(),
+                stats.get_time().as_millis(),
+                json_rtt(stats)
+            );
+            return;
+        }
+        print!(
+            "--- {} last {}ms: {} completed, {} received, {}% packet loss",
+            target,
+            stats.get_time().as_millis(),
+            stats.get_transmitted(),
+            stats.get_received(),
+            stats.get_loss().round(),
+        );
+        match stats.get_rtt() {
+            Some((min, avg, max, mdev)) => println!(
+                ", rtt min/avg/max/mdev = {:.3}/{:.3}/{:.3}/{:.3} ms",
+                min, avg, max, mdev
+            ),
+            None => println!(),
+        }
+    }
+
+    // print the outage of echo requests to target
+    pub fn outage(&self, target: IpAddr, outage: &Outage) {
+        if self.json {
+            println!(
+                "{{\"type\":\"outage\",\"target\":\"{}\",\"first_seq\":{},\
+                \"last_seq\":{},\"missed\":{},\"duration\":{:.3}}}",
+                target,
+                outage.first,
+                outage.last,
+                outage.missed,
+                get_millis(outage.get_duration())
+            );
+            return;
+        }
+        println!(
+            "Outage of {}: icmp_seq={}-{}, {} missed, {:.3} ms",
+            target,
+            outage.first,
+            outage.last,
+            outage.missed,
+            get_millis(outage.get_duration())
+        );
+    }
+
+    // print the statistics of the echo requests to target with each of the
+    // payload sizes of a size sweep
+    pub fn sizes(&self, target: IpAddr, sizes: &[usize], stats: &[Statistics]) {
+        if self.json {
+            for (size, stats) in sizes.iter().zip(stats) {
+                println!(
+                    "{{\"type\":\"size\",\"target\":\"{}\",\"size\":{},\"transmitted\":{},\
+                    \"received\":{},\"loss\":{:.1},\"rtt\":{}}}",
+                    target,
+                    size,
+                    stats.get_transmitted(),
+                    stats.get_received(),
+                    stats.get_loss(),
+                    json_rtt(stats)
+                );
+            }
+            return;
+        }
+        println!(
+            "\n{:>5}  {:>5}  {:>5}  {:>5}  {:>9}  {:>9}  {:>9}",
+            "SIZE", "SENT", "RECV", "LOSS", "MIN", "AVG", "MAX"
+        );
+        for (size, stats) in sizes.iter().zip(stats) {
+            let (min, avg, max) = match stats.get_rtt() {
+                Some((min, avg, max, _)) => (
+                    format!("{:.3}", min),
+                    format!("{:.3}", avg),
+                    format!("{:.3}", max),
+                ),
+                None => ("-".to_string(), "-".to_string(), "-".to_string()),
+            };
+            println!(
+                "{:>5}  {:>5}  {:>5}  {:>4}%  {:>9}  {:>9}  {:>9}",
+                size,
+                stats.get_transmitted(),
+                stats.get_received(),
+                stats.get_loss().round(),
+                min,
+                avg,
+                max
+            );
+        }
+    }
+
+    // print the statistics of the run to destination
+    pub fn summary(&self, destination: &str, stats: &Statistics) {
+        if self.json {
+            println!(
+                "{{\"type\":\"summary\",\"target\":{},\"transmitted\":{},\"received\":{},\
+                \"duplicates\":{},\"errors\":{},\"loss\":{:.1},\"time\":{},\"rtt\":{}}}",
+                json_string(destination),
+                stats.get_transmitted(),
+                stats.get_received(),
+                stats.get_duplicates(),
+                stats.get_errors(),
+                stats.get_loss(),
+                stats.get_time().as_millis(),
+                json_rtt(stats)
+            );
+            return;
+        }
+        if self.flood && !self.quiet {
+            println!();
+        }
+        println!("\n--- {} ping statistics ---", destination);
+        println!("{}", stats);
+    }
+}
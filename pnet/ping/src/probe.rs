@@ -0,0 +1,361 @@
+use packet_utils::bytes::read_be_u16;
+use packet_utils::checksum::*;
+use packet_utils::pacer::TokenBucket;
+use pnet::packet::icmp::IcmpPacket;
+use pnet::packet::icmpv6::Icmpv6Packet;
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::transport::{TransportReceiver, TransportSender};
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
+use std::net::IpAddr;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::channel::READ_TIMEOUT;
+use crate::config::Config;
+use crate::output::Output;
+use crate::stats::Statistics;
+use crate::timestamp::receive;
+
+// icmp and icmpv6 types of extended echo requests and replies
+const EXTENDED_ECHO_REQUEST: u8 = 42;
+const EXTENDED_ECHO_REPLY: u8 = 43;
+const EXTENDED_ECHO6_REQUEST: u8 = 160;
+const EXTENDED_ECHO6_REPLY: u8 = 161;
+
+// size of the extended echo header before the icmp extension structure
+const HEADER_SIZE: usize = 8;
+
+// local bit of extended echo requests: the probed interface resides on the
+// node receiving the request
+const LOCAL_BIT: u8 = 0x01;
+
+// version of the icmp extension structure and the offset of its checksum
+const EXTENSION_VERSION: u8 = 2;
+const EXTENSION_CHECKSUM_OFFSET: usize = 2;
+
+// class of the interface identification object and its c-types
+const INTERFACE_CLASS: u8 = 3;
+const BY_NAME: u8 = 1;
+const BY_INDEX: u8 = 2;
+const BY_ADDRESS: u8 = 3;
+
+// address family identifiers of addresses in interface identification
+// objects
+const AFI_IPV4: u16 = 1;
+const AFI_IPV6: u16 = 2;
+
+// time to wait for replies after sending the last extended echo request
+const REPLY_TIMEOUT: Duration = Duration::from_secs(1);
+
+// interval in which the send loop checks if it should stop while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// interface probed with extended echo requests, identified by its name, its
+// index or one of its addresses
+#[derive(Clone, Debug)]
+pub enum InterfaceId {
+    Name(String),
+    Index(u32),
+    Address(IpAddr),
+}
+
+impl InterfaceId {
+    // create the interface identification object; names are padded with
+    // zeros to a multiple of 4 bytes
+    fn create_object(&self) -> Vec<u8> {
+        let (c_type, payload) = match self {
+            InterfaceId::Name(name) => {
+                let mut payload = name.as_bytes().to_vec();
+                payload.resize(payload.len().div_ceil(4) * 4, 0);
+                (BY_NAME, payload)
+            }
+            InterfaceId::Index(index) => (BY_INDEX, index.to_be_bytes().to_vec()),
+            InterfaceId::Address(address) => {
+                let (afi, octets) = match address {
+                    IpAddr::V4(address) => (AFI_IPV4, address.octets().to_vec()),
+                    IpAddr::V6(address) => (AFI_IPV6, address.octets().to_vec()),
+                };
+                let mut payload = afi.to_be_bytes().to_vec();
+                payload.push(u8::try_from(octets.len()).unwrap());
+                payload.push(0);
+                payload.extend(octets);
+                (BY_ADDRESS, payload)
+            }
+        };
+        let length = u16::try_from(4 + payload.len()).unwrap();
+        [
+            &length.to_be_bytes()[..],
+            &[INTERFACE_CLASS, c_type],
+            &payload,
+        ]
+        .concat()
+    }
+}
+
+impl fmt::Display for InterfaceId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InterfaceId::Name(name) => write!(f, "{}", name),
+            InterfaceId::Index(index) => write!(f, "index {}", index),
+            InterfaceId::Address(address) => write!(f, "{}", address),
+        }
+    }
+}
+
+// parse the probed interface: an index, an ip address or a name
+pub fn parse_interface_id(s: &str) -> Result<InterfaceId, String> {
+    if let Ok(index) = s.parse() {
+        return Ok(InterfaceId::Index(index));
+    }
+    if let Ok(address) = s.parse() {
+        return Ok(InterfaceId::Address(address));
+    }
+    match s.is_empty() || !s.is_ascii() {
+        true => Err(format!("invalid interface: {}", s)),
+        false => Ok(InterfaceId::Name(s.to_string())),
+    }
+}
+
+// create the extended echo request to destination with identifier and
+// sequence number that probes interface on destination; the kernel sets
+// the checksum of icmpv6 packets
+fn create_probe_request(
+    destination: IpAddr,
+    identifier: u16,
+    sequence: u8,
+    interface: &InterfaceId,
+) -> Vec<u8> {
+    let mut extension = vec![EXTENSION_VERSION << 4, 0, 0, 0];
+    extension.extend(interface.create_object());
+    set_checksum(&mut extension, EXTENSION_CHECKSUM_OFFSET);
+
+    let request_type = match destination {
+        IpAddr::V4(_) => EXTENDED_ECHO_REQUEST,
+        IpAddr::V6(_) => EXTENDED_ECHO6_REQUEST,
+    };
+    let [id_high, id_low] = identifier.to_be_bytes();
+    let mut request = vec![request_type, 0, 0, 0, id_high, id_low, sequence, LOCAL_BIT];
+    request.extend(extension);
+    if destination.is_ipv4() {
+        set_checksum(&mut request, ICMP_CHECKSUM_OFFSET);
+    }
+    request
+}
+
+// extended echo reply with the state of the probed interface
+pub struct ProbeReply {
+    pub source: IpAddr,
+    pub sequence: u8,
+
+    // error code, zero if the interface was found
+    pub code: u8,
+
+    // whether the interface is active and runs ipv4 and ipv6
+    pub active: bool,
+    pub ipv4: bool,
+    pub ipv6: bool,
+}
+
+impl ProbeReply {
+    // get the error message of the code or none if there is no error
+    pub fn get_error(&self) -> Option<&'static str> {
+        match self.code {
+            0 => None,
+            1 => Some("Malformed Query"),
+            2 => Some("No Such Interface"),
+            3 => Some("No Such Table Entry"),
+            4 => Some("Multiple Interfaces Satisfy Query"),
+            _ => Some("Unknown Error"),
+        }
+    }
+
+    // get the state of the interface or the error as text
+    pub fn get_status(&self) -> String {
+        if let Some(error) = self.get_error() {
+            return error.to_string();
+        }
+        let mut status = match self.active {
+            true => "interface active".to_string(),
+            false => "interface inactive".to_string(),
+        };
+        if self.ipv4 {
+            status.push_str(", ipv4");
+        }
+        if self.ipv6 {
+            status.push_str(", ipv6");
+        }
+        status
+    }
+}
+
+// parse the icmp or icmpv6 message from source and get the extended echo
+// reply to a request with identifier; ipv4 replies with a wrong checksum
+// are ignored
+fn parse_probe_reply(message: &[u8], source: IpAddr, identifier: u16) -> Option<ProbeReply> {
+    let header = message.get(..HEADER_SIZE)?;
+    let reply_type = match source {
+        IpAddr::V4(_) => EXTENDED_ECHO_REPLY,
+        IpAddr::V6(_) => EXTENDED_ECHO6_REPLY,
+    };
+    if header[0] != reply_type || read_be_u16(&header[4..6]) != identifier {
+        return None;
+    }
+    if source.is_ipv4() && !is_checksum_valid(message, ICMP_CHECKSUM_OFFSET) {
+        return None;
+    }
+    Some(ProbeReply {
+        source,
+        sequence: header[6],
+        code: header[1],
+        active: header[7] & 0x04 != 0,
+        ipv4: header[7] & 0x02 != 0,
+        ipv6: header[7] & 0x01 != 0,
+    })
+}
+
+// receive the next extended echo reply to a request with identifier on the
+// transport channel rx with the time it was received
+fn receive_probe_reply(
+    rx: &mut TransportReceiver,
+    destination: IpAddr,
+    identifier: u16,
+) -> io::Result<Option<(ProbeReply, SystemTime)>> {
+    let (size, source, received) = match receive(rx.socket.fd, &mut rx.buffer, READ_TIMEOUT)? {
+        Some(packet) => packet,
+        None => return Ok(None),
+    };
+
+    // the kernel passes on icmp packets with their ipv4 header, but
+    // removes the ipv6 header
+    let packet = &rx.buffer[..size];
+    let message = match destination {
+        IpAddr::V4(_) => match Ipv4Packet::new(packet) {
+            Some(ipv4) => packet.get(usize::from(ipv4.get_header_length()) * 4..),
+            None => None,
+        },
+        IpAddr::V6(_) => Some(packet),
+    };
+    let reply = message.and_then(|message| parse_probe_reply(message, source, identifier));
+    Ok(reply.map(|reply| (reply, received.unwrap_or_else(SystemTime::now))))
+}
+
+// extended echo requests sent to the destination and the statistics of the
+// run, shared between the sending and the receiving thread
+#[derive(Default)]
+struct Probes {
+    // send times of requests and whether they were answered by sequence
+    // number
+    sent: HashMap<u8, (SystemTime, bool)>,
+
+    stats: Statistics,
+}
+
+// receive extended echo replies from destination to the requests with
+// identifier and print them to output until receiving is unset
+fn receive_replies(
+    mut rx: TransportReceiver,
+    (destination, identifier): (IpAddr, u16),
+    probes: &Mutex<Probes>,
+    receiving: &AtomicBool,
+    output: &Output,
+) {
+    while receiving.load(atomic::Ordering::Relaxed) {
+        let (reply, received) = match receive_probe_reply(&mut rx, destination, identifier) {
+            Ok(Some(reply)) => reply,
+            Ok(None) => continue,
+            Err(e) => match e.kind() {
+                io::ErrorKind::Interrupted => continue,
+                _ => panic!("Error happened {}", e),
+            },
+        };
+        if reply.source != destination {
+            continue;
+        }
+        let mut probes = probes.lock().unwrap();
+        let (sent, answered) = match probes.sent.get_mut(&reply.sequence) {
+            Some(request) => request,
+            None => continue,
+        };
+        let rtt = received.duration_since(*sent).unwrap_or_default();
+        let duplicate = *answered;
+        *answered = true;
+        probes.stats.add_reply(rtt, duplicate);
+        output.probe_reply(destination, &reply, rtt, duplicate);
+    }
+}
+
+// send extended echo requests that probe interface to destination on the
+// transport channel paced by the interval or rate of config and print the
+// replies to output until all requests are sent or running is unset;
+// returns the statistics of the run
+pub fn probe(
+    (mut tx, rx): (TransportSender, TransportReceiver),
+    destination: IpAddr,
+    interface: &InterfaceId,
+    config: &Config,
+    output: &Output,
+    running: &AtomicBool,
+) -> Statistics {
+    let identifier = std::process::id() as u16;
+    let mut pacer = match config.rate {
+        Some(rate) => TokenBucket::new(rate),
+        None => TokenBucket::new(1.0 / config.interval),
+    };
+    let mut last_send = Instant::now();
+    let probes = Mutex::new(Probes::default());
+    let receiving = AtomicBool::new(true);
+    std::thread::scope(|s| {
+        s.spawn(|| {
+            let request = (destination, identifier);
+            receive_replies(rx, request, &probes, &receiving, output)
+        });
+        while running.load(atomic::Ordering::Relaxed) {
+            let (transmitted, received) = {
+                let probes = probes.lock().unwrap();
+                (probes.stats.get_transmitted(), probes.stats.get_received())
+            };
+            if config.count.is_some_and(|count| transmitted >= count) {
+                if last_send.elapsed() >= REPLY_TIMEOUT || received >= transmitted {
+                    break;
+                }
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            if !pacer.try_take() {
+                std::thread::sleep(pacer.get_wait().min(POLL_INTERVAL));
+                continue;
+            }
+
+            // the sequence number of extended echo requests has 8 bits;
+            // add the request before sending it, so the receiving thread
+            // does not miss a fast reply
+            let sequence = transmitted as u8;
+            let request = create_probe_request(destination, identifier, sequence, interface);
+            last_send = Instant::now();
+            {
+                let mut probes = probes.lock().unwrap();
+                probes.sent.insert(sequence, (SystemTime::now(), false));
+                probes.stats.add_request();
+            }
+            let result = match destination {
+                IpAddr::V4(_) => tx.send_to(IcmpPacket::new(&request).unwrap(), destination),
+                IpAddr::V6(_) => tx.send_to(Icmpv6Packet::new(&request).unwrap(), destination),
+            };
+            if let Err(e) = result {
+                let mut probes = probes.lock().unwrap();
+                probes.sent.remove(&sequence);
+                probes.stats.remove_request();
+                drop(probes);
+                output.send_error(destination, transmitted, &e);
+            }
+        }
+        probes.lock().unwrap().stats.finish();
+        receiving.store(false, atomic::Ordering::Relaxed);
+    });
+    probes.into_inner().unwrap().stats
+}
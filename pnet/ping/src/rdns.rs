@@ -0,0 +1,108 @@
+use dns::{Data, DnsBuilder, DnsPacket};
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// file with the name servers of the system
+const RESOLV_CONF: &str = "/etc/resolv.conf";
+
+// time to wait for the response of the name server
+const DNS_TIMEOUT: Duration = Duration::from_secs(1);
+
+// get the address of the first name server in the resolver configuration
+fn get_name_server() -> Option<SocketAddr> {
+    let resolv_conf = std::fs::read_to_string(RESOLV_CONF).ok()?;
+    resolv_conf.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        match (fields.next(), fields.next()) {
+            (Some("nameserver"), Some(addr)) => Some(SocketAddr::new(addr.parse().ok()?, 53)),
+            _ => None,
+        }
+    })
+}
+
+// look up the host name of addr on the name server
+fn lookup(server: Option<SocketAddr>, addr: IpAddr) -> Option<String> {
+    let server = server?;
+    let unspecified = match server {
+        SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+    };
+    let socket = UdpSocket::bind(SocketAddr::new(unspecified, 0)).ok()?;
+    socket.connect(server).ok()?;
+    socket.set_read_timeout(Some(DNS_TIMEOUT)).ok()?;
+    let id = std::process::id() as u16;
+    let query = DnsBuilder::reverse_query(id, addr).ok()?.build();
+    socket.send(&query).ok()?;
+
+    let mut buf = [0; 65535];
+    loop {
+        let len = socket.recv(&mut buf).ok()?;
+        let response = match DnsPacket::parse(&buf[..len]) {
+            Ok(response) if response.get_id() == id => response,
+            _ => continue,
+        };
+        let mut answers = (0..response.get_answers().into()).map_while(|i| response.get_answer(i));
+        return answers.find_map(|answer| match answer.get_data() {
+            Data::Ptr(name) => Some(name.trim_end_matches('.').to_string()),
+            _ => None,
+        });
+    }
+}
+
+// resolver of host names of ip addresses with reverse lookups; the names
+// are cached, including failed lookups, so every address is only looked up
+// once. lookups run in a background thread, so they do not block the
+// callers; the name of a new address is only available after its lookup
+pub struct Resolver {
+    server: Option<SocketAddr>,
+    names: Arc<Mutex<HashMap<IpAddr, Option<String>>>>,
+    lookups: Sender<IpAddr>,
+}
+
+impl Resolver {
+    pub fn new() -> Resolver {
+        let server = get_name_server();
+        let names = Arc::new(Mutex::new(HashMap::new()));
+        let (lookups, addrs) = mpsc::channel();
+        {
+            let names = names.clone();
+            thread::spawn(move || {
+                for addr in addrs {
+                    let name = lookup(server, addr);
+                    names.lock().unwrap().insert(addr, name);
+                }
+            });
+        }
+        Resolver {
+            server,
+            names,
+            lookups,
+        }
+    }
+
+    // look up the host name of addr and wait for the result, e.g., for the
+    // destination before the first reply
+    pub fn resolve(&self, addr: IpAddr) {
+        if !self.names.lock().unwrap().contains_key(&addr) {
+            let name = lookup(self.server, addr);
+            self.names.lock().unwrap().insert(addr, name);
+        }
+    }
+
+    // get the host name of addr if it is known; unknown addresses are
+    // looked up in the background
+    pub fn get_name(&self, addr: IpAddr) -> Option<String> {
+        let mut names = self.names.lock().unwrap();
+        if let Some(name) = names.get(&addr) {
+            return name.clone();
+        }
+        names.insert(addr, None);
+        let _ = self.lookups.send(addr);
+        None
+    }
+}
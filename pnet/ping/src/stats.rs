@@ -0,0 +1,152 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// statistics of the echo requests sent and the echo replies received
+pub struct Statistics {
+    start: Instant,
+    end: Option<Instant>,
+    transmitted: u64,
+    received: u64,
+    duplicates: u64,
+    errors: u64,
+
+    // round-trip times of the received replies in milliseconds
+    rtt_min: f64,
+    rtt_max: f64,
+    rtt_sum: f64,
+    rtt_sum_squares: f64,
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        Statistics {
+            start: Instant::now(),
+            end: None,
+            transmitted: 0,
+            received: 0,
+            duplicates: 0,
+            errors: 0,
+            rtt_min: f64::MAX,
+            rtt_max: 0.0,
+            rtt_sum: 0.0,
+            rtt_sum_squares: 0.0,
+        }
+    }
+}
+
+impl Statistics {
+    // count a sent echo request
+    pub fn add_request(&mut self) {
+        self.transmitted += 1;
+    }
+
+    // remove a counted echo request that could not be sent
+    pub fn remove_request(&mut self) {
+        self.transmitted = self.transmitted.saturating_sub(1);
+    }
+
+    // count a received echo reply with its round-trip time; duplicate
+    // replies do not count as received
+    pub fn add_reply(&mut self, rtt: Duration, duplicate: bool) {
+        if duplicate {
+            self.duplicates += 1;
+            return;
+        }
+        let rtt = rtt.as_secs_f64() * 1000.0;
+        self.received += 1;
+        self.rtt_min = self.rtt_min.min(rtt);
+        self.rtt_max = self.rtt_max.max(rtt);
+        self.rtt_sum += rtt;
+        self.rtt_sum_squares += rtt * rtt;
+    }
+
+    // count a received icmp error message for an echo request
+    pub fn add_error(&mut self) {
+        self.errors += 1;
+    }
+
+    // end the run, so its time does not include waiting for the receiver
+    pub fn finish(&mut self) {
+        self.end.get_or_insert_with(Instant::now);
+    }
+
+    // get the number of sent echo requests
+    pub fn get_transmitted(&self) -> u64 {
+        self.transmitted
+    }
+
+    // get the number of received echo replies without duplicates
+    pub fn get_received(&self) -> u64 {
+        self.received
+    }
+
+    // get the number of duplicate echo replies
+    pub fn get_duplicates(&self) -> u64 {
+        self.duplicates
+    }
+
+    // get the number of received icmp error messages
+    pub fn get_errors(&self) -> u64 {
+        self.errors
+    }
+
+    // get the time of the run
+    pub fn get_time(&self) -> Duration {
+        self.end
+            .unwrap_or_else(Instant::now)
+            .duration_since(self.start)
+    }
+
+    // get the minimum, average, maximum and mean deviation of the round-trip
+    // times in milliseconds; returns none without received replies
+    pub fn get_rtt(&self) -> Option<(f64, f64, f64, f64)> {
+        if self.received == 0 {
+            return None;
+        }
+
+        // mean deviation is the standard deviation of the round-trip times
+        // like in iputils ping
+        let avg = self.rtt_sum / self.received as f64;
+        let variance = self.rtt_sum_squares / self.received as f64 - avg * avg;
+        Some((self.rtt_min, avg, self.rtt_max, variance.max(0.0).sqrt()))
+    }
+
+    // get the percentage of echo requests without a reply
+    pub fn get_loss(&self) -> f64 {
+        if self.transmitted == 0 {
+            return 0.0;
+        }
+        let lost = self.transmitted.saturating_sub(self.received);
+        lost as f64 * 100.0 / self.transmitted as f64
+    }
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} packets transmitted, {} received, ",
+            self.transmitted, self.received
+        )?;
+        if self.duplicates > 0 {
+            write!(f, "+{} duplicates, ", self.duplicates)?;
+        }
+        if self.errors > 0 {
+            write!(f, "+{} errors, ", self.errors)?;
+        }
+        write!(
+            f,
+            "{}% packet loss, time {}ms",
+            self.get_loss().round(),
+            self.get_time().as_millis()
+        )?;
+        if let Some((min, avg, max, mdev)) = self.get_rtt() {
+            write!(
+                f,
+                "\nrtt min/avg/max/mdev = {:.3}/{:.3}/{:.3}/{:.3} ms",
+                min, avg, max, mdev
+            )?;
+        }
+        Ok(())
+    }
+}
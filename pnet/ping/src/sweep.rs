@@ -0,0 +1,226 @@
+use packet_utils::interface::*;
+use packet_utils::pacer::TokenBucket;
+use pnet::datalink::{DataLinkReceiver, DataLinkSender, MacAddr, NetworkInterface};
+use pnet::ipnetwork::Ipv4Network;
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::arp;
+use crate::cache::NeighborCache;
+use crate::config::Config;
+use crate::echo::*;
+use crate::fragment::Reassembler;
+
+// rate of requests in packets per second without a configured rate
+const SWEEP_RATE: f64 = 100.0;
+
+// smallest prefix length of networks that can be swept
+pub const MIN_SWEEP_PREFIX: u8 = 16;
+
+// time to wait for arp and echo replies after sending the last request
+const SWEEP_TIMEOUT: Duration = Duration::from_secs(1);
+
+// interval in which the send loop checks if it should stop while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// host that answered an echo request of the sweep
+pub struct LiveHost {
+    pub ip: Ipv4Addr,
+    pub mac: MacAddr,
+    pub rtt: Duration,
+}
+
+// arp and echo replies received during the sweep
+#[derive(Default)]
+struct Replies {
+    // mac addresses of hosts in the network of the interface that answered
+    // an arp request
+    macs: HashMap<Ipv4Addr, MacAddr>,
+
+    // send times of echo requests keyed by their sequence number
+    sent: HashMap<u16, (Ipv4Addr, Instant)>,
+
+    hosts: HashMap<Ipv4Addr, LiveHost>,
+}
+
+// get the hosts of network; the network and broadcast addresses are only
+// included in networks without them
+fn get_hosts(network: Ipv4Network) -> Vec<Ipv4Addr> {
+    network
+        .iter()
+        .filter(|ip| {
+            network.prefix() >= 31 || (*ip != network.network() && *ip != network.broadcast())
+        })
+        .collect()
+}
+
+// receive arp and echo replies to the echo requests with identifier until
+// receiving is unset
+fn receive_replies(
+    mut rx: Box<dyn DataLinkReceiver>,
+    identifier: u16,
+    replies: &Mutex<Replies>,
+    receiving: &AtomicBool,
+) {
+    let mut fragments = Reassembler::default();
+    while receiving.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("Error happened {}", e),
+            },
+        };
+        if let Some((ip, mac)) = arp::parse_arp_reply(frame) {
+            replies.lock().unwrap().macs.insert(ip, mac);
+            continue;
+        }
+        let reply = match fragments
+            .add(frame)
+            .and_then(|f| parse_echo_reply(&f, identifier))
+        {
+            Some(reply) => reply,
+            None => continue,
+        };
+        let mut replies = replies.lock().unwrap();
+        let (ip, sent) = match replies.sent.get(&reply.sequence) {
+            Some((ip, sent)) if IpAddr::V4(*ip) == reply.source => (*ip, *sent),
+            _ => continue,
+        };
+        replies.hosts.entry(ip).or_insert(LiveHost {
+            ip,
+            mac: reply.source_mac,
+            rtt: sent.elapsed(),
+        });
+    }
+}
+
+// wait for replies to the last request until the timeout or running is
+// unset
+fn wait_replies(last_send: Instant, running: &AtomicBool) {
+    while last_send.elapsed() < SWEEP_TIMEOUT && running.load(atomic::Ordering::Relaxed) {
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+// sweep the network: resolve the mac addresses of the hosts in the network
+// of the interface with arp requests, send an echo request with the payload
+// and ip header fields of config to every host in the network, and get the
+// hosts that answered sorted by ip address; requests are sent with the rate
+// of config and the sweep stops early if running is unset
+pub fn sweep(
+    mut tx: Box<dyn DataLinkSender>,
+    mut rx: Box<dyn DataLinkReceiver>,
+    interface: &NetworkInterface,
+    network: Ipv4Network,
+    config: &Config,
+    running: &AtomicBool,
+) -> Vec<LiveHost> {
+    let payload = create_payload(config.size, config.pattern.as_deref());
+    let fields = IpFields::new(config);
+    let rate = config.rate.unwrap_or(SWEEP_RATE);
+    let source_mac = interface.mac.unwrap();
+    let source_ip = match get_interface_ip(interface) {
+        Some(source_ip) => source_ip,
+        None => panic!("No ipv4 address on interface {}", interface.name),
+    };
+    let hosts = get_hosts(network);
+
+    // hosts outside of the network of the interface are reached through
+    // the default gateway
+    let local_network = get_interface_network(interface);
+    let is_on_link = |host: Ipv4Addr| local_network.is_some_and(|n| n.contains(host));
+    let on_link: Vec<Ipv4Addr> = hosts.iter().copied().filter(|h| is_on_link(*h)).collect();
+    let gateway_mac = match hosts.iter().find(|h| !is_on_link(**h)) {
+        Some(host) => get_next_hop(interface, *host).and_then(|gateway| {
+            let mut cache = NeighborCache::default();
+            arp::resolve(
+                &mut *tx,
+                &mut *rx,
+                (source_mac, source_ip),
+                gateway,
+                &mut cache,
+                None,
+            )
+        }),
+        None => None,
+    };
+
+    let identifier = std::process::id() as u16;
+    let replies = Mutex::new(Replies::default());
+    let receiving = AtomicBool::new(true);
+    std::thread::scope(|s| {
+        s.spawn(|| receive_replies(rx, identifier, &replies, &receiving));
+        let mut pacer = TokenBucket::new(rate);
+        let mut last_send = Instant::now();
+
+        // resolve the mac addresses of hosts in the network of the
+        // interface
+        for host in &on_link {
+            while running.load(atomic::Ordering::Relaxed) && !pacer.try_take() {
+                std::thread::sleep(pacer.get_wait());
+            }
+            if !running.load(atomic::Ordering::Relaxed) {
+                break;
+            }
+            let ethernet_buffer = arp::create_arp_packet(source_mac, source_ip, *host);
+            tx.send_to(&ethernet_buffer, None).unwrap().unwrap();
+            last_send = Instant::now();
+        }
+        if !on_link.is_empty() {
+            wait_replies(last_send, running);
+        }
+
+        // send echo requests to the hosts with known next hop mac addresses
+        let macs = replies.lock().unwrap().macs.clone();
+        for (sequence, host) in hosts.iter().enumerate() {
+            let destination_mac = match is_on_link(*host) {
+                true => macs.get(host).copied(),
+                false => gateway_mac,
+            };
+            let destination_mac = match destination_mac {
+                Some(mac) => mac,
+                None => continue,
+            };
+            while running.load(atomic::Ordering::Relaxed) && !pacer.try_take() {
+                std::thread::sleep(pacer.get_wait());
+            }
+            if !running.load(atomic::Ordering::Relaxed) {
+                break;
+            }
+            let sequence = sequence as u16;
+            let frames = create_ping_packet(
+                (interface, source_ip),
+                *host,
+                destination_mac,
+                identifier,
+                sequence,
+                &payload,
+                &fields,
+            );
+            last_send = Instant::now();
+            replies
+                .lock()
+                .unwrap()
+                .sent
+                .insert(sequence, (*host, last_send));
+            let sent = frames
+                .iter()
+                .try_for_each(|frame| tx.send_to(frame, None).unwrap());
+            if let Err(e) = sent {
+                println!("Could not send echo request to {}: {}", host, e);
+            }
+        }
+        wait_replies(last_send, running);
+        receiving.store(false, atomic::Ordering::Relaxed);
+    });
+
+    let mut hosts: Vec<LiveHost> = replies.into_inner().unwrap().hosts.into_values().collect();
+    hosts.sort_by_key(|host| host.ip);
+    hosts
+}
@@ -0,0 +1,110 @@
+use socket2::SockAddr;
+
+use std::io;
+use std::mem;
+use std::net::IpAddr;
+use std::os::unix::io::RawFd;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// size of the buffer for control messages of received packets; large
+// enough for a timestamp control message with a 64 bit timespec
+const CONTROL_SIZE: usize = 64;
+
+// enable receive timestamps taken by the kernel on socket fd; they are
+// passed with nanosecond resolution as control messages of received
+// packets; libc selects the option with a 64 bit timespec on targets with
+// a 64 bit time_t, so timestamps do not overflow in 2038 there
+pub fn enable_timestamps(fd: RawFd) {
+    let enable: libc::c_int = 1;
+    // safety: setsockopt reads a c_int from enable; if timestamps cannot be
+    // enabled, replies get the time they were read as receive time
+    unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_TIMESTAMPNS,
+            &enable as *const libc::c_int as *const libc::c_void,
+            mem::size_of::<libc::c_int>() as libc::socklen_t,
+        );
+    }
+}
+
+// get the receive timestamp from the control message cmsg if it is one
+fn get_timestamp(cmsg: &libc::cmsghdr) -> Option<SystemTime> {
+    if cmsg.cmsg_level != libc::SOL_SOCKET || cmsg.cmsg_type != libc::SCM_TIMESTAMPNS {
+        return None;
+    }
+    // safety: the data of timestamp control messages is a timespec that
+    // may not be aligned
+    let time: libc::timespec =
+        unsafe { std::ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const libc::timespec) };
+    let seconds = Duration::from_secs(u64::try_from(time.tv_sec).ok()?);
+    let nanos = Duration::from_nanos(u64::try_from(time.tv_nsec).ok()?);
+    Some(UNIX_EPOCH + seconds + nanos)
+}
+
+// receive the next packet on socket fd into buffer within timeout; returns
+// its size, its source address and the time the kernel received it if
+// there is a receive timestamp, or none if no packet was received
+pub fn receive(
+    fd: RawFd,
+    buffer: &mut [u8],
+    timeout: Duration,
+) -> io::Result<Option<(usize, IpAddr, Option<SystemTime>)>> {
+    // wait for the next packet
+    let mut pollfd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let timeout = libc::c_int::try_from(timeout.as_millis()).unwrap_or(libc::c_int::MAX);
+    // safety: poll reads and writes the single pollfd
+    match unsafe { libc::poll(&mut pollfd, 1, timeout) } {
+        -1 => return Err(io::Error::last_os_error()),
+        0 => return Ok(None),
+        _ => (),
+    }
+
+    // read the packet with its source address and control messages
+    let mut iov = libc::iovec {
+        iov_base: buffer.as_mut_ptr() as *mut libc::c_void,
+        iov_len: buffer.len(),
+    };
+    let mut control = [0u64; CONTROL_SIZE / 8];
+    // safety: all-zero is a valid sockaddr_storage and msghdr
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_name = &mut storage as *mut libc::sockaddr_storage as *mut libc::c_void;
+    msg.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+    msg.msg_controllen = CONTROL_SIZE as _;
+    // safety: msg points to the buffers above that outlive the call
+    let size = match unsafe { libc::recvmsg(fd, &mut msg, 0) } {
+        -1 => {
+            let e = io::Error::last_os_error();
+            if e.kind() == io::ErrorKind::WouldBlock {
+                return Ok(None);
+            }
+            return Err(e);
+        }
+        size => size as usize,
+    };
+
+    // safety: storage was filled by recvmsg up to msg_namelen
+    let source = match unsafe { SockAddr::new(storage, msg.msg_namelen) }.as_socket() {
+        Some(source) => source.ip(),
+        None => return Ok(None),
+    };
+
+    // find the timestamp in the control messages
+    let mut time = None;
+    // safety: the control messages were written by recvmsg to control
+    let mut cmsg = unsafe { libc::CMSG_FIRSTHDR(&msg) };
+    while let Some(header) = unsafe { cmsg.as_ref() } {
+        time = time.or_else(|| get_timestamp(header));
+        cmsg = unsafe { libc::CMSG_NXTHDR(&msg, cmsg) };
+    }
+    Ok(Some((size, source, time)))
+}
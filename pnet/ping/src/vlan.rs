@@ -0,0 +1,34 @@
+use pnet::packet::ethernet::EtherTypes;
+
+// size of the mac addresses at the start of ethernet frames; the vlan tag
+// is inserted after them
+const MACS_SIZE: usize = 12;
+
+// 802.1q vlan tag with the vlan id and the priority code point
+#[derive(Clone, Copy)]
+pub struct VlanTag {
+    pub id: u16,
+    pub pcp: u8,
+}
+
+impl VlanTag {
+    // get the tag control information field of the vlan tag
+    fn get_tci(&self) -> u16 {
+        u16::from(self.pcp) << 13 | self.id & 0x0fff
+    }
+}
+
+// insert the vlan tag into the ethernet frame; frames are sent untagged
+// without a vlan tag
+pub fn insert_vlan_tag(frame: Vec<u8>, vlan: Option<VlanTag>) -> Vec<u8> {
+    let vlan = match vlan {
+        Some(vlan) => vlan,
+        None => return frame,
+    };
+    let mut tagged = Vec::with_capacity(frame.len() + 4);
+    tagged.extend_from_slice(&frame[..MACS_SIZE]);
+    tagged.extend_from_slice(&EtherTypes::Vlan.0.to_be_bytes());
+    tagged.extend_from_slice(&vlan.get_tci().to_be_bytes());
+    tagged.extend_from_slice(&frame[MACS_SIZE..]);
+    tagged
+}
@@ -0,0 +1,175 @@
+use pnet::datalink::{DataLinkReceiver, DataLinkSender, MacAddr};
+use pnet::packet::icmp::IcmpPacket;
+use pnet::packet::Packet;
+use pnet::transport::icmp_packet_iter;
+use pnet::transport::{TransportReceiver, TransportSender};
+
+use pnet::packet::ipv4::Ipv4Packet;
+
+use std::io;
+use std::mem;
+use std::net::{IpAddr, Ipv4Addr};
+use std::os::unix::io::RawFd;
+use std::time::Duration;
+
+use crate::datalink::*;
+
+// source of a request and destination of its reply with its mac address on
+// datalink channels
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Peer {
+    pub ip: IpAddr,
+    pub mac: MacAddr,
+}
+
+// icmp reply to destination with the type of service it is sent with or
+// none for the default of the sender
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub struct Reply {
+    pub destination: Peer,
+    pub tos: Option<u8>,
+    pub packet: Vec<u8>,
+}
+
+// set the type of service of packets sent on the ipv4 socket fd
+fn set_tos(fd: RawFd, tos: u8) -> io::Result<()> {
+    let tos = libc::c_int::from(tos);
+    let ret = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::IPPROTO_IP,
+            libc::IP_TOS,
+            &tos as *const libc::c_int as *const libc::c_void,
+            mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// sender of replies
+pub enum Sender {
+    // transport channel with the ip header built by the kernel
+    Transport(TransportSender),
+
+    // datalink channel with the ethernet and ip headers built from the mac
+    // address of the interface, the address that is answered and the ttl
+    Datalink {
+        tx: Box<dyn DataLinkSender>,
+        mac: MacAddr,
+        address: Ipv4Addr,
+        ttl: u8,
+    },
+}
+
+impl Sender {
+    // send the icmp reply; on transport channels, its type of service is
+    // set on the socket before sending it
+    pub fn send(&mut self, reply: &Reply) -> io::Result<()> {
+        let destination = reply.destination;
+        match (self, destination.ip) {
+            (Sender::Transport(tx), ip) => {
+                if let Some(tos) = reply.tos {
+                    set_tos(tx.socket.fd, tos)?;
+                }
+                tx.send_to(IcmpPacket::new(&reply.packet).unwrap(), ip)
+                    .map(|_| ())
+            }
+            (
+                Sender::Datalink {
+                    tx,
+                    mac,
+                    address,
+                    ttl,
+                },
+                IpAddr::V4(ip),
+            ) => {
+                let frame = create_reply_frame(
+                    (*mac, *address),
+                    (destination.mac, ip),
+                    (*ttl, reply.tos.unwrap_or(0)),
+                    &reply.packet,
+                );
+                tx.send_to(&frame, None).unwrap()
+            }
+            (Sender::Datalink { .. }, IpAddr::V6(_)) => unreachable!(),
+        }
+    }
+
+    // send the ethernet frame on datalink channels
+    pub fn send_frame(&mut self, frame: &[u8]) -> io::Result<()> {
+        match self {
+            Sender::Transport(_) => Ok(()),
+            Sender::Datalink { tx, .. } => tx.send_to(frame, None).unwrap(),
+        }
+    }
+}
+
+// message received on a channel
+pub enum Message {
+    // icmp packet from source with the type of service of its ip header
+    Icmp(Peer, u8, Vec<u8>),
+
+    // arp request for the answered address from sender ip and mac address
+    // with the arp reply frame
+    Arp(Ipv4Addr, MacAddr, Vec<u8>),
+}
+
+// receiver of icmp requests
+pub enum Receiver {
+    Transport(TransportReceiver),
+    Datalink {
+        rx: Box<dyn DataLinkReceiver>,
+        mac: MacAddr,
+        address: Ipv4Addr,
+    },
+}
+
+impl Receiver {
+    // receive the next icmp packet or arp request; returns none if no such
+    // message was received within timeout, which is also the read timeout
+    // of datalink channels
+    pub fn receive(&mut self, timeout: Duration) -> io::Result<Option<Message>> {
+        match self {
+            Receiver::Transport(rx) => {
+                let mut iter = icmp_packet_iter(rx);
+                let (packet, source) = match iter.next_with_timeout(timeout)? {
+                    Some(received) => received,
+                    None => return Ok(None),
+                };
+                let source = Peer {
+                    ip: source,
+                    mac: MacAddr::zero(),
+                };
+                let packet = packet.packet().to_vec();
+
+                // the kernel passes on icmp packets with their ipv4 header,
+                // so the type of service is taken from the receive buffer
+                let tos =
+                    Ipv4Packet::new(&rx.buffer).map_or(0, |ipv4_packet| get_tos(&ipv4_packet));
+                Ok(Some(Message::Icmp(source, tos, packet)))
+            }
+            Receiver::Datalink { rx, mac, address } => {
+                let frame = match rx.next() {
+                    Ok(frame) => frame,
+                    Err(e) if e.kind() == io::ErrorKind::TimedOut => return Ok(None),
+                    Err(e) => return Err(e),
+                };
+                if let Some((ip, sender_mac, reply)) = create_arp_reply(frame, *mac, *address) {
+                    return Ok(Some(Message::Arp(ip, sender_mac, reply)));
+                }
+                Ok(
+                    parse_icmp_frame(frame, *address).map(|(source_mac, source, tos, packet)| {
+                        let source = Peer {
+                            ip: IpAddr::V4(source),
+                            mac: source_mac,
+                        };
+                        Message::Icmp(source, tos, packet.to_vec())
+                    }),
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use clap::Parser;
+use packet_utils::hex::parse_hex;
+use packet_utils::parse::{parse_interval, parse_rate, parse_tos};
+use pnet::ipnetwork::IpNetwork;
+
+use std::net::Ipv4Addr;
+
+// command line configuration of the pong tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Answer icmp echo requests with echo replies")]
+pub struct Config {
+    // address answered on the datalink layer
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        requires = "interface",
+        help = "Answer arp requests and icmp requests for ADDRESS directly on \
+        the datalink layer of --interface, so the host does not need to own \
+        ADDRESS"
+    )]
+    pub address: Option<Ipv4Addr>,
+
+    // interface of the datalink layer responder
+    #[arg(
+        short = 'I',
+        long,
+        value_name = "INTERFACE",
+        requires = "address",
+        help = "Receive requests and send replies on INTERFACE for --address"
+    )]
+    pub interface: Option<String>,
+
+    // json output
+    #[arg(
+        long,
+        help = "Print one json object per request, report and summary \
+        instead of text"
+    )]
+    pub json: bool,
+
+    // sources that get replies
+    #[arg(
+        long,
+        value_name = "CIDR",
+        value_parser = parse_network,
+        help = "Only reply to sources in CIDR, an ip network or address; can \
+        be given multiple times"
+    )]
+    pub allow: Vec<IpNetwork>,
+
+    // sources that do not get replies
+    #[arg(
+        long,
+        value_name = "CIDR",
+        value_parser = parse_network,
+        help = "Do not reply to sources in CIDR, an ip network or address, \
+        even if they are allowed; can be given multiple times"
+    )]
+    pub deny: Vec<IpNetwork>,
+
+    // global reply rate limit
+    #[arg(
+        long,
+        value_name = "PPS",
+        value_parser = parse_rate,
+        help = "Send at most PPS echo replies per second to all sources \
+        together and drop the other requests"
+    )]
+    pub rate: Option<f64>,
+
+    // per-source reply rate limit
+    #[arg(
+        long,
+        value_name = "PPS",
+        value_parser = parse_rate,
+        help = "Send at most PPS echo replies per second to each source and \
+        drop the other requests"
+    )]
+    pub source_rate: Option<f64>,
+
+    // burst size of the rate limits
+    #[arg(
+        long,
+        value_name = "COUNT",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Allow bursts of COUNT echo replies above the rate limits; by \
+        default, a burst is one second at the rate limit"
+    )]
+    pub burst: Option<u32>,
+
+    // interval of statistics reports
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 60.0,
+        value_parser = parse_interval,
+        help = "Print the statistics of the answered requests every SECONDS \
+        seconds"
+    )]
+    pub report: f64,
+
+    // number of reply workers
+    #[arg(
+        long,
+        value_name = "COUNT",
+        default_value_t = 4,
+        value_parser = clap::value_parser!(u16).range(1..=256),
+        help = "Create and send replies in COUNT worker threads"
+    )]
+    pub workers: u16,
+
+    // ttl of replies
+    #[arg(
+        long,
+        value_name = "TTL",
+        value_parser = clap::value_parser!(u8).range(1..),
+        help = "Send replies with time to live TTL instead of the default of \
+        the host"
+    )]
+    pub ttl: Option<u8>,
+
+    // type of service of replies
+    #[arg(
+        long,
+        value_name = "TOS",
+        value_parser = parse_tos,
+        conflicts_with = "mirror_tos",
+        help = "Send replies with type of service TOS, a decimal or hex \
+        number of the dscp and ecn bits"
+    )]
+    pub tos: Option<u8>,
+
+    // type of service of replies from requests
+    #[arg(
+        long,
+        help = "Send replies with the type of service, the dscp and ecn bits, \
+        of their requests"
+    )]
+    pub mirror_tos: bool,
+
+    // identifiers of answered requests
+    #[arg(
+        long,
+        value_name = "ID",
+        value_parser = parse_identifier,
+        help = "Only answer requests with identifier ID, a decimal or hex \
+        number; can be given multiple times"
+    )]
+    pub identifier: Vec<u16>,
+
+    // payload magic of answered requests
+    #[arg(
+        long,
+        value_name = "HEX",
+        value_parser = parse_magic,
+        help = "Only answer requests with the bytes HEX, up to 32 hex digits, \
+        in their payload at --magic-offset"
+    )]
+    pub magic: Option<std::vec::Vec<u8>>,
+
+    // offset of the payload magic
+    #[arg(
+        long,
+        value_name = "OFFSET",
+        default_value_t = 0,
+        requires = "magic",
+        help = "Look for the bytes of --magic at OFFSET bytes into the \
+        payload"
+    )]
+    pub magic_offset: usize,
+
+    // handling of requests with invalid checksums
+    #[arg(
+        long,
+        help = "Do not answer requests with an invalid icmp checksum; they \
+        are logged in any case"
+    )]
+    pub reject_corrupted: bool,
+
+    // share of dropped requests
+    #[arg(
+        long,
+        value_name = "PERCENT",
+        value_parser = parse_percent,
+        help = "Drop PERCENT percent of the requests at random instead of \
+        answering them"
+    )]
+    pub drop: Option<f64>,
+
+    // delay of replies
+    #[arg(
+        long,
+        value_name = "MILLISECONDS",
+        value_parser = parse_millis,
+        help = "Delay replies by MILLISECONDS milliseconds"
+    )]
+    pub delay: Option<f64>,
+
+    // jitter of the delay of replies
+    #[arg(
+        long,
+        value_name = "MILLISECONDS",
+        value_parser = parse_millis,
+        help = "Delay replies by a random time of up to MILLISECONDS \
+        milliseconds in addition to --delay"
+    )]
+    pub jitter: Option<f64>,
+
+    // share of replies with corrupted payload
+    #[arg(
+        long,
+        value_name = "PERCENT",
+        value_parser = parse_percent,
+        help = "Change a random byte of the payload of PERCENT percent of the \
+        replies; the checksum of the reply is still valid"
+    )]
+    pub corrupt_payload: Option<f64>,
+
+    // share of replies with corrupted checksum
+    #[arg(
+        long,
+        value_name = "PERCENT",
+        value_parser = parse_percent,
+        help = "Send PERCENT percent of the replies with an invalid checksum"
+    )]
+    pub corrupt_checksum: Option<f64>,
+}
+
+// parse the identifier of requests as decimal or hex number
+fn parse_identifier(s: &str) -> Result<u16, String> {
+    let identifier = match s.strip_prefix("0x") {
+        Some(hex) => u16::from_str_radix(hex, 16),
+        None => s.parse::<u16>(),
+    };
+    identifier.map_err(|_| format!("invalid identifier: {}", s))
+}
+
+// parse the payload magic from up to 16 bytes in hex
+fn parse_magic(s: &str) -> Result<Vec<u8>, String> {
+    match parse_hex(s) {
+        Some(magic) if !magic.is_empty() && magic.len() <= 16 => Ok(magic),
+        _ => Err(format!("invalid magic: {}", s)),
+    }
+}
+
+// parse a percentage between 0 and 100
+fn parse_percent(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(percent) if (0.0..=100.0).contains(&percent) => Ok(percent),
+        _ => Err(format!("invalid percentage: {}", s)),
+    }
+}
+
+// parse a time in milliseconds
+fn parse_millis(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(millis) if millis.is_finite() && millis >= 0.0 => Ok(millis),
+        _ => Err(format!("invalid time: {}", s)),
+    }
+}
+
+// parse an ip network in cidr notation or a single ip address
+fn parse_network(s: &str) -> Result<IpNetwork, String> {
+    s.parse::<IpNetwork>()
+        .map_err(|_| format!("invalid network: {}", s))
+}
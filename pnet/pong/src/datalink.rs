@@ -0,0 +1,134 @@
+use pnet::datalink::MacAddr;
+use pnet::packet::arp::{ArpHardwareTypes, ArpOperations, ArpPacket, MutableArpPacket};
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket, MutableEthernetPacket};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv4::{self, Ipv4Flags, Ipv4Packet, MutableIpv4Packet};
+use pnet::packet::{MutablePacket, Packet};
+
+use std::net::Ipv4Addr;
+
+// packet constants
+const ETHERNET_HEADER_SIZE: usize = EthernetPacket::minimum_packet_size();
+const ARP_SIZE: usize = ArpPacket::minimum_packet_size();
+const IPV4_HEADER_SIZE: usize = Ipv4Packet::minimum_packet_size();
+
+// default ttl of replies sent on the datalink layer
+pub const DEFAULT_TTL: u8 = 64;
+
+// get the type of service of the ipv4 packet from its dscp and ecn fields
+pub fn get_tos(ipv4_packet: &Ipv4Packet) -> u8 {
+    (ipv4_packet.get_dscp() << 2) | ipv4_packet.get_ecn()
+}
+
+// parse the ethernet frame and create the arp reply with mac if it is an arp
+// request for address; returns the sender ip and mac address of the request
+// with the reply
+pub fn create_arp_reply(
+    frame: &[u8],
+    mac: MacAddr,
+    address: Ipv4Addr,
+) -> Option<(Ipv4Addr, MacAddr, Vec<u8>)> {
+    let ethernet_packet = EthernetPacket::new(frame)?;
+    if ethernet_packet.get_ethertype() != EtherTypes::Arp {
+        return None;
+    }
+    let request = ArpPacket::new(ethernet_packet.payload())?;
+    if request.get_operation() != ArpOperations::Request
+        || request.get_target_proto_addr() != address
+    {
+        return None;
+    }
+    let sender_ip = request.get_sender_proto_addr();
+    let sender_mac = request.get_sender_hw_addr();
+
+    // create arp packet
+    let mut arp_buffer = [0u8; ARP_SIZE];
+    let mut arp_packet = MutableArpPacket::new(&mut arp_buffer).unwrap();
+    arp_packet.set_hardware_type(ArpHardwareTypes::Ethernet);
+    arp_packet.set_protocol_type(EtherTypes::Ipv4);
+    arp_packet.set_hw_addr_len(6);
+    arp_packet.set_proto_addr_len(4);
+    arp_packet.set_operation(ArpOperations::Reply);
+    arp_packet.set_sender_hw_addr(mac);
+    arp_packet.set_sender_proto_addr(address);
+    arp_packet.set_target_hw_addr(sender_mac);
+    arp_packet.set_target_proto_addr(sender_ip);
+
+    // create ethernet packet
+    let mut ethernet_buffer = vec![0u8; ETHERNET_HEADER_SIZE + ARP_SIZE];
+    let mut ethernet_packet = MutableEthernetPacket::new(&mut ethernet_buffer).unwrap();
+    ethernet_packet.set_source(mac);
+    ethernet_packet.set_destination(sender_mac);
+    ethernet_packet.set_ethertype(EtherTypes::Arp);
+    ethernet_packet.set_payload(arp_packet.packet_mut());
+
+    Some((sender_ip, sender_mac, ethernet_buffer))
+}
+
+// parse the ethernet frame and get the source mac and ip address, the type
+// of service and the icmp packet if it is an unfragmented icmp packet to
+// address
+pub fn parse_icmp_frame(frame: &[u8], address: Ipv4Addr) -> Option<(MacAddr, Ipv4Addr, u8, &[u8])> {
+    let ethernet_packet = EthernetPacket::new(frame)?;
+    if ethernet_packet.get_ethertype() != EtherTypes::Ipv4 {
+        return None;
+    }
+    let payload = &frame[ETHERNET_HEADER_SIZE..];
+    let ipv4_packet = Ipv4Packet::new(payload)?;
+    if ipv4_packet.get_destination() != address
+        || ipv4_packet.get_next_level_protocol() != IpNextHeaderProtocols::Icmp
+        || ipv4_packet.get_flags() & Ipv4Flags::MoreFragments != 0
+        || ipv4_packet.get_fragment_offset() != 0
+    {
+        return None;
+    }
+    let header_length = usize::from(ipv4_packet.get_header_length()) * 4;
+    let total_length = usize::from(ipv4_packet.get_total_length());
+    if header_length < IPV4_HEADER_SIZE
+        || total_length > payload.len()
+        || total_length < header_length
+    {
+        return None;
+    }
+    Some((
+        ethernet_packet.get_source(),
+        ipv4_packet.get_source(),
+        get_tos(&ipv4_packet),
+        &payload[header_length..total_length],
+    ))
+}
+
+// create the ethernet frame with the icmp reply from source mac and ip
+// address to destination mac and ip address with ttl and type of service
+pub fn create_reply_frame(
+    source: (MacAddr, Ipv4Addr),
+    destination: (MacAddr, Ipv4Addr),
+    (ttl, tos): (u8, u8),
+    reply: &[u8],
+) -> Vec<u8> {
+    // create ipv4 packet
+    let mut ipv4_buffer = vec![0u8; IPV4_HEADER_SIZE + reply.len()];
+    let mut ipv4_packet = MutableIpv4Packet::new(&mut ipv4_buffer).unwrap();
+    ipv4_packet.set_version(4);
+    ipv4_packet.set_header_length((IPV4_HEADER_SIZE / 4) as u8);
+    ipv4_packet.set_dscp(tos >> 2);
+    ipv4_packet.set_ecn(tos & 0b11);
+    ipv4_packet.set_total_length((IPV4_HEADER_SIZE + reply.len()) as u16);
+    ipv4_packet.set_flags(Ipv4Flags::DontFragment);
+    ipv4_packet.set_ttl(ttl);
+    ipv4_packet.set_next_level_protocol(IpNextHeaderProtocols::Icmp);
+    ipv4_packet.set_source(source.1);
+    ipv4_packet.set_destination(destination.1);
+    ipv4_packet.set_payload(reply);
+    ipv4_packet.set_checksum(ipv4::checksum(&ipv4_packet.to_immutable()));
+
+    // create ethernet packet
+    let mut ethernet_buffer = vec![0u8; ETHERNET_HEADER_SIZE + ipv4_buffer.len()];
+    let mut ethernet_packet = MutableEthernetPacket::new(&mut ethernet_buffer).unwrap();
+    ethernet_packet.set_source(source.0);
+    ethernet_packet.set_destination(destination.0);
+    ethernet_packet.set_ethertype(EtherTypes::Ipv4);
+    ethernet_packet.set_payload(&ipv4_buffer);
+
+    ethernet_buffer
+}
@@ -0,0 +1,101 @@
+use packet_utils::checksum::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::time::{Duration, Instant};
+
+use crate::channel::Reply;
+use crate::config::Config;
+
+// offset of the data after the sequence number in icmp replies
+const DATA_OFFSET: usize = 8;
+
+// injector of faults into the replies to test the handling of losses,
+// round-trip times and corrupted replies of ping clients
+pub struct FaultInjector {
+    drop: f64,
+    delay: Duration,
+    jitter: Duration,
+    corrupt_payload: f64,
+    corrupt_checksum: f64,
+    rng: StdRng,
+}
+
+impl FaultInjector {
+    pub fn new(config: &Config) -> FaultInjector {
+        let millis = |millis: Option<f64>| Duration::from_secs_f64(millis.unwrap_or(0.0) / 1000.0);
+        FaultInjector {
+            drop: config.drop.unwrap_or(0.0) / 100.0,
+            delay: millis(config.delay),
+            jitter: millis(config.jitter),
+            corrupt_payload: config.corrupt_payload.unwrap_or(0.0) / 100.0,
+            corrupt_checksum: config.corrupt_checksum.unwrap_or(0.0) / 100.0,
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    // check if the next request should be dropped
+    pub fn is_dropped(&mut self) -> bool {
+        self.rng.gen_bool(self.drop)
+    }
+
+    // get the delay of the next reply
+    pub fn get_delay(&mut self) -> Duration {
+        self.delay + self.jitter.mul_f64(self.rng.gen::<f64>())
+    }
+
+    // corrupt the payload or the checksum of the reply with a valid checksum
+    // and add the names of the faults to faults
+    pub fn corrupt(&mut self, reply: &mut [u8], faults: &mut Vec<String>) {
+        if reply.len() > DATA_OFFSET && self.rng.gen_bool(self.corrupt_payload) {
+            let index = self.rng.gen_range(DATA_OFFSET..reply.len());
+            reply[index] ^= self.rng.gen_range(1..=u8::MAX);
+
+            // adjust the checksum to the changed byte, so the reply is still
+            // valid
+            set_checksum(reply, ICMP_CHECKSUM_OFFSET);
+            faults.push("corrupted payload".to_string());
+        }
+        if self.rng.gen_bool(self.corrupt_checksum) {
+            reply[ICMP_CHECKSUM_OFFSET] ^= self.rng.gen_range(1..=u8::MAX);
+            faults.push("corrupted checksum".to_string());
+        }
+    }
+}
+
+// queue of delayed replies ordered by the time they are due; replies due at
+// the same time keep their order
+#[derive(Default)]
+pub struct DelayQueue {
+    replies: BinaryHeap<Reverse<(Instant, u64, Reply)>>,
+    count: u64,
+}
+
+impl DelayQueue {
+    // add the reply that is due after delay
+    pub fn push(&mut self, delay: Duration, reply: Reply) {
+        let due = Instant::now() + delay;
+        self.replies.push(Reverse((due, self.count, reply)));
+        self.count += 1;
+    }
+
+    // remove and get the next reply if it is due
+    pub fn pop_due(&mut self) -> Option<Reply> {
+        let Reverse((due, ..)) = self.replies.peek()?;
+        if *due > Instant::now() {
+            return None;
+        }
+        let Reverse((_, _, reply)) = self.replies.pop()?;
+        Some(reply)
+    }
+
+    // get the time until the next reply is due or timeout if there is none
+    pub fn get_wait(&self, timeout: Duration) -> Duration {
+        match self.replies.peek() {
+            Some(Reverse((due, ..))) => due.saturating_duration_since(Instant::now()).min(timeout),
+            None => timeout,
+        }
+    }
+}
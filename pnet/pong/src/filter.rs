@@ -0,0 +1,60 @@
+use pnet::ipnetwork::IpNetwork;
+
+use std::net::IpAddr;
+
+use crate::config::Config;
+use crate::reply::{Request, HEADER_SIZE};
+
+// filter of the sources that get echo replies
+pub struct SourceFilter {
+    allow: Vec<IpNetwork>,
+    deny: Vec<IpNetwork>,
+}
+
+impl SourceFilter {
+    pub fn new(config: &Config) -> SourceFilter {
+        SourceFilter {
+            allow: config.allow.clone(),
+            deny: config.deny.clone(),
+        }
+    }
+
+    // check if source gets replies; without allowed networks, all sources
+    // that are not denied are allowed
+    pub fn is_allowed(&self, source: IpAddr) -> bool {
+        let allowed = self.allow.is_empty() || self.allow.iter().any(|n| n.contains(source));
+        allowed && !self.deny.iter().any(|n| n.contains(source))
+    }
+}
+
+// filter of the requests that get replies by their identifier and payload
+// magic, so test harnesses do not answer each other's requests
+pub struct RequestFilter {
+    identifiers: Vec<u16>,
+    magic: Option<Vec<u8>>,
+    magic_offset: usize,
+}
+
+impl RequestFilter {
+    pub fn new(config: &Config) -> RequestFilter {
+        RequestFilter {
+            identifiers: config.identifier.clone(),
+            magic: config.magic.clone(),
+            magic_offset: config.magic_offset,
+        }
+    }
+
+    // check if the request in the icmp packet gets a reply; without
+    // identifiers or magic, all requests are answered
+    pub fn is_allowed(&self, request: &Request, packet: &[u8]) -> bool {
+        if !self.identifiers.is_empty() && !self.identifiers.contains(&request.identifier) {
+            return false;
+        }
+        match &self.magic {
+            Some(magic) => packet[HEADER_SIZE..]
+                .get(self.magic_offset..)
+                .is_some_and(|payload| payload.starts_with(magic)),
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use packet_utils::pacer::TokenBucket;
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+
+// maximum number of sources with their own token bucket; further sources
+// share a single bucket, so spoofed source addresses cannot exhaust the
+// memory or the time spent per request
+const MAX_SOURCES: usize = 4096;
+
+// interval in which idle per-source buckets are removed
+const SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+// interval in which dropped requests are reported
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+// get the burst of token buckets for rate packets per second: the
+// configured burst or one second at the rate by default
+fn get_burst(rate: f64, config: &Config) -> f64 {
+    config.burst.map_or(rate, f64::from)
+}
+
+// rate limiter of echo replies with a global and a per-source token bucket
+// that counts the dropped requests
+pub struct RateLimiter {
+    rate: Option<TokenBucket>,
+    source_rate: Option<(f64, f64)>,
+    sources: HashMap<IpAddr, TokenBucket>,
+
+    // bucket shared by the sources over the maximum number of sources
+    overflow: Option<TokenBucket>,
+    last_sweep: Instant,
+
+    // requests dropped by the global and the per-source limit in total and
+    // since the last report
+    dropped: u64,
+    dropped_global: u64,
+    dropped_source: u64,
+    last_report: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(config: &Config) -> RateLimiter {
+        RateLimiter {
+            rate: config
+                .rate
+                .map(|rate| TokenBucket::with_burst(rate, get_burst(rate, config))),
+            source_rate: config
+                .source_rate
+                .map(|rate| (rate, get_burst(rate, config))),
+            sources: HashMap::new(),
+            overflow: config
+                .source_rate
+                .map(|rate| TokenBucket::with_burst(rate, get_burst(rate, config))),
+            last_sweep: Instant::now(),
+            dropped: 0,
+            dropped_global: 0,
+            dropped_source: 0,
+            last_report: Instant::now(),
+        }
+    }
+
+    // check if a reply to source is allowed; the per-source limit is checked
+    // first, so a single flooding source does not use up the global limit
+    pub fn allow(&mut self, source: IpAddr) -> bool {
+        if let Some((rate, burst)) = self.source_rate {
+            let bucket = if self.sources.contains_key(&source) {
+                self.sources.get_mut(&source)
+            } else if self.sources.len() < MAX_SOURCES {
+                Some(
+                    self.sources
+                        .entry(source)
+                        .or_insert_with(|| TokenBucket::with_burst(rate, burst)),
+                )
+            } else {
+                self.overflow.as_mut()
+            };
+            if bucket.is_some_and(|bucket| !bucket.try_take()) {
+                self.dropped_source += 1;
+                return false;
+            }
+        }
+        if let Some(bucket) = &mut self.rate {
+            if !bucket.try_take() {
+                self.dropped_global += 1;
+                return false;
+            }
+        }
+        true
+    }
+
+    // remove the per-source buckets that refilled completely if the sweep
+    // interval is over, so the buckets of idle sources do not use up the
+    // maximum number of sources
+    pub fn sweep(&mut self) {
+        if self.last_sweep.elapsed() < SWEEP_INTERVAL {
+            return;
+        }
+        self.sources.retain(|_, bucket| !bucket.is_full());
+        self.last_sweep = Instant::now();
+    }
+
+    // get the number of requests dropped by the global and the per-source
+    // limit since the last report if the report interval is over
+    pub fn get_report(&mut self) -> Option<(u64, u64)> {
+        let dropped = self.dropped_global + self.dropped_source;
+        if dropped == 0 || self.last_report.elapsed() < REPORT_INTERVAL {
+            return None;
+        }
+        let report = (self.dropped_global, self.dropped_source);
+        self.dropped += dropped;
+        self.dropped_global = 0;
+        self.dropped_source = 0;
+        self.last_report = Instant::now();
+        Some(report)
+    }
+
+    // get the total number of dropped requests
+    pub fn get_dropped(&self) -> u64 {
+        self.dropped + self.dropped_global + self.dropped_source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::net::Ipv4Addr;
+
+    fn limiter(args: &[&str]) -> RateLimiter {
+        let config = Config::parse_from([&["pong"], args].concat());
+        RateLimiter::new(&config)
+    }
+
+    fn source(i: u32) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::from(0x0a00_0000 + i))
+    }
+
+    #[test]
+    fn allow_drops_requests_over_source_rate() {
+        let mut limiter = limiter(&["--source-rate", "1", "--burst", "2"]);
+        assert!(limiter.allow(source(1)));
+        assert!(limiter.allow(source(1)));
+        assert!(!limiter.allow(source(1)));
+        assert!(limiter.allow(source(2)));
+        assert_eq!(limiter.get_dropped(), 1);
+    }
+
+    #[test]
+    fn allow_shares_a_bucket_over_max_sources() {
+        let mut limiter = limiter(&["--source-rate", "1", "--burst", "1"]);
+        for i in 0..MAX_SOURCES as u32 {
+            assert!(limiter.allow(source(i)));
+        }
+        assert_eq!(limiter.sources.len(), MAX_SOURCES);
+
+        // sources over the limit share the overflow bucket
+        let first = MAX_SOURCES as u32;
+        assert!(limiter.allow(source(first)));
+        assert!(!limiter.allow(source(first + 1)));
+        assert_eq!(limiter.sources.len(), MAX_SOURCES);
+
+        // known sources keep their own bucket
+        assert!(!limiter.allow(source(0)));
+        assert_eq!(limiter.get_dropped(), 2);
+    }
+
+    #[test]
+    fn sweep_removes_idle_sources() {
+        let mut limiter = limiter(&["--source-rate", "1000", "--burst", "1"]);
+        assert!(limiter.allow(source(1)));
+        limiter.last_sweep -= SWEEP_INTERVAL;
+        std::thread::sleep(Duration::from_millis(5));
+        limiter.sweep();
+        assert!(limiter.sources.is_empty());
+    }
+}
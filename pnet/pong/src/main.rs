@@ -1,51 +1,231 @@
 extern crate pnet;
 
-use pnet::packet::icmp::IcmpTypes;
-use pnet::packet::icmp::MutableIcmpPacket;
+mod channel;
+mod config;
+mod datalink;
+mod fault;
+mod filter;
+mod limiter;
+mod output;
+mod reply;
+mod stats;
+mod worker;
+
+use clap::Parser;
+
+use packet_utils::interface::get_interface;
+use pnet::packet::icmp::IcmpPacket;
 use pnet::packet::ip::IpNextHeaderProtocols;
-use pnet::packet::{MutablePacket, Packet};
-use pnet::transport::icmp_packet_iter;
 use pnet::transport::transport_channel;
 use pnet::transport::TransportChannelType::Layer4;
 use pnet::transport::TransportProtocol::Ipv4;
 
+use std::io;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use channel::*;
+use config::Config;
+use datalink::DEFAULT_TTL;
+use fault::FaultInjector;
+use filter::{RequestFilter, SourceFilter};
+use limiter::RateLimiter;
+use output::Output;
+use reply::*;
+use stats::Statistics;
+use worker::{Job, Responder};
+
+// interval in which the receive loop checks if it should stop or report
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// number of received requests that wait for a worker before the receive loop
+// blocks
+const QUEUE_SIZE: usize = 1024;
+
 fn main() {
-    let protocol = Layer4(Ipv4(IpNextHeaderProtocols::Icmp));
-    let (mut tx, mut rx) = match transport_channel(4096, protocol) {
-        Ok((tx, rx)) => (tx, rx),
+    let config = Config::parse();
+    let mut limiter = RateLimiter::new(&config);
+    let output = Output::new(&config);
+    let (tx, mut rx) = create_channel(&config);
+
+    // stop on ctrl-c or sigterm and print the statistics; a second signal
+    // exits immediately
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            if !running.swap(false, atomic::Ordering::Relaxed) {
+                std::process::exit(1);
+            }
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    // receive requests and pass them on to the workers that answer them
+    let stats = Statistics::new(Duration::from_secs_f64(config.report));
+    let responder = Responder::new(tx, stats, &output);
+    let (jobs_tx, jobs_rx) = mpsc::sync_channel(QUEUE_SIZE);
+    let jobs_rx = Mutex::new(jobs_rx);
+    let receiving = AtomicBool::new(true);
+    let failed = std::thread::scope(|s| {
+        for _ in 0..config.workers {
+            let faults = FaultInjector::new(&config);
+            let (responder, jobs_rx) = (&responder, &jobs_rx);
+            s.spawn(move || responder.work(jobs_rx, faults));
+        }
+        s.spawn(|| responder.send_delayed(&receiving));
+        let failed = receive_requests(
+            &mut rx,
+            &config,
+            &responder,
+            &mut limiter,
+            jobs_tx,
+            &running,
+        );
+        receiving.store(false, atomic::Ordering::Relaxed);
+        failed
+    });
+
+    output.summary(&responder.into_stats(), limiter.get_dropped());
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+// create the transport channel or, if an address is configured, the datalink
+// channel on the interface that answers arp and icmp requests for the address
+fn create_channel(config: &Config) -> (Sender, Receiver) {
+    let address = match config.address {
+        Some(address) => address,
+        None => {
+            let protocol = Layer4(Ipv4(IpNextHeaderProtocols::Icmp));
+            let (mut tx, rx) = match transport_channel(4096, protocol) {
+                Ok((tx, rx)) => (tx, rx),
+                Err(e) => panic!(
+                    "An error occurred when creating the transport channel: {}",
+                    e
+                ),
+            };
+            if let Some(ttl) = config.ttl {
+                if let Err(e) = tx.set_ttl(ttl) {
+                    panic!("An error occurred when setting the ttl: {}", e);
+                }
+            }
+            return (Sender::Transport(tx), Receiver::Transport(rx));
+        }
+    };
+    let name = config.interface.as_deref().unwrap();
+    let interface = match get_interface(name) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting interface {}", name),
+    };
+    let mac = match interface.mac {
+        Some(mac) => mac,
+        None => panic!("An error occurred when getting the mac address of {}", name),
+    };
+    let (tx, rx) = match packet_utils::channel::create_channel(&interface, READ_TIMEOUT, false) {
+        Ok(channel) => channel,
         Err(e) => panic!(
-            "An error occurred when creating the transport channel: {}",
+            "An error occurred when creating the datalink channel: {}",
             e
         ),
     };
+    (
+        Sender::Datalink {
+            tx,
+            mac,
+            address,
+            ttl: config.ttl.unwrap_or(DEFAULT_TTL),
+        },
+        Receiver::Datalink { rx, mac, address },
+    )
+}
 
-    let mut iter = icmp_packet_iter(&mut rx);
-    loop {
-        match iter.next() {
-            Ok((packet, addr)) => {
-                // only handle icmp echo requests
-                if packet.get_icmp_type() != IcmpTypes::EchoRequest {
-                    continue;
-                }
-                println!("got icmp echo request from {}", addr);
+// receive requests on rx until running is unset and pass the ones that pass
+// the filters and rate limits on to the workers with jobs; arp requests are
+// answered right away; returns true if receiving failed
+fn receive_requests(
+    rx: &mut Receiver,
+    config: &Config,
+    responder: &Responder,
+    limiter: &mut RateLimiter,
+    jobs: SyncSender<Job>,
+    running: &AtomicBool,
+) -> bool {
+    let filter = SourceFilter::new(config);
+    let request_filter = RequestFilter::new(config);
+    let output = responder.get_output();
+    while running.load(atomic::Ordering::Relaxed) {
+        {
+            let mut stats = responder.lock_stats();
+            if stats.is_report_due() {
+                output.report(&stats, limiter.get_dropped());
+                stats.next_window();
+            }
+        }
+        limiter.sweep();
+        let result = rx.receive(READ_TIMEOUT);
+        let received = SystemTime::now();
+        let (source, tos, packet) = match result {
+            Ok(Some(Message::Icmp(source, tos, packet))) => (source, tos, packet),
+            Ok(Some(Message::Arp(sender_ip, sender_mac, reply))) => {
+                output.arp_request(sender_ip, sender_mac);
+                responder.send_arp_reply(sender_ip, &reply);
+                continue;
+            }
+            Ok(None) => continue,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                output.read_error(&e);
+                return true;
+            }
+        };
 
-                // create echo reply packet
-                let mut vec: Vec<u8> = vec![0; packet.packet().len()];
-                let mut reply = MutableIcmpPacket::new(&mut vec[..]).unwrap();
+        // only handle supported icmp requests
+        let addr = source.ip;
+        let request = match IcmpPacket::new(&packet).and_then(|p| parse_request(&p)) {
+            Some(request) => request,
+            None => continue,
+        };
 
-                reply.clone_from(&packet);
-                reply.set_icmp_type(IcmpTypes::EchoReply);
-                reply.set_checksum(pnet::util::checksum(reply.packet(), 1));
+        // ignore requests from sources that are not allowed
+        if !filter.is_allowed(addr) {
+            continue;
+        }
 
-                // send echo reply back to sender address
-                match tx.send_to(reply, addr) {
-                    Ok(n) => assert_eq!(n, packet.packet().len()),
-                    Err(e) => panic!("failed to send packet: {}", e),
-                }
-            }
-            Err(e) => {
-                panic!("An error occurred while reading: {}", e);
+        // ignore requests of other test harnesses
+        if !request_filter.is_allowed(&request, &packet) {
+            continue;
+        }
+
+        // do not answer corrupted requests if configured
+        if !request.checksum && config.reject_corrupted {
+            output.rejected(addr, &request);
+            continue;
+        }
+
+        // drop requests over the rate limits and report them
+        if !limiter.allow(addr) {
+            if let Some((global, source)) = limiter.get_report() {
+                output.dropped(global, source, limiter.get_dropped());
             }
+            continue;
+        }
+
+        // reply with the configured or the mirrored type of service
+        let job = Job {
+            source,
+            tos: config.tos.or(config.mirror_tos.then_some(tos)),
+            packet,
+            request,
+            received,
+        };
+        if jobs.send(job).is_err() {
+            return true;
         }
     }
+    false
 }
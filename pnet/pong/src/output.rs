@@ -0,0 +1,221 @@
+use packet_utils::json::json_string;
+use pnet::datalink::MacAddr;
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr};
+
+use crate::config::Config;
+use crate::reply::Request;
+use crate::stats::Statistics;
+
+// printer of the requests and statistics of the pong tool as text or as one
+// json object per line
+pub struct Output {
+    json: bool,
+}
+
+impl Output {
+    pub fn new(config: &Config) -> Output {
+        Output { json: config.json }
+    }
+
+    // print the request received from source that passed the filters and
+    // rate limits with the faults injected into its reply
+    pub fn request(&self, source: IpAddr, request: &Request, faults: &[String]) {
+        if self.json {
+            let faults: Vec<_> = faults.iter().map(|f| format!("\"{}\"", f)).collect();
+            println!(
+                "{{\"type\":\"request\",\"source\":\"{}\",\"request\":\"{}\",\
+                \"identifier\":{},\"sequence\":{},\"size\":{},\"checksum\":{},\
+                \"faults\":[{}]}}",
+                source,
+                request.name,
+                request.identifier,
+                request.sequence,
+                request.size,
+                request.checksum,
+                faults.join(",")
+            );
+            return;
+        }
+        let faults = if faults.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", faults.join(", "))
+        };
+        println!(
+            "got icmp {} from {}: id={} seq={} size={}{}{}",
+            request.name,
+            source,
+            request.identifier,
+            request.sequence,
+            request.size,
+            if request.checksum {
+                ""
+            } else {
+                " (BAD CHECKSUM!)"
+            },
+            faults
+        );
+    }
+
+    // print the request received from source that is not answered because
+    // of its invalid checksum
+    pub fn rejected(&self, source: IpAddr, request: &Request) {
+        if self.json {
+            println!(
+                "{{\"type\":\"rejected\",\"source\":\"{}\",\"request\":\"{}\",\
+                \"identifier\":{},\"sequence\":{},\"size\":{}}}",
+                source, request.name, request.identifier, request.sequence, request.size
+            );
+            return;
+        }
+        println!(
+            "rejected icmp {} from {} with bad checksum: id={} seq={} size={}",
+            request.name, source, request.identifier, request.sequence, request.size
+        );
+    }
+
+    // print the arp request for the answered address from sender ip and mac
+    // address
+    pub fn arp_request(&self, sender_ip: Ipv4Addr, sender_mac: MacAddr) {
+        if self.json {
+            println!(
+                "{{\"type\":\"arp\",\"source\":\"{}\",\"mac\":\"{}\"}}",
+                sender_ip, sender_mac
+            );
+            return;
+        }
+        println!("got arp request from {} ({})", sender_ip, sender_mac);
+    }
+
+    // print that sending the reply to destination failed with error
+    pub fn send_error(&self, destination: IpAddr, error: &io::Error) {
+        if self.json {
+            println!(
+                "{{\"type\":\"error\",\"destination\":\"{}\",\"error\":{}}}",
+                destination,
+                json_string(&error.to_string())
+            );
+            return;
+        }
+        println!("Could not send reply to {}: {}", destination, error);
+    }
+
+    // print that receiving requests failed with error, which stops the
+    // responder
+    pub fn read_error(&self, error: &io::Error) {
+        if self.json {
+            println!(
+                "{{\"type\":\"error\",\"error\":{}}}",
+                json_string(&error.to_string())
+            );
+            return;
+        }
+        println!("An error occurred while reading: {}", error);
+    }
+
+    // print the number of requests dropped over the global and the
+    // per-source rate limit since the last report and in total
+    pub fn dropped(&self, global: u64, source: u64, total: u64) {
+        if self.json {
+            println!(
+                "{{\"type\":\"dropped\",\"global\":{},\"source\":{},\"total\":{}}}",
+                global, source, total
+            );
+            return;
+        }
+        println!(
+            "dropped {} requests over the global and {} over the per-source \
+            rate limit, {} in total",
+            global, source, total
+        );
+    }
+
+    // print the statistics of the current reporting interval with the
+    // number of dropped requests
+    pub fn report(&self, stats: &Statistics, dropped: u64) {
+        let (window, sources) = stats.get_window();
+        let total = stats.get_total();
+        if self.json {
+            println!(
+                "{{\"type\":\"report\",\"interval\":{},\"requests\":{},\"bytes\":{},\
+                \"sources\":{},\"total_requests\":{},\"total_sources\":{},\"dropped\":{}}}",
+                stats.get_interval().as_secs_f64(),
+                window.requests,
+                window.bytes,
+                sources,
+                total.requests,
+                stats.get_sources(),
+                dropped
+            );
+            return;
+        }
+        println!(
+            "answered {} requests with {} bytes from {} sources in the last {}s, \
+            {} requests from {} sources and {} dropped in total",
+            window.requests,
+            window.bytes,
+            sources,
+            stats.get_interval().as_secs_f64(),
+            total.requests,
+            stats.get_sources(),
+            dropped
+        );
+    }
+
+    // print the totals and the statistics of each source ordered by their
+    // number of requests
+    pub fn summary(&self, stats: &Statistics, dropped: u64) {
+        let total = stats.get_total();
+        let peers = stats.get_peers();
+        if self.json {
+            let peers: Vec<_> = peers
+                .iter()
+                .map(|(source, c)| {
+                    format!(
+                        "{{\"source\":\"{}\",\"requests\":{},\"bytes\":{}}}",
+                        source, c.requests, c.bytes
+                    )
+                })
+                .collect();
+            println!(
+                "{{\"type\":\"summary\",\"requests\":{},\"bytes\":{},\"dropped\":{},\
+                \"errors\":{},\"time\":{},\"sources\":[{}]}}",
+                total.requests,
+                total.bytes,
+                dropped,
+                stats.get_errors(),
+                stats.get_time().as_millis(),
+                peers.join(",")
+            );
+            return;
+        }
+        println!("\n--- pong statistics ---");
+        let errors = match stats.get_errors() {
+            0 => String::new(),
+            errors => format!(", {} send errors", errors),
+        };
+        println!(
+            "{} requests answered with {} bytes from {} sources, {} dropped{}, time {}ms",
+            total.requests,
+            total.bytes,
+            peers.len(),
+            dropped,
+            errors,
+            stats.get_time().as_millis()
+        );
+        if peers.is_empty() {
+            return;
+        }
+        println!("\n{:<39}  {:>10}  {:>12}", "SOURCE", "REQUESTS", "BYTES");
+        for (source, counters) in peers {
+            println!(
+                "{:<39}  {:>10}  {:>12}",
+                source.to_string(),
+                counters.requests,
+                counters.bytes
+            );
+        }
+    }
+}
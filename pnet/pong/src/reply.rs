@@ -0,0 +1,202 @@
+use packet_utils::bytes::*;
+use packet_utils::checksum::*;
+use pnet::packet::icmp::{IcmpPacket, IcmpType, IcmpTypes, MutableIcmpPacket};
+use pnet::packet::Packet;
+
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// sizes of timestamp and address mask messages including the icmp header,
+// identifier and sequence number
+const TIMESTAMP_SIZE: usize = 20;
+const ADDRESS_MASK_SIZE: usize = 12;
+
+// offsets of the receive and transmit timestamps and of the address mask
+const RECEIVE_TIMESTAMP_OFFSET: usize = 12;
+const TRANSMIT_TIMESTAMP_OFFSET: usize = 16;
+const ADDRESS_MASK_OFFSET: usize = 8;
+
+// number of milliseconds in a day
+const DAY_MILLIS: u128 = 24 * 60 * 60 * 1000;
+
+// size of the icmp header with identifier and sequence number of requests
+pub const HEADER_SIZE: usize = 8;
+
+// icmp request that is answered
+pub struct Request {
+    pub name: &'static str,
+    pub identifier: u16,
+    pub sequence: u16,
+
+    // size of the data after the sequence number, the payload of echo
+    // requests
+    pub size: usize,
+
+    // whether the icmp checksum of the request is valid
+    pub checksum: bool,
+}
+
+// get the name of the icmp request type that is answered; returns none for
+// other message types
+fn get_request_name(icmp_type: IcmpType) -> Option<&'static str> {
+    match icmp_type {
+        IcmpTypes::EchoRequest => Some("echo request"),
+        IcmpTypes::Timestamp => Some("timestamp request"),
+        IcmpTypes::AddressMaskRequest => Some("address mask request"),
+        _ => None,
+    }
+}
+
+// parse the icmp packet and get the request; returns none if packet is not
+// a supported request
+pub fn parse_request(packet: &IcmpPacket) -> Option<Request> {
+    let name = get_request_name(packet.get_icmp_type())?;
+    let buffer = packet.packet();
+    if buffer.len() < HEADER_SIZE {
+        return None;
+    }
+    Some(Request {
+        name,
+        identifier: read_be_u16(&buffer[4..6]),
+        sequence: read_be_u16(&buffer[6..8]),
+        size: buffer.len() - HEADER_SIZE,
+        checksum: is_checksum_valid(buffer, ICMP_CHECKSUM_OFFSET),
+    })
+}
+
+// get time in milliseconds since midnight ut as used in icmp timestamp
+// messages
+fn get_timestamp(time: SystemTime) -> u32 {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    (since_epoch.as_millis() % DAY_MILLIS) as u32
+}
+
+// update the internet checksum for the change of a 16 bit word from old to
+// new (rfc 1624)
+fn update_checksum(checksum: u16, old: u16, new: u16) -> u16 {
+    let mut sum = u32::from(!checksum) + u32::from(!old) + u32::from(new);
+    sum = (sum & 0xffff) + (sum >> 16);
+    sum = (sum & 0xffff) + (sum >> 16);
+    !(sum as u16)
+}
+
+// set the transmit timestamp of the timestamp reply to the current time
+// when it is sent; the checksum is updated incrementally, so an injected
+// checksum fault is kept. other replies are not changed
+pub fn set_transmit_timestamp(reply: &mut [u8]) {
+    if reply.len() < TIMESTAMP_SIZE || reply[0] != IcmpTypes::TimestampReply.0 {
+        return;
+    }
+    let timestamp = get_timestamp(SystemTime::now());
+    let field = &mut reply[TRANSMIT_TIMESTAMP_OFFSET..][..4];
+    let old = [read_be_u16(&field[0..2]), read_be_u16(&field[2..4])];
+    write_be_u32(field, timestamp);
+    let new = [(timestamp >> 16) as u16, timestamp as u16];
+    let checksum = &mut reply[ICMP_CHECKSUM_OFFSET..][..2];
+    let mut sum = read_be_u16(checksum);
+    for (old, new) in old.into_iter().zip(new) {
+        sum = update_checksum(sum, old, new);
+    }
+    write_be_u16(checksum, sum);
+}
+
+// get the address mask of the interface with the local address the kernel
+// uses to reach source; the request does not tell on which interface it was
+// received, so this is the interface the reply is sent on
+fn get_address_mask(source: Ipv4Addr) -> Option<Ipv4Addr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((source, 9)).ok()?;
+    let local = socket.local_addr().ok()?.ip();
+    pnet::datalink::interfaces()
+        .iter()
+        .flat_map(|interface| interface.ips.iter())
+        .find(|network| network.ip() == local)
+        .and_then(|network| match network.mask() {
+            IpAddr::V4(mask) => Some(mask),
+            IpAddr::V6(_) => None,
+        })
+}
+
+// create the reply to the icmp request packet from source received at time
+// received; echo requests are answered with their identifier, sequence
+// number and payload, timestamp requests with the receive timestamp and
+// address mask requests with the mask of the interface; the transmit
+// timestamp is set when the reply is sent. returns none if packet is not a
+// supported or valid request
+pub fn create_reply(packet: &IcmpPacket, source: IpAddr, received: SystemTime) -> Option<Vec<u8>> {
+    let mut buffer = packet.packet().to_vec();
+    let reply_type = match packet.get_icmp_type() {
+        IcmpTypes::EchoRequest => IcmpTypes::EchoReply,
+        IcmpTypes::Timestamp => {
+            if buffer.len() < TIMESTAMP_SIZE {
+                return None;
+            }
+            buffer.truncate(TIMESTAMP_SIZE);
+            let timestamp = get_timestamp(received);
+            write_be_u32(&mut buffer[RECEIVE_TIMESTAMP_OFFSET..][..4], timestamp);
+            IcmpTypes::TimestampReply
+        }
+        IcmpTypes::AddressMaskRequest => {
+            if buffer.len() < ADDRESS_MASK_SIZE {
+                return None;
+            }
+            let mask = match source {
+                IpAddr::V4(source) => get_address_mask(source)?,
+                IpAddr::V6(_) => return None,
+            };
+            buffer.truncate(ADDRESS_MASK_SIZE);
+            buffer[ADDRESS_MASK_OFFSET..][..4].copy_from_slice(&mask.octets());
+            IcmpTypes::AddressMaskReply
+        }
+        _ => return None,
+    };
+
+    let mut reply = MutableIcmpPacket::new(&mut buffer).unwrap();
+    reply.set_icmp_type(reply_type);
+    reply.set_checksum(get_checksum(reply.packet(), ICMP_CHECKSUM_OFFSET));
+    Some(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // get a timestamp request with originate timestamp 1
+    fn timestamp_request() -> Vec<u8> {
+        let mut request = vec![0; TIMESTAMP_SIZE];
+        request[0] = IcmpTypes::Timestamp.0;
+        request[11] = 1;
+        set_checksum(&mut request, ICMP_CHECKSUM_OFFSET);
+        request
+    }
+
+    #[test]
+    fn create_reply_with_receive_time() {
+        let request = timestamp_request();
+        let received = UNIX_EPOCH + Duration::from_millis(DAY_MILLIS as u64 + 1234);
+        let packet = IcmpPacket::new(&request).unwrap();
+        let reply = create_reply(&packet, Ipv4Addr::LOCALHOST.into(), received).unwrap();
+        assert_eq!(reply[0], IcmpTypes::TimestampReply.0);
+        assert_eq!(read_be_u32(&reply[8..12]), 1);
+        assert_eq!(read_be_u32(&reply[RECEIVE_TIMESTAMP_OFFSET..][..4]), 1234);
+        assert!(is_checksum_valid(&reply, ICMP_CHECKSUM_OFFSET));
+    }
+
+    #[test]
+    fn set_transmit_timestamp_keeps_checksum_state() {
+        let request = timestamp_request();
+        let packet = IcmpPacket::new(&request).unwrap();
+        let mut reply = create_reply(&packet, Ipv4Addr::LOCALHOST.into(), UNIX_EPOCH).unwrap();
+        set_transmit_timestamp(&mut reply);
+        assert_ne!(read_be_u32(&reply[TRANSMIT_TIMESTAMP_OFFSET..][..4]), 0);
+        assert!(is_checksum_valid(&reply, ICMP_CHECKSUM_OFFSET));
+
+        // a corrupted checksum stays corrupted
+        reply[ICMP_CHECKSUM_OFFSET] ^= 0x40;
+        set_transmit_timestamp(&mut reply);
+        assert!(!is_checksum_valid(&reply, ICMP_CHECKSUM_OFFSET));
+        reply[ICMP_CHECKSUM_OFFSET] ^= 0x40;
+        assert!(is_checksum_valid(&reply, ICMP_CHECKSUM_OFFSET));
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+// number of sources with their own statistics and of sources counted in a
+// reporting interval, so spoofed source addresses cannot exhaust the memory;
+// requests of other sources are only counted in the totals
+const MAX_PEERS: usize = 65536;
+
+// number of answered requests and the bytes of their replies
+#[derive(Default, Clone, Copy)]
+pub struct Counters {
+    pub requests: u64,
+    pub bytes: u64,
+}
+
+impl Counters {
+    fn add(&mut self, bytes: usize) {
+        self.requests += 1;
+        self.bytes += bytes as u64;
+    }
+}
+
+// statistics of the answered requests in total, per source and in the
+// current reporting interval
+pub struct Statistics {
+    start: Instant,
+    total: Counters,
+    peers: HashMap<IpAddr, Counters>,
+    errors: u64,
+
+    interval: Duration,
+    window_start: Instant,
+    window: Counters,
+    window_sources: HashSet<IpAddr>,
+}
+
+impl Statistics {
+    pub fn new(interval: Duration) -> Statistics {
+        Statistics {
+            start: Instant::now(),
+            total: Counters::default(),
+            peers: HashMap::new(),
+            errors: 0,
+            interval,
+            window_start: Instant::now(),
+            window: Counters::default(),
+            window_sources: HashSet::new(),
+        }
+    }
+
+    // count a request from source answered with a reply of bytes
+    pub fn add_reply(&mut self, source: IpAddr, bytes: usize) {
+        self.total.add(bytes);
+        self.window.add(bytes);
+        if self.window_sources.len() < MAX_PEERS {
+            self.window_sources.insert(source);
+        }
+        if self.peers.len() < MAX_PEERS || self.peers.contains_key(&source) {
+            self.peers.entry(source).or_default().add(bytes);
+        }
+    }
+
+    // count a reply that could not be sent
+    pub fn add_error(&mut self) {
+        self.errors += 1;
+    }
+
+    // check if the current reporting interval is over
+    pub fn is_report_due(&self) -> bool {
+        self.window_start.elapsed() >= self.interval
+    }
+
+    // start the next reporting interval
+    pub fn next_window(&mut self) {
+        self.window_start = Instant::now();
+        self.window = Counters::default();
+        self.window_sources.clear();
+    }
+
+    // get the reporting interval
+    pub fn get_interval(&self) -> Duration {
+        self.interval
+    }
+
+    // get the counters and the number of sources of the current reporting
+    // interval
+    pub fn get_window(&self) -> (Counters, usize) {
+        (self.window, self.window_sources.len())
+    }
+
+    // get the total counters
+    pub fn get_total(&self) -> Counters {
+        self.total
+    }
+
+    // get the number of replies that could not be sent
+    pub fn get_errors(&self) -> u64 {
+        self.errors
+    }
+
+    // get the number of sources
+    pub fn get_sources(&self) -> usize {
+        self.peers.len()
+    }
+
+    // get the counters of each source ordered by their number of requests
+    pub fn get_peers(&self) -> Vec<(IpAddr, Counters)> {
+        let mut peers: Vec<_> = self.peers.iter().map(|(ip, c)| (*ip, *c)).collect();
+        peers.sort_by(|(a, x), (b, y)| y.requests.cmp(&x.requests).then(a.cmp(b)));
+        peers
+    }
+
+    // get the time since the start
+    pub fn get_time(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn add_reply_limits_sources() {
+        let mut stats = Statistics::new(Duration::from_secs(1));
+        for i in 0..=MAX_PEERS as u32 {
+            stats.add_reply(IpAddr::V4(Ipv4Addr::from(i)), 10);
+        }
+        stats.add_reply(IpAddr::V4(Ipv4Addr::from(0)), 10);
+        let (window, sources) = stats.get_window();
+        assert_eq!(window.requests, MAX_PEERS as u64 + 2);
+        assert_eq!(sources, MAX_PEERS);
+        assert_eq!(stats.get_sources(), MAX_PEERS);
+        assert_eq!(stats.get_peers()[0].1.requests, 2);
+
+        stats.next_window();
+        assert_eq!(stats.get_window().1, 0);
+        assert_eq!(stats.get_total().requests, MAX_PEERS as u64 + 2);
+    }
+}
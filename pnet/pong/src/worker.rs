@@ -0,0 +1,149 @@
+use pnet::packet::icmp::IcmpPacket;
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::mpsc::Receiver;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use crate::channel::{Peer, Reply, Sender};
+use crate::fault::{DelayQueue, FaultInjector};
+use crate::output::Output;
+use crate::reply::*;
+use crate::stats::Statistics;
+
+// interval in which the sender of delayed replies checks if it should stop
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// request received from source that is answered by a worker
+pub struct Job {
+    pub source: Peer,
+
+    // type of service of the reply or none for the default
+    pub tos: Option<u8>,
+
+    pub packet: Vec<u8>,
+    pub request: Request,
+
+    // time the request was received
+    pub received: SystemTime,
+}
+
+// state shared by the receive loop, the workers that create and send the
+// replies and the sender of delayed replies
+pub struct Responder<'a> {
+    tx: Mutex<Sender>,
+    stats: Mutex<Statistics>,
+    delayed: Mutex<DelayQueue>,
+    delay_added: Condvar,
+    output: &'a Output,
+}
+
+impl<'a> Responder<'a> {
+    pub fn new(tx: Sender, stats: Statistics, output: &'a Output) -> Responder<'a> {
+        Responder {
+            tx: Mutex::new(tx),
+            stats: Mutex::new(stats),
+            delayed: Mutex::new(DelayQueue::default()),
+            delay_added: Condvar::new(),
+            output,
+        }
+    }
+
+    // get the output
+    pub fn get_output(&self) -> &'a Output {
+        self.output
+    }
+
+    // lock and get the statistics
+    pub fn lock_stats(&self) -> MutexGuard<'_, Statistics> {
+        self.stats.lock().unwrap()
+    }
+
+    // send the reply and count it in the statistics; the transmit timestamp
+    // of timestamp replies is set now. send errors are printed and counted,
+    // but do not stop the responder
+    fn send_reply(&self, mut reply: Reply) {
+        set_transmit_timestamp(&mut reply.packet);
+        let sent = self.tx.lock().unwrap().send(&reply);
+        let destination = reply.destination.ip;
+        let mut stats = self.lock_stats();
+        match sent {
+            Ok(()) => stats.add_reply(destination, reply.packet.len()),
+            Err(e) => {
+                self.output.send_error(destination, &e);
+                stats.add_error();
+            }
+        }
+    }
+
+    // send the arp reply frame to sender ip; send errors are printed
+    pub fn send_arp_reply(&self, sender: Ipv4Addr, frame: &[u8]) {
+        if let Err(e) = self.tx.lock().unwrap().send_frame(frame) {
+            self.output.send_error(IpAddr::V4(sender), &e);
+        }
+    }
+
+    // answer the requests received on jobs with the faults injected by
+    // faults until the channel is closed
+    pub fn work(&self, jobs: &Mutex<Receiver<Job>>, mut faults: FaultInjector) {
+        loop {
+            let job = match jobs.lock().unwrap().recv() {
+                Ok(job) => job,
+                Err(_) => return,
+            };
+
+            // inject the configured faults into the reply
+            if faults.is_dropped() {
+                self.output
+                    .request(job.source.ip, &job.request, &["dropped".to_string()]);
+                continue;
+            }
+            let packet = IcmpPacket::new(&job.packet).unwrap();
+            let mut reply = match create_reply(&packet, job.source.ip, job.received) {
+                Some(reply) => reply,
+                None => continue,
+            };
+            let mut injected = Vec::new();
+            faults.corrupt(&mut reply, &mut injected);
+            let delay = faults.get_delay();
+            if !delay.is_zero() {
+                injected.push(format!("delayed {:.3} ms", delay.as_secs_f64() * 1000.0));
+            }
+            self.output.request(job.source.ip, &job.request, &injected);
+
+            // send reply back to sender address now or after the delay
+            let reply = Reply {
+                destination: job.source,
+                tos: job.tos,
+                packet: reply,
+            };
+            if delay.is_zero() {
+                self.send_reply(reply);
+            } else {
+                self.delayed.lock().unwrap().push(delay, reply);
+                self.delay_added.notify_one();
+            }
+        }
+    }
+
+    // send the delayed replies when they are due until running is unset;
+    // delayed replies that are not due yet are discarded
+    pub fn send_delayed(&self, running: &AtomicBool) {
+        let mut delayed = self.delayed.lock().unwrap();
+        while running.load(atomic::Ordering::Relaxed) {
+            while let Some(reply) = delayed.pop_due() {
+                drop(delayed);
+                self.send_reply(reply);
+                delayed = self.delayed.lock().unwrap();
+            }
+            let wait = delayed.get_wait(POLL_INTERVAL);
+            delayed = self.delay_added.wait_timeout(delayed, wait).unwrap().0;
+        }
+    }
+
+    // get the statistics
+    pub fn into_stats(self) -> Statistics {
+        self.stats.into_inner().unwrap()
+    }
+}
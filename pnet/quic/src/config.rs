@@ -0,0 +1,54 @@
+use clap::Parser;
+
+// command line configuration of the quic tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Listen for quic packets and inspect their initial packets")]
+pub struct Config {
+    // network interface to capture packets on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Capture packets on the network interface NAME; by default, \
+        the first interface that is up, is not a loopback and has an ip \
+        address is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., to see the quic \
+        traffic of other hosts on a mirrored port"
+    )]
+    pub promiscuous: bool,
+
+    // ip versions
+    #[arg(
+        short = '4',
+        long,
+        conflicts_with = "ipv6",
+        help = "Only listen for quic packets over ipv4"
+    )]
+    pub ipv4: bool,
+    #[arg(short = '6', long, help = "Only listen for quic packets over ipv6")]
+    pub ipv6: bool,
+
+    // udp port
+    #[arg(
+        short,
+        long,
+        default_value_t = 443,
+        help = "Listen for quic packets from or to the udp port PORT"
+    )]
+    pub port: u16,
+
+    // only print client hellos
+    #[arg(
+        short,
+        long,
+        help = "Only print the server names and protocols of client hellos \
+        instead of every packet"
+    )]
+    pub sni: bool,
+}
@@ -0,0 +1,183 @@
+use aes::cipher::{generic_array::GenericArray, BlockEncrypt};
+use aes::Aes128;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes128Gcm, Nonce};
+use hkdf::Hkdf;
+use sha2::Sha256;
+
+use crate::packet::{LongHeaderPacket, VERSION_1, VERSION_2};
+
+// salts for deriving the initial secrets from the destination connection id
+// of the client's first initial packet, see rfc 9001 and rfc 9369
+const INITIAL_SALT_V1: [u8; 20] = [
+    0x38, 0x76, 0x2c, 0xf7, 0xf5, 0x59, 0x34, 0xb3, 0x4d, 0x17, 0x9a, 0xe6, 0xa4, 0xc8, 0x0c, 0xad,
+    0xcc, 0xbb, 0x7f, 0x0a,
+];
+const INITIAL_SALT_V2: [u8; 20] = [
+    0x0d, 0xed, 0xe3, 0xde, 0xf7, 0x00, 0xa6, 0xdb, 0x81, 0x93, 0x81, 0xbe, 0x6e, 0x26, 0x9d, 0xcb,
+    0xf9, 0xbd, 0x2e, 0xd9,
+];
+
+// length of the header protection sample and the aead tag
+const SAMPLE_LENGTH: usize = 16;
+const TAG_LENGTH: usize = 16;
+
+// expand the secret with the tls 1.3 hkdf-expand-label function and an
+// empty context into output
+fn expand_label(secret: &[u8], label: &str, output: &mut [u8]) -> Option<()> {
+    let label = format!("tls13 {}", label);
+    let mut info = Vec::with_capacity(4 + label.len());
+    info.extend_from_slice(&(output.len() as u16).to_be_bytes());
+    info.push(label.len() as u8);
+    info.extend_from_slice(label.as_bytes());
+    info.push(0);
+    let hkdf = Hkdf::<Sha256>::from_prk(secret).ok()?;
+    hkdf.expand(&info, output).ok()
+}
+
+// packet protection keys of the initial packets of one side of a connection
+pub struct InitialKeys {
+    key: [u8; 16],
+    iv: [u8; 12],
+    hp: [u8; 16],
+}
+
+impl InitialKeys {
+    // derive the client's or server's initial keys of the quic version from
+    // the destination connection id the client chose for its first initial
+    // packet; unknown versions have no known keys
+    pub fn new(version: u32, dcid: &[u8], client: bool) -> Option<InitialKeys> {
+        let (salt, labels) = match version {
+            VERSION_1 => (&INITIAL_SALT_V1, ["quic key", "quic iv", "quic hp"]),
+            VERSION_2 => (&INITIAL_SALT_V2, ["quicv2 key", "quicv2 iv", "quicv2 hp"]),
+            _ => return None,
+        };
+        let (initial_secret, _) = Hkdf::<Sha256>::extract(Some(salt), dcid);
+        let mut secret = [0; 32];
+        let side = if client { "client in" } else { "server in" };
+        expand_label(&initial_secret, side, &mut secret)?;
+
+        let mut keys = InitialKeys {
+            key: [0; 16],
+            iv: [0; 12],
+            hp: [0; 16],
+        };
+        expand_label(&secret, labels[0], &mut keys.key)?;
+        expand_label(&secret, labels[1], &mut keys.iv)?;
+        expand_label(&secret, labels[2], &mut keys.hp)?;
+        Some(keys)
+    }
+
+    // remove the header and packet protection of the packet and get its
+    // truncated packet number and its decrypted payload
+    pub fn decrypt(&self, packet: &LongHeaderPacket) -> Option<(u32, Vec<u8>)> {
+        let raw = packet.get_raw();
+        let pn_offset = packet.get_pn_offset();
+
+        // the sample for the header protection mask starts 4 bytes after
+        // the start of the packet number
+        let sample = raw.get(pn_offset + 4..pn_offset + 4 + SAMPLE_LENGTH)?;
+        let mut mask = GenericArray::clone_from_slice(sample);
+        Aes128::new(GenericArray::from_slice(&self.hp)).encrypt_block(&mut mask);
+
+        // unprotect the first byte and the packet number to get the header
+        let mut header = raw[..pn_offset].to_vec();
+        header[0] ^= mask[0] & 0x0f;
+        let pn_length = usize::from(header[0] & 0x3) + 1;
+        let mut pn = 0;
+        for (i, byte) in raw
+            .get(pn_offset..pn_offset + pn_length)?
+            .iter()
+            .enumerate()
+        {
+            let byte = byte ^ mask[1 + i];
+            header.push(byte);
+            pn = (pn << 8) | u32::from(byte);
+        }
+
+        // the nonce is the iv xored with the packet number
+        let mut nonce = self.iv;
+        for (i, byte) in pn.to_be_bytes().iter().enumerate() {
+            nonce[8 + i] ^= byte;
+        }
+        let ciphertext = &raw[pn_offset + pn_length..];
+        if ciphertext.len() < TAG_LENGTH {
+            return None;
+        }
+        let cipher = Aes128Gcm::new(GenericArray::from_slice(&self.key));
+        let payload = Payload {
+            msg: ciphertext,
+            aad: &header,
+        };
+        let plaintext = cipher.decrypt(Nonce::from_slice(&nonce), payload).ok()?;
+        Some((pn, plaintext))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // decode the hex string s with optional whitespace into bytes
+    fn hex(s: &str) -> Vec<u8> {
+        let s: String = s.split_whitespace().collect();
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // destination connection id of the client's first initial packet in
+    // the examples of rfc 9001 appendix a
+    const DCID: &str = "8394c8f03e515708";
+
+    #[test]
+    fn derive_client_initial_keys() {
+        // rfc 9001 appendix a.1
+        let keys = InitialKeys::new(VERSION_1, &hex(DCID), true).unwrap();
+        assert_eq!(keys.key.to_vec(), hex("1f369613dd76d5467730efcbe3b1a22d"));
+        assert_eq!(keys.iv.to_vec(), hex("fa044b2f42a3fd3b46fb255c"));
+        assert_eq!(keys.hp.to_vec(), hex("9f50449e04a0e810283a1e9933adedd2"));
+
+        // rfc 9001 appendix a.2: header protection mask of the sample
+        let mut mask = GenericArray::clone_from_slice(&hex("d1b1c98dd7689fb8ec11d242b123dc9b"));
+        Aes128::new(GenericArray::from_slice(&keys.hp)).encrypt_block(&mut mask);
+        assert_eq!(mask[..5].to_vec(), hex("437b9aec36"));
+    }
+
+    #[test]
+    fn derive_server_initial_keys() {
+        // rfc 9001 appendix a.1
+        let keys = InitialKeys::new(VERSION_1, &hex(DCID), false).unwrap();
+        assert_eq!(keys.key.to_vec(), hex("cf3a5331653c364c88f0f379b6067e37"));
+        assert_eq!(keys.iv.to_vec(), hex("0ac1493ca1905853b0bba03e"));
+        assert_eq!(keys.hp.to_vec(), hex("c206b8d9b9f0f37644430b490eeaa314"));
+    }
+
+    #[test]
+    fn decrypt_server_initial() {
+        // rfc 9001 appendix a.3
+        let datagram = hex(
+            "cf000000010008f067a5502a4262b5004075c0d95a482cd0991cd25b0aac406a
+            5816b6394100f37a1c69797554780bb38cc5a99f5ede4cf73c3ec2493a1839b3
+            dbcba3f6ea46c5b7684df3548e7ddeb9c3bf9c73cc3f3bded74b562bfb19fb84
+            022f8ef4cdd93795d77d06edbb7aaf2f58891850abbdca3d20398c276456cbc4
+            2158407dd074ee",
+        );
+        let payload = hex(
+            "02000000000600405a020000560303eefce7f7b37ba1d1632e96677825ddf739
+            88cfc79825df566dc5430b9a045a1200130100002e00330024001d00209d3c94
+            0d89690b84d08a60993c144eca684d1081287c834d5311bcf32bb9da1a002b00
+            020304",
+        );
+        let (packet, rest) = LongHeaderPacket::parse(&datagram).unwrap();
+        assert!(rest.is_empty());
+        let keys = InitialKeys::new(VERSION_1, &hex(DCID), false).unwrap();
+        assert_eq!(keys.decrypt(&packet), Some((1, payload)));
+    }
+
+    #[test]
+    fn reject_unknown_version() {
+        assert!(InitialKeys::new(0xff00_001d, &hex(DCID), true).is_none());
+    }
+}
@@ -0,0 +1,87 @@
+use crate::packet::read_varint;
+
+// Frame Types:
+// frame types that may appear in initial packets; parsing stops at others
+// 0x00            PADDING
+// 0x01            PING
+// 0x02-0x03       ACK
+// 0x06            CRYPTO
+const FRAME_PADDING: u64 = 0x00;
+const FRAME_PING: u64 = 0x01;
+const FRAME_ACK: u64 = 0x02;
+const FRAME_ACK_ECN: u64 = 0x03;
+const FRAME_CRYPTO: u64 = 0x06;
+
+// reader of variable-length integers and bytes in a decrypted payload
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn varint(&mut self) -> Option<u64> {
+        let (value, size) = read_varint(self.bytes.get(self.offset..)?)?;
+        self.offset += size;
+        Some(value)
+    }
+
+    fn bytes(&mut self, length: u64) -> Option<&'a [u8]> {
+        let end = self.offset.checked_add(usize::try_from(length).ok()?)?;
+        let bytes = self.bytes.get(self.offset..end)?;
+        self.offset = end;
+        Some(bytes)
+    }
+
+    // skip the rest of an ack frame after its type
+    fn skip_ack(&mut self, frame_type: u64) -> Option<()> {
+        // largest acknowledged, ack delay, ack range count, first ack range
+        self.varint()?;
+        self.varint()?;
+        let ranges = self.varint()?;
+        self.varint()?;
+
+        // gap and ack range length of each additional range
+        for _ in 0..ranges {
+            self.varint()?;
+            self.varint()?;
+        }
+
+        // ect0, ect1 and ecn-ce counts
+        if frame_type == FRAME_ACK_ECN {
+            for _ in 0..3 {
+                self.varint()?;
+            }
+        }
+        Some(())
+    }
+
+    // read the offset and data of a crypto frame after its type
+    fn crypto(&mut self) -> Option<(u64, &'a [u8])> {
+        let offset = self.varint()?;
+        let length = self.varint()?;
+        Some((offset, self.bytes(length)?))
+    }
+}
+
+// get the data of the crypto frames in the decrypted payload of an initial
+// packet with their offsets in the crypto stream; parsing stops at the
+// first invalid frame or at a frame of another type
+pub fn get_crypto_frames(payload: &[u8]) -> Vec<(u64, &[u8])> {
+    let mut frames = Vec::new();
+    let mut reader = Reader {
+        bytes: payload,
+        offset: 0,
+    };
+    while let Some(frame_type) = reader.varint() {
+        let parsed = match frame_type {
+            FRAME_PADDING | FRAME_PING => Some(()),
+            FRAME_ACK | FRAME_ACK_ECN => reader.skip_ack(frame_type),
+            FRAME_CRYPTO => reader.crypto().map(|frame| frames.push(frame)),
+            _ => None,
+        };
+        if parsed.is_none() {
+            break;
+        }
+    }
+    frames
+}
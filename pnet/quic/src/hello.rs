@@ -0,0 +1,182 @@
+use std::fmt;
+
+// tls handshake message type of client hellos
+const HANDSHAKE_CLIENT_HELLO: u8 = 1;
+
+// tls extension types
+const EXTENSION_SERVER_NAME: u16 = 0;
+const EXTENSION_ALPN: u16 = 16;
+const EXTENSION_SUPPORTED_VERSIONS: u16 = 43;
+const EXTENSION_QUIC_TRANSPORT_PARAMETERS: u16 = 57;
+
+// server name type of host names in the server name extension
+const SERVER_NAME_HOST_NAME: u8 = 0;
+
+// length of the tls handshake message header
+pub const HANDSHAKE_HEADER_LENGTH: usize = 4;
+
+// get the length of the tls handshake message in the header at the start of
+// bytes, including the header
+pub fn get_handshake_length(bytes: &[u8]) -> Option<usize> {
+    let header = bytes.get(..HANDSHAKE_HEADER_LENGTH)?;
+    let length = u32::from_be_bytes([0, header[1], header[2], header[3]]);
+    Some(HANDSHAKE_HEADER_LENGTH + length as usize)
+}
+
+// reader of the length-prefixed fields in a tls handshake message
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, length: usize) -> Option<&'a [u8]> {
+        let bytes = self.bytes.get(..length)?;
+        self.bytes = &self.bytes[length..];
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        let bytes = self.bytes(2)?;
+        Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    // read a vector with a one byte length
+    fn vec8(&mut self) -> Option<Reader<'a>> {
+        let length = usize::from(self.u8()?);
+        Some(Reader {
+            bytes: self.bytes(length)?,
+        })
+    }
+
+    // read a vector with a two byte length
+    fn vec16(&mut self) -> Option<Reader<'a>> {
+        let length = usize::from(self.u16()?);
+        Some(Reader {
+            bytes: self.bytes(length)?,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+}
+
+// get the name of the tls version
+fn get_version_name(version: u16) -> String {
+    match version {
+        0x0301 => "tls 1.0".into(),
+        0x0302 => "tls 1.1".into(),
+        0x0303 => "tls 1.2".into(),
+        0x0304 => "tls 1.3".into(),
+        v if v & 0x0f0f == 0x0a0a => "grease".into(),
+        v => format!("{:#06x}", v),
+    }
+}
+
+// tls client hello in the crypto stream of a quic connection with the
+// fields that identify the server and the application protocols
+pub struct ClientHello {
+    server_name: Option<String>,
+    alpn: Vec<String>,
+    versions: Vec<u16>,
+    cipher_suites: usize,
+    transport_parameters: bool,
+}
+
+impl ClientHello {
+    // parse the client hello handshake message in bytes
+    pub fn parse(bytes: &[u8]) -> Option<ClientHello> {
+        let mut reader = Reader { bytes };
+        if reader.u8()? != HANDSHAKE_CLIENT_HELLO {
+            return None;
+        }
+        let length = get_handshake_length(bytes)?;
+        let mut reader = Reader {
+            bytes: bytes.get(HANDSHAKE_HEADER_LENGTH..length)?,
+        };
+
+        // legacy version, random, legacy session id, cipher suites and
+        // legacy compression methods
+        reader.u16()?;
+        reader.bytes(32)?;
+        reader.vec8()?;
+        let cipher_suites = reader.vec16()?.bytes.len() / 2;
+        reader.vec8()?;
+
+        let mut hello = ClientHello {
+            server_name: None,
+            alpn: Vec::new(),
+            versions: Vec::new(),
+            cipher_suites,
+            transport_parameters: false,
+        };
+        if reader.is_empty() {
+            return Some(hello);
+        }
+        let mut extensions = reader.vec16()?;
+        while !extensions.is_empty() {
+            let typ = extensions.u16()?;
+            let mut extension = extensions.vec16()?;
+            match typ {
+                EXTENSION_SERVER_NAME => {
+                    let mut names = extension.vec16()?;
+                    while !names.is_empty() {
+                        let name_type = names.u8()?;
+                        let name = names.vec16()?;
+                        if name_type == SERVER_NAME_HOST_NAME {
+                            hello.server_name =
+                                Some(String::from_utf8_lossy(name.bytes).into_owned());
+                        }
+                    }
+                }
+                EXTENSION_ALPN => {
+                    let mut protocols = extension.vec16()?;
+                    while !protocols.is_empty() {
+                        let protocol = protocols.vec8()?;
+                        hello
+                            .alpn
+                            .push(String::from_utf8_lossy(protocol.bytes).into_owned());
+                    }
+                }
+                EXTENSION_SUPPORTED_VERSIONS => {
+                    let mut versions = extension.vec8()?;
+                    while !versions.is_empty() {
+                        hello.versions.push(versions.u16()?);
+                    }
+                }
+                EXTENSION_QUIC_TRANSPORT_PARAMETERS => hello.transport_parameters = true,
+                _ => (),
+            }
+        }
+        Some(hello)
+    }
+
+    // get the host name of the server
+    pub fn get_server_name(&self) -> Option<&str> {
+        self.server_name.as_deref()
+    }
+}
+
+impl fmt::Display for ClientHello {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let versions = self
+            .versions
+            .iter()
+            .map(|v| get_version_name(*v))
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "{{server name: {}, alpn: [{}], versions: [{}], cipher suites: {}, \
+            transport parameters: {}}}",
+            self.get_server_name().unwrap_or("-"),
+            self.alpn.join(", "),
+            versions.join(", "),
+            self.cipher_suites,
+            self.transport_parameters,
+        )
+    }
+}
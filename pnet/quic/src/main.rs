@@ -0,0 +1,140 @@
+extern crate pnet;
+
+mod config;
+mod crypto;
+mod frames;
+mod hello;
+mod packet;
+mod stream;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::dissect::{parse_ip_frame, parse_udp, Datagram};
+use packet_utils::hex::to_hex;
+use packet_utils::interface::find_interface;
+use pnet::datalink::DataLinkReceiver;
+
+use std::io;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::Duration;
+
+use config::Config;
+use crypto::InitialKeys;
+use frames::get_crypto_frames;
+use hello::ClientHello;
+use packet::{LongHeaderPacket, PacketType};
+use stream::CryptoStreams;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// parse the ethernet frame and get the quic datagram inside it if its ip
+// version is enabled in config; fragmented ipv4 packets and ipv6 packets
+// with extension headers are ignored
+fn parse_frame<'a>(frame: &'a [u8], config: &Config) -> Option<Datagram<'a>> {
+    let ip = parse_ip_frame(frame, config.ipv4, config.ipv6)?;
+    parse_udp(&ip, &[config.port])
+}
+
+// inspect the long header packets in the datagram; the initial packets of
+// clients are decrypted and their crypto frames are added to streams, so
+// client hellos are printed once they are complete
+fn handle_datagram(datagram: &Datagram, config: &Config, streams: &mut CryptoStreams) {
+    let mut rest = datagram.payload;
+    while let Some((packet, next)) = LongHeaderPacket::parse(rest) {
+        rest = next;
+        if !config.sni {
+            println!(
+                "got quic packet from {} to {}: {}",
+                datagram.source, datagram.destination, packet
+            );
+        }
+        if packet.get_packet_type() != PacketType::Initial
+            || datagram.destination.port() != config.port
+        {
+            continue;
+        }
+
+        // the keys of the client's initial packets are derived from the
+        // destination connection id of its first initial packet; later
+        // initial packets with the server's connection id cannot be
+        // decrypted without tracking the connection
+        let keys = InitialKeys::new(packet.get_version(), packet.get_dcid(), true);
+        let payload = match keys.and_then(|keys| keys.decrypt(&packet)) {
+            Some((_, payload)) => payload,
+            None => {
+                if !config.sni {
+                    println!(
+                        "could not decrypt quic initial packet from {} to {}",
+                        datagram.source, datagram.destination
+                    );
+                }
+                continue;
+            }
+        };
+        let frames = get_crypto_frames(&payload);
+        let message = match streams.add(datagram.source, packet.get_dcid(), &frames) {
+            Some(message) => message,
+            None => continue,
+        };
+        match ClientHello::parse(&message) {
+            Some(hello) => println!(
+                "got quic client hello from {} to {} with dcid {}: {}",
+                datagram.source,
+                datagram.destination,
+                to_hex(packet.get_dcid()),
+                hello
+            ),
+            None => println!(
+                "got invalid quic client hello from {} to {}",
+                datagram.source, datagram.destination
+            ),
+        }
+    }
+}
+
+// receive quic packets and inspect them until running is unset
+fn listen(rx: &mut dyn DataLinkReceiver, config: &Config, running: &AtomicBool) {
+    let mut streams = CryptoStreams::default();
+    while running.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading packets: {}", e),
+            },
+        };
+        if let Some(datagram) = parse_frame(frame, config) {
+            handle_datagram(&datagram, config, &mut streams);
+        }
+    }
+}
+fn main() {
+    let config = Config::parse();
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let mut rx = match create_channel(&interface, READ_TIMEOUT, config.promiscuous) {
+        Ok((_, rx)) => rx,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+
+    // stop on ctrl-c or sigterm
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    listen(rx.as_mut(), &config, &running);
+}
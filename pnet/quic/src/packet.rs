@@ -0,0 +1,225 @@
+use packet_utils::hex::to_hex;
+
+use std::fmt;
+
+// quic versions
+pub const VERSION_NEGOTIATION: u32 = 0;
+pub const VERSION_1: u32 = 0x0000_0001;
+pub const VERSION_2: u32 = 0x6b33_43cf;
+
+// maximum length of a connection id in quic version 1 and 2
+const MAX_CID_LENGTH: usize = 20;
+
+// read a variable-length integer from the start of bytes and get it with
+// the number of bytes it occupies; the two most significant bits of the
+// first byte encode the length
+pub fn read_varint(bytes: &[u8]) -> Option<(u64, usize)> {
+    let first = *bytes.first()?;
+    let length = 1 << (first >> 6);
+    let mut value = u64::from(first & 0x3f);
+    for byte in bytes.get(1..length)? {
+        value = (value << 8) | u64::from(*byte);
+    }
+    Some((value, length))
+}
+
+// get the name of the quic version
+fn get_version_name(version: u32) -> &'static str {
+    match version {
+        VERSION_NEGOTIATION => "version negotiation",
+        VERSION_1 => "v1",
+        VERSION_2 => "v2",
+        v if v & 0x0f0f_0f0f == 0x0a0a_0a0a => "reserved",
+        v if v >> 8 == 0xff_0000 => "draft",
+        _ => "unknown",
+    }
+}
+
+// Long Packet Type:
+// type of a long header packet; the values differ between versions:
+//                 v1  v2
+// Initial         0   1
+// 0-RTT           1   2
+// Handshake       2   3
+// Retry           3   0
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PacketType {
+    Initial,
+    ZeroRtt,
+    Handshake,
+    Retry,
+    VersionNegotiation,
+}
+
+impl PacketType {
+    // get the packet type from the type bits of the first byte
+    fn from_bits(version: u32, bits: u8) -> PacketType {
+        match (version, bits & 0x3) {
+            (VERSION_NEGOTIATION, _) => PacketType::VersionNegotiation,
+            (VERSION_2, 0) => PacketType::Retry,
+            (VERSION_2, 1) => PacketType::Initial,
+            (VERSION_2, 2) => PacketType::ZeroRtt,
+            (VERSION_2, _) => PacketType::Handshake,
+            (_, 0) => PacketType::Initial,
+            (_, 1) => PacketType::ZeroRtt,
+            (_, 2) => PacketType::Handshake,
+            (_, _) => PacketType::Retry,
+        }
+    }
+}
+
+impl fmt::Display for PacketType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PacketType::Initial => write!(f, "Initial"),
+            PacketType::ZeroRtt => write!(f, "0-RTT"),
+            PacketType::Handshake => write!(f, "Handshake"),
+            PacketType::Retry => write!(f, "Retry"),
+            PacketType::VersionNegotiation => write!(f, "Version Negotiation"),
+        }
+    }
+}
+
+// quic long header packet consists of the following fields:
+//
+// Header Form (1) = 1, Fixed Bit (1), Long Packet Type (2),
+// Type-Specific Bits (4), Version (32),
+// Destination Connection ID Length (8), Destination Connection ID (0..160),
+// Source Connection ID Length (8), Source Connection ID (0..160),
+// Type-Specific Payload (..)
+//
+// the payload of initial packets starts with a token length (i), the token,
+// the length (i) of the rest of the packet and the protected packet number;
+// the payload of 0-rtt and handshake packets starts with the length
+//
+// use methods to read fields from the packet
+pub struct LongHeaderPacket<'a> {
+    raw: &'a [u8],
+    version: u32,
+    dcid: &'a [u8],
+    scid: &'a [u8],
+    token: &'a [u8],
+    pn_offset: usize,
+}
+
+impl<'a> LongHeaderPacket<'a> {
+    // parse the first long header packet in the datagram and get it with the
+    // rest of the datagram that contains coalesced packets
+    pub fn parse(datagram: &'a [u8]) -> Option<(LongHeaderPacket<'a>, &'a [u8])> {
+        let first = *datagram.first()?;
+        if first & 0x80 == 0 {
+            return None;
+        }
+        let version = u32::from_be_bytes(datagram.get(1..5)?.try_into().ok()?);
+        let mut offset = 5;
+
+        // connection ids
+        let read_cid = |offset: &mut usize| -> Option<&'a [u8]> {
+            let length = usize::from(*datagram.get(*offset)?);
+            if length > MAX_CID_LENGTH && version != VERSION_NEGOTIATION {
+                return None;
+            }
+            let cid = datagram.get(*offset + 1..*offset + 1 + length)?;
+            *offset += 1 + length;
+            Some(cid)
+        };
+        let dcid = read_cid(&mut offset)?;
+        let scid = read_cid(&mut offset)?;
+
+        let mut packet = LongHeaderPacket {
+            raw: datagram,
+            version,
+            dcid,
+            scid,
+            token: &[],
+            pn_offset: offset,
+        };
+
+        // version negotiation and retry packets fill the rest of the datagram
+        let packet_type = packet.get_packet_type();
+        if matches!(
+            packet_type,
+            PacketType::VersionNegotiation | PacketType::Retry
+        ) || !matches!(version, VERSION_1 | VERSION_2)
+        {
+            return Some((packet, &[]));
+        }
+
+        if packet_type == PacketType::Initial {
+            let (token_length, size) = read_varint(datagram.get(offset..)?)?;
+            let token_length = usize::try_from(token_length).ok()?;
+            packet.token = datagram.get(offset + size..offset + size + token_length)?;
+            offset += size + token_length;
+        }
+        let (length, size) = read_varint(datagram.get(offset..)?)?;
+        let end = offset + size + usize::try_from(length).ok()?;
+        packet.raw = datagram.get(..end)?;
+        packet.pn_offset = offset + size;
+        Some((packet, &datagram[end..]))
+    }
+
+    // get the raw bytes of the packet
+    pub fn get_raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    // get the version
+    pub fn get_version(&self) -> u32 {
+        self.version
+    }
+
+    // get the packet type
+    pub fn get_packet_type(&self) -> PacketType {
+        PacketType::from_bits(self.version, self.raw[0] >> 4)
+    }
+
+    // get the destination connection id
+    pub fn get_dcid(&self) -> &'a [u8] {
+        self.dcid
+    }
+
+    // get the offset of the protected packet number in the packet
+    pub fn get_pn_offset(&self) -> usize {
+        self.pn_offset
+    }
+
+    // get the versions offered in a version negotiation packet
+    pub fn get_supported_versions(&self) -> Vec<u32> {
+        if self.get_packet_type() != PacketType::VersionNegotiation {
+            return Vec::new();
+        }
+        self.raw[self.pn_offset..]
+            .chunks_exact(4)
+            .map(|v| u32::from_be_bytes(v.try_into().unwrap()))
+            .collect()
+    }
+}
+
+impl<'a> fmt::Display for LongHeaderPacket<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{type: {}, version: {:#010x} ({}), dcid: {}, scid: {}",
+            self.get_packet_type(),
+            self.version,
+            get_version_name(self.version),
+            to_hex(self.dcid),
+            to_hex(self.scid),
+        )?;
+        match self.get_packet_type() {
+            PacketType::VersionNegotiation => {
+                let versions = self
+                    .get_supported_versions()
+                    .iter()
+                    .map(|v| format!("{:#010x} ({})", v, get_version_name(*v)))
+                    .collect::<Vec<_>>();
+                write!(f, ", supported versions: [{}]", versions.join(", "))?;
+            }
+            PacketType::Initial => {
+                write!(f, ", token: {} bytes", self.token.len())?;
+            }
+            _ => (),
+        }
+        write!(f, ", length: {}}}", self.raw.len())
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use crate::hello::get_handshake_length;
+
+// time after which incomplete crypto streams are removed
+const STREAM_TIMEOUT: Duration = Duration::from_secs(10);
+
+// maximum number of incomplete crypto streams before old ones are removed
+const MAX_STREAMS: usize = 4096;
+
+// maximum length of a client hello that is reassembled
+const MAX_HELLO_LENGTH: usize = 64 * 1024;
+
+// crypto frames received in the initial packets of a client
+struct CryptoStream {
+    fragments: BTreeMap<u64, Vec<u8>>,
+    last: Instant,
+}
+
+impl CryptoStream {
+    // get the contiguous data at the start of the stream
+    fn get_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        for (offset, fragment) in &self.fragments {
+            let offset = *offset as usize;
+            if offset > data.len() {
+                break;
+            }
+            let end = offset + fragment.len();
+            if end > data.len() {
+                data.extend_from_slice(&fragment[data.len() - offset..]);
+            }
+        }
+        data
+    }
+}
+
+// reassembler of the crypto streams in the initial packets of clients that
+// collects crypto frames until the first handshake message is complete;
+// clients may split their client hello over several initial packets and
+// reorder the crypto frames inside them
+#[derive(Default)]
+pub struct CryptoStreams {
+    streams: HashMap<(SocketAddr, Vec<u8>), CryptoStream>,
+}
+
+impl CryptoStreams {
+    // add the crypto frames of the client with the destination connection id
+    // to its stream and get the first handshake message if it is complete
+    pub fn add(
+        &mut self,
+        client: SocketAddr,
+        dcid: &[u8],
+        frames: &[(u64, &[u8])],
+    ) -> Option<Vec<u8>> {
+        if frames.is_empty() {
+            return None;
+        }
+        let now = Instant::now();
+        self.prune(now);
+
+        let key = (client, dcid.to_vec());
+        let stream = self
+            .streams
+            .entry(key.clone())
+            .or_insert_with(|| CryptoStream {
+                fragments: BTreeMap::new(),
+                last: now,
+            });
+        for (offset, data) in frames {
+            if offset.saturating_add(data.len() as u64) > MAX_HELLO_LENGTH as u64 {
+                continue;
+            }
+            let fragment = stream.fragments.entry(*offset).or_default();
+            if data.len() > fragment.len() {
+                *fragment = data.to_vec();
+            }
+        }
+        stream.last = now;
+
+        let data = stream.get_data();
+        let length = get_handshake_length(&data)?;
+        if data.len() < length {
+            return None;
+        }
+        self.streams.remove(&key);
+        Some(data[..length].to_vec())
+    }
+
+    // remove incomplete streams that timed out; if there are still too many,
+    // the oldest ones are removed
+    fn prune(&mut self, now: Instant) {
+        self.streams
+            .retain(|_, s| now.duration_since(s.last) < STREAM_TIMEOUT);
+        while self.streams.len() >= MAX_STREAMS {
+            let oldest = self
+                .streams
+                .iter()
+                .min_by_key(|(_, s)| s.last)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => self.streams.remove(&key),
+                None => break,
+            };
+        }
+    }
+}
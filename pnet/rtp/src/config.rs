@@ -0,0 +1,64 @@
+use clap::Parser;
+
+// command line configuration of the rtp tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Listen for rtp and rtcp packets and analyze rtp streams")]
+pub struct Config {
+    // network interface to capture packets on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Capture packets on the network interface NAME; by default, \
+        the first interface that is up, is not a loopback and has an ip \
+        address is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., to see the rtp \
+        traffic of other hosts on a mirrored port"
+    )]
+    pub promiscuous: bool,
+
+    // ip versions
+    #[arg(
+        short = '4',
+        long,
+        conflicts_with = "ipv6",
+        help = "Only listen for rtp packets over ipv4"
+    )]
+    pub ipv4: bool,
+    #[arg(short = '6', long, help = "Only listen for rtp packets over ipv6")]
+    pub ipv6: bool,
+
+    // sip port
+    #[arg(
+        long,
+        value_name = "PORT",
+        default_value_t = 5060,
+        help = "Read the sdp bodies of sip messages from or to the udp port \
+        PORT to find announced rtp streams and their codecs"
+    )]
+    pub sip_port: u16,
+
+    // only announced streams
+    #[arg(
+        short,
+        long,
+        help = "Only track rtp streams announced in sip/sdp instead of also \
+        detecting streams by their consecutive sequence numbers"
+    )]
+    pub sdp_only: bool,
+
+    // only print stream events
+    #[arg(
+        short,
+        long,
+        help = "Only print new and ended rtp streams instead of also sip and \
+        rtcp messages"
+    )]
+    pub quiet: bool,
+}
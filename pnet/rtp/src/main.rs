@@ -0,0 +1,140 @@
+extern crate pnet;
+
+mod config;
+mod rtcp;
+mod rtp;
+mod sdp;
+mod streams;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::dissect::{parse_ip_frame, parse_udp, Datagram};
+use packet_utils::interface::find_interface;
+use pnet::datalink::DataLinkReceiver;
+
+use std::io;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use config::Config;
+use rtcp::RtcpPacket;
+use rtp::RtpPacket;
+use sdp::SipMessage;
+use streams::Streams;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// parse the ethernet frame and get the udp datagram inside it if its ip
+// version is enabled in config; rtp streams use dynamic ports, so all udp
+// packets are considered; fragmented ipv4 packets and ipv6 packets with
+// extension headers are ignored
+fn parse_frame<'a>(frame: &'a [u8], config: &Config) -> Option<Datagram<'a>> {
+    let ip = parse_ip_frame(frame, config.ipv4, config.ipv6)?;
+    parse_udp(&ip, &[])
+}
+
+// handle the datagram received at now: read announced streams from sip
+// messages, print rtcp packets and add rtp packets to their streams
+fn handle_datagram(datagram: &Datagram, streams: &mut Streams, config: &Config, now: Instant) {
+    if datagram.source.port() == config.sip_port || datagram.destination.port() == config.sip_port {
+        if let Some(message) = SipMessage::parse(datagram.payload) {
+            if !config.quiet {
+                println!(
+                    "got sip message from {} to {}: {}",
+                    datagram.source, datagram.destination, message
+                );
+            }
+            streams.announce(&message, now);
+        }
+        return;
+    }
+
+    // rtcp feedback packet types overlap with rtp payload types with the
+    // marker bit, so rtcp is checked first
+    if streams.is_rtcp(datagram.destination) {
+        if let Some(packets) = RtcpPacket::parse_compound(datagram.payload) {
+            if !config.quiet {
+                for packet in packets {
+                    println!(
+                        "got rtcp packet from {} to {}: {}",
+                        datagram.source, datagram.destination, packet
+                    );
+                }
+            }
+            return;
+        }
+    }
+    if let Some(packet) = RtpPacket::parse(datagram.payload) {
+        let event = streams.add(datagram.source, datagram.destination, &packet, now);
+        if let Some(event) = event {
+            println!("{}", event);
+        }
+    }
+}
+
+// receive rtp, rtcp and sip packets and print the rtp streams until
+// running is unset
+fn listen(
+    rx: &mut dyn DataLinkReceiver,
+    streams: &mut Streams,
+    config: &Config,
+    running: &AtomicBool,
+) {
+    while running.load(atomic::Ordering::Relaxed) {
+        let now = Instant::now();
+        for event in streams.expire(now) {
+            println!("{}", event);
+        }
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading packets: {}", e),
+            },
+        };
+        if let Some(datagram) = parse_frame(frame, config) {
+            handle_datagram(&datagram, streams, config, Instant::now());
+        }
+    }
+}
+
+// print the active rtp streams
+fn print_streams(streams: &Streams) {
+    println!("--- rtp streams ---");
+    for (key, stream) in streams.get_streams() {
+        println!("{}: {}", key, stream);
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let mut rx = match create_channel(&interface, READ_TIMEOUT, config.promiscuous) {
+        Ok((_, rx)) => rx,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+
+    // stop on ctrl-c or sigterm and print the streams
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    let mut streams = Streams::new(!config.sdp_only);
+    listen(rx.as_mut(), &mut streams, &config, &running);
+    print_streams(&streams);
+}
@@ -0,0 +1,266 @@
+use std::fmt;
+
+use crate::rtp::RTP_VERSION;
+
+// rtcp packet types
+const RTCP_SR: u8 = 200;
+const RTCP_RR: u8 = 201;
+const RTCP_SDES: u8 = 202;
+const RTCP_BYE: u8 = 203;
+const RTCP_APP: u8 = 204;
+const RTCP_RTPFB: u8 = 205;
+const RTCP_PSFB: u8 = 206;
+
+// sdes item type of canonical names
+const SDES_CNAME: u8 = 1;
+
+// length of a report block
+const REPORT_BLOCK_LENGTH: usize = 24;
+
+// read a 32 bit field at offset in bytes
+fn read_be_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+// reception report about one source in sender and receiver reports
+pub struct ReportBlock {
+    pub ssrc: u32,
+    pub fraction_lost: u8,
+    pub cumulative_lost: i32,
+    pub highest_sequence: u32,
+    pub jitter: u32,
+}
+
+impl ReportBlock {
+    fn parse(bytes: &[u8]) -> Option<ReportBlock> {
+        let lost = read_be_u32(bytes, 4)?;
+        Some(ReportBlock {
+            ssrc: read_be_u32(bytes, 0)?,
+            fraction_lost: (lost >> 24) as u8,
+            // sign extend the 24 bit cumulative number of lost packets
+            cumulative_lost: ((lost << 8) as i32) >> 8,
+            highest_sequence: read_be_u32(bytes, 8)?,
+            jitter: read_be_u32(bytes, 12)?,
+        })
+    }
+}
+
+impl fmt::Display for ReportBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{ssrc: {:#010x}, fraction lost: {:.1}%, lost: {}, highest sequence: {}, \
+            jitter: {}}}",
+            self.ssrc,
+            f64::from(self.fraction_lost) * 100.0 / 256.0,
+            self.cumulative_lost,
+            self.highest_sequence,
+            self.jitter
+        )
+    }
+}
+
+// parse count report blocks in bytes
+fn parse_reports(bytes: &[u8], count: u8) -> Option<Vec<ReportBlock>> {
+    (0..usize::from(count))
+        .map(|i| ReportBlock::parse(bytes.get(i * REPORT_BLOCK_LENGTH..)?))
+        .collect()
+}
+
+// rtcp packet in a compound rtcp packet
+pub enum RtcpPacket {
+    SenderReport {
+        ssrc: u32,
+        ntp_timestamp: u64,
+        rtp_timestamp: u32,
+        packets: u32,
+        octets: u32,
+        reports: Vec<ReportBlock>,
+    },
+    ReceiverReport {
+        ssrc: u32,
+        reports: Vec<ReportBlock>,
+    },
+    SourceDescription(Vec<(u32, Option<String>)>),
+    Goodbye(Vec<u32>, Option<String>),
+    App {
+        ssrc: u32,
+        name: String,
+    },
+    Feedback {
+        packet_type: u8,
+        ssrc: u32,
+    },
+}
+
+impl RtcpPacket {
+    // parse the rtcp packet with packet type and count in the header in
+    // the body after the header
+    fn parse(packet_type: u8, count: u8, body: &[u8]) -> Option<RtcpPacket> {
+        let packet = match packet_type {
+            RTCP_SR => RtcpPacket::SenderReport {
+                ssrc: read_be_u32(body, 0)?,
+                ntp_timestamp: (u64::from(read_be_u32(body, 4)?) << 32)
+                    | u64::from(read_be_u32(body, 8)?),
+                rtp_timestamp: read_be_u32(body, 12)?,
+                packets: read_be_u32(body, 16)?,
+                octets: read_be_u32(body, 20)?,
+                reports: parse_reports(body.get(24..)?, count)?,
+            },
+            RTCP_RR => RtcpPacket::ReceiverReport {
+                ssrc: read_be_u32(body, 0)?,
+                reports: parse_reports(body.get(4..)?, count)?,
+            },
+            RTCP_SDES => {
+                // chunks of a source and its items, each chunk ends with a
+                // null item and is padded to a multiple of 4 bytes
+                let mut chunks = Vec::new();
+                let mut offset = 0;
+                for _ in 0..count {
+                    let ssrc = read_be_u32(body, offset)?;
+                    offset += 4;
+                    let mut cname = None;
+                    loop {
+                        let item_type = *body.get(offset)?;
+                        if item_type == 0 {
+                            offset = (offset + 4) & !3;
+                            break;
+                        }
+                        let length = usize::from(*body.get(offset + 1)?);
+                        let text = body.get(offset + 2..offset + 2 + length)?;
+                        if item_type == SDES_CNAME {
+                            cname = Some(String::from_utf8_lossy(text).into_owned());
+                        }
+                        offset += 2 + length;
+                    }
+                    chunks.push((ssrc, cname));
+                }
+                RtcpPacket::SourceDescription(chunks)
+            }
+            RTCP_BYE => {
+                let sources = (0..usize::from(count))
+                    .map(|i| read_be_u32(body, i * 4))
+                    .collect::<Option<Vec<_>>>()?;
+                let offset = sources.len() * 4;
+                let reason = body.get(offset).and_then(|length| {
+                    let reason = body.get(offset + 1..offset + 1 + usize::from(*length))?;
+                    Some(String::from_utf8_lossy(reason).into_owned())
+                });
+                RtcpPacket::Goodbye(sources, reason)
+            }
+            RTCP_APP => RtcpPacket::App {
+                ssrc: read_be_u32(body, 0)?,
+                name: String::from_utf8_lossy(body.get(4..8)?).into_owned(),
+            },
+            RTCP_RTPFB | RTCP_PSFB => RtcpPacket::Feedback {
+                packet_type,
+                ssrc: read_be_u32(body, 0)?,
+            },
+            _ => return None,
+        };
+        Some(packet)
+    }
+
+    // parse the compound rtcp packet in raw; all packets in it must be
+    // valid and fill it completely
+    pub fn parse_compound(raw: &[u8]) -> Option<Vec<RtcpPacket>> {
+        let mut packets = Vec::new();
+        let mut rest = raw;
+        while !rest.is_empty() {
+            let header = rest.get(..4)?;
+            if header[0] >> 6 != RTP_VERSION {
+                return None;
+            }
+            let length = (usize::from(u16::from_be_bytes([header[2], header[3]])) + 1) * 4;
+            let packet = rest.get(..length)?;
+
+            // only the last packet in a compound packet may be padded
+            let padding = match header[0] & 0x20 {
+                0 => 0,
+                _ if length == rest.len() => usize::from(*packet.last()?),
+                _ => return None,
+            };
+            let body = packet.get(4..length.checked_sub(padding)?)?;
+            packets.push(RtcpPacket::parse(header[1], header[0] & 0x1f, body)?);
+            rest = &rest[length..];
+        }
+        (!packets.is_empty()).then_some(packets)
+    }
+}
+
+impl fmt::Display for RtcpPacket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |reports: &[ReportBlock]| {
+            reports
+                .iter()
+                .map(|r| r.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            RtcpPacket::SenderReport {
+                ssrc,
+                ntp_timestamp,
+                rtp_timestamp,
+                packets,
+                octets,
+                reports,
+            } => write!(
+                f,
+                "{{type: sender report, ssrc: {:#010x}, ntp timestamp: {:#018x}, \
+                rtp timestamp: {}, packets: {}, octets: {}, reports: [{}]}}",
+                ssrc,
+                ntp_timestamp,
+                rtp_timestamp,
+                packets,
+                octets,
+                join(reports)
+            ),
+            RtcpPacket::ReceiverReport { ssrc, reports } => write!(
+                f,
+                "{{type: receiver report, ssrc: {:#010x}, reports: [{}]}}",
+                ssrc,
+                join(reports)
+            ),
+            RtcpPacket::SourceDescription(chunks) => {
+                let chunks = chunks
+                    .iter()
+                    .map(|(ssrc, cname)| match cname {
+                        Some(cname) => format!("{:#010x} cname {:?}", ssrc, cname),
+                        None => format!("{:#010x}", ssrc),
+                    })
+                    .collect::<Vec<_>>();
+                write!(
+                    f,
+                    "{{type: source description, sources: [{}]}}",
+                    chunks.join(", ")
+                )
+            }
+            RtcpPacket::Goodbye(sources, reason) => {
+                let sources = sources
+                    .iter()
+                    .map(|s| format!("{:#010x}", s))
+                    .collect::<Vec<_>>();
+                write!(f, "{{type: goodbye, sources: [{}]", sources.join(", "))?;
+                if let Some(reason) = reason {
+                    write!(f, ", reason: {:?}", reason)?;
+                }
+                write!(f, "}}")
+            }
+            RtcpPacket::App { ssrc, name } => write!(
+                f,
+                "{{type: application, ssrc: {:#010x}, name: {:?}}}",
+                ssrc, name
+            ),
+            RtcpPacket::Feedback { packet_type, ssrc } => {
+                let name = match *packet_type {
+                    RTCP_RTPFB => "transport feedback",
+                    _ => "payload feedback",
+                };
+                write!(f, "{{type: {}, ssrc: {:#010x}}}", name, ssrc)
+            }
+        }
+    }
+}
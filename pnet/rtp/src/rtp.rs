@@ -0,0 +1,124 @@
+use std::fmt;
+
+// length of the fixed rtp header
+const RTP_HEADER_LENGTH: usize = 12;
+
+// rtp version
+pub const RTP_VERSION: u8 = 2;
+
+// payload type and clock rate of a codec
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Codec {
+    pub name: String,
+    pub clock_rate: u32,
+}
+
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.name, self.clock_rate)
+    }
+}
+
+// get the codec of the static payload type, see rfc 3551
+pub fn get_static_codec(payload_type: u8) -> Option<Codec> {
+    let (name, clock_rate) = match payload_type {
+        0 => ("PCMU", 8000),
+        3 => ("GSM", 8000),
+        4 => ("G723", 8000),
+        5 => ("DVI4", 8000),
+        6 => ("DVI4", 16000),
+        7 => ("LPC", 8000),
+        8 => ("PCMA", 8000),
+        9 => ("G722", 8000),
+        10 | 11 => ("L16", 44100),
+        12 => ("QCELP", 8000),
+        13 => ("CN", 8000),
+        14 => ("MPA", 90000),
+        15 => ("G728", 8000),
+        16 => ("DVI4", 11025),
+        17 => ("DVI4", 22050),
+        18 => ("G729", 8000),
+        25 => ("CelB", 90000),
+        26 => ("JPEG", 90000),
+        28 => ("nv", 90000),
+        31 => ("H261", 90000),
+        32 => ("MPV", 90000),
+        33 => ("MP2T", 90000),
+        34 => ("H263", 90000),
+        _ => return None,
+    };
+    Some(Codec {
+        name: name.into(),
+        clock_rate,
+    })
+}
+
+// rtp packet consists of the following fields:
+//
+// V (2 bits), P (1 bit), X (1 bit), CC (4 bits), M (1 bit), PT (7 bits),
+// Sequence Number (16 bits), Timestamp (32 bits), SSRC (32 bits),
+// CSRC list (CC * 32 bits),
+// optional header extension and payload with optional padding
+//
+// use methods to read fields from the packet
+pub struct RtpPacket<'a> {
+    raw: &'a [u8],
+    payload_length: usize,
+}
+
+impl<'a> RtpPacket<'a> {
+    // parse the rtp packet in raw; packets with rtcp payload types are
+    // rejected, so rtp and rtcp sharing a port can be told apart
+    pub fn parse(raw: &'a [u8]) -> Option<RtpPacket<'a>> {
+        if raw.len() < RTP_HEADER_LENGTH || raw[0] >> 6 != RTP_VERSION {
+            return None;
+        }
+        if (72..=76).contains(&(raw[1] & 0x7f)) {
+            return None;
+        }
+
+        // csrc list and header extension
+        let mut header_length = RTP_HEADER_LENGTH + usize::from(raw[0] & 0xf) * 4;
+        if raw[0] & 0x10 != 0 {
+            let extension = raw.get(header_length..header_length + 4)?;
+            let words = usize::from(u16::from_be_bytes([extension[2], extension[3]]));
+            header_length += 4 + words * 4;
+        }
+
+        // padding with its length in the last byte
+        let padding = match raw[0] & 0x20 {
+            0 => 0,
+            _ => usize::from(*raw.last()?),
+        };
+        let payload_length = raw.len().checked_sub(header_length + padding)?;
+        Some(RtpPacket {
+            raw,
+            payload_length,
+        })
+    }
+
+    // get the payload type
+    pub fn get_payload_type(&self) -> u8 {
+        self.raw[1] & 0x7f
+    }
+
+    // get the sequence number
+    pub fn get_sequence(&self) -> u16 {
+        u16::from_be_bytes([self.raw[2], self.raw[3]])
+    }
+
+    // get the timestamp
+    pub fn get_timestamp(&self) -> u32 {
+        u32::from_be_bytes(self.raw[4..8].try_into().unwrap())
+    }
+
+    // get the synchronization source identifier
+    pub fn get_ssrc(&self) -> u32 {
+        u32::from_be_bytes(self.raw[8..12].try_into().unwrap())
+    }
+
+    // get the length of the payload without header and padding
+    pub fn get_payload_length(&self) -> usize {
+        self.payload_length
+    }
+}
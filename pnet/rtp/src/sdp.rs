@@ -0,0 +1,171 @@
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+
+use crate::rtp::{get_static_codec, Codec};
+
+// media stream announced in an sdp session description
+pub struct Media {
+    pub media: String,
+    pub address: SocketAddr,
+    pub rtcp_port: u16,
+    pub codecs: Vec<(u8, Codec)>,
+}
+
+impl fmt::Display for Media {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let codecs = self
+            .codecs
+            .iter()
+            .map(|(payload_type, codec)| format!("{} ({})", payload_type, codec))
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "{} at {} with [{}]",
+            self.media,
+            self.address,
+            codecs.join(", ")
+        )
+    }
+}
+
+// get the address in the connection line value, e.g., "IN IP4 10.0.0.1"
+fn parse_connection(value: &str) -> Option<IpAddr> {
+    let address = value.split_whitespace().nth(2)?;
+    // multicast addresses may carry a ttl and a number of addresses
+    address.split('/').next()?.parse().ok()
+}
+
+// parse the media streams in the sdp session description in text; the
+// connection address of a media stream is its own or the session's
+pub fn parse_sdp(text: &str) -> Vec<Media> {
+    let mut session_address = None;
+    let mut media: Vec<(Media, Option<IpAddr>, Vec<u8>)> = Vec::new();
+    for line in text.lines() {
+        let (key, value) = match line.trim_end().split_once('=') {
+            Some(line) => line,
+            None => continue,
+        };
+        match (key, media.last_mut()) {
+            ("c", None) => session_address = parse_connection(value),
+            ("c", Some((_, address, _))) => *address = parse_connection(value),
+            ("m", _) => {
+                // m=<media> <port>[/<count>] <proto> <fmt> ...
+                let mut fields = value.split_whitespace();
+                let name = fields.next().unwrap_or_default().to_string();
+                let port = fields
+                    .next()
+                    .and_then(|p| p.split('/').next()?.parse::<u16>().ok());
+                let formats = fields.skip(1).filter_map(|f| f.parse().ok()).collect();
+                if let Some(port) = port {
+                    let item = Media {
+                        media: name,
+                        address: SocketAddr::new([0, 0, 0, 0].into(), port),
+                        rtcp_port: port.wrapping_add(1),
+                        codecs: Vec::new(),
+                    };
+                    media.push((item, None, formats));
+                }
+            }
+            ("a", Some((item, _, _))) => {
+                if let Some(rtpmap) = value.strip_prefix("rtpmap:") {
+                    // a=rtpmap:<payload type> <encoding>/<clock rate>[/<params>]
+                    let mut fields = rtpmap.splitn(2, ' ');
+                    let payload_type = fields.next().and_then(|p| p.parse().ok());
+                    let mut encoding = fields.next().unwrap_or_default().split('/');
+                    let name = encoding.next().unwrap_or_default().to_string();
+                    let clock_rate = encoding.next().and_then(|c| c.parse().ok());
+                    if let (Some(payload_type), Some(clock_rate)) = (payload_type, clock_rate) {
+                        item.codecs.push((payload_type, Codec { name, clock_rate }));
+                    }
+                } else if let Some(rtcp) = value.strip_prefix("rtcp:") {
+                    if let Some(port) = rtcp.split_whitespace().next().and_then(|p| p.parse().ok())
+                    {
+                        item.rtcp_port = port;
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // add static payload types without rtpmap and keep the media streams
+    // with a connection address; port 0 rejects a stream
+    media
+        .into_iter()
+        .filter_map(|(mut item, address, formats)| {
+            let address = address.or(session_address)?;
+            if item.address.port() == 0 || address.is_unspecified() {
+                return None;
+            }
+            item.address.set_ip(address);
+            for payload_type in formats {
+                if item.codecs.iter().any(|(p, _)| *p == payload_type) {
+                    continue;
+                }
+                if let Some(codec) = get_static_codec(payload_type) {
+                    item.codecs.push((payload_type, codec));
+                }
+            }
+            item.codecs.sort_by_key(|(p, _)| *p);
+            Some(item)
+        })
+        .collect()
+}
+
+// sip message with an sdp body that announces media streams
+pub struct SipMessage {
+    pub start_line: String,
+    pub call_id: Option<String>,
+    pub media: Vec<Media>,
+}
+
+impl SipMessage {
+    // parse the sip message in bytes and get it if it carries an sdp body
+    pub fn parse(bytes: &[u8]) -> Option<SipMessage> {
+        let text = std::str::from_utf8(bytes).ok()?;
+        let (head, body) = text.split_once("\r\n\r\n")?;
+        let mut lines = head.split("\r\n");
+        let start_line = lines.next()?.to_string();
+        if !start_line.starts_with("SIP/2.0 ") && !start_line.ends_with(" SIP/2.0") {
+            return None;
+        }
+
+        // headers may use their compact forms
+        let mut call_id = None;
+        let mut is_sdp = false;
+        for line in lines {
+            let (name, value) = match line.split_once(':') {
+                Some((name, value)) => (name.trim().to_ascii_lowercase(), value.trim()),
+                None => continue,
+            };
+            match name.as_str() {
+                "call-id" | "i" => call_id = Some(value.to_string()),
+                "content-type" | "c" => {
+                    is_sdp = value.to_ascii_lowercase().starts_with("application/sdp")
+                }
+                _ => (),
+            }
+        }
+        if !is_sdp {
+            return None;
+        }
+        Some(SipMessage {
+            start_line,
+            call_id,
+            media: parse_sdp(body),
+        })
+    }
+}
+
+impl fmt::Display for SipMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let media = self.media.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+        write!(
+            f,
+            "{{start line: {:?}, call id: {}, media: [{}]}}",
+            self.start_line,
+            self.call_id.as_deref().unwrap_or("-"),
+            media.join(", ")
+        )
+    }
+}
@@ -0,0 +1,407 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use crate::rtp::{get_static_codec, Codec, RtpPacket};
+use crate::sdp::SipMessage;
+
+// time after which streams without packets end
+const STREAM_TIMEOUT: Duration = Duration::from_secs(10);
+
+// number of packets with consecutive sequence numbers before a stream that
+// was not announced is considered rtp, see rfc 3550
+const MIN_SEQUENTIAL: u8 = 4;
+
+// maximum number of streams and candidates before old ones are removed
+const MAX_STREAMS: usize = 4096;
+
+// sequence number differences of packets that are considered in order or
+// misordered instead of a restart of the stream, see rfc 3550
+const MAX_DROPOUT: u16 = 3000;
+const MAX_MISORDER: u16 = 100;
+
+// rtp stream of a source identified by source and destination address and
+// the ssrc
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct StreamKey {
+    pub source: SocketAddr,
+    pub destination: SocketAddr,
+    pub ssrc: u32,
+}
+
+impl fmt::Display for StreamKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rtp stream {:#010x} from {} to {}",
+            self.ssrc, self.source, self.destination
+        )
+    }
+}
+
+// media stream announced in sip/sdp
+#[derive(Clone)]
+struct Announcement {
+    call_id: Option<String>,
+    codecs: Vec<(u8, Codec)>,
+}
+
+// statistics of an rtp stream
+pub struct Stream {
+    call_id: Option<String>,
+    codecs: Vec<(u8, Codec)>,
+    payload_types: Vec<u8>,
+    start: Instant,
+    last_seen: Instant,
+
+    // sequence numbers
+    base_sequence: u16,
+    max_sequence: u16,
+    cycles: u64,
+    received: u64,
+    reordered: u64,
+    octets: u64,
+
+    // interarrival jitter in timestamp units and the arrival time and
+    // timestamp of the last packet it was computed from
+    jitter: f64,
+    last: Option<(Instant, u32, u8)>,
+}
+
+impl Stream {
+    fn new(announcement: Option<Announcement>, sequence: u16, now: Instant) -> Stream {
+        let announcement = announcement.unwrap_or(Announcement {
+            call_id: None,
+            codecs: Vec::new(),
+        });
+        Stream {
+            call_id: announcement.call_id,
+            codecs: announcement.codecs,
+            payload_types: Vec::new(),
+            start: now,
+            last_seen: now,
+            base_sequence: sequence,
+            max_sequence: sequence,
+            cycles: 0,
+            received: 0,
+            reordered: 0,
+            octets: 0,
+            jitter: 0.0,
+            last: None,
+        }
+    }
+
+    // get the codec of the payload type from sdp or the static payload
+    // types
+    fn get_codec(&self, payload_type: u8) -> Option<Codec> {
+        self.codecs
+            .iter()
+            .find(|(p, _)| *p == payload_type)
+            .map(|(_, codec)| codec.clone())
+            .or_else(|| get_static_codec(payload_type))
+    }
+
+    // get the payload type with its codec as text
+    fn format_payload_type(&self, payload_type: u8) -> String {
+        match self.get_codec(payload_type) {
+            Some(codec) => format!("{} ({})", payload_type, codec),
+            None => payload_type.to_string(),
+        }
+    }
+
+    // add the sequence number of a packet to the statistics; sequence
+    // numbers are extended with the number of wraparounds
+    fn add_sequence(&mut self, sequence: u16) {
+        let delta = sequence.wrapping_sub(self.max_sequence);
+        if delta != 0 && delta < MAX_DROPOUT {
+            if sequence < self.max_sequence {
+                self.cycles += 1 << 16;
+            }
+            self.max_sequence = sequence;
+        } else if delta != 0 && delta <= u16::MAX - MAX_MISORDER {
+            // large jump, the source restarted
+            self.base_sequence = sequence;
+            self.max_sequence = sequence;
+            self.cycles = 0;
+            self.received = 0;
+        } else {
+            self.reordered += 1;
+        }
+    }
+
+    // add the rtp packet received at now to the statistics
+    fn add(&mut self, packet: &RtpPacket, now: Instant) {
+        self.add_sequence(packet.get_sequence());
+        self.add_packet(packet, now);
+    }
+
+    // add the rtp packet received at now to the statistics except for its
+    // sequence number
+    fn add_packet(&mut self, packet: &RtpPacket, now: Instant) {
+        self.last_seen = now;
+        self.received += 1;
+        self.octets += packet.get_payload_length() as u64;
+        let payload_type = packet.get_payload_type();
+        if !self.payload_types.contains(&payload_type) {
+            self.payload_types.push(payload_type);
+        }
+
+        // interarrival jitter of consecutive packets with the same payload
+        // type: j += (|d| - j) / 16 with the difference d of the relative
+        // transit times in timestamp units
+        let timestamp = packet.get_timestamp();
+        if let (Some((arrival, last_timestamp, last_type)), Some(codec)) =
+            (self.last, self.get_codec(payload_type))
+        {
+            if last_type == payload_type {
+                let arrival =
+                    now.duration_since(arrival).as_secs_f64() * f64::from(codec.clock_rate);
+                let sent = f64::from(timestamp.wrapping_sub(last_timestamp) as i32);
+                self.jitter += ((arrival - sent).abs() - self.jitter) / 16.0;
+            }
+        }
+        self.last = Some((now, timestamp, payload_type));
+    }
+
+    // get the number of expected and lost packets
+    fn get_lost(&self) -> (u64, i64) {
+        let expected =
+            self.cycles + u64::from(self.max_sequence) - u64::from(self.base_sequence) + 1;
+        (expected, expected as i64 - self.received as i64)
+    }
+
+    // get the jitter in milliseconds, if the clock rate of the last payload
+    // type is known
+    fn get_jitter(&self) -> Option<f64> {
+        let (_, _, payload_type) = self.last?;
+        let codec = self.get_codec(payload_type)?;
+        Some(self.jitter * 1000.0 / f64::from(codec.clock_rate))
+    }
+}
+
+impl fmt::Display for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (expected, lost) = self.get_lost();
+        let payload_types = self
+            .payload_types
+            .iter()
+            .map(|p| self.format_payload_type(*p))
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "{} packets, {} lost ({:.1}%), {} reordered, jitter ",
+            self.received,
+            lost,
+            lost.max(0) as f64 * 100.0 / expected as f64,
+            self.reordered
+        )?;
+        match self.get_jitter() {
+            Some(jitter) => write!(f, "{:.3} ms", jitter)?,
+            None => write!(f, "unknown")?,
+        }
+        write!(
+            f,
+            ", {} bytes, {:.1} s, payload types [{}]",
+            self.octets,
+            self.last_seen.duration_since(self.start).as_secs_f64(),
+            payload_types.join(", ")
+        )?;
+        if let Some(call_id) = &self.call_id {
+            write!(f, ", call {}", call_id)?;
+        }
+        Ok(())
+    }
+}
+
+// change of an rtp stream
+pub enum Event {
+    New(StreamKey, String),
+    Ended(StreamKey, String),
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::New(key, description) => write!(f, "new {}: {}", key, description),
+            Event::Ended(key, summary) => write!(f, "{} ended: {}", key, summary),
+        }
+    }
+}
+
+// packets of a possible rtp stream before it is considered rtp: first
+// and last sequence number and number of consecutive packets
+struct Candidate {
+    first: u16,
+    last: u16,
+    count: u8,
+    last_seen: Instant,
+}
+
+// tracker of rtp streams that were announced in sip/sdp or detected by
+// their consecutive sequence numbers
+pub struct Streams {
+    heuristics: bool,
+    announced: HashMap<SocketAddr, Announcement>,
+    rtcp: HashMap<SocketAddr, Instant>,
+    candidates: HashMap<StreamKey, Candidate>,
+    streams: HashMap<StreamKey, Stream>,
+}
+
+impl Streams {
+    // create a new tracker; heuristics enables the detection of streams
+    // that were not announced
+    pub fn new(heuristics: bool) -> Streams {
+        Streams {
+            heuristics,
+            announced: HashMap::new(),
+            rtcp: HashMap::new(),
+            candidates: HashMap::new(),
+            streams: HashMap::new(),
+        }
+    }
+
+    // add the media streams announced in the sip message
+    pub fn announce(&mut self, message: &SipMessage, now: Instant) {
+        for media in &message.media {
+            let announcement = Announcement {
+                call_id: message.call_id.clone(),
+                codecs: media.codecs.clone(),
+            };
+            self.announced.insert(media.address, announcement);
+            let mut rtcp = media.address;
+            rtcp.set_port(media.rtcp_port);
+            self.rtcp.insert(rtcp, now);
+        }
+    }
+
+    // check if the datagram to destination may be rtcp
+    pub fn is_rtcp(&self, destination: SocketAddr) -> bool {
+        self.heuristics || self.rtcp.contains_key(&destination)
+    }
+
+    // add the rtp packet from source to destination received at now to its
+    // stream and get the event if it is a new stream
+    pub fn add(
+        &mut self,
+        source: SocketAddr,
+        destination: SocketAddr,
+        packet: &RtpPacket,
+        now: Instant,
+    ) -> Option<Event> {
+        let key = StreamKey {
+            source,
+            destination,
+            ssrc: packet.get_ssrc(),
+        };
+        if let Some(stream) = self.streams.get_mut(&key) {
+            stream.add(packet, now);
+            return None;
+        }
+
+        // streams to announced addresses are rtp, other streams need
+        // consecutive sequence numbers
+        let sequence = packet.get_sequence();
+        let announcement = self.announced.get(&destination).cloned();
+        let first = match (&announcement, self.heuristics) {
+            (Some(_), _) => sequence,
+            (None, false) => return None,
+            (None, true) => {
+                let candidate = self.candidates.entry(key).or_insert(Candidate {
+                    first: sequence,
+                    last: sequence.wrapping_sub(1),
+                    count: 0,
+                    last_seen: now,
+                });
+                if candidate.last.wrapping_add(1) == sequence {
+                    candidate.count += 1;
+                } else {
+                    candidate.first = sequence;
+                    candidate.count = 1;
+                }
+                candidate.last = sequence;
+                candidate.last_seen = now;
+                if candidate.count < MIN_SEQUENTIAL {
+                    return None;
+                }
+                let first = candidate.first;
+                self.candidates.remove(&key);
+                first
+            }
+        };
+
+        // the stream starts with the first of the consecutive packets
+        let announced = announcement.is_some();
+        let mut stream = Stream::new(announcement, first, now);
+        stream.max_sequence = sequence;
+        if sequence < first {
+            stream.cycles = 1 << 16;
+        }
+        stream.received = u64::from(sequence.wrapping_sub(first));
+        stream.add_packet(packet, now);
+        let mut description = format!(
+            "payload type {}",
+            stream.format_payload_type(packet.get_payload_type())
+        );
+        if announced {
+            description.push_str(", announced in sdp");
+        }
+        if let Some(call_id) = &stream.call_id {
+            description.push_str(&format!(" of call {}", call_id));
+        }
+        self.streams.insert(key, stream);
+        Some(Event::New(key, description))
+    }
+
+    // end streams without packets for some time and remove old candidates
+    // and announcements
+    pub fn expire(&mut self, now: Instant) -> Vec<Event> {
+        self.candidates
+            .retain(|_, c| now.duration_since(c.last_seen) < STREAM_TIMEOUT);
+        while self.candidates.len() >= MAX_STREAMS {
+            let oldest = self
+                .candidates
+                .iter()
+                .min_by_key(|(_, c)| c.last_seen)
+                .map(|(key, _)| *key);
+            match oldest {
+                Some(key) => self.candidates.remove(&key),
+                None => break,
+            };
+        }
+        if self.announced.len() >= MAX_STREAMS {
+            self.announced.clear();
+            self.rtcp.clear();
+        }
+
+        let mut ended = self
+            .streams
+            .iter()
+            .filter(|(_, s)| now.duration_since(s.last_seen) >= STREAM_TIMEOUT)
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+        if self.streams.len() - ended.len() >= MAX_STREAMS {
+            let mut active = self
+                .streams
+                .iter()
+                .filter(|(key, _)| !ended.contains(key))
+                .map(|(key, s)| (s.last_seen, *key))
+                .collect::<Vec<_>>();
+            active.sort_by_key(|(last_seen, _)| *last_seen);
+            let count = active.len() + 1 - MAX_STREAMS;
+            ended.extend(active.into_iter().take(count).map(|(_, key)| key));
+        }
+        ended
+            .into_iter()
+            .filter_map(|key| {
+                let stream = self.streams.remove(&key)?;
+                Some(Event::Ended(key, stream.to_string()))
+            })
+            .collect()
+    }
+
+    // get the streams
+    pub fn get_streams(&self) -> &HashMap<StreamKey, Stream> {
+        &self.streams
+    }
+}
@@ -0,0 +1,104 @@
+use clap::Parser;
+use packet_utils::parse::parse_rate;
+
+// command line configuration of the scan tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Scan the tcp ports of a host with syn probes")]
+pub struct Config {
+    // host to scan
+    #[arg(
+        value_name = "DESTINATION",
+        help = "Scan the host DESTINATION given as ip address or host name"
+    )]
+    pub destination: String,
+
+    // ports to scan
+    #[arg(
+        short,
+        long,
+        value_name = "PORTS",
+        default_value = "1-1024",
+        value_parser = parse_ports,
+        help = "Scan the comma-separated ports and port ranges in PORTS, \
+        e.g., 22,80,8000-8080"
+    )]
+    pub ports: Ports,
+
+    // rate of syn probes
+    #[arg(
+        short,
+        long,
+        value_name = "PACKETS",
+        default_value_t = 100.0,
+        value_parser = parse_rate,
+        help = "Send at most PACKETS syn probes per second"
+    )]
+    pub rate: f64,
+
+    // time to wait for responses
+    #[arg(
+        short,
+        long,
+        value_name = "SECONDS",
+        default_value_t = 1.0,
+        value_parser = parse_wait,
+        help = "Wait SECONDS seconds for responses after the last syn probe \
+        of each round"
+    )]
+    pub wait: f64,
+
+    // retries of unanswered probes
+    #[arg(
+        long,
+        value_name = "COUNT",
+        default_value_t = 1,
+        help = "Resend unanswered syn probes up to COUNT times before a port \
+        is reported as filtered"
+    )]
+    pub retries: u32,
+
+    // ip version of host names
+    #[arg(short = '6', long, help = "Resolve DESTINATION to an ipv6 address")]
+    pub ipv6: bool,
+
+    // print all ports
+    #[arg(
+        short,
+        long,
+        help = "Print closed and filtered ports in addition to the open ports"
+    )]
+    pub all: bool,
+}
+
+// sorted ports to scan
+#[derive(Clone, Debug)]
+pub struct Ports(pub Vec<u16>);
+
+// parse the comma-separated ports and port ranges; duplicates are removed
+fn parse_ports(s: &str) -> Result<Ports, String> {
+    let invalid = || format!("invalid ports: {}", s);
+    let mut ports = Vec::new();
+    for part in s.split(',') {
+        let (first, last) = match part.split_once('-') {
+            Some((first, last)) => (first, last),
+            None => (part, part),
+        };
+        let first = first.trim().parse::<u16>().map_err(|_| invalid())?;
+        let last = last.trim().parse::<u16>().map_err(|_| invalid())?;
+        if first == 0 || first > last {
+            return Err(invalid());
+        }
+        ports.extend(first..=last);
+    }
+    ports.sort_unstable();
+    ports.dedup();
+    Ok(Ports(ports))
+}
+
+// parse the time to wait for responses in seconds
+fn parse_wait(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(wait) if wait.is_finite() && wait >= 0.0 => Ok(wait),
+        _ => Err(format!("invalid wait time: {}", s)),
+    }
+}
@@ -0,0 +1,251 @@
+extern crate pnet;
+
+mod config;
+mod probe;
+
+use clap::Parser;
+
+use packet_utils::interface::resolve_destination;
+use packet_utils::pacer::TokenBucket;
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::tcp::TcpPacket;
+use pnet::transport::TransportChannelType::Layer4;
+use pnet::transport::TransportProtocol::{Ipv4, Ipv6};
+use pnet::transport::{tcp_packet_iter, transport_channel, TransportReceiver, TransportSender};
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use config::Config;
+use probe::PortState;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// interval in which the send loop checks if it should stop while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// size of the receive buffer of the transport channel
+const TRANSPORT_BUFFER_SIZE: usize = 65535;
+
+// first port of the dynamic port range the source port is selected from
+const DYNAMIC_PORTS: u16 = 49152;
+
+// get the local address the kernel uses to reach destination; it is needed
+// for the checksum of syn probes
+fn get_source_ip(destination: IpAddr) -> Option<IpAddr> {
+    let unspecified = match destination {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+    };
+    let socket = UdpSocket::bind(SocketAddr::new(unspecified, 0)).ok()?;
+    socket.connect((destination, 9)).ok()?;
+    Some(socket.local_addr().ok()?.ip())
+}
+
+// open the tcp transport channel for syn probes to destination; the kernel
+// builds the ip header and selects the interface
+fn open_transport_channel(destination: IpAddr) -> io::Result<(TransportSender, TransportReceiver)> {
+    let protocol = match destination {
+        IpAddr::V4(_) => Layer4(Ipv4(IpNextHeaderProtocols::Tcp)),
+        IpAddr::V6(_) => Layer4(Ipv6(IpNextHeaderProtocols::Tcp)),
+    };
+    transport_channel(TRANSPORT_BUFFER_SIZE, protocol)
+}
+
+// source port and sequence number of syn probes; responses acknowledge the
+// sequence number
+#[derive(Clone, Copy)]
+struct ProbeId {
+    port: u16,
+    sequence: u32,
+}
+
+impl ProbeId {
+    // create a probe id from the process id and the current time, so
+    // concurrent scans use different source ports and sequence numbers
+    fn new() -> ProbeId {
+        let pid = std::process::id();
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .subsec_nanos();
+        ProbeId {
+            port: DYNAMIC_PORTS + (pid % u32::from(u16::MAX - DYNAMIC_PORTS)) as u16,
+            sequence: nanos ^ pid.rotate_left(16),
+        }
+    }
+}
+
+// receive the responses to the syn probes from destination until
+// receiving is unset and add the port states to results; open ports are
+// printed when their response arrives, other ports only if all is set
+fn receive_responses(
+    mut rx: TransportReceiver,
+    destination: IpAddr,
+    id: ProbeId,
+    results: &Mutex<BTreeMap<u16, PortState>>,
+    receiving: &AtomicBool,
+    all: bool,
+) {
+    let mut iter = tcp_packet_iter(&mut rx);
+    while receiving.load(atomic::Ordering::Relaxed) {
+        let (packet, source) = match iter.next_with_timeout(READ_TIMEOUT) {
+            Ok(Some(received)) => received,
+            Ok(None) => continue,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading responses: {}", e),
+            },
+        };
+        if source != destination {
+            continue;
+        }
+        let (port, state) = match probe::parse_response(&packet, id.port, id.sequence) {
+            Some(response) => response,
+            None => continue,
+        };
+        if results.lock().unwrap().insert(port, state).is_some() {
+            continue;
+        }
+        if state == PortState::Open || all {
+            println!("port {}/tcp {}", port, state);
+        }
+    }
+}
+
+// send syn probes to the ports of config on destination at the rate of
+// config and get the states of the probed ports; unanswered probes are
+// resent in the retry rounds of config and their ports are filtered in the
+// end; the scan stops early if running is unset
+fn scan(
+    mut tx: TransportSender,
+    rx: TransportReceiver,
+    source: IpAddr,
+    destination: IpAddr,
+    config: &Config,
+    running: &AtomicBool,
+) -> BTreeMap<u16, PortState> {
+    let id = ProbeId::new();
+    let results = Mutex::new(BTreeMap::new());
+    let mut probed = BTreeSet::new();
+    let receiving = AtomicBool::new(true);
+    std::thread::scope(|s| {
+        s.spawn(|| receive_responses(rx, destination, id, &results, &receiving, config.all));
+        let mut pacer = TokenBucket::new(config.rate);
+        let wait = Duration::from_secs_f64(config.wait);
+        'rounds: for _ in 0..=config.retries {
+            let pending = {
+                let results = results.lock().unwrap();
+                let ports = config.ports.0.iter();
+                ports
+                    .filter(|port| !results.contains_key(port))
+                    .copied()
+                    .collect::<Vec<u16>>()
+            };
+            if pending.is_empty() {
+                break;
+            }
+            for port in &pending {
+                while !pacer.try_take() {
+                    if !running.load(atomic::Ordering::Relaxed) {
+                        break 'rounds;
+                    }
+                    std::thread::sleep(pacer.get_wait().min(POLL_INTERVAL));
+                }
+                let probe =
+                    probe::create_probe((source, id.port), (destination, *port), id.sequence);
+                if let Err(e) = tx.send_to(TcpPacket::new(&probe).unwrap(), destination) {
+                    panic!("An error occurred when sending syn probes: {}", e);
+                }
+                probed.insert(*port);
+            }
+
+            // wait for the responses of this round
+            let sent = Instant::now();
+            while sent.elapsed() < wait && running.load(atomic::Ordering::Relaxed) {
+                if results.lock().unwrap().len() == config.ports.0.len() {
+                    break;
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+        receiving.store(false, atomic::Ordering::Relaxed);
+    });
+
+    let mut results = results.into_inner().unwrap();
+    for port in probed {
+        results.entry(port).or_insert(PortState::Filtered);
+    }
+    results
+}
+
+// print the states of the scanned ports; closed and filtered ports are
+// only listed if all is set
+fn print_results(
+    destination: IpAddr,
+    results: &BTreeMap<u16, PortState>,
+    duration: Duration,
+    all: bool,
+) {
+    let count = |state| results.values().filter(|s| **s == state).count();
+    println!("--- scan of {} ---", destination);
+    println!("{:<10} STATE", "PORT");
+    for (port, state) in results {
+        if *state == PortState::Open || all {
+            println!("{:<10} {}", format!("{}/tcp", port), state);
+        }
+    }
+    println!(
+        "{} ports scanned in {:.1} s: {} open, {} closed, {} filtered",
+        results.len(),
+        duration.as_secs_f64(),
+        count(PortState::Open),
+        count(PortState::Closed),
+        count(PortState::Filtered)
+    );
+}
+
+fn main() {
+    let config = Config::parse();
+    let destination = match resolve_destination(&config.destination, config.ipv6) {
+        Some(destination) => destination,
+        None => panic!("An error occurred when resolving the destination"),
+    };
+    let source = match get_source_ip(destination) {
+        Some(source) => source,
+        None => panic!("An error occurred when getting the source address"),
+    };
+    let (tx, rx) = match open_transport_channel(destination) {
+        Ok(channel) => channel,
+        Err(e) => panic!(
+            "An error occurred when creating the transport channel: {}",
+            e
+        ),
+    };
+
+    // stop on ctrl-c or sigterm and print the results so far
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    println!(
+        "scanning {} ports of {} from {}",
+        config.ports.0.len(),
+        destination,
+        source
+    );
+    let start = Instant::now();
+    let results = scan(tx, rx, source, destination, &config, &running);
+    print_results(destination, &results, start.elapsed(), config.all);
+}
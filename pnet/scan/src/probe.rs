@@ -0,0 +1,85 @@
+use pnet::packet::tcp::TcpOption;
+use pnet::packet::tcp::{ipv4_checksum, ipv6_checksum, MutableTcpPacket, TcpFlags, TcpPacket};
+
+use std::fmt;
+use std::net::IpAddr;
+
+// window size advertised in syn probes
+const WINDOW_SIZE: u16 = 1024;
+
+// maximum segment size option of syn probes
+const MSS: u16 = 1460;
+
+// length of syn probes: the tcp header with the 4 byte mss option
+const PROBE_LENGTH: usize = 24;
+
+// state of a scanned port derived from the response to its syn probe
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PortState {
+    Open,
+    Closed,
+    Filtered,
+}
+
+impl fmt::Display for PortState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortState::Open => write!(f, "open"),
+            PortState::Closed => write!(f, "closed"),
+            PortState::Filtered => write!(f, "filtered"),
+        }
+    }
+}
+
+// compute the tcp checksum of packet from source to destination
+fn get_checksum(packet: &TcpPacket, source: IpAddr, destination: IpAddr) -> u16 {
+    match (source, destination) {
+        (IpAddr::V4(source), IpAddr::V4(destination)) => {
+            ipv4_checksum(packet, &source, &destination)
+        }
+        (IpAddr::V6(source), IpAddr::V6(destination)) => {
+            ipv6_checksum(packet, &source, &destination)
+        }
+        _ => 0,
+    }
+}
+
+// create the syn probe from source port to destination port with the
+// sequence number; the checksum covers the source and destination addresses
+pub fn create_probe(source: (IpAddr, u16), destination: (IpAddr, u16), sequence: u32) -> Vec<u8> {
+    let mut buffer = vec![0u8; PROBE_LENGTH];
+    let mut packet = MutableTcpPacket::new(&mut buffer).unwrap();
+    packet.set_source(source.1);
+    packet.set_destination(destination.1);
+    packet.set_sequence(sequence);
+    packet.set_data_offset((PROBE_LENGTH / 4) as u8);
+    packet.set_flags(TcpFlags::SYN);
+    packet.set_window(WINDOW_SIZE);
+    packet.set_options(&[TcpOption::mss(MSS)]);
+    let checksum = get_checksum(&packet.to_immutable(), source.0, destination.0);
+    packet.set_checksum(checksum);
+    buffer
+}
+
+// get the port and its state from the response to a syn probe from source
+// port with sequence number; only syn-acks and resets that acknowledge the
+// probe are responses
+pub fn parse_response(
+    packet: &TcpPacket,
+    source_port: u16,
+    sequence: u32,
+) -> Option<(u16, PortState)> {
+    if packet.get_destination() != source_port
+        || packet.get_flags() & TcpFlags::ACK == 0
+        || packet.get_acknowledgement() != sequence.wrapping_add(1)
+    {
+        return None;
+    }
+    let flags = packet.get_flags();
+    let state = match flags {
+        f if f & TcpFlags::RST != 0 => PortState::Closed,
+        f if f & TcpFlags::SYN != 0 => PortState::Open,
+        _ => return None,
+    };
+    Some((packet.get_source(), state))
+}
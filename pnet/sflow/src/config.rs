@@ -0,0 +1,37 @@
+use clap::Parser;
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+// command line configuration of the sflow tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Collect sflow v5 datagrams over udp and print their samples")]
+pub struct Config {
+    // address to receive datagrams on
+    #[arg(
+        short,
+        long,
+        value_name = "ADDRESS",
+        default_value = "0.0.0.0:6343",
+        help = "Receive sflow datagrams on the udp socket address ADDRESS, \
+        e.g., [::]:6343 for ipv6"
+    )]
+    pub listen: SocketAddr,
+
+    // skip counter samples
+    #[arg(
+        short,
+        long,
+        help = "Only print flow samples and omit the counter samples"
+    )]
+    pub flows: bool,
+
+    // output file
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        help = "Append samples to FILE instead of writing them to stdout"
+    )]
+    pub output: Option<PathBuf>,
+}
@@ -0,0 +1,615 @@
+use packet_utils::dissect::format_layers;
+use pnet::datalink::MacAddr;
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::raw_header;
+
+// sflow version 5
+const VERSION_5: u32 = 5;
+
+// agent address types
+const ADDRESS_IPV4: u32 = 1;
+const ADDRESS_IPV6: u32 = 2;
+
+// standard sample formats
+const SAMPLE_FLOW: u32 = 1;
+const SAMPLE_COUNTER: u32 = 2;
+const SAMPLE_EXPANDED_FLOW: u32 = 3;
+const SAMPLE_EXPANDED_COUNTER: u32 = 4;
+
+// standard flow record formats
+const FLOW_RAW_HEADER: u32 = 1;
+const FLOW_ETHERNET: u32 = 2;
+const FLOW_EXTENDED_SWITCH: u32 = 1001;
+const FLOW_EXTENDED_ROUTER: u32 = 1002;
+
+// standard counter record formats
+const COUNTER_INTERFACE: u32 = 1;
+const COUNTER_ETHERNET: u32 = 2;
+
+// reader of the big-endian xdr values in a datagram; opaque data is padded
+// to multiples of four bytes
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes, offset: 0 }
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let bytes = self.bytes.get(self.offset..self.offset + 4)?;
+        self.offset += 4;
+        Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        let high = self.u32()?;
+        let low = self.u32()?;
+        Some(u64::from(high) << 32 | u64::from(low))
+    }
+
+    fn bytes(&mut self, length: usize) -> Option<&'a [u8]> {
+        let end = self.offset.checked_add(length)?;
+        let bytes = self.bytes.get(self.offset..end)?;
+        self.offset = end + (4 - length % 4) % 4;
+        Some(bytes)
+    }
+
+    // read the opaque data preceded by its length
+    fn opaque(&mut self) -> Option<&'a [u8]> {
+        let length = self.u32()?;
+        self.bytes(usize::try_from(length).ok()?)
+    }
+
+    // read the address preceded by its type
+    fn address(&mut self) -> Option<IpAddr> {
+        match self.u32()? {
+            ADDRESS_IPV4 => {
+                let b = self.bytes(4)?;
+                Some(IpAddr::V4(Ipv4Addr::new(b[0], b[1], b[2], b[3])))
+            }
+            ADDRESS_IPV6 => {
+                let mut address = [0; 16];
+                address.copy_from_slice(self.bytes(16)?);
+                Some(IpAddr::V6(Ipv6Addr::from(address)))
+            }
+            _ => None,
+        }
+    }
+
+    fn mac(&mut self) -> Option<MacAddr> {
+        let b = self.bytes(6)?;
+        Some(MacAddr::new(b[0], b[1], b[2], b[3], b[4], b[5]))
+    }
+}
+
+// split the data format into enterprise and format
+fn split_format(data_format: u32) -> (u32, u32) {
+    (data_format >> 12, data_format & 0xfff)
+}
+
+// format the interface in the compact format of flow samples: the two most
+// significant bits select an interface index, a discard reason or a number
+// of interfaces
+fn format_interface(interface: u32) -> String {
+    let value = interface & 0x3fff_ffff;
+    match interface >> 30 {
+        0 if value == 0x3fff_ffff => "internal".to_string(),
+        0 => value.to_string(),
+        1 => format!("discarded ({})", value),
+        2 => format!("multiple ({})", value),
+        _ => format!("invalid ({:08x})", interface),
+    }
+}
+
+// record of a flow sample
+pub enum FlowRecord {
+    RawHeader {
+        protocol: u32,
+        frame_length: u32,
+        stripped: u32,
+        header: Vec<u8>,
+    },
+    Ethernet {
+        length: u32,
+        source: MacAddr,
+        destination: MacAddr,
+        ethertype: u32,
+    },
+    ExtendedSwitch {
+        source_vlan: u32,
+        source_priority: u32,
+        destination_vlan: u32,
+        destination_priority: u32,
+    },
+    ExtendedRouter {
+        next_hop: IpAddr,
+        source_mask: u32,
+        destination_mask: u32,
+    },
+    Other {
+        enterprise: u32,
+        format: u32,
+        length: usize,
+    },
+}
+
+impl FlowRecord {
+    fn parse(data_format: u32, data: &[u8]) -> Option<FlowRecord> {
+        let mut reader = Reader::new(data);
+        let record = match split_format(data_format) {
+            (0, FLOW_RAW_HEADER) => FlowRecord::RawHeader {
+                protocol: reader.u32()?,
+                frame_length: reader.u32()?,
+                stripped: reader.u32()?,
+                header: reader.opaque()?.to_vec(),
+            },
+            (0, FLOW_ETHERNET) => FlowRecord::Ethernet {
+                length: reader.u32()?,
+                source: reader.mac()?,
+                destination: reader.mac()?,
+                ethertype: reader.u32()?,
+            },
+            (0, FLOW_EXTENDED_SWITCH) => FlowRecord::ExtendedSwitch {
+                source_vlan: reader.u32()?,
+                source_priority: reader.u32()?,
+                destination_vlan: reader.u32()?,
+                destination_priority: reader.u32()?,
+            },
+            (0, FLOW_EXTENDED_ROUTER) => FlowRecord::ExtendedRouter {
+                next_hop: reader.address()?,
+                source_mask: reader.u32()?,
+                destination_mask: reader.u32()?,
+            },
+            (enterprise, format) => FlowRecord::Other {
+                enterprise,
+                format,
+                length: data.len(),
+            },
+        };
+        Some(record)
+    }
+}
+
+impl fmt::Display for FlowRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlowRecord::RawHeader {
+                protocol,
+                frame_length,
+                stripped,
+                header,
+            } => write!(
+                f,
+                "raw header {{protocol: {}, frame length: {}, stripped: {}, header: {}}}",
+                raw_header::get_header_protocol_name(*protocol),
+                frame_length,
+                stripped,
+                format_layers(&raw_header::dissect(*protocol, header))
+            ),
+            FlowRecord::Ethernet {
+                length,
+                source,
+                destination,
+                ethertype,
+            } => write!(
+                f,
+                "ethernet {{length: {}, {} -> {}, ethertype: {:04x}}}",
+                length, source, destination, ethertype
+            ),
+            FlowRecord::ExtendedSwitch {
+                source_vlan,
+                source_priority,
+                destination_vlan,
+                destination_priority,
+            } => write!(
+                f,
+                "switch {{vlan: {} -> {}, priority: {} -> {}}}",
+                source_vlan, destination_vlan, source_priority, destination_priority
+            ),
+            FlowRecord::ExtendedRouter {
+                next_hop,
+                source_mask,
+                destination_mask,
+            } => write!(
+                f,
+                "router {{next hop: {}, masks: /{} -> /{}}}",
+                next_hop, source_mask, destination_mask
+            ),
+            FlowRecord::Other {
+                enterprise,
+                format,
+                length,
+            } => write!(f, "record {}:{} ({} bytes)", enterprise, format, length),
+        }
+    }
+}
+
+// generic interface counters of a counter sample
+pub struct InterfaceCounters {
+    pub index: u32,
+    pub if_type: u32,
+    pub speed: u64,
+    pub direction: u32,
+    pub status: u32,
+    pub in_octets: u64,
+    pub in_unicast: u32,
+    pub in_multicast: u32,
+    pub in_broadcast: u32,
+    pub in_discards: u32,
+    pub in_errors: u32,
+    pub in_unknown_protocols: u32,
+    pub out_octets: u64,
+    pub out_unicast: u32,
+    pub out_multicast: u32,
+    pub out_broadcast: u32,
+    pub out_discards: u32,
+    pub out_errors: u32,
+    pub promiscuous: u32,
+}
+
+impl InterfaceCounters {
+    fn parse(reader: &mut Reader) -> Option<InterfaceCounters> {
+        Some(InterfaceCounters {
+            index: reader.u32()?,
+            if_type: reader.u32()?,
+            speed: reader.u64()?,
+            direction: reader.u32()?,
+            status: reader.u32()?,
+            in_octets: reader.u64()?,
+            in_unicast: reader.u32()?,
+            in_multicast: reader.u32()?,
+            in_broadcast: reader.u32()?,
+            in_discards: reader.u32()?,
+            in_errors: reader.u32()?,
+            in_unknown_protocols: reader.u32()?,
+            out_octets: reader.u64()?,
+            out_unicast: reader.u32()?,
+            out_multicast: reader.u32()?,
+            out_broadcast: reader.u32()?,
+            out_discards: reader.u32()?,
+            out_errors: reader.u32()?,
+            promiscuous: reader.u32()?,
+        })
+    }
+}
+
+impl fmt::Display for InterfaceCounters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the status contains the admin status in bit 0 and the operational
+        // status in bit 1
+        let status = match self.status & 0x3 {
+            0x3 => "up",
+            0x1 => "admin up, oper down",
+            _ => "down",
+        };
+        let direction = match self.direction {
+            1 => "full-duplex",
+            2 => "half-duplex",
+            3 => "in",
+            4 => "out",
+            _ => "unknown",
+        };
+        let promiscuous = match self.promiscuous {
+            1 => "yes",
+            _ => "no",
+        };
+        write!(
+            f,
+            "interface {{index: {}, type: {}, speed: {}, direction: {}, status: {}, \
+            promiscuous: {}, \
+            in: {} bytes, {} unicast, {} multicast, {} broadcast, {} discards, \
+            {} errors, {} unknown protocols, \
+            out: {} bytes, {} unicast, {} multicast, {} broadcast, {} discards, \
+            {} errors}}",
+            self.index,
+            self.if_type,
+            self.speed,
+            direction,
+            status,
+            promiscuous,
+            self.in_octets,
+            self.in_unicast,
+            self.in_multicast,
+            self.in_broadcast,
+            self.in_discards,
+            self.in_errors,
+            self.in_unknown_protocols,
+            self.out_octets,
+            self.out_unicast,
+            self.out_multicast,
+            self.out_broadcast,
+            self.out_discards,
+            self.out_errors,
+        )
+    }
+}
+
+// record of a counter sample
+pub enum CounterRecord {
+    Interface(InterfaceCounters),
+    Ethernet {
+        alignment_errors: u32,
+        fcs_errors: u32,
+        single_collisions: u32,
+        multiple_collisions: u32,
+    },
+    Other {
+        enterprise: u32,
+        format: u32,
+        length: usize,
+    },
+}
+
+impl CounterRecord {
+    fn parse(data_format: u32, data: &[u8]) -> Option<CounterRecord> {
+        let mut reader = Reader::new(data);
+        let record = match split_format(data_format) {
+            (0, COUNTER_INTERFACE) => {
+                CounterRecord::Interface(InterfaceCounters::parse(&mut reader)?)
+            }
+            (0, COUNTER_ETHERNET) => CounterRecord::Ethernet {
+                alignment_errors: reader.u32()?,
+                fcs_errors: reader.u32()?,
+                single_collisions: reader.u32()?,
+                multiple_collisions: reader.u32()?,
+            },
+            (enterprise, format) => CounterRecord::Other {
+                enterprise,
+                format,
+                length: data.len(),
+            },
+        };
+        Some(record)
+    }
+}
+
+impl fmt::Display for CounterRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CounterRecord::Interface(counters) => write!(f, "{}", counters),
+            CounterRecord::Ethernet {
+                alignment_errors,
+                fcs_errors,
+                single_collisions,
+                multiple_collisions,
+            } => write!(
+                f,
+                "ethernet {{alignment errors: {}, fcs errors: {}, \
+                single collisions: {}, multiple collisions: {}}}",
+                alignment_errors, fcs_errors, single_collisions, multiple_collisions
+            ),
+            CounterRecord::Other {
+                enterprise,
+                format,
+                length,
+            } => write!(f, "record {}:{} ({} bytes)", enterprise, format, length),
+        }
+    }
+}
+
+// read the records of a sample with parse; invalid records are skipped
+fn read_records<T>(reader: &mut Reader, parse: fn(u32, &[u8]) -> Option<T>) -> Option<Vec<T>> {
+    let count = reader.u32()?;
+    let mut records = Vec::new();
+    for _ in 0..count {
+        let data_format = reader.u32()?;
+        let data = reader.opaque()?;
+        if let Some(record) = parse(data_format, data) {
+            records.push(record);
+        }
+    }
+    Some(records)
+}
+
+// flow sample with the records of a sampled packet
+pub struct FlowSample {
+    pub sequence: u32,
+    pub source_type: u32,
+    pub source_index: u32,
+    pub sampling_rate: u32,
+    pub sample_pool: u32,
+    pub drops: u32,
+    pub input: u32,
+    pub output: u32,
+    pub records: Vec<FlowRecord>,
+}
+
+impl FlowSample {
+    // parse the flow sample; expanded samples contain the source type and
+    // the interface formats in separate fields
+    fn parse(data: &[u8], expanded: bool) -> Option<FlowSample> {
+        let mut reader = Reader::new(data);
+        let sequence = reader.u32()?;
+        let (source_type, source_index) = match expanded {
+            true => (reader.u32()?, reader.u32()?),
+            false => {
+                let source = reader.u32()?;
+                (source >> 24, source & 0xff_ffff)
+            }
+        };
+        let sampling_rate = reader.u32()?;
+        let sample_pool = reader.u32()?;
+        let drops = reader.u32()?;
+        let (input, output) = match expanded {
+            true => (
+                reader.u32()? << 30 | reader.u32()?,
+                reader.u32()? << 30 | reader.u32()?,
+            ),
+            false => (reader.u32()?, reader.u32()?),
+        };
+        Some(FlowSample {
+            sequence,
+            source_type,
+            source_index,
+            sampling_rate,
+            sample_pool,
+            drops,
+            input,
+            output,
+            records: read_records(&mut reader, FlowRecord::parse)?,
+        })
+    }
+}
+
+impl fmt::Display for FlowSample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let records = self
+            .records
+            .iter()
+            .map(|r| r.to_string())
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "{{sequence: {}, source: {}:{}, sampling rate: 1/{}, sample pool: {}, \
+            drops: {}, input: {}, output: {}, records: [{}]}}",
+            self.sequence,
+            self.source_type,
+            self.source_index,
+            self.sampling_rate,
+            self.sample_pool,
+            self.drops,
+            format_interface(self.input),
+            format_interface(self.output),
+            records.join(", ")
+        )
+    }
+}
+
+// counter sample with the records of a data source
+pub struct CounterSample {
+    pub sequence: u32,
+    pub source_type: u32,
+    pub source_index: u32,
+    pub records: Vec<CounterRecord>,
+}
+
+impl CounterSample {
+    fn parse(data: &[u8], expanded: bool) -> Option<CounterSample> {
+        let mut reader = Reader::new(data);
+        let sequence = reader.u32()?;
+        let (source_type, source_index) = match expanded {
+            true => (reader.u32()?, reader.u32()?),
+            false => {
+                let source = reader.u32()?;
+                (source >> 24, source & 0xff_ffff)
+            }
+        };
+        Some(CounterSample {
+            sequence,
+            source_type,
+            source_index,
+            records: read_records(&mut reader, CounterRecord::parse)?,
+        })
+    }
+}
+
+impl fmt::Display for CounterSample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let records = self
+            .records
+            .iter()
+            .map(|r| r.to_string())
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "{{sequence: {}, source: {}:{}, records: [{}]}}",
+            self.sequence,
+            self.source_type,
+            self.source_index,
+            records.join(", ")
+        )
+    }
+}
+
+// sample in a datagram
+pub enum Sample {
+    Flow(FlowSample),
+    Counter(CounterSample),
+    Other {
+        enterprise: u32,
+        format: u32,
+        length: usize,
+    },
+}
+
+impl Sample {
+    fn parse(data_format: u32, data: &[u8]) -> Option<Sample> {
+        let sample = match split_format(data_format) {
+            (0, SAMPLE_FLOW) => Sample::Flow(FlowSample::parse(data, false)?),
+            (0, SAMPLE_EXPANDED_FLOW) => Sample::Flow(FlowSample::parse(data, true)?),
+            (0, SAMPLE_COUNTER) => Sample::Counter(CounterSample::parse(data, false)?),
+            (0, SAMPLE_EXPANDED_COUNTER) => Sample::Counter(CounterSample::parse(data, true)?),
+            (enterprise, format) => Sample::Other {
+                enterprise,
+                format,
+                length: data.len(),
+            },
+        };
+        Some(sample)
+    }
+}
+
+// sflow v5 datagram of an agent
+pub struct Datagram {
+    pub agent: IpAddr,
+    pub sub_agent: u32,
+    pub sequence: u32,
+    pub uptime: u32,
+    pub samples: Vec<Sample>,
+}
+
+impl Datagram {
+    // parse the sflow datagram in bytes
+    pub fn parse(bytes: &[u8]) -> Result<Datagram, String> {
+        let mut reader = Reader::new(bytes);
+        let version = reader.u32().ok_or("datagram too short")?;
+        if version != VERSION_5 {
+            return Err(format!("unsupported version {}", version));
+        }
+        let agent = reader.address().ok_or("invalid agent address")?;
+        let (sub_agent, sequence, uptime, count) =
+            match (reader.u32(), reader.u32(), reader.u32(), reader.u32()) {
+                (Some(sub_agent), Some(sequence), Some(uptime), Some(count)) => {
+                    (sub_agent, sequence, uptime, count)
+                }
+                _ => return Err("datagram too short".into()),
+            };
+
+        let mut samples = Vec::new();
+        for i in 0..count {
+            let sample = reader
+                .u32()
+                .zip(reader.opaque())
+                .and_then(|(data_format, data)| Sample::parse(data_format, data))
+                .ok_or_else(|| format!("invalid sample {}", i))?;
+            samples.push(sample);
+        }
+        Ok(Datagram {
+            agent,
+            sub_agent,
+            sequence,
+            uptime,
+            samples,
+        })
+    }
+}
+
+impl fmt::Display for Datagram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{agent: {}, sub agent: {}, sequence: {}, uptime: {} ms, samples: {}}}",
+            self.agent,
+            self.sub_agent,
+            self.sequence,
+            self.uptime,
+            self.samples.len()
+        )
+    }
+}
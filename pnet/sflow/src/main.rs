@@ -0,0 +1,125 @@
+extern crate pnet;
+
+mod config;
+mod datagram;
+mod raw_header;
+
+use clap::Parser;
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::Duration;
+
+use config::Config;
+use datagram::{Datagram, Sample};
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// maximum size of sflow datagrams
+const BUFFER_SIZE: usize = 65535;
+
+// create the udp socket that receives sflow datagrams
+fn create_socket(config: &Config) -> io::Result<UdpSocket> {
+    let socket = UdpSocket::bind(config.listen)?;
+    socket.set_read_timeout(Some(READ_TIMEOUT))?;
+    Ok(socket)
+}
+
+// create the output the samples are written to
+fn create_output(config: &Config) -> io::Result<Box<dyn Write>> {
+    match &config.output {
+        Some(path) => {
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            Ok(Box::new(file))
+        }
+        None => Ok(Box::new(io::stdout())),
+    }
+}
+
+// format the datagram from source and its samples as output lines
+fn format_datagram(datagram: &Datagram, source: &SocketAddr, config: &Config) -> Vec<String> {
+    let mut lines = vec![format!("got sflow datagram from {}: {}", source, datagram)];
+    for sample in &datagram.samples {
+        let line = match sample {
+            Sample::Flow(sample) => {
+                format!("got sflow flow sample from {}: {}", datagram.agent, sample)
+            }
+            Sample::Counter(_) if config.flows => continue,
+            Sample::Counter(sample) => format!(
+                "got sflow counter sample from {}: {}",
+                datagram.agent, sample
+            ),
+            Sample::Other {
+                enterprise,
+                format,
+                length,
+            } => format!(
+                "got sflow sample {}:{} from {}: {} bytes",
+                enterprise, format, datagram.agent, length
+            ),
+        };
+        lines.push(line);
+    }
+    lines
+}
+
+// receive sflow datagrams and write their samples to output until running
+// is unset
+fn listen(socket: &UdpSocket, output: &mut dyn Write, config: &Config, running: &AtomicBool) {
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+    while running.load(atomic::Ordering::Relaxed) {
+        let (size, source) = match socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(e) => match e.kind() {
+                io::ErrorKind::WouldBlock
+                | io::ErrorKind::TimedOut
+                | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading sflow datagrams: {}", e),
+            },
+        };
+        let lines = match Datagram::parse(&buffer[..size]) {
+            Ok(datagram) => format_datagram(&datagram, &source, config),
+            Err(e) => vec![format!("got invalid sflow datagram from {}: {}", source, e)],
+        };
+        for line in lines {
+            if let Err(e) = writeln!(output, "{}", line) {
+                panic!("An error occurred when writing sflow samples: {}", e);
+            }
+        }
+        if let Err(e) = output.flush() {
+            panic!("An error occurred when writing sflow samples: {}", e);
+        }
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let socket = match create_socket(&config) {
+        Ok(socket) => socket,
+        Err(e) => panic!(
+            "An error occurred when creating the collector socket: {}",
+            e
+        ),
+    };
+    let mut output = match create_output(&config) {
+        Ok(output) => output,
+        Err(e) => panic!("An error occurred when opening the output file: {}", e),
+    };
+
+    // stop on ctrl-c or sigterm
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    listen(&socket, output.as_mut(), &config, &running);
+}
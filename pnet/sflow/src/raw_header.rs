@@ -0,0 +1,72 @@
+use packet_utils::dissect::{Dissector, Layer, UdpDecoder, UdpPayload};
+use pnet::packet::ethernet::EtherTypes;
+
+use dhcp::DhcpPacket;
+use dns::DnsPacket;
+
+// header protocols of sampled packet headers
+const HEADER_ETHERNET: u32 = 1;
+const HEADER_IPV4: u32 = 11;
+const HEADER_IPV6: u32 = 12;
+
+// udp ports of dns and dhcp
+const DNS_PORT: u16 = 53;
+const DHCP_SERVER_PORT: u16 = 67;
+const DHCP_CLIENT_PORT: u16 = 68;
+
+// get the name of the header protocol of a sampled packet header
+pub fn get_header_protocol_name(protocol: u32) -> String {
+    let name = match protocol {
+        HEADER_ETHERNET => "ethernet",
+        2 => "token bus",
+        3 => "token ring",
+        4 => "fddi",
+        5 => "frame relay",
+        6 => "x25",
+        7 => "ppp",
+        8 => "smds",
+        9 => "aal5",
+        10 => "aal5 ip",
+        HEADER_IPV4 => "ipv4",
+        HEADER_IPV6 => "ipv6",
+        13 => "mpls",
+        14 => "pos",
+        _ => return format!("unknown ({})", protocol),
+    };
+    name.to_string()
+}
+
+// decode the udp payload in bytes from source to destination port as dns or
+// dhcp message; sampled headers are usually truncated, so the message is
+// only decoded if the captured bytes contain all of it
+fn decode_udp(source: u16, destination: u16, bytes: &[u8]) -> Option<UdpPayload<'_, String>> {
+    let is_port = |port| source == port || destination == port;
+    if is_port(DNS_PORT) {
+        return match DnsPacket::parse(bytes) {
+            Ok(packet) => Some(UdpPayload::Application(format!("dns {}", packet))),
+            Err(_) => Some(UdpPayload::Undecoded("dns".to_string())),
+        };
+    }
+    if is_port(DHCP_SERVER_PORT) || is_port(DHCP_CLIENT_PORT) {
+        return match DhcpPacket::parse(bytes) {
+            Ok(packet) => Some(UdpPayload::Application(format!("dhcp {}", packet))),
+            Err(_) => Some(UdpPayload::Undecoded("dhcp".to_string())),
+        };
+    }
+    None
+}
+
+// decode the sampled packet header with header protocol into its layers
+pub fn dissect(protocol: u32, header: &[u8]) -> Vec<Layer<String>> {
+    let decode_udp: &UdpDecoder<String> = &decode_udp;
+    let dissector = Dissector::new(decode_udp);
+    match protocol {
+        HEADER_ETHERNET => dissector.dissect(header),
+        HEADER_IPV4 => dissector.dissect_packet(EtherTypes::Ipv4, header),
+        HEADER_IPV6 => dissector.dissect_packet(EtherTypes::Ipv6, header),
+        protocol => vec![Layer::Payload {
+            protocol: get_header_protocol_name(protocol),
+            length: header.len(),
+        }],
+    }
+}
@@ -0,0 +1,537 @@
+use std::fmt;
+use std::net::Ipv4Addr;
+
+use packet_utils::ber::*;
+
+// application tag numbers of snmp types
+const TAG_IP_ADDRESS: u32 = 0;
+const TAG_COUNTER32: u32 = 1;
+const TAG_GAUGE32: u32 = 2;
+const TAG_TIME_TICKS: u32 = 3;
+const TAG_OPAQUE: u32 = 4;
+const TAG_COUNTER64: u32 = 6;
+
+// context-specific tag numbers of varbind exceptions
+const TAG_NO_SUCH_OBJECT: u32 = 0;
+const TAG_NO_SUCH_INSTANCE: u32 = 1;
+const TAG_END_OF_MIB_VIEW: u32 = 2;
+
+// Version:
+// 0               snmpv1
+// 1               snmpv2c
+// 3               snmpv3
+fn get_version_name(version: i64) -> String {
+    match version {
+        0 => "v1".into(),
+        1 => "v2c".into(),
+        3 => "v3".into(),
+        v => format!("{} (unknown)", v),
+    }
+}
+
+// PDU Type:
+// context-specific tag number of the pdu
+// 0               get-request
+// 1               get-next-request
+// 2               response
+// 3               set-request
+// 4               trap (snmpv1)
+// 5               get-bulk-request
+// 6               inform-request
+// 7               snmpv2-trap
+// 8               report
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PduType {
+    GetRequest,
+    GetNextRequest,
+    Response,
+    SetRequest,
+    Trap,
+    GetBulkRequest,
+    InformRequest,
+    Snmpv2Trap,
+    Report,
+}
+
+impl PduType {
+    fn from_tag(tag: Tag) -> Option<PduType> {
+        if tag.class != Class::Context || !tag.constructed {
+            return None;
+        }
+        match tag.number {
+            0 => Some(PduType::GetRequest),
+            1 => Some(PduType::GetNextRequest),
+            2 => Some(PduType::Response),
+            3 => Some(PduType::SetRequest),
+            4 => Some(PduType::Trap),
+            5 => Some(PduType::GetBulkRequest),
+            6 => Some(PduType::InformRequest),
+            7 => Some(PduType::Snmpv2Trap),
+            8 => Some(PduType::Report),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PduType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PduType::GetRequest => write!(f, "get-request"),
+            PduType::GetNextRequest => write!(f, "get-next-request"),
+            PduType::Response => write!(f, "response"),
+            PduType::SetRequest => write!(f, "set-request"),
+            PduType::Trap => write!(f, "trap"),
+            PduType::GetBulkRequest => write!(f, "get-bulk-request"),
+            PduType::InformRequest => write!(f, "inform-request"),
+            PduType::Snmpv2Trap => write!(f, "snmpv2-trap"),
+            PduType::Report => write!(f, "report"),
+        }
+    }
+}
+
+// get the name of the error status
+fn get_error_name(status: i64) -> &'static str {
+    match status {
+        0 => "noError",
+        1 => "tooBig",
+        2 => "noSuchName",
+        3 => "badValue",
+        4 => "readOnly",
+        5 => "genErr",
+        6 => "noAccess",
+        7 => "wrongType",
+        8 => "wrongLength",
+        9 => "wrongEncoding",
+        10 => "wrongValue",
+        11 => "noCreation",
+        12 => "inconsistentValue",
+        13 => "resourceUnavailable",
+        14 => "commitFailed",
+        15 => "undoFailed",
+        16 => "authorizationError",
+        17 => "notWritable",
+        18 => "inconsistentName",
+        _ => "unknown",
+    }
+}
+
+// get the name of the generic trap of an snmpv1 trap
+fn get_generic_trap_name(trap: i64) -> &'static str {
+    match trap {
+        0 => "coldStart",
+        1 => "warmStart",
+        2 => "linkDown",
+        3 => "linkUp",
+        4 => "authenticationFailure",
+        5 => "egpNeighborLoss",
+        6 => "enterpriseSpecific",
+        _ => "unknown",
+    }
+}
+
+// get the value of a varbind as text
+fn format_value(value: &Tlv) -> String {
+    let tag = value.get_tag();
+    let text = match (tag.class, tag.number) {
+        (Class::Universal, TAG_INTEGER) => value.get_integer().map(|v| v.to_string()),
+        (Class::Universal, TAG_OCTET_STRING) => Some(format_octets(value.get_value())),
+        (Class::Universal, TAG_NULL) => Some("null".into()),
+        (Class::Universal, TAG_OID) => value.get_oid(),
+        (Class::Application, TAG_IP_ADDRESS) => <[u8; 4]>::try_from(value.get_value())
+            .ok()
+            .map(|ip| Ipv4Addr::from(ip).to_string()),
+        (Class::Application, TAG_COUNTER32) => {
+            value.get_unsigned().map(|v| format!("counter32 {}", v))
+        }
+        (Class::Application, TAG_GAUGE32) => value.get_unsigned().map(|v| format!("gauge32 {}", v)),
+        (Class::Application, TAG_TIME_TICKS) => {
+            value.get_unsigned().map(|v| format!("timeticks {}", v))
+        }
+        (Class::Application, TAG_OPAQUE) => {
+            Some(format!("opaque {}", format_octets(value.get_value())))
+        }
+        (Class::Application, TAG_COUNTER64) => {
+            value.get_unsigned().map(|v| format!("counter64 {}", v))
+        }
+        (Class::Context, TAG_NO_SUCH_OBJECT) => Some("noSuchObject".into()),
+        (Class::Context, TAG_NO_SUCH_INSTANCE) => Some("noSuchInstance".into()),
+        (Class::Context, TAG_END_OF_MIB_VIEW) => Some("endOfMibView".into()),
+        _ => None,
+    };
+    text.unwrap_or_else(|| format!("{} {}", tag, format_octets(value.get_value())))
+}
+
+// variable binding of an object identifier and its value
+pub struct VarBind {
+    pub oid: String,
+    pub value: String,
+}
+
+impl fmt::Display for VarBind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.oid, self.value)
+    }
+}
+
+// parse the varbind list in tlv
+fn parse_varbinds(tlv: &Tlv) -> Option<Vec<VarBind>> {
+    let mut varbinds = Vec::new();
+    for varbind in tlv.get_children()? {
+        match varbind.get_children()?.as_slice() {
+            [oid, value] if oid.get_tag().is_universal(TAG_OID) => varbinds.push(VarBind {
+                oid: oid.get_oid()?,
+                value: format_value(value),
+            }),
+            _ => return None,
+        }
+    }
+    Some(varbinds)
+}
+
+// fields of a pdu that depend on its type
+pub enum PduFields {
+    // request id, error status and error index of most pdus
+    Standard {
+        request_id: i64,
+        error_status: i64,
+        error_index: i64,
+    },
+
+    // request id, non-repeaters and max-repetitions of get-bulk requests
+    Bulk {
+        request_id: i64,
+        non_repeaters: i64,
+        max_repetitions: i64,
+    },
+
+    // enterprise, agent address, generic trap, specific trap and time
+    // stamp of snmpv1 traps
+    Trap {
+        enterprise: String,
+        agent: String,
+        generic_trap: i64,
+        specific_trap: i64,
+        timestamp: u64,
+    },
+}
+
+// snmp protocol data unit
+pub struct Pdu {
+    pdu_type: PduType,
+    fields: PduFields,
+    varbinds: Vec<VarBind>,
+}
+
+impl Pdu {
+    // parse the pdu in tlv
+    fn parse(tlv: &Tlv) -> Option<Pdu> {
+        let pdu_type = PduType::from_tag(tlv.get_tag())?;
+        let children = tlv.get_children()?;
+        let (fields, varbinds) = match (pdu_type, children.as_slice()) {
+            (PduType::Trap, [enterprise, agent, generic, specific, timestamp, varbinds]) => {
+                let fields = PduFields::Trap {
+                    enterprise: enterprise.get_oid()?,
+                    agent: format_value(agent),
+                    generic_trap: generic.get_integer()?,
+                    specific_trap: specific.get_integer()?,
+                    timestamp: timestamp.get_unsigned()?,
+                };
+                (fields, varbinds)
+            }
+            (PduType::GetBulkRequest, [request_id, non_repeaters, max_repetitions, varbinds]) => {
+                let fields = PduFields::Bulk {
+                    request_id: request_id.get_integer()?,
+                    non_repeaters: non_repeaters.get_integer()?,
+                    max_repetitions: max_repetitions.get_integer()?,
+                };
+                (fields, varbinds)
+            }
+            (PduType::Trap | PduType::GetBulkRequest, _) => return None,
+            (_, [request_id, error_status, error_index, varbinds]) => {
+                let fields = PduFields::Standard {
+                    request_id: request_id.get_integer()?,
+                    error_status: error_status.get_integer()?,
+                    error_index: error_index.get_integer()?,
+                };
+                (fields, varbinds)
+            }
+            _ => return None,
+        };
+        Some(Pdu {
+            pdu_type,
+            fields,
+            varbinds: parse_varbinds(varbinds)?,
+        })
+    }
+}
+
+impl fmt::Display for Pdu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pdu: {}", self.pdu_type)?;
+        match &self.fields {
+            PduFields::Standard {
+                request_id,
+                error_status,
+                error_index,
+            } => write!(
+                f,
+                ", request id: {}, error status: {} ({}), error index: {}",
+                request_id,
+                error_status,
+                get_error_name(*error_status),
+                error_index
+            )?,
+            PduFields::Bulk {
+                request_id,
+                non_repeaters,
+                max_repetitions,
+            } => write!(
+                f,
+                ", request id: {}, non-repeaters: {}, max-repetitions: {}",
+                request_id, non_repeaters, max_repetitions
+            )?,
+            PduFields::Trap {
+                enterprise,
+                agent,
+                generic_trap,
+                specific_trap,
+                timestamp,
+            } => write!(
+                f,
+                ", enterprise: {}, agent: {}, generic trap: {} ({}), specific trap: {}, \
+                timestamp: {}",
+                enterprise,
+                agent,
+                generic_trap,
+                get_generic_trap_name(*generic_trap),
+                specific_trap,
+                timestamp
+            )?,
+        }
+        let varbinds = self
+            .varbinds
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>();
+        write!(f, ", varbinds: [{}]", varbinds.join(", "))
+    }
+}
+
+// security parameters of the user-based security model of snmpv3
+pub struct UsmParameters {
+    engine_id: Vec<u8>,
+    engine_boots: i64,
+    engine_time: i64,
+    user_name: Vec<u8>,
+}
+
+impl UsmParameters {
+    // parse the usm parameters in the octets of the security parameters
+    fn parse(octets: &[u8]) -> Option<UsmParameters> {
+        let tlv = Tlv::parse_all(octets)?;
+        match tlv.get_children()?.as_slice() {
+            [engine_id, boots, time, user_name, _auth, _privacy] => Some(UsmParameters {
+                engine_id: engine_id.get_value().to_vec(),
+                engine_boots: boots.get_integer()?,
+                engine_time: time.get_integer()?,
+                user_name: user_name.get_value().to_vec(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+// scoped pdu of snmpv3 that is either in plaintext or encrypted
+pub enum ScopedPdu {
+    Plaintext {
+        context_engine_id: Vec<u8>,
+        context_name: Vec<u8>,
+        pdu: Pdu,
+    },
+    Encrypted(usize),
+}
+
+// fields of the message that depend on its version
+pub enum Security {
+    // community of snmpv1 and snmpv2c
+    Community(Vec<u8>, Pdu),
+
+    // message id, flags and security model of snmpv3 with the usm
+    // parameters, if the user-based security model is used
+    Usm {
+        message_id: i64,
+        flags: u8,
+        security_model: i64,
+        usm: Option<UsmParameters>,
+        scoped_pdu: ScopedPdu,
+    },
+}
+
+// snmp message consists of the following fields in snmpv1 and snmpv2c:
+//
+// SEQUENCE { version INTEGER, community OCTET STRING, data PDU }
+//
+// and of the following fields in snmpv3:
+//
+// SEQUENCE { version INTEGER,
+//            header SEQUENCE { msgID INTEGER, msgMaxSize INTEGER,
+//                              msgFlags OCTET STRING,
+//                              msgSecurityModel INTEGER },
+//            msgSecurityParameters OCTET STRING,
+//            data ScopedPduData }
+pub struct SnmpMessage {
+    version: i64,
+    security: Security,
+}
+
+impl SnmpMessage {
+    // parse the snmp message in bytes
+    pub fn parse(bytes: &[u8]) -> Option<SnmpMessage> {
+        let message = Tlv::parse_all(bytes)?;
+        if !message.get_tag().is_universal(TAG_SEQUENCE) {
+            return None;
+        }
+        let children = message.get_children()?;
+        let version = children.first()?.get_integer()?;
+        let security = match (version, children.as_slice()) {
+            (0 | 1, [_, community, pdu]) => {
+                if !community.get_tag().is_universal(TAG_OCTET_STRING) {
+                    return None;
+                }
+                Security::Community(community.get_value().to_vec(), Pdu::parse(pdu)?)
+            }
+            (3, [_, header, parameters, data]) => {
+                let (message_id, flags, security_model) = match header.get_children()?.as_slice() {
+                    [id, _max_size, flags, model] => (
+                        id.get_integer()?,
+                        *flags.get_value().first()?,
+                        model.get_integer()?,
+                    ),
+                    _ => return None,
+                };
+                Security::Usm {
+                    message_id,
+                    flags,
+                    security_model,
+                    usm: match security_model {
+                        3 => UsmParameters::parse(parameters.get_value()),
+                        _ => None,
+                    },
+                    scoped_pdu: Self::parse_scoped_pdu(data)?,
+                }
+            }
+            _ => return None,
+        };
+        Some(SnmpMessage { version, security })
+    }
+
+    // parse the plaintext or encrypted scoped pdu in data
+    fn parse_scoped_pdu(data: &Tlv) -> Option<ScopedPdu> {
+        if data.get_tag().is_universal(TAG_OCTET_STRING) {
+            return Some(ScopedPdu::Encrypted(data.get_value().len()));
+        }
+        match data.get_children()?.as_slice() {
+            [engine_id, name, pdu] => Some(ScopedPdu::Plaintext {
+                context_engine_id: engine_id.get_value().to_vec(),
+                context_name: name.get_value().to_vec(),
+                pdu: Pdu::parse(pdu)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SnmpMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{version: {}", get_version_name(self.version))?;
+        match &self.security {
+            Security::Community(community, pdu) => {
+                write!(f, ", community: {}, {}", format_octets(community), pdu)?;
+            }
+            Security::Usm {
+                message_id,
+                flags,
+                security_model,
+                usm,
+                scoped_pdu,
+            } => {
+                write!(
+                    f,
+                    ", message id: {}, flags: {:#04x} (auth: {}, priv: {}, reportable: {}), \
+                    security model: {}",
+                    message_id,
+                    flags,
+                    flags & 0x1 != 0,
+                    flags & 0x2 != 0,
+                    flags & 0x4 != 0,
+                    security_model
+                )?;
+                if let Some(usm) = usm {
+                    write!(
+                        f,
+                        ", engine id: {}, engine boots: {}, engine time: {}, user: {}",
+                        format_octets(&usm.engine_id),
+                        usm.engine_boots,
+                        usm.engine_time,
+                        format_octets(&usm.user_name)
+                    )?;
+                }
+                match scoped_pdu {
+                    ScopedPdu::Plaintext {
+                        context_engine_id,
+                        context_name,
+                        pdu,
+                    } => write!(
+                        f,
+                        ", context engine id: {}, context name: {}, {}",
+                        format_octets(context_engine_id),
+                        format_octets(context_name),
+                        pdu
+                    )?,
+                    ScopedPdu::Encrypted(length) => {
+                        write!(f, ", pdu: encrypted ({} bytes)", length)?
+                    }
+                }
+            }
+        }
+        write!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // snmpv2c get-request for sysDescr.0 with community public
+    const GET_REQUEST: [u8; 40] = [
+        0x30, 0x26, 0x02, 0x01, 0x01, 0x04, 0x06, 0x70, 0x75, 0x62, 0x6c, 0x69, 0x63, 0xa0, 0x19,
+        0x02, 0x01, 0x01, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x30, 0x0e, 0x30, 0x0c, 0x06, 0x08,
+        0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x01, 0x00, 0x05, 0x00,
+    ];
+
+    #[test]
+    fn parse_get_request() {
+        let message = SnmpMessage::parse(&GET_REQUEST).unwrap();
+        assert_eq!(
+            message.to_string(),
+            "{version: v2c, community: \"public\", pdu: get-request, request id: 1, \
+            error status: 0 (noError), error index: 0, varbinds: [1.3.6.1.2.1.1.1.0 = null]}"
+        );
+    }
+
+    #[test]
+    fn parse_invalid_messages() {
+        // truncated message
+        assert!(SnmpMessage::parse(&GET_REQUEST[..GET_REQUEST.len() - 1]).is_none());
+
+        // unknown version
+        let mut message = GET_REQUEST;
+        message[4] = 2;
+        assert!(SnmpMessage::parse(&message).is_none());
+
+        // varbind without an object identifier
+        let mut message = GET_REQUEST;
+        message[28] = 0x04;
+        assert!(SnmpMessage::parse(&message).is_none());
+    }
+}
@@ -0,0 +1,48 @@
+use clap::Parser;
+
+// command line configuration of the ssdp tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Listen for ssdp messages and discover upnp devices")]
+pub struct Config {
+    // network interface to join the ssdp group on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Join the ssdp multicast group on the network interface NAME; \
+        by default, the kernel selects the interface"
+    )]
+    pub interface: Option<String>,
+
+    // send a search request
+    #[arg(
+        short,
+        long,
+        value_name = "TARGET",
+        num_args = 0..=1,
+        default_missing_value = "ssdp:all",
+        help = "Send an M-SEARCH request for the search target TARGET, e.g., \
+        upnp:rootdevice, when starting; without TARGET, all devices and \
+        services are searched"
+    )]
+    pub search: Option<String>,
+
+    // maximum response delay of search requests
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 2,
+        value_parser = clap::value_parser!(u8).range(1..=5),
+        help = "Ask devices to answer search requests within SECONDS"
+    )]
+    pub mx: u8,
+
+    // only print devices
+    #[arg(
+        short,
+        long,
+        help = "Only print discovered and departed devices instead of every \
+        ssdp message"
+    )]
+    pub quiet: bool,
+}
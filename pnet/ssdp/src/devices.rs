@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use crate::message::{Method, SsdpMessage};
+
+// time devices are kept if their messages do not contain a max-age
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(1800);
+
+// upnp device or service announced in notifications or search responses
+#[derive(Clone)]
+pub struct Device {
+    pub device_type: String,
+    pub location: String,
+    pub server: String,
+    pub address: IpAddr,
+    pub expires: Instant,
+}
+
+impl fmt::Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {} from {} ({})",
+            self.device_type, self.location, self.address, self.server
+        )
+    }
+}
+
+// change of the discovered devices, with the unique service name of the
+// device
+pub enum Event {
+    Discovered(String, Device),
+    Moved(String, Device),
+    Left(String, Device),
+    Expired(String, Device),
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Discovered(usn, device) => write!(f, "discovered {}: {}", usn, device),
+            Event::Moved(usn, device) => write!(f, "moved {}: {}", usn, device),
+            Event::Left(usn, device) => write!(f, "left {}: {}", usn, device),
+            Event::Expired(usn, device) => write!(f, "expired {}: {}", usn, device),
+        }
+    }
+}
+
+// table of the devices announced in ssdp messages, keyed by their unique
+// service names
+#[derive(Default)]
+pub struct Devices {
+    devices: BTreeMap<String, Device>,
+}
+
+impl Devices {
+    // remove the devices whose announcements expired and get them
+    pub fn expire(&mut self) -> Vec<Event> {
+        let now = Instant::now();
+        let expired: Vec<String> = self
+            .devices
+            .iter()
+            .filter(|(_, device)| device.expires <= now)
+            .map(|(usn, _)| usn.clone())
+            .collect();
+        expired
+            .into_iter()
+            .filter_map(|usn| {
+                let device = self.devices.remove(&usn)?;
+                Some(Event::Expired(usn, device))
+            })
+            .collect()
+    }
+
+    // add the device announced in the ssdp message from address or remove
+    // it if it left and get the changes
+    pub fn add(&mut self, message: &SsdpMessage, address: IpAddr) -> Vec<Event> {
+        let now = Instant::now();
+        let mut events = Vec::new();
+        if matches!(message.method, Method::Search | Method::Other(_)) {
+            return events;
+        }
+        let usn = match message.get_header("usn") {
+            Some(usn) => usn.to_string(),
+            None => return events,
+        };
+        if message.get_header("nts") == Some("ssdp:byebye") {
+            if let Some(device) = self.devices.remove(&usn) {
+                events.push(Event::Left(usn, device));
+            }
+            return events;
+        }
+
+        let max_age = message
+            .get_max_age()
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_MAX_AGE);
+        let device = Device {
+            device_type: message.get_type().unwrap_or("unknown").to_string(),
+            location: message.get_header("location").unwrap_or("-").to_string(),
+            server: message.get_header("server").unwrap_or("-").to_string(),
+            address,
+            expires: now + max_age,
+        };
+        match self.devices.insert(usn.clone(), device.clone()) {
+            None => events.push(Event::Discovered(usn, device)),
+            Some(old) if old.location != device.location => events.push(Event::Moved(usn, device)),
+            Some(_) => (),
+        }
+        events
+    }
+
+    // get the devices sorted by their unique service names
+    pub fn get_devices(&self) -> &BTreeMap<String, Device> {
+        &self.devices
+    }
+}
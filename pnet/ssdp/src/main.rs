@@ -0,0 +1,138 @@
+mod config;
+mod devices;
+mod message;
+
+use clap::Parser;
+
+use packet_utils::interface::{get_interface, get_interface_ip};
+use socket2::{Domain, Protocol, Socket, Type};
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::Duration;
+
+use config::Config;
+use devices::Devices;
+use message::SsdpMessage;
+
+// multicast group and port of ssdp
+const SSDP_GROUP: Ipv4Addr = Ipv4Addr::new(239, 255, 255, 250);
+const SSDP_PORT: u16 = 1900;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// maximum size of ssdp messages
+const BUFFER_SIZE: usize = 65535;
+
+// create the udp socket on the ssdp port that joined the ssdp group on the
+// interface with ip address interface; the port is shared with other ssdp
+// listeners on this host
+fn create_socket(interface: Ipv4Addr) -> io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.set_reuse_port(true)?;
+    let address = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), SSDP_PORT);
+    socket.bind(&address.into())?;
+    socket.join_multicast_v4(&SSDP_GROUP, &interface)?;
+    if !interface.is_unspecified() {
+        socket.set_multicast_if_v4(&interface)?;
+    }
+    socket.set_read_timeout(Some(READ_TIMEOUT))?;
+    Ok(socket.into())
+}
+
+// send an m-search request for target with the maximum response delay mx to
+// the ssdp group
+fn send_search(socket: &UdpSocket, target: &str, mx: u8) -> io::Result<()> {
+    let request = format!(
+        "M-SEARCH * HTTP/1.1\r\n\
+        HOST: {}:{}\r\n\
+        MAN: \"ssdp:discover\"\r\n\
+        MX: {}\r\n\
+        ST: {}\r\n\r\n",
+        SSDP_GROUP, SSDP_PORT, mx, target
+    );
+    socket
+        .send_to(request.as_bytes(), (SSDP_GROUP, SSDP_PORT))
+        .map(|_| ())
+}
+
+// receive ssdp messages, add the devices in them to the table and print
+// the messages and the changes of the devices until running is unset
+fn listen(socket: &UdpSocket, devices: &mut Devices, config: &Config, running: &AtomicBool) {
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+    while running.load(atomic::Ordering::Relaxed) {
+        for event in devices.expire() {
+            println!("{}", event);
+        }
+        let (size, source) = match socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(e) => match e.kind() {
+                io::ErrorKind::WouldBlock
+                | io::ErrorKind::TimedOut
+                | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading ssdp messages: {}", e),
+            },
+        };
+        let message = match SsdpMessage::parse(&buffer[..size]) {
+            Some(message) => message,
+            None => continue,
+        };
+        if !config.quiet {
+            println!("got ssdp message from {}: {}", source, message);
+        }
+        for event in devices.add(&message, source.ip()) {
+            println!("{}", event);
+        }
+    }
+}
+
+// print the devices in the table
+fn print_devices(devices: &Devices) {
+    println!("--- discovered devices ---");
+    for (usn, device) in devices.get_devices() {
+        println!("{}: {}", usn, device);
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let interface = match &config.interface {
+        Some(name) => match get_interface(name).as_ref().and_then(get_interface_ip) {
+            Some(ip) => ip,
+            None => panic!(
+                "An error occurred when getting the ipv4 address of {}",
+                name
+            ),
+        },
+        None => Ipv4Addr::UNSPECIFIED,
+    };
+    let socket = match create_socket(interface) {
+        Ok(socket) => socket,
+        Err(e) => panic!("An error occurred when creating the ssdp socket: {}", e),
+    };
+
+    // stop on ctrl-c or sigterm and print the devices
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    if let Some(target) = &config.search {
+        if let Err(e) = send_search(&socket, target, config.mx) {
+            panic!("An error occurred when sending the search request: {}", e);
+        }
+    }
+
+    let mut devices = Devices::default();
+    listen(&socket, &mut devices, &config, &running);
+    print_devices(&devices);
+}
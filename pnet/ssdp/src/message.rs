@@ -0,0 +1,94 @@
+use std::fmt;
+
+// start line of an ssdp message
+pub enum Method {
+    Search,
+    Notify,
+
+    // response to a search request with its status code
+    Response(u16),
+
+    Other(String),
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Method::Search => write!(f, "M-SEARCH"),
+            Method::Notify => write!(f, "NOTIFY"),
+            Method::Response(status) => write!(f, "RESPONSE {}", status),
+            Method::Other(method) => write!(f, "{}", method),
+        }
+    }
+}
+
+// ssdp message, an http request or response over udp, with its headers;
+// header names are converted to lower case
+pub struct SsdpMessage {
+    pub method: Method,
+    headers: Vec<(String, String)>,
+}
+
+impl SsdpMessage {
+    // parse the ssdp message in data; returns none if it is not an http
+    // request or response
+    pub fn parse(data: &[u8]) -> Option<SsdpMessage> {
+        let text = String::from_utf8_lossy(data);
+        let mut lines = text.lines();
+        let start = lines.next()?;
+        let mut fields = start.split_whitespace();
+        let method = match (fields.next()?, fields.next()?) {
+            ("M-SEARCH", _) => Method::Search,
+            ("NOTIFY", _) => Method::Notify,
+            (version, status) if version.starts_with("HTTP/") => {
+                Method::Response(status.parse().ok()?)
+            }
+            (method, _) if start.ends_with("HTTP/1.1") || start.ends_with("HTTP/1.0") => {
+                Method::Other(method.to_string())
+            }
+            _ => return None,
+        };
+        let headers = lines
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+            .collect();
+        Some(SsdpMessage { method, headers })
+    }
+
+    // get the value of the first header with the lower case name
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // get the device or service type in the nt header of notifications or
+    // the st header of search requests and responses
+    pub fn get_type(&self) -> Option<&str> {
+        match self.method {
+            Method::Notify => self.get_header("nt"),
+            _ => self.get_header("st"),
+        }
+    }
+
+    // get the max-age in the cache-control header in seconds
+    pub fn get_max_age(&self) -> Option<u64> {
+        self.get_header("cache-control")?
+            .split(',')
+            .filter_map(|directive| directive.split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("max-age"))
+            .and_then(|(_, value)| value.trim().parse().ok())
+    }
+}
+
+impl fmt::Display for SsdpMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{method: {}", self.method)?;
+        for (name, value) in &self.headers {
+            write!(f, ", {}: {}", name, value)?;
+        }
+        write!(f, "}}")
+    }
+}
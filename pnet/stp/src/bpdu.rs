@@ -0,0 +1,263 @@
+use pnet::datalink::MacAddr;
+use pnet::packet::ethernet::EthernetPacket;
+use pnet::packet::Packet;
+
+use std::fmt;
+
+// bpdus are sent in 802.3 frames with an 802.2 llc header with the spanning
+// tree saps
+const LLC_HEADER: [u8; 3] = [0x42, 0x42, 0x03];
+
+// largest value of the ethertype field that is a length of an 802.3 frame
+const MAX_LENGTH: u16 = 1500;
+
+// bpdu types
+const TYPE_CONFIG: u8 = 0x00;
+const TYPE_TCN: u8 = 0x80;
+const TYPE_RST: u8 = 0x02;
+
+// length of configuration and rst bpdus without the protocol id, version
+// and type
+const CONFIG_LENGTH: usize = 31;
+
+// bpdu flags
+const FLAG_TC: u8 = 0x01;
+const FLAG_PROPOSAL: u8 = 0x02;
+const FLAG_LEARNING: u8 = 0x10;
+const FLAG_FORWARDING: u8 = 0x20;
+const FLAG_AGREEMENT: u8 = 0x40;
+const FLAG_TCA: u8 = 0x80;
+
+// identifier of a bridge consisting of a priority, that includes the
+// system id extension, and a mac address
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct BridgeId {
+    pub priority: u16,
+    pub mac: MacAddr,
+}
+
+impl BridgeId {
+    fn parse(bytes: &[u8]) -> BridgeId {
+        BridgeId {
+            priority: u16::from_be_bytes([bytes[0], bytes[1]]),
+            mac: MacAddr::new(bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]),
+        }
+    }
+}
+
+impl fmt::Display for BridgeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04x}.{}", self.priority, self.mac)
+    }
+}
+
+// protocol version of a bpdu
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Version {
+    Stp,
+    Rstp,
+    Mstp,
+    Unknown(u8),
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Version::Stp => write!(f, "stp"),
+            Version::Rstp => write!(f, "rstp"),
+            Version::Mstp => write!(f, "mstp"),
+            Version::Unknown(v) => write!(f, "unknown ({})", v),
+        }
+    }
+}
+
+// role of the port that sent an rst bpdu
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PortRole {
+    Unknown,
+    AlternateBackup,
+    Root,
+    Designated,
+}
+
+impl fmt::Display for PortRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortRole::Unknown => write!(f, "unknown"),
+            PortRole::AlternateBackup => write!(f, "alternate/backup"),
+            PortRole::Root => write!(f, "root"),
+            PortRole::Designated => write!(f, "designated"),
+        }
+    }
+}
+
+// fields of configuration and rst bpdus; times are in 1/256 seconds
+pub struct Config {
+    pub flags: u8,
+    pub root: BridgeId,
+    pub root_path_cost: u32,
+    pub bridge: BridgeId,
+    pub port: u16,
+    pub message_age: u16,
+    pub max_age: u16,
+    pub hello_time: u16,
+    pub forward_delay: u16,
+}
+
+impl Config {
+    fn parse(bytes: &[u8]) -> Option<Config> {
+        let bytes = bytes.get(..CONFIG_LENGTH)?;
+        let read_u16 = |i: usize| u16::from_be_bytes([bytes[i], bytes[i + 1]]);
+        Some(Config {
+            flags: bytes[0],
+            root: BridgeId::parse(&bytes[1..9]),
+            root_path_cost: u32::from_be_bytes(bytes[9..13].try_into().ok()?),
+            bridge: BridgeId::parse(&bytes[13..21]),
+            port: read_u16(21),
+            message_age: read_u16(23),
+            max_age: read_u16(25),
+            hello_time: read_u16(27),
+            forward_delay: read_u16(29),
+        })
+    }
+
+    // check if the topology change flag is set
+    pub fn is_topology_change(&self) -> bool {
+        self.flags & FLAG_TC != 0
+    }
+
+    // get the port role in the flags of rst bpdus
+    pub fn get_role(&self) -> PortRole {
+        match (self.flags >> 2) & 0x3 {
+            1 => PortRole::AlternateBackup,
+            2 => PortRole::Root,
+            3 => PortRole::Designated,
+            _ => PortRole::Unknown,
+        }
+    }
+
+    // get the port state in the flags of rst bpdus
+    pub fn get_state(&self) -> &'static str {
+        match (
+            self.flags & FLAG_LEARNING != 0,
+            self.flags & FLAG_FORWARDING != 0,
+        ) {
+            (_, true) => "forwarding",
+            (true, false) => "learning",
+            (false, false) => "discarding",
+        }
+    }
+
+    // get the names of the flags that are set
+    fn get_flags(&self, rst: bool) -> Vec<&'static str> {
+        let mut names = Vec::new();
+        let mut flags = vec![(FLAG_TC, "tc"), (FLAG_TCA, "tca")];
+        if rst {
+            flags.extend([(FLAG_PROPOSAL, "proposal"), (FLAG_AGREEMENT, "agreement")]);
+        }
+        for (flag, name) in flags {
+            if self.flags & flag != 0 {
+                names.push(name);
+            }
+        }
+        names
+    }
+}
+
+// format a bpdu time in 1/256 seconds
+fn format_time(time: u16) -> String {
+    format!("{}", f64::from(time) / 256.0)
+}
+
+// type of a bpdu
+pub enum BpduType {
+    Config(Config),
+    Rst(Config),
+    TopologyChangeNotification,
+}
+
+// spanning tree bpdu
+pub struct Bpdu {
+    pub source: MacAddr,
+    pub version: Version,
+    pub bpdu_type: BpduType,
+}
+
+impl Bpdu {
+    // parse the bpdu in the ethernet frame
+    pub fn parse(frame: &[u8]) -> Option<Bpdu> {
+        // bpdus use 802.3 frames, so the ethertype field contains the length
+        let ethernet_packet = EthernetPacket::new(frame)?;
+        let length = ethernet_packet.get_ethertype().0;
+        if length > MAX_LENGTH {
+            return None;
+        }
+        let payload = ethernet_packet.payload().get(..usize::from(length))?;
+        if payload.get(..LLC_HEADER.len())? != LLC_HEADER {
+            return None;
+        }
+
+        // Bpdu Header:
+        // protocol id (2 bytes, always 0), version (1 byte), type (1 byte)
+        let bpdu = &payload[LLC_HEADER.len()..];
+        let header = bpdu.get(..4)?;
+        if header[..2] != [0, 0] {
+            return None;
+        }
+        let version = match header[2] {
+            0 => Version::Stp,
+            2 => Version::Rstp,
+            3 => Version::Mstp,
+            v => Version::Unknown(v),
+        };
+
+        // mstp bpdus are rst bpdus of the common and internal spanning tree
+        // followed by the msti configurations, only the former are decoded
+        let bpdu_type = match header[3] {
+            TYPE_CONFIG => BpduType::Config(Config::parse(&bpdu[4..])?),
+            TYPE_RST => BpduType::Rst(Config::parse(&bpdu[4..])?),
+            TYPE_TCN => BpduType::TopologyChangeNotification,
+            _ => return None,
+        };
+        Some(Bpdu {
+            source: ethernet_packet.get_source(),
+            version,
+            bpdu_type,
+        })
+    }
+}
+
+impl fmt::Display for Bpdu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bpdu from {}: ", self.version, self.source)?;
+        let (config, rst) = match &self.bpdu_type {
+            BpduType::Config(config) => (config, false),
+            BpduType::Rst(config) => (config, true),
+            BpduType::TopologyChangeNotification => {
+                return write!(f, "topology change notification");
+            }
+        };
+        write!(
+            f,
+            "{{root: {}, root path cost: {}, bridge: {}, port: {:04x}",
+            config.root, config.root_path_cost, config.bridge, config.port
+        )?;
+        if rst {
+            write!(
+                f,
+                ", role: {}, state: {}",
+                config.get_role(),
+                config.get_state()
+            )?;
+        }
+        write!(
+            f,
+            ", flags: [{}], message age: {}, max age: {}, hello time: {}, forward delay: {}}}",
+            config.get_flags(rst).join(", "),
+            format_time(config.message_age),
+            format_time(config.max_age),
+            format_time(config.hello_time),
+            format_time(config.forward_delay),
+        )
+    }
+}
@@ -0,0 +1,29 @@
+use clap::Parser;
+
+// command line configuration of the stp tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Monitor spanning tree bpdus, root bridge changes and topology changes")]
+pub struct Config {
+    // network interface to capture bpdus on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Capture bpdus on the network interface NAME; by default, the \
+        first interface that is up, is not a loopback and has an ip address \
+        is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., if the network \
+        card drops frames sent to the bridge group address"
+    )]
+    pub promiscuous: bool,
+
+    // only print alerts
+    #[arg(short, long, help = "Only print alerts instead of every bpdu")]
+    pub quiet: bool,
+}
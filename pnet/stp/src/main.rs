@@ -0,0 +1,114 @@
+extern crate pnet;
+
+mod bpdu;
+mod config;
+mod topology;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::interface::find_interface;
+use pnet::datalink::DataLinkReceiver;
+
+use std::io;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bpdu::Bpdu;
+use config::Config;
+use topology::Topology;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// receive bpdus, add them to the topology and print the bpdus and the
+// alerts they caused until running is unset
+fn monitor(
+    rx: &mut dyn DataLinkReceiver,
+    topology: &mut Topology,
+    config: &Config,
+    running: &AtomicBool,
+) {
+    while running.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading bpdus: {}", e),
+            },
+        };
+        let bpdu = match Bpdu::parse(frame) {
+            Some(bpdu) => bpdu,
+            None => continue,
+        };
+        if !config.quiet {
+            println!("{}", bpdu);
+        }
+        for alert in topology.add(&bpdu) {
+            println!("{}", alert);
+        }
+    }
+}
+
+// print the root bridge and the bridge ports in the topology
+fn print_topology(topology: &Topology) {
+    println!("--- spanning tree ---");
+    match topology.get_root() {
+        Some(root) => println!(
+            "root bridge: {} ({} changes)",
+            root,
+            topology.get_root_changes()
+        ),
+        None => println!("root bridge: unknown"),
+    }
+    println!(
+        "{:<22} {:<4} {:<17} {:<7} {:<22} {:>10} {:<16} {:>6} {:>7} {:>10}",
+        "BRIDGE", "PORT", "MAC", "VERSION", "ROOT", "COST", "ROLE", "BPDUS", "CHANGES", "LAST SEEN"
+    );
+    for ((bridge, id), port) in topology.get_ports() {
+        println!(
+            "{:<22} {:04x} {:<17} {:<7} {:<22} {:>10} {:<16} {:>6} {:>7} {:>8.1} s",
+            bridge.to_string(),
+            id,
+            port.source.to_string(),
+            port.version.to_string(),
+            port.root.to_string(),
+            port.root_path_cost,
+            port.role.map_or("-".to_string(), |r| r.to_string()),
+            port.bpdus,
+            port.topology_changes,
+            port.last_seen.elapsed().as_secs_f64(),
+        );
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let mut rx = match create_channel(&interface, READ_TIMEOUT, config.promiscuous) {
+        Ok((_, rx)) => rx,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+
+    // stop on ctrl-c or sigterm and print the topology
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    let mut topology = Topology::default();
+    monitor(rx.as_mut(), &mut topology, &config, &running);
+    print_topology(&topology);
+}
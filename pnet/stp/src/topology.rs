@@ -0,0 +1,154 @@
+use pnet::datalink::MacAddr;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Instant;
+
+use crate::bpdu::{Bpdu, BpduType, BridgeId, PortRole, Version};
+
+// spanning tree events that indicate an unstable topology
+pub enum Alert {
+    // bpdus announce another root bridge than before
+    RootChange {
+        old: BridgeId,
+        new: BridgeId,
+        bridge: BridgeId,
+    },
+
+    // bridge port started setting the topology change flag
+    TopologyChange {
+        bridge: BridgeId,
+        port: u16,
+    },
+
+    // bridge sent a topology change notification towards the root
+    TopologyChangeNotification {
+        source: MacAddr,
+    },
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alert::RootChange { old, new, bridge } => write!(
+                f,
+                "ALERT: root bridge change: {} -> {} announced by {}",
+                old, new, bridge
+            ),
+            Alert::TopologyChange { bridge, port } => write!(
+                f,
+                "ALERT: topology change: flag set by bridge {} port {:04x}",
+                bridge, port
+            ),
+            Alert::TopologyChangeNotification { source } => {
+                write!(f, "ALERT: topology change notification: sent by {}", source)
+            }
+        }
+    }
+}
+
+// bridge port that sent configuration or rst bpdus
+pub struct Port {
+    pub source: MacAddr,
+    pub version: Version,
+    pub root: BridgeId,
+    pub root_path_cost: u32,
+    pub role: Option<PortRole>,
+    pub topology_change: bool,
+    pub last_seen: Instant,
+
+    // number of bpdus and of topology changes the port announced
+    pub bpdus: u64,
+    pub topology_changes: u64,
+}
+
+// bridge ports are identified by their bridge id and port id
+pub type PortKey = (BridgeId, u16);
+
+// spanning tree topology learned from bpdus that detects root bridge and
+// topology changes
+#[derive(Default)]
+pub struct Topology {
+    root: Option<BridgeId>,
+    root_changes: u64,
+    ports: BTreeMap<PortKey, Port>,
+}
+
+impl Topology {
+    // add the bpdu to the topology and get the alerts it caused
+    pub fn add(&mut self, bpdu: &Bpdu) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        let config = match &bpdu.bpdu_type {
+            BpduType::Config(config) | BpduType::Rst(config) => config,
+            BpduType::TopologyChangeNotification => {
+                alerts.push(Alert::TopologyChangeNotification {
+                    source: bpdu.source,
+                });
+                return alerts;
+            }
+        };
+
+        match self.root {
+            Some(root) if root != config.root => {
+                alerts.push(Alert::RootChange {
+                    old: root,
+                    new: config.root,
+                    bridge: config.bridge,
+                });
+                self.root_changes += 1;
+            }
+            _ => (),
+        }
+        self.root = Some(config.root);
+
+        let now = Instant::now();
+        let port = self
+            .ports
+            .entry((config.bridge, config.port))
+            .or_insert(Port {
+                source: bpdu.source,
+                version: bpdu.version,
+                root: config.root,
+                root_path_cost: config.root_path_cost,
+                role: None,
+                topology_change: false,
+                last_seen: now,
+                bpdus: 0,
+                topology_changes: 0,
+            });
+        if config.is_topology_change() && !port.topology_change {
+            alerts.push(Alert::TopologyChange {
+                bridge: config.bridge,
+                port: config.port,
+            });
+            port.topology_changes += 1;
+        }
+        port.source = bpdu.source;
+        port.version = bpdu.version;
+        port.root = config.root;
+        port.root_path_cost = config.root_path_cost;
+        port.role = match bpdu.bpdu_type {
+            BpduType::Rst(_) => Some(config.get_role()),
+            _ => None,
+        };
+        port.topology_change = config.is_topology_change();
+        port.last_seen = now;
+        port.bpdus += 1;
+        alerts
+    }
+
+    // get the current root bridge
+    pub fn get_root(&self) -> Option<BridgeId> {
+        self.root
+    }
+
+    // get the number of root bridge changes
+    pub fn get_root_changes(&self) -> u64 {
+        self.root_changes
+    }
+
+    // get the bridge ports sorted by bridge id and port id
+    pub fn get_ports(&self) -> &BTreeMap<PortKey, Port> {
+        &self.ports
+    }
+}
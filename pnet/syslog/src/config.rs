@@ -0,0 +1,44 @@
+use clap::Parser;
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+// command line configuration of the syslog tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Receive syslog messages over udp and parse them")]
+pub struct Config {
+    // address to receive messages on
+    #[arg(
+        short,
+        long,
+        value_name = "ADDRESS",
+        default_value = "0.0.0.0:514",
+        help = "Receive syslog messages on the udp socket address ADDRESS, \
+        e.g., [::]:514 for ipv6"
+    )]
+    pub listen: SocketAddr,
+
+    // relay messages to other collectors
+    #[arg(
+        short,
+        long,
+        value_name = "ADDRESS",
+        help = "Relay every received message unchanged to the syslog \
+        collector at the udp socket address ADDRESS; can be specified \
+        multiple times"
+    )]
+    pub relay: Vec<SocketAddr>,
+
+    // output format
+    #[arg(short, long, help = "Write messages as json objects, one per line")]
+    pub json: bool,
+
+    // output file
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        help = "Append messages to FILE instead of writing them to stdout"
+    )]
+    pub output: Option<PathBuf>,
+}
@@ -0,0 +1,101 @@
+mod config;
+mod message;
+
+use clap::Parser;
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::net::UdpSocket;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use config::Config;
+use message::SyslogMessage;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// maximum size of syslog messages
+const BUFFER_SIZE: usize = 65535;
+
+// create the udp socket that receives syslog messages on address and
+// relays them
+fn create_socket(config: &Config) -> io::Result<UdpSocket> {
+    let socket = UdpSocket::bind(config.listen)?;
+    socket.set_read_timeout(Some(READ_TIMEOUT))?;
+    Ok(socket)
+}
+
+// create the output the messages are written to
+fn create_output(config: &Config) -> io::Result<Box<dyn Write>> {
+    match &config.output {
+        Some(path) => {
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            Ok(Box::new(file))
+        }
+        None => Ok(Box::new(io::stdout())),
+    }
+}
+
+// receive syslog messages, write them to output and relay them until
+// running is unset
+fn listen(socket: &UdpSocket, output: &mut dyn Write, config: &Config, running: &AtomicBool) {
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+    while running.load(atomic::Ordering::Relaxed) {
+        let (size, source) = match socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(e) => match e.kind() {
+                io::ErrorKind::WouldBlock
+                | io::ErrorKind::TimedOut
+                | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading syslog messages: {}", e),
+            },
+        };
+        let received = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+
+        // relay the raw message, failing relays do not stop the listener
+        for relay in &config.relay {
+            if let Err(e) = socket.send_to(&buffer[..size], relay) {
+                eprintln!("Could not relay syslog message to {}: {}", relay, e);
+            }
+        }
+
+        let message = SyslogMessage::parse(&buffer[..size]);
+        let line = match config.json {
+            true => message.to_json(&source.to_string(), received),
+            false => format!("got syslog message from {}: {}", source, message),
+        };
+        if let Err(e) = writeln!(output, "{}", line).and_then(|_| output.flush()) {
+            panic!("An error occurred when writing syslog messages: {}", e);
+        }
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let socket = match create_socket(&config) {
+        Ok(socket) => socket,
+        Err(e) => panic!("An error occurred when creating the syslog socket: {}", e),
+    };
+    let mut output = match create_output(&config) {
+        Ok(output) => output,
+        Err(e) => panic!("An error occurred when opening the output file: {}", e),
+    };
+
+    // stop on ctrl-c or sigterm
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    listen(&socket, output.as_mut(), &config, &running);
+}
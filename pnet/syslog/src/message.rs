@@ -0,0 +1,295 @@
+use packet_utils::json::json_string;
+
+use std::fmt;
+
+// Facility:
+// facility of a message in the priority value divided by 8
+const FACILITIES: [&str; 24] = [
+    "kern", "user", "mail", "daemon", "auth", "syslog", "lpr", "news", "uucp", "cron", "authpriv",
+    "ftp", "ntp", "audit", "alert", "clock", "local0", "local1", "local2", "local3", "local4",
+    "local5", "local6", "local7",
+];
+
+// Severity:
+// severity of a message in the priority value modulo 8
+const SEVERITIES: [&str; 8] = [
+    "emerg", "alert", "crit", "err", "warning", "notice", "info", "debug",
+];
+
+// maximum priority value
+const MAX_PRIORITY: u8 = 191;
+
+// months in rfc 3164 timestamps
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+// nil value of rfc 5424 header fields and structured data
+const NIL: &str = "-";
+
+// byte order mark that may precede the message of rfc 5424 messages
+const BOM: char = '\u{feff}';
+
+// get the optional text as json string or null
+fn json_option(s: Option<&str>) -> String {
+    s.map(json_string).unwrap_or_else(|| "null".into())
+}
+
+// format of a syslog message
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Format {
+    Rfc3164,
+    Rfc5424,
+    #[default]
+    Unknown,
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Rfc3164 => write!(f, "rfc3164"),
+            Format::Rfc5424 => write!(f, "rfc5424"),
+            Format::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+// split the first space separated field from text
+fn split_field(text: &str) -> Option<(&str, &str)> {
+    match text.split_once(' ') {
+        Some((field, rest)) if !field.is_empty() => Some((field, rest)),
+        _ => None,
+    }
+}
+
+// get the length of the structured data at the start of text; it consists
+// of elements in brackets with quoted parameter values that may contain
+// escaped quotes and brackets
+fn get_structured_data_length(text: &str) -> Option<usize> {
+    let mut length = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((_, '[')) = chars.peek() {
+        let mut quoted = false;
+        let mut escaped = false;
+        loop {
+            let (i, c) = chars.next()?;
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if quoted => escaped = true,
+                '"' => quoted = !quoted,
+                ']' if !quoted => {
+                    length = i + 1;
+                    break;
+                }
+                _ => (),
+            }
+        }
+    }
+    (length > 0).then_some(length)
+}
+
+// check if text starts with an rfc 3164 timestamp, e.g., "Oct 11 22:14:15"
+fn is_rfc3164_timestamp(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    bytes.len() >= 15
+        && MONTHS.iter().any(|m| text.starts_with(m))
+        && bytes[3] == b' '
+        && bytes[6] == b' '
+        && bytes[9] == b':'
+        && bytes[12] == b':'
+        && bytes[7..15]
+            .iter()
+            .all(|b| b.is_ascii_digit() || *b == b':')
+}
+
+// syslog message in the rfc 3164 or rfc 5424 format; header fields that
+// are missing or nil are none
+#[derive(Default)]
+pub struct SyslogMessage {
+    format: Format,
+    priority: Option<u8>,
+    timestamp: Option<String>,
+    hostname: Option<String>,
+    app_name: Option<String>,
+    proc_id: Option<String>,
+    msg_id: Option<String>,
+    structured_data: Option<String>,
+    message: String,
+}
+
+impl SyslogMessage {
+    // parse the syslog message in bytes; messages that do not follow one of
+    // the formats are kept as unknown messages with their text
+    pub fn parse(bytes: &[u8]) -> SyslogMessage {
+        let text = String::from_utf8_lossy(bytes);
+        let text = text.trim_end_matches(['\n', '\r', '\0']);
+
+        // priority value in angle brackets
+        let mut priority = None;
+        let mut rest = text;
+        if let Some((value, after)) = text
+            .strip_prefix('<')
+            .and_then(|t| t.split_once('>'))
+            .filter(|(p, _)| (1..=3).contains(&p.len()))
+        {
+            if let Some(value) = value.parse().ok().filter(|p| *p <= MAX_PRIORITY) {
+                priority = Some(value);
+                rest = after;
+            }
+        }
+
+        // rfc 5424 messages start with the version after the priority,
+        // everything else is handled as rfc 3164 message
+        if priority.is_some() {
+            let mut message = SyslogMessage {
+                priority,
+                ..Default::default()
+            };
+            if message.parse_rfc5424(rest).is_some() {
+                return message;
+            }
+        }
+        let mut message = SyslogMessage {
+            priority,
+            ..Default::default()
+        };
+        message.parse_rfc3164(rest);
+        message
+    }
+
+    // parse the header, structured data and message of an rfc 5424 message
+    // after the priority
+    fn parse_rfc5424(&mut self, text: &str) -> Option<()> {
+        let (version, text) = split_field(text)?;
+        if version != "1" {
+            return None;
+        }
+        self.format = Format::Rfc5424;
+        let nil = |field: &str| (field != NIL).then(|| field.to_string());
+        let (timestamp, text) = split_field(text)?;
+        let (hostname, text) = split_field(text)?;
+        let (app_name, text) = split_field(text)?;
+        let (proc_id, text) = split_field(text)?;
+        let (msg_id, text) = split_field(text).unwrap_or((text, ""));
+        self.timestamp = nil(timestamp);
+        self.hostname = nil(hostname);
+        self.app_name = nil(app_name);
+        self.proc_id = nil(proc_id);
+        self.msg_id = nil(msg_id);
+
+        let text = match text.strip_prefix(NIL) {
+            Some(text) => text,
+            None => {
+                let length = get_structured_data_length(text)?;
+                self.structured_data = Some(text[..length].to_string());
+                &text[length..]
+            }
+        };
+        let text = text.strip_prefix(' ').unwrap_or(text);
+        self.message = text.strip_prefix(BOM).unwrap_or(text).to_string();
+        Some(())
+    }
+
+    // parse the timestamp, hostname, tag and content of an rfc 3164
+    // message after the priority; the hostname is often omitted and the
+    // tag consists of the app name and an optional process id in brackets
+    fn parse_rfc3164(&mut self, text: &str) {
+        let mut text = text;
+        if is_rfc3164_timestamp(text) {
+            self.format = Format::Rfc3164;
+            self.timestamp = Some(text[..15].to_string());
+            text = text[15..].trim_start_matches(' ');
+            if let Some((hostname, rest)) = split_field(text) {
+                if !hostname.ends_with(':') && !hostname.contains('[') {
+                    self.hostname = Some(hostname.to_string());
+                    text = rest;
+                }
+            }
+        }
+
+        // tag with optional process id that ends with a colon
+        if let Some((tag, rest)) = text.split_once(':') {
+            let (app_name, proc_id) = match tag.strip_suffix(']').and_then(|t| t.split_once('[')) {
+                Some((app_name, proc_id)) => (app_name, Some(proc_id)),
+                None => (tag, None),
+            };
+            let valid = |s: &str| {
+                !s.is_empty()
+                    && s.len() <= 48
+                    && s.chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "-_./".contains(c))
+            };
+            if valid(app_name) && proc_id.map(valid).unwrap_or(true) {
+                self.format = Format::Rfc3164;
+                self.app_name = Some(app_name.to_string());
+                self.proc_id = proc_id.map(|p| p.to_string());
+                text = rest.strip_prefix(' ').unwrap_or(rest);
+            }
+        }
+        self.message = text.to_string();
+    }
+
+    // get the format
+    pub fn get_format(&self) -> Format {
+        self.format
+    }
+
+    // get the facility name
+    pub fn get_facility(&self) -> Option<&'static str> {
+        self.priority.map(|p| FACILITIES[usize::from(p / 8)])
+    }
+
+    // get the severity name
+    pub fn get_severity(&self) -> Option<&'static str> {
+        self.priority.map(|p| SEVERITIES[usize::from(p % 8)])
+    }
+
+    // get the message as json object with the source address and the
+    // receive time in seconds since the unix epoch
+    pub fn to_json(&self, source: &str, received: f64) -> String {
+        format!(
+            "{{\"received\":{:.6},\"source\":{},\"format\":{},\"priority\":{},\
+            \"facility\":{},\"severity\":{},\"timestamp\":{},\"hostname\":{},\
+            \"app_name\":{},\"proc_id\":{},\"msg_id\":{},\"structured_data\":{},\
+            \"message\":{}}}",
+            received,
+            json_string(source),
+            json_string(&self.get_format().to_string()),
+            self.priority
+                .map(|p| p.to_string())
+                .unwrap_or_else(|| "null".into()),
+            json_option(self.get_facility()),
+            json_option(self.get_severity()),
+            json_option(self.timestamp.as_deref()),
+            json_option(self.hostname.as_deref()),
+            json_option(self.app_name.as_deref()),
+            json_option(self.proc_id.as_deref()),
+            json_option(self.msg_id.as_deref()),
+            json_option(self.structured_data.as_deref()),
+            json_string(&self.message),
+        )
+    }
+}
+
+impl fmt::Display for SyslogMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field = |value: &Option<String>| value.clone().unwrap_or_else(|| NIL.into());
+        write!(
+            f,
+            "{{format: {}, facility: {}, severity: {}, timestamp: {}, hostname: {}, \
+            app: {}, pid: {}, msgid: {}",
+            self.get_format(),
+            self.get_facility().unwrap_or(NIL),
+            self.get_severity().unwrap_or(NIL),
+            field(&self.timestamp),
+            field(&self.hostname),
+            field(&self.app_name),
+            field(&self.proc_id),
+            field(&self.msg_id),
+        )?;
+        if let Some(structured_data) = &self.structured_data {
+            write!(f, ", structured data: {}", structured_data)?;
+        }
+        write!(f, ", message: {:?}}}", self.message)
+    }
+}
@@ -0,0 +1,45 @@
+use clap::Parser;
+
+// command line configuration of the tftp tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Listen for tftp packets and track the progress of transfers")]
+pub struct Config {
+    // network interface to capture packets on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Capture packets on the network interface NAME; by default, \
+        the first interface that is up, is not a loopback and has an ip \
+        address is used"
+    )]
+    pub interface: Option<String>,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        help = "Put the interface into promiscuous mode, e.g., to see the tftp \
+        traffic of other hosts on a mirrored port"
+    )]
+    pub promiscuous: bool,
+
+    // ip versions
+    #[arg(
+        short = '4',
+        long,
+        conflicts_with = "ipv6",
+        help = "Only listen for tftp packets over ipv4"
+    )]
+    pub ipv4: bool,
+    #[arg(short = '6', long, help = "Only listen for tftp packets over ipv6")]
+    pub ipv6: bool,
+
+    // also print data and ack packets
+    #[arg(
+        short,
+        long,
+        help = "Print every data and ack packet in addition to the requests, \
+        errors and transfer events"
+    )]
+    pub verbose: bool,
+}
@@ -0,0 +1,149 @@
+extern crate pnet;
+
+mod config;
+mod packet;
+mod transfer;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::dissect::{parse_ip_frame, parse_udp, Datagram};
+use packet_utils::interface::find_interface;
+use pnet::datalink::DataLinkReceiver;
+
+use std::io;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::Duration;
+
+use config::Config;
+use packet::TftpPacket;
+use transfer::Transfers;
+
+// well-known port of tftp requests; the rest of a transfer uses other ports
+const TFTP_PORT: u16 = 69;
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// parse the ethernet frame and get the udp packet inside it if its ip
+// version is enabled in config; fragmented ipv4 packets and ipv6 packets
+// with extension headers are ignored
+fn parse_frame<'a>(frame: &'a [u8], config: &Config) -> Option<Datagram<'a>> {
+    let ip = parse_ip_frame(frame, config.ipv4, config.ipv6)?;
+    parse_udp(&ip, &[])
+}
+
+// handle the udp packet: parse it if it is a request to the tftp port or
+// belongs to a known transfer and print it and the events it caused
+fn handle_datagram(datagram: &Datagram, transfers: &mut Transfers, config: &Config) {
+    let (source, destination) = (datagram.source, datagram.destination);
+    let is_request = destination.port() == TFTP_PORT;
+    if !is_request && !transfers.contains(source, destination) {
+        return;
+    }
+    let tftp_packet = match TftpPacket::parse(datagram.payload) {
+        Some(tftp_packet) => tftp_packet,
+        None => {
+            println!("got invalid tftp packet from {} to {}", source, destination);
+            return;
+        }
+    };
+    if config.verbose || !tftp_packet.is_data_or_ack() {
+        println!(
+            "got tftp packet from {} to {}: {}",
+            source, destination, tftp_packet
+        );
+    }
+    let events = match is_request && tftp_packet.is_request() {
+        true => transfers.add_request(source, destination.ip(), &tftp_packet),
+        false => transfers.add(source, destination, &tftp_packet),
+    };
+    for event in events {
+        println!("{}", event);
+    }
+}
+
+// receive tftp packets and track the transfers until running is unset
+fn listen(
+    rx: &mut dyn DataLinkReceiver,
+    transfers: &mut Transfers,
+    config: &Config,
+    running: &AtomicBool,
+) {
+    while running.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => Some(frame),
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => None,
+                _ => panic!("An error occurred when reading packets: {}", e),
+            },
+        };
+
+        // transfers also stall while no packets are received
+        for event in transfers.expire() {
+            println!("{}", event);
+        }
+        if let Some(datagram) = frame.and_then(|f| parse_frame(f, config)) {
+            handle_datagram(&datagram, transfers, config);
+        }
+    }
+}
+
+// print the transfers
+fn print_transfers(transfers: &Transfers) {
+    println!("--- tftp transfers ---");
+    println!(
+        "{:<47} {:<39} {:<5} {:<12} {:>10} {:>10} {:>8} {:>7} {:>10} FILENAME",
+        "CLIENT", "SERVER", "DIR", "STATE", "BYTES", "SIZE", "BLOCKS", "RETRANS", "RATE"
+    );
+    for ((client, server), transfer) in transfers.get_transfers() {
+        let size = match transfer.size {
+            Some(size) => size.to_string(),
+            None => "-".to_string(),
+        };
+        println!(
+            "{:<47} {:<39} {:<5} {:<12} {:>10} {:>10} {:>8} {:>7} {:>5.1} KB/s {}",
+            client.to_string(),
+            server.to_string(),
+            transfer.direction.to_string(),
+            transfer.state.to_string(),
+            transfer.bytes,
+            size,
+            transfer.blocks,
+            transfer.retransmissions,
+            transfer.get_rate() / 1000.0,
+            transfer.filename,
+        );
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let mut rx = match create_channel(&interface, READ_TIMEOUT, config.promiscuous) {
+        Ok((_, rx)) => rx,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+
+    // stop on ctrl-c or sigterm and print the transfers
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    let mut transfers = Transfers::default();
+    listen(rx.as_mut(), &mut transfers, &config, &running);
+    print_transfers(&transfers);
+}
@@ -0,0 +1,189 @@
+use std::fmt;
+
+// opcodes of tftp packets
+const OPCODE_RRQ: u16 = 1;
+const OPCODE_WRQ: u16 = 2;
+const OPCODE_DATA: u16 = 3;
+const OPCODE_ACK: u16 = 4;
+const OPCODE_ERROR: u16 = 5;
+const OPCODE_OACK: u16 = 6;
+
+// get the name of the tftp error code
+fn get_error_name(code: u16) -> &'static str {
+    match code {
+        0 => "not defined",
+        1 => "file not found",
+        2 => "access violation",
+        3 => "disk full",
+        4 => "illegal operation",
+        5 => "unknown transfer id",
+        6 => "file already exists",
+        7 => "no such user",
+        8 => "option negotiation failed",
+        _ => "unknown",
+    }
+}
+
+// split bytes into null-terminated strings; a missing terminator at the end
+// makes the packet invalid
+fn parse_strings(bytes: &[u8]) -> Option<Vec<String>> {
+    let mut strings = Vec::new();
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let end = rest.iter().position(|b| *b == 0)?;
+        strings.push(String::from_utf8_lossy(&rest[..end]).into_owned());
+        rest = &rest[end + 1..];
+    }
+    Some(strings)
+}
+
+// parse the option name and value pairs in strings; option names are case
+// insensitive
+fn parse_options(strings: &[String]) -> Vec<(String, String)> {
+    strings
+        .chunks_exact(2)
+        .map(|o| (o[0].to_lowercase(), o[1].clone()))
+        .collect()
+}
+
+// read or write request with filename, transfer mode and options
+pub struct Request {
+    pub filename: String,
+    pub mode: String,
+    pub options: Vec<(String, String)>,
+}
+
+impl Request {
+    fn parse(bytes: &[u8]) -> Option<Request> {
+        let strings = parse_strings(bytes)?;
+        if strings.len() < 2 {
+            return None;
+        }
+        Some(Request {
+            filename: strings[0].clone(),
+            mode: strings[1].to_lowercase(),
+            options: parse_options(&strings[2..]),
+        })
+    }
+}
+
+impl fmt::Display for Request {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{filename: \"{}\", mode: {}, options: [{}]}}",
+            self.filename,
+            self.mode,
+            format_options(&self.options)
+        )
+    }
+}
+
+// format the options as name=value list
+fn format_options(options: &[(String, String)]) -> String {
+    options
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+// get the value of option name as number
+pub fn get_option(options: &[(String, String)], name: &str) -> Option<u64> {
+    options
+        .iter()
+        .find(|(n, _)| n == name)
+        .and_then(|(_, value)| value.parse().ok())
+}
+
+// tftp packet
+pub enum TftpPacket {
+    ReadRequest(Request),
+    WriteRequest(Request),
+    Data { block: u16, length: usize },
+    Ack { block: u16 },
+    Error { code: u16, message: String },
+    OptionAck { options: Vec<(String, String)> },
+}
+
+impl TftpPacket {
+    // parse the tftp packet in bytes
+    pub fn parse(bytes: &[u8]) -> Option<TftpPacket> {
+        if bytes.len() < 4 {
+            return None;
+        }
+        let opcode = u16::from_be_bytes([bytes[0], bytes[1]]);
+        let number = u16::from_be_bytes([bytes[2], bytes[3]]);
+        let packet = match opcode {
+            OPCODE_RRQ => TftpPacket::ReadRequest(Request::parse(&bytes[2..])?),
+            OPCODE_WRQ => TftpPacket::WriteRequest(Request::parse(&bytes[2..])?),
+            OPCODE_DATA => TftpPacket::Data {
+                block: number,
+                length: bytes.len() - 4,
+            },
+            OPCODE_ACK if bytes.len() == 4 => TftpPacket::Ack { block: number },
+            OPCODE_ERROR => {
+                let message = parse_strings(&bytes[4..])?;
+                if message.len() > 1 {
+                    return None;
+                }
+                TftpPacket::Error {
+                    code: number,
+                    message: message.into_iter().next().unwrap_or_default(),
+                }
+            }
+            OPCODE_OACK => {
+                let strings = parse_strings(&bytes[2..])?;
+                if strings.len() % 2 != 0 {
+                    return None;
+                }
+                TftpPacket::OptionAck {
+                    options: parse_options(&strings),
+                }
+            }
+            _ => return None,
+        };
+        Some(packet)
+    }
+
+    // check if the packet is a read or write request
+    pub fn is_request(&self) -> bool {
+        matches!(
+            self,
+            TftpPacket::ReadRequest(..) | TftpPacket::WriteRequest(..)
+        )
+    }
+
+    // check if the packet is a data or ack packet
+    pub fn is_data_or_ack(&self) -> bool {
+        matches!(self, TftpPacket::Data { .. } | TftpPacket::Ack { .. })
+    }
+}
+
+impl fmt::Display for TftpPacket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TftpPacket::ReadRequest(request) => write!(f, "rrq {}", request),
+            TftpPacket::WriteRequest(request) => write!(f, "wrq {}", request),
+            TftpPacket::Data { block, length } => {
+                write!(f, "data {{block: {}, length: {}}}", block, length)
+            }
+            TftpPacket::Ack { block } => write!(f, "ack {{block: {}}}", block),
+            TftpPacket::Error { code, message } => write!(
+                f,
+                "error {{code: {} ({}), message: \"{}\"}}",
+                code,
+                get_error_name(*code),
+                message
+            ),
+            TftpPacket::OptionAck { options } => {
+                write!(f, "oack {{options: [{}]}}", format_options(options))
+            }
+        }
+    }
+}
+
+// format the error code and message of an error packet
+pub fn format_error(code: u16, message: &str) -> String {
+    format!("error {} ({}): {}", code, get_error_name(code), message)
+}
@@ -0,0 +1,369 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use crate::packet::{self, Request, TftpPacket};
+
+// default block size without blksize option
+const DEFAULT_BLOCK_SIZE: u64 = 512;
+
+// interval in which progress events of a transfer are generated
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+// time without packets after which a transfer is considered stalled
+const STALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+// transfer identified by the client address and port and the server address;
+// the server uses a new port for the transfer
+pub type TransferKey = (SocketAddr, IpAddr);
+
+// direction of a transfer from the client's view
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Read,
+    Write,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::Read => write!(f, "read"),
+            Direction::Write => write!(f, "write"),
+        }
+    }
+}
+
+// state of a transfer
+#[derive(Clone, PartialEq, Eq)]
+pub enum State {
+    Requested,
+    Transferring,
+    Completed,
+    Failed,
+    Stalled,
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            State::Requested => write!(f, "requested"),
+            State::Transferring => write!(f, "transferring"),
+            State::Completed => write!(f, "completed"),
+            State::Failed => write!(f, "failed"),
+            State::Stalled => write!(f, "stalled"),
+        }
+    }
+}
+
+// tftp transfer with its progress
+pub struct Transfer {
+    pub direction: Direction,
+    pub filename: String,
+    pub block_size: u64,
+    pub size: Option<u64>,
+    pub state: State,
+    pub blocks: u64,
+    pub bytes: u64,
+    pub retransmissions: u64,
+    pub started: Instant,
+    pub last_seen: Instant,
+    last_block: u16,
+    last_progress: Instant,
+}
+
+impl Transfer {
+    fn new(direction: Direction, request: &Request, now: Instant) -> Transfer {
+        // the size of a read request is only known from the server's oack
+        let size = match direction {
+            Direction::Read => None,
+            Direction::Write => packet::get_option(&request.options, "tsize"),
+        };
+        Transfer {
+            direction,
+            filename: request.filename.clone(),
+            block_size: DEFAULT_BLOCK_SIZE,
+            size,
+            state: State::Requested,
+            blocks: 0,
+            bytes: 0,
+            retransmissions: 0,
+            started: now,
+            last_seen: now,
+            last_block: 0,
+            last_progress: now,
+        }
+    }
+
+    // get the transfer rate in bytes per second
+    pub fn get_rate(&self) -> f64 {
+        let seconds = self.last_seen.duration_since(self.started).as_secs_f64();
+        match seconds {
+            s if s > 0.0 => self.bytes as f64 / s,
+            _ => 0.0,
+        }
+    }
+
+    // add the data packet with block and length and get the event it caused
+    fn add_data(&mut self, name: String, block: u16, length: usize, now: Instant) -> Option<Event> {
+        if block == self.last_block {
+            self.retransmissions += 1;
+            return None;
+        }
+        if block != self.last_block.wrapping_add(1) {
+            return None;
+        }
+        self.state = State::Transferring;
+        self.last_block = block;
+        self.blocks += 1;
+        self.bytes += length as u64;
+
+        // a short block ends the transfer
+        if (length as u64) < self.block_size {
+            self.state = State::Completed;
+            return Some(Event::Completed {
+                name,
+                bytes: self.bytes,
+                duration: now.duration_since(self.started),
+                rate: self.get_rate(),
+            });
+        }
+        if now.duration_since(self.last_progress) < PROGRESS_INTERVAL {
+            return None;
+        }
+        self.last_progress = now;
+        Some(Event::Progress {
+            name,
+            bytes: self.bytes,
+            size: self.size,
+            rate: self.get_rate(),
+        })
+    }
+
+    // add the option ack with the negotiated options
+    fn add_option_ack(&mut self, options: &[(String, String)]) {
+        if let Some(block_size) = packet::get_option(options, "blksize") {
+            self.block_size = block_size;
+        }
+        if let Some(size) = packet::get_option(options, "tsize") {
+            self.size = Some(size);
+        }
+    }
+}
+
+// transfer events
+pub enum Event {
+    Started {
+        name: String,
+    },
+    Progress {
+        name: String,
+        bytes: u64,
+        size: Option<u64>,
+        rate: f64,
+    },
+    Completed {
+        name: String,
+        bytes: u64,
+        duration: Duration,
+        rate: f64,
+    },
+    Failed {
+        name: String,
+        error: String,
+    },
+    Stalled {
+        name: String,
+        bytes: u64,
+    },
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Started { name } => write!(f, "transfer started: {}", name),
+            Event::Progress {
+                name,
+                bytes,
+                size,
+                rate,
+            } => {
+                write!(f, "transfer progress: {}: {} bytes", name, bytes)?;
+                if let Some(size) = size.filter(|s| *s > 0) {
+                    let percent = *bytes as f64 * 100.0 / size as f64;
+                    write!(f, " of {} ({:.0}%)", size, percent)?;
+                }
+                write!(f, ", {:.1} KB/s", rate / 1000.0)
+            }
+            Event::Completed {
+                name,
+                bytes,
+                duration,
+                rate,
+            } => write!(
+                f,
+                "transfer completed: {}: {} bytes in {:.1} s, {:.1} KB/s",
+                name,
+                bytes,
+                duration.as_secs_f64(),
+                rate / 1000.0
+            ),
+            Event::Failed { name, error } => {
+                write!(f, "ALERT: transfer failed: {}: {}", name, error)
+            }
+            Event::Stalled { name, bytes } => write!(
+                f,
+                "ALERT: transfer stalled: {}: no packets for {} s after {} bytes",
+                name,
+                STALL_TIMEOUT.as_secs(),
+                bytes
+            ),
+        }
+    }
+}
+
+// get the name of the transfer with key in events
+fn get_name(key: &TransferKey, transfer: &Transfer) -> String {
+    let (client, server) = key;
+    let preposition = match transfer.direction {
+        Direction::Read => "from",
+        Direction::Write => "to",
+    };
+    format!(
+        "{} of \"{}\" by {} {} {}",
+        transfer.direction, transfer.filename, client, preposition, server
+    )
+}
+
+// tftp transfers
+#[derive(Default)]
+pub struct Transfers {
+    transfers: BTreeMap<TransferKey, Transfer>,
+}
+
+impl Transfers {
+    // get the key of the transfer the packet from source to destination
+    // belongs to and if it was sent by the client
+    fn get_key(&self, source: SocketAddr, destination: SocketAddr) -> Option<(TransferKey, bool)> {
+        let from_client = (source, destination.ip());
+        if self.transfers.contains_key(&from_client) {
+            return Some((from_client, true));
+        }
+        let from_server = (destination, source.ip());
+        if self.transfers.contains_key(&from_server) {
+            return Some((from_server, false));
+        }
+        None
+    }
+
+    // check if the packet from source to destination belongs to a transfer
+    pub fn contains(&self, source: SocketAddr, destination: SocketAddr) -> bool {
+        self.get_key(source, destination).is_some()
+    }
+
+    // add the request from the client to the server and get the events it
+    // caused; a new request from the same client port replaces an old
+    // transfer
+    pub fn add_request(
+        &mut self,
+        client: SocketAddr,
+        server: IpAddr,
+        packet: &TftpPacket,
+    ) -> Vec<Event> {
+        let transfer = match packet {
+            TftpPacket::ReadRequest(request) => {
+                Transfer::new(Direction::Read, request, Instant::now())
+            }
+            TftpPacket::WriteRequest(request) => {
+                Transfer::new(Direction::Write, request, Instant::now())
+            }
+            _ => return Vec::new(),
+        };
+        let key = (client, server);
+        let name = get_name(&key, &transfer);
+        self.transfers.insert(key, transfer);
+        vec![Event::Started { name }]
+    }
+
+    // add the packet from source to destination to its transfer and get the
+    // events it caused
+    pub fn add(
+        &mut self,
+        source: SocketAddr,
+        destination: SocketAddr,
+        packet: &TftpPacket,
+    ) -> Vec<Event> {
+        let (key, from_client) = match self.get_key(source, destination) {
+            Some(key) => key,
+            None => return Vec::new(),
+        };
+        let now = Instant::now();
+        let transfer = match self.transfers.get_mut(&key) {
+            Some(transfer) => transfer,
+            None => return Vec::new(),
+        };
+        if matches!(transfer.state, State::Completed | State::Failed) {
+            return Vec::new();
+        }
+        transfer.last_seen = now;
+        if transfer.state == State::Stalled {
+            transfer.state = State::Transferring;
+        }
+        let name = get_name(&key, transfer);
+
+        // data is sent by the server in read and by the client in write
+        // transfers
+        let data_from_client = transfer.direction == Direction::Write;
+        let event = match packet {
+            TftpPacket::Data { block, length } if from_client == data_from_client => {
+                transfer.add_data(name, *block, *length, now)
+            }
+            TftpPacket::Ack { block: 0 } if transfer.state == State::Requested => {
+                transfer.state = State::Transferring;
+                None
+            }
+            TftpPacket::OptionAck { options } if !from_client => {
+                transfer.add_option_ack(options);
+                None
+            }
+            TftpPacket::Error { code, message } => {
+                transfer.state = State::Failed;
+                let sender = match from_client {
+                    true => "client",
+                    false => "server",
+                };
+                Some(Event::Failed {
+                    name,
+                    error: format!("{} by {}", packet::format_error(*code, message), sender),
+                })
+            }
+            _ => None,
+        };
+        event.into_iter().collect()
+    }
+
+    // mark transfers without packets for a while as stalled and get the
+    // events
+    pub fn expire(&mut self) -> Vec<Event> {
+        let now = Instant::now();
+        let mut events = Vec::new();
+        for (key, transfer) in &mut self.transfers {
+            if !matches!(transfer.state, State::Requested | State::Transferring)
+                || now.duration_since(transfer.last_seen) < STALL_TIMEOUT
+            {
+                continue;
+            }
+            transfer.state = State::Stalled;
+            events.push(Event::Stalled {
+                name: get_name(key, transfer),
+                bytes: transfer.bytes,
+            });
+        }
+        events
+    }
+
+    // get the transfers sorted by client and server
+    pub fn get_transfers(&self) -> &BTreeMap<TransferKey, Transfer> {
+        &self.transfers
+    }
+}
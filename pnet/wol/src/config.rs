@@ -0,0 +1,88 @@
+use clap::Parser;
+use packet_utils::parse::parse_mac;
+use pnet::datalink::MacAddr;
+
+use std::net::IpAddr;
+
+use crate::magic::Password;
+
+// command line configuration of the wol tool
+#[derive(Parser, Clone, Debug)]
+#[command(about = "Send wake-on-lan magic packets or detect them on the network")]
+pub struct Config {
+    // target of magic packets
+    #[arg(
+        value_name = "MAC",
+        value_parser = parse_mac,
+        help = "Send a magic packet that wakes up the host with the mac \
+        address MAC; without MAC, listen for magic packets on the network"
+    )]
+    pub target: Option<MacAddr>,
+
+    // network interface to send or capture magic packets on
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Send or capture magic packets on the network interface NAME; \
+        by default, the first interface that is up, is not a loopback and has \
+        an ip address is used"
+    )]
+    pub interface: Option<String>,
+
+    // secureon password
+    #[arg(
+        long,
+        value_name = "PASSWORD",
+        value_parser = Password::parse,
+        requires = "target",
+        help = "Append the secureon PASSWORD, written like an ipv4 address \
+        (4 bytes) or a mac address (6 bytes), to the magic packet"
+    )]
+    pub password: Option<Password>,
+
+    // send magic packets in udp datagrams
+    #[arg(
+        short,
+        long,
+        value_name = "ADDRESS",
+        num_args = 0..=1,
+        default_missing_value = "255.255.255.255",
+        requires = "target",
+        help = "Send the magic packet in an udp datagram to ADDRESS, e.g., a \
+        directed broadcast address like 192.0.2.255, instead of an ethernet \
+        frame with ethertype 0x0842; without ADDRESS, the limited broadcast \
+        address is used; the kernel selects the interface"
+    )]
+    pub udp: Option<IpAddr>,
+    #[arg(
+        short,
+        long,
+        value_name = "PORT",
+        default_value_t = 9,
+        requires = "udp",
+        help = "Send udp datagrams to PORT"
+    )]
+    pub port: u16,
+
+    // number of magic packets
+    #[arg(
+        short,
+        long,
+        value_name = "COUNT",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..),
+        requires = "target",
+        help = "Send COUNT magic packets, one per second"
+    )]
+    pub count: u32,
+
+    // capture in promiscuous mode
+    #[arg(
+        long,
+        conflicts_with = "target",
+        help = "Put the interface into promiscuous mode, e.g., to see magic \
+        packets sent to other hosts on a mirrored port"
+    )]
+    pub promiscuous: bool,
+}
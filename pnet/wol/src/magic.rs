@@ -0,0 +1,113 @@
+use pnet::datalink::MacAddr;
+
+use std::fmt;
+
+// length of the synchronization stream of 0xff bytes at the start of magic
+// packets
+const SYNC_LENGTH: usize = 6;
+
+// number of repetitions of the target mac address in magic packets
+const REPETITIONS: usize = 16;
+
+// length of the magic packet without password
+pub const MAGIC_LENGTH: usize = SYNC_LENGTH + REPETITIONS * 6;
+
+// optional secureon password of magic packets, either 4 bytes written like
+// an ipv4 address or 6 bytes written like a mac address
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Password(pub Vec<u8>);
+
+impl Password {
+    // parse a password like "192.0.2.1" or "01:02:03:04:05:06"
+    pub fn parse(s: &str) -> Result<Password, String> {
+        if let Ok(ip) = s.parse::<std::net::Ipv4Addr>() {
+            return Ok(Password(ip.octets().to_vec()));
+        }
+        match s.parse::<MacAddr>() {
+            Ok(mac) => Ok(Password(mac.octets().to_vec())),
+            Err(_) => Err(format!("invalid password: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.0.len() == 4 { "." } else { ":" };
+        let bytes: Vec<String> = match self.0.len() {
+            4 => self.0.iter().map(u8::to_string).collect(),
+            _ => self.0.iter().map(|b| format!("{:02x}", b)).collect(),
+        };
+        write!(f, "{}", bytes.join(separator))
+    }
+}
+
+// wake-on-lan magic packet: 6 bytes 0xff followed by 16 repetitions of the
+// target mac address and an optional password
+#[derive(Clone, Debug)]
+pub struct MagicPacket {
+    pub target: MacAddr,
+    pub password: Option<Password>,
+}
+
+impl MagicPacket {
+    // find a magic packet anywhere in payload, e.g., in an udp datagram
+    pub fn find(payload: &[u8]) -> Option<MagicPacket> {
+        let mut start = 0;
+        while payload.len() - start >= MAGIC_LENGTH {
+            let offset = payload[start..]
+                .windows(SYNC_LENGTH)
+                .position(|w| w.iter().all(|b| *b == 0xff))?;
+            start += offset;
+            if let Some(packet) = MagicPacket::parse(&payload[start..]) {
+                return Some(packet);
+            }
+            start += 1;
+        }
+        None
+    }
+
+    // parse the magic packet at the start of bytes; the password is taken
+    // from the bytes following the repetitions if there are exactly 4 or 6
+    fn parse(bytes: &[u8]) -> Option<MagicPacket> {
+        if bytes.len() < MAGIC_LENGTH || bytes[..SYNC_LENGTH].iter().any(|b| *b != 0xff) {
+            return None;
+        }
+        let target = &bytes[SYNC_LENGTH..SYNC_LENGTH + 6];
+        let repetitions = bytes[SYNC_LENGTH..MAGIC_LENGTH].chunks(6);
+        if repetitions.into_iter().any(|r| r != target) {
+            return None;
+        }
+        let password = match bytes.len() - MAGIC_LENGTH {
+            4 | 6 => Some(Password(bytes[MAGIC_LENGTH..].to_vec())),
+            _ => None,
+        };
+        Some(MagicPacket {
+            target: MacAddr::new(
+                target[0], target[1], target[2], target[3], target[4], target[5],
+            ),
+            password,
+        })
+    }
+
+    // get the raw bytes of the magic packet
+    pub fn build(&self) -> Vec<u8> {
+        let mut bytes = vec![0xff; SYNC_LENGTH];
+        for _ in 0..REPETITIONS {
+            bytes.extend_from_slice(&self.target.octets());
+        }
+        if let Some(password) = &self.password {
+            bytes.extend_from_slice(&password.0);
+        }
+        bytes
+    }
+}
+
+impl fmt::Display for MagicPacket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "magic packet for {}", self.target)?;
+        if let Some(password) = &self.password {
+            write!(f, " with password {}", password)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+extern crate pnet;
+
+mod config;
+mod magic;
+mod sighting;
+
+use clap::Parser;
+
+use packet_utils::channel::create_channel;
+use packet_utils::interface::find_interface;
+use pnet::datalink::{DataLinkReceiver, DataLinkSender, MacAddr};
+use pnet::packet::ethernet::MutableEthernetPacket;
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use config::Config;
+use magic::MagicPacket;
+use sighting::{Sighting, Targets, WOL_ETHERTYPE};
+
+// interval in which the receive loop checks if it should stop
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+// interval between magic packets
+const SEND_INTERVAL: Duration = Duration::from_secs(1);
+
+// length of the ethernet header
+const ETHERNET_HEADER_LENGTH: usize = 14;
+
+// create the broadcast ethernet frame from source with the magic packet
+fn create_frame(source: MacAddr, packet: &MagicPacket) -> Vec<u8> {
+    let payload = packet.build();
+    let mut frame = vec![0; ETHERNET_HEADER_LENGTH + payload.len()];
+    let mut ethernet = MutableEthernetPacket::new(&mut frame).unwrap();
+    ethernet.set_destination(MacAddr::broadcast());
+    ethernet.set_source(source);
+    ethernet.set_ethertype(WOL_ETHERTYPE);
+    ethernet.set_payload(&payload);
+    frame
+}
+
+// create the udp socket for sending magic packets to destination
+fn create_socket(destination: IpAddr) -> io::Result<UdpSocket> {
+    let socket = match destination {
+        IpAddr::V4(_) => UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?,
+        IpAddr::V6(_) => UdpSocket::bind((Ipv6Addr::UNSPECIFIED, 0))?,
+    };
+    socket.set_broadcast(destination.is_ipv4())?;
+    Ok(socket)
+}
+
+// sender of magic packets in ethernet frames or udp datagrams
+enum Sender {
+    Ethernet(Box<dyn DataLinkSender>, Vec<u8>),
+    Udp(UdpSocket, SocketAddr, Vec<u8>),
+}
+
+impl Sender {
+    // create the sender of packet on the configured interface or to the
+    // configured udp destination
+    fn new(packet: &MagicPacket, config: &Config) -> Sender {
+        if let Some(destination) = config.udp {
+            let socket = match create_socket(destination) {
+                Ok(socket) => socket,
+                Err(e) => panic!("An error occurred when creating the udp socket: {}", e),
+            };
+            let destination = SocketAddr::new(destination, config.port);
+            println!("sending {} to {}", packet, destination);
+            return Sender::Udp(socket, destination, packet.build());
+        }
+        let interface = match find_interface(config.interface.as_deref()) {
+            Some(interface) => interface,
+            None => panic!("An error occurred when getting the network interface"),
+        };
+        let source = match interface.mac {
+            Some(mac) => mac,
+            None => panic!("An error occurred when getting the mac address of the interface"),
+        };
+        let tx = match create_channel(&interface, READ_TIMEOUT, false) {
+            Ok((tx, _)) => tx,
+            Err(e) => panic!(
+                "An error occurred when creating the datalink channel: {}",
+                e
+            ),
+        };
+        println!("sending {} on {}", packet, interface.name);
+        Sender::Ethernet(tx, create_frame(source, packet))
+    }
+
+    // send the magic packet once
+    fn send(&mut self) -> io::Result<()> {
+        match self {
+            Sender::Ethernet(tx, frame) => match tx.send_to(frame, None) {
+                Some(result) => result,
+                None => Err(io::Error::other("frame not sent")),
+            },
+            Sender::Udp(socket, destination, payload) => {
+                socket.send_to(payload, *destination).map(|_| ())
+            }
+        }
+    }
+}
+
+// send the magic packet count times, one per interval, until running is
+// unset
+fn send(packet: &MagicPacket, config: &Config, running: &AtomicBool) {
+    let mut sender = Sender::new(packet, config);
+    let mut next = Instant::now();
+    let mut sent = 0;
+    while sent < config.count && running.load(atomic::Ordering::Relaxed) {
+        let now = Instant::now();
+        if now < next {
+            std::thread::sleep(READ_TIMEOUT.min(next - now));
+            continue;
+        }
+        if let Err(e) = sender.send() {
+            panic!("An error occurred when sending the magic packet: {}", e);
+        }
+        sent += 1;
+        next += SEND_INTERVAL;
+    }
+    println!("sent {} magic packets", sent);
+}
+
+// receive frames, print the magic packets in them and add them to the
+// targets until running is unset
+fn listen(rx: &mut dyn DataLinkReceiver, targets: &mut Targets, running: &AtomicBool) {
+    while running.load(atomic::Ordering::Relaxed) {
+        let frame = match rx.next() {
+            Ok(frame) => frame,
+            Err(e) => match e.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => continue,
+                _ => panic!("An error occurred when reading frames: {}", e),
+            },
+        };
+        if let Some(sighting) = Sighting::parse(frame) {
+            println!("{}", sighting);
+            targets.add(&sighting);
+        }
+    }
+}
+
+// print the targets of magic packets
+fn print_targets(targets: &Targets) {
+    println!("--- wake-on-lan targets ---");
+    println!(
+        "{:<17} {:>7} {:>10} SENDERS",
+        "TARGET", "PACKETS", "LAST SEEN"
+    );
+    for (mac, target) in targets.get_targets() {
+        let senders: Vec<String> = target.senders.iter().map(MacAddr::to_string).collect();
+        println!(
+            "{:<17} {:>7} {:>8.1} s {}",
+            mac.to_string(),
+            target.packets,
+            target.last_seen.elapsed().as_secs_f64(),
+            senders.join(","),
+        );
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+
+    // stop on ctrl-c or sigterm
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || {
+            running.store(false, atomic::Ordering::Relaxed);
+        }) {
+            panic!("An error occurred when setting the ctrl-c handler: {}", e);
+        }
+    }
+
+    if let Some(target) = config.target {
+        let packet = MagicPacket {
+            target,
+            password: config.password.clone(),
+        };
+        send(&packet, &config, &running);
+        return;
+    }
+
+    let interface = match find_interface(config.interface.as_deref()) {
+        Some(interface) => interface,
+        None => panic!("An error occurred when getting the network interface"),
+    };
+    let mut rx = match create_channel(&interface, READ_TIMEOUT, config.promiscuous) {
+        Ok((_, rx)) => rx,
+        Err(e) => panic!(
+            "An error occurred when creating the datalink channel: {}",
+            e
+        ),
+    };
+    let mut targets = Targets::default();
+    listen(rx.as_mut(), &mut targets, &running);
+    print_targets(&targets);
+}
@@ -0,0 +1,146 @@
+use pnet::datalink::MacAddr;
+use pnet::packet::ethernet::{EtherType, EtherTypes, EthernetPacket};
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::udp::UdpPacket;
+use pnet::packet::Packet;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Instant;
+
+use crate::magic::MagicPacket;
+
+// ethertype of magic packets sent directly in ethernet frames
+pub const WOL_ETHERTYPE: EtherType = EtherType(0x0842);
+
+// transport of a magic packet seen on the network
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transport {
+    Ethernet,
+    Udp(SocketAddr, SocketAddr),
+}
+
+impl fmt::Display for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Transport::Ethernet => write!(f, "ethertype 0x0842"),
+            Transport::Udp(source, destination) => {
+                write!(f, "udp {} -> {}", source, destination)
+            }
+        }
+    }
+}
+
+// magic packet seen on the network with the addresses of its ethernet
+// frame
+pub struct Sighting {
+    pub ethernet_source: MacAddr,
+    pub ethernet_destination: MacAddr,
+    pub transport: Transport,
+    pub packet: MagicPacket,
+}
+
+// find a magic packet in the udp datagram in the ip payload
+fn find_udp(
+    source: IpAddr,
+    destination: IpAddr,
+    protocol: IpNextHeaderProtocol,
+    payload: &[u8],
+) -> Option<(Transport, MagicPacket)> {
+    if protocol != IpNextHeaderProtocols::Udp {
+        return None;
+    }
+    let udp = UdpPacket::new(payload)?;
+    let packet = MagicPacket::find(udp.payload())?;
+    let transport = Transport::Udp(
+        SocketAddr::new(source, udp.get_source()),
+        SocketAddr::new(destination, udp.get_destination()),
+    );
+    Some((transport, packet))
+}
+
+impl Sighting {
+    // parse the ethernet frame and get the magic packet inside it, either
+    // directly in the frame or in an udp datagram over ipv4 or ipv6
+    pub fn parse(frame: &[u8]) -> Option<Sighting> {
+        let ethernet = EthernetPacket::new(frame)?;
+        let (transport, packet) = match ethernet.get_ethertype() {
+            WOL_ETHERTYPE => (Transport::Ethernet, MagicPacket::find(ethernet.payload())?),
+            EtherTypes::Ipv4 => {
+                let ip = Ipv4Packet::new(ethernet.payload())?;
+                find_udp(
+                    ip.get_source().into(),
+                    ip.get_destination().into(),
+                    ip.get_next_level_protocol(),
+                    ip.payload(),
+                )?
+            }
+            EtherTypes::Ipv6 => {
+                let ip = Ipv6Packet::new(ethernet.payload())?;
+                find_udp(
+                    ip.get_source().into(),
+                    ip.get_destination().into(),
+                    ip.get_next_header(),
+                    ip.payload(),
+                )?
+            }
+            _ => return None,
+        };
+        Some(Sighting {
+            ethernet_source: ethernet.get_source(),
+            ethernet_destination: ethernet.get_destination(),
+            transport,
+            packet,
+        })
+    }
+}
+
+impl fmt::Display for Sighting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} from {} to {} ({})",
+            self.packet, self.ethernet_source, self.ethernet_destination, self.transport
+        )
+    }
+}
+
+// magic packets seen for a target
+pub struct Target {
+    pub packets: u64,
+    pub senders: Vec<MacAddr>,
+    pub last_seen: Instant,
+}
+
+// targets of magic packets seen on the network, keyed by mac address
+#[derive(Default)]
+pub struct Targets {
+    targets: BTreeMap<MacAddr, Target>,
+}
+
+impl Targets {
+    // add the sighting of a magic packet
+    pub fn add(&mut self, sighting: &Sighting) {
+        let target = self
+            .targets
+            .entry(sighting.packet.target)
+            .or_insert_with(|| Target {
+                packets: 0,
+                senders: Vec::new(),
+                last_seen: Instant::now(),
+            });
+        target.packets += 1;
+        target.last_seen = Instant::now();
+        if !target.senders.contains(&sighting.ethernet_source) {
+            target.senders.push(sighting.ethernet_source);
+        }
+    }
+
+    // get the targets ordered by mac address
+    pub fn get_targets(&self) -> impl Iterator<Item = (&MacAddr, &Target)> {
+        self.targets.iter()
+    }
+}